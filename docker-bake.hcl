@@ -186,6 +186,7 @@ group "rust-services" {
     "graph-schema-manager",
     "grapl-web-ui",
     "kafka-retry",
+    "log-collector",
     "node-identifier",
     "organization-management",
     "pipeline-ingress",
@@ -398,6 +399,14 @@ target "kafka-retry" {
   ]
 }
 
+target "log-collector" {
+  inherits = ["_rust-base"]
+  target   = "log-collector-deploy"
+  tags = [
+    upstream_aware_tag("log-collector")
+  ]
+}
+
 target "node-identifier" {
   inherits = ["_rust-base"]
   target   = "node-identifier-deploy"
@@ -42,5 +42,7 @@ class GraplStack:
 
         self.user_auth_table = require_str("user-auth-table")
         self.user_session_table = require_str("user-session-table")
+        self.session_table_name = require_str("dynamic-session-table")
+        self.static_mapping_table_name = require_str("static-mapping-table")
 
         self.graph_db = cast(NomadServiceScyllaDbArgs, ref.require_output("graph-db"))
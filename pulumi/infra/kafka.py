@@ -120,10 +120,12 @@ class Kafka(pulumi.ComponentResource):
             topics = [
                 "raw-logs",
                 "raw-logs-retry",
+                "raw-logs-dead-letter",
                 "generated-graphs",
                 "identified-graphs",
                 "merged-graphs",
                 "merged-graphs-retry",
+                "merged-graphs-dead-letter",
                 "analyzer-executions",
                 "engagements",
             ]
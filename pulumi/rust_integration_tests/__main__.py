@@ -86,6 +86,8 @@ def main() -> None:
         "plugin_work_queue_db": grapl_stack.plugin_work_queue_db,
         "user_auth_table": grapl_stack.user_auth_table,
         "user_session_table": grapl_stack.user_session_table,
+        "session_table_name": grapl_stack.session_table_name,
+        "static_mapping_table_name": grapl_stack.static_mapping_table_name,
     }
 
     NomadJob(
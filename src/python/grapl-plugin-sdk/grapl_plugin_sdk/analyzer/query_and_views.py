@@ -25,6 +25,7 @@ from python_proto.api.graph_query.v1beta1.messages import (
     OrStringFilters,
     QueryId,
     StringFilter,
+    TimeWindow,
     UidFilter,
 )
 from python_proto.api.graph_query_proxy.v1beta1.client import GraphQueryProxyClient
@@ -79,6 +80,14 @@ class NodeQuery:
         self.node_property_query.with_uid_filters(filters)
         return self
 
+    def with_time_window(self, time_window: TimeWindow) -> NodeQuery:
+        """
+        Only match nodes whose session overlaps `time_window`, e.g. processes
+        alive at some point in the last hour.
+        """
+        self.node_property_query.with_time_window(time_window)
+        return self
+
     def with_edge_filter(
         self,
         edge_name: EdgeName,
@@ -127,6 +136,7 @@ class NodeQuery:
             node_type=self.node_property_query.node_type,
             query_id=self.node_property_query.query_id,
             string_filters=self.node_property_query.string_filters,
+            time_window=self.node_property_query.time_window,
         )
 
 
@@ -335,6 +335,34 @@ class EdgeQueryMap(SerDe[proto.EdgeQueryMap]):
         return self[key]
 
 
+@dataclass(frozen=True, slots=True)
+class TimeWindow(SerDe[proto.TimeWindow]):
+    """
+    A window of time, in milliseconds since the epoch, that a
+    session-identified node must overlap in order to match. `None` leaves
+    that side of the window unbounded. The node's session timestamps are
+    read from the properties its type's deployed schema declares with
+    `@create_time`, `@last_seen_time` and `@terminate_time`.
+    """
+
+    start_time: int | None = None
+    end_time: int | None = None
+    _proto_cls = proto.TimeWindow
+
+    @classmethod
+    def from_proto(cls, proto_value: proto.TimeWindow) -> TimeWindow:
+        return cls(
+            start_time=proto_value.start_time or None,
+            end_time=proto_value.end_time or None,
+        )
+
+    def into_proto(self) -> proto.TimeWindow:
+        msg = self.new_proto()
+        msg.start_time = self.start_time or 0
+        msg.end_time = self.end_time or 0
+        return msg
+
+
 @dataclass(frozen=True, slots=True)
 class NodePropertyQuery(SerDe[proto.NodePropertyQuery]):
     node_type: NodeType
@@ -346,6 +374,7 @@ class NodePropertyQuery(SerDe[proto.NodePropertyQuery]):
         default_factory=lambda: defaultdict(OrIntFilters)
     )
     uid_filters: UidFilters = field(default_factory=UidFilters)
+    time_window: TimeWindow | None = None
     _proto_cls = proto.NodePropertyQuery
 
     @classmethod
@@ -368,6 +397,9 @@ class NodePropertyQuery(SerDe[proto.NodePropertyQuery]):
                 },
             ),
             uid_filters=UidFilters.from_proto(proto.uid_filters),
+            time_window=TimeWindow.from_proto(proto.time_window)
+            if proto.HasField("time_window")
+            else None,
         )
 
     def into_proto(self) -> proto.NodePropertyQuery:
@@ -379,6 +411,8 @@ class NodePropertyQuery(SerDe[proto.NodePropertyQuery]):
         for p, int_filter in self.int_filters.items():
             msg.int_filters[p.value].CopyFrom(int_filter.into_proto())
         msg.uid_filters.CopyFrom(self.uid_filters.into_proto())
+        if self.time_window is not None:
+            msg.time_window.CopyFrom(self.time_window.into_proto())
         return msg
 
     def with_string_filters(
@@ -391,6 +425,11 @@ class NodePropertyQuery(SerDe[proto.NodePropertyQuery]):
         self.uid_filters.extend(filters)
         return self
 
+    def with_time_window(self, time_window: TimeWindow) -> NodePropertyQuery:
+        # The query is built up in place, like its filters
+        object.__setattr__(self, "time_window", time_window)
+        return self
+
 
 @dataclass(frozen=True, slots=True)
 class EdgeNameMap(SerDe[proto.EdgeNameMap]):
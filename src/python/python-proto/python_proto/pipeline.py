@@ -7,6 +7,7 @@ from typing import Generic
 
 from google.protobuf.any_pb2 import Any as _Any
 from graplinc.grapl.pipeline.v1beta1.types_pb2 import Envelope as _Envelope
+from graplinc.grapl.pipeline.v1beta1.types_pb2 import PayloadEncoding as _PayloadEncoding
 from graplinc.grapl.pipeline.v1beta1.types_pb2 import RawLog as _RawLog
 from python_proto.common import Timestamp, Uuid
 from python_proto.serde import I, SerDe, SerDeWithInner
@@ -28,6 +29,15 @@ class Envelope(SerDeWithInner[I, _Envelope], Generic[I]):
     def from_proto(
         cls: type[Envelope[I]], proto_envelope: _Envelope, inner_cls: type[I]
     ) -> Envelope[I]:
+        # Compressed payloads are only understood by the Rust consumers
+        if proto_envelope.inner_message_encoding not in (
+            _PayloadEncoding.PAYLOAD_ENCODING_UNSPECIFIED,
+            _PayloadEncoding.PAYLOAD_ENCODING_IDENTITY,
+        ):
+            raise NotImplementedError(
+                "unsupported payload encoding "
+                f"{_PayloadEncoding.Name(proto_envelope.inner_message_encoding)}"
+            )
         inner_message_proto = inner_cls.new_proto()
         proto_envelope.inner_message.Unpack(inner_message_proto)
         inner_message = inner_cls.from_proto(inner_message_proto)
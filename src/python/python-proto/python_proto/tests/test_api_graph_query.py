@@ -140,6 +140,21 @@ def edge_query_maps(
     return st.builds(graph_query_msgs.EdgeQueryMap, entries=entries)
 
 
+# 0 is encoded as an unbounded window
+window_bounds = st.none() | st.integers(min_value=1, max_value=strategies.UINT64_MAX)
+
+
+def time_windows(
+    start_time: st.SearchStrategy[int | None] = window_bounds,
+    end_time: st.SearchStrategy[int | None] = window_bounds,
+) -> st.SearchStrategy[graph_query_msgs.TimeWindow]:
+    return st.builds(
+        graph_query_msgs.TimeWindow,
+        start_time=start_time,
+        end_time=end_time,
+    )
+
+
 def node_property_queries(
     node_type: st.SearchStrategy[grapl_common_msgs.NodeType] = node_types(),
     query_id: st.SearchStrategy[graph_query_msgs.QueryId] = query_ids(),
@@ -158,6 +173,8 @@ def node_property_queries(
         max_size=hypothesis_collections_max_size,
     ),
     uid_filters: st.SearchStrategy[graph_query_msgs.UidFilters] = uid_filters_plural(),
+    time_window: st.SearchStrategy[graph_query_msgs.TimeWindow | None] = st.none()
+    | time_windows(),
 ) -> st.SearchStrategy[graph_query_msgs.NodePropertyQuery]:
     return st.builds(
         graph_query_msgs.NodePropertyQuery,
@@ -166,6 +183,7 @@ def node_property_queries(
         string_filters=string_filters,
         int_filters=int_filters,
         uid_filters=uid_filters,
+        time_window=time_window,
     )
 
 
@@ -368,3 +386,7 @@ def test_query_graph_from_uid_requests() -> None:
 
 def test_query_graph_from_uid_responses() -> None:
     check_encode_decode_invariant(query_graph_from_uid_responses())
+
+
+def test_time_windows() -> None:
+    check_encode_decode_invariant(time_windows())
@@ -252,6 +252,7 @@ impl AnalyzerDispatcher {
                                         retry_message(
                                             &graph_update_retry_producer,
                                             envelope,
+                                            "analyzer IDs cache miss",
                                         )
                                             .instrument(span)
                                             .await?;
@@ -271,6 +272,7 @@ impl AnalyzerDispatcher {
                                         retry_message(
                                             &graph_update_retry_producer,
                                             envelope,
+                                            &format!("analyzer IDs cache error {reason}"),
                                         )
                                             .instrument(span)
                                             .await?;
@@ -317,9 +319,10 @@ impl AnalyzerDispatcher {
 async fn retry_message(
     graph_update_retry_producer: &RetryProducer<Update>,
     envelope: Envelope<Update>,
+    reason: &str,
 ) -> Result<(), ProducerError> {
     // TODO: be a little smarter about handling ProducerError here
-    graph_update_retry_producer.send(envelope).await
+    graph_update_retry_producer.send(envelope, reason).await
 }
 
 #[tracing::instrument(skip(plugin_work_queue_client, analyzer_ids, envelope), err)]
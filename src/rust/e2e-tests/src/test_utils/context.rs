@@ -161,6 +161,7 @@ impl E2eTestContext {
                 schema: get_example_graphql_schema().unwrap(),
                 schema_type: SchemaType::GraphqlV0,
                 schema_version: 0,
+                dry_run: false,
             })
             .await?;
         Ok(())
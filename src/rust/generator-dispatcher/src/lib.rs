@@ -212,7 +212,8 @@ impl GeneratorDispatcher {
 
                                         retry_message(
                                             &raw_logs_retry_producer,
-                                            envelope
+                                            envelope,
+                                            "generator IDs cache miss",
                                         ).instrument(span).await?;
 
                                         Ok(())
@@ -229,7 +230,8 @@ impl GeneratorDispatcher {
 
                                         retry_message(
                                             &raw_logs_retry_producer,
-                                            envelope
+                                            envelope,
+                                            &format!("generator IDs cache error {reason}"),
                                         )
                                             .instrument(span)
                                             .await?;
@@ -276,9 +278,10 @@ impl GeneratorDispatcher {
 async fn retry_message(
     raw_logs_retry_producer: &RetryProducer<RawLog>,
     envelope: Envelope<RawLog>,
+    reason: &str,
 ) -> Result<(), ProducerError> {
     // TODO: be a little smarter about handling ProducerError here
-    raw_logs_retry_producer.send(envelope).await
+    raw_logs_retry_producer.send(envelope, reason).await
 }
 
 #[tracing::instrument(skip(plugin_work_queue_client, generator_ids, envelope), err)]
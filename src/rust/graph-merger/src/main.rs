@@ -12,6 +12,7 @@ use kafka::{
     config::{
         ConsumerConfig,
         ProducerConfig,
+        TransactionConfig,
     },
     StreamProcessor,
 };
@@ -65,7 +66,20 @@ async fn handler(
     // TODO: also construct a stream processor for retries
 
     let stream_processor: StreamProcessor<IdentifiedGraph, Update> =
-        StreamProcessor::new(consumer_config, producer_config)?;
+        match TransactionConfig::from_env_vars() {
+            Some(transaction_config) => {
+                tracing::info!(
+                    message = "using kafka transactions",
+                    transactional_id = %transaction_config.transactional_id,
+                );
+                StreamProcessor::new_transactional(
+                    consumer_config,
+                    producer_config,
+                    transaction_config,
+                )?
+            }
+            None => StreamProcessor::new(consumer_config, producer_config)?,
+        };
 
     tracing::info!(message = "kafka stream processor configured successfully",);
 
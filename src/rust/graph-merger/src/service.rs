@@ -9,16 +9,14 @@ use rust_proto::graplinc::grapl::{
     api::{
         client::ClientError,
         graph::v1beta1::{
-            IdentifiedEdge,
             IdentifiedGraph,
             Property,
         },
         graph_mutation::v1beta1::{
             client::GraphMutationClient,
             messages::{
-                CreateEdgeRequest,
+                ApplyGraphRequest,
                 MutationRedundancy,
-                SetNodePropertyRequest,
             },
         },
         plugin_sdk::analyzers::v1beta1::messages::{
@@ -30,8 +28,6 @@ use rust_proto::graplinc::grapl::{
         },
     },
     common::v1beta1::types::{
-        EdgeName,
-        NodeType,
         PropertyName,
         Uid,
     },
@@ -110,103 +106,73 @@ impl GraphMerger {
                     edges =? subgraph.edges.len(),
                 );
 
-                let mut updates = Vec::with_capacity(subgraph.nodes.len() + subgraph.edges.len());
-
-                let node_types: HashMap<Uid, String> = subgraph
+                // Property types, to turn the results into updates
+                let properties: HashMap<(Uid, String), Property> = subgraph
                     .nodes
                     .iter()
-                    .map(|(uid, n)| (*uid, n.node_type.clone()))
+                    .flat_map(|(uid, node)| {
+                        node.properties.iter().map(|(property_name, property)| {
+                            ((*uid, property_name.clone()), property.property.clone())
+                        })
+                    })
                     .collect();
-                let nodes = subgraph.nodes;
-                let edges = subgraph.edges;
-
-                for node in nodes.into_values() {
-                    for (prop_name, prop_value) in node.properties {
-                        let update =
-                            property_to_update(node.uid, prop_name.clone(), &prop_value.property);
-
-                        let response = self
-                            .graph_mutation_client
-                            .set_node_property(SetNodePropertyRequest {
-                                tenant_id,
-                                node_type: NodeType {
-                                    value: node.node_type.clone(),
-                                },
-                                uid: node.uid,
-                                property_name: PropertyName { value: prop_name },
-                                property: prop_value,
-                            })
-                            .await;
-
-                        match response {
-                            Ok(set_node_property_response) => {
-                                if let MutationRedundancy::True =
-                                    set_node_property_response.mutation_redundancy
-                                {
+
+                let response = match self
+                    .graph_mutation_client
+                    .apply_graph(ApplyGraphRequest {
+                        tenant_id,
+                        identified_graph: subgraph,
+                    })
+                    .await
+                {
+                    Ok(response) => response,
+                    Err(e) => return vec![Err(e.into())],
+                };
+
+                let mut updates =
+                    Vec::with_capacity(response.node_results.len() + response.edge_results.len());
+
+                for node_result in response.node_results {
+                    for property_result in node_result.property_results {
+                        if let MutationRedundancy::True = property_result.mutation_redundancy {
+                            continue;
+                        }
+                        let property_name = property_result.property_name.value;
+                        let property =
+                            match properties.get(&(node_result.uid, property_name.clone())) {
+                                Some(property) => property,
+                                None => {
+                                    updates.push(Err(GraphMergerError::Unexpected(format!(
+                                        "result for unknown property {property_name} of {:?}",
+                                        node_result.uid
+                                    ))));
                                     continue;
-                                } else {
-                                    updates.push(Ok(Envelope::new(
-                                        tenant_id,
-                                        trace_id,
-                                        event_source_id,
-                                        update,
-                                    )));
                                 }
-                            }
-                            Err(e) => updates.push(Err(e.into())),
-                        }
+                            };
+                        updates.push(Ok(Envelope::new(
+                            tenant_id,
+                            trace_id,
+                            event_source_id,
+                            property_to_update(node_result.uid, property_name, property),
+                        )));
                     }
                 }
 
-                for edge_list in edges.into_values() {
-                    for edge in edge_list.edges {
-                        let IdentifiedEdge {
-                            to_uid,
-                            from_uid,
-                            edge_name,
-                        } = edge;
-                        let response = self
-                            .graph_mutation_client
-                            .create_edge(CreateEdgeRequest {
-                                tenant_id,
-                                edge_name: EdgeName {
-                                    value: edge_name.clone(),
-                                },
-                                from_uid,
-                                to_uid,
-                                source_node_type: NodeType {
-                                    value: node_types[&from_uid].clone(),
-                                },
-                            })
-                            .await;
-
-                        match response {
-                            Ok(create_edge_response) => {
-                                if let MutationRedundancy::True =
-                                    create_edge_response.mutation_redundancy
-                                {
-                                    continue;
-                                } else {
-                                    updates.push(Ok(Envelope::new(
-                                        tenant_id,
-                                        trace_id,
-                                        event_source_id,
-                                        Update::Edge(EdgeUpdate {
-                                            src_uid: from_uid,
-                                            dst_uid: to_uid,
-                                            forward_edge_name: EdgeName {
-                                                value: edge_name.clone(),
-                                            },
-                                            reverse_edge_name: EdgeName {
-                                                value: edge_name.clone(),
-                                            },
-                                        }),
-                                    )));
-                                }
-                            }
-                            Err(e) => updates.push(Err(e.into())),
-                        }
+                for edge_result in response.edge_results {
+                    if let MutationRedundancy::True = edge_result.mutation_redundancy {
+                        continue;
                     }
+                    updates.push(Ok(Envelope::new(
+                        tenant_id,
+                        trace_id,
+                        event_source_id,
+                        Update::Edge(EdgeUpdate {
+                            src_uid: edge_result.from_uid,
+                            dst_uid: edge_result.to_uid,
+                            forward_edge_name: edge_result.edge_name,
+                            reverse_edge_name: edge_result.reverse_edge_name,
+                        }),
+                    )));
                 }
 
                 updates
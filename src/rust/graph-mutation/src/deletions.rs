@@ -0,0 +1,26 @@
+use scylla::{
+    query::Query,
+    transport::errors::QueryError,
+    CachingSession,
+};
+
+use crate::table_names::GRAPH_DELETIONS_TABLE_NAME;
+
+/// Replaces a tenant's deletion generation once something has been deleted
+/// from its graph. graph-query keys what it caches by the generation, so it
+/// stops serving the deleted properties and edges once it next reads it.
+#[tracing::instrument(skip(scylla_client), err)]
+pub async fn record_deletion(
+    scylla_client: &CachingSession,
+    tenant_id: uuid::Uuid,
+) -> Result<(), QueryError> {
+    // Retrying this only bumps the generation again, which is harmless
+    let mut query = Query::new(format!(
+        "UPDATE tenant_graph_ks.{GRAPH_DELETIONS_TABLE_NAME} \
+        SET generation = now() \
+        WHERE tenant_id = ?"
+    ));
+    query.set_is_idempotent(true);
+    scylla_client.execute(query, &(tenant_id,)).await?;
+    Ok(())
+}
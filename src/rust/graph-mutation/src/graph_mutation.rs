@@ -1,17 +1,42 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+};
 
+use futures::{
+    StreamExt,
+    TryStreamExt,
+};
 use grapl_utils::future_ext::GraplFutureExt;
+use moka::PredicateError;
 use rust_proto::graplinc::grapl::{
     api::{
         client::ClientError,
-        graph::v1beta1::Property,
+        graph::v1beta1::{
+            IdentifiedGraph,
+            Property,
+        },
         graph_mutation::v1beta1::{
             messages::{
+                ApplyGraphRequest,
+                ApplyGraphResponse,
                 CreateEdgeRequest,
                 CreateEdgeResponse,
                 CreateNodeRequest,
                 CreateNodeResponse,
+                DeleteEdgeRequest,
+                DeleteEdgeResponse,
+                DeleteNodeRequest,
+                DeleteNodeResponse,
+                EdgeResult,
+                GetPurgeStatusRequest,
+                GetPurgeStatusResponse,
                 MutationRedundancy,
+                NodeResult,
+                PropertyResult,
+                PurgeTenantRequest,
+                PurgeTenantResponse,
                 SetNodePropertyRequest,
                 SetNodePropertyResponse,
             },
@@ -27,29 +52,88 @@ use rust_proto::graplinc::grapl::{
     },
 };
 use scylla::{
+    batch::Batch,
+    cql_to_rust::{
+        FromCqlVal,
+        FromRowError,
+    },
+    frame::{
+        response::result::CqlValue,
+        value::{
+            SerializeValuesError,
+            SerializedValues,
+            Value,
+            ValueList,
+        },
+    },
     query::Query,
     CachingSession,
+    QueryResult,
 };
 use tracing::Instrument;
 use uid_allocator::client::CachingUidAllocatorClient as UidAllocatorClient;
 
 use crate::{
+    deletions::record_deletion,
+    purge::{
+        PurgeError,
+        TenantPurger,
+    },
+    retention_policy_resolver::{
+        RetentionPolicyResolver,
+        RetentionPolicyResolverError,
+        Ttl,
+    },
     reverse_edge_resolver::{
         ReverseEdgeResolver,
         ReverseEdgeResolverError,
     },
     table_names::{
+        int_index_bucket,
+        EDGE_I_64_TABLE_NAME,
+        EDGE_PROPERTY_TABLE_NAMES,
+        EDGE_STRING_TABLE_NAME,
+        EDGE_U_64_TABLE_NAME,
+        IMM_I_64_BUCKETED_INDEX_TABLE_NAME,
         IMM_I_64_TABLE_NAME,
+        IMM_STRING_INDEX_TABLE_NAME,
         IMM_STRING_TABLE_NAME,
+        IMM_U_64_BUCKETED_INDEX_TABLE_NAME,
         IMM_U_64_TABLE_NAME,
         MAX_I_64_TABLE_NAME,
         MAX_U_64_TABLE_NAME,
         MIN_I_64_TABLE_NAME,
         MIN_U_64_TABLE_NAME,
     },
-    write_dropper::WriteDropper,
+    write_dropper::{
+        PendingWrite,
+        WriteDropper,
+    },
 };
 
+/// The most statements put in a single batch
+const MAX_BATCH_STATEMENTS: usize = 16;
+
+/// The number of writes `apply_graph` prepares at once
+const APPLY_GRAPH_CONCURRENCY: usize = 16;
+
+const EDGE_INSERT_STATEMENT: &str = "INSERT INTO tenant_graph_ks.edges (\
+        tenant_id, \
+        source_uid, \
+        destination_uid, \
+        f_edge_name, \
+        r_edge_name\
+    ) \
+    VALUES (?, ?, ?, ?, ?)";
+
+const NODE_TYPE_INSERT_STATEMENT: &str = "INSERT INTO tenant_graph_ks.node_type \
+        (tenant_id, uid, node_type) \
+        VALUES (?, ?, ?) \
+        USING TTL ?";
+
+/// A statement along with the values to bind to it
+type Statement = (String, SerializedValues);
+
 #[derive(thiserror::Error, Debug)]
 pub enum GraphMutationManagerError {
     #[error("gRPC client error {0}")]
@@ -61,8 +145,36 @@ pub enum GraphMutationManagerError {
     #[error("Scylla Error: {0}")]
     ScyllaError(#[from] scylla::transport::errors::QueryError),
 
+    #[error("Failed to serialize Scylla values: {0}")]
+    SerializeValuesError(#[from] SerializeValuesError),
+
     #[error("ReverseEdgeResolverError: {0}")]
     ReverseEdgeResolverError(#[from] ReverseEdgeResolverError),
+
+    #[error("RetentionPolicyResolverError: {0}")]
+    RetentionPolicyResolverError(#[from] RetentionPolicyResolverError),
+
+    #[error("Edge from {0:?} has no source node in the graph")]
+    MissingSourceNode(Uid),
+
+    #[error("Row was invalid {0}")]
+    FromRowError(#[from] FromRowError),
+
+    #[error("Failed to invalidate caches: {0}")]
+    CacheInvalidationError(#[from] PredicateError),
+
+    #[error("Tenant {0} is being purged")]
+    TenantBeingPurged(uuid::Uuid),
+
+    #[error("PurgeError: {0}")]
+    PurgeError(#[from] PurgeError),
+
+    #[error("Malformed lightweight transaction result")]
+    MalformedLwtResult,
+
+    #[error("Value {value} of {property_name} is too large to store")]
+    UintOutOfRange { property_name: String, value: u64 },
+
     #[error("Scylla Insert Timeout: {tenant_id:?} {insert_type:?}")]
     ScyllaInsertTimeout {
         tenant_id: uuid::Uuid,
@@ -81,6 +193,13 @@ impl From<GraphMutationManagerError> for Status {
                 Status::internal(format!("UidAllocatorClient error: {e:?}"))
             }
             GraphMutationManagerError::ZeroUid => Status::failed_precondition("Allocated Zero Uid"),
+            GraphMutationManagerError::MissingSourceNode(_)
+            | GraphMutationManagerError::UintOutOfRange { .. } => {
+                Status::invalid_argument(e.to_string())
+            }
+            GraphMutationManagerError::TenantBeingPurged(_) => {
+                Status::failed_precondition(e.to_string())
+            }
             e => Status::internal(e.to_string()),
         }
     }
@@ -90,7 +209,9 @@ pub struct GraphMutationManager {
     scylla_client: Arc<CachingSession>,
     uid_allocator_client: UidAllocatorClient,
     reverse_edge_resolver: ReverseEdgeResolver,
+    retention_policy_resolver: RetentionPolicyResolver,
     write_dropper: WriteDropper,
+    tenant_purger: TenantPurger,
 }
 
 impl GraphMutationManager {
@@ -98,412 +219,1133 @@ impl GraphMutationManager {
         scylla_client: Arc<CachingSession>,
         uid_allocator_client: UidAllocatorClient,
         reverse_edge_resolver: ReverseEdgeResolver,
+        retention_policy_resolver: RetentionPolicyResolver,
         max_write_drop_size: u64,
     ) -> Self {
+        let write_dropper = WriteDropper::new(max_write_drop_size);
+        let tenant_purger = TenantPurger::new(
+            scylla_client.clone(),
+            write_dropper.clone(),
+            reverse_edge_resolver.clone(),
+        );
         Self {
             scylla_client,
             uid_allocator_client,
             reverse_edge_resolver,
-            write_dropper: WriteDropper::new(max_write_drop_size),
+            retention_policy_resolver,
+            write_dropper,
+            tenant_purger,
+        }
+    }
+
+    async fn ensure_not_purging(
+        &self,
+        tenant_id: uuid::Uuid,
+    ) -> Result<(), GraphMutationManagerError> {
+        if self.tenant_purger.is_purging(tenant_id).await? {
+            return Err(GraphMutationManagerError::TenantBeingPurged(tenant_id));
         }
+        Ok(())
     }
 
     #[tracing::instrument(skip(self), err)]
-    async fn upsert_max_u64(
+    async fn set_node_type(
         &self,
         tenant_id: uuid::Uuid,
         uid: Uid,
         node_type: NodeType,
-        property_name: PropertyName,
-        property_value: u64,
     ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_max_u64(
-                tenant_id,
-                node_type.clone(),
-                property_name.clone(),
-                property_value,
-                || async move {
-                    let property_value = property_value as i64;
-                    let mut query = Query::new(format!(
-                        "INSERT INTO tenant_graph_ks.{MAX_U_64_TABLE_NAME} \
-                        (tenant_id, uid, populated_field, value) \
-                        VALUES (?, ?, ?, ?)"
-                    ));
-                    query.set_timestamp(Some(property_value));
-
-                    self.scylla_client
-                        .execute(
-                            query,
-                            &(tenant_id, uid.as_i64(), property_name.value, property_value),
-                        )
-                        .await?;
-                    Ok(())
-                },
-            )
+        let ttl = self
+            .retention_policy_resolver
+            .resolve_ttl(tenant_id, node_type.clone())
+            .await?;
+        let write = async move {
+            self.scylla_client
+                .execute(
+                    Query::new(NODE_TYPE_INSERT_STATEMENT),
+                    &(tenant_id, uid.as_i64(), node_type.value, ttl.as_seconds()),
+                )
+                .timeout(std::time::Duration::from_secs(3))
+                .await
+                .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
+                    tenant_id,
+                    insert_type: "NODE_TYPE",
+                })??;
+            Ok::<_, GraphMutationManagerError>(())
+        }
+        .instrument(tracing::info_span!("set_node_type"));
+
+        // Writes that expire can't be dropped, since the WriteDropper would
+        // keep dropping them once the data they duplicate had expired
+        if ttl.expires() {
+            write.await
+        } else if let Some(pending_write) = self.write_dropper.pending_node_type(tenant_id, uid) {
+            write.await?;
+            self.write_dropper.commit(pending_write).await;
+            Ok(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads the stored `value` of a property, if it's set
+    async fn get_property_value<V>(
+        &self,
+        tenant_id: uuid::Uuid,
+        owner: PropertyOwner<'_>,
+        property_name: &PropertyName,
+        table_name: &'static str,
+    ) -> Result<Option<V>, GraphMutationManagerError>
+    where
+        V: FromCqlVal<CqlValue>,
+    {
+        let key_condition = owner.key_condition();
+        let mut query = Query::new(format!(
+            "SELECT value FROM tenant_graph_ks.{table_name} \
+            WHERE tenant_id = ? AND {key_condition} AND populated_field = ?"
+        ));
+        query.set_is_idempotent(true);
+        let mut values = SerializedValues::new();
+        owner.add_key_values(tenant_id, &mut values)?;
+        values.add_value(&property_name.value)?;
+
+        let query_result = self.scylla_client.execute(query, values).await?;
+        match query_result.rows_typed_or_empty::<(V,)>().next() {
+            Some(row) => Ok(Some(row?.0)),
+            None => Ok(None),
+        }
+    }
+
+    /// Executes a lightweight transaction, returning whether it was applied
+    /// and, if it wasn't, the current `value` of the row it was conditioned on
+    async fn execute_lwt(
+        &self,
+        tenant_id: uuid::Uuid,
+        insert_type: &'static str,
+        (statement, values): Statement,
+    ) -> Result<(bool, Option<CqlValue>), GraphMutationManagerError> {
+        let result = self
+            .scylla_client
+            .execute(Query::new(statement), values)
+            .timeout(std::time::Duration::from_secs(3))
             .await
-            .map(|_| ())
+            .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
+                tenant_id,
+                insert_type,
+            })??;
+        lwt_result(result)
     }
 
+    /// Writes a max or min property with a compare-and-set, returning whether
+    /// it was redundant. Unlike the other writes it isn't batched: a read
+    /// followed by a plain write would let a concurrent write of a less
+    /// extreme value replace it, and max and min properties have no index
+    /// entries that must be written alongside them. A redundant write that
+    /// expires refreshes the stored value's TTL, unless a concurrent write
+    /// already replaced it.
     #[tracing::instrument(skip(self), err)]
-    async fn upsert_min_u64(
+    async fn upsert_extremum(
         &self,
         tenant_id: uuid::Uuid,
-        uid: Uid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        property_value: u64,
-    ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_min_u64(
+        owner: PropertyOwner<'_>,
+        property_name: &PropertyName,
+        table_name: &'static str,
+        extremum: Extremum,
+        value: i64,
+        ttl: Ttl,
+    ) -> Result<MutationRedundancy, GraphMutationManagerError> {
+        let (applied, current_value) = self
+            .execute_lwt(
                 tenant_id,
-                node_type.clone(),
-                property_name.clone(),
-                property_value,
-                || {
-                    async move {
-                        let property_value = property_value as i64;
-                        let mut query = Query::new(format!(
-                            "INSERT INTO tenant_graph_ks.{MIN_U_64_TABLE_NAME} \
-                            (tenant_id, uid, populated_field, value) \
-                            VALUES (?, ?, ?, ?)"
-                        ));
-
-                        query.set_timestamp(Some(-property_value));
-
-                        self.scylla_client
-                            .execute(
-                                query,
-                                &(tenant_id, uid.as_i64(), property_name.value, property_value),
-                            )
-                            .timeout(std::time::Duration::from_secs(3))
-                            .await
-                            .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                                tenant_id,
-                                insert_type: "MIN_U_64",
-                            })??;
-                        Ok(())
-                    }
-                    .instrument(tracing::info_span!("upsert_min_u64"))
-                },
+                table_name,
+                owner.insert_if_not_exists(tenant_id, table_name, property_name, &value, ttl)?,
             )
-            .await
-            .map(|_| ())
+            .await?;
+        if applied {
+            return Ok(MutationRedundancy::False);
+        }
+
+        let current_value = current_value
+            .and_then(|current_value| current_value.as_bigint())
+            .ok_or(GraphMutationManagerError::MalformedLwtResult)?;
+        if extremum.is_improved_by(current_value, value) {
+            let (applied, _) = self
+                .execute_lwt(
+                    tenant_id,
+                    table_name,
+                    owner.update_if(
+                        tenant_id,
+                        table_name,
+                        property_name,
+                        value,
+                        ttl,
+                        extremum.condition(),
+                    )?,
+                )
+                .await?;
+            // If the condition failed, a concurrent write stored a value at
+            // least as extreme as this one
+            if applied {
+                return Ok(MutationRedundancy::False);
+            }
+        } else if ttl.expires() {
+            self.execute_lwt(
+                tenant_id,
+                table_name,
+                owner.update_if(
+                    tenant_id,
+                    table_name,
+                    property_name,
+                    current_value,
+                    ttl,
+                    "IF value = ?",
+                )?,
+            )
+            .await?;
+        }
+        Ok(MutationRedundancy::True)
     }
 
-    #[tracing::instrument(skip(self), err)]
-    async fn upsert_immutable_u64(
+    /// Writes an immutable property unless it's already set, returning
+    /// whichever value is stored along with the statement writing it, if any.
+    /// When it's already set and the write expires, the stored value is
+    /// rewritten to refresh its TTL, so that it doesn't expire before the
+    /// node's type.
+    #[tracing::instrument(skip(self, value), err)]
+    async fn upsert_immutable<V>(
         &self,
         tenant_id: uuid::Uuid,
-        uid: Uid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        property_value: u64,
-    ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_imm_u64(tenant_id, node_type.clone(), property_name.clone(), || {
-                async move {
-                    let property_value = property_value as i64;
-                    let query = Query::new(format!(
-                        r"
-                        INSERT INTO tenant_graph_ks.{IMM_U_64_TABLE_NAME}
-                        (tenant_id, uid, populated_field, value)
-                        VALUES (?, ?, ?, ?)
-                    "
-                    ));
-
-                    self.scylla_client
-                        .execute(
-                            query,
-                            &(tenant_id, uid.as_i64(), property_name.value, property_value),
-                        )
-                        .timeout(std::time::Duration::from_secs(3))
-                        .await
-                        .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                            tenant_id,
-                            insert_type: "MAX_U_64",
-                        })??;
-                    Ok(())
+        owner: PropertyOwner<'_>,
+        property_name: &PropertyName,
+        table_name: &'static str,
+        value: V,
+        ttl: Ttl,
+    ) -> Result<(MutationRedundancy, V, Vec<Statement>), GraphMutationManagerError>
+    where
+        V: Value + FromCqlVal<CqlValue> + Send + Sync,
+    {
+        let current_value: Option<V> = self
+            .get_property_value(tenant_id, owner, property_name, table_name)
+            .await?;
+        let (mutation_redundancy, value) = match current_value {
+            Some(current_value) => (MutationRedundancy::True, current_value),
+            None => (MutationRedundancy::False, value),
+        };
+        if mutation_redundancy == MutationRedundancy::True && !ttl.expires() {
+            return Ok((mutation_redundancy, value, vec![]));
+        }
+
+        let statement = owner.insert(tenant_id, table_name, property_name, &value, ttl)?;
+        Ok((mutation_redundancy, value, vec![statement]))
+    }
+
+    /// Writes `property`, returning whether it was redundant along with the
+    /// statements making up the write, which must be executed in the same
+    /// batch. Max and min properties are written by `upsert_extremum` itself,
+    /// so have none. An immutable property's index entries are written
+    /// whenever it is, including when an expiring write refreshes its TTL. A
+    /// stored property therefore implies that its index entries were stored
+    /// too, so a write whose batch failed is never reported as redundant when
+    /// it's retried.
+    async fn upsert_property(
+        &self,
+        tenant_id: uuid::Uuid,
+        owner: PropertyOwner<'_>,
+        property_name: &PropertyName,
+        property: &Property,
+        ttl: Ttl,
+    ) -> Result<(MutationRedundancy, Vec<Statement>), GraphMutationManagerError> {
+        match property {
+            Property::IncrementOnlyUintProp(p) => {
+                let mutation_redundancy = self
+                    .upsert_extremum(
+                        tenant_id,
+                        owner,
+                        property_name,
+                        owner.table_name(MAX_U_64_TABLE_NAME, EDGE_U_64_TABLE_NAME),
+                        Extremum::Max,
+                        u64_to_bigint(property_name, p.prop)?,
+                        ttl,
+                    )
+                    .await?;
+                Ok((mutation_redundancy, vec![]))
+            }
+            Property::DecrementOnlyUintProp(p) => {
+                let mutation_redundancy = self
+                    .upsert_extremum(
+                        tenant_id,
+                        owner,
+                        property_name,
+                        owner.table_name(MIN_U_64_TABLE_NAME, EDGE_U_64_TABLE_NAME),
+                        Extremum::Min,
+                        u64_to_bigint(property_name, p.prop)?,
+                        ttl,
+                    )
+                    .await?;
+                Ok((mutation_redundancy, vec![]))
+            }
+            Property::ImmutableUintProp(p) => {
+                let (mutation_redundancy, value, mut statements) = self
+                    .upsert_immutable(
+                        tenant_id,
+                        owner,
+                        property_name,
+                        owner.table_name(IMM_U_64_TABLE_NAME, EDGE_U_64_TABLE_NAME),
+                        u64_to_bigint(property_name, p.prop)?,
+                        ttl,
+                    )
+                    .await?;
+                if !statements.is_empty() {
+                    statements.extend(owner.int_index_statements(
+                        tenant_id,
+                        IMM_U_64_BUCKETED_INDEX_TABLE_NAME,
+                        property_name,
+                        value,
+                        ttl,
+                    )?);
                 }
-                .instrument(tracing::info_span!("upsert_max_u64"))
-            })
-            .await
-            .map(|_| ())
+                Ok((mutation_redundancy, statements))
+            }
+            Property::IncrementOnlyIntProp(p) => {
+                let mutation_redundancy = self
+                    .upsert_extremum(
+                        tenant_id,
+                        owner,
+                        property_name,
+                        owner.table_name(MAX_I_64_TABLE_NAME, EDGE_I_64_TABLE_NAME),
+                        Extremum::Max,
+                        p.prop,
+                        ttl,
+                    )
+                    .await?;
+                Ok((mutation_redundancy, vec![]))
+            }
+            Property::DecrementOnlyIntProp(p) => {
+                let mutation_redundancy = self
+                    .upsert_extremum(
+                        tenant_id,
+                        owner,
+                        property_name,
+                        owner.table_name(MIN_I_64_TABLE_NAME, EDGE_I_64_TABLE_NAME),
+                        Extremum::Min,
+                        p.prop,
+                        ttl,
+                    )
+                    .await?;
+                Ok((mutation_redundancy, vec![]))
+            }
+            Property::ImmutableIntProp(p) => {
+                let (mutation_redundancy, value, mut statements) = self
+                    .upsert_immutable(
+                        tenant_id,
+                        owner,
+                        property_name,
+                        owner.table_name(IMM_I_64_TABLE_NAME, EDGE_I_64_TABLE_NAME),
+                        p.prop,
+                        ttl,
+                    )
+                    .await?;
+                if !statements.is_empty() {
+                    statements.extend(owner.int_index_statements(
+                        tenant_id,
+                        IMM_I_64_BUCKETED_INDEX_TABLE_NAME,
+                        property_name,
+                        value,
+                        ttl,
+                    )?);
+                }
+                Ok((mutation_redundancy, statements))
+            }
+            Property::ImmutableStrProp(p) => {
+                let (mutation_redundancy, value, mut statements) = self
+                    .upsert_immutable(
+                        tenant_id,
+                        owner,
+                        property_name,
+                        owner.table_name(IMM_STRING_TABLE_NAME, EDGE_STRING_TABLE_NAME),
+                        p.prop.clone(),
+                        ttl,
+                    )
+                    .await?;
+                if !statements.is_empty() {
+                    statements.extend(owner.index_statements(
+                        tenant_id,
+                        IMM_STRING_INDEX_TABLE_NAME,
+                        property_name,
+                        value,
+                        ttl,
+                    )?);
+                }
+                Ok((mutation_redundancy, statements))
+            }
+        }
+    }
+
+    /// Whether the edge from `from_uid` to `to_uid` is stored
+    async fn edge_exists(
+        &self,
+        tenant_id: uuid::Uuid,
+        from_uid: Uid,
+        to_uid: Uid,
+        f_edge_name: &EdgeName,
+    ) -> Result<bool, GraphMutationManagerError> {
+        let mut query = Query::new(
+            "SELECT r_edge_name FROM tenant_graph_ks.edges \
+            WHERE tenant_id = ? AND source_uid = ? AND f_edge_name = ? AND destination_uid = ?",
+        );
+        query.set_is_idempotent(true);
+
+        let query_result = self
+            .scylla_client
+            .execute(
+                query,
+                &(
+                    tenant_id,
+                    from_uid.as_i64(),
+                    &f_edge_name.value,
+                    to_uid.as_i64(),
+                ),
+            )
+            .await?;
+        Ok(query_result
+            .rows_typed_or_empty::<(String,)>()
+            .next()
+            .is_some())
     }
 
+    /// Writes an edge and its reverse unless the edge already exists,
+    /// returning the statements writing them. Both directions are written in
+    /// the same batch, so an edge that exists always has its reverse. An edge
+    /// that already exists is still rewritten when the write expires, to
+    /// refresh the TTL of both directions.
     #[tracing::instrument(skip(self), err)]
-    async fn upsert_max_i64(
+    async fn upsert_edge(
+        &self,
+        tenant_id: uuid::Uuid,
+        from_uid: Uid,
+        to_uid: Uid,
+        f_edge_name: &EdgeName,
+        r_edge_name: &EdgeName,
+        ttl: Ttl,
+    ) -> Result<(MutationRedundancy, Vec<Statement>), GraphMutationManagerError> {
+        let mutation_redundancy = if self
+            .edge_exists(tenant_id, from_uid, to_uid, f_edge_name)
+            .await?
+        {
+            MutationRedundancy::True
+        } else {
+            MutationRedundancy::False
+        };
+        if mutation_redundancy == MutationRedundancy::True && !ttl.expires() {
+            return Ok((mutation_redundancy, vec![]));
+        }
+
+        let edge_statement = |source_uid: Uid,
+                              destination_uid: Uid,
+                              f_edge_name: &EdgeName,
+                              r_edge_name: &EdgeName| {
+            Ok::<_, SerializeValuesError>((
+                format!("{EDGE_INSERT_STATEMENT} USING TTL ?"),
+                serialize((
+                    tenant_id,
+                    source_uid.as_i64(),
+                    destination_uid.as_i64(),
+                    &f_edge_name.value,
+                    &r_edge_name.value,
+                    ttl.as_seconds(),
+                ))?,
+            ))
+        };
+        Ok((
+            mutation_redundancy,
+            vec![
+                edge_statement(from_uid, to_uid, f_edge_name, r_edge_name)?,
+                edge_statement(to_uid, from_uid, r_edge_name, f_edge_name)?,
+            ],
+        ))
+    }
+
+    /// Writes `property` unless the WriteDropper drops it, returning the
+    /// write to batch, if any. Writes that expire are never dropped, since the
+    /// WriteDropper would keep dropping them once the data they duplicate had
+    /// expired.
+    async fn write_property(
         &self,
         tenant_id: uuid::Uuid,
         uid: Uid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        property_value: i64,
-    ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_max_i64(
+        node_type: &NodeType,
+        property_name: &PropertyName,
+        property: &Property,
+        ttl: Ttl,
+    ) -> Result<(MutationRedundancy, Option<BatchedWrite>), GraphMutationManagerError> {
+        let pending_write = if ttl.expires() {
+            None
+        } else {
+            match self.write_dropper.pending_property(
                 tenant_id,
+                uid,
                 node_type.clone(),
                 property_name.clone(),
-                property_value,
-                || {
-                    async move {
-                        let mut query = Query::new(format!(
-                            r"
-                        INSERT INTO tenant_graph_ks.{MAX_I_64_TABLE_NAME}
-                        (tenant_id, uid, populated_field, value)
-                        VALUES (?, ?, ?, ?)
-                    "
-                        ));
-                        query.set_timestamp(Some(property_value));
-
-                        self.scylla_client
-                            .execute(
-                                query,
-                                &(tenant_id, uid.as_i64(), property_name.value, property_value),
-                            )
-                            .timeout(std::time::Duration::from_secs(3))
-                            .await
-                            .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                                tenant_id,
-                                insert_type: "MAX_I_64",
-                            })??;
-                        Ok(())
-                    }
-                    .instrument(tracing::info_span!("upsert_max_i64"))
-                },
+                property,
+            ) {
+                Some(pending_write) => Some(pending_write),
+                None => return Ok((MutationRedundancy::True, None)),
+            }
+        };
+
+        let (mutation_redundancy, statements) = self
+            .upsert_property(
+                tenant_id,
+                PropertyOwner::Node { uid, node_type },
+                property_name,
+                property,
+                ttl,
             )
-            .await
-            .map(|_| ())
+            .await?;
+        Ok((
+            mutation_redundancy,
+            Some(BatchedWrite {
+                statements,
+                pending_write,
+            }),
+        ))
     }
 
-    #[tracing::instrument(skip(self), err)]
-    async fn upsert_min_i64(
+    /// Writes an edge and its reverse unless the WriteDropper drops them,
+    /// returning the write to batch, if any. As with properties, writes that
+    /// expire are never dropped.
+    async fn write_edge(
         &self,
         tenant_id: uuid::Uuid,
-        uid: Uid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        property_value: i64,
-    ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_min_i64(
+        from_uid: Uid,
+        to_uid: Uid,
+        f_edge_name: &EdgeName,
+        r_edge_name: &EdgeName,
+        ttl: Ttl,
+    ) -> Result<(MutationRedundancy, Option<BatchedWrite>), GraphMutationManagerError> {
+        let pending_write = if ttl.expires() {
+            None
+        } else {
+            match self.write_dropper.pending_edges(
                 tenant_id,
-                node_type.clone(),
+                from_uid,
+                to_uid,
+                f_edge_name,
+                r_edge_name,
+            ) {
+                Some(pending_write) => Some(pending_write),
+                None => return Ok((MutationRedundancy::True, None)),
+            }
+        };
+
+        let (mutation_redundancy, statements) = self
+            .upsert_edge(tenant_id, from_uid, to_uid, f_edge_name, r_edge_name, ttl)
+            .await?;
+        Ok((
+            mutation_redundancy,
+            Some(BatchedWrite {
+                statements,
+                pending_write,
+            }),
+        ))
+    }
+
+    /// Writes an edge's `property` in both directions unless the
+    /// WriteDropper drops it, returning the write to batch, if any. Both
+    /// directions are batched together, and the write is only redundant if
+    /// it was in both directions.
+    async fn write_edge_property(
+        &self,
+        tenant_id: uuid::Uuid,
+        edge: &ResolvedEdge,
+        property_name: &PropertyName,
+        property: &Property,
+    ) -> Result<(MutationRedundancy, Option<BatchedWrite>), GraphMutationManagerError> {
+        let pending_write = if edge.ttl.expires() {
+            None
+        } else {
+            match self.write_dropper.pending_edge_property(
+                tenant_id,
+                edge.from_uid,
+                edge.to_uid,
+                &edge.edge_name,
                 property_name.clone(),
-                property_value,
-                || {
-                    async move {
-                        let mut query = Query::new(format!(
-                            r"
-                                INSERT INTO tenant_graph_ks.{MIN_I_64_TABLE_NAME}
-                                (tenant_id, uid, populated_field, value)
-                                VALUES (?, ?, ?, ?)
-                            "
-                        ));
-                        query.set_timestamp(Some(-property_value));
-
-                        self.scylla_client
-                            .execute(
-                                query,
-                                &(
-                                    &tenant_id,
-                                    uid.as_i64(),
-                                    property_name.value,
-                                    property_value,
-                                ),
-                            )
-                            .timeout(std::time::Duration::from_secs(3))
-                            .await
-                            .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                                tenant_id,
-                                insert_type: "MIN_I_64",
-                            })??;
-                        Ok(())
-                    }
-                    .instrument(tracing::info_span!("upsert_min_i64"))
-                },
-            )
-            .await
-            .map(|_| ())
+                property,
+            ) {
+                Some(pending_write) => Some(pending_write),
+                None => return Ok((MutationRedundancy::True, None)),
+            }
+        };
+
+        let forward = PropertyOwner::Edge {
+            source_uid: edge.from_uid,
+            edge_name: &edge.edge_name,
+            destination_uid: edge.to_uid,
+        };
+        let reverse = PropertyOwner::Edge {
+            source_uid: edge.to_uid,
+            edge_name: &edge.reverse_edge_name,
+            destination_uid: edge.from_uid,
+        };
+        let ((forward_redundancy, mut statements), (reverse_redundancy, reverse_statements)) = futures::try_join!(
+            self.upsert_property(tenant_id, forward, property_name, property, edge.ttl),
+            self.upsert_property(tenant_id, reverse, property_name, property, edge.ttl),
+        )?;
+        statements.extend(reverse_statements);
+        let mutation_redundancy = match (forward_redundancy, reverse_redundancy) {
+            (MutationRedundancy::True, MutationRedundancy::True) => MutationRedundancy::True,
+            _ => MutationRedundancy::False,
+        };
+        Ok((
+            mutation_redundancy,
+            Some(BatchedWrite {
+                statements,
+                pending_write,
+            }),
+        ))
     }
 
-    #[tracing::instrument(skip(self), err)]
-    async fn upsert_immutable_i64(
+    /// Executes the statements of `writes` in batches, committing each
+    /// batch's writes to the WriteDropper once the batch succeeds. A write's
+    /// statements are always executed in the same batch.
+    #[tracing::instrument(skip(self, writes), fields(writes = writes.len()), err)]
+    async fn execute_batched_writes(
         &self,
         tenant_id: uuid::Uuid,
-        uid: Uid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        property_value: i64,
+        writes: Vec<BatchedWrite>,
     ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_imm_i64(tenant_id, node_type.clone(), property_name.clone(), || {
-                async move {
-                    let query = Query::new(format!(
-                        "INSERT INTO tenant_graph_ks.{IMM_I_64_TABLE_NAME} \
-                        (tenant_id, uid, populated_field, value) \
-                        VALUES (?, ?, ?, ?)\
-                    "
-                    ));
-
-                    self.scylla_client
-                        .execute(
-                            query,
-                            &(tenant_id, uid.as_i64(), property_name.value, property_value),
-                        )
-                        .timeout(std::time::Duration::from_secs(3))
-                        .await
-                        .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                            tenant_id,
-                            insert_type: "IMM_I_64",
-                        })??;
-                    Ok(())
-                }
-                .instrument(tracing::info_span!("upsert_imm_i64"))
-            })
-            .await
-            .map(|_| ())
+        execute_in_batches(&self.write_dropper, writes, move |statements| {
+            self.execute_batch(tenant_id, statements)
+        })
+        .await
     }
 
-    #[tracing::instrument(skip(self), err)]
-    async fn set_node_type(
+    /// Executes `statements` in order, in batches of at most
+    /// `MAX_BATCH_STATEMENTS`
+    async fn execute_batched_statements(
         &self,
         tenant_id: uuid::Uuid,
-        uid: Uid,
-        node_type: NodeType,
+        statements: Vec<Statement>,
     ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_node_type(tenant_id, uid, || {
-                async move {
-                    let query = Query::new(
-                        "INSERT INTO tenant_graph_ks.node_type \
-                        (tenant_id, uid, node_type) \
-                        VALUES (?, ?, ?)",
-                    );
-
-                    self.scylla_client
-                        .execute(query, &(tenant_id, uid.as_i64(), node_type.value))
-                        .timeout(std::time::Duration::from_secs(3))
-                        .await
-                        .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                            tenant_id,
-                            insert_type: "NODE_TYPE",
-                        })??;
-                    Ok(())
-                }
-                .instrument(tracing::info_span!("set_node_type"))
-            })
-            .await
-            .map(|_| ())
+        let mut statements = statements.into_iter();
+        loop {
+            let chunk: Vec<Statement> = statements.by_ref().take(MAX_BATCH_STATEMENTS).collect();
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            self.execute_batch(tenant_id, chunk).await?;
+        }
     }
 
-    #[tracing::instrument(skip(self), err)]
-    async fn upsert_immutable_string(
+    async fn execute_batch(
         &self,
         tenant_id: uuid::Uuid,
-        uid: Uid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        property_value: String,
+        statements: Vec<Statement>,
     ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_imm_string(tenant_id, node_type.clone(), property_name.clone(), || {
-                async move {
-                    let query = Query::new(format!(
-                        "INSERT INTO tenant_graph_ks.{IMM_STRING_TABLE_NAME} \
-                        (tenant_id, uid, populated_field, value) \
-                        VALUES (?, ?, ?, ?)"
-                    ));
-
-                    self.scylla_client
-                        .execute(
-                            query,
-                            &(tenant_id, uid.as_i64(), property_name.value, property_value),
-                        )
-                        .timeout(std::time::Duration::from_secs(3))
-                        .await
-                        .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                            tenant_id,
-                            insert_type: "IMM_STRING",
-                        })??;
-                    Ok(())
-                }
-                .instrument(tracing::info_span!("upsert_imm_string"))
-            })
+        if statements.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch: Batch = Default::default();
+        let mut values: Vec<SerializedValues> = Vec::with_capacity(statements.len());
+        for (statement, statement_values) in statements {
+            batch.append_statement(Query::new(statement));
+            values.push(statement_values);
+        }
+        batch.set_is_idempotent(true);
+
+        self.scylla_client
+            .batch(&batch, values)
+            .timeout(std::time::Duration::from_secs(3))
             .await
-            .map(|_| ())
+            .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
+                tenant_id,
+                insert_type: "BATCH",
+            })??;
+        Ok(())
     }
 
-    #[tracing::instrument(skip(self), err)]
-    async fn upsert_edges(
+    async fn get_node_type(
         &self,
         tenant_id: uuid::Uuid,
-        from_uid: Uid,
-        to_uid: Uid,
-        f_edge_name: EdgeName,
-        r_edge_name: EdgeName,
-    ) -> Result<(), GraphMutationManagerError> {
-        self.write_dropper
-            .check_edges(
-                tenant_id,
-                from_uid,
-                to_uid,
-                f_edge_name,
-                r_edge_name,
-                |f_edge_name, r_edge_name| {
-                    async move {
-                        let f_statement = "INSERT INTO tenant_graph_ks.edges (\
-                                tenant_id, \
-                                source_uid, \
-                                destination_uid, \
-                                f_edge_name, \
-                                r_edge_name\
-                            ) \
-                            VALUES (?, ?, ?, ?, ?)"
-                            .to_string();
-                        let r_statement = f_statement.clone();
-
-                        let mut batch: scylla::batch::Batch = Default::default();
-                        batch.statements.reserve(2);
-                        batch.append_statement(Query::from(f_statement));
-                        batch.append_statement(Query::from(r_statement));
-                        batch.set_is_idempotent(true);
-
-                        self.scylla_client
-                            .batch(
-                                &batch,
-                                (
-                                    (
-                                        tenant_id,
-                                        from_uid.as_i64(),
-                                        to_uid.as_i64(),
-                                        &f_edge_name.value,
-                                        &r_edge_name.value,
-                                    ),
-                                    (
-                                        tenant_id,
-                                        to_uid.as_i64(),
-                                        from_uid.as_i64(),
-                                        &r_edge_name.value,
-                                        &f_edge_name.value,
-                                    ),
-                                ),
-                            )
-                            .timeout(std::time::Duration::from_secs(3))
-                            .await
-                            .map_err(|_| GraphMutationManagerError::ScyllaInsertTimeout {
-                                tenant_id,
-                                insert_type: "EDGES",
-                            })??;
-                        Ok(())
-                    }
-                    .instrument(tracing::info_span!("upsert_edges"))
-                },
+        uid: Uid,
+    ) -> Result<Option<String>, GraphMutationManagerError> {
+        let mut query = Query::new(
+            "SELECT node_type FROM tenant_graph_ks.node_type \
+            WHERE tenant_id = ? AND uid = ?",
+        );
+        query.set_is_idempotent(true);
+
+        let query_result = self
+            .scylla_client
+            .execute(query, &(tenant_id, uid.as_i64()))
+            .await?;
+        match query_result.rows_typed_or_empty::<(String,)>().next() {
+            Some(row) => Ok(Some(row?.0)),
+            None => Ok(None),
+        }
+    }
+
+    /// The statements deleting the index entries of a node's immutable
+    /// properties
+    async fn unindex_statements(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+        node_type: &str,
+    ) -> Result<Vec<Statement>, GraphMutationManagerError> {
+        let delete_statement = |index_table_name: &str| {
+            format!(
+                "DELETE FROM tenant_graph_ks.{index_table_name} \
+                WHERE tenant_id = ? AND node_type = ? AND populated_field = ? AND value = ? AND uid = ?"
             )
-            .await
-            .map(|_| ())
+        };
+        let select_statement = |table_name: &str| {
+            let mut query = Query::new(format!(
+                "SELECT populated_field, value FROM tenant_graph_ks.{table_name} \
+                WHERE tenant_id = ? AND uid = ?"
+            ));
+            query.set_is_idempotent(true);
+            query
+        };
+
+        let mut statements = Vec::new();
+        let query_result = self
+            .scylla_client
+            .execute(
+                select_statement(IMM_STRING_TABLE_NAME),
+                &(tenant_id, uid.as_i64()),
+            )
+            .await?;
+        for row in query_result.rows_typed_or_empty::<(String, String)>() {
+            let (populated_field, value) = row?;
+            statements.push((
+                delete_statement(IMM_STRING_INDEX_TABLE_NAME),
+                serialize((tenant_id, node_type, populated_field, value, uid.as_i64()))?,
+            ));
+        }
+
+        for (table_name, index_table_name) in [
+            (IMM_I_64_TABLE_NAME, IMM_I_64_BUCKETED_INDEX_TABLE_NAME),
+            (IMM_U_64_TABLE_NAME, IMM_U_64_BUCKETED_INDEX_TABLE_NAME),
+        ] {
+            let query_result = self
+                .scylla_client
+                .execute(select_statement(table_name), &(tenant_id, uid.as_i64()))
+                .await?;
+            for row in query_result.rows_typed_or_empty::<(String, i64)>() {
+                let (populated_field, value) = row?;
+                statements.push((
+                    format!(
+                        "DELETE FROM tenant_graph_ks.{index_table_name} \
+                        WHERE tenant_id = ? AND node_type = ? AND populated_field = ? \
+                        AND bucket = ? AND value = ? AND uid = ?"
+                    ),
+                    serialize((
+                        tenant_id,
+                        node_type,
+                        populated_field,
+                        int_index_bucket(value),
+                        value,
+                        uid.as_i64(),
+                    ))?,
+                ));
+            }
+        }
+        Ok(statements)
+    }
+}
+
+/// Which way a max or min property is allowed to move
+#[derive(Clone, Copy, Debug)]
+enum Extremum {
+    Max,
+    Min,
+}
+
+impl Extremum {
+    fn is_improved_by(self, current_value: i64, value: i64) -> bool {
+        match self {
+            Extremum::Max => value > current_value,
+            Extremum::Min => value < current_value,
+        }
     }
+
+    /// The condition under which the stored value may be replaced by the
+    /// bound value
+    fn condition(self) -> &'static str {
+        match self {
+            Extremum::Max => "IF value < ?",
+            Extremum::Min => "IF value > ?",
+        }
+    }
+}
+
+/// Where a property is stored: on a node, or on one direction of an edge
+#[derive(Clone, Copy, Debug)]
+enum PropertyOwner<'a> {
+    Node {
+        uid: Uid,
+        node_type: &'a NodeType,
+    },
+    Edge {
+        source_uid: Uid,
+        edge_name: &'a EdgeName,
+        destination_uid: Uid,
+    },
+}
+
+impl PropertyOwner<'_> {
+    fn table_name(
+        self,
+        node_table_name: &'static str,
+        edge_table_name: &'static str,
+    ) -> &'static str {
+        match self {
+            PropertyOwner::Node { .. } => node_table_name,
+            PropertyOwner::Edge { .. } => edge_table_name,
+        }
+    }
+
+    /// A statement inserting a property into `table_name`, whose values come
+    /// from `insert_values`, besides the TTL
+    fn insert_statement(self, table_name: &str) -> String {
+        let (key_columns, key_placeholders) = match self {
+            PropertyOwner::Node { .. } => ("uid", "?"),
+            PropertyOwner::Edge { .. } => ("source_uid, f_edge_name, destination_uid", "?, ?, ?"),
+        };
+        format!(
+            "INSERT INTO tenant_graph_ks.{table_name} \
+            (tenant_id, {key_columns}, populated_field, value) \
+            VALUES (?, {key_placeholders}, ?, ?)"
+        )
+    }
+
+    /// A statement inserting a property into `table_name` with `ttl`
+    fn insert(
+        self,
+        tenant_id: uuid::Uuid,
+        table_name: &str,
+        property_name: &PropertyName,
+        value: &impl Value,
+        ttl: Ttl,
+    ) -> Result<Statement, SerializeValuesError> {
+        Ok((
+            format!("{} USING TTL ?", self.insert_statement(table_name)),
+            self.insert_values(tenant_id, property_name, value, ttl)?,
+        ))
+    }
+
+    /// A lightweight transaction inserting a property into `table_name`
+    /// unless it's already set
+    fn insert_if_not_exists(
+        self,
+        tenant_id: uuid::Uuid,
+        table_name: &str,
+        property_name: &PropertyName,
+        value: &impl Value,
+        ttl: Ttl,
+    ) -> Result<Statement, SerializeValuesError> {
+        Ok((
+            format!(
+                "{} IF NOT EXISTS USING TTL ?",
+                self.insert_statement(table_name)
+            ),
+            self.insert_values(tenant_id, property_name, value, ttl)?,
+        ))
+    }
+
+    /// A lightweight transaction setting a property in `table_name` to
+    /// `value` if `condition` holds, with `value` bound to it
+    fn update_if(
+        self,
+        tenant_id: uuid::Uuid,
+        table_name: &str,
+        property_name: &PropertyName,
+        value: i64,
+        ttl: Ttl,
+        condition: &str,
+    ) -> Result<Statement, SerializeValuesError> {
+        let key_condition = self.key_condition();
+        let mut values = SerializedValues::new();
+        values.add_value(&ttl.as_seconds())?;
+        values.add_value(&value)?;
+        self.add_key_values(tenant_id, &mut values)?;
+        values.add_value(&property_name.value)?;
+        values.add_value(&value)?;
+        Ok((
+            format!(
+                "UPDATE tenant_graph_ks.{table_name} USING TTL ? \
+                SET value = ? \
+                WHERE tenant_id = ? AND {key_condition} AND populated_field = ? \
+                {condition}"
+            ),
+            values,
+        ))
+    }
+
+    fn insert_values(
+        self,
+        tenant_id: uuid::Uuid,
+        property_name: &PropertyName,
+        value: &impl Value,
+        ttl: Ttl,
+    ) -> Result<SerializedValues, SerializeValuesError> {
+        let mut values = SerializedValues::new();
+        self.add_key_values(tenant_id, &mut values)?;
+        values.add_value(&property_name.value)?;
+        values.add_value(value)?;
+        values.add_value(&ttl.as_seconds())?;
+        Ok(values)
+    }
+
+    /// The condition selecting this owner's row, besides its tenant and
+    /// property name
+    fn key_condition(self) -> &'static str {
+        match self {
+            PropertyOwner::Node { .. } => "uid = ?",
+            PropertyOwner::Edge { .. } => {
+                "source_uid = ? AND f_edge_name = ? AND destination_uid = ?"
+            }
+        }
+    }
+
+    /// Adds the values for the tenant and `key_condition`
+    fn add_key_values(
+        self,
+        tenant_id: uuid::Uuid,
+        values: &mut SerializedValues,
+    ) -> Result<(), SerializeValuesError> {
+        values.add_value(&tenant_id)?;
+        match self {
+            PropertyOwner::Node { uid, .. } => values.add_value(&uid.as_i64()),
+            PropertyOwner::Edge {
+                source_uid,
+                edge_name,
+                destination_uid,
+            } => {
+                values.add_value(&source_uid.as_i64())?;
+                values.add_value(&edge_name.value)?;
+                values.add_value(&destination_uid.as_i64())
+            }
+        }
+    }
+
+    /// The statements indexing an immutable property by its value. Only node
+    /// properties are indexed.
+    fn index_statements(
+        self,
+        tenant_id: uuid::Uuid,
+        index_table_name: &str,
+        property_name: &PropertyName,
+        value: impl Value,
+        ttl: Ttl,
+    ) -> Result<Vec<Statement>, SerializeValuesError> {
+        match self {
+            PropertyOwner::Node { uid, node_type } => Ok(vec![(
+                format!(
+                    "INSERT INTO tenant_graph_ks.{index_table_name} \
+                    (tenant_id, node_type, populated_field, value, uid) \
+                    VALUES (?, ?, ?, ?, ?) \
+                    USING TTL ?"
+                ),
+                serialize((
+                    tenant_id,
+                    &node_type.value,
+                    &property_name.value,
+                    value,
+                    uid.as_i64(),
+                    ttl.as_seconds(),
+                ))?,
+            )]),
+            PropertyOwner::Edge { .. } => Ok(vec![]),
+        }
+    }
+
+    /// The statements indexing an immutable int property by its value, in
+    /// the partition of the value's bucket. Only node properties are indexed.
+    fn int_index_statements(
+        self,
+        tenant_id: uuid::Uuid,
+        index_table_name: &str,
+        property_name: &PropertyName,
+        value: i64,
+        ttl: Ttl,
+    ) -> Result<Vec<Statement>, SerializeValuesError> {
+        match self {
+            PropertyOwner::Node { uid, node_type } => Ok(vec![(
+                format!(
+                    "INSERT INTO tenant_graph_ks.{index_table_name} \
+                    (tenant_id, node_type, populated_field, bucket, value, uid) \
+                    VALUES (?, ?, ?, ?, ?, ?) \
+                    USING TTL ?"
+                ),
+                serialize((
+                    tenant_id,
+                    &node_type.value,
+                    &property_name.value,
+                    int_index_bucket(value),
+                    value,
+                    uid.as_i64(),
+                    ttl.as_seconds(),
+                ))?,
+            )]),
+            PropertyOwner::Edge { .. } => Ok(vec![]),
+        }
+    }
+}
+
+/// An edge along with the name of its reverse and the TTL of its source
+/// node's type, which both directions and their properties are written with
+#[derive(Debug)]
+struct ResolvedEdge {
+    from_uid: Uid,
+    to_uid: Uid,
+    edge_name: EdgeName,
+    reverse_edge_name: EdgeName,
+    ttl: Ttl,
+}
+
+/// A write that the WriteDropper didn't drop, along with the statements
+/// making it up, which are executed in the same batch so that they're stored
+/// together. Its pending write, if the write may be dropped in future, is
+/// committed to the WriteDropper once they've been executed.
+struct BatchedWrite {
+    statements: Vec<Statement>,
+    pending_write: Option<PendingWrite>,
+}
+
+/// Runs the statements of `writes` through `execute_batch`, at most
+/// `MAX_BATCH_STATEMENTS` writes at a time, and commits each batch's pending
+/// writes to `write_dropper` only once that batch has succeeded. Stops at the
+/// first batch that fails, so that none of its writes are dropped when
+/// they're retried.
+async fn execute_in_batches<F, Fut>(
+    write_dropper: &WriteDropper,
+    writes: Vec<BatchedWrite>,
+    mut execute_batch: F,
+) -> Result<(), GraphMutationManagerError>
+where
+    F: FnMut(Vec<Statement>) -> Fut,
+    Fut: Future<Output = Result<(), GraphMutationManagerError>>,
+{
+    let mut writes = writes.into_iter();
+    loop {
+        let chunk: Vec<BatchedWrite> = writes.by_ref().take(MAX_BATCH_STATEMENTS).collect();
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        let mut statements = Vec::with_capacity(chunk.len());
+        let mut pending_writes = Vec::with_capacity(chunk.len());
+        for write in chunk {
+            statements.extend(write.statements);
+            pending_writes.extend(write.pending_write);
+        }
+        execute_batch(statements).await?;
+
+        for pending_write in pending_writes {
+            write_dropper.commit(pending_write).await;
+        }
+    }
+}
+
+/// Unsigned properties are stored in bigint columns. A value beyond
+/// `i64::MAX` would wrap around to a negative number, breaking both max/min
+/// comparisons and range lookups in the index, so it's rejected instead.
+fn u64_to_bigint(
+    property_name: &PropertyName,
+    value: u64,
+) -> Result<i64, GraphMutationManagerError> {
+    i64::try_from(value).map_err(|_| GraphMutationManagerError::UintOutOfRange {
+        property_name: property_name.value.clone(),
+        value,
+    })
+}
+
+/// Rejects a property that can't be stored, so that a request is refused
+/// before any of it is written.
+fn validate_property(
+    property_name: &PropertyName,
+    property: &Property,
+) -> Result<(), GraphMutationManagerError> {
+    match property {
+        Property::IncrementOnlyUintProp(p) => u64_to_bigint(property_name, p.prop).map(|_| ()),
+        Property::DecrementOnlyUintProp(p) => u64_to_bigint(property_name, p.prop).map(|_| ()),
+        Property::ImmutableUintProp(p) => u64_to_bigint(property_name, p.prop).map(|_| ()),
+        Property::IncrementOnlyIntProp(_)
+        | Property::DecrementOnlyIntProp(_)
+        | Property::ImmutableIntProp(_)
+        | Property::ImmutableStrProp(_) => Ok(()),
+    }
+}
+
+/// Whether a lightweight transaction was applied and, if it wasn't, the
+/// current `value` of the row it was conditioned on
+fn lwt_result(result: QueryResult) -> Result<(bool, Option<CqlValue>), GraphMutationManagerError> {
+    let column_index = |name: &str| {
+        result
+            .col_specs
+            .iter()
+            .position(|col_spec| col_spec.name == name)
+    };
+    let applied_index =
+        column_index("[applied]").ok_or(GraphMutationManagerError::MalformedLwtResult)?;
+    let value_index = column_index("value");
+
+    let mut row = result
+        .rows
+        .and_then(|rows| rows.into_iter().next())
+        .ok_or(GraphMutationManagerError::MalformedLwtResult)?;
+    let applied = row
+        .columns
+        .get(applied_index)
+        .and_then(|applied| applied.as_ref())
+        .and_then(CqlValue::as_boolean)
+        .ok_or(GraphMutationManagerError::MalformedLwtResult)?;
+    let value = value_index
+        .and_then(|value_index| row.columns.get_mut(value_index))
+        .and_then(Option::take);
+    Ok((applied, value))
+}
+
+fn serialize(values: impl ValueList) -> Result<SerializedValues, SerializeValuesError> {
+    Ok(values.serialized()?.into_owned())
+}
+
+/// The statements deleting one direction of an edge, along with its
+/// properties
+fn edge_delete_statements(
+    tenant_id: uuid::Uuid,
+    source_uid: i64,
+    f_edge_name: &str,
+    destination_uid: i64,
+) -> Result<Vec<Statement>, SerializeValuesError> {
+    ["edges"]
+        .into_iter()
+        .chain(EDGE_PROPERTY_TABLE_NAMES)
+        .map(|table_name| {
+            Ok((
+                format!(
+                    "DELETE FROM tenant_graph_ks.{table_name} \
+                    WHERE tenant_id = ? AND source_uid = ? AND f_edge_name = ? AND destination_uid = ?"
+                ),
+                serialize((tenant_id, source_uid, f_edge_name, destination_uid))?,
+            ))
+        })
+        .collect()
+}
+
+fn node_type_statements(
+    tenant_id: uuid::Uuid,
+    uid: Uid,
+    node_type: &NodeType,
+    ttl: Ttl,
+) -> Result<Vec<Statement>, GraphMutationManagerError> {
+    Ok(vec![(
+        NODE_TYPE_INSERT_STATEMENT.to_string(),
+        serialize((tenant_id, uid.as_i64(), &node_type.value, ttl.as_seconds()))?,
+    )])
 }
 
 #[async_trait::async_trait]
@@ -517,6 +1359,7 @@ impl GraphMutationApi for GraphMutationManager {
         request: CreateNodeRequest,
     ) -> Result<CreateNodeResponse, Self::Error> {
         tracing::debug!(message = "Creating node",);
+        self.ensure_not_purging(request.tenant_id).await?;
         let uid = self
             .uid_allocator_client
             .allocate_id(request.tenant_id)
@@ -555,53 +1398,33 @@ impl GraphMutationApi for GraphMutationManager {
             property_name,
             property,
         } = request;
+        self.ensure_not_purging(tenant_id).await?;
+        validate_property(&property_name, &property.property)?;
         tracing::debug!(
             message="Setting node property",
             uid=?uid,
             property_name=?property_name,
         );
-        match property.property {
-            Property::IncrementOnlyUintProp(property) => {
-                self.upsert_max_u64(tenant_id, uid, node_type, property_name, property.prop)
-                    .await?;
-            }
-            Property::DecrementOnlyUintProp(property) => {
-                self.upsert_min_u64(tenant_id, uid, node_type, property_name, property.prop)
-                    .await?;
-            }
-            Property::ImmutableUintProp(property) => {
-                self.upsert_immutable_u64(tenant_id, uid, node_type, property_name, property.prop)
-                    .await?;
-            }
-            Property::IncrementOnlyIntProp(property) => {
-                self.upsert_max_i64(tenant_id, uid, node_type, property_name, property.prop)
-                    .await?;
-            }
-            Property::DecrementOnlyIntProp(property) => {
-                self.upsert_min_i64(tenant_id, uid, node_type, property_name, property.prop)
-                    .await?;
-            }
-            Property::ImmutableIntProp(property) => {
-                self.upsert_immutable_i64(tenant_id, uid, node_type, property_name, property.prop)
-                    .await?;
-            }
-            Property::ImmutableStrProp(property) => {
-                self.upsert_immutable_string(
-                    tenant_id,
-                    uid,
-                    node_type,
-                    property_name,
-                    property.prop,
-                )
-                .await?;
-            }
-        };
+
+        let ttl = self
+            .retention_policy_resolver
+            .resolve_ttl(tenant_id, node_type.clone())
+            .await?;
+        let (mutation_redundancy, write) = self
+            .write_property(
+                tenant_id,
+                uid,
+                &node_type,
+                &property_name,
+                &property.property,
+                ttl,
+            )
+            .await?;
+        self.execute_batched_writes(tenant_id, write.into_iter().collect())
+            .await?;
 
         Ok(SetNodePropertyResponse {
-            // todo: At this point we can't tell if the update was redundant
-            //       but it is always safe (albeit suboptimal) to assume that
-            //       it was not.
-            mutation_redundancy: MutationRedundancy::Maybe,
+            mutation_redundancy,
         })
     }
 
@@ -619,20 +1442,580 @@ impl GraphMutationApi for GraphMutationManager {
             to_uid,
             source_node_type,
         } = request;
+        self.ensure_not_purging(tenant_id).await?;
 
         let reverse_edge_name = self
             .reverse_edge_resolver
             .resolve_reverse_edge(tenant_id, source_node_type.clone(), edge_name.clone())
             .await?;
+        let ttl = self
+            .retention_policy_resolver
+            .resolve_ttl(tenant_id, source_node_type)
+            .await?;
 
-        self.upsert_edges(tenant_id, from_uid, to_uid, edge_name, reverse_edge_name)
+        let (mutation_redundancy, write) = self
+            .write_edge(
+                tenant_id,
+                from_uid,
+                to_uid,
+                &edge_name,
+                &reverse_edge_name,
+                ttl,
+            )
+            .await?;
+        self.execute_batched_writes(tenant_id, write.into_iter().collect())
             .await?;
 
         Ok(CreateEdgeResponse {
-            // todo: At this point we don't track if the update was redundant
-            //       but it is always safe (albeit suboptimal) to assume that
-            //       it was not.
-            mutation_redundancy: MutationRedundancy::Maybe,
+            mutation_redundancy,
+        })
+    }
+
+    /// ApplyGraph writes every node property and edge of an identified graph,
+    /// using batches, and reports the redundancy of each write.
+    #[tracing::instrument(
+    skip(self, request),
+    fields(
+        tenant_id=?request.tenant_id,
+        nodes=request.identified_graph.nodes.len(),
+        edges=request.identified_graph.edges.len(),
+    ), err)]
+    async fn apply_graph(
+        &self,
+        request: ApplyGraphRequest,
+    ) -> Result<ApplyGraphResponse, Self::Error> {
+        let ApplyGraphRequest {
+            tenant_id,
+            identified_graph: IdentifiedGraph { nodes, edges },
+        } = request;
+        self.ensure_not_purging(tenant_id).await?;
+
+        for node in nodes.values() {
+            for (property_name, property) in &node.properties {
+                validate_property(
+                    &PropertyName {
+                        value: property_name.clone(),
+                    },
+                    &property.property,
+                )?;
+            }
+        }
+        for edge in edges.values().flat_map(|edge_list| edge_list.edges.iter()) {
+            for (property_name, property) in &edge.properties {
+                validate_property(
+                    &PropertyName {
+                        value: property_name.clone(),
+                    },
+                    &property.property,
+                )?;
+            }
+        }
+
+        let mut node_type_writes = Vec::new();
+        let mut node_results = Vec::with_capacity(nodes.len());
+        let mut node_ttls = HashMap::with_capacity(nodes.len());
+        let mut properties = Vec::new();
+        for (node_index, node) in nodes.values().enumerate() {
+            let node_type = NodeType {
+                value: node.node_type.clone(),
+            };
+            let ttl = self
+                .retention_policy_resolver
+                .resolve_ttl(tenant_id, node_type.clone())
+                .await?;
+            node_ttls.insert(node.uid, ttl);
+
+            if ttl.expires() {
+                node_type_writes.push(BatchedWrite {
+                    statements: node_type_statements(tenant_id, node.uid, &node_type, ttl)?,
+                    pending_write: None,
+                });
+            } else if let Some(pending_write) =
+                self.write_dropper.pending_node_type(tenant_id, node.uid)
+            {
+                node_type_writes.push(BatchedWrite {
+                    statements: node_type_statements(tenant_id, node.uid, &node_type, ttl)?,
+                    pending_write: Some(pending_write),
+                });
+            }
+            for (property_name, property) in &node.properties {
+                let property_name = PropertyName {
+                    value: property_name.clone(),
+                };
+                properties.push((node_index, node_type.clone(), property_name, property, ttl));
+            }
+            node_results.push(NodeResult {
+                uid: node.uid,
+                property_results: Vec::with_capacity(node.properties.len()),
+            });
+        }
+
+        // Node types are written before their properties, so that a node whose
+        // data expires vanishes as a whole rather than a property at a time
+        self.execute_batched_writes(tenant_id, node_type_writes)
+            .await?;
+
+        let mut writes = Vec::new();
+        let property_writes: Vec<_> = futures::stream::iter(properties)
+            .map(|(node_index, node_type, property_name, property, ttl)| {
+                let uid = node_results[node_index].uid;
+                async move {
+                    let (mutation_redundancy, write) = self
+                        .write_property(
+                            tenant_id,
+                            uid,
+                            &node_type,
+                            &property_name,
+                            &property.property,
+                            ttl,
+                        )
+                        .await?;
+                    Ok::<_, GraphMutationManagerError>((
+                        node_index,
+                        PropertyResult {
+                            property_name,
+                            mutation_redundancy,
+                        },
+                        write,
+                    ))
+                }
+            })
+            .buffered(APPLY_GRAPH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        for (node_index, property_result, write) in property_writes {
+            node_results[node_index]
+                .property_results
+                .push(property_result);
+            writes.extend(write);
+        }
+
+        let mut edges_to_write = Vec::new();
+        for edge in edges.into_values().flat_map(|edge_list| edge_list.edges) {
+            let source_node = nodes
+                .get(&edge.from_uid)
+                .ok_or(GraphMutationManagerError::MissingSourceNode(edge.from_uid))?;
+            let edge_name = EdgeName {
+                value: edge.edge_name,
+            };
+            let reverse_edge_name = self
+                .reverse_edge_resolver
+                .resolve_reverse_edge(
+                    tenant_id,
+                    NodeType {
+                        value: source_node.node_type.clone(),
+                    },
+                    edge_name.clone(),
+                )
+                .await?;
+            let resolved_edge = ResolvedEdge {
+                from_uid: edge.from_uid,
+                to_uid: edge.to_uid,
+                edge_name,
+                reverse_edge_name,
+                ttl: node_ttls[&edge.from_uid],
+            };
+            edges_to_write.push((resolved_edge, edge.properties));
+        }
+
+        let edge_writes: Vec<_> = futures::stream::iter(edges_to_write)
+            .map(|(edge, properties)| async move {
+                let (mutation_redundancy, write) = self
+                    .write_edge(
+                        tenant_id,
+                        edge.from_uid,
+                        edge.to_uid,
+                        &edge.edge_name,
+                        &edge.reverse_edge_name,
+                        edge.ttl,
+                    )
+                    .await?;
+                let mut edge_writes: Vec<BatchedWrite> = write.into_iter().collect();
+
+                let mut property_results = Vec::with_capacity(properties.len());
+                for (property_name, property) in properties {
+                    let property_name = PropertyName {
+                        value: property_name,
+                    };
+                    let (mutation_redundancy, write) = self
+                        .write_edge_property(tenant_id, &edge, &property_name, &property.property)
+                        .await?;
+                    property_results.push(PropertyResult {
+                        property_name,
+                        mutation_redundancy,
+                    });
+                    edge_writes.extend(write);
+                }
+
+                let ResolvedEdge {
+                    from_uid,
+                    to_uid,
+                    edge_name,
+                    reverse_edge_name,
+                    ..
+                } = edge;
+                Ok::<_, GraphMutationManagerError>((
+                    EdgeResult {
+                        from_uid,
+                        to_uid,
+                        edge_name,
+                        reverse_edge_name,
+                        mutation_redundancy,
+                        property_results,
+                    },
+                    edge_writes,
+                ))
+            })
+            .buffered(APPLY_GRAPH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        let mut edge_results = Vec::with_capacity(edge_writes.len());
+        for (edge_result, edge_writes) in edge_writes {
+            edge_results.push(edge_result);
+            writes.extend(edge_writes);
+        }
+
+        self.execute_batched_writes(tenant_id, writes).await?;
+
+        Ok(ApplyGraphResponse {
+            node_results,
+            edge_results,
+        })
+    }
+
+    /// DeleteNode deletes the node with the given uid, along with its properties
+    /// and all of its edges in both directions.
+    #[tracing::instrument(skip(self), err)]
+    async fn delete_node(
+        &self,
+        request: DeleteNodeRequest,
+    ) -> Result<DeleteNodeResponse, Self::Error> {
+        let DeleteNodeRequest { tenant_id, uid } = request;
+        self.ensure_not_purging(tenant_id).await?;
+
+        // Index entries go first and the node type last, so that a failed
+        // deletion can find everything again when it's retried
+        let mut statements = match self.get_node_type(tenant_id, uid).await? {
+            Some(node_type) => self.unindex_statements(tenant_id, uid, &node_type).await?,
+            None => Vec::new(),
+        };
+
+        let mut query = Query::new(
+            "SELECT destination_uid, f_edge_name, r_edge_name FROM tenant_graph_ks.edges \
+            WHERE tenant_id = ? AND source_uid = ?",
+        );
+        query.set_is_idempotent(true);
+        let query_result = self
+            .scylla_client
+            .execute(query, &(tenant_id, uid.as_i64()))
+            .await?;
+        let mut deleted_edges = 0;
+        for row in query_result.rows_typed_or_empty::<(i64, String, String)>() {
+            let (destination_uid, f_edge_name, r_edge_name) = row?;
+            statements.extend(edge_delete_statements(
+                tenant_id,
+                destination_uid,
+                &r_edge_name,
+                uid.as_i64(),
+            )?);
+            tracing::debug!(
+                message = "Deleting edge",
+                f_edge_name = %f_edge_name,
+                destination_uid = destination_uid,
+            );
+            deleted_edges += 1;
+        }
+
+        for table_name in [
+            IMM_STRING_TABLE_NAME,
+            MAX_I_64_TABLE_NAME,
+            MIN_I_64_TABLE_NAME,
+            IMM_I_64_TABLE_NAME,
+            MAX_U_64_TABLE_NAME,
+            MIN_U_64_TABLE_NAME,
+            IMM_U_64_TABLE_NAME,
+        ] {
+            statements.push((
+                format!("DELETE FROM tenant_graph_ks.{table_name} WHERE tenant_id = ? AND uid = ?"),
+                serialize((tenant_id, uid.as_i64()))?,
+            ));
+        }
+        for table_name in EDGE_PROPERTY_TABLE_NAMES {
+            statements.push((
+                format!(
+                    "DELETE FROM tenant_graph_ks.{table_name} WHERE tenant_id = ? AND source_uid = ?"
+                ),
+                serialize((tenant_id, uid.as_i64()))?,
+            ));
+        }
+        statements.push((
+            "DELETE FROM tenant_graph_ks.edges WHERE tenant_id = ? AND source_uid = ?".to_string(),
+            serialize((tenant_id, uid.as_i64()))?,
+        ));
+        statements.push((
+            "DELETE FROM tenant_graph_ks.node_type WHERE tenant_id = ? AND uid = ?".to_string(),
+            serialize((tenant_id, uid.as_i64()))?,
+        ));
+
+        self.execute_batched_statements(tenant_id, statements)
+            .await?;
+        self.write_dropper.invalidate_node(tenant_id, uid)?;
+        record_deletion(&self.scylla_client, tenant_id).await?;
+
+        Ok(DeleteNodeResponse { deleted_edges })
+    }
+
+    /// DeleteEdge deletes the edge with the name edge_name between the nodes
+    /// that have the given uids. It will also delete the reverse edge.
+    #[tracing::instrument(skip(self), err)]
+    async fn delete_edge(
+        &self,
+        request: DeleteEdgeRequest,
+    ) -> Result<DeleteEdgeResponse, Self::Error> {
+        let DeleteEdgeRequest {
+            tenant_id,
+            from_uid,
+            to_uid,
+            edge_name,
+        } = request;
+        self.ensure_not_purging(tenant_id).await?;
+
+        // The reverse edge's name is read from the edge rather than resolved
+        // from the schema, in case the schema changed since it was written
+        let mut query = Query::new(
+            "SELECT r_edge_name FROM tenant_graph_ks.edges \
+            WHERE tenant_id = ? AND source_uid = ? AND f_edge_name = ? AND destination_uid = ?",
+        );
+        query.set_is_idempotent(true);
+        let query_result = self
+            .scylla_client
+            .execute(
+                query,
+                &(
+                    tenant_id,
+                    from_uid.as_i64(),
+                    &edge_name.value,
+                    to_uid.as_i64(),
+                ),
+            )
+            .await?;
+        let reverse_edge_name = match query_result.rows_typed_or_empty::<(String,)>().next() {
+            Some(row) => EdgeName { value: row?.0 },
+            None => return Ok(DeleteEdgeResponse { existed: false }),
+        };
+
+        let mut statements = edge_delete_statements(
+            tenant_id,
+            from_uid.as_i64(),
+            &edge_name.value,
+            to_uid.as_i64(),
+        )?;
+        statements.extend(edge_delete_statements(
+            tenant_id,
+            to_uid.as_i64(),
+            &reverse_edge_name.value,
+            from_uid.as_i64(),
+        )?);
+        self.execute_batch(tenant_id, statements).await?;
+        self.write_dropper
+            .invalidate_edges(tenant_id, from_uid, to_uid, &edge_name, &reverse_edge_name)
+            .await?;
+        record_deletion(&self.scylla_client, tenant_id).await?;
+
+        Ok(DeleteEdgeResponse { existed: true })
+    }
+
+    /// PurgeTenant starts deleting a tenant's whole graph in the background.
+    #[tracing::instrument(skip(self), err)]
+    async fn purge_tenant(
+        &self,
+        request: PurgeTenantRequest,
+    ) -> Result<PurgeTenantResponse, Self::Error> {
+        self.tenant_purger.start(request.tenant_id).await?;
+        Ok(PurgeTenantResponse {})
+    }
+
+    /// GetPurgeStatus reports on the state of a tenant's purge.
+    #[tracing::instrument(skip(self), err)]
+    async fn get_purge_status(
+        &self,
+        request: GetPurgeStatusRequest,
+    ) -> Result<GetPurgeStatusResponse, Self::Error> {
+        Ok(GetPurgeStatusResponse {
+            purge_status: self.tenant_purger.status(request.tenant_id).await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_proto::graplinc::grapl::api::graph::v1beta1::{
+        ImmutableStrProp,
+        ImmutableUintProp,
+        IncrementOnlyUintProp,
+    };
+
+    use super::*;
+
+    fn property_name() -> PropertyName {
+        PropertyName {
+            value: "arbitrary_prop_name".to_string(),
+        }
+    }
+
+    fn node_type() -> NodeType {
+        NodeType {
+            value: "Process".to_string(),
+        }
+    }
+
+    fn property() -> Property {
+        Property::ImmutableStrProp(ImmutableStrProp {
+            prop: "value".to_string(),
+        })
+    }
+
+    /// A write of `property` to each uid, with one statement apiece
+    fn pending_writes(
+        write_dropper: &WriteDropper,
+        tenant_id: uuid::Uuid,
+        uids: &[Uid],
+    ) -> Vec<BatchedWrite> {
+        uids.iter()
+            .map(|uid| BatchedWrite {
+                statements: vec![(
+                    "INSERT INTO arbitrary_table".to_string(),
+                    SerializedValues::new(),
+                )],
+                pending_write: write_dropper.pending_property(
+                    tenant_id,
+                    *uid,
+                    node_type(),
+                    property_name(),
+                    &property(),
+                ),
+            })
+            .collect()
+    }
+
+    fn is_dropped(write_dropper: &WriteDropper, tenant_id: uuid::Uuid, uid: Uid) -> bool {
+        write_dropper
+            .pending_property(tenant_id, uid, node_type(), property_name(), &property())
+            .is_none()
+    }
+
+    fn uids(count: u64) -> Vec<Uid> {
+        (1..=count).map(|uid| Uid::from_u64(uid).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_committed_once_their_batch_succeeds(
+    ) -> Result<(), GraphMutationManagerError> {
+        let write_dropper = WriteDropper::new(128);
+        let tenant_id = uuid::Uuid::new_v4();
+        let uids = uids(MAX_BATCH_STATEMENTS as u64 + 1);
+        let writes = pending_writes(&write_dropper, tenant_id, &uids);
+
+        let mut batch_sizes = Vec::new();
+        execute_in_batches(&write_dropper, writes, |statements| {
+            batch_sizes.push(statements.len());
+            async { Ok(()) }
         })
+        .await?;
+
+        assert_eq!(batch_sizes, vec![MAX_BATCH_STATEMENTS, 1]);
+        for uid in uids {
+            assert!(is_dropped(&write_dropper, tenant_id, uid));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_not_committed_when_their_batch_fails() {
+        let write_dropper = WriteDropper::new(128);
+        let tenant_id = uuid::Uuid::new_v4();
+        let uids = uids(MAX_BATCH_STATEMENTS as u64 + 1);
+        let writes = pending_writes(&write_dropper, tenant_id, &uids);
+
+        // The first batch succeeds and the second fails
+        let mut batches = 0;
+        let result = execute_in_batches(&write_dropper, writes, |_| {
+            batches += 1;
+            let batch = batches;
+            async move {
+                match batch {
+                    1 => Ok(()),
+                    _ => Err(GraphMutationManagerError::ScyllaInsertTimeout {
+                        tenant_id,
+                        insert_type: "BATCH",
+                    }),
+                }
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+        let (committed, failed) = uids.split_at(MAX_BATCH_STATEMENTS);
+        for uid in committed {
+            assert!(is_dropped(&write_dropper, tenant_id, *uid));
+        }
+        // A retry of the failed write isn't dropped, and goes through once
+        // it succeeds
+        for uid in failed {
+            assert!(!is_dropped(&write_dropper, tenant_id, *uid));
+        }
+        let retry = pending_writes(&write_dropper, tenant_id, failed);
+        assert!(retry[0].pending_write.is_some());
+        execute_in_batches(&write_dropper, retry, |_| async { Ok(()) })
+            .await
+            .unwrap();
+        for uid in failed {
+            assert!(is_dropped(&write_dropper, tenant_id, *uid));
+        }
+    }
+
+    #[test]
+    fn test_u64_to_bigint_keeps_values_in_range() -> Result<(), GraphMutationManagerError> {
+        assert_eq!(u64_to_bigint(&property_name(), 0)?, 0);
+        assert_eq!(u64_to_bigint(&property_name(), i64::MAX as u64)?, i64::MAX);
+        Ok(())
+    }
+
+    #[test]
+    fn test_u64_to_bigint_rejects_values_that_would_wrap() {
+        let value = i64::MAX as u64 + 1;
+        match u64_to_bigint(&property_name(), value) {
+            Err(GraphMutationManagerError::UintOutOfRange {
+                property_name,
+                value: rejected,
+            }) => {
+                assert_eq!(property_name, "arbitrary_prop_name");
+                assert_eq!(rejected, value);
+            }
+            other => panic!("expected UintOutOfRange, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_property() {
+        let property_name = property_name();
+
+        assert!(validate_property(
+            &property_name,
+            &Property::ImmutableUintProp(ImmutableUintProp { prop: 1 }),
+        )
+        .is_ok());
+        assert!(validate_property(
+            &property_name,
+            &Property::ImmutableStrProp(ImmutableStrProp {
+                prop: "value".to_string(),
+            }),
+        )
+        .is_ok());
+        assert!(validate_property(
+            &property_name,
+            &Property::IncrementOnlyUintProp(IncrementOnlyUintProp { prop: u64::MAX }),
+        )
+        .is_err());
     }
 }
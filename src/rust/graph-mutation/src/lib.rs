@@ -1,5 +1,8 @@
 pub mod config;
+pub mod deletions;
 pub mod graph_mutation;
+pub mod purge;
+pub mod retention_policy_resolver;
 pub mod reverse_edge_resolver;
 pub mod table_names;
 pub mod write_dropper;
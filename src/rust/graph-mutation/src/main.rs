@@ -12,6 +12,7 @@ use figment::{
 use graph_mutation::{
     config::GraphMutationServiceConfig,
     graph_mutation::GraphMutationManager,
+    retention_policy_resolver::RetentionPolicyResolver,
     reverse_edge_resolver::ReverseEdgeResolver,
 };
 use rust_proto::graplinc::grapl::api::{
@@ -57,7 +58,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let graph_mutation_service = GraphMutationManager::new(
         scylla_client,
         uid_allocator_client,
-        ReverseEdgeResolver::new(graph_schema_manager_client, 10_000),
+        ReverseEdgeResolver::new(graph_schema_manager_client.clone(), 10_000),
+        RetentionPolicyResolver::new(graph_schema_manager_client, 10_000, Duration::from_secs(60)),
         1_000_000,
     );
     exec_service(config.graph_mutation_bind_address, graph_mutation_service).await
@@ -0,0 +1,388 @@
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::Duration,
+};
+
+use futures::{
+    StreamExt,
+    TryStreamExt,
+};
+use moka::{
+    future::{
+        Cache,
+        CacheBuilder,
+    },
+    PredicateError,
+};
+use rust_proto::graplinc::grapl::api::graph_mutation::v1beta1::messages::PurgeStatus;
+use scylla::{
+    cql_to_rust::FromRowError,
+    query::Query,
+    transport::{
+        errors::QueryError,
+        iterator::NextRowError,
+    },
+    CachingSession,
+};
+use tracing::Instrument;
+
+use crate::{
+    deletions::record_deletion,
+    reverse_edge_resolver::ReverseEdgeResolver,
+    table_names::{
+        int_index_bucket,
+        EDGE_PROPERTY_TABLE_NAMES,
+        IMM_I_64_BUCKETED_INDEX_TABLE_NAME,
+        IMM_I_64_TABLE_NAME,
+        IMM_STRING_INDEX_TABLE_NAME,
+        IMM_STRING_TABLE_NAME,
+        IMM_U_64_BUCKETED_INDEX_TABLE_NAME,
+        IMM_U_64_TABLE_NAME,
+        MAX_I_64_TABLE_NAME,
+        MAX_U_64_TABLE_NAME,
+        MIN_I_64_TABLE_NAME,
+        MIN_U_64_TABLE_NAME,
+        TENANT_PURGES_TABLE_NAME,
+    },
+    write_dropper::WriteDropper,
+};
+
+/// The number of index partitions a purge deletes at once
+const PURGE_CONCURRENCY: usize = 16;
+
+/// How long a tenant's purge status is cached for when checking whether its
+/// graph may be written to
+const PURGE_STATUS_CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// The most tenants whose purge statuses are cached
+const PURGE_STATUS_CACHE_SIZE: u64 = 10_000;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PurgeError {
+    #[error("Scylla Error: {0}")]
+    ScyllaError(#[from] QueryError),
+
+    #[error("Failed to read row: {0}")]
+    NextRowError(#[from] NextRowError),
+
+    #[error("Row was invalid {0}")]
+    FromRowError(#[from] FromRowError),
+
+    #[error("Invalid stored purge status {0}")]
+    InvalidPurgeStatus(String),
+
+    #[error("Failed to invalidate caches: {0}")]
+    CacheInvalidationError(#[from] PredicateError),
+}
+
+/// TenantPurger deletes tenants' whole graphs in the background.
+///
+/// Every table but the immutable property indexes is partitioned by tenant,
+/// so those are deleted a partition at a time. The indexes are partitioned by
+/// node type and property, so their partitions are found from the tenant's
+/// nodes before the nodes are deleted.
+///
+/// Purge statuses are stored in the graph keyspace, so that every server
+/// rejects writes to a tenant being purged, for up to
+/// `PURGE_STATUS_CACHE_TTL` after the purge starts. A purge is idempotent, so
+/// one left running by a server that stopped is resumed by starting it again.
+#[derive(Clone)]
+pub struct TenantPurger {
+    scylla_client: Arc<CachingSession>,
+    write_dropper: WriteDropper,
+    reverse_edge_resolver: ReverseEdgeResolver,
+    /// The tenants this server is purging
+    running_purges: Arc<Mutex<HashSet<uuid::Uuid>>>,
+    purge_status_cache: Cache<uuid::Uuid, PurgeStatus>,
+}
+
+impl TenantPurger {
+    pub fn new(
+        scylla_client: Arc<CachingSession>,
+        write_dropper: WriteDropper,
+        reverse_edge_resolver: ReverseEdgeResolver,
+    ) -> Self {
+        let purge_status_cache = CacheBuilder::new(PURGE_STATUS_CACHE_SIZE)
+            .time_to_live(PURGE_STATUS_CACHE_TTL)
+            .build();
+        Self {
+            scylla_client,
+            write_dropper,
+            reverse_edge_resolver,
+            running_purges: Default::default(),
+            purge_status_cache,
+        }
+    }
+
+    /// Starts purging a tenant's graph, unless this server is already purging
+    /// it
+    pub async fn start(&self, tenant_id: uuid::Uuid) -> Result<(), PurgeError> {
+        if !self
+            .running_purges
+            .lock()
+            .expect("poisoned lock")
+            .insert(tenant_id)
+        {
+            return Ok(());
+        }
+        if let Err(e) = self.set_status(tenant_id, PurgeStatus::Running).await {
+            self.running_purges
+                .lock()
+                .expect("poisoned lock")
+                .remove(&tenant_id);
+            return Err(e);
+        }
+
+        let purger = self.clone();
+        tokio::spawn(
+            async move {
+                let purge_status = match purger.purge(tenant_id).await {
+                    Ok(()) => {
+                        tracing::info!(message = "Purged tenant");
+                        PurgeStatus::Completed
+                    }
+                    Err(e) => {
+                        tracing::error!(message = "Failed to purge tenant", error = %e);
+                        PurgeStatus::Failed {
+                            reason: e.to_string(),
+                        }
+                    }
+                };
+                if let Err(e) = purger.set_status(tenant_id, purge_status).await {
+                    tracing::error!(message = "Failed to store purge status", error = %e);
+                }
+                purger
+                    .running_purges
+                    .lock()
+                    .expect("poisoned lock")
+                    .remove(&tenant_id);
+            }
+            .instrument(tracing::info_span!("purge_tenant", tenant_id = %tenant_id)),
+        );
+        Ok(())
+    }
+
+    async fn set_status(
+        &self,
+        tenant_id: uuid::Uuid,
+        purge_status: PurgeStatus,
+    ) -> Result<(), PurgeError> {
+        let (status, reason) = match &purge_status {
+            PurgeStatus::NotStarted => ("not_started", None),
+            PurgeStatus::Running => ("running", None),
+            PurgeStatus::Completed => ("completed", None),
+            PurgeStatus::Failed { reason } => ("failed", Some(reason.as_str())),
+        };
+        let mut query = Query::new(format!(
+            "INSERT INTO tenant_graph_ks.{TENANT_PURGES_TABLE_NAME} \
+            (tenant_id, status, reason) \
+            VALUES (?, ?, ?)"
+        ));
+        query.set_is_idempotent(true);
+        self.scylla_client
+            .execute(query, &(tenant_id, status, reason))
+            .await?;
+        self.purge_status_cache
+            .insert(tenant_id, purge_status)
+            .await;
+        Ok(())
+    }
+
+    /// The stored status of a tenant's purge
+    pub async fn status(&self, tenant_id: uuid::Uuid) -> Result<PurgeStatus, PurgeError> {
+        let mut query = Query::new(format!(
+            "SELECT status, reason FROM tenant_graph_ks.{TENANT_PURGES_TABLE_NAME} \
+            WHERE tenant_id = ?"
+        ));
+        query.set_is_idempotent(true);
+        let query_result = self.scylla_client.execute(query, &(tenant_id,)).await?;
+        let row = match query_result
+            .rows_typed_or_empty::<(String, Option<String>)>()
+            .next()
+        {
+            Some(row) => row?,
+            None => return Ok(PurgeStatus::NotStarted),
+        };
+        let (status, reason) = row;
+        let purge_status = match status.as_str() {
+            "not_started" => PurgeStatus::NotStarted,
+            "running" => PurgeStatus::Running,
+            "completed" => PurgeStatus::Completed,
+            "failed" => PurgeStatus::Failed {
+                reason: reason.unwrap_or_default(),
+            },
+            other => return Err(PurgeError::InvalidPurgeStatus(other.to_string())),
+        };
+        self.purge_status_cache
+            .insert(tenant_id, purge_status.clone())
+            .await;
+        Ok(purge_status)
+    }
+
+    /// Whether a purge of the tenant is running, in which case writes to its
+    /// graph must be rejected
+    pub async fn is_purging(&self, tenant_id: uuid::Uuid) -> Result<bool, PurgeError> {
+        let purge_status = match self.purge_status_cache.get(&tenant_id) {
+            Some(purge_status) => purge_status,
+            None => self.status(tenant_id).await?,
+        };
+        Ok(purge_status == PurgeStatus::Running)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn purge(&self, tenant_id: uuid::Uuid) -> Result<(), PurgeError> {
+        let node_types = self.get_node_types(tenant_id).await?;
+
+        self.delete_string_index_partitions(tenant_id, &node_types)
+            .await?;
+        for (table_name, index_table_name) in [
+            (IMM_I_64_TABLE_NAME, IMM_I_64_BUCKETED_INDEX_TABLE_NAME),
+            (IMM_U_64_TABLE_NAME, IMM_U_64_BUCKETED_INDEX_TABLE_NAME),
+        ] {
+            self.delete_int_index_partitions(tenant_id, &node_types, table_name, index_table_name)
+                .await?;
+        }
+
+        // The node types go last, so that a failed purge can find the index
+        // partitions again when it's retried
+        let table_names = [
+            IMM_STRING_TABLE_NAME,
+            MAX_I_64_TABLE_NAME,
+            MIN_I_64_TABLE_NAME,
+            IMM_I_64_TABLE_NAME,
+            MAX_U_64_TABLE_NAME,
+            MIN_U_64_TABLE_NAME,
+            IMM_U_64_TABLE_NAME,
+        ]
+        .into_iter()
+        .chain(EDGE_PROPERTY_TABLE_NAMES)
+        .chain(["edges", "node_type"]);
+        for table_name in table_names {
+            let mut query = Query::new(format!(
+                "DELETE FROM tenant_graph_ks.{table_name} WHERE tenant_id = ?"
+            ));
+            query.set_is_idempotent(true);
+            self.scylla_client.execute(query, &(tenant_id,)).await?;
+        }
+
+        self.write_dropper.invalidate_tenant(tenant_id)?;
+        self.reverse_edge_resolver.invalidate_tenant(tenant_id)?;
+        record_deletion(&self.scylla_client, tenant_id).await?;
+        Ok(())
+    }
+
+    async fn get_node_types(
+        &self,
+        tenant_id: uuid::Uuid,
+    ) -> Result<HashMap<i64, String>, PurgeError> {
+        let mut rows = self
+            .scylla_client
+            .execute_iter(
+                Query::new(
+                    "SELECT uid, node_type FROM tenant_graph_ks.node_type WHERE tenant_id = ?",
+                ),
+                &(tenant_id,),
+            )
+            .await?
+            .into_typed::<(i64, String)>();
+
+        let mut node_types = HashMap::new();
+        while let Some((uid, node_type)) = rows.try_next().await? {
+            node_types.insert(uid, node_type);
+        }
+        Ok(node_types)
+    }
+
+    async fn delete_string_index_partitions(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_types: &HashMap<i64, String>,
+    ) -> Result<(), PurgeError> {
+        let mut rows = self
+            .scylla_client
+            .execute_iter(
+                Query::new(format!(
+                    "SELECT uid, populated_field, value \
+                    FROM tenant_graph_ks.{IMM_STRING_TABLE_NAME} \
+                    WHERE tenant_id = ?"
+                )),
+                &(tenant_id,),
+            )
+            .await?
+            .into_typed::<(i64, String, String)>();
+
+        let mut partitions = HashSet::new();
+        while let Some((uid, populated_field, value)) = rows.try_next().await? {
+            if let Some(node_type) = node_types.get(&uid) {
+                partitions.insert((node_type, populated_field, value));
+            }
+        }
+
+        futures::stream::iter(partitions)
+            .map(|(node_type, populated_field, value)| async move {
+                let mut query = Query::new(format!(
+                    "DELETE FROM tenant_graph_ks.{IMM_STRING_INDEX_TABLE_NAME} \
+                    WHERE tenant_id = ? AND node_type = ? AND populated_field = ? AND value = ?"
+                ));
+                query.set_is_idempotent(true);
+                self.scylla_client
+                    .execute(query, &(tenant_id, node_type, populated_field, value))
+                    .await
+            })
+            .buffer_unordered(PURGE_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_int_index_partitions(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_types: &HashMap<i64, String>,
+        table_name: &str,
+        index_table_name: &str,
+    ) -> Result<(), PurgeError> {
+        let mut rows = self
+            .scylla_client
+            .execute_iter(
+                Query::new(format!(
+                    "SELECT uid, populated_field, value \
+                    FROM tenant_graph_ks.{table_name} \
+                    WHERE tenant_id = ?"
+                )),
+                &(tenant_id,),
+            )
+            .await?
+            .into_typed::<(i64, String, i64)>();
+
+        let mut partitions = HashSet::new();
+        while let Some((uid, populated_field, value)) = rows.try_next().await? {
+            if let Some(node_type) = node_types.get(&uid) {
+                partitions.insert((node_type, populated_field, int_index_bucket(value)));
+            }
+        }
+
+        futures::stream::iter(partitions)
+            .map(|(node_type, populated_field, bucket)| async move {
+                let mut query = Query::new(format!(
+                    "DELETE FROM tenant_graph_ks.{index_table_name} \
+                    WHERE tenant_id = ? AND node_type = ? AND populated_field = ? AND bucket = ?"
+                ));
+                query.set_is_idempotent(true);
+                self.scylla_client
+                    .execute(query, &(tenant_id, node_type, populated_field, bucket))
+                    .await
+            })
+            .buffer_unordered(PURGE_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(())
+    }
+}
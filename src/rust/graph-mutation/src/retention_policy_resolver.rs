@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use moka::future::{
+    Cache,
+    CacheBuilder,
+};
+use rust_proto::graplinc::grapl::{
+    api::{
+        client::ClientError,
+        graph_schema_manager::v1beta1::{
+            client::GraphSchemaManagerClient,
+            messages::{
+                GetRetentionPolicyRequest,
+                RetentionPolicy,
+            },
+        },
+    },
+    common::v1beta1::types::NodeType,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum RetentionPolicyResolverError {
+    #[error("gRPC client error: {0}")]
+    ClientError(#[from] ClientError),
+}
+
+/// A Scylla TTL, in seconds. A TTL of 0 means the data never expires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ttl(i32);
+
+impl Ttl {
+    pub const FOREVER: Ttl = Ttl(0);
+
+    /// Whether data written with this TTL expires
+    pub fn expires(self) -> bool {
+        self != Self::FOREVER
+    }
+
+    /// The value bound to a `USING TTL ?` clause
+    pub fn as_seconds(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<RetentionPolicy> for Ttl {
+    fn from(retention_policy: RetentionPolicy) -> Self {
+        match retention_policy.ttl_seconds() {
+            // The schema manager rejects TTLs above Scylla's maximum
+            Some(seconds) => Ttl(i32::try_from(seconds).unwrap_or(i32::MAX)),
+            None => Ttl::FOREVER,
+        }
+    }
+}
+
+/// Resolves the TTL that a node type's data is written with. Policies are
+/// only cached for `time_to_live`, so that changes to them are picked up.
+#[derive(Clone)]
+pub struct RetentionPolicyResolver {
+    schema_client: GraphSchemaManagerClient,
+    ttl_cache: Cache<(uuid::Uuid, NodeType), Ttl>,
+}
+
+impl RetentionPolicyResolver {
+    pub fn new(
+        schema_client: GraphSchemaManagerClient,
+        cache_size: u64,
+        time_to_live: Duration,
+    ) -> Self {
+        let ttl_cache = CacheBuilder::new(cache_size)
+            .time_to_live(time_to_live)
+            .build();
+        Self {
+            schema_client,
+            ttl_cache,
+        }
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn resolve_ttl(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type: NodeType,
+    ) -> Result<Ttl, RetentionPolicyResolverError> {
+        let cache = &self.ttl_cache;
+        let key = (tenant_id, node_type.clone());
+
+        match cache.get(&key) {
+            Some(ttl) => Ok(ttl),
+            None => {
+                let mut schema_client = self.schema_client.clone();
+                let response = schema_client
+                    .get_retention_policy(GetRetentionPolicyRequest {
+                        tenant_id,
+                        node_type,
+                    })
+                    .await?;
+
+                let ttl = Ttl::from(response.retention_policy);
+                cache.insert(key, ttl).await;
+                Ok(ttl)
+            }
+        }
+    }
+}
@@ -1,4 +1,10 @@
-use moka::future::Cache;
+use moka::{
+    future::{
+        Cache,
+        CacheBuilder,
+    },
+    PredicateError,
+};
 use rust_proto::graplinc::grapl::{
     api::{
         client::ClientError,
@@ -27,13 +33,23 @@ pub struct ReverseEdgeResolver {
 
 impl ReverseEdgeResolver {
     pub fn new(schema_client: GraphSchemaManagerClient, cache_size: u64) -> Self {
-        let r_edge_cache = Cache::new(cache_size);
+        let r_edge_cache = CacheBuilder::new(cache_size)
+            .support_invalidation_closures()
+            .build();
         Self {
             schema_client,
             r_edge_cache,
         }
     }
 
+    /// Forgets a tenant's reverse edges, so that they're looked up again if
+    /// the tenant's graph is rebuilt after a purge
+    pub fn invalidate_tenant(&self, tenant_id: uuid::Uuid) -> Result<(), PredicateError> {
+        self.r_edge_cache
+            .invalidate_entries_if(move |(key_tenant_id, _, _), _| *key_tenant_id == tenant_id)?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self), err)]
     pub async fn resolve_reverse_edge(
         &self,
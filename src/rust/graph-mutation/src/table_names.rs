@@ -5,6 +5,28 @@ pub const MAX_U_64_TABLE_NAME: &str = "max_u64";
 pub const MIN_U_64_TABLE_NAME: &str = "min_u64";
 pub const IMM_U_64_TABLE_NAME: &str = "imm_u64";
 pub const IMM_STRING_TABLE_NAME: &str = "imm_string";
+pub const IMM_I_64_BUCKETED_INDEX_TABLE_NAME: &str = "imm_i64_bucketed_index";
+pub const IMM_U_64_BUCKETED_INDEX_TABLE_NAME: &str = "imm_u64_bucketed_index";
+pub const IMM_STRING_INDEX_TABLE_NAME: &str = "imm_string_index";
+pub const EDGE_I_64_TABLE_NAME: &str = "edge_i64";
+pub const EDGE_U_64_TABLE_NAME: &str = "edge_u64";
+pub const EDGE_STRING_TABLE_NAME: &str = "edge_string";
+pub const TENANT_PURGES_TABLE_NAME: &str = "tenant_purges";
+pub const GRAPH_DELETIONS_TABLE_NAME: &str = "graph_deletions";
+pub const EDGE_PROPERTY_TABLE_NAMES: [&str; 3] = [
+    EDGE_STRING_TABLE_NAME,
+    EDGE_I_64_TABLE_NAME,
+    EDGE_U_64_TABLE_NAME,
+];
+
+/// The int property indexes are partitioned by buckets of `2^20` values, so
+/// that a partition only holds a bounded range of a property's values
+pub const INT_INDEX_BUCKET_BITS: u32 = 20;
+
+/// The index bucket holding `value`
+pub fn int_index_bucket(value: i64) -> i64 {
+    value >> INT_INDEX_BUCKET_BITS
+}
 
 pub fn tenant_keyspace_name(tenant_id: uuid::Uuid) -> String {
     // scylla keyspace names must be alphanumeric + underscores, and max out at 48.
@@ -1,19 +1,25 @@
-use std::{
-    future::Future,
-    hash::Hash,
-};
+use std::hash::Hash;
 
 use blake2::{
     digest::typenum::U16,
     Blake2b,
     Digest,
 };
-use moka::future::Cache;
-use rust_proto::graplinc::grapl::common::v1beta1::types::{
-    EdgeName,
-    NodeType,
-    PropertyName,
-    Uid,
+use moka::{
+    future::{
+        Cache,
+        CacheBuilder,
+    },
+    PredicateError,
+};
+use rust_proto::graplinc::grapl::{
+    api::graph::v1beta1::Property,
+    common::v1beta1::types::{
+        EdgeName,
+        NodeType,
+        PropertyName,
+        Uid,
+    },
 };
 
 type Blake2b16 = Blake2b<U16>;
@@ -24,18 +30,51 @@ struct NodeTypeKey {
     uid: Uid,
 }
 
+/// What a property belongs to, besides the key's uid
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum PropertyOwner {
+    Node(NodeType),
+    /// The edge from the key's uid, in that direction
+    Edge {
+        dest_uid: Uid,
+        edge_name_hash: [u8; 16],
+    },
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct PropertyKey {
     tenant_id: uuid::Uuid,
-    node_type: NodeType,
+    uid: Uid,
+    owner: PropertyOwner,
     property_name: PropertyName,
 }
 
-// This enum/return type mostly exists for testing behavior.
-#[derive(PartialEq, Eq)]
-pub enum WriteDropStatus {
-    Stored,
-    Dropped,
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct EdgeKey {
+    tenant_id: uuid::Uuid,
+    source_uid: Uid,
+    dest_uid: Uid,
+    edge_name_hash: [u8; 16],
+}
+
+/// A write that the WriteDropper didn't drop. Once the write has been
+/// applied it must be passed to `WriteDropper::commit`, so that later
+/// identical writes are dropped. Until then, a failed write isn't dropped
+/// when it's retried.
+#[derive(Clone, Debug)]
+pub struct PendingWrite(PendingWriteKind);
+
+#[derive(Clone, Debug)]
+enum PendingWriteKind {
+    MaxI64(PropertyKey, i64),
+    MinI64(PropertyKey, i64),
+    ImmI64(PropertyKey),
+    MaxU64(PropertyKey, u64),
+    MinU64(PropertyKey, u64),
+    ImmU64(PropertyKey),
+    ImmString(PropertyKey),
+    NodeType(NodeTypeKey),
+    Edges(EdgeKey, EdgeKey),
 }
 
 /// WriteDropper lets us save database IO by proactively "dropping" db writes
@@ -56,279 +95,295 @@ pub struct WriteDropper {
     imm_u64: Cache<PropertyKey, ()>,
     imm_string: Cache<PropertyKey, ()>,
     node_type: Cache<NodeTypeKey, ()>,
-    edges: Cache<[u8; 16], ()>,
+    edges: Cache<EdgeKey, ()>,
 }
 
 impl WriteDropper {
     pub fn new(max_size: u64) -> Self {
         Self {
-            max_i64: Cache::new(max_size),
-            min_i64: Cache::new(max_size),
-            imm_i64: Cache::new(max_size),
-            max_u64: Cache::new(max_size),
-            min_u64: Cache::new(max_size),
-            imm_u64: Cache::new(max_size),
-            imm_string: Cache::new(max_size),
-            node_type: Cache::new(max_size),
-            edges: Cache::new(max_size),
+            max_i64: new_cache(max_size),
+            min_i64: new_cache(max_size),
+            imm_i64: new_cache(max_size),
+            max_u64: new_cache(max_size),
+            min_u64: new_cache(max_size),
+            imm_u64: new_cache(max_size),
+            imm_string: new_cache(max_size),
+            node_type: new_cache(max_size),
+            edges: new_cache(max_size),
         }
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_max_i64<T, E, Fut>(
-        &self,
-        tenant_id: uuid::Uuid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        value: i64,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
-        let key = PropertyKey {
-            tenant_id,
-            node_type,
-            property_name,
-        };
-        get_or_insert_into_cache(self.max_i64.clone(), key, value, callback, |new, old| {
-            new > old
-        })
-        .await
+    /// Forgets every write to a tenant's graph, so that none are dropped
+    /// after it has been purged
+    pub fn invalidate_tenant(&self, tenant_id: uuid::Uuid) -> Result<(), PredicateError> {
+        self.invalidate_properties_if(move |key| key.tenant_id == tenant_id)?;
+        self.node_type
+            .invalidate_entries_if(move |key, _| key.tenant_id == tenant_id)?;
+        self.edges
+            .invalidate_entries_if(move |key, _| key.tenant_id == tenant_id)?;
+        Ok(())
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_min_i64<T, E, Fut>(
-        &self,
-        tenant_id: uuid::Uuid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        value: i64,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
-        let key = PropertyKey {
-            tenant_id,
-            node_type,
-            property_name,
-        };
-        get_or_insert_into_cache(self.min_i64.clone(), key, value, callback, |new, old| {
-            new < old
-        })
-        .await
+    /// Forgets every write to a node, and to the edges in both directions
+    /// between it and other nodes, so that none are dropped after it has been
+    /// deleted
+    pub fn invalidate_node(&self, tenant_id: uuid::Uuid, uid: Uid) -> Result<(), PredicateError> {
+        self.invalidate_properties_if(move |key| {
+            key.tenant_id == tenant_id
+                && (key.uid == uid
+                    || matches!(key.owner, PropertyOwner::Edge { dest_uid, .. } if dest_uid == uid))
+        })?;
+        self.node_type
+            .invalidate_entries_if(move |key, _| key.tenant_id == tenant_id && key.uid == uid)?;
+        self.edges.invalidate_entries_if(move |key, _| {
+            key.tenant_id == tenant_id && (key.source_uid == uid || key.dest_uid == uid)
+        })?;
+        Ok(())
     }
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_imm_i64<T, E, Fut>(
+
+    /// Forgets the write of an edge and its reverse, along with their
+    /// properties, so that they aren't dropped after being deleted
+    pub async fn invalidate_edges(
         &self,
         tenant_id: uuid::Uuid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
-        let key = PropertyKey {
-            tenant_id,
-            node_type,
-            property_name,
+        source_uid: Uid,
+        dest_uid: Uid,
+        f_edge_name: &EdgeName,
+        r_edge_name: &EdgeName,
+    ) -> Result<(), PredicateError> {
+        let f_owner = PropertyOwner::Edge {
+            dest_uid,
+            edge_name_hash: edge_name_hash(f_edge_name),
         };
-        get_or_insert_into_cache(self.imm_i64.clone(), key, (), callback, |_, _| false).await
+        let r_owner = PropertyOwner::Edge {
+            dest_uid: source_uid,
+            edge_name_hash: edge_name_hash(r_edge_name),
+        };
+        self.invalidate_properties_if(move |key| {
+            key.tenant_id == tenant_id
+                && ((key.uid == source_uid && key.owner == f_owner)
+                    || (key.uid == dest_uid && key.owner == r_owner))
+        })?;
+
+        self.edges
+            .invalidate(&edge_key(tenant_id, source_uid, dest_uid, f_edge_name))
+            .await;
+        self.edges
+            .invalidate(&edge_key(tenant_id, dest_uid, source_uid, r_edge_name))
+            .await;
+        Ok(())
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_max_u64<T, E, Fut>(
+    fn invalidate_properties_if(
         &self,
-        tenant_id: uuid::Uuid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        value: u64,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
-        let key = PropertyKey {
-            tenant_id,
-            node_type,
-            property_name,
-        };
-        get_or_insert_into_cache(self.max_u64.clone(), key, value, callback, |new, old| {
-            new > old
-        })
-        .await
+        predicate: impl Fn(&PropertyKey) -> bool + Clone + Send + Sync + 'static,
+    ) -> Result<(), PredicateError> {
+        let p = predicate.clone();
+        self.max_i64.invalidate_entries_if(move |key, _| p(key))?;
+        let p = predicate.clone();
+        self.min_i64.invalidate_entries_if(move |key, _| p(key))?;
+        let p = predicate.clone();
+        self.imm_i64.invalidate_entries_if(move |key, _| p(key))?;
+        let p = predicate.clone();
+        self.max_u64.invalidate_entries_if(move |key, _| p(key))?;
+        let p = predicate.clone();
+        self.min_u64.invalidate_entries_if(move |key, _| p(key))?;
+        let p = predicate.clone();
+        self.imm_u64.invalidate_entries_if(move |key, _| p(key))?;
+        self.imm_string
+            .invalidate_entries_if(move |key, _| predicate(key))?;
+        Ok(())
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_min_u64<T, E, Fut>(
+    /// Checks whether a write of `property` would be dropped, without
+    /// recording it. Returns `None` if it would be.
+    pub fn pending_property(
         &self,
         tenant_id: uuid::Uuid,
+        uid: Uid,
         node_type: NodeType,
         property_name: PropertyName,
-        value: u64,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
+        property: &Property,
+    ) -> Option<PendingWrite> {
         let key = PropertyKey {
             tenant_id,
-            node_type,
+            uid,
+            owner: PropertyOwner::Node(node_type),
             property_name,
         };
-        get_or_insert_into_cache(self.min_u64.clone(), key, value, callback, |new, old| {
-            new < old
-        })
-        .await
+        self.pending_property_write(key, property)
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_imm_u64<T, E, Fut>(
+    /// Checks whether a write of an edge's `property` would be dropped,
+    /// without recording it. Returns `None` if it would be.
+    pub fn pending_edge_property(
         &self,
         tenant_id: uuid::Uuid,
-        node_type: NodeType,
+        source_uid: Uid,
+        dest_uid: Uid,
+        edge_name: &EdgeName,
         property_name: PropertyName,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
+        property: &Property,
+    ) -> Option<PendingWrite> {
         let key = PropertyKey {
             tenant_id,
-            node_type,
+            uid: source_uid,
+            owner: PropertyOwner::Edge {
+                dest_uid,
+                edge_name_hash: edge_name_hash(edge_name),
+            },
             property_name,
         };
-        get_or_insert_into_cache(self.imm_u64.clone(), key, (), callback, |_, _| false).await
+        self.pending_property_write(key, property)
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_imm_string<T, E, Fut>(
+    fn pending_property_write(
         &self,
-        tenant_id: uuid::Uuid,
-        node_type: NodeType,
-        property_name: PropertyName,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
-        let key = PropertyKey {
-            tenant_id,
-            node_type,
-            property_name,
+        key: PropertyKey,
+        property: &Property,
+    ) -> Option<PendingWrite> {
+        let pending_write = match property {
+            Property::IncrementOnlyIntProp(p) => {
+                should_store(&self.max_i64, &key, &p.prop, |new, old| new > old)
+                    .then_some(PendingWriteKind::MaxI64(key, p.prop))
+            }
+            Property::DecrementOnlyIntProp(p) => {
+                should_store(&self.min_i64, &key, &p.prop, |new, old| new < old)
+                    .then_some(PendingWriteKind::MinI64(key, p.prop))
+            }
+            Property::ImmutableIntProp(_) => should_store(&self.imm_i64, &key, &(), |_, _| false)
+                .then_some(PendingWriteKind::ImmI64(key)),
+            Property::IncrementOnlyUintProp(p) => {
+                should_store(&self.max_u64, &key, &p.prop, |new, old| new > old)
+                    .then_some(PendingWriteKind::MaxU64(key, p.prop))
+            }
+            Property::DecrementOnlyUintProp(p) => {
+                should_store(&self.min_u64, &key, &p.prop, |new, old| new < old)
+                    .then_some(PendingWriteKind::MinU64(key, p.prop))
+            }
+            Property::ImmutableUintProp(_) => should_store(&self.imm_u64, &key, &(), |_, _| false)
+                .then_some(PendingWriteKind::ImmU64(key)),
+            Property::ImmutableStrProp(_) => {
+                should_store(&self.imm_string, &key, &(), |_, _| false)
+                    .then_some(PendingWriteKind::ImmString(key))
+            }
         };
-        get_or_insert_into_cache(self.imm_string.clone(), key, (), callback, |_, _| false).await
+        pending_write.map(PendingWrite)
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_node_type<T, E, Fut>(
-        &self,
-        tenant_id: uuid::Uuid,
-        uid: Uid,
-        callback: impl FnOnce() -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
+    /// Checks whether a write of a node's type would be dropped, without
+    /// recording it. Returns `None` if it would be.
+    pub fn pending_node_type(&self, tenant_id: uuid::Uuid, uid: Uid) -> Option<PendingWrite> {
         let key = NodeTypeKey { tenant_id, uid };
-        get_or_insert_into_cache(self.node_type.clone(), key, (), callback, |_, _| false).await
+        should_store(&self.node_type, &key, &(), |_, _| false)
+            .then_some(PendingWrite(PendingWriteKind::NodeType(key)))
     }
 
-    #[tracing::instrument(skip(self, callback), err)]
-    pub async fn check_edges<T, E, Fut>(
+    /// Checks whether a write of an edge and its reverse would be dropped,
+    /// without recording it. Returns `None` if it would be.
+    pub fn pending_edges(
         &self,
         tenant_id: uuid::Uuid,
         source_uid: Uid,
         dest_uid: Uid,
-        f_edge_name: EdgeName,
-        r_edge_name: EdgeName,
-        callback: impl FnOnce(EdgeName, EdgeName) -> Fut,
-    ) -> Result<WriteDropStatus, E>
-    where
-        Fut: Future<Output = Result<T, E>>,
-        E: std::error::Error,
-    {
-        let cache = self.edges.clone();
-        let fkey = edge_key(tenant_id, source_uid, dest_uid, &f_edge_name);
-
-        let should_insert_value = match cache.get(&fkey) {
-            None => true,
-            Some(_) => false,
-        };
+        f_edge_name: &EdgeName,
+        r_edge_name: &EdgeName,
+    ) -> Option<PendingWrite> {
+        let fkey = edge_key(tenant_id, source_uid, dest_uid, f_edge_name);
+        should_store(&self.edges, &fkey, &(), |_, _| false).then(|| {
+            let rkey = edge_key(tenant_id, dest_uid, source_uid, r_edge_name);
+            PendingWrite(PendingWriteKind::Edges(fkey, rkey))
+        })
+    }
 
-        if should_insert_value {
-            let rkey = edge_key(tenant_id, dest_uid, source_uid, &r_edge_name);
-            callback(f_edge_name, r_edge_name).await?;
-            cache.insert(fkey, ()).await;
-            cache.insert(rkey, ()).await;
+    /// Records a write that has been applied, so that later identical writes
+    /// are dropped
+    pub async fn commit(&self, pending_write: PendingWrite) {
+        match pending_write.0 {
+            PendingWriteKind::MaxI64(key, value) => self.max_i64.insert(key, value).await,
+            PendingWriteKind::MinI64(key, value) => self.min_i64.insert(key, value).await,
+            PendingWriteKind::ImmI64(key) => self.imm_i64.insert(key, ()).await,
+            PendingWriteKind::MaxU64(key, value) => self.max_u64.insert(key, value).await,
+            PendingWriteKind::MinU64(key, value) => self.min_u64.insert(key, value).await,
+            PendingWriteKind::ImmU64(key) => self.imm_u64.insert(key, ()).await,
+            PendingWriteKind::ImmString(key) => self.imm_string.insert(key, ()).await,
+            PendingWriteKind::NodeType(key) => self.node_type.insert(key, ()).await,
+            PendingWriteKind::Edges(fkey, rkey) => {
+                self.edges.insert(fkey, ()).await;
+                self.edges.insert(rkey, ()).await;
+            }
         }
-        Ok(match should_insert_value {
-            true => WriteDropStatus::Stored,
-            false => WriteDropStatus::Dropped,
-        })
     }
 }
 
-async fn get_or_insert_into_cache<Key, Value, T, E, Fut>(
-    cache: Cache<Key, Value>,
-    key: Key,
-    new_value: Value,
-    callback: impl FnOnce() -> Fut,
+fn should_store<Key, Value>(
+    cache: &Cache<Key, Value>,
+    key: &Key,
+    new_value: &Value,
     is_new_value_better: impl FnOnce(&Value, &Value) -> bool,
-) -> Result<WriteDropStatus, E>
+) -> bool
 where
     Key: Hash + Eq + Clone + Sync + Send + 'static,
     Value: Clone + Sync + Send + 'static,
-    Fut: Future<Output = Result<T, E>>,
-    E: std::error::Error,
 {
-    let should_insert_value = match cache.get(&key) {
+    match cache.get(key) {
         None => true,
-        Some(stored_value) => is_new_value_better(&new_value, &stored_value),
-    };
+        Some(stored_value) => is_new_value_better(new_value, &stored_value),
+    }
+}
 
-    if should_insert_value {
-        callback().await?;
-        cache.insert(key, new_value.clone()).await;
+/// Caches support invalidation closures, so that a tenant's or node's writes
+/// can be forgotten once they're deleted
+fn new_cache<Key, Value>(max_size: u64) -> Cache<Key, Value>
+where
+    Key: Hash + Eq + Clone + Sync + Send + 'static,
+    Value: Clone + Sync + Send + 'static,
+{
+    CacheBuilder::new(max_size)
+        .support_invalidation_closures()
+        .build()
+}
+
+fn edge_key(tenant_id: uuid::Uuid, source_uid: Uid, dst_uid: Uid, edge_name: &EdgeName) -> EdgeKey {
+    EdgeKey {
+        tenant_id,
+        source_uid,
+        dest_uid: dst_uid,
+        edge_name_hash: edge_name_hash(edge_name),
     }
-    Ok(match should_insert_value {
-        true => WriteDropStatus::Stored,
-        false => WriteDropStatus::Dropped,
-    })
 }
 
-fn edge_key(
-    tenant_id: uuid::Uuid,
-    source_uid: Uid,
-    dst_uid: Uid,
-    edge_name: &EdgeName,
-) -> [u8; 16] {
+fn edge_name_hash(edge_name: &EdgeName) -> [u8; 16] {
     let mut hasher = Blake2b16::new();
-    hasher.update(tenant_id.as_bytes());
-    hasher.update(source_uid.as_u64().to_le_bytes());
-    hasher.update(dst_uid.as_u64().to_le_bytes());
     hasher.update(edge_name.value.as_bytes());
     hasher.finalize().into()
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use rust_proto::graplinc::grapl::api::graph::v1beta1::{
+        DecrementOnlyIntProp,
+        DecrementOnlyUintProp,
+        ImmutableIntProp,
+        ImmutableStrProp,
+        ImmutableUintProp,
+        IncrementOnlyIntProp,
+        IncrementOnlyUintProp,
+    };
 
     use super::*;
 
-    #[derive(Debug, thiserror::Error)]
-    enum CallbackError {}
+    /// Commits `pending_write`, as a successful write would, returning whether
+    /// there was a write to store
+    async fn store(write_dropper: &WriteDropper, pending_write: Option<PendingWrite>) -> bool {
+        match pending_write {
+            Some(pending_write) => {
+                write_dropper.commit(pending_write).await;
+                true
+            }
+            None => false,
+        }
+    }
 
     #[tokio::test]
     async fn test_every_cache_drops_when_expected() -> eyre::Result<()> {
@@ -339,252 +394,356 @@ mod tests {
         let property_name = PropertyName {
             value: "arbitrary_prop_name".to_string(),
         };
-
-        let callback = || async {
-            let res: Result<(), CallbackError> = Ok(());
-            res
+        let uid = Uid::from_u64(123).unwrap();
+        let other_uid = Uid::from_u64(456).unwrap();
+        let write_dropper = WriteDropper::new(8);
+
+        let pending = |uid, property: Property| {
+            write_dropper.pending_property(
+                tenant_id,
+                uid,
+                node_type.clone(),
+                property_name.clone(),
+                &property,
+            )
         };
-        let write_dropper = Arc::new(WriteDropper::new(3));
-
-        // ##### check_max_i64 #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let nt = node_type.clone();
-            let pn = property_name.clone();
-            let check = move |value| {
-                let write_dropper = Arc::clone(&write_dropper);
-                let nt = nt.clone();
-                let pn = pn.clone();
-                async move {
-                    write_dropper
-                        .check_max_i64(tenant_id, nt, pn, value, callback)
-                        .await
-                }
-            };
-
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "same value, drop it");
-            let status = check(-3).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "lesser value, drop it");
-            let status = check(4).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "greater value, store it");
-        }
 
-        // ##### check_min_i64 #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let nt = node_type.clone();
-            let pn = property_name.clone();
-            let check = move |value| {
-                let write_dropper = Arc::clone(&write_dropper);
-                let nt = nt.clone();
-                let pn = pn.clone();
-                async move {
-                    write_dropper
-                        .check_min_i64(tenant_id, nt, pn, value, callback)
-                        .await
-                }
-            };
-
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "same value, drop it");
-            let status = check(4).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "greater value, drop it");
-            let status = check(-3).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "lesser value, store it");
-        }
-
-        // ##### check_imm_i64 #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let nt = node_type.clone();
-            let pn = property_name.clone();
-            let check = move || {
-                let write_dropper = Arc::clone(&write_dropper);
-                let nt = nt.clone();
-                let pn = pn.clone();
-                async move {
-                    write_dropper
-                        .check_imm_i64(tenant_id, nt, pn, callback)
-                        .await
-                }
-            };
-
-            let status = check().await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = check().await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "immutable");
-        }
+        // ##### max i64 #####
+        let max_i64 = |prop| {
+            pending(
+                uid,
+                Property::IncrementOnlyIntProp(IncrementOnlyIntProp { prop }),
+            )
+        };
+        eyre::ensure!(
+            store(&write_dropper, max_i64(3)).await,
+            "initial always stores"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, max_i64(3)).await,
+            "same value, drop it"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, max_i64(-3)).await,
+            "lesser value, drop it"
+        );
+        eyre::ensure!(
+            store(&write_dropper, max_i64(4)).await,
+            "greater value, store it"
+        );
+
+        // ##### min i64 #####
+        let min_i64 = |prop| {
+            pending(
+                uid,
+                Property::DecrementOnlyIntProp(DecrementOnlyIntProp { prop }),
+            )
+        };
+        eyre::ensure!(
+            store(&write_dropper, min_i64(3)).await,
+            "initial always stores"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, min_i64(3)).await,
+            "same value, drop it"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, min_i64(4)).await,
+            "greater value, drop it"
+        );
+        eyre::ensure!(
+            store(&write_dropper, min_i64(-3)).await,
+            "lesser value, store it"
+        );
+
+        // ##### immutable i64 #####
+        let imm_i64 = |prop| pending(uid, Property::ImmutableIntProp(ImmutableIntProp { prop }));
+        eyre::ensure!(
+            store(&write_dropper, imm_i64(3)).await,
+            "initial always stores"
+        );
+        eyre::ensure!(!store(&write_dropper, imm_i64(4)).await, "immutable");
+
+        // ##### max u64 #####
+        let max_u64 = |prop| {
+            pending(
+                uid,
+                Property::IncrementOnlyUintProp(IncrementOnlyUintProp { prop }),
+            )
+        };
+        eyre::ensure!(
+            store(&write_dropper, max_u64(3)).await,
+            "initial always stores"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, max_u64(3)).await,
+            "same value, drop it"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, max_u64(2)).await,
+            "lesser value, drop it"
+        );
+        eyre::ensure!(
+            store(&write_dropper, max_u64(4)).await,
+            "greater value, store it"
+        );
+
+        // ##### min u64 #####
+        let min_u64 = |prop| {
+            pending(
+                uid,
+                Property::DecrementOnlyUintProp(DecrementOnlyUintProp { prop }),
+            )
+        };
+        eyre::ensure!(
+            store(&write_dropper, min_u64(3)).await,
+            "initial always stores"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, min_u64(3)).await,
+            "same value, drop it"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, min_u64(4)).await,
+            "greater value, drop it"
+        );
+        eyre::ensure!(
+            store(&write_dropper, min_u64(2)).await,
+            "lesser value, store it"
+        );
+
+        // ##### immutable u64 #####
+        let imm_u64 = |prop| pending(uid, Property::ImmutableUintProp(ImmutableUintProp { prop }));
+        eyre::ensure!(
+            store(&write_dropper, imm_u64(3)).await,
+            "initial always stores"
+        );
+        eyre::ensure!(!store(&write_dropper, imm_u64(4)).await, "immutable");
+
+        // ##### immutable string #####
+        let imm_string = |uid, prop: &str| {
+            pending(
+                uid,
+                Property::ImmutableStrProp(ImmutableStrProp {
+                    prop: prop.to_owned(),
+                }),
+            )
+        };
+        eyre::ensure!(
+            store(&write_dropper, imm_string(uid, "a")).await,
+            "initial always stores"
+        );
+        eyre::ensure!(
+            !store(&write_dropper, imm_string(uid, "b")).await,
+            "immutable"
+        );
+
+        // ##### properties of different nodes don't drop each other #####
+        eyre::ensure!(
+            store(&write_dropper, imm_string(other_uid, "a")).await,
+            "different uid, store it"
+        );
+
+        // ##### node type #####
+        let node_type = || write_dropper.pending_node_type(tenant_id, uid);
+        eyre::ensure!(
+            store(&write_dropper, node_type()).await,
+            "initial always stores"
+        );
+        eyre::ensure!(!store(&write_dropper, node_type()).await, "immutable");
+
+        // ##### edges #####
+        let f_edge_name = EdgeName::try_from("a_to_b")?;
+        let r_edge_name = EdgeName::try_from("b_to_a")?;
+        let edges = |source_uid, dest_uid, f_edge_name, r_edge_name| {
+            write_dropper.pending_edges(tenant_id, source_uid, dest_uid, f_edge_name, r_edge_name)
+        };
+        eyre::ensure!(
+            store(
+                &write_dropper,
+                edges(uid, other_uid, &f_edge_name, &r_edge_name)
+            )
+            .await,
+            "initial always stores"
+        );
+        eyre::ensure!(
+            !store(
+                &write_dropper,
+                edges(uid, other_uid, &f_edge_name, &r_edge_name)
+            )
+            .await,
+            "forward is already stored"
+        );
+        eyre::ensure!(
+            !store(
+                &write_dropper,
+                edges(other_uid, uid, &r_edge_name, &f_edge_name)
+            )
+            .await,
+            "reverse is already stored"
+        );
 
-        // ##### check_max_u64 #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let nt = node_type.clone();
-            let pn = property_name.clone();
-            let check = move |value| {
-                let write_dropper = Arc::clone(&write_dropper);
-                let nt = nt.clone();
-                let pn = pn.clone();
-                async move {
-                    write_dropper
-                        .check_max_u64(tenant_id, nt, pn, value, callback)
-                        .await
-                }
-            };
-
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "same value, drop it");
-            let status = check(2).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "lesser value, drop it");
-            let status = check(4).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "greater value, store it");
-        }
+        Ok(())
+    }
 
-        // ##### check_min_u64 #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let nt = node_type.clone();
-            let pn = property_name.clone();
-            let check = move |value| {
-                let write_dropper = Arc::clone(&write_dropper);
-                let nt = nt.clone();
-                let pn = pn.clone();
-                async move {
-                    write_dropper
-                        .check_min_u64(tenant_id, nt, pn, value, callback)
-                        .await
-                }
-            };
-
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = check(3).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "same value, drop it");
-            let status = check(4).await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "greater value, drop it");
-            let status = check(2).await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "lesser value, store it");
-        }
+    #[tokio::test]
+    async fn test_pending_writes_drop_only_once_committed() -> eyre::Result<()> {
+        let tenant_id = uuid::Uuid::new_v4();
+        let node_type = NodeType {
+            value: "arbitrary_node_type".to_string(),
+        };
+        let property_name = PropertyName {
+            value: "arbitrary_prop_name".to_string(),
+        };
+        let uid = Uid::from_u64(123).unwrap();
+        let write_dropper = WriteDropper::new(8);
+
+        let pending = |value| {
+            write_dropper.pending_property(
+                tenant_id,
+                uid,
+                node_type.clone(),
+                property_name.clone(),
+                &Property::IncrementOnlyUintProp(IncrementOnlyUintProp { prop: value }),
+            )
+        };
 
-        // ##### check_imm_u64 #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let nt = node_type.clone();
-            let pn = property_name.clone();
-            let check = move || {
-                let write_dropper = Arc::clone(&write_dropper);
-                let nt = nt.clone();
-                let pn = pn.clone();
-                async move {
-                    write_dropper
-                        .check_imm_u64(tenant_id, nt, pn, callback)
-                        .await
-                }
-            };
+        let pending_write = pending(3);
+        eyre::ensure!(pending_write.is_some(), "initial always stores");
+        eyre::ensure!(pending(3).is_some(), "uncommitted, store it again");
+        write_dropper.commit(pending_write.unwrap()).await;
+        eyre::ensure!(pending(3).is_none(), "same value, drop it");
+        eyre::ensure!(pending(2).is_none(), "lesser value, drop it");
+        eyre::ensure!(pending(4).is_some(), "greater value, store it");
+
+        let f_edge_name = EdgeName::try_from("a_to_b")?;
+        let r_edge_name = EdgeName::try_from("b_to_a")?;
+        let other_uid = Uid::from_u64(456).unwrap();
+        let pending_write = write_dropper
+            .pending_edges(tenant_id, uid, other_uid, &f_edge_name, &r_edge_name)
+            .ok_or_else(|| eyre::eyre!("initial always stores"))?;
+        write_dropper.commit(pending_write).await;
+        eyre::ensure!(
+            write_dropper
+                .pending_edges(tenant_id, other_uid, uid, &r_edge_name, &f_edge_name)
+                .is_none(),
+            "reverse is already stored"
+        );
 
-            let status = check().await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = check().await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "immutable");
-        }
+        Ok(())
+    }
 
-        // ##### check_imm_string #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let nt = node_type.clone();
-            let pn = property_name.clone();
-            let check = move || {
-                let write_dropper = Arc::clone(&write_dropper);
-                let nt = nt.clone();
-                let pn = pn.clone();
-                async move {
-                    write_dropper
-                        .check_imm_string(tenant_id, nt, pn, callback)
-                        .await
+    #[tokio::test]
+    async fn test_invalidated_writes_are_stored_again() -> eyre::Result<()> {
+        let tenant_id = uuid::Uuid::new_v4();
+        let other_tenant_id = uuid::Uuid::new_v4();
+        let node_type = NodeType {
+            value: "arbitrary_node_type".to_string(),
+        };
+        let property_name = PropertyName {
+            value: "arbitrary_prop_name".to_string(),
+        };
+        let uid = Uid::from_u64(123).unwrap();
+        let other_uid = Uid::from_u64(456).unwrap();
+        let f_edge_name = EdgeName::try_from("a_to_b")?;
+        let r_edge_name = EdgeName::try_from("b_to_a")?;
+        let write_dropper = WriteDropper::new(16);
+
+        let pending_property = |tenant_id, uid| {
+            write_dropper.pending_property(
+                tenant_id,
+                uid,
+                node_type.clone(),
+                property_name.clone(),
+                &Property::IncrementOnlyUintProp(IncrementOnlyUintProp { prop: 3 }),
+            )
+        };
+        let pending_edges = |tenant_id| {
+            write_dropper.pending_edges(tenant_id, uid, other_uid, &f_edge_name, &r_edge_name)
+        };
+        let pending_edge_property = |tenant_id| {
+            write_dropper.pending_edge_property(
+                tenant_id,
+                uid,
+                other_uid,
+                &f_edge_name,
+                property_name.clone(),
+                &Property::IncrementOnlyUintProp(IncrementOnlyUintProp { prop: 3 }),
+            )
+        };
+        let pending_everything = || {
+            let mut pending_writes = Vec::new();
+            for tenant_id in [tenant_id, other_tenant_id] {
+                for uid in [uid, other_uid] {
+                    pending_writes.extend(pending_property(tenant_id, uid));
                 }
-            };
+                pending_writes.extend(pending_edges(tenant_id));
+                pending_writes.extend(pending_edge_property(tenant_id));
+            }
+            pending_writes
+        };
 
-            let status = check().await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = check().await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "immutable");
+        for pending_write in pending_everything() {
+            write_dropper.commit(pending_write).await;
         }
-
-        // ##### check_node_type #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let uid = Uid::from_u64(123).unwrap();
-
-            let status = write_dropper
-                .check_node_type(tenant_id, uid, callback)
-                .await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = write_dropper
-                .check_node_type(tenant_id, uid, callback)
-                .await?;
-            eyre::ensure!(status == WriteDropStatus::Dropped, "immutable");
+        write_dropper.invalidate_node(tenant_id, other_uid)?;
+        eyre::ensure!(
+            pending_property(tenant_id, other_uid).is_some(),
+            "deleted node, store it"
+        );
+        eyre::ensure!(
+            pending_edges(tenant_id).is_some(),
+            "edge to deleted node, store it"
+        );
+        eyre::ensure!(
+            pending_edge_property(tenant_id).is_some(),
+            "property of edge to deleted node, store it"
+        );
+        eyre::ensure!(
+            pending_property(tenant_id, uid).is_none(),
+            "other node, drop it"
+        );
+        eyre::ensure!(
+            pending_edges(other_tenant_id).is_none(),
+            "other tenant, drop it"
+        );
+
+        for pending_write in pending_everything() {
+            write_dropper.commit(pending_write).await;
         }
-
-        // ##### check_edge #####
-        {
-            let write_dropper = Arc::clone(&write_dropper);
-            let source_uid = Uid::from_u64(123).unwrap();
-            let dest_uid = Uid::from_u64(456).unwrap();
-            let f_edge_name = EdgeName::try_from("a_to_b")?;
-            let r_edge_name = EdgeName::try_from("b_to_a")?;
-
-            let edge_names_callback = |_, _| async {
-                let res: Result<(), CallbackError> = Ok(());
-                res
-            };
-
-            let status = write_dropper
-                .check_edges(
-                    tenant_id,
-                    source_uid,
-                    dest_uid,
-                    f_edge_name.clone(),
-                    r_edge_name.clone(),
-                    edge_names_callback,
-                )
-                .await?;
-            eyre::ensure!(status == WriteDropStatus::Stored, "initial always stores");
-            let status = write_dropper
-                .check_edges(
-                    tenant_id,
-                    source_uid,
-                    dest_uid,
-                    f_edge_name.clone(),
-                    r_edge_name.clone(),
-                    edge_names_callback,
-                )
-                .await?;
-            eyre::ensure!(
-                status == WriteDropStatus::Dropped,
-                "forward is already stored"
-            );
-            // Do the other direction
-            let status = write_dropper
-                .check_edges(
-                    tenant_id,
-                    dest_uid,
-                    source_uid,
-                    r_edge_name.clone(),
-                    f_edge_name.clone(),
-                    edge_names_callback,
-                )
-                .await?;
-            eyre::ensure!(
-                status == WriteDropStatus::Dropped,
-                "reverse is already stored"
-            );
+        write_dropper
+            .invalidate_edges(tenant_id, other_uid, uid, &r_edge_name, &f_edge_name)
+            .await?;
+        eyre::ensure!(
+            pending_edges(tenant_id).is_some(),
+            "deleted reverse, store it"
+        );
+        eyre::ensure!(
+            pending_edge_property(tenant_id).is_some(),
+            "property of deleted reverse, store it"
+        );
+        eyre::ensure!(
+            pending_property(tenant_id, uid).is_none(),
+            "property, drop it"
+        );
+
+        for pending_write in pending_everything() {
+            write_dropper.commit(pending_write).await;
         }
+        write_dropper.invalidate_tenant(tenant_id)?;
+        eyre::ensure!(
+            pending_property(tenant_id, uid).is_some(),
+            "purged, store it"
+        );
+        eyre::ensure!(pending_edges(tenant_id).is_some(), "purged, store it");
+        eyre::ensure!(
+            pending_edge_property(tenant_id).is_some(),
+            "purged, store it"
+        );
+        eyre::ensure!(
+            pending_property(other_tenant_id, uid).is_none(),
+            "other tenant, drop it"
+        );
 
         Ok(())
     }
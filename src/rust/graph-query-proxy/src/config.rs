@@ -8,4 +8,8 @@ pub struct GraphQueryProxyConfig {
     #[clap(long, env)]
     /// The address to bind the graph query service to
     pub graph_query_proxy_bind_address: SocketAddr,
+    /// How long a streaming request to the graph query service may take,
+    /// including consuming all of its responses
+    #[clap(long, env, default_value = "60000")]
+    pub graph_query_streaming_request_timeout_ms: u64,
 }
@@ -34,6 +34,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let graph_query_service = GraphQueryProxy::new(
         config.tenant_id,
         GraphQueryClient::connect(graph_query_client_config).await?,
+        std::time::Duration::from_millis(config.graph_query_streaming_request_timeout_ms),
     );
 
     exec_service(config, graph_query_service).await
@@ -1,3 +1,10 @@
+use std::time::Duration;
+
+use futures::{
+    stream::BoxStream,
+    StreamExt,
+    TryStreamExt,
+};
 use rust_proto::graplinc::grapl::api::{
     client::ClientError,
     graph_query::v1beta1::{
@@ -6,6 +13,8 @@ use rust_proto::graplinc::grapl::api::{
     },
     graph_query_proxy::v1beta1::{
         messages::{
+            QueryAllGraphsWithUidRequest,
+            QueryAllGraphsWithUidResponse,
             QueryGraphFromUidRequest,
             QueryGraphFromUidResponse,
             QueryGraphWithUidRequest,
@@ -34,13 +43,19 @@ impl From<GraphQueryProxyError> for Status {
 pub struct GraphQueryProxy {
     tenant_id: uuid::Uuid,
     graph_query_client: GraphQueryClient,
+    streaming_request_timeout: Duration,
 }
 
 impl GraphQueryProxy {
-    pub fn new(tenant_id: uuid::Uuid, graph_query_client: GraphQueryClient) -> Self {
+    pub fn new(
+        tenant_id: uuid::Uuid,
+        graph_query_client: GraphQueryClient,
+        streaming_request_timeout: Duration,
+    ) -> Self {
         Self {
             tenant_id,
             graph_query_client,
+            streaming_request_timeout,
         }
     }
 }
@@ -58,6 +73,7 @@ impl GraphQueryProxyApi for GraphQueryProxy {
             tenant_id: self.tenant_id,
             graph_query: request.graph_query,
             node_uid: request.node_uid,
+            profile: request.profile,
         };
         let mut graph_query_client = self.graph_query_client.clone();
         Ok(graph_query_client
@@ -75,6 +91,7 @@ impl GraphQueryProxyApi for GraphQueryProxy {
             tenant_id: self.tenant_id,
             graph_query: request.graph_query,
             node_uid: request.node_uid,
+            profile: request.profile,
         };
         let mut graph_query_client = self.graph_query_client.clone();
         Ok(graph_query_client
@@ -82,4 +99,28 @@ impl GraphQueryProxyApi for GraphQueryProxy {
             .await?
             .into())
     }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn query_all_graphs_with_uid(
+        &self,
+        request: QueryAllGraphsWithUidRequest,
+    ) -> Result<
+        BoxStream<'static, Result<QueryAllGraphsWithUidResponse, GraphQueryProxyError>>,
+        GraphQueryProxyError,
+    > {
+        let request = non_proxy_messages::QueryAllGraphsWithUidRequest {
+            tenant_id: self.tenant_id,
+            graph_query: request.graph_query,
+            node_uid: request.node_uid,
+            limit: request.limit,
+            after_root_uid: request.after_root_uid,
+        };
+        let mut graph_query_client = self.graph_query_client.clone();
+        Ok(graph_query_client
+            .query_all_graphs_with_uid(self.streaming_request_timeout, request)
+            .await?
+            .map_ok(Into::into)
+            .map_err(Into::into)
+            .boxed())
+    }
 }
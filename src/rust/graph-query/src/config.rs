@@ -15,6 +15,51 @@ pub struct GraphDbConfig {
     pub graph_db_auth_password: secrecy::SecretString,
 }
 
+#[derive(clap::Parser, Debug, Clone)]
+pub struct PropertyCacheConfig {
+    #[clap(long, env, default_value = "100000")]
+    /// Maximum number of entries kept in each property and edge cache
+    pub graph_query_property_cache_max_entries: u64,
+    #[clap(long, env, default_value = "600000")]
+    /// How long immutable properties are cached for
+    pub graph_query_property_cache_ttl_ms: u64,
+    #[clap(long, env, default_value = "5000")]
+    /// How long edge lists are cached for. New edges may not be visible
+    /// to queries until this has elapsed.
+    pub graph_query_edge_cache_ttl_ms: u64,
+    #[clap(long, env, default_value = "1000")]
+    /// How long properties and edges that were not found are cached for
+    pub graph_query_negative_cache_ttl_ms: u64,
+    #[clap(long, env, default_value = "1000")]
+    /// How often each tenant's graph is checked for deletions. Deleted
+    /// properties and edges may still be returned until this has elapsed.
+    pub graph_query_deletion_check_interval_ms: u64,
+}
+
+#[derive(clap::Parser, Debug, Clone)]
+pub struct DeployedSchemaCacheConfig {
+    #[clap(long, env, default_value = "10000")]
+    /// Maximum number of node types' deployed schemas kept in memory
+    pub graph_query_deployed_schema_cache_max_entries: u64,
+    #[clap(long, env, default_value = "60000")]
+    /// How long a node type's deployed schema is cached for, so that newly
+    /// deployed schemas are picked up
+    pub graph_query_deployed_schema_cache_ttl_ms: u64,
+}
+
+#[derive(clap::Parser, Debug, Clone)]
+pub struct QueryBudgetConfig {
+    #[clap(long, env, default_value = "10000")]
+    /// Maximum number of database reads a single query may make
+    pub graph_query_max_reads: u64,
+    #[clap(long, env, default_value = "10000")]
+    /// Maximum number of nodes a single query may visit
+    pub graph_query_max_visited_nodes: u64,
+    #[clap(long, env, default_value = "30000")]
+    /// How long a single query may run for
+    pub graph_query_timeout_ms: u64,
+}
+
 #[derive(clap::Parser, Debug, Clone)]
 pub struct GraphQueryServiceConfig {
     #[clap(env)]
@@ -23,4 +68,13 @@ pub struct GraphQueryServiceConfig {
 
     #[clap(flatten)]
     pub graph_db_config: GraphDbConfig,
+
+    #[clap(flatten)]
+    pub property_cache_config: PropertyCacheConfig,
+
+    #[clap(flatten)]
+    pub deployed_schema_cache_config: DeployedSchemaCacheConfig,
+
+    #[clap(flatten)]
+    pub query_budget_config: QueryBudgetConfig,
 }
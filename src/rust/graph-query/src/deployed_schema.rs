@@ -0,0 +1,186 @@
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::Duration,
+};
+
+use moka::future::{
+    Cache,
+    CacheBuilder,
+};
+use rust_proto::graplinc::grapl::{
+    api::{
+        client::ClientError,
+        graph_schema_manager::v1beta1::{
+            client::GraphSchemaManagerClient,
+            messages::{
+                GetNodeSchemaRequest,
+                IdentityAlgorithm,
+                NodeSchema,
+                PropertyType,
+                SessionIdentity,
+            },
+        },
+        protocol::status::{
+            Code,
+            Status,
+        },
+    },
+    common::v1beta1::types::{
+        NodeType,
+        PropertyName,
+    },
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum DeployedSchemaError {
+    #[error("gRPC client error: {0}")]
+    ClientError(#[from] ClientError),
+    #[error("{node_type} is not a session-identified node type in the deployed schema, so it has no session to match a time window against")]
+    NotSessionIdentified { node_type: NodeType },
+}
+
+/// The properties holding a session-identified node type's timestamps, as
+/// declared by the `@create_time`, `@last_seen_time` and `@terminate_time`
+/// fields of its schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionTimestampProperties {
+    pub create_time: PropertyName,
+    pub last_seen_time: PropertyName,
+    pub terminate_time: PropertyName,
+}
+
+impl From<SessionIdentity> for SessionTimestampProperties {
+    fn from(session_identity: SessionIdentity) -> Self {
+        Self {
+            create_time: session_identity.creation_timestamp_property,
+            last_seen_time: session_identity.last_seen_timestamp_property,
+            terminate_time: session_identity.termination_timestamp_property,
+        }
+    }
+}
+
+/// What queries need to know about a node type from its deployed schema
+#[derive(Debug, Clone, Default)]
+struct DeployedNodeSchema {
+    /// None unless the node type is session-identified
+    session_properties: Option<SessionTimestampProperties>,
+    /// The int properties which can be looked up in the immutable int index
+    immutable_int_properties: HashSet<PropertyName>,
+}
+
+impl From<NodeSchema> for DeployedNodeSchema {
+    fn from(node_schema: NodeSchema) -> Self {
+        let session_properties = match node_schema.identity_algorithm {
+            IdentityAlgorithm::Session(session_identity) => {
+                Some(SessionTimestampProperties::from(session_identity))
+            }
+            IdentityAlgorithm::Static(_) => None,
+        };
+        let immutable_int_properties = node_schema
+            .properties
+            .into_iter()
+            .filter(|property| {
+                matches!(
+                    property.property_type,
+                    PropertyType::ImmutableI64 | PropertyType::ImmutableU64
+                )
+            })
+            .map(|property| property.property_name)
+            .collect();
+        Self {
+            session_properties,
+            immutable_int_properties,
+        }
+    }
+}
+
+/// Resolves where a node type's session timestamps are stored, and which of
+/// its int properties are immutable, from the tenant's deployed schema.
+/// They're only cached for `time_to_live`, so that newly deployed node types
+/// are picked up.
+#[derive(Clone)]
+pub struct DeployedSchemaResolver {
+    schema_client: GraphSchemaManagerClient,
+    schema_cache: Cache<(uuid::Uuid, NodeType), Arc<DeployedNodeSchema>>,
+}
+
+impl DeployedSchemaResolver {
+    pub fn new(
+        schema_client: GraphSchemaManagerClient,
+        cache_size: u64,
+        time_to_live: Duration,
+    ) -> Self {
+        let schema_cache = CacheBuilder::new(cache_size)
+            .time_to_live(time_to_live)
+            .build();
+        Self {
+            schema_client,
+            schema_cache,
+        }
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn resolve_session_properties(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type: &NodeType,
+    ) -> Result<SessionTimestampProperties, DeployedSchemaError> {
+        let deployed_schema = self.resolve_deployed_schema(tenant_id, node_type).await?;
+        deployed_schema.session_properties.clone().ok_or_else(|| {
+            DeployedSchemaError::NotSessionIdentified {
+                node_type: node_type.clone(),
+            }
+        })
+    }
+
+    /// Whether `property_name` is an immutable int property of `node_type`.
+    /// Only those are indexed, since a max or min property's value changes.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn is_immutable_int_property(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type: &NodeType,
+        property_name: &PropertyName,
+    ) -> Result<bool, DeployedSchemaError> {
+        let deployed_schema = self.resolve_deployed_schema(tenant_id, node_type).await?;
+        Ok(deployed_schema
+            .immutable_int_properties
+            .contains(property_name))
+    }
+
+    /// A node type that isn't deployed has no properties and isn't
+    /// session-identified
+    async fn resolve_deployed_schema(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type: &NodeType,
+    ) -> Result<Arc<DeployedNodeSchema>, DeployedSchemaError> {
+        let cache = &self.schema_cache;
+        let key = (tenant_id, node_type.clone());
+
+        if let Some(deployed_schema) = cache.get(&key) {
+            return Ok(deployed_schema);
+        }
+
+        let mut schema_client = self.schema_client.clone();
+        let deployed_schema = match schema_client
+            .get_node_schema(GetNodeSchemaRequest {
+                tenant_id,
+                node_type: node_type.clone(),
+                schema_version: None,
+            })
+            .await
+        {
+            Ok(response) => DeployedNodeSchema::from(response.node_schema),
+            Err(ClientError::Status(Status {
+                code: Code::NotFound,
+                ..
+            })) => DeployedNodeSchema::default(),
+            Err(e) => return Err(e.into()),
+        };
+        let deployed_schema = Arc::new(deployed_schema);
+        cache.insert(key, deployed_schema.clone()).await;
+        Ok(deployed_schema)
+    }
+}
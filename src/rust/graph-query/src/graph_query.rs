@@ -1,19 +1,51 @@
-use futures::future::join_all;
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+        HashSet,
+    },
+    sync::Arc,
+};
+
+use futures::{
+    future::{
+        join_all,
+        try_join_all,
+    },
+    Stream,
+    StreamExt,
+    TryStreamExt,
+};
 pub use rust_proto::graplinc::grapl::api::graph_query::v1beta1::messages::StringCmp;
 use rust_proto::graplinc::grapl::{
     api::graph_query::v1beta1::messages::{
         GraphQuery,
         GraphView,
+        IntOperation,
+        NodePropertyQuery,
+        StringOperation,
+    },
+    common::v1beta1::types::{
+        PropertyName,
+        Uid,
     },
-    common::v1beta1::types::Uid,
 };
 
 use crate::{
+    deployed_schema::{
+        DeployedSchemaError,
+        DeployedSchemaResolver,
+    },
     node_query::{
         fetch_node_with_edges,
         NodeQueryError,
     },
-    property_query::PropertyQueryExecutor,
+    property_query::{
+        is_int_range_indexable,
+        PropertyQueryError,
+        PropertyQueryExecutor,
+    },
+    query_budget::QueryBudgetExceeded,
     short_circuit::ShortCircuit,
     visited::Visited,
 };
@@ -22,8 +54,42 @@ use crate::{
 pub enum GraphQueryError {
     #[error("Node query failed (uid: '{uid:?}'): {source}")]
     NodeQueryError { uid: Uid, source: NodeQueryError },
+    #[error(
+        "No node query has a predicate that can be looked up in an index: \
+        a string equality or a bounded range of an immutable int"
+    )]
+    NoIndexedPredicate,
+    #[error("Index lookup failed: {0}")]
+    IndexLookupError(#[from] PropertyQueryError),
+    #[error("Schema lookup failed: {0}")]
+    SchemaLookupError(#[from] DeployedSchemaError),
 }
 
+impl GraphQueryError {
+    /// The budget this query exceeded, if that's why it failed
+    pub fn budget_exceeded(&self) -> Option<&QueryBudgetExceeded> {
+        match self {
+            Self::NodeQueryError { source, .. } => source.budget_exceeded(),
+            Self::IndexLookupError(PropertyQueryError::BudgetExceeded(e)) => Some(e),
+            Self::NoIndexedPredicate | Self::IndexLookupError(_) | Self::SchemaLookupError(_) => {
+                None
+            }
+        }
+    }
+
+    /// Whether the query itself is invalid, rather than failing to run
+    pub fn is_invalid_query(&self) -> bool {
+        match self {
+            Self::NodeQueryError { source, .. } => source.is_invalid_query(),
+            Self::NoIndexedPredicate => true,
+            Self::IndexLookupError(_) | Self::SchemaLookupError(_) => false,
+        }
+    }
+}
+
+/// The number of entry points of an indexed query that are matched at once
+const INDEXED_ENTRY_POINT_CONCURRENCY: usize = 8;
+
 #[tracing::instrument(skip(graph_query, property_query_executor))]
 pub async fn query_graph(
     graph_query: &GraphQuery,
@@ -33,7 +99,13 @@ pub async fn query_graph(
 ) -> Result<Option<(GraphView, Uid)>, GraphQueryError> {
     let mut query_handles = Vec::with_capacity(graph_query.node_property_queries.len());
     let x_query_short_circuiter = ShortCircuit::new();
+    // A match never contains a node for a negated query, so there's no point
+    // starting from one
+    let negated_query_ids = graph_query.negated_query_ids();
     for node_query in graph_query.node_property_queries.values() {
+        if negated_query_ids.contains(&node_query.query_id) {
+            continue;
+        }
         let property_query_executor = property_query_executor.clone();
         let node_query = node_query.clone();
         let x_query_short_circuiter = x_query_short_circuiter.clone();
@@ -84,3 +156,424 @@ pub async fn query_graph(
     }
     Ok(None)
 }
+
+/// Collects the uids of the nodes which may be the root of a match of
+/// `graph_query` that `uid` takes part in, deduplicated and in ascending
+/// order. Unlike `query_graph`, every entry point is explored to completion
+/// rather than stopping at the first match.
+#[tracing::instrument(skip(graph_query, property_query_executor))]
+pub async fn find_root_candidates(
+    graph_query: &GraphQuery,
+    uid: Uid,
+    tenant_id: uuid::Uuid,
+    property_query_executor: PropertyQueryExecutor,
+) -> Result<Vec<Uid>, GraphQueryError> {
+    let root_node_type = match graph_query
+        .node_property_queries
+        .get(&graph_query.root_query_id)
+    {
+        Some(root_query) => &root_query.node_type,
+        None => return Ok(vec![]),
+    };
+
+    let negated_query_ids = graph_query.negated_query_ids();
+    let mut query_handles = Vec::with_capacity(graph_query.node_property_queries.len());
+    for node_query in graph_query.node_property_queries.values() {
+        if negated_query_ids.contains(&node_query.query_id) {
+            continue;
+        }
+        let property_query_executor = property_query_executor.clone();
+        query_handles.push(async move {
+            fetch_node_with_edges(
+                node_query,
+                graph_query,
+                uid,
+                tenant_id,
+                property_query_executor,
+                Visited::new(),
+                ShortCircuit::new(),
+                &mut None,
+            )
+            .await
+            .map_err(|e| GraphQueryError::NodeQueryError { uid, source: e })
+        });
+    }
+
+    let mut candidates = BTreeSet::new();
+    for graph in try_join_all(query_handles).await?.into_iter().flatten() {
+        candidates.extend(
+            graph
+                .nodes
+                .values()
+                .filter(|node| &node.node_type == root_node_type)
+                .map(|node| node.uid),
+        );
+    }
+    Ok(candidates.into_iter().collect())
+}
+
+/// Matches `graph_query` from each of the `candidates` as its root, in
+/// order, yielding the matches that `uid` takes part in along with their
+/// root uid. Candidates are only queried as the stream is polled.
+pub fn match_root_candidates(
+    graph_query: Arc<GraphQuery>,
+    candidates: Vec<Uid>,
+    uid: Uid,
+    tenant_id: uuid::Uuid,
+    property_query_executor: PropertyQueryExecutor,
+) -> impl Stream<Item = Result<(GraphView, Uid), GraphQueryError>> + Send + 'static {
+    futures::stream::iter(candidates)
+        .then(move |root_uid| {
+            let graph_query = graph_query.clone();
+            let property_query_executor = property_query_executor.clone();
+            async move {
+                let root_query = &graph_query.node_property_queries[&graph_query.root_query_id];
+                match fetch_node_with_edges(
+                    root_query,
+                    &graph_query,
+                    root_uid,
+                    tenant_id,
+                    property_query_executor,
+                    Visited::new(),
+                    ShortCircuit::new(),
+                    &mut None,
+                )
+                .await
+                {
+                    Ok(graph) => Ok(graph
+                        .filter(|graph| graph.nodes.contains_key(&uid))
+                        .map(|graph| (graph, root_uid))),
+                    Err(e) => Err(GraphQueryError::NodeQueryError {
+                        uid: root_uid,
+                        source: e,
+                    }),
+                }
+            }
+        })
+        .try_filter_map(futures::future::ok)
+}
+
+/// A predicate on a node query's immutable property which can be looked up
+/// in a secondary index to find the nodes it holds for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexedPredicate {
+    StringEq {
+        property_name: PropertyName,
+        value: String,
+    },
+    IntRange {
+        property_name: PropertyName,
+        min: i64,
+        max: i64,
+    },
+}
+
+impl IndexedPredicate {
+    /// A property's filters are only indexable if every match must satisfy
+    /// them, i.e. they aren't or'd with other filters or negated. Int filters
+    /// must also be on a property that `is_immutable_int`, since max and min
+    /// properties aren't indexed, and bound the range of values closely
+    /// enough that looking it up reads a handful of index partitions.
+    fn for_node_query(
+        node_query: &NodePropertyQuery,
+        is_immutable_int: impl Fn(&PropertyName) -> bool,
+    ) -> Option<Self> {
+        for (property_name, or_filters) in node_query.string_filters.iter() {
+            if let [and_filters] = or_filters.and_string_filters.as_slice() {
+                let equal_to = and_filters
+                    .string_filters
+                    .iter()
+                    .find(|filter| filter.operation == StringOperation::Equal && !filter.negated);
+                if let Some(filter) = equal_to {
+                    return Some(Self::StringEq {
+                        property_name: property_name.clone(),
+                        value: filter.value.clone(),
+                    });
+                }
+            }
+        }
+
+        for (property_name, or_filters) in node_query.int_filters.iter() {
+            if !is_immutable_int(property_name) {
+                continue;
+            }
+            let and_filters = match or_filters.and_int_filters.as_slice() {
+                [and_filters] => and_filters,
+                _ => continue,
+            };
+            if and_filters.int_filters.iter().any(|filter| filter.negated) {
+                continue;
+            }
+            let (mut min, mut max) = (i64::MIN, i64::MAX);
+            for filter in and_filters.int_filters.iter() {
+                let value = filter.value;
+                match filter.operation {
+                    IntOperation::Has => {}
+                    IntOperation::Equal => {
+                        min = min.max(value);
+                        max = max.min(value);
+                    }
+                    // An empty range is represented by min > max
+                    IntOperation::LessThan => match value.checked_sub(1) {
+                        Some(value) => max = max.min(value),
+                        None => (min, max) = (i64::MAX, i64::MIN),
+                    },
+                    IntOperation::LessThanOrEqual => max = max.min(value),
+                    IntOperation::GreaterThan => match value.checked_add(1) {
+                        Some(value) => min = min.max(value),
+                        None => (min, max) = (i64::MAX, i64::MIN),
+                    },
+                    IntOperation::GreaterThanOrEqual => min = min.max(value),
+                }
+            }
+            // A range spanning too many index partitions, e.g. one with only
+            // a `Has` filter, would be a scan of the property's whole index
+            if min <= max && !is_int_range_indexable(min, max) {
+                continue;
+            }
+            return Some(Self::IntRange {
+                property_name: property_name.clone(),
+                min,
+                max,
+            });
+        }
+
+        None
+    }
+}
+
+/// Picks the node query to start an indexed query from, along with the
+/// predicate to look its nodes up by. The root query is preferred, since
+/// every match found from it has a distinct root. Which int properties are
+/// immutable is looked up in the tenant's deployed schema.
+pub async fn find_indexed_entry_point<'a>(
+    graph_query: &'a GraphQuery,
+    tenant_id: uuid::Uuid,
+    deployed_schema_resolver: &DeployedSchemaResolver,
+) -> Result<Option<(&'a NodePropertyQuery, IndexedPredicate)>, GraphQueryError> {
+    let negated_query_ids = graph_query.negated_query_ids();
+    let root_query = graph_query
+        .node_property_queries
+        .get(&graph_query.root_query_id);
+    let node_queries = root_query
+        .into_iter()
+        .chain(
+            graph_query
+                .node_property_queries
+                .values()
+                .filter(|node_query| node_query.query_id != graph_query.root_query_id),
+        )
+        .filter(|node_query| !negated_query_ids.contains(&node_query.query_id));
+
+    for node_query in node_queries {
+        let mut immutable_int_properties = HashSet::new();
+        for property_name in node_query.int_filters.keys() {
+            if deployed_schema_resolver
+                .is_immutable_int_property(tenant_id, &node_query.node_type, property_name)
+                .await?
+            {
+                immutable_int_properties.insert(property_name);
+            }
+        }
+        let predicate = IndexedPredicate::for_node_query(node_query, |property_name| {
+            immutable_int_properties.contains(property_name)
+        });
+        if let Some(predicate) = predicate {
+            return Ok(Some((node_query, predicate)));
+        }
+    }
+    Ok(None)
+}
+
+/// Finds the matches of `graph_query` without a known uid to start from.
+/// The nodes matching an indexed predicate of one of its node queries are
+/// looked up, and the query is run from each of them in turn. Returns the
+/// distinct matches, at most one per root uid, in ascending order of root uid.
+#[tracing::instrument(skip(graph_query, property_query_executor))]
+pub async fn query_graph_from_index(
+    graph_query: &GraphQuery,
+    tenant_id: uuid::Uuid,
+    property_query_executor: PropertyQueryExecutor,
+    limit: Option<usize>,
+) -> Result<Vec<(GraphView, Uid)>, GraphQueryError> {
+    let (entry_query, predicate) = find_indexed_entry_point(
+        graph_query,
+        tenant_id,
+        property_query_executor.deployed_schema_resolver(),
+    )
+    .await?
+    .ok_or(GraphQueryError::NoIndexedPredicate)?;
+
+    let entry_uids = match &predicate {
+        IndexedPredicate::StringEq {
+            property_name,
+            value,
+        } => {
+            property_query_executor
+                .get_uids_by_immutable_string(
+                    tenant_id,
+                    &entry_query.node_type,
+                    property_name,
+                    value,
+                )
+                .await?
+        }
+        IndexedPredicate::IntRange {
+            property_name,
+            min,
+            max,
+        } if min <= max => {
+            property_query_executor
+                .get_uids_by_immutable_int(
+                    tenant_id,
+                    &entry_query.node_type,
+                    property_name,
+                    *min,
+                    *max,
+                )
+                .await?
+        }
+        IndexedPredicate::IntRange { .. } => vec![],
+    };
+
+    tracing::debug!(
+        message = "Looked up indexed entry points",
+        predicate = ?predicate,
+        count = entry_uids.len(),
+    );
+
+    let mut entry_matches = futures::stream::iter(entry_uids)
+        .map(|uid| {
+            let property_query_executor = property_query_executor.clone();
+            async move {
+                let mut root_uid = None;
+                match fetch_node_with_edges(
+                    entry_query,
+                    graph_query,
+                    uid,
+                    tenant_id,
+                    property_query_executor,
+                    Visited::new(),
+                    ShortCircuit::new(),
+                    &mut root_uid,
+                )
+                .await
+                {
+                    Ok(graph) => Ok(graph.zip(root_uid)),
+                    Err(e) => Err(GraphQueryError::NodeQueryError { uid, source: e }),
+                }
+            }
+        })
+        .buffered(INDEXED_ENTRY_POINT_CONCURRENCY);
+
+    let mut matches = BTreeMap::new();
+    while let Some(entry_match) = entry_matches.try_next().await? {
+        if let Some((graph, root_uid)) = entry_match {
+            matches.entry(root_uid).or_insert(graph);
+            if limit.map_or(false, |limit| matches.len() >= limit) {
+                break;
+            }
+        }
+    }
+
+    Ok(matches
+        .into_iter()
+        .map(|(root_uid, graph)| (graph, root_uid))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_proto::graplinc::grapl::{
+        api::graph_query::v1beta1::messages::{
+            AndIntFilters,
+            IntFilter,
+            OrIntFilters,
+        },
+        common::v1beta1::types::NodeType,
+    };
+
+    use super::*;
+
+    fn node_query_with_int_filters(int_filters: Vec<(IntOperation, i64)>) -> NodePropertyQuery {
+        let mut node_query = NodePropertyQuery::new(NodeType::try_from("Process").unwrap());
+        node_query.int_filters.insert(
+            PropertyName::try_from("process_id").unwrap(),
+            OrIntFilters {
+                and_int_filters: vec![AndIntFilters {
+                    int_filters: int_filters
+                        .into_iter()
+                        .map(|(operation, value)| IntFilter {
+                            operation,
+                            value,
+                            negated: false,
+                        })
+                        .collect(),
+                }],
+            },
+        );
+        node_query
+    }
+
+    #[test]
+    fn test_bounded_int_range_is_indexed() {
+        let node_query = node_query_with_int_filters(vec![
+            (IntOperation::GreaterThan, 10),
+            (IntOperation::LessThanOrEqual, 2_000_000),
+        ]);
+
+        assert_eq!(
+            IndexedPredicate::for_node_query(&node_query, |_| true),
+            Some(IndexedPredicate::IntRange {
+                property_name: PropertyName::try_from("process_id").unwrap(),
+                min: 11,
+                max: 2_000_000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unbounded_int_range_is_not_indexed() {
+        for int_filters in [
+            vec![(IntOperation::Has, 0)],
+            vec![(IntOperation::GreaterThan, 10)],
+            vec![(IntOperation::LessThan, 10)],
+            vec![
+                (IntOperation::GreaterThanOrEqual, 0),
+                (IntOperation::LessThan, i64::MAX),
+            ],
+        ] {
+            let node_query = node_query_with_int_filters(int_filters.clone());
+            assert_eq!(
+                IndexedPredicate::for_node_query(&node_query, |_| true),
+                None,
+                "{int_filters:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_int_range_is_indexed() {
+        // Nothing can match, which the lookup answers without reading
+        let node_query = node_query_with_int_filters(vec![
+            (IntOperation::GreaterThan, 10),
+            (IntOperation::LessThan, 5),
+        ]);
+
+        assert!(matches!(
+            IndexedPredicate::for_node_query(&node_query, |_| true),
+            Some(IndexedPredicate::IntRange { min, max, .. }) if min > max
+        ));
+    }
+
+    #[test]
+    fn test_mutable_int_is_not_indexed() {
+        // Max and min properties have no index entries to look up
+        let node_query = node_query_with_int_filters(vec![(IntOperation::Equal, 10)]);
+
+        assert_eq!(
+            IndexedPredicate::for_node_query(&node_query, |_| false),
+            None
+        );
+    }
+}
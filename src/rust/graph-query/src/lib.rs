@@ -1,7 +1,12 @@
 pub mod config;
+pub mod deployed_schema;
 pub mod graph_query;
+pub mod neighborhood;
 pub mod node_query;
+pub mod property_cache;
 pub mod property_query;
+pub mod query_budget;
+pub mod query_profile;
 pub mod server;
 pub mod short_circuit;
 pub mod table_names;
@@ -4,13 +4,22 @@ use std::{
 };
 
 use clap::Parser;
+use figment::{
+    providers::Env,
+    Figment,
+};
 use graph_query::{
     config,
+    deployed_schema::DeployedSchemaResolver,
+    property_cache::PropertyCache,
     server,
 };
+use grapl_metrics::setup_metrics::setup_metrics;
 use grapl_tracing::setup_tracing;
 use rust_proto::graplinc::grapl::api::{
+    client::Connect,
     graph_query::v1beta1::server::GraphQueryServiceServer,
+    graph_schema_manager::v1beta1::client::GraphSchemaManagerClient,
     protocol::healthcheck::HealthcheckStatus,
 };
 use scylla::CachingSession;
@@ -25,6 +34,7 @@ const SERVICE_NAME: &'static str = "graph-query";
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _guard = setup_tracing(SERVICE_NAME)?;
+    let _metrics = setup_metrics()?;
     let config = GraphQueryServiceConfig::parse();
     let mut scylla_config = scylla::SessionConfig::new();
     scylla_config.add_known_nodes_addr(&config.graph_db_config.graph_db_addresses[..]);
@@ -41,7 +51,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         scylla::Session::connect(scylla_config).await?,
         10_000,
     ));
-    let graph_query_service = GraphQueryService::new(scylla_client);
+    let property_cache = PropertyCache::new(&config.property_cache_config);
+
+    let graph_schema_manager_client_config = Figment::new()
+        .merge(Env::prefixed("GRAPH_SCHEMA_MANAGER_CLIENT_"))
+        .extract()?;
+    let graph_schema_manager_client =
+        GraphSchemaManagerClient::connect(graph_schema_manager_client_config).await?;
+    let deployed_schema_cache_config = &config.deployed_schema_cache_config;
+    let deployed_schema_resolver = DeployedSchemaResolver::new(
+        graph_schema_manager_client,
+        deployed_schema_cache_config.graph_query_deployed_schema_cache_max_entries,
+        Duration::from_millis(
+            deployed_schema_cache_config.graph_query_deployed_schema_cache_ttl_ms,
+        ),
+    );
+
+    let graph_query_service = GraphQueryService::new(
+        scylla_client,
+        property_cache,
+        deployed_schema_resolver,
+        config.query_budget_config.clone(),
+    );
 
     exec_service(config, graph_query_service).await
 }
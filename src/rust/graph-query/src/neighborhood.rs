@@ -0,0 +1,248 @@
+use futures::{
+    StreamExt,
+    TryStreamExt,
+};
+use rust_proto::graplinc::grapl::{
+    api::graph_query::v1beta1::messages::{
+        GetNeighborhoodRequest,
+        GraphView,
+    },
+    common::v1beta1::types::{
+        EdgeName,
+        NodeType,
+        Uid,
+    },
+};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    property_query::{
+        EdgeRow,
+        PropertyQueryError,
+        PropertyQueryExecutor,
+    },
+    query_budget::QueryBudgetExceeded,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum NeighborhoodError {
+    #[error("Property query failed: {0:?}")]
+    PropertyQueryError(#[from] PropertyQueryError),
+    #[error("{0}")]
+    BudgetExceeded(#[from] QueryBudgetExceeded),
+    #[error("Node {0:?} does not exist")]
+    NodeNotFound(Uid),
+}
+
+impl NeighborhoodError {
+    /// The budget this query exceeded, if that's why it failed
+    pub fn budget_exceeded(&self) -> Option<&QueryBudgetExceeded> {
+        match self {
+            Self::PropertyQueryError(PropertyQueryError::BudgetExceeded(e)) => Some(e),
+            Self::BudgetExceeded(e) => Some(e),
+            Self::PropertyQueryError(_) | Self::NodeNotFound(_) => None,
+        }
+    }
+}
+
+/// The number of nodes at a single level of the neighborhood that are
+/// fetched at once. The query's budget bounds the total work.
+const NEIGHBORHOOD_CONCURRENCY: usize = 16;
+
+/// Expands the neighborhood of `request.node_uid` breadth-first, one level
+/// per hop, returning it along with whether it was truncated by
+/// `max_nodes` or `max_edges`.
+///
+/// A node is only included along with an edge connecting it to the rest of
+/// the neighborhood, so the result is always connected. Nodes and edges are
+/// visited in uid order, so a truncated neighborhood is deterministic.
+#[tracing::instrument(skip(property_query_executor))]
+pub async fn get_neighborhood(
+    request: &GetNeighborhoodRequest,
+    property_query_executor: PropertyQueryExecutor,
+) -> Result<(GraphView, bool), NeighborhoodError> {
+    let tenant_id = request.tenant_id;
+    let max_nodes = request.max_nodes.map_or(usize::MAX, |n| n as usize);
+    let max_edges = request.max_edges.map_or(usize::MAX, |n| n as usize);
+
+    let mut neighborhood = GraphView::default();
+    let mut truncated = false;
+
+    // The start node is included whatever its type
+    let node_type = property_query_executor
+        .get_node_type(tenant_id, request.node_uid)
+        .await?
+        .ok_or(NeighborhoodError::NodeNotFound(request.node_uid))?;
+    property_query_executor.budget().charge_visit()?;
+    neighborhood.add_node(
+        property_query_executor
+            .get_node_view(tenant_id, request.node_uid, node_type)
+            .await?,
+    );
+
+    // Nodes included in the neighborhood, and nodes left out for their type
+    let mut included: FxHashSet<Uid> = FxHashSet::default();
+    included.insert(request.node_uid);
+    let mut rejected: FxHashSet<Uid> = FxHashSet::default();
+    // Each edge is stored in both directions, but only counts once
+    let mut counted_edges: FxHashSet<(Uid, EdgeName, Uid)> = FxHashSet::default();
+    let mut frontier = vec![request.node_uid];
+
+    for _ in 0..request.max_depth {
+        if frontier.is_empty() {
+            break;
+        }
+
+        let mut edge_rows: Vec<EdgeRow> = futures::stream::iter(frontier.iter().copied())
+            .map(|uid| get_edges(request, uid, &property_query_executor))
+            .buffered(NEIGHBORHOOD_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        edge_rows.sort_unstable_by(|a, b| {
+            (a.source_uid, &a.f_edge_name.value, a.destination_uid).cmp(&(
+                b.source_uid,
+                &b.f_edge_name.value,
+                b.destination_uid,
+            ))
+        });
+
+        let mut candidates: Vec<Uid> = edge_rows
+            .iter()
+            .map(|edge_row| edge_row.destination_uid)
+            .filter(|uid| !included.contains(uid) && !rejected.contains(uid))
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        // Sorted by uid, since the candidates were
+        let mut allowed: Vec<(Uid, NodeType)> = Vec::with_capacity(candidates.len());
+        let candidate_types: Vec<(Uid, Option<NodeType>)> = futures::stream::iter(candidates)
+            .map(|uid| {
+                let property_query_executor = property_query_executor.clone();
+                async move {
+                    let node_type = property_query_executor
+                        .get_node_type(tenant_id, uid)
+                        .await?;
+                    Ok::<_, NeighborhoodError>((uid, node_type))
+                }
+            })
+            .buffered(NEIGHBORHOOD_CONCURRENCY)
+            .try_collect()
+            .await?;
+        for (uid, node_type) in candidate_types {
+            match node_type {
+                Some(node_type)
+                    if request.node_types.is_empty() || request.node_types.contains(&node_type) =>
+                {
+                    allowed.push((uid, node_type))
+                }
+                _ => {
+                    rejected.insert(uid);
+                }
+            }
+        }
+
+        let mut next_frontier: Vec<(Uid, NodeType)> = Vec::new();
+        for edge_row in edge_rows {
+            let EdgeRow {
+                source_uid,
+                f_edge_name,
+                r_edge_name,
+                destination_uid,
+                properties,
+                ..
+            } = edge_row;
+
+            if rejected.contains(&destination_uid) {
+                continue;
+            }
+            if !included.contains(&destination_uid) {
+                let node_type =
+                    match allowed.binary_search_by_key(&destination_uid, |(uid, _)| *uid) {
+                        Ok(idx) => allowed[idx].1.clone(),
+                        Err(_) => continue,
+                    };
+                // A new node needs room for itself and the edge reaching it
+                if included.len() >= max_nodes || counted_edges.len() >= max_edges {
+                    truncated = true;
+                    continue;
+                }
+                included.insert(destination_uid);
+                next_frontier.push((destination_uid, node_type));
+            }
+
+            let edge_key =
+                if (source_uid, &f_edge_name.value) <= (destination_uid, &r_edge_name.value) {
+                    (source_uid, f_edge_name.clone(), destination_uid)
+                } else {
+                    (destination_uid, r_edge_name.clone(), source_uid)
+                };
+            if !counted_edges.contains(&edge_key) {
+                if counted_edges.len() >= max_edges {
+                    truncated = true;
+                    continue;
+                }
+                counted_edges.insert(edge_key);
+            }
+            neighborhood.add_edge(source_uid, f_edge_name.clone(), destination_uid);
+            neighborhood.add_edge(destination_uid, r_edge_name.clone(), source_uid);
+            // Both directions of an edge store the same properties
+            neighborhood.add_edge_properties(
+                destination_uid,
+                r_edge_name,
+                source_uid,
+                properties.clone(),
+            );
+            neighborhood.add_edge_properties(source_uid, f_edge_name, destination_uid, properties);
+        }
+
+        let nodes: Vec<_> = futures::stream::iter(next_frontier.iter().cloned())
+            .map(|(uid, node_type)| {
+                let property_query_executor = property_query_executor.clone();
+                async move {
+                    property_query_executor.budget().charge_visit()?;
+                    let node = property_query_executor
+                        .get_node_view(tenant_id, uid, node_type)
+                        .await?;
+                    Ok::<_, NeighborhoodError>(node)
+                }
+            })
+            .buffered(NEIGHBORHOOD_CONCURRENCY)
+            .try_collect()
+            .await?;
+        for node in nodes {
+            neighborhood.add_node(node);
+        }
+
+        frontier = next_frontier.into_iter().map(|(uid, _)| uid).collect();
+    }
+
+    Ok((neighborhood, truncated))
+}
+
+/// The edges leaving `uid` that the request follows
+async fn get_edges(
+    request: &GetNeighborhoodRequest,
+    uid: Uid,
+    property_query_executor: &PropertyQueryExecutor,
+) -> Result<Vec<EdgeRow>, NeighborhoodError> {
+    if request.edge_names.is_empty() {
+        return Ok(property_query_executor
+            .get_all_edges(request.tenant_id, uid)
+            .await?);
+    }
+
+    let mut edge_rows = Vec::new();
+    for edge_name in &request.edge_names {
+        if let Some(rows) = property_query_executor
+            .get_edges(request.tenant_id, uid, edge_name)
+            .await?
+        {
+            edge_rows.extend(rows.iter().cloned());
+        }
+    }
+    Ok(edge_rows)
+}
@@ -1,12 +1,16 @@
 use std::{
     cell::RefCell,
     rc::Rc,
+    time::Instant,
 };
 
 use async_recursion::async_recursion;
+use futures::StreamExt;
 use rust_proto::graplinc::grapl::{
     api::graph_query::v1beta1::messages::{
         AndStringFilters,
+        AnyOfEdges,
+        EdgeConstraint,
         GraphQuery,
         GraphView,
         NodePropertiesView,
@@ -15,6 +19,7 @@ use rust_proto::graplinc::grapl::{
         QueryId,
         StrCmp,
         StringProperties,
+        TimeWindow,
     },
     common::v1beta1::types::{
         EdgeName,
@@ -29,12 +34,17 @@ use rustc_hash::{
 };
 
 use crate::{
+    deployed_schema::{
+        DeployedSchemaError,
+        SessionTimestampProperties,
+    },
     property_query::{
         EdgeRow,
         PropertyQueryError,
         PropertyQueryExecutor,
         StringField,
     },
+    query_budget::QueryBudgetExceeded,
     short_circuit::ShortCircuit,
     visited::Visited,
 };
@@ -43,8 +53,35 @@ use crate::{
 pub enum NodeQueryError {
     #[error("Property query failed: {0:?}")]
     PropertyQueryError(#[from] PropertyQueryError),
+    #[error("{0}")]
+    BudgetExceeded(#[from] QueryBudgetExceeded),
+    #[error("{0}")]
+    DeployedSchema(#[from] DeployedSchemaError),
+}
+
+impl NodeQueryError {
+    /// The budget this query exceeded, if that's why it failed
+    pub fn budget_exceeded(&self) -> Option<&QueryBudgetExceeded> {
+        match self {
+            Self::PropertyQueryError(PropertyQueryError::BudgetExceeded(e)) => Some(e),
+            Self::BudgetExceeded(e) => Some(e),
+            Self::PropertyQueryError(_) | Self::DeployedSchema(_) => None,
+        }
+    }
+
+    /// Whether the query itself is invalid, rather than failing to run
+    pub fn is_invalid_query(&self) -> bool {
+        matches!(
+            self,
+            Self::DeployedSchema(DeployedSchemaError::NotSessionIdentified { .. })
+        )
+    }
 }
 
+/// The number of neighbors along a single edge that are matched at once,
+/// at each level of the query. The query's budget bounds the total work.
+const NEIGHBOR_CONCURRENCY: usize = 16;
+
 pub(crate) fn match_property(
     node_properties_query: &NodePropertyQuery,
     property_name: &PropertyName,
@@ -91,7 +128,12 @@ pub async fn fetch_node_properties(
                 .await?;
             match property {
                 Some(p) => fields.push(p),
-                None => return Ok(None),
+                None => {
+                    if let Some(profiler) = property_query_executor.profiler() {
+                        profiler.record_rejected(prop_name);
+                    }
+                    return Ok(None);
+                }
             }
             filter_names.remove(prop_name);
         }
@@ -105,6 +147,113 @@ pub async fn fetch_node_properties(
     Ok(Some(fields))
 }
 
+/// The `(start, end)` of a node's session, each along with the property it
+/// was read from, given its unsigned int properties, or `None` if none of its
+/// session timestamps are set. Unset timestamps are stored as 0.
+pub(crate) fn session_bounds<'a>(
+    session_properties: &'a SessionTimestampProperties,
+    uint_properties: &FxHashMap<PropertyName, u64>,
+) -> Option<((&'a PropertyName, u64), (&'a PropertyName, u64))> {
+    let timestamp = |property_name: &'a PropertyName| {
+        uint_properties
+            .get(property_name)
+            .copied()
+            .filter(|timestamp| *timestamp != 0)
+            .map(|timestamp| (property_name, timestamp))
+    };
+    let last_seen_time = timestamp(&session_properties.last_seen_time);
+    let start = timestamp(&session_properties.create_time).or(last_seen_time)?;
+    let end = timestamp(&session_properties.terminate_time)
+        .or(last_seen_time)
+        .unwrap_or(start);
+    Some((start, end))
+}
+
+/// Whether a node's session overlaps a time window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionMatch {
+    /// The session overlaps the window. Holds the node's session timestamps.
+    Overlaps(Vec<(PropertyName, u64)>),
+    /// The session lies outside the window. Holds the session timestamp
+    /// property that put it there.
+    Rejected(PropertyName),
+}
+
+/// Whether a session bounded by `start` and `end` overlaps `time_window`
+pub(crate) fn match_session(
+    time_window: &TimeWindow,
+    (start, end): ((&PropertyName, u64), (&PropertyName, u64)),
+) -> Option<PropertyName> {
+    let (start_property, start_time) = start;
+    let (end_property, end_time) = end;
+    if time_window.overlaps(start_time, end_time) {
+        None
+    } else if time_window
+        .end_time
+        .map_or(false, |window_end| start_time > window_end)
+    {
+        // The session began after the window ended
+        Some(start_property.clone())
+    } else {
+        // The session ended before the window began
+        Some(end_property.clone())
+    }
+}
+
+/// Whether the node's session overlaps `time_window`. Where its session
+/// timestamps are stored is resolved from the node type's deployed session
+/// identity.
+#[tracing::instrument(skip(property_query_executor))]
+pub async fn fetch_session_in_window(
+    time_window: &TimeWindow,
+    node_type: &NodeType,
+    uid: Uid,
+    tenant_id: uuid::Uuid,
+    property_query_executor: PropertyQueryExecutor,
+) -> Result<SessionMatch, NodeQueryError> {
+    let session_properties = property_query_executor
+        .deployed_schema_resolver()
+        .resolve_session_properties(tenant_id, node_type)
+        .await?;
+    let uint_properties: FxHashMap<PropertyName, u64> = property_query_executor
+        .get_uint_properties(tenant_id, uid)
+        .await?
+        .into_iter()
+        .filter(|(property_name, _)| {
+            *property_name == session_properties.create_time
+                || *property_name == session_properties.last_seen_time
+                || *property_name == session_properties.terminate_time
+        })
+        .collect();
+
+    let rejected_by = match session_bounds(&session_properties, &uint_properties) {
+        Some(bounds) => match_session(time_window, bounds),
+        // A session that was never seen can't overlap any window
+        None => Some(session_properties.create_time.clone()),
+    };
+    match rejected_by {
+        Some(property_name) => Ok(SessionMatch::Rejected(property_name)),
+        None => Ok(SessionMatch::Overlaps(
+            uint_properties.into_iter().collect(),
+        )),
+    }
+}
+
+/// Whether the node query `query_id` can only match if at least one of its
+/// `edge_name` edges leads to a node matching `neighbor_query_id`.
+fn is_required_edge(
+    graph_query: &GraphQuery,
+    query_id: QueryId,
+    edge_name: &EdgeName,
+    neighbor_query_id: QueryId,
+) -> bool {
+    graph_query.edge_constraint(query_id, edge_name, neighbor_query_id) == EdgeConstraint::Required
+        && !graph_query
+            .any_of_edges
+            .iter()
+            .any(|group| group.query_id == query_id && group.contains(edge_name, neighbor_query_id))
+}
+
 #[tracing::instrument(skip(node_properties_query, graph_query, property_query_executor))]
 pub async fn fetch_edges(
     node_properties_query: &NodePropertyQuery,
@@ -114,7 +263,7 @@ pub async fn fetch_edges(
     property_query_executor: PropertyQueryExecutor,
 ) -> Result<Option<FxHashMap<EdgeName, Vec<EdgeRow>>>, NodeQueryError> {
     let mut edge_rows = FxHashMap::default();
-    for (src_id, edge_name) in graph_query.edge_filters.keys() {
+    for ((src_id, edge_name), neighbor_query_ids) in graph_query.edge_filters.iter() {
         if *src_id != node_properties_query.query_id {
             continue;
         }
@@ -124,10 +273,20 @@ pub async fn fetch_edges(
             .await?;
 
         let rows = match rows {
-            None => return Ok(None),
-            Some(rows) => rows,
+            Some(rows) => {
+                debug_assert!(!rows.is_empty());
+                rows.as_ref().clone()
+            }
+            // Without any edges only optional, negated or any-of edges can
+            // be satisfied
+            None if neighbor_query_ids.iter().any(|neighbor_query_id| {
+                is_required_edge(graph_query, *src_id, edge_name, *neighbor_query_id)
+            }) =>
+            {
+                return Ok(None)
+            }
+            None => vec![],
         };
-        debug_assert!(!rows.is_empty());
 
         println!("edge name {}, rows {:?}", edge_name, rows);
         edge_rows.insert(edge_name.to_owned(), rows);
@@ -151,19 +310,47 @@ pub async fn fetch_node_with_edges(
         return Ok(None);
     }
 
+    property_query_executor.budget().charge_visit()?;
+
+    let property_query_executor =
+        property_query_executor.for_query_id(node_properties_query.query_id);
+    let profiler = property_query_executor.profiler().cloned();
+    if let Some(profiler) = &profiler {
+        profiler.record_examined();
+    }
+
+    // A node's type is written before the rest of its data, and each write of
+    // a node under an expiring retention policy rewrites all of it, even what
+    // was already stored, so its type expires first. Without its type the
+    // node is treated as though it doesn't exist, rather than returned with
+    // only some of its data.
+    if property_query_executor
+        .get_node_type(tenant_id, uid)
+        .await?
+        .is_none()
+    {
+        visited.set_short_circuit();
+        return Ok(None);
+    }
+
     let mut node = NodePropertiesView::new(
         uid,
         node_properties_query.node_type.clone(),
         StringProperties::default(),
     );
 
+    let started = Instant::now();
     let node_properties = fetch_node_properties(
         node_properties_query,
         uid,
         tenant_id,
         property_query_executor.clone(),
     )
-    .await?;
+    .await;
+    if let Some(profiler) = &profiler {
+        profiler.record_elapsed(started.elapsed());
+    }
+    let node_properties = node_properties?;
 
     let node_properties = match node_properties {
         None => {
@@ -184,11 +371,47 @@ pub async fn fetch_node_with_edges(
                 node_property.value.clone(),
             );
         } else {
+            if let Some(profiler) = &profiler {
+                profiler.record_rejected(&node_property.populated_field);
+            }
             visited.set_short_circuit();
             return Ok(None);
         }
     }
 
+    if let Some(time_window) = &node_properties_query.time_window {
+        let started = Instant::now();
+        let session = fetch_session_in_window(
+            time_window,
+            &node_properties_query.node_type,
+            uid,
+            tenant_id,
+            property_query_executor.clone(),
+        )
+        .await;
+        if let Some(profiler) = &profiler {
+            profiler.record_elapsed(started.elapsed());
+        }
+        match session? {
+            SessionMatch::Overlaps(session_timestamps) => {
+                for (property_name, timestamp) in session_timestamps {
+                    node.add_uint_property(property_name, timestamp);
+                }
+            }
+            SessionMatch::Rejected(property_name) => {
+                if let Some(profiler) = &profiler {
+                    profiler.record_rejected(&property_name);
+                }
+                visited.set_short_circuit();
+                return Ok(None);
+            }
+        }
+    }
+
+    if let Some(profiler) = &profiler {
+        profiler.record_matched();
+    }
+
     if node_properties_query.query_id == graph_query.root_query_id {
         *root_node_uid = Some(uid);
     }
@@ -206,15 +429,22 @@ pub async fn fetch_node_with_edges(
     }
 
     // fetch the edges for the uid
-    let edges = match fetch_edges(
+    let started = Instant::now();
+    let edges = fetch_edges(
         node_properties_query,
         uid,
         graph_query,
         tenant_id,
         property_query_executor.clone(),
     )
-    .await?
-    {
+    .await;
+    if let Some(profiler) = &profiler {
+        profiler.record_elapsed(started.elapsed());
+        if let Ok(Some(edges)) = &edges {
+            profiler.record_edges_fetched(edges.values().map(|rows| rows.len() as u64).sum());
+        }
+    }
+    let edges = match edges? {
         Some(edges) => edges,
         None => {
             visited.set_short_circuit();
@@ -222,6 +452,14 @@ pub async fn fetch_node_with_edges(
         }
     };
 
+    // Whether each of this node's any-of groups has had a member satisfied
+    let any_of_groups: Vec<&AnyOfEdges> = graph_query
+        .any_of_edges
+        .iter()
+        .filter(|group| group.query_id == node_properties_query.query_id)
+        .collect();
+    let mut any_of_satisfied = vec![false; any_of_groups.len()];
+
     for ((src_id, edge_name), edge_queries) in graph_query.edge_filters.iter() {
         if *src_id != node_properties_query.query_id {
             continue;
@@ -247,37 +485,106 @@ pub async fn fetch_node_with_edges(
                 continue;
             }
 
-            // When we support 'OR' logic on edges we'll add that logic here
+            let constraint = graph_query.edge_constraint(
+                node_properties_query.query_id,
+                edge_name,
+                edge_query.query_id,
+            );
+            let groups: Vec<usize> = any_of_groups
+                .iter()
+                .enumerate()
+                .filter(|(_, group)| group.contains(edge_name, edge_query.query_id))
+                .map(|(i, _)| i)
+                .collect();
+            let required = constraint == EdgeConstraint::Required && groups.is_empty();
+
+            // Neighbors are matched concurrently, but their results are
+            // merged in edge order
+            let mut neighbor_matches = futures::stream::iter(edge_rows.iter())
+                .map(|edge_row| {
+                    // A neighbor that fails to match only fails this node if
+                    // the edge is required, so other neighbors are matched in
+                    // isolation
+                    let neighbor_visited = if required {
+                        visited.clone()
+                    } else {
+                        visited.fork()
+                    };
+                    let property_query_executor = property_query_executor.clone();
+                    let x_short_circuit = x_short_circuit.clone();
+                    async move {
+                        let mut neighbor_root_uid = None;
+                        let neighbors = fetch_node_with_edges(
+                            edge_query,
+                            graph_query,
+                            edge_row.destination_uid,
+                            tenant_id,
+                            property_query_executor,
+                            neighbor_visited,
+                            x_short_circuit,
+                            &mut neighbor_root_uid,
+                        )
+                        .await?;
+                        Ok::<_, NodeQueryError>((edge_row, neighbors, neighbor_root_uid))
+                    }
+                })
+                .buffered(NEIGHBOR_CONCURRENCY);
 
             let mut any = false;
-            for edge_row in edge_rows {
-                // we can do this in parallel
+            while let Some(neighbor_match) = neighbor_matches.next().await {
                 if x_short_circuit.get_short_circuit() {
                     return Ok(None);
                 }
-                let neighbors = match fetch_node_with_edges(
-                    edge_query,
-                    graph_query,
-                    edge_row.destination_uid,
-                    tenant_id,
-                    property_query_executor.clone(),
-                    visited.clone(),
-                    x_short_circuit.clone(),
-                    root_node_uid,
-                )
-                .await?
-                {
+                let (edge_row, neighbors, neighbor_root_uid) = neighbor_match?;
+                if neighbor_root_uid.is_some() {
+                    *root_node_uid = neighbor_root_uid;
+                }
+                let neighbors = match neighbors {
                     Some(neighbors) => neighbors,
                     None => continue,
                 };
                 any = true;
+                if constraint == EdgeConstraint::Negated {
+                    // the match is discarded, one is enough to know
+                    break;
+                }
                 for neighbor in neighbors.nodes.keys() {
                     graph.add_edge(uid, edge_name.to_owned(), *neighbor);
                     graph.add_edge(*neighbor, graph_query.edge_map[edge_name].to_owned(), uid);
                 }
+                // Both directions of an edge store the same properties
+                graph.add_edge_properties(
+                    edge_row.destination_uid,
+                    graph_query.edge_map[edge_name].to_owned(),
+                    uid,
+                    edge_row.properties.clone(),
+                );
+                graph.add_edge_properties(
+                    uid,
+                    edge_name.to_owned(),
+                    edge_row.destination_uid,
+                    edge_row.properties.clone(),
+                );
                 graph.merge(neighbors);
             }
-            if !any {
+
+            // The other tasks may have stopped early, in which case `any`
+            // can't be trusted for negated edges
+            if x_short_circuit.get_short_circuit() {
+                return Ok(None);
+            }
+
+            let satisfied = match constraint {
+                EdgeConstraint::Negated => !any,
+                EdgeConstraint::Required | EdgeConstraint::Optional => any,
+            };
+            for group in groups.iter() {
+                any_of_satisfied[*group] |= satisfied;
+            }
+
+            if !satisfied
+                && (required || (constraint == EdgeConstraint::Negated && groups.is_empty()))
+            {
                 // if a given query has no matches, return
                 visited.set_short_circuit();
                 return Ok(None);
@@ -285,6 +592,11 @@ pub async fn fetch_node_with_edges(
         }
     }
 
+    if any_of_satisfied.contains(&false) {
+        visited.set_short_circuit();
+        return Ok(None);
+    }
+
     Ok(Some(graph))
 }
 
@@ -303,6 +615,7 @@ impl NodeQuery {
             int_filters: Default::default(),
             string_filters: Default::default(),
             uid_filters: Default::default(),
+            time_window: None,
         };
         let mut node_property_queries = FxHashMap::default();
         node_property_queries.insert(query_id, inner_query);
@@ -312,6 +625,8 @@ impl NodeQuery {
             node_property_queries,
             edge_filters: Default::default(),
             edge_map: Default::default(),
+            edge_constraints: Default::default(),
+            any_of_edges: Default::default(),
         };
 
         Self {
@@ -352,6 +667,19 @@ impl NodeQuery {
             .insert(property_name, comparisons);
     }
 
+    /// The node only matches if its session overlaps `time_window`, e.g. a
+    /// process that was alive at some point in the last hour
+    pub fn with_time_window(&mut self, time_window: TimeWindow) -> &mut Self {
+        let mut inner = self.graph.as_mut().unwrap().borrow_mut();
+        inner
+            .node_property_queries
+            .get_mut(&self.query_id)
+            .unwrap()
+            .time_window = Some(time_window);
+        drop(inner);
+        self
+    }
+
     pub fn with_shared_edge(
         &mut self,
         edge_name: EdgeName,
@@ -400,6 +728,78 @@ impl NodeQuery {
         node_type: NodeType,
         init_edge: impl FnOnce(&mut Self),
     ) -> &mut Self {
+        self.add_edge_to(edge_name, reverse_edge_name, node_type, init_edge);
+        self
+    }
+
+    /// Like `with_edge_to`, but the node still matches if none of its
+    /// neighbors do. Any that do are included in the match.
+    pub fn with_optional_edge_to(
+        &mut self,
+        edge_name: EdgeName,
+        reverse_edge_name: EdgeName,
+        node_type: NodeType,
+        init_edge: impl FnOnce(&mut Self),
+    ) -> &mut Self {
+        let neighbor_query_id =
+            self.add_edge_to(edge_name.clone(), reverse_edge_name, node_type, init_edge);
+        self.set_edge_constraint(edge_name, neighbor_query_id, EdgeConstraint::Optional);
+        self
+    }
+
+    /// The node only matches if none of its neighbors match the neighbor
+    /// query, e.g. a process with no parent.
+    pub fn without_edge_to(
+        &mut self,
+        edge_name: EdgeName,
+        reverse_edge_name: EdgeName,
+        node_type: NodeType,
+        init_edge: impl FnOnce(&mut Self),
+    ) -> &mut Self {
+        let neighbor_query_id =
+            self.add_edge_to(edge_name.clone(), reverse_edge_name, node_type, init_edge);
+        self.set_edge_constraint(edge_name, neighbor_query_id, EdgeConstraint::Negated);
+        self
+    }
+
+    /// The node only matches if at least one of the edges added in
+    /// `init_group` matches, e.g. a file written by one of two processes.
+    pub fn with_any_of_edges(
+        &mut self,
+        init_group: impl FnOnce(&mut AnyOfEdgesQuery<'_>),
+    ) -> &mut Self {
+        let mut group = AnyOfEdgesQuery {
+            group: AnyOfEdges::new(self.query_id),
+            node: self,
+        };
+        init_group(&mut group);
+
+        let group = group.group;
+        let graph = self.graph.as_mut().unwrap();
+        graph.borrow_mut().any_of_edges.push(group);
+        self
+    }
+
+    fn set_edge_constraint(
+        &mut self,
+        edge_name: EdgeName,
+        neighbor_query_id: QueryId,
+        constraint: EdgeConstraint,
+    ) {
+        let graph = self.graph.as_mut().unwrap();
+        graph
+            .borrow_mut()
+            .edge_constraints
+            .insert((self.query_id, edge_name, neighbor_query_id), constraint);
+    }
+
+    fn add_edge_to(
+        &mut self,
+        edge_name: EdgeName,
+        reverse_edge_name: EdgeName,
+        node_type: NodeType,
+        init_edge: impl FnOnce(&mut Self),
+    ) -> QueryId {
         let new_neighbor_id = QueryId::default();
 
         {
@@ -433,7 +833,7 @@ impl NodeQuery {
                 .insert(edge_name.clone(), reverse_edge_name.clone());
             graph.edge_map.insert(reverse_edge_name, edge_name);
         }
-        self
+        new_neighbor_id
     }
 
     pub fn build(&mut self) -> GraphQuery {
@@ -447,6 +847,126 @@ impl NodeQuery {
             node_property_queries: Default::default(),
             edge_filters: Default::default(),
             edge_map: Default::default(),
+            edge_constraints: Default::default(),
+            any_of_edges: Default::default(),
         })
     }
 }
+
+/// Collects the edges of a `NodeQuery::with_any_of_edges` group.
+pub struct AnyOfEdgesQuery<'a> {
+    node: &'a mut NodeQuery,
+    group: AnyOfEdges,
+}
+
+impl AnyOfEdgesQuery<'_> {
+    pub fn with_edge_to(
+        &mut self,
+        edge_name: EdgeName,
+        reverse_edge_name: EdgeName,
+        node_type: NodeType,
+        init_edge: impl FnOnce(&mut NodeQuery),
+    ) -> &mut Self {
+        let neighbor_query_id =
+            self.node
+                .add_edge_to(edge_name.clone(), reverse_edge_name, node_type, init_edge);
+        self.group.edges.insert((edge_name, neighbor_query_id));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_properties() -> SessionTimestampProperties {
+        SessionTimestampProperties {
+            create_time: PropertyName::try_from("created_at").unwrap(),
+            last_seen_time: PropertyName::try_from("last_seen_at").unwrap(),
+            terminate_time: PropertyName::try_from("terminated_at").unwrap(),
+        }
+    }
+
+    fn uint_properties(timestamps: &[(&str, u64)]) -> FxHashMap<PropertyName, u64> {
+        timestamps
+            .iter()
+            .map(|(property_name, timestamp)| {
+                (PropertyName::try_from(*property_name).unwrap(), *timestamp)
+            })
+            .collect()
+    }
+
+    /// The property that put a session outside of the window, if any
+    fn rejected_by(
+        timestamps: &[(&str, u64)],
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) -> Option<String> {
+        let session_properties = session_properties();
+        let uint_properties = uint_properties(timestamps);
+        let bounds = session_bounds(&session_properties, &uint_properties).unwrap();
+        match_session(&TimeWindow::new(start_time, end_time), bounds)
+            .map(|property_name| property_name.value)
+    }
+
+    #[test]
+    fn test_session_bounds_fall_back_to_last_seen_time() {
+        let session_properties = session_properties();
+        let bounds = |timestamps: &[(&str, u64)]| {
+            session_bounds(&session_properties, &uint_properties(timestamps))
+                .map(|((_, start), (_, end))| (start, end))
+        };
+
+        assert_eq!(
+            bounds(&[
+                ("created_at", 100),
+                ("last_seen_at", 150),
+                ("terminated_at", 200)
+            ]),
+            Some((100, 200))
+        );
+        assert_eq!(
+            bounds(&[("created_at", 100), ("last_seen_at", 150)]),
+            Some((100, 150))
+        );
+        assert_eq!(
+            bounds(&[("created_at", 0), ("last_seen_at", 150)]),
+            Some((150, 150))
+        );
+        assert_eq!(bounds(&[("created_at", 100)]), Some((100, 100)));
+        assert_eq!(bounds(&[("created_at", 0), ("terminated_at", 0)]), None);
+    }
+
+    #[test]
+    fn test_session_in_window_is_not_rejected() {
+        let timestamps = [("created_at", 100), ("terminated_at", 200)];
+        assert_eq!(rejected_by(&timestamps, Some(150), Some(300)), None);
+        assert_eq!(rejected_by(&timestamps, None, Some(100)), None);
+        assert_eq!(rejected_by(&timestamps, Some(200), None), None);
+        assert_eq!(rejected_by(&timestamps, None, None), None);
+    }
+
+    #[test]
+    fn test_session_rejected_by_the_bound_outside_the_window() {
+        let timestamps = [
+            ("created_at", 100),
+            ("last_seen_at", 150),
+            ("terminated_at", 200),
+        ];
+        // The session began after the window ended
+        assert_eq!(
+            rejected_by(&timestamps, None, Some(50)),
+            Some("created_at".to_owned())
+        );
+        // The session ended before the window began
+        assert_eq!(
+            rejected_by(&timestamps, Some(250), None),
+            Some("terminated_at".to_owned())
+        );
+        // A session that hasn't terminated ended when it was last seen
+        assert_eq!(
+            rejected_by(&timestamps[..2], Some(250), None),
+            Some("last_seen_at".to_owned())
+        );
+    }
+}
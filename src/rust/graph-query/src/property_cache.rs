@@ -1,13 +1,29 @@
 use std::{
-    fmt::Debug,
-    hash::Hash,
+    sync::Arc,
+    time::Duration,
 };
 
 use moka::future::{
     Cache,
     CacheBuilder,
 };
-use rust_proto::graplinc::grapl::common::v1beta1::types::Uid;
+use opentelemetry::{
+    global,
+    metrics::Counter,
+    Context,
+    KeyValue,
+};
+use rust_proto::graplinc::grapl::common::v1beta1::types::{
+    EdgeName,
+    PropertyName,
+    Uid,
+};
+
+use crate::{
+    config::PropertyCacheConfig,
+    property_query::EdgeRow,
+};
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum CacheMatch {
     Matched,
@@ -15,30 +31,424 @@ pub enum CacheMatch {
     CouldMatch,
 }
 
+/// The generation of a tenant's graph, which changes whenever anything is
+/// deleted from it. Entries are keyed by the generation they were read in, so
+/// that nothing read before a deletion is served after it. `None` until the
+/// tenant's graph has had something deleted.
+pub type DeletionGeneration = Option<uuid::Uuid>;
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Key {
     property_name: String,
     uid: Uid,
     tenant_id: uuid::Uuid,
+    deletion_generation: DeletionGeneration,
+}
+
+impl Key {
+    pub fn new(
+        tenant_id: uuid::Uuid,
+        deletion_generation: DeletionGeneration,
+        uid: Uid,
+        property_name: &PropertyName,
+    ) -> Self {
+        Self {
+            property_name: property_name.value.clone(),
+            uid,
+            tenant_id,
+            deletion_generation,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EdgeKey {
+    edge_name: String,
+    uid: Uid,
+    tenant_id: uuid::Uuid,
+    deletion_generation: DeletionGeneration,
+}
+
+impl EdgeKey {
+    pub fn new(
+        tenant_id: uuid::Uuid,
+        deletion_generation: DeletionGeneration,
+        uid: Uid,
+        edge_name: &EdgeName,
+    ) -> Self {
+        Self {
+            edge_name: edge_name.value.clone(),
+            uid,
+            tenant_id,
+            deletion_generation,
+        }
+    }
+}
+
+/// The result of consulting the cache before going to the database
+#[derive(Clone, Debug)]
+pub enum CacheLookup<T> {
+    /// The value was cached
+    Hit(T),
+    /// The value was recently looked up and did not exist
+    Missing,
+    /// Nothing is known about the value
+    Miss,
 }
 
+#[derive(Clone)]
+struct CacheMetrics {
+    lookups: Counter<u64>,
+}
+
+impl CacheMetrics {
+    fn new() -> Self {
+        let meter = global::meter("graph-query");
+        Self {
+            lookups: meter
+                .u64_counter("graph_query.property_cache.lookups")
+                .with_description("Property cache lookups, by cache and outcome")
+                .init(),
+        }
+    }
+
+    fn record<T>(&self, cache: &'static str, lookup: &CacheLookup<T>) {
+        let outcome = match lookup {
+            CacheLookup::Hit(_) => "hit",
+            CacheLookup::Missing => "negative_hit",
+            CacheLookup::Miss => "miss",
+        };
+        self.lookups.add(
+            &Context::current(),
+            1,
+            &[
+                KeyValue::new("cache", cache),
+                KeyValue::new("outcome", outcome),
+            ],
+        );
+    }
+}
+
+/// Bounded caches in front of the property and edge tables.
+///
+/// Immutable properties can never change once written, so they're only
+/// evicted to bound memory. Edge lists can grow, so they're cached for a
+/// shorter time. Properties and edges that don't exist yet are remembered
+/// only briefly, since they may be written at any moment. Anything may be
+/// deleted, so each tenant's deletion generation is looked up again after
+/// a short while, and entries from earlier generations are never hit.
 #[derive(Clone)]
 pub struct PropertyCache {
     string_cache: Cache<Key, String>,
-    int_cache: Cache<Key, i64>,
-    uint_cache: Cache<Key, u64>,
+    missing_string_cache: Cache<Key, ()>,
+    edge_cache: Cache<EdgeKey, Arc<Vec<EdgeRow>>>,
+    missing_edge_cache: Cache<EdgeKey, ()>,
+    deletion_generations: Cache<uuid::Uuid, DeletionGeneration>,
+    metrics: CacheMetrics,
 }
 
 impl PropertyCache {
-    pub fn new(
-        string_cache: Cache<Key, String>,
-        int_cache: Cache<Key, i64>,
-        uint_cache: Cache<Key, u64>,
-    ) -> Self {
+    pub fn new(config: &PropertyCacheConfig) -> Self {
+        let max_entries = config.graph_query_property_cache_max_entries;
+        let property_ttl = Duration::from_millis(config.graph_query_property_cache_ttl_ms);
+        let edge_ttl = Duration::from_millis(config.graph_query_edge_cache_ttl_ms);
+        let negative_ttl = Duration::from_millis(config.graph_query_negative_cache_ttl_ms);
+        let deletion_check_interval =
+            Duration::from_millis(config.graph_query_deletion_check_interval_ms);
+
         Self {
-            string_cache,
-            int_cache,
-            uint_cache,
+            string_cache: CacheBuilder::new(max_entries)
+                .time_to_live(property_ttl)
+                .build(),
+            missing_string_cache: CacheBuilder::new(max_entries)
+                .time_to_live(negative_ttl)
+                .build(),
+            edge_cache: CacheBuilder::new(max_entries)
+                .time_to_live(edge_ttl)
+                .build(),
+            missing_edge_cache: CacheBuilder::new(max_entries)
+                .time_to_live(negative_ttl)
+                .build(),
+            deletion_generations: CacheBuilder::new(max_entries)
+                .time_to_live(deletion_check_interval)
+                .build(),
+            metrics: CacheMetrics::new(),
+        }
+    }
+
+    /// The tenant's deletion generation, unless it's due to be looked up
+    /// again
+    pub fn get_deletion_generation(&self, tenant_id: uuid::Uuid) -> Option<DeletionGeneration> {
+        self.deletion_generations.get(&tenant_id)
+    }
+
+    pub async fn put_deletion_generation(
+        &self,
+        tenant_id: uuid::Uuid,
+        deletion_generation: DeletionGeneration,
+    ) {
+        self.deletion_generations
+            .insert(tenant_id, deletion_generation)
+            .await;
+    }
+
+    pub fn get_immutable_string(&self, key: &Key) -> CacheLookup<String> {
+        let lookup = if let Some(value) = self.string_cache.get(key) {
+            CacheLookup::Hit(value)
+        } else if self.missing_string_cache.get(key).is_some() {
+            CacheLookup::Missing
+        } else {
+            CacheLookup::Miss
+        };
+        self.metrics.record("immutable_string", &lookup);
+        lookup
+    }
+
+    pub async fn put_immutable_string(&self, key: Key, value: Option<String>) {
+        match value {
+            Some(value) => {
+                self.missing_string_cache.invalidate(&key).await;
+                self.string_cache.insert(key, value).await;
+            }
+            None => self.missing_string_cache.insert(key, ()).await,
+        }
+    }
+
+    pub fn get_edges(&self, key: &EdgeKey) -> CacheLookup<Arc<Vec<EdgeRow>>> {
+        let lookup = if let Some(edges) = self.edge_cache.get(key) {
+            CacheLookup::Hit(edges)
+        } else if self.missing_edge_cache.get(key).is_some() {
+            CacheLookup::Missing
+        } else {
+            CacheLookup::Miss
+        };
+        self.metrics.record("edges", &lookup);
+        lookup
+    }
+
+    pub async fn put_edges(&self, key: EdgeKey, edges: Option<Arc<Vec<EdgeRow>>>) {
+        match edges {
+            Some(edges) => {
+                self.missing_edge_cache.invalidate(&key).await;
+                self.edge_cache.insert(key, edges).await;
+            }
+            None => self.missing_edge_cache.insert(key, ()).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moka::future::ConcurrentCacheExt;
+
+    use super::*;
+
+    const NEGATIVE_CACHE_TTL: Duration = Duration::from_millis(50);
+    const DELETION_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+    fn property_cache(max_entries: u64) -> PropertyCache {
+        PropertyCache::new(&PropertyCacheConfig {
+            graph_query_property_cache_max_entries: max_entries,
+            graph_query_property_cache_ttl_ms: 60_000,
+            graph_query_edge_cache_ttl_ms: 60_000,
+            graph_query_negative_cache_ttl_ms: NEGATIVE_CACHE_TTL.as_millis() as u64,
+            graph_query_deletion_check_interval_ms: DELETION_CHECK_INTERVAL.as_millis() as u64,
+        })
+    }
+
+    fn tenant_id() -> uuid::Uuid {
+        uuid::Uuid::from_u128(1)
+    }
+
+    fn key(uid: u64) -> Key {
+        Key::new(
+            tenant_id(),
+            None,
+            Uid::from_u64(uid).unwrap(),
+            &PropertyName {
+                value: "process_name".to_string(),
+            },
+        )
+    }
+
+    fn edge_key(uid: u64) -> EdgeKey {
+        EdgeKey::new(
+            tenant_id(),
+            None,
+            Uid::from_u64(uid).unwrap(),
+            &EdgeName {
+                value: "children".to_string(),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_immutable_string_miss_then_hit() {
+        let cache = property_cache(100);
+
+        assert!(matches!(
+            cache.get_immutable_string(&key(1)),
+            CacheLookup::Miss
+        ));
+
+        cache
+            .put_immutable_string(key(1), Some("cmd.exe".to_string()))
+            .await;
+
+        assert!(matches!(
+            cache.get_immutable_string(&key(1)),
+            CacheLookup::Hit(value) if value == "cmd.exe"
+        ));
+        // Other nodes' properties aren't affected
+        assert!(matches!(
+            cache.get_immutable_string(&key(2)),
+            CacheLookup::Miss
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_immutable_string_is_negatively_cached() {
+        let cache = property_cache(100);
+
+        cache.put_immutable_string(key(1), None).await;
+        assert!(matches!(
+            cache.get_immutable_string(&key(1)),
+            CacheLookup::Missing
+        ));
+
+        // Once the property is written, the negative entry is replaced
+        cache
+            .put_immutable_string(key(1), Some("cmd.exe".to_string()))
+            .await;
+        assert!(matches!(
+            cache.get_immutable_string(&key(1)),
+            CacheLookup::Hit(value) if value == "cmd.exe"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_negative_entries_expire() {
+        let cache = property_cache(100);
+
+        cache.put_immutable_string(key(1), None).await;
+        cache.put_edges(edge_key(1), None).await;
+        assert!(matches!(
+            cache.get_immutable_string(&key(1)),
+            CacheLookup::Missing
+        ));
+        assert!(matches!(
+            cache.get_edges(&edge_key(1)),
+            CacheLookup::Missing
+        ));
+
+        tokio::time::sleep(NEGATIVE_CACHE_TTL * 4).await;
+
+        assert!(matches!(
+            cache.get_immutable_string(&key(1)),
+            CacheLookup::Miss
+        ));
+        assert!(matches!(cache.get_edges(&edge_key(1)), CacheLookup::Miss));
+    }
+
+    #[tokio::test]
+    async fn test_edges_miss_missing_and_hit() {
+        let cache = property_cache(100);
+
+        assert!(matches!(cache.get_edges(&edge_key(1)), CacheLookup::Miss));
+
+        cache.put_edges(edge_key(1), None).await;
+        assert!(matches!(
+            cache.get_edges(&edge_key(1)),
+            CacheLookup::Missing
+        ));
+
+        cache.put_edges(edge_key(1), Some(Arc::new(vec![]))).await;
+        assert!(matches!(
+            cache.get_edges(&edge_key(1)),
+            CacheLookup::Hit(edges) if edges.is_empty()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_evicted_beyond_max_entries() {
+        let max_entries = 10;
+        let cache = property_cache(max_entries);
+
+        for uid in 1..=100 {
+            cache
+                .put_immutable_string(key(uid), Some(uid.to_string()))
+                .await;
+            cache.put_immutable_string(key(uid + 1000), None).await;
         }
+        // Evictions are applied lazily, force them to be applied now
+        cache.string_cache.sync();
+        cache.missing_string_cache.sync();
+
+        assert!(cache.string_cache.entry_count() <= max_entries);
+        assert!(cache.missing_string_cache.entry_count() <= max_entries);
+
+        let hits = (1..=100)
+            .filter(|uid| matches!(cache.get_immutable_string(&key(*uid)), CacheLookup::Hit(_)))
+            .count();
+        assert!(hits as u64 <= max_entries, "{hits} entries still cached");
+    }
+
+    #[tokio::test]
+    async fn test_entries_from_before_a_deletion_are_not_hit() {
+        let cache = property_cache(100);
+        let uid = Uid::from_u64(1).unwrap();
+        let property_name = PropertyName {
+            value: "process_name".to_string(),
+        };
+        let edge_name = EdgeName {
+            value: "children".to_string(),
+        };
+        let deletion_generation = Some(uuid::Uuid::from_u128(2));
+
+        cache.put_deletion_generation(tenant_id(), None).await;
+        cache
+            .put_immutable_string(key(1), Some("cmd.exe".to_string()))
+            .await;
+        cache.put_edges(edge_key(1), None).await;
+
+        // Something is deleted from the tenant's graph
+        cache
+            .put_deletion_generation(tenant_id(), deletion_generation)
+            .await;
+        assert_eq!(
+            cache.get_deletion_generation(tenant_id()),
+            Some(deletion_generation)
+        );
+        assert!(matches!(
+            cache.get_immutable_string(&Key::new(
+                tenant_id(),
+                deletion_generation,
+                uid,
+                &property_name
+            )),
+            CacheLookup::Miss
+        ));
+        assert!(matches!(
+            cache.get_edges(&EdgeKey::new(
+                tenant_id(),
+                deletion_generation,
+                uid,
+                &edge_name
+            )),
+            CacheLookup::Miss
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_deletion_generations_are_looked_up_again() {
+        let cache = property_cache(100);
+
+        assert_eq!(cache.get_deletion_generation(tenant_id()), None);
+        cache.put_deletion_generation(tenant_id(), None).await;
+        assert_eq!(cache.get_deletion_generation(tenant_id()), Some(None));
+
+        tokio::time::sleep(DELETION_CHECK_INTERVAL * 4).await;
+
+        assert_eq!(cache.get_deletion_generation(tenant_id()), None);
     }
 }
@@ -1,13 +1,26 @@
-use std::sync::Arc;
+use std::{
+    ops::RangeInclusive,
+    sync::Arc,
+};
 
 use rust_proto::{
-    graplinc::grapl::common::v1beta1::types::{
-        EdgeName,
-        PropertyName,
-        Uid,
+    graplinc::grapl::{
+        api::graph_query::v1beta1::messages::{
+            EdgePropertiesView,
+            NodePropertiesView,
+            QueryId,
+            StringProperties,
+        },
+        common::v1beta1::types::{
+            EdgeName,
+            NodeType,
+            PropertyName,
+            Uid,
+        },
     },
     SerDeError,
 };
+use rustc_hash::FxHashMap;
 use scylla::{
     cql_to_rust::FromRowError,
     transport::{
@@ -17,7 +30,54 @@ use scylla::{
     CachingSession,
 };
 
-use crate::table_names::IMM_STRING_TABLE_NAME;
+use crate::{
+    deployed_schema::DeployedSchemaResolver,
+    property_cache::{
+        CacheLookup,
+        DeletionGeneration,
+        EdgeKey,
+        Key,
+        PropertyCache,
+    },
+    query_budget::{
+        QueryBudget,
+        QueryBudgetExceeded,
+    },
+    query_profile::QueryProfiler,
+    table_names::{
+        int_index_bucket,
+        EDGE_I_64_TABLE_NAME,
+        EDGE_STRING_TABLE_NAME,
+        EDGE_U_64_TABLE_NAME,
+        GRAPH_DELETIONS_TABLE_NAME,
+        IMM_I_64_BUCKETED_INDEX_TABLE_NAME,
+        IMM_I_64_TABLE_NAME,
+        IMM_STRING_INDEX_TABLE_NAME,
+        IMM_STRING_TABLE_NAME,
+        IMM_U_64_BUCKETED_INDEX_TABLE_NAME,
+        IMM_U_64_TABLE_NAME,
+        MAX_I_64_TABLE_NAME,
+        MAX_U_64_TABLE_NAME,
+        MIN_I_64_TABLE_NAME,
+        MIN_U_64_TABLE_NAME,
+    },
+};
+
+/// An int range lookup reads one index partition per bucket its range spans,
+/// so a range spanning more buckets than this isn't looked up in the index.
+pub const MAX_INT_INDEX_BUCKETS: i64 = 16;
+
+/// The index buckets holding the values `min..=max`
+pub fn int_index_buckets(min: i64, max: i64) -> RangeInclusive<i64> {
+    int_index_bucket(min)..=int_index_bucket(max)
+}
+
+/// Whether looking up `min..=max` reads at most `MAX_INT_INDEX_BUCKETS`
+/// index partitions. An empty range reads none.
+pub fn is_int_range_indexable(min: i64, max: i64) -> bool {
+    let buckets = int_index_buckets(min, max);
+    buckets.end() - buckets.start() < MAX_INT_INDEX_BUCKETS
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum PropertyQueryError {
@@ -35,6 +95,12 @@ pub enum PropertyQueryError {
     },
     #[error("Invalid stored edge name {0}")]
     InvalidStoredEdgeName(#[from] SerDeError),
+    #[error("Invalid uid '{uid}' in index {table_name}")]
+    InvalidIndexedUid { uid: i64, table_name: &'static str },
+    #[error("Int range {min}..={max} spans too many index buckets")]
+    IntRangeTooWide { min: i64, max: i64 },
+    #[error("{0}")]
+    BudgetExceeded(#[from] QueryBudgetExceeded),
 }
 
 #[derive(Debug, Clone)]
@@ -44,6 +110,10 @@ pub struct EdgeRow {
     pub r_edge_name: EdgeName,
     pub destination_uid: Uid,
     pub tenant_id: uuid::Uuid,
+    /// The edge's properties, as stored on its source node's side. They're
+    /// cached along with the edge, so mutable ones may lag behind writes by
+    /// up to the edge cache's TTL.
+    pub properties: EdgePropertiesView,
 }
 
 #[derive(Debug, Clone)]
@@ -58,11 +128,105 @@ pub struct StringField {
 #[derive(Clone)]
 pub struct PropertyQueryExecutor {
     scylla_client: Arc<CachingSession>,
+    property_cache: PropertyCache,
+    deployed_schema_resolver: DeployedSchemaResolver,
+    budget: QueryBudget,
+    profiler: Option<QueryProfiler>,
 }
 
 impl PropertyQueryExecutor {
-    pub fn new(scylla_client: Arc<CachingSession>) -> Self {
-        Self { scylla_client }
+    pub fn new(
+        scylla_client: Arc<CachingSession>,
+        property_cache: PropertyCache,
+        deployed_schema_resolver: DeployedSchemaResolver,
+    ) -> Self {
+        Self {
+            scylla_client,
+            property_cache,
+            deployed_schema_resolver,
+            budget: QueryBudget::unlimited(),
+            profiler: None,
+        }
+    }
+
+    /// An executor sharing this one's session and cache, whose reads are
+    /// charged against `budget`
+    pub fn with_budget(&self, budget: QueryBudget) -> Self {
+        Self {
+            scylla_client: self.scylla_client.clone(),
+            property_cache: self.property_cache.clone(),
+            deployed_schema_resolver: self.deployed_schema_resolver.clone(),
+            budget,
+            profiler: self.profiler.clone(),
+        }
+    }
+
+    /// An executor whose reads are recorded by `profiler`
+    pub fn with_profiler(&self, profiler: QueryProfiler) -> Self {
+        Self {
+            profiler: Some(profiler),
+            ..self.clone()
+        }
+    }
+
+    /// An executor whose reads are attributed to `query_id` when profiling
+    pub fn for_query_id(&self, query_id: QueryId) -> Self {
+        Self {
+            profiler: self
+                .profiler
+                .as_ref()
+                .map(|profiler| profiler.for_query_id(query_id)),
+            ..self.clone()
+        }
+    }
+
+    pub fn budget(&self) -> &QueryBudget {
+        &self.budget
+    }
+
+    pub fn profiler(&self) -> Option<&QueryProfiler> {
+        self.profiler.as_ref()
+    }
+
+    pub fn deployed_schema_resolver(&self) -> &DeployedSchemaResolver {
+        &self.deployed_schema_resolver
+    }
+
+    fn charge_read(&self) -> Result<(), PropertyQueryError> {
+        self.budget.charge_read()?;
+        if let Some(profiler) = &self.profiler {
+            profiler.record_read();
+        }
+        Ok(())
+    }
+
+    /// The tenant's deletion generation, which what's cached for it is keyed
+    /// by
+    async fn deletion_generation(
+        &self,
+        tenant_id: uuid::Uuid,
+    ) -> Result<DeletionGeneration, PropertyQueryError> {
+        if let Some(deletion_generation) = self.property_cache.get_deletion_generation(tenant_id) {
+            return Ok(deletion_generation);
+        }
+
+        let mut query = scylla::query::Query::from(format!(
+            r"
+            SELECT generation
+            FROM tenant_graph_ks.{GRAPH_DELETIONS_TABLE_NAME}
+            WHERE tenant_id = ?
+            "
+        ));
+        query.set_is_idempotent(true);
+
+        let query_result = self.scylla_client.execute(query, &(tenant_id,)).await?;
+        let deletion_generation = query_result
+            .maybe_first_row_typed::<(Option<uuid::Uuid>,)>()?
+            .and_then(|(generation,)| generation);
+        self.property_cache
+            .put_deletion_generation(tenant_id, deletion_generation)
+            .await;
+        Ok(deletion_generation)
     }
 
     pub async fn get_immutable_string(
@@ -71,6 +235,35 @@ impl PropertyQueryExecutor {
         uid: Uid,
         property_name: &PropertyName,
     ) -> Result<Option<StringField>, PropertyQueryError> {
+        let deletion_generation = self.deletion_generation(tenant_id).await?;
+        let key = Key::new(tenant_id, deletion_generation, uid, property_name);
+        let value = match self.property_cache.get_immutable_string(&key) {
+            CacheLookup::Hit(value) => Some(value),
+            CacheLookup::Missing => None,
+            CacheLookup::Miss => {
+                let value = self
+                    .fetch_immutable_string(tenant_id, uid, property_name)
+                    .await?;
+                self.property_cache
+                    .put_immutable_string(key, value.clone())
+                    .await;
+                value
+            }
+        };
+
+        Ok(value.map(|value| StringField {
+            uid,
+            populated_field: property_name.clone(),
+            value,
+        }))
+    }
+
+    async fn fetch_immutable_string(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+        property_name: &PropertyName,
+    ) -> Result<Option<String>, PropertyQueryError> {
         let mut query = scylla::query::Query::from(format!(
             r"
             SELECT value
@@ -85,22 +278,16 @@ impl PropertyQueryExecutor {
         ));
 
         query.set_is_idempotent(true);
+        self.charge_read()?;
 
         let query_result = self
             .scylla_client
             .execute(query, &(tenant_id, uid.as_i64(), &property_name.value))
             .await?;
 
-        let row = match query_result.maybe_first_row_typed::<(String,)>()? {
-            Some((row,)) => row,
-            None => return Ok(None),
-        };
-
-        Ok(Some(StringField {
-            uid,
-            populated_field: property_name.clone(),
-            value: row,
-        }))
+        Ok(query_result
+            .maybe_first_row_typed::<(String,)>()?
+            .map(|(value,)| value))
     }
 
     pub async fn get_edges(
@@ -108,6 +295,28 @@ impl PropertyQueryExecutor {
         tenant_id: uuid::Uuid,
         uid: Uid,
         edge_name: &EdgeName,
+    ) -> Result<Option<Arc<Vec<EdgeRow>>>, PropertyQueryError> {
+        let deletion_generation = self.deletion_generation(tenant_id).await?;
+        let key = EdgeKey::new(tenant_id, deletion_generation, uid, edge_name);
+        match self.property_cache.get_edges(&key) {
+            CacheLookup::Hit(edge_rows) => Ok(Some(edge_rows)),
+            CacheLookup::Missing => Ok(None),
+            CacheLookup::Miss => {
+                let edge_rows = self
+                    .fetch_edges(tenant_id, uid, edge_name)
+                    .await?
+                    .map(Arc::new);
+                self.property_cache.put_edges(key, edge_rows.clone()).await;
+                Ok(edge_rows)
+            }
+        }
+    }
+
+    async fn fetch_edges(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+        edge_name: &EdgeName,
     ) -> Result<Option<Vec<EdgeRow>>, PropertyQueryError> {
         let mut query = scylla::query::Query::from(
             r"
@@ -124,6 +333,7 @@ impl PropertyQueryExecutor {
         println!("query: \n{}\n", &query.contents);
 
         query.set_is_idempotent(true);
+        self.charge_read()?;
 
         let query_result = self
             .scylla_client
@@ -148,13 +358,462 @@ impl PropertyQueryExecutor {
                     }
                 })?,
                 tenant_id,
+                properties: EdgePropertiesView::default(),
             });
         }
 
         if edge_rows.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(edge_rows))
+            return Ok(None);
+        }
+
+        let mut edge_properties = self
+            .get_edge_properties(tenant_id, uid, Some(edge_name))
+            .await?;
+        for edge_row in edge_rows.iter_mut() {
+            if let Some(properties) = edge_properties
+                .remove(&(edge_name.value.clone(), edge_row.destination_uid.as_i64()))
+            {
+                edge_row.properties = properties;
+            }
+        }
+        Ok(Some(edge_rows))
+    }
+
+    /// Looks up the `node_type` nodes whose immutable string property
+    /// `property_name` is equal to `value`, in ascending order of uid.
+    pub async fn get_uids_by_immutable_string(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type: &NodeType,
+        property_name: &PropertyName,
+        value: &str,
+    ) -> Result<Vec<Uid>, PropertyQueryError> {
+        let mut query = scylla::query::Query::from(format!(
+            r"
+            SELECT uid
+            FROM tenant_graph_ks.{IMM_STRING_INDEX_TABLE_NAME}
+            WHERE
+                tenant_id = ? AND
+                node_type = ? AND
+                populated_field = ? AND
+                value = ?;
+            ",
+        ));
+
+        query.set_is_idempotent(true);
+        self.charge_read()?;
+
+        let query_result = self
+            .scylla_client
+            .execute(
+                query,
+                &(tenant_id, &node_type.value, &property_name.value, value),
+            )
+            .await?;
+
+        let mut uids = Vec::new();
+        for row in query_result.rows_typed_or_empty::<(i64,)>() {
+            let (uid,) = row?;
+            uids.push(
+                Uid::from_i64(uid).ok_or(PropertyQueryError::InvalidIndexedUid {
+                    uid,
+                    table_name: IMM_STRING_INDEX_TABLE_NAME,
+                })?,
+            );
+        }
+        Ok(uids)
+    }
+
+    /// Looks up the `node_type` nodes whose immutable int property
+    /// `property_name` is within `min..=max`, in ascending order of uid.
+    /// Signed and unsigned properties are both searched, one index partition
+    /// per bucket of the range.
+    pub async fn get_uids_by_immutable_int(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type: &NodeType,
+        property_name: &PropertyName,
+        min: i64,
+        max: i64,
+    ) -> Result<Vec<Uid>, PropertyQueryError> {
+        if !is_int_range_indexable(min, max) {
+            return Err(PropertyQueryError::IntRangeTooWide { min, max });
+        }
+
+        let mut uids = Vec::new();
+        for bucket in int_index_buckets(min, max) {
+            for table_name in [
+                IMM_I_64_BUCKETED_INDEX_TABLE_NAME,
+                IMM_U_64_BUCKETED_INDEX_TABLE_NAME,
+            ] {
+                let mut query = scylla::query::Query::from(format!(
+                    r"
+                    SELECT uid
+                    FROM tenant_graph_ks.{table_name}
+                    WHERE
+                        tenant_id = ? AND
+                        node_type = ? AND
+                        populated_field = ? AND
+                        bucket = ? AND
+                        value >= ? AND
+                        value <= ?;
+                    ",
+                ));
+
+                query.set_is_idempotent(true);
+                self.charge_read()?;
+
+                let query_result = self
+                    .scylla_client
+                    .execute(
+                        query,
+                        &(
+                            tenant_id,
+                            &node_type.value,
+                            &property_name.value,
+                            bucket,
+                            min,
+                            max,
+                        ),
+                    )
+                    .await?;
+
+                for row in query_result.rows_typed_or_empty::<(i64,)>() {
+                    let (uid,) = row?;
+                    uids.push(
+                        Uid::from_i64(uid)
+                            .ok_or(PropertyQueryError::InvalidIndexedUid { uid, table_name })?,
+                    );
+                }
+            }
+        }
+        // Rows are ordered by value, not uid
+        uids.sort_unstable();
+        uids.dedup();
+        Ok(uids)
+    }
+
+    /// The type of the node `uid`, if it exists
+    pub async fn get_node_type(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+    ) -> Result<Option<NodeType>, PropertyQueryError> {
+        let mut query = scylla::query::Query::from(
+            r"
+            SELECT node_type
+            FROM tenant_graph_ks.node_type
+            WHERE
+                tenant_id = ? AND
+                uid = ?
+            LIMIT 1;
+            ",
+        );
+
+        query.set_is_idempotent(true);
+        self.charge_read()?;
+
+        let query_result = self
+            .scylla_client
+            .execute(query, &(tenant_id, uid.as_i64()))
+            .await?;
+
+        Ok(query_result
+            .maybe_first_row_typed::<(String,)>()?
+            .map(|(node_type,)| NodeType { value: node_type }))
+    }
+
+    /// Every property of the node `uid`, of any type
+    pub async fn get_node_view(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+        node_type: NodeType,
+    ) -> Result<NodePropertiesView, PropertyQueryError> {
+        let mut node = NodePropertiesView::new(uid, node_type, StringProperties::default());
+
+        for (property_name, value) in self.get_all_string_properties(tenant_id, uid).await? {
+            node.add_string_property(property_name, value);
+        }
+        for table_name in [
+            IMM_I_64_TABLE_NAME,
+            MAX_I_64_TABLE_NAME,
+            MIN_I_64_TABLE_NAME,
+        ] {
+            for (property_name, value) in self
+                .get_all_int_properties(tenant_id, uid, table_name)
+                .await?
+            {
+                node.add_int_property(property_name, value);
+            }
+        }
+        for (property_name, value) in self.get_uint_properties(tenant_id, uid).await? {
+            node.add_uint_property(property_name, value);
+        }
+
+        Ok(node)
+    }
+
+    /// Every unsigned int property of the node `uid`, whatever its merge
+    /// behavior
+    pub async fn get_uint_properties(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+    ) -> Result<Vec<(PropertyName, u64)>, PropertyQueryError> {
+        let mut properties = Vec::new();
+        for table_name in [
+            IMM_U_64_TABLE_NAME,
+            MAX_U_64_TABLE_NAME,
+            MIN_U_64_TABLE_NAME,
+        ] {
+            for (property_name, value) in self
+                .get_all_int_properties(tenant_id, uid, table_name)
+                .await?
+            {
+                // Unsigned values are stored as their bit-equivalent bigint
+                properties.push((property_name, value as u64));
+            }
+        }
+        Ok(properties)
+    }
+
+    async fn get_all_string_properties(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+    ) -> Result<Vec<(PropertyName, String)>, PropertyQueryError> {
+        let mut query = scylla::query::Query::from(format!(
+            r"
+            SELECT populated_field, value
+            FROM tenant_graph_ks.{IMM_STRING_TABLE_NAME}
+            WHERE
+                tenant_id = ? AND
+                uid = ?;
+            ",
+        ));
+
+        query.set_is_idempotent(true);
+        self.charge_read()?;
+
+        let query_result = self
+            .scylla_client
+            .execute(query, &(tenant_id, uid.as_i64()))
+            .await?;
+
+        let mut properties = Vec::new();
+        for row in query_result.rows_typed_or_empty::<(String, String)>() {
+            let (populated_field, value) = row?;
+            properties.push((PropertyName::try_from(populated_field)?, value));
+        }
+        Ok(properties)
+    }
+
+    async fn get_all_int_properties(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+        table_name: &str,
+    ) -> Result<Vec<(PropertyName, i64)>, PropertyQueryError> {
+        let mut query = scylla::query::Query::from(format!(
+            r"
+            SELECT populated_field, value
+            FROM tenant_graph_ks.{table_name}
+            WHERE
+                tenant_id = ? AND
+                uid = ?;
+            ",
+        ));
+
+        query.set_is_idempotent(true);
+        self.charge_read()?;
+
+        let query_result = self
+            .scylla_client
+            .execute(query, &(tenant_id, uid.as_i64()))
+            .await?;
+
+        let mut properties = Vec::new();
+        for row in query_result.rows_typed_or_empty::<(String, i64)>() {
+            let (populated_field, value) = row?;
+            properties.push((PropertyName::try_from(populated_field)?, value));
+        }
+        Ok(properties)
+    }
+
+    /// Every edge leaving the node `uid`, whatever its name. Unlike
+    /// `get_edges` this isn't cached.
+    pub async fn get_all_edges(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+    ) -> Result<Vec<EdgeRow>, PropertyQueryError> {
+        let mut query = scylla::query::Query::from(
+            r"
+            SELECT f_edge_name, r_edge_name, destination_uid
+            FROM tenant_graph_ks.edges
+            WHERE
+                tenant_id = ? AND
+                source_uid = ?;
+            ",
+        );
+
+        query.set_is_idempotent(true);
+        self.charge_read()?;
+
+        let query_result = self
+            .scylla_client
+            .execute(query, &(tenant_id, uid.as_i64()))
+            .await?;
+
+        let mut edge_properties = self.get_edge_properties(tenant_id, uid, None).await?;
+
+        let mut edge_rows = Vec::new();
+        for row in query_result.rows_typed_or_empty::<(String, String, i64)>() {
+            let (f_edge_name, r_edge_name, destination_uid) = row?;
+            let properties = edge_properties
+                .remove(&(f_edge_name.clone(), destination_uid))
+                .unwrap_or_default();
+            edge_rows.push(EdgeRow {
+                source_uid: uid,
+                destination_uid: Uid::from_i64(destination_uid).ok_or_else(|| {
+                    PropertyQueryError::InvalidUidInDb {
+                        destination_uid,
+                        source_uid: uid,
+                        f_edge_name: f_edge_name.clone(),
+                    }
+                })?,
+                f_edge_name: EdgeName::try_from(f_edge_name)
+                    .map_err(PropertyQueryError::InvalidStoredEdgeName)?,
+                r_edge_name: EdgeName::try_from(r_edge_name)
+                    .map_err(PropertyQueryError::InvalidStoredEdgeName)?,
+                tenant_id,
+                properties,
+            });
+        }
+        Ok(edge_rows)
+    }
+
+    /// The properties of the edges leaving the node `uid`, keyed by their
+    /// name and destination uid. Only edges named `edge_name` are read, if
+    /// given.
+    async fn get_edge_properties(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+        edge_name: Option<&EdgeName>,
+    ) -> Result<FxHashMap<(String, i64), EdgePropertiesView>, PropertyQueryError> {
+        let mut edge_properties: FxHashMap<(String, i64), EdgePropertiesView> =
+            FxHashMap::default();
+
+        for (f_edge_name, destination_uid, property_name, value) in self
+            .get_all_edge_properties::<String>(tenant_id, uid, edge_name, EDGE_STRING_TABLE_NAME)
+            .await?
+        {
+            edge_properties
+                .entry((f_edge_name, destination_uid))
+                .or_default()
+                .add_string_property(property_name, value);
+        }
+        for (f_edge_name, destination_uid, property_name, value) in self
+            .get_all_edge_properties::<i64>(tenant_id, uid, edge_name, EDGE_I_64_TABLE_NAME)
+            .await?
+        {
+            edge_properties
+                .entry((f_edge_name, destination_uid))
+                .or_default()
+                .add_int_property(property_name, value);
+        }
+        for (f_edge_name, destination_uid, property_name, value) in self
+            .get_all_edge_properties::<i64>(tenant_id, uid, edge_name, EDGE_U_64_TABLE_NAME)
+            .await?
+        {
+            // Unsigned values are stored as their bit-equivalent bigint
+            edge_properties
+                .entry((f_edge_name, destination_uid))
+                .or_default()
+                .add_uint_property(property_name, value as u64);
+        }
+
+        Ok(edge_properties)
+    }
+
+    async fn get_all_edge_properties<V>(
+        &self,
+        tenant_id: uuid::Uuid,
+        uid: Uid,
+        edge_name: Option<&EdgeName>,
+        table_name: &str,
+    ) -> Result<Vec<(String, i64, PropertyName, V)>, PropertyQueryError>
+    where
+        V: scylla::cql_to_rust::FromCqlVal<scylla::frame::response::result::CqlValue>,
+    {
+        let edge_name_condition = match edge_name {
+            Some(_) => "AND f_edge_name = ?",
+            None => "",
+        };
+        let mut query = scylla::query::Query::from(format!(
+            r"
+            SELECT f_edge_name, destination_uid, populated_field, value
+            FROM tenant_graph_ks.{table_name}
+            WHERE
+                tenant_id = ? AND
+                source_uid = ?
+                {edge_name_condition};
+            ",
+        ));
+
+        query.set_is_idempotent(true);
+        self.charge_read()?;
+
+        let query_result = match edge_name {
+            Some(edge_name) => {
+                self.scylla_client
+                    .execute(query, &(tenant_id, uid.as_i64(), &edge_name.value))
+                    .await?
+            }
+            None => {
+                self.scylla_client
+                    .execute(query, &(tenant_id, uid.as_i64()))
+                    .await?
+            }
+        };
+
+        let mut properties = Vec::new();
+        for row in query_result.rows_typed_or_empty::<(String, i64, String, V)>() {
+            let (f_edge_name, destination_uid, populated_field, value) = row?;
+            properties.push((
+                f_edge_name,
+                destination_uid,
+                PropertyName::try_from(populated_field)?,
+                value,
+            ));
         }
+        Ok(properties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int_index_buckets() {
+        assert_eq!(int_index_buckets(0, (1 << 20) - 1), 0..=0);
+        assert_eq!(int_index_buckets(0, 1 << 20), 0..=1);
+        // Negative values round down, into buckets of their own
+        assert_eq!(int_index_buckets(-1, 0), -1..=0);
+        assert_eq!(
+            int_index_buckets(i64::MIN, i64::MAX),
+            -(1 << 43)..=(1 << 43) - 1
+        );
+    }
+
+    #[test]
+    fn test_is_int_range_indexable() {
+        assert!(is_int_range_indexable(0, 0));
+        assert!(is_int_range_indexable(0, (MAX_INT_INDEX_BUCKETS << 20) - 1));
+        assert!(!is_int_range_indexable(0, MAX_INT_INDEX_BUCKETS << 20));
+        assert!(!is_int_range_indexable(i64::MIN, i64::MAX));
     }
 }
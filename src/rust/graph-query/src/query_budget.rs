@@ -0,0 +1,90 @@
+use std::{
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use crate::config::QueryBudgetConfig;
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum QueryBudgetExceeded {
+    #[error("Query exceeded its budget of {0} database reads")]
+    Reads(u64),
+    #[error("Query exceeded its budget of {0} visited nodes")]
+    VisitedNodes(u64),
+    #[error("Query exceeded its deadline of {0:?}")]
+    Deadline(Duration),
+}
+
+/// Bounds the cost of a single query. The budget is shared between all of
+/// the tasks working on the query, so a clone charges against the original.
+#[derive(Clone, Debug)]
+pub struct QueryBudget {
+    max_reads: u64,
+    max_visited_nodes: u64,
+    timeout: Duration,
+    deadline: Instant,
+    reads: Arc<AtomicU64>,
+    visited_nodes: Arc<AtomicU64>,
+}
+
+impl QueryBudget {
+    pub fn new(max_reads: u64, max_visited_nodes: u64, timeout: Duration) -> Self {
+        Self {
+            max_reads,
+            max_visited_nodes,
+            timeout,
+            deadline: Instant::now() + timeout,
+            reads: Arc::new(AtomicU64::new(0)),
+            visited_nodes: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// A fresh budget for a query starting now
+    pub fn from_config(config: &QueryBudgetConfig) -> Self {
+        Self::new(
+            config.graph_query_max_reads,
+            config.graph_query_max_visited_nodes,
+            Duration::from_millis(config.graph_query_timeout_ms),
+        )
+    }
+
+    /// A budget that can't be exceeded, for callers that bound their own cost
+    pub fn unlimited() -> Self {
+        // Far enough in the future without risking `Instant` overflow
+        Self::new(u64::MAX, u64::MAX, Duration::from_secs(60 * 60 * 24 * 365))
+    }
+
+    /// Charges one database read against the budget
+    pub fn charge_read(&self) -> Result<(), QueryBudgetExceeded> {
+        self.check_deadline()?;
+        if self.reads.fetch_add(1, Ordering::Relaxed) >= self.max_reads {
+            return Err(QueryBudgetExceeded::Reads(self.max_reads));
+        }
+        Ok(())
+    }
+
+    /// Charges one node visit against the budget
+    pub fn charge_visit(&self) -> Result<(), QueryBudgetExceeded> {
+        self.check_deadline()?;
+        if self.visited_nodes.fetch_add(1, Ordering::Relaxed) >= self.max_visited_nodes {
+            return Err(QueryBudgetExceeded::VisitedNodes(self.max_visited_nodes));
+        }
+        Ok(())
+    }
+
+    fn check_deadline(&self) -> Result<(), QueryBudgetExceeded> {
+        if Instant::now() >= self.deadline {
+            return Err(QueryBudgetExceeded::Deadline(self.timeout));
+        }
+        Ok(())
+    }
+}
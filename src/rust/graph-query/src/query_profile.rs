@@ -0,0 +1,114 @@
+use std::{
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use rust_proto::graplinc::grapl::{
+    api::graph_query::v1beta1::messages::{
+        QueryId,
+        QueryIdProfile,
+        QueryProfile,
+    },
+    common::v1beta1::types::PropertyName,
+};
+use rustc_hash::FxHashMap;
+
+/// Collects a `QueryProfile` for a single query. Like the `QueryBudget`,
+/// the statistics are shared between all of the tasks working on the query.
+///
+/// Statistics are attributed to the node query set with `for_query_id`, and
+/// are dropped if there isn't one.
+#[derive(Clone)]
+pub struct QueryProfiler {
+    started: Instant,
+    query_id: Option<QueryId>,
+    stats: Arc<Mutex<FxHashMap<QueryId, QueryIdProfile>>>,
+}
+
+impl Default for QueryProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueryProfiler {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            query_id: None,
+            stats: Arc::new(Mutex::new(FxHashMap::default())),
+        }
+    }
+
+    /// A profiler sharing this one's statistics, attributing them to `query_id`
+    pub fn for_query_id(&self, query_id: QueryId) -> Self {
+        Self {
+            started: self.started,
+            query_id: Some(query_id),
+            stats: self.stats.clone(),
+        }
+    }
+
+    pub fn record_examined(&self) {
+        self.update(|stats| stats.nodes_examined += 1);
+    }
+
+    pub fn record_matched(&self) {
+        self.update(|stats| stats.nodes_matched += 1);
+    }
+
+    pub fn record_rejected(&self, property_name: &PropertyName) {
+        self.update(|stats| {
+            *stats
+                .rejected_by_property
+                .entry(property_name.clone())
+                .or_default() += 1
+        });
+    }
+
+    pub fn record_edges_fetched(&self, count: u64) {
+        self.update(|stats| stats.edges_fetched += count);
+    }
+
+    pub fn record_read(&self) {
+        self.update(|stats| stats.database_reads += 1);
+    }
+
+    pub fn record_elapsed(&self, elapsed: Duration) {
+        self.update(|stats| stats.elapsed += elapsed);
+    }
+
+    fn update(&self, f: impl FnOnce(&mut QueryIdProfile)) {
+        let query_id = match self.query_id {
+            Some(query_id) => query_id,
+            None => return,
+        };
+        let mut stats = self.stats.lock().unwrap();
+        f(stats.entry(query_id).or_insert_with(|| QueryIdProfile {
+            query_id,
+            nodes_examined: 0,
+            nodes_matched: 0,
+            rejected_by_property: FxHashMap::default(),
+            edges_fetched: 0,
+            database_reads: 0,
+            elapsed: Duration::ZERO,
+        }));
+    }
+
+    /// The profile of everything recorded so far
+    pub fn finish(&self) -> QueryProfile {
+        let mut query_ids: Vec<QueryIdProfile> =
+            self.stats.lock().unwrap().values().cloned().collect();
+        query_ids.sort_unstable_by_key(|stats| stats.query_id.value);
+        QueryProfile {
+            query_ids,
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
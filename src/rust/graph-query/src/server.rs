@@ -1,14 +1,25 @@
 use std::sync::Arc;
 
+use futures::{
+    stream::BoxStream,
+    StreamExt,
+    TryStreamExt,
+};
 use rust_proto::graplinc::grapl::api::{
     graph_query::v1beta1::{
         messages::{
+            GetNeighborhoodRequest,
+            GetNeighborhoodResponse,
             GraphQuery,
             MatchedGraphWithUid,
             MaybeMatchWithUid,
             NoMatchWithUid,
+            QueryAllGraphsWithUidRequest,
+            QueryAllGraphsWithUidResponse,
             QueryGraphFromUidRequest,
             QueryGraphFromUidResponse,
+            QueryGraphRequest,
+            QueryGraphResponse,
             QueryGraphWithUidRequest,
             QueryGraphWithUidResponse,
         },
@@ -19,15 +30,27 @@ use rust_proto::graplinc::grapl::api::{
 use scylla::CachingSession;
 
 use crate::{
+    config::QueryBudgetConfig,
+    deployed_schema::DeployedSchemaResolver,
     graph_query::{
+        find_root_candidates,
+        match_root_candidates,
         query_graph,
+        query_graph_from_index,
         GraphQueryError,
     },
+    neighborhood::{
+        get_neighborhood,
+        NeighborhoodError,
+    },
     node_query::{
         fetch_node_with_edges,
         NodeQueryError,
     },
+    property_cache::PropertyCache,
     property_query::PropertyQueryExecutor,
+    query_budget::QueryBudget,
+    query_profile::QueryProfiler,
     short_circuit::ShortCircuit,
     visited::Visited,
 };
@@ -38,14 +61,34 @@ pub enum GraphQueryServiceError {
     GraphQueryError(#[from] GraphQueryError),
     #[error("NodeQueryError {0}")]
     NodeQueryError(#[from] NodeQueryError),
+    #[error("NeighborhoodError {0}")]
+    NeighborhoodError(#[from] NeighborhoodError),
 }
 
 impl From<GraphQueryServiceError> for Status {
     fn from(gqs_err: GraphQueryServiceError) -> Self {
         type GQSErr = GraphQueryServiceError;
+        let budget_exceeded = match &gqs_err {
+            GQSErr::GraphQueryError(e) => e.budget_exceeded(),
+            GQSErr::NodeQueryError(e) => e.budget_exceeded(),
+            GQSErr::NeighborhoodError(e) => e.budget_exceeded(),
+        };
+        if let Some(e) = budget_exceeded {
+            return Status::resource_exhausted(e.to_string());
+        }
         match gqs_err {
+            GQSErr::GraphQueryError(e) if e.is_invalid_query() => {
+                Status::invalid_argument(e.to_string())
+            }
             GQSErr::GraphQueryError(e) => Status::unknown(e.to_string()),
+            GQSErr::NodeQueryError(e) if e.is_invalid_query() => {
+                Status::invalid_argument(e.to_string())
+            }
             GQSErr::NodeQueryError(e) => Status::unknown(e.to_string()),
+            GQSErr::NeighborhoodError(e @ NeighborhoodError::NodeNotFound(_)) => {
+                Status::not_found(e.to_string())
+            }
+            GQSErr::NeighborhoodError(e) => Status::unknown(e.to_string()),
         }
     }
 }
@@ -53,14 +96,44 @@ impl From<GraphQueryServiceError> for Status {
 #[derive(Clone)]
 pub struct GraphQueryService {
     property_query_executor: PropertyQueryExecutor,
+    query_budget_config: QueryBudgetConfig,
 }
 
 impl GraphQueryService {
-    pub fn new(scylla_client: Arc<CachingSession>) -> Self {
+    pub fn new(
+        scylla_client: Arc<CachingSession>,
+        property_cache: PropertyCache,
+        deployed_schema_resolver: DeployedSchemaResolver,
+        query_budget_config: QueryBudgetConfig,
+    ) -> Self {
         Self {
-            property_query_executor: PropertyQueryExecutor::new(scylla_client),
+            property_query_executor: PropertyQueryExecutor::new(
+                scylla_client,
+                property_cache,
+                deployed_schema_resolver,
+            ),
+            query_budget_config,
         }
     }
+
+    /// An executor with a fresh budget, for a single query
+    fn property_query_executor(&self) -> PropertyQueryExecutor {
+        self.property_query_executor
+            .with_budget(QueryBudget::from_config(&self.query_budget_config))
+    }
+
+    /// An executor for a single query, along with its profiler if `profile`
+    fn profiled_executor(&self, profile: bool) -> (PropertyQueryExecutor, Option<QueryProfiler>) {
+        let property_query_executor = self.property_query_executor();
+        if !profile {
+            return (property_query_executor, None);
+        }
+        let profiler = QueryProfiler::new();
+        (
+            property_query_executor.with_profiler(profiler.clone()),
+            Some(profiler),
+        )
+    }
 }
 
 #[async_trait::async_trait]
@@ -72,30 +145,28 @@ impl GraphQueryApi for GraphQueryService {
         request: QueryGraphWithUidRequest,
     ) -> Result<QueryGraphWithUidResponse, GraphQueryServiceError> {
         let node_uid = request.node_uid;
+        let (property_query_executor, profiler) = self.profiled_executor(request.profile);
 
         let graph_query: GraphQuery = request.graph_query;
         let graph = query_graph(
             &graph_query,
             node_uid,
             request.tenant_id,
-            self.property_query_executor.clone(),
+            property_query_executor,
         )
         .await?;
 
-        let (matched_graph, root_uid) = match graph {
-            Some((graph, root_uid)) => (graph, root_uid),
-            None => {
-                return Ok(QueryGraphWithUidResponse {
-                    maybe_match: MaybeMatchWithUid::Missed(NoMatchWithUid {}),
-                })
-            }
-        };
-
-        Ok(QueryGraphWithUidResponse {
-            maybe_match: MaybeMatchWithUid::Matched(MatchedGraphWithUid {
+        let maybe_match = match graph {
+            Some((matched_graph, root_uid)) => MaybeMatchWithUid::Matched(MatchedGraphWithUid {
                 matched_graph,
                 root_uid,
             }),
+            None => MaybeMatchWithUid::Missed(NoMatchWithUid {}),
+        };
+
+        Ok(QueryGraphWithUidResponse {
+            maybe_match,
+            profile: profiler.map(|profiler| profiler.finish()),
         })
     }
 
@@ -104,6 +175,7 @@ impl GraphQueryApi for GraphQueryService {
         request: QueryGraphFromUidRequest,
     ) -> Result<QueryGraphFromUidResponse, GraphQueryServiceError> {
         let node_uid = request.node_uid;
+        let (property_query_executor, profiler) = self.profiled_executor(request.profile);
 
         let graph_query: GraphQuery = request.graph_query;
         let node_query = &graph_query
@@ -118,7 +190,7 @@ impl GraphQueryApi for GraphQueryService {
             &graph_query,
             node_uid,
             request.tenant_id,
-            self.property_query_executor.clone(),
+            property_query_executor,
             visited,
             x_short_circuit,
             &mut None,
@@ -127,6 +199,90 @@ impl GraphQueryApi for GraphQueryService {
 
         Ok(QueryGraphFromUidResponse {
             matched_graph: graph,
+            profile: profiler.map(|profiler| profiler.finish()),
+        })
+    }
+
+    async fn query_all_graphs_with_uid(
+        &self,
+        request: QueryAllGraphsWithUidRequest,
+    ) -> Result<
+        BoxStream<'static, Result<QueryAllGraphsWithUidResponse, GraphQueryServiceError>>,
+        GraphQueryServiceError,
+    > {
+        let node_uid = request.node_uid;
+        let tenant_id = request.tenant_id;
+
+        // Finding and matching the candidates share a single budget
+        let property_query_executor = self.property_query_executor();
+
+        let graph_query: GraphQuery = request.graph_query;
+        let mut candidates = find_root_candidates(
+            &graph_query,
+            node_uid,
+            tenant_id,
+            property_query_executor.clone(),
+        )
+        .await?;
+        if let Some(after_root_uid) = request.after_root_uid {
+            candidates.retain(|root_uid| *root_uid > after_root_uid);
+        }
+
+        let matches = match_root_candidates(
+            Arc::new(graph_query),
+            candidates,
+            node_uid,
+            tenant_id,
+            property_query_executor,
+        )
+        .map_ok(|(matched_graph, root_uid)| QueryAllGraphsWithUidResponse {
+            matched: MatchedGraphWithUid {
+                matched_graph,
+                root_uid,
+            },
+        })
+        .map_err(GraphQueryServiceError::from);
+
+        Ok(match request.limit {
+            Some(limit) => matches.take(limit as usize).boxed(),
+            None => matches.boxed(),
+        })
+    }
+
+    async fn query_graph(
+        &self,
+        request: QueryGraphRequest,
+    ) -> Result<QueryGraphResponse, GraphQueryServiceError> {
+        let graph_query: GraphQuery = request.graph_query;
+        let matches = query_graph_from_index(
+            &graph_query,
+            request.tenant_id,
+            self.property_query_executor(),
+            request.limit.map(|limit| limit as usize),
+        )
+        .await?;
+
+        Ok(QueryGraphResponse {
+            matches: matches
+                .into_iter()
+                .map(|(matched_graph, root_uid)| MatchedGraphWithUid {
+                    matched_graph,
+                    root_uid,
+                })
+                .collect(),
+        })
+    }
+
+    async fn get_neighborhood(
+        &self,
+        request: GetNeighborhoodRequest,
+    ) -> Result<GetNeighborhoodResponse, GraphQueryServiceError> {
+        let (neighborhood, truncated) =
+            get_neighborhood(&request, self.property_query_executor()).await?;
+
+        Ok(GetNeighborhoodResponse {
+            neighborhood,
+            truncated,
         })
     }
 }
@@ -38,6 +38,16 @@ impl Visited {
         }
     }
 
+    /// A copy of the edges visited so far with its own short circuit, for
+    /// evaluating a subquery whose failure must not fail the whole query.
+    pub fn fork(&self) -> Self {
+        let already_visited = (*self.already_visited.lock().unwrap()).clone();
+        Self {
+            short_circuit: Arc::new(AtomicBool::new(false)),
+            already_visited: Arc::new(Mutex::new(already_visited)),
+        }
+    }
+
     pub fn get_short_circuit(&self) -> bool {
         self.short_circuit.as_ref().load(Ordering::Acquire)
     }
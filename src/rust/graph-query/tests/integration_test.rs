@@ -1,15 +1,25 @@
 #![cfg(feature = "integration_tests")]
+use std::{
+    collections::HashMap,
+    time::Duration,
+};
+
 use bytes::Bytes;
 use figment::{
     providers::Env,
     Figment,
 };
+use futures::TryStreamExt;
 use graph_query::node_query::NodeQuery;
 use rust_proto::graplinc::grapl::{
     api::{
         client::Connect,
         graph::v1beta1::{
+            DecrementOnlyUintProp,
+            IdentifiedGraph,
+            IdentifiedNode,
             ImmutableStrProp,
+            ImmutableUintProp,
             IncrementOnlyUintProp,
             NodeProperty,
             Property,
@@ -21,12 +31,21 @@ use rust_proto::graplinc::grapl::{
         graph_query::v1beta1::{
             client::GraphQueryClient,
             messages::{
+                AndIntFilters,
+                GetNeighborhoodRequest,
+                GraphQuery,
+                IntFilter,
+                IntOperation,
                 MatchedGraphWithUid,
                 MaybeMatchWithUid,
                 NodePropertyQuery,
+                OrIntFilters,
+                QueryAllGraphsWithUidRequest,
                 QueryGraphFromUidRequest,
+                QueryGraphRequest,
                 QueryGraphWithUidRequest,
                 StringCmp,
+                TimeWindow,
             },
         },
         graph_schema_manager::v1beta1::{
@@ -41,9 +60,15 @@ use rust_proto::graplinc::grapl::{
     common::v1beta1::types::{
         EdgeName,
         NodeType,
+        PropertyName,
+        Uid,
     },
 };
 
+/// A little longer than graph-query's default deletion check interval, after
+/// which deletions are visible to queries
+const DELETION_CHECK_INTERVAL: Duration = Duration::from_millis(1500);
+
 async fn provision_example_graph_schema(tenant_id: uuid::Uuid) -> eyre::Result<()> {
     let graph_schema_manager_client_config = Figment::new()
         .merge(Env::prefixed("GRAPH_SCHEMA_MANAGER_CLIENT_"))
@@ -63,6 +88,7 @@ async fn provision_example_graph_schema(tenant_id: uuid::Uuid) -> eyre::Result<(
             schema: get_example_graphql_schema().unwrap(),
             schema_type: graph_schema_manager_api::SchemaType::GraphqlV0,
             schema_version: 0,
+            dry_run: false,
         })
         .await?;
     Ok(())
@@ -193,6 +219,7 @@ async fn test_query_two_attached_nodes() -> eyre::Result<()> {
             tenant_id: tenant_id.into(),
             node_uid: first_node_uid,
             graph_query,
+            profile: false,
         })
         .await?;
 
@@ -242,6 +269,7 @@ async fn test_query_two_attached_nodes() -> eyre::Result<()> {
             tenant_id: tenant_id.into(),
             node_uid: first_node_uid,
             graph_query,
+            profile: false,
         })
         .await?;
 
@@ -331,4 +359,1882 @@ async fn test_set_node_property() -> eyre::Result<()> {
     Ok(())
 }
 
-// TODO: test `with_edge_to`
+async fn create_process(
+    graph_mutation_client: &mut GraphMutationClient,
+    tenant_id: uuid::Uuid,
+    process_name: &str,
+) -> eyre::Result<Uid> {
+    let process_node_type = NodeType::try_from("Process").unwrap();
+
+    let mutation::CreateNodeResponse { uid } = graph_mutation_client
+        .create_node(mutation::CreateNodeRequest {
+            tenant_id,
+            node_type: process_node_type.clone(),
+        })
+        .await?;
+
+    graph_mutation_client
+        .set_node_property(mutation::SetNodePropertyRequest {
+            tenant_id,
+            uid,
+            node_type: process_node_type,
+            property_name: "process_name".try_into()?,
+            property: NodeProperty {
+                property: Property::ImmutableStrProp(ImmutableStrProp {
+                    prop: process_name.into(),
+                }),
+            },
+        })
+        .await?;
+
+    Ok(uid)
+}
+
+async fn create_file_created_by(
+    graph_mutation_client: &mut GraphMutationClient,
+    tenant_id: uuid::Uuid,
+    process_name: &str,
+) -> eyre::Result<Uid> {
+    let process_uid = create_process(graph_mutation_client, tenant_id, process_name).await?;
+
+    let mutation::CreateNodeResponse { uid } = graph_mutation_client
+        .create_node(mutation::CreateNodeRequest {
+            tenant_id,
+            node_type: NodeType::try_from("File").unwrap(),
+        })
+        .await?;
+
+    graph_mutation_client
+        .create_edge(mutation::CreateEdgeRequest {
+            edge_name: EdgeName::try_from("created_files")?,
+            tenant_id,
+            from_uid: process_uid,
+            to_uid: uid,
+            source_node_type: NodeType::try_from("Process").unwrap(),
+        })
+        .await?;
+
+    Ok(uid)
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_negated_edge() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+
+    let parent_uid = create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    let child_uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+    let orphan_uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+
+    graph_mutation_client
+        .create_edge(mutation::CreateEdgeRequest {
+            edge_name: children.clone(),
+            tenant_id,
+            from_uid: parent_uid,
+            to_uid: child_uid,
+            source_node_type: process_node_type.clone(),
+        })
+        .await?;
+
+    // A cmd.exe process with no parent
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("cmd.exe".to_owned(), false)],
+        )
+        .without_edge_to(
+            parent.clone(),
+            children.clone(),
+            process_node_type.clone(),
+            |_| {},
+        )
+        .build();
+
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: orphan_uid,
+            graph_query: graph_query.clone(),
+            profile: false,
+        })
+        .await?;
+
+    let matched_graph = response.matched_graph.expect("Expected a matched graph");
+    assert_eq!(matched_graph.nodes.len(), 1);
+    assert!(matched_graph.nodes.contains_key(&orphan_uid));
+    assert_eq!(matched_graph.edges.len(), 0);
+
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: child_uid,
+            graph_query: graph_query.clone(),
+            profile: false,
+        })
+        .await?;
+    assert!(response.matched_graph.is_none());
+
+    // The parent must not be used as a starting point for a match
+    let response = graph_query_client
+        .query_graph_with_uid(QueryGraphWithUidRequest {
+            tenant_id,
+            node_uid: parent_uid,
+            graph_query,
+            profile: false,
+        })
+        .await?;
+    assert!(matches!(response.maybe_match, MaybeMatchWithUid::Missed(_)));
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_optional_edge() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+
+    let parent_uid = create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    let child_uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+    let childless_uid =
+        create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+
+    graph_mutation_client
+        .create_edge(mutation::CreateEdgeRequest {
+            edge_name: children.clone(),
+            tenant_id,
+            from_uid: parent_uid,
+            to_uid: child_uid,
+            source_node_type: process_node_type.clone(),
+        })
+        .await?;
+
+    // An explorer.exe process, along with its cmd.exe children if it has any
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("explorer.exe".to_owned(), false)],
+        )
+        .with_optional_edge_to(
+            children.clone(),
+            parent.clone(),
+            process_node_type.clone(),
+            |child| {
+                child.with_string_comparisons(
+                    "process_name".try_into().unwrap(),
+                    vec![StringCmp::Eq("cmd.exe".to_owned(), false)],
+                );
+            },
+        )
+        .build();
+
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: parent_uid,
+            graph_query: graph_query.clone(),
+            profile: false,
+        })
+        .await?;
+
+    let matched_graph = response.matched_graph.expect("Expected a matched graph");
+    assert_eq!(matched_graph.nodes.len(), 2);
+    assert!(matched_graph.nodes.contains_key(&child_uid));
+    assert_eq!(matched_graph.edges.len(), 2); // forward and reverse edge
+
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: childless_uid,
+            graph_query,
+            profile: false,
+        })
+        .await?;
+
+    let matched_graph = response.matched_graph.expect("Expected a matched graph");
+    assert_eq!(matched_graph.nodes.len(), 1);
+    assert!(matched_graph.nodes.contains_key(&childless_uid));
+    assert_eq!(matched_graph.edges.len(), 0);
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_any_of_edges() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let file_node_type = NodeType::try_from("File").unwrap();
+    let created_files = EdgeName::try_from("created_files")?;
+    let created_by_process = EdgeName::try_from("created_by_process")?;
+
+    let word_file_uid =
+        create_file_created_by(&mut graph_mutation_client, tenant_id, "winword.exe").await?;
+    let excel_file_uid =
+        create_file_created_by(&mut graph_mutation_client, tenant_id, "excel.exe").await?;
+    let notepad_file_uid =
+        create_file_created_by(&mut graph_mutation_client, tenant_id, "notepad.exe").await?;
+
+    // A file created by either winword.exe or excel.exe
+    let created_by = |process_name: &'static str| {
+        move |process: &mut NodeQuery| {
+            process.with_string_comparisons(
+                "process_name".try_into().unwrap(),
+                vec![StringCmp::Eq(process_name.to_owned(), false)],
+            );
+        }
+    };
+    let graph_query = NodeQuery::root(file_node_type.clone())
+        .with_any_of_edges(|any_of| {
+            any_of
+                .with_edge_to(
+                    created_by_process.clone(),
+                    created_files.clone(),
+                    process_node_type.clone(),
+                    created_by("winword.exe"),
+                )
+                .with_edge_to(
+                    created_by_process.clone(),
+                    created_files.clone(),
+                    process_node_type.clone(),
+                    created_by("excel.exe"),
+                );
+        })
+        .build();
+
+    for file_uid in [word_file_uid, excel_file_uid] {
+        let response = graph_query_client
+            .query_graph_from_uid(QueryGraphFromUidRequest {
+                tenant_id,
+                node_uid: file_uid,
+                graph_query: graph_query.clone(),
+                profile: false,
+            })
+            .await?;
+
+        let matched_graph = response.matched_graph.expect("Expected a matched graph");
+        assert_eq!(matched_graph.nodes.len(), 2);
+        assert!(matched_graph.nodes.contains_key(&file_uid));
+    }
+
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: notepad_file_uid,
+            graph_query,
+            profile: false,
+        })
+        .await?;
+    assert!(response.matched_graph.is_none());
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_all_graphs_with_uid() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+
+    let parent_uid = create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    let mut child_uids = vec![];
+    for _ in 0..3 {
+        let child_uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+        graph_mutation_client
+            .create_edge(mutation::CreateEdgeRequest {
+                edge_name: children.clone(),
+                tenant_id,
+                from_uid: parent_uid,
+                to_uid: child_uid,
+                source_node_type: process_node_type.clone(),
+            })
+            .await?;
+        child_uids.push(child_uid);
+    }
+    child_uids.sort();
+
+    // Every cmd.exe process spawned by explorer.exe
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("cmd.exe".to_owned(), false)],
+        )
+        .with_edge_to(
+            parent.clone(),
+            children.clone(),
+            process_node_type.clone(),
+            |parent| {
+                parent.with_string_comparisons(
+                    "process_name".try_into().unwrap(),
+                    vec![StringCmp::Eq("explorer.exe".to_owned(), false)],
+                );
+            },
+        )
+        .build();
+
+    let request_timeout = Duration::from_secs(30);
+
+    // The first page holds the two lowest root uids, each matched once
+    let first_page: Vec<MatchedGraphWithUid> = graph_query_client
+        .query_all_graphs_with_uid(
+            request_timeout,
+            QueryAllGraphsWithUidRequest {
+                tenant_id,
+                node_uid: parent_uid,
+                graph_query: graph_query.clone(),
+                limit: Some(2),
+                after_root_uid: None,
+            },
+        )
+        .await?
+        .map_ok(|response| response.matched)
+        .try_collect()
+        .await?;
+
+    let first_page_roots: Vec<Uid> = first_page.iter().map(|m| m.root_uid).collect();
+    assert_eq!(first_page_roots, child_uids[..2]);
+    for matched in first_page.iter() {
+        assert_eq!(matched.matched_graph.nodes.len(), 2);
+        assert!(matched.matched_graph.nodes.contains_key(&parent_uid));
+    }
+
+    // The second page picks up after the last root uid of the first
+    let second_page: Vec<MatchedGraphWithUid> = graph_query_client
+        .query_all_graphs_with_uid(
+            request_timeout,
+            QueryAllGraphsWithUidRequest {
+                tenant_id,
+                node_uid: parent_uid,
+                graph_query: graph_query.clone(),
+                limit: Some(2),
+                after_root_uid: first_page_roots.last().copied(),
+            },
+        )
+        .await?
+        .map_ok(|response| response.matched)
+        .try_collect()
+        .await?;
+
+    let second_page_roots: Vec<Uid> = second_page.iter().map(|m| m.root_uid).collect();
+    assert_eq!(second_page_roots, child_uids[2..]);
+
+    // Starting from one of the children only finds the match rooted there
+    let from_child: Vec<MatchedGraphWithUid> = graph_query_client
+        .query_all_graphs_with_uid(
+            request_timeout,
+            QueryAllGraphsWithUidRequest {
+                tenant_id,
+                node_uid: child_uids[0],
+                graph_query,
+                limit: None,
+                after_root_uid: None,
+            },
+        )
+        .await?
+        .map_ok(|response| response.matched)
+        .try_collect()
+        .await?;
+
+    assert_eq!(from_child.len(), 1);
+    assert_eq!(from_child[0].root_uid, child_uids[0]);
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_graph_from_indexed_property() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+
+    let parent_uid = create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    let spawned_uid = create_process(&mut graph_mutation_client, tenant_id, "mimikatz.exe").await?;
+    let orphan_uid = create_process(&mut graph_mutation_client, tenant_id, "mimikatz.exe").await?;
+    create_process(&mut graph_mutation_client, tenant_id, "svchost.exe").await?;
+
+    graph_mutation_client
+        .create_edge(mutation::CreateEdgeRequest {
+            edge_name: children.clone(),
+            tenant_id,
+            from_uid: parent_uid,
+            to_uid: spawned_uid,
+            source_node_type: process_node_type.clone(),
+        })
+        .await?;
+
+    // Every mimikatz.exe process
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("mimikatz.exe".to_owned(), false)],
+        )
+        .build();
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: graph_query.clone(),
+            limit: None,
+        })
+        .await?;
+
+    let mut expected_roots = vec![spawned_uid, orphan_uid];
+    expected_roots.sort();
+    let roots: Vec<Uid> = response.matches.iter().map(|m| m.root_uid).collect();
+    assert_eq!(roots, expected_roots);
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query,
+            limit: Some(1),
+        })
+        .await?;
+    assert_eq!(response.matches.len(), 1);
+
+    // Only the mimikatz.exe process spawned by explorer.exe
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("mimikatz.exe".to_owned(), false)],
+        )
+        .with_edge_to(
+            parent.clone(),
+            children.clone(),
+            process_node_type.clone(),
+            |parent| {
+                parent.with_string_comparisons(
+                    "process_name".try_into().unwrap(),
+                    vec![StringCmp::Eq("explorer.exe".to_owned(), false)],
+                );
+            },
+        )
+        .build();
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query,
+            limit: None,
+        })
+        .await?;
+
+    assert_eq!(response.matches.len(), 1);
+    let MatchedGraphWithUid {
+        matched_graph,
+        root_uid,
+    } = &response.matches[0];
+    assert_eq!(*root_uid, spawned_uid);
+    assert!(matched_graph.nodes.contains_key(&parent_uid));
+
+    // Without an equality predicate there's nothing to look up
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Contains("mimikatz".to_owned(), false)],
+        )
+        .build();
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query,
+            limit: None,
+        })
+        .await;
+    assert!(response.is_err());
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_graph_from_indexed_int_range() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+
+    // Values in different index buckets
+    let mut uids = Vec::new();
+    for process_id in [100, 200, 5_000_000] {
+        let uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+        graph_mutation_client
+            .set_node_property(mutation::SetNodePropertyRequest {
+                tenant_id,
+                uid,
+                node_type: process_node_type.clone(),
+                property_name: "process_id".try_into()?,
+                property: NodeProperty {
+                    property: Property::ImmutableUintProp(ImmutableUintProp { prop: process_id }),
+                },
+            })
+            .await?;
+        uids.push(uid);
+    }
+
+    let int_query = |int_filters: Vec<IntFilter>| {
+        let mut graph_query = NodeQuery::root(process_node_type.clone()).build();
+        let root_query_id = graph_query.root_query_id;
+        graph_query
+            .node_property_queries
+            .get_mut(&root_query_id)
+            .expect("root query")
+            .int_filters
+            .insert(
+                PropertyName::try_from("process_id").unwrap(),
+                OrIntFilters {
+                    and_int_filters: vec![AndIntFilters { int_filters }],
+                },
+            );
+        graph_query
+    };
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: int_query(vec![
+                IntFilter {
+                    operation: IntOperation::GreaterThanOrEqual,
+                    value: 150,
+                    negated: false,
+                },
+                IntFilter {
+                    operation: IntOperation::LessThanOrEqual,
+                    value: 5_000_000,
+                    negated: false,
+                },
+            ]),
+            limit: None,
+        })
+        .await?;
+
+    let mut expected_roots = vec![uids[1], uids[2]];
+    expected_roots.sort();
+    let roots: Vec<Uid> = response.matches.iter().map(|m| m.root_uid).collect();
+    assert_eq!(roots, expected_roots);
+
+    // A range without an upper bound would scan the property's whole index
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: int_query(vec![IntFilter {
+                operation: IntOperation::GreaterThan,
+                value: 150,
+                negated: false,
+            }]),
+            limit: None,
+        })
+        .await;
+    assert!(response.is_err());
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: int_query(vec![IntFilter {
+                operation: IntOperation::Has,
+                value: 0,
+                negated: false,
+            }]),
+            limit: None,
+        })
+        .await;
+    assert!(response.is_err());
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_graph_by_max_property() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+
+    // last_seen_at is a max property, so its stored value moves on from the
+    // first one written
+    let mut uids = Vec::new();
+    for last_seen_ats in [vec![100, 200], vec![100]] {
+        let uid = create_process(&mut graph_mutation_client, tenant_id, "lsass.exe").await?;
+        for last_seen_at in last_seen_ats {
+            graph_mutation_client
+                .set_node_property(mutation::SetNodePropertyRequest {
+                    tenant_id,
+                    uid,
+                    node_type: process_node_type.clone(),
+                    property_name: "last_seen_at".try_into()?,
+                    property: NodeProperty {
+                        property: Property::IncrementOnlyUintProp(IncrementOnlyUintProp {
+                            prop: last_seen_at,
+                        }),
+                    },
+                })
+                .await?;
+        }
+        uids.push(uid);
+    }
+
+    let last_seen_at_query = |graph_query: &mut GraphQuery| {
+        let root_query_id = graph_query.root_query_id;
+        graph_query
+            .node_property_queries
+            .get_mut(&root_query_id)
+            .expect("root query")
+            .int_filters
+            .insert(
+                PropertyName::try_from("last_seen_at").unwrap(),
+                OrIntFilters {
+                    and_int_filters: vec![AndIntFilters {
+                        int_filters: vec![IntFilter {
+                            operation: IntOperation::Equal,
+                            value: 100,
+                            negated: false,
+                        }],
+                    }],
+                },
+            );
+    };
+
+    // Max properties aren't indexed, so there's nothing to look up
+    let mut graph_query = NodeQuery::root(process_node_type.clone()).build();
+    last_seen_at_query(&mut graph_query);
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query,
+            limit: None,
+        })
+        .await;
+    assert!(response.is_err());
+
+    // Looked up by name instead, only the process whose value is still 100
+    // matches
+    let mut graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("lsass.exe".to_owned(), false)],
+        )
+        .build();
+    last_seen_at_query(&mut graph_query);
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query,
+            limit: None,
+        })
+        .await?;
+
+    let roots: Vec<Uid> = response.matches.iter().map(|m| m.root_uid).collect();
+    assert_eq!(roots, vec![uids[1]]);
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_hub_node() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+
+    // More children than are matched at once, so expansion has to queue them
+    let parent_uid = create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    let mut child_uids = Vec::new();
+    for _ in 0..40 {
+        let child_uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+        graph_mutation_client
+            .create_edge(mutation::CreateEdgeRequest {
+                edge_name: children.clone(),
+                tenant_id,
+                from_uid: parent_uid,
+                to_uid: child_uid,
+                source_node_type: process_node_type.clone(),
+            })
+            .await?;
+        child_uids.push(child_uid);
+    }
+
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("explorer.exe".to_owned(), false)],
+        )
+        .with_edge_to(
+            children.clone(),
+            parent.clone(),
+            process_node_type.clone(),
+            |child| {
+                child.with_string_comparisons(
+                    "process_name".try_into().unwrap(),
+                    vec![StringCmp::Eq("cmd.exe".to_owned(), false)],
+                );
+            },
+        )
+        .build();
+
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: parent_uid,
+            graph_query,
+            profile: false,
+        })
+        .await?;
+
+    let matched_graph = response.matched_graph.expect("Expected a matched graph");
+    assert_eq!(matched_graph.nodes.len(), child_uids.len() + 1);
+    for child_uid in child_uids.iter() {
+        assert!(matched_graph.nodes.contains_key(child_uid));
+    }
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_profile() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+    let process_name = PropertyName::try_from("process_name")?;
+
+    let parent_uid = create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    for child_name in ["cmd.exe", "notepad.exe"] {
+        let child_uid = create_process(&mut graph_mutation_client, tenant_id, child_name).await?;
+        graph_mutation_client
+            .create_edge(mutation::CreateEdgeRequest {
+                edge_name: children.clone(),
+                tenant_id,
+                from_uid: parent_uid,
+                to_uid: child_uid,
+                source_node_type: process_node_type.clone(),
+            })
+            .await?;
+    }
+
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            process_name.clone(),
+            vec![StringCmp::Eq("explorer.exe".to_owned(), false)],
+        )
+        .with_optional_edge_to(
+            children.clone(),
+            parent.clone(),
+            process_node_type.clone(),
+            |child| {
+                child.with_string_comparisons(
+                    "process_name".try_into().unwrap(),
+                    vec![StringCmp::Eq("cmd.exe".to_owned(), false)],
+                );
+            },
+        )
+        .build();
+    let root_query_id = graph_query.root_query_id;
+
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: parent_uid,
+            graph_query: graph_query.clone(),
+            profile: true,
+        })
+        .await?;
+
+    assert_eq!(
+        response
+            .matched_graph
+            .expect("Expected a match")
+            .nodes
+            .len(),
+        2
+    );
+    let profile = response.profile.expect("Expected a profile");
+    assert_eq!(profile.query_ids.len(), 2);
+
+    let root_profile = profile
+        .query_ids
+        .iter()
+        .find(|stats| stats.query_id == root_query_id)
+        .expect("Expected a profile for the root query");
+    assert_eq!(root_profile.nodes_examined, 1);
+    assert_eq!(root_profile.nodes_matched, 1);
+    assert_eq!(root_profile.edges_fetched, 2);
+    assert!(root_profile.rejected_by_property.is_empty());
+
+    // Of the two children, only cmd.exe matches
+    let child_profile = profile
+        .query_ids
+        .iter()
+        .find(|stats| stats.query_id != root_query_id)
+        .expect("Expected a profile for the child query");
+    assert_eq!(child_profile.nodes_examined, 2);
+    assert_eq!(child_profile.nodes_matched, 1);
+    assert_eq!(
+        child_profile.rejected_by_property.get(&process_name),
+        Some(&1)
+    );
+
+    // Profiles are only returned when asked for
+    let response = graph_query_client
+        .query_graph_from_uid(QueryGraphFromUidRequest {
+            tenant_id,
+            node_uid: parent_uid,
+            graph_query,
+            profile: false,
+        })
+        .await?;
+    assert!(response.profile.is_none());
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_get_neighborhood() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+
+    // explorer.exe -> 3 x cmd.exe, the first of which -> conhost.exe
+    let explorer_uid =
+        create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    let mut cmd_uids = Vec::new();
+    for _ in 0..3 {
+        let cmd_uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+        graph_mutation_client
+            .create_edge(mutation::CreateEdgeRequest {
+                edge_name: children.clone(),
+                tenant_id,
+                from_uid: explorer_uid,
+                to_uid: cmd_uid,
+                source_node_type: process_node_type.clone(),
+            })
+            .await?;
+        cmd_uids.push(cmd_uid);
+    }
+    let conhost_uid = create_process(&mut graph_mutation_client, tenant_id, "conhost.exe").await?;
+    graph_mutation_client
+        .create_edge(mutation::CreateEdgeRequest {
+            edge_name: children.clone(),
+            tenant_id,
+            from_uid: cmd_uids[0],
+            to_uid: conhost_uid,
+            source_node_type: process_node_type.clone(),
+        })
+        .await?;
+
+    let neighborhood_request = |node_uid, max_depth| GetNeighborhoodRequest {
+        tenant_id,
+        node_uid,
+        max_depth,
+        edge_names: vec![],
+        node_types: vec![],
+        max_nodes: None,
+        max_edges: None,
+    };
+
+    // One hop reaches the children, but not the grandchild
+    let response = graph_query_client
+        .get_neighborhood(neighborhood_request(explorer_uid, 1))
+        .await?;
+    assert!(!response.truncated);
+    let neighborhood = response.neighborhood;
+    assert_eq!(neighborhood.nodes.len(), 4);
+    assert!(!neighborhood.nodes.contains_key(&conhost_uid));
+    assert_eq!(
+        neighborhood.nodes[&explorer_uid]
+            .string_properties
+            .prop_map
+            .get(&PropertyName::try_from("process_name")?)
+            .map(String::as_str),
+        Some("explorer.exe")
+    );
+    for cmd_uid in cmd_uids.iter() {
+        assert!(neighborhood.edges[&(explorer_uid, children.clone())].contains(cmd_uid));
+        assert!(neighborhood.edges[&(*cmd_uid, parent.clone())].contains(&explorer_uid));
+    }
+
+    // Two hops reach the grandchild
+    let response = graph_query_client
+        .get_neighborhood(neighborhood_request(explorer_uid, 2))
+        .await?;
+    assert!(!response.truncated);
+    assert_eq!(response.neighborhood.nodes.len(), 5);
+    assert!(response.neighborhood.nodes.contains_key(&conhost_uid));
+
+    // A node limit truncates the neighborhood
+    let response = graph_query_client
+        .get_neighborhood(GetNeighborhoodRequest {
+            max_nodes: Some(2),
+            ..neighborhood_request(explorer_uid, 2)
+        })
+        .await?;
+    assert!(response.truncated);
+    assert_eq!(response.neighborhood.nodes.len(), 2);
+
+    // Only following `parent` walks up from the grandchild, skipping siblings
+    let response = graph_query_client
+        .get_neighborhood(GetNeighborhoodRequest {
+            edge_names: vec![parent.clone()],
+            ..neighborhood_request(conhost_uid, 3)
+        })
+        .await?;
+    assert!(!response.truncated);
+    let mut uids: Vec<Uid> = response.neighborhood.nodes.keys().copied().collect();
+    uids.sort_unstable();
+    let mut expected = vec![conhost_uid, cmd_uids[0], explorer_uid];
+    expected.sort_unstable();
+    assert_eq!(uids, expected);
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_edge_properties() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+    let first_seen_at = PropertyName::try_from("first_seen_at")?;
+    let last_seen_at = PropertyName::try_from("last_seen_at")?;
+
+    let parent_uid = create_process(&mut graph_mutation_client, tenant_id, "bash").await?;
+    let child_uid = create_process(&mut graph_mutation_client, tenant_id, "curl").await?;
+
+    let seen_between = |first: u64, last: u64| {
+        let mut graph = IdentifiedGraph::new();
+        for uid in [parent_uid, child_uid] {
+            graph.add_node(IdentifiedNode {
+                properties: HashMap::new(),
+                uid,
+                node_type: "Process".to_string(),
+            });
+        }
+        graph.add_edge_with_properties(
+            "children",
+            parent_uid,
+            child_uid,
+            HashMap::from([
+                (
+                    "first_seen_at".to_string(),
+                    NodeProperty {
+                        property: Property::DecrementOnlyUintProp(DecrementOnlyUintProp {
+                            prop: first,
+                        }),
+                    },
+                ),
+                (
+                    "last_seen_at".to_string(),
+                    NodeProperty {
+                        property: Property::IncrementOnlyUintProp(IncrementOnlyUintProp {
+                            prop: last,
+                        }),
+                    },
+                ),
+            ]),
+        );
+        mutation::ApplyGraphRequest {
+            tenant_id,
+            identified_graph: graph,
+        }
+    };
+    let edge_property_redundancies = |response: mutation::ApplyGraphResponse| {
+        let mut redundancies: Vec<(String, mutation::MutationRedundancy)> = response
+            .edge_results
+            .into_iter()
+            .flat_map(|edge_result| edge_result.property_results)
+            .map(|property_result| {
+                (
+                    property_result.property_name.value,
+                    property_result.mutation_redundancy,
+                )
+            })
+            .collect();
+        redundancies.sort_by(|a, b| a.0.cmp(&b.0));
+        redundancies
+    };
+
+    let response = graph_mutation_client
+        .apply_graph(seen_between(100, 200))
+        .await?;
+    assert_eq!(
+        edge_property_redundancies(response),
+        vec![
+            (
+                "first_seen_at".to_string(),
+                mutation::MutationRedundancy::False
+            ),
+            (
+                "last_seen_at".to_string(),
+                mutation::MutationRedundancy::False
+            ),
+        ]
+    );
+
+    // Only the wider bound moves
+    let response = graph_mutation_client
+        .apply_graph(seen_between(150, 300))
+        .await?;
+    assert_eq!(
+        edge_property_redundancies(response),
+        vec![
+            (
+                "first_seen_at".to_string(),
+                mutation::MutationRedundancy::True
+            ),
+            (
+                "last_seen_at".to_string(),
+                mutation::MutationRedundancy::False
+            ),
+        ]
+    );
+
+    let response = graph_query_client
+        .get_neighborhood(GetNeighborhoodRequest {
+            tenant_id,
+            node_uid: parent_uid,
+            max_depth: 1,
+            edge_names: vec![],
+            node_types: vec![],
+            max_nodes: None,
+            max_edges: None,
+        })
+        .await?;
+    let neighborhood = response.neighborhood;
+    assert!(neighborhood.edges[&(parent_uid, children.clone())].contains(&child_uid));
+
+    // Both directions of the edge carry the same properties
+    for (from, edge_name, to) in [
+        (parent_uid, &children, child_uid),
+        (child_uid, &parent, parent_uid),
+    ] {
+        let properties = neighborhood
+            .get_edge_properties(from, edge_name, to)
+            .expect("missing edge properties");
+        assert_eq!(
+            properties.uint_properties.prop_map.get(&first_seen_at),
+            Some(&100)
+        );
+        assert_eq!(
+            properties.uint_properties.prop_map.get(&last_seen_at),
+            Some(&300)
+        );
+    }
+
+    // Concurrent writes each widen both bounds, and race to do so. Whichever
+    // is applied last must not replace a wider bound stored before it.
+    let writes = (1..=16u64).map(|i| {
+        let mut graph_mutation_client = graph_mutation_client.clone();
+        let request = seen_between(100 - i, 300 + i);
+        async move { graph_mutation_client.apply_graph(request).await }
+    });
+    futures::future::try_join_all(writes).await?;
+
+    let response = graph_query_client
+        .get_neighborhood(GetNeighborhoodRequest {
+            tenant_id,
+            node_uid: parent_uid,
+            max_depth: 1,
+            edge_names: vec![],
+            node_types: vec![],
+            max_nodes: None,
+            max_edges: None,
+        })
+        .await?;
+    let neighborhood = response.neighborhood;
+    for (from, edge_name, to) in [
+        (parent_uid, &children, child_uid),
+        (child_uid, &parent, parent_uid),
+    ] {
+        let properties = neighborhood
+            .get_edge_properties(from, edge_name, to)
+            .expect("missing edge properties");
+        assert_eq!(
+            properties.uint_properties.prop_map.get(&first_seen_at),
+            Some(&84)
+        );
+        assert_eq!(
+            properties.uint_properties.prop_map.get(&last_seen_at),
+            Some(&316)
+        );
+    }
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_query_time_window() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    // As declared by Process's session identity in the example schema
+    let created_at = PropertyName::try_from("created_at")?;
+    let last_seen_at = PropertyName::try_from("last_seen_at")?;
+    let terminated_at = PropertyName::try_from("terminated_at")?;
+
+    // (created_at, last_seen_at, terminated_at), with 0 for unset
+    let mut uids = Vec::new();
+    for (create_time, last_seen_time, terminate_time) in
+        [(1000, 1500, 2000), (5000, 6000, 0), (9000, 9400, 9500)]
+    {
+        let uid = create_process(&mut graph_mutation_client, tenant_id, "backup.exe").await?;
+        let mut timestamps = vec![
+            (
+                &created_at,
+                Property::ImmutableUintProp(ImmutableUintProp { prop: create_time }),
+            ),
+            (
+                &last_seen_at,
+                Property::IncrementOnlyUintProp(IncrementOnlyUintProp {
+                    prop: last_seen_time,
+                }),
+            ),
+        ];
+        if terminate_time != 0 {
+            timestamps.push((
+                &terminated_at,
+                Property::ImmutableUintProp(ImmutableUintProp {
+                    prop: terminate_time,
+                }),
+            ));
+        }
+        for (property_name, property) in timestamps {
+            graph_mutation_client
+                .set_node_property(mutation::SetNodePropertyRequest {
+                    tenant_id,
+                    uid,
+                    node_type: process_node_type.clone(),
+                    property_name: property_name.clone(),
+                    property: NodeProperty { property },
+                })
+                .await?;
+        }
+        uids.push(uid);
+    }
+
+    let query_window = |start_time, end_time| {
+        NodeQuery::root(process_node_type.clone())
+            .with_string_comparisons(
+                "process_name".try_into().unwrap(),
+                vec![StringCmp::Eq("backup.exe".to_owned(), false)],
+            )
+            .with_time_window(TimeWindow::new(start_time, end_time))
+            .build()
+    };
+
+    // Alive at some point between 1500 and 5500: the first has terminated
+    // by then and the second is still running
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: query_window(Some(1500), Some(5500)),
+            limit: None,
+        })
+        .await?;
+    let mut expected_roots = vec![uids[0], uids[1]];
+    expected_roots.sort();
+    let roots: Vec<Uid> = response.matches.iter().map(|m| m.root_uid).collect();
+    assert_eq!(roots, expected_roots);
+
+    // The matched node includes its session timestamps
+    let root = &response.matches[0].matched_graph.nodes[&response.matches[0].root_uid];
+    assert!(root.uint_properties.prop_map.contains_key(&created_at));
+
+    // Alive at some point after 8000
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: query_window(Some(8000), None),
+            limit: None,
+        })
+        .await?;
+    let roots: Vec<Uid> = response.matches.iter().map(|m| m.root_uid).collect();
+    assert_eq!(roots, vec![uids[2]]);
+
+    // Nothing was alive before 500
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: query_window(None, Some(500)),
+            limit: None,
+        })
+        .await?;
+    assert!(response.matches.is_empty());
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_apply_graph() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+    let process_name = PropertyName::try_from("process_name")?;
+
+    let mut uids = Vec::new();
+    for _ in 0..2 {
+        let mutation::CreateNodeResponse { uid } = graph_mutation_client
+            .create_node(mutation::CreateNodeRequest {
+                tenant_id,
+                node_type: process_node_type.clone(),
+            })
+            .await?;
+        uids.push(uid);
+    }
+    let (parent_uid, child_uid) = (uids[0], uids[1]);
+
+    let apply_graph_request = || {
+        let mut graph = IdentifiedGraph::new();
+        for (uid, name, process_id) in [(parent_uid, "bash", 100), (child_uid, "curl", 101)] {
+            graph.add_node(IdentifiedNode {
+                properties: HashMap::from([
+                    (
+                        "process_name".to_string(),
+                        NodeProperty {
+                            property: Property::ImmutableStrProp(ImmutableStrProp {
+                                prop: name.to_string(),
+                            }),
+                        },
+                    ),
+                    (
+                        "process_id".to_string(),
+                        NodeProperty {
+                            property: Property::ImmutableUintProp(ImmutableUintProp {
+                                prop: process_id,
+                            }),
+                        },
+                    ),
+                ]),
+                uid,
+                node_type: "Process".to_string(),
+            });
+        }
+        graph.add_edge("children", parent_uid, child_uid);
+        mutation::ApplyGraphRequest {
+            tenant_id,
+            identified_graph: graph,
+        }
+    };
+    let redundancies = |response: &mutation::ApplyGraphResponse| {
+        let mut redundancies: Vec<mutation::MutationRedundancy> = response
+            .node_results
+            .iter()
+            .flat_map(|node_result| node_result.property_results.iter())
+            .map(|property_result| property_result.mutation_redundancy.clone())
+            .collect();
+        redundancies.extend(
+            response
+                .edge_results
+                .iter()
+                .map(|edge_result| edge_result.mutation_redundancy.clone()),
+        );
+        redundancies
+    };
+
+    let response = graph_mutation_client
+        .apply_graph(apply_graph_request())
+        .await?;
+    assert_eq!(
+        redundancies(&response),
+        vec![mutation::MutationRedundancy::False; 5]
+    );
+    assert_eq!(response.edge_results.len(), 1);
+    assert_eq!(response.edge_results[0].edge_name, children);
+    assert_eq!(response.edge_results[0].reverse_edge_name, parent);
+
+    let neighborhood_request = |node_uid| GetNeighborhoodRequest {
+        tenant_id,
+        node_uid,
+        max_depth: 1,
+        edge_names: vec![],
+        node_types: vec![],
+        max_nodes: None,
+        max_edges: None,
+    };
+
+    // The nodes' properties and the edge are written...
+    let neighborhood = graph_query_client
+        .get_neighborhood(neighborhood_request(parent_uid))
+        .await?
+        .neighborhood;
+    for (uid, name) in [(parent_uid, "bash"), (child_uid, "curl")] {
+        assert_eq!(
+            neighborhood.nodes[&uid]
+                .string_properties
+                .prop_map
+                .get(&process_name)
+                .map(String::as_str),
+            Some(name)
+        );
+    }
+    assert!(neighborhood.edges[&(parent_uid, children.clone())].contains(&child_uid));
+
+    // ...along with the reverse edge, under the reverse edge's own name
+    let neighborhood = graph_query_client
+        .get_neighborhood(neighborhood_request(child_uid))
+        .await?
+        .neighborhood;
+    assert!(neighborhood.edges[&(child_uid, parent.clone())].contains(&parent_uid));
+    assert!(!neighborhood
+        .edges
+        .contains_key(&(child_uid, children.clone())));
+
+    // Once the writes have gone through, applying the same graph again
+    // changes nothing
+    let response = graph_mutation_client
+        .apply_graph(apply_graph_request())
+        .await?;
+    assert_eq!(
+        redundancies(&response),
+        vec![mutation::MutationRedundancy::True; 5]
+    );
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_apply_graph_retry_after_failed_batch() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        graph_query_client: _,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+
+    let mut uids = Vec::new();
+    for _ in 0..2 {
+        let mutation::CreateNodeResponse { uid } = graph_mutation_client
+            .create_node(mutation::CreateNodeRequest {
+                tenant_id,
+                node_type: process_node_type.clone(),
+            })
+            .await?;
+        uids.push(uid);
+    }
+    let (parent_uid, child_uid) = (uids[0], uids[1]);
+
+    let apply_graph_request = |parent_name: &str| {
+        let mut graph = IdentifiedGraph::new();
+        for (uid, name) in [(parent_uid, parent_name), (child_uid, "curl")] {
+            graph.add_node(IdentifiedNode {
+                properties: HashMap::from([(
+                    "process_name".to_string(),
+                    NodeProperty {
+                        property: Property::ImmutableStrProp(ImmutableStrProp {
+                            prop: name.to_string(),
+                        }),
+                    },
+                )]),
+                uid,
+                node_type: "Process".to_string(),
+            });
+        }
+        graph.add_edge("children", parent_uid, child_uid);
+        mutation::ApplyGraphRequest {
+            tenant_id,
+            identified_graph: graph,
+        }
+    };
+    let redundancies = |response: &mutation::ApplyGraphResponse| {
+        let mut redundancies: Vec<mutation::MutationRedundancy> = response
+            .node_results
+            .iter()
+            .flat_map(|node_result| node_result.property_results.iter())
+            .map(|property_result| property_result.mutation_redundancy.clone())
+            .collect();
+        redundancies.extend(
+            response
+                .edge_results
+                .iter()
+                .map(|edge_result| edge_result.mutation_redundancy.clone()),
+        );
+        redundancies
+    };
+
+    // A process name too long to be an index key fails the batch every
+    // write of this graph is in
+    let oversized_name = "a".repeat(u16::MAX as usize + 1);
+    assert!(graph_mutation_client
+        .apply_graph(apply_graph_request(&oversized_name))
+        .await
+        .is_err());
+
+    // None of those writes were stored, so retrying them isn't redundant...
+    let response = graph_mutation_client
+        .apply_graph(apply_graph_request("bash"))
+        .await?;
+    assert_eq!(
+        redundancies(&response),
+        vec![mutation::MutationRedundancy::False; 3]
+    );
+
+    // ...until they've gone through
+    let response = graph_mutation_client
+        .apply_graph(apply_graph_request("bash"))
+        .await?;
+    assert_eq!(
+        redundancies(&response),
+        vec![mutation::MutationRedundancy::True; 3]
+    );
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_delete_node_and_edge() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let children = EdgeName::try_from("children")?;
+
+    // explorer.exe -> 2 x cmd.exe
+    let explorer_uid =
+        create_process(&mut graph_mutation_client, tenant_id, "explorer.exe").await?;
+    let mut cmd_uids = Vec::new();
+    for _ in 0..2 {
+        let cmd_uid = create_process(&mut graph_mutation_client, tenant_id, "cmd.exe").await?;
+        graph_mutation_client
+            .create_edge(mutation::CreateEdgeRequest {
+                edge_name: children.clone(),
+                tenant_id,
+                from_uid: explorer_uid,
+                to_uid: cmd_uid,
+                source_node_type: process_node_type.clone(),
+            })
+            .await?;
+        cmd_uids.push(cmd_uid);
+    }
+
+    let neighborhood_request = |node_uid| GetNeighborhoodRequest {
+        tenant_id,
+        node_uid,
+        max_depth: 1,
+        edge_names: vec![],
+        node_types: vec![],
+        max_nodes: None,
+        max_edges: None,
+    };
+
+    // Query the graph first, so that what's deleted from it is cached
+    let response = graph_query_client
+        .get_neighborhood(neighborhood_request(explorer_uid))
+        .await?;
+    assert_eq!(response.neighborhood.nodes.len(), 3);
+
+    let delete_edge_request = mutation::DeleteEdgeRequest {
+        tenant_id,
+        from_uid: explorer_uid,
+        to_uid: cmd_uids[1],
+        edge_name: children.clone(),
+    };
+    let response = graph_mutation_client
+        .delete_edge(delete_edge_request.clone())
+        .await?;
+    assert!(response.existed);
+    let response = graph_mutation_client
+        .delete_edge(delete_edge_request)
+        .await?;
+    assert!(!response.existed);
+
+    let response = graph_mutation_client
+        .delete_node(mutation::DeleteNodeRequest {
+            tenant_id,
+            uid: cmd_uids[0],
+        })
+        .await?;
+    assert_eq!(response.deleted_edges, 1);
+
+    // Neither child is reachable from explorer.exe, in either direction, once
+    // graph-query has seen that something was deleted
+    tokio::time::sleep(DELETION_CHECK_INTERVAL).await;
+    let response = graph_query_client
+        .get_neighborhood(neighborhood_request(explorer_uid))
+        .await?;
+    assert_eq!(response.neighborhood.nodes.len(), 1);
+    let response = graph_query_client
+        .get_neighborhood(neighborhood_request(cmd_uids[1]))
+        .await?;
+    assert_eq!(response.neighborhood.nodes.len(), 1);
+    assert!(graph_query_client
+        .get_neighborhood(neighborhood_request(cmd_uids[0]))
+        .await
+        .is_err());
+
+    // The deleted edge isn't dropped as redundant when it's created again
+    let response = graph_mutation_client
+        .create_edge(mutation::CreateEdgeRequest {
+            edge_name: children.clone(),
+            tenant_id,
+            from_uid: explorer_uid,
+            to_uid: cmd_uids[1],
+            source_node_type: process_node_type.clone(),
+        })
+        .await?;
+    assert_eq!(
+        response.mutation_redundancy,
+        mutation::MutationRedundancy::False
+    );
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_purge_tenant() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let process_uid = create_process(&mut graph_mutation_client, tenant_id, "chrome.exe").await?;
+    create_file_created_by(&mut graph_mutation_client, tenant_id, "chrome.exe").await?;
+
+    let neighborhood_request = GetNeighborhoodRequest {
+        tenant_id,
+        node_uid: process_uid,
+        max_depth: 1,
+        edge_names: vec![],
+        node_types: vec![],
+        max_nodes: None,
+        max_edges: None,
+    };
+
+    // Query the graph first, so that what's purged from it is cached
+    graph_query_client
+        .get_neighborhood(neighborhood_request.clone())
+        .await?;
+
+    let response = graph_mutation_client
+        .get_purge_status(mutation::GetPurgeStatusRequest { tenant_id })
+        .await?;
+    assert_eq!(response.purge_status, mutation::PurgeStatus::NotStarted);
+
+    graph_mutation_client
+        .purge_tenant(mutation::PurgeTenantRequest { tenant_id })
+        .await?;
+    let mut attempts = 0;
+    loop {
+        let response = graph_mutation_client
+            .get_purge_status(mutation::GetPurgeStatusRequest { tenant_id })
+            .await?;
+        match response.purge_status {
+            mutation::PurgeStatus::Completed => break,
+            mutation::PurgeStatus::Running if attempts < 100 => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            purge_status => eyre::bail!("unexpected purge status {purge_status:?}"),
+        }
+    }
+
+    tokio::time::sleep(DELETION_CHECK_INTERVAL).await;
+    assert!(graph_query_client
+        .get_neighborhood(neighborhood_request)
+        .await
+        .is_err());
+
+    // Purged properties aren't dropped as redundant when they're written again
+    let response = graph_mutation_client
+        .set_node_property(mutation::SetNodePropertyRequest {
+            tenant_id,
+            uid: process_uid,
+            node_type: NodeType::try_from("Process").unwrap(),
+            property_name: "process_name".try_into()?,
+            property: NodeProperty {
+                property: Property::ImmutableStrProp(ImmutableStrProp {
+                    prop: "chrome.exe".into(),
+                }),
+            },
+        })
+        .await?;
+    assert_eq!(
+        response.mutation_redundancy,
+        mutation::MutationRedundancy::False
+    );
+
+    drop(_span);
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_retention_policy() -> eyre::Result<()> {
+    let GraphQueryIntegTestSetup {
+        mut graph_query_client,
+        mut graph_mutation_client,
+        tenant_id,
+        _span,
+    } = GraphQueryIntegTestSetup::setup().await?;
+
+    let graph_schema_manager_client_config = Figment::new()
+        .merge(Env::prefixed("GRAPH_SCHEMA_MANAGER_CLIENT_"))
+        .extract()?;
+    let mut graph_schema_manager_client =
+        GraphSchemaManagerClient::connect(graph_schema_manager_client_config).await?;
+
+    let process_node_type = NodeType::try_from("Process").unwrap();
+    let file_node_type = NodeType::try_from("File").unwrap();
+
+    graph_schema_manager_client
+        .set_retention_policy(graph_schema_manager_api::SetRetentionPolicyRequest {
+            tenant_id,
+            node_type: None,
+            retention_policy: Some(graph_schema_manager_api::RetentionPolicy::TimeToLive(
+                Duration::from_secs(60 * 60 * 24),
+            )),
+        })
+        .await?;
+    graph_schema_manager_client
+        .set_retention_policy(graph_schema_manager_api::SetRetentionPolicyRequest {
+            tenant_id,
+            node_type: Some(process_node_type.clone()),
+            retention_policy: Some(graph_schema_manager_api::RetentionPolicy::TimeToLive(
+                Duration::from_secs(2),
+            )),
+        })
+        .await?;
+
+    // A node type without a policy of its own falls back to the tenant's
+    let response = graph_schema_manager_client
+        .get_retention_policy(graph_schema_manager_api::GetRetentionPolicyRequest {
+            tenant_id,
+            node_type: file_node_type,
+        })
+        .await?;
+    assert_eq!(
+        response.retention_policy,
+        graph_schema_manager_api::RetentionPolicy::TimeToLive(Duration::from_secs(60 * 60 * 24))
+    );
+
+    create_process(&mut graph_mutation_client, tenant_id, "expiring.exe").await?;
+
+    let graph_query = NodeQuery::root(process_node_type.clone())
+        .with_string_comparisons(
+            "process_name".try_into()?,
+            vec![StringCmp::Eq("expiring.exe".to_owned(), false)],
+        )
+        .build();
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query: graph_query.clone(),
+            limit: None,
+        })
+        .await?;
+    assert_eq!(response.matches.len(), 1);
+
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    let response = graph_query_client
+        .query_graph(QueryGraphRequest {
+            tenant_id,
+            graph_query,
+            limit: None,
+        })
+        .await?;
+    assert!(response.matches.is_empty());
+
+    // Writing a node again refreshes the TTL of everything stored for it,
+    // including what the write was redundant with
+    let children = EdgeName::try_from("children")?;
+    let parent = EdgeName::try_from("parent")?;
+    let process_name = PropertyName::try_from("process_name")?;
+    let mut uids = Vec::new();
+    for _ in 0..2 {
+        let mutation::CreateNodeResponse { uid } = graph_mutation_client
+            .create_node(mutation::CreateNodeRequest {
+                tenant_id,
+                node_type: process_node_type.clone(),
+            })
+            .await?;
+        uids.push(uid);
+    }
+    let (parent_uid, child_uid) = (uids[0], uids[1]);
+    let apply_graph_request = || {
+        let mut graph = IdentifiedGraph::new();
+        for (uid, name) in [(parent_uid, "bash"), (child_uid, "curl")] {
+            graph.add_node(IdentifiedNode {
+                properties: HashMap::from([(
+                    "process_name".to_string(),
+                    NodeProperty {
+                        property: Property::ImmutableStrProp(ImmutableStrProp {
+                            prop: name.to_string(),
+                        }),
+                    },
+                )]),
+                uid,
+                node_type: "Process".to_string(),
+            });
+        }
+        graph.add_edge("children", parent_uid, child_uid);
+        mutation::ApplyGraphRequest {
+            tenant_id,
+            identified_graph: graph,
+        }
+    };
+
+    graph_mutation_client
+        .apply_graph(apply_graph_request())
+        .await?;
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let response = graph_mutation_client
+        .apply_graph(apply_graph_request())
+        .await?;
+    assert!(response
+        .edge_results
+        .iter()
+        .all(|edge_result| edge_result.mutation_redundancy == mutation::MutationRedundancy::True));
+    // Past the first write's TTL, but not the second's
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+
+    let neighborhood_request = |node_uid| GetNeighborhoodRequest {
+        tenant_id,
+        node_uid,
+        max_depth: 1,
+        edge_names: vec![],
+        node_types: vec![],
+        max_nodes: None,
+        max_edges: None,
+    };
+    let neighborhood = graph_query_client
+        .get_neighborhood(neighborhood_request(parent_uid))
+        .await?
+        .neighborhood;
+    for (uid, name) in [(parent_uid, "bash"), (child_uid, "curl")] {
+        assert_eq!(
+            neighborhood.nodes[&uid]
+                .string_properties
+                .prop_map
+                .get(&process_name)
+                .map(String::as_str),
+            Some(name)
+        );
+    }
+    assert!(neighborhood.edges[&(parent_uid, children)].contains(&child_uid));
+    let neighborhood = graph_query_client
+        .get_neighborhood(neighborhood_request(child_uid))
+        .await?
+        .neighborhood;
+    assert!(neighborhood.edges[&(child_uid, parent)].contains(&parent_uid));
+
+    // Removing the node type's policy falls back to the tenant's again
+    graph_schema_manager_client
+        .set_retention_policy(graph_schema_manager_api::SetRetentionPolicyRequest {
+            tenant_id,
+            node_type: Some(process_node_type.clone()),
+            retention_policy: None,
+        })
+        .await?;
+    let response = graph_schema_manager_client
+        .get_retention_policy(graph_schema_manager_api::GetRetentionPolicyRequest {
+            tenant_id,
+            node_type: process_node_type,
+        })
+        .await?;
+    assert_eq!(
+        response.retention_policy,
+        graph_schema_manager_api::RetentionPolicy::TimeToLive(Duration::from_secs(60 * 60 * 24))
+    );
+
+    drop(_span);
+    Ok(())
+}
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS graph_schema_manager.edge_property_schemas
+(
+    tenant_id      uuid                         NOT NULL,
+    node_type      text                         NOT NULL CHECK (node_type <> '' AND length(node_type) < 32),
+    schema_version smallint                     NOT NULL,
+    edge_name      text                         NOT NULL CHECK (edge_name <> '' AND length(edge_name) < 32),
+    property_name  text                         NOT NULL CHECK (property_name <> '' AND length(property_name) < 32),
+    property_type  graph_schema_manager.property_type NOT NULL,
+    PRIMARY KEY (tenant_id, node_type, schema_version, edge_name, property_name),
+    FOREIGN KEY (tenant_id, node_type, schema_version)
+        REFERENCES graph_schema_manager.node_schemas (tenant_id, node_type, schema_version)
+);
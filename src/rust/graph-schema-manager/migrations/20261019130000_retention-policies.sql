@@ -0,0 +1,15 @@
+-- A NULL retention_seconds means the data is retained forever
+CREATE TABLE IF NOT EXISTS graph_schema_manager.tenant_retention_policies
+(
+    tenant_id         uuid   NOT NULL,
+    retention_seconds bigint CHECK (retention_seconds > 0),
+    PRIMARY KEY (tenant_id)
+);
+
+CREATE TABLE IF NOT EXISTS graph_schema_manager.node_type_retention_policies
+(
+    tenant_id         uuid   NOT NULL,
+    node_type         text   NOT NULL CHECK (node_type <> '' AND length(node_type) < 32),
+    retention_seconds bigint CHECK (retention_seconds > 0),
+    PRIMARY KEY (tenant_id, node_type)
+);
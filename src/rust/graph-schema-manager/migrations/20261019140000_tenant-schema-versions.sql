@@ -0,0 +1,16 @@
+-- The latest schema version each tenant deployed. A deployment claims its
+-- version here before anything else, which keeps the tenant's row locked until
+-- the deployment's transaction ends, so that concurrent deployments are
+-- checked against each other one at a time.
+CREATE TABLE IF NOT EXISTS graph_schema_manager.tenant_schema_versions
+(
+    tenant_id             uuid     NOT NULL,
+    latest_schema_version smallint NOT NULL,
+    PRIMARY KEY (tenant_id)
+);
+
+INSERT INTO graph_schema_manager.tenant_schema_versions (tenant_id, latest_schema_version)
+SELECT tenant_id, max(schema_version)
+FROM graph_schema_manager.node_schemas
+GROUP BY tenant_id
+ON CONFLICT (tenant_id) DO NOTHING;
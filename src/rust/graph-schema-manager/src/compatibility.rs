@@ -0,0 +1,285 @@
+use rust_proto::graplinc::grapl::api::graph_schema_manager::v1beta1::messages::{
+    EdgeCardinality,
+    NodeSchemaChanges,
+    NodeSchemaDiff,
+    PropertySchema,
+    PropertyType,
+};
+
+use crate::schema_diff::diff_by_name;
+
+/// A change between schema versions that would corrupt data already written
+/// under the earlier version.
+///
+/// Adding or removing node types, properties and edges is always allowed.
+/// Changing how an existing node type is identified, or how an existing
+/// property or edge is written, is not.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum CompatibilityViolation {
+    #[error("schema_version {schema_version} must be greater than the last deployed version {last_schema_version}")]
+    SchemaVersionNotIncreasing {
+        last_schema_version: u32,
+        schema_version: u32,
+    },
+    #[error("{node_type}: identity algorithm can not change")]
+    IdentityAlgorithmChanged { node_type: String },
+    #[error("{node_type}.{property_name}: property type can not change from {from:?} to {to:?}")]
+    PropertyTypeChanged {
+        node_type: String,
+        property_name: String,
+        from: PropertyType,
+        to: PropertyType,
+    },
+    #[error("{node_type}.{edge_name}: reverse edge name can not change from {from} to {to}")]
+    ReverseEdgeNameChanged {
+        node_type: String,
+        edge_name: String,
+        from: String,
+        to: String,
+    },
+    #[error("{node_type}.{edge_name}: cardinality can not change from {from:?} to {to:?}")]
+    EdgeCardinalityChanged {
+        node_type: String,
+        edge_name: String,
+        from: EdgeCardinality,
+        to: EdgeCardinality,
+    },
+    #[error("{node_type}.{edge_name}: reverse cardinality can not change from {from:?} to {to:?}")]
+    ReverseEdgeCardinalityChanged {
+        node_type: String,
+        edge_name: String,
+        from: EdgeCardinality,
+        to: EdgeCardinality,
+    },
+    #[error("{node_type}.{edge_name}.{property_name}: edge property type can not change from {from:?} to {to:?}")]
+    EdgePropertyTypeChanged {
+        node_type: String,
+        edge_name: String,
+        property_name: String,
+        from: PropertyType,
+        to: PropertyType,
+    },
+}
+
+/// Every violation in the diff from a deployed schema version to the one
+/// being deployed
+pub fn check_compatibility(node_schema_diffs: &[NodeSchemaDiff]) -> Vec<CompatibilityViolation> {
+    node_schema_diffs
+        .iter()
+        .flat_map(|diff| match diff {
+            NodeSchemaDiff::Added(_) | NodeSchemaDiff::Removed(_) => vec![],
+            NodeSchemaDiff::Changed(changes) => check_node_schema_changes(changes),
+        })
+        .collect()
+}
+
+fn check_node_schema_changes(changes: &NodeSchemaChanges) -> Vec<CompatibilityViolation> {
+    let node_type = &changes.node_type.value;
+    let mut violations = vec![];
+
+    if changes.identity_algorithm_change.is_some() {
+        violations.push(CompatibilityViolation::IdentityAlgorithmChanged {
+            node_type: node_type.clone(),
+        });
+    }
+
+    for change in changes.changed_properties.iter() {
+        if change.from.property_type != change.to.property_type {
+            violations.push(CompatibilityViolation::PropertyTypeChanged {
+                node_type: node_type.clone(),
+                property_name: change.to.property_name.value.clone(),
+                from: change.from.property_type,
+                to: change.to.property_type,
+            });
+        }
+    }
+
+    for change in changes.changed_edges.iter() {
+        let (from, to) = (&change.from, &change.to);
+        let edge_name = &to.edge_name.value;
+
+        if from.reverse_edge_name != to.reverse_edge_name {
+            violations.push(CompatibilityViolation::ReverseEdgeNameChanged {
+                node_type: node_type.clone(),
+                edge_name: edge_name.clone(),
+                from: from.reverse_edge_name.value.clone(),
+                to: to.reverse_edge_name.value.clone(),
+            });
+        }
+        if from.cardinality != to.cardinality {
+            violations.push(CompatibilityViolation::EdgeCardinalityChanged {
+                node_type: node_type.clone(),
+                edge_name: edge_name.clone(),
+                from: from.cardinality.clone(),
+                to: to.cardinality.clone(),
+            });
+        }
+        if from.reverse_cardinality != to.reverse_cardinality {
+            violations.push(CompatibilityViolation::ReverseEdgeCardinalityChanged {
+                node_type: node_type.clone(),
+                edge_name: edge_name.clone(),
+                from: from.reverse_cardinality.clone(),
+                to: to.reverse_cardinality.clone(),
+            });
+        }
+
+        let (_, _, changed_properties) = diff_by_name(
+            from.properties.clone(),
+            to.properties.clone(),
+            |property: &PropertySchema| property.property_name.value.clone(),
+        );
+        for (from, to) in changed_properties {
+            if from.property_type != to.property_type {
+                violations.push(CompatibilityViolation::EdgePropertyTypeChanged {
+                    node_type: node_type.clone(),
+                    edge_name: edge_name.clone(),
+                    property_name: to.property_name.value,
+                    from: from.property_type,
+                    to: to.property_type,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_proto::graplinc::grapl::{
+        api::graph_schema_manager::v1beta1::messages::{
+            EdgeSchema,
+            IdentityAlgorithm,
+            NodeSchema,
+            StaticIdentity,
+        },
+        common::v1beta1::types::{
+            EdgeName,
+            NodeType,
+            PropertyName,
+        },
+    };
+
+    use super::*;
+    use crate::schema_diff::diff_schemas;
+
+    fn property(name: &'static str, property_type: PropertyType) -> PropertySchema {
+        PropertySchema {
+            property_name: PropertyName::try_from(name).unwrap(),
+            property_type,
+            identity_only: false,
+        }
+    }
+
+    fn edge(
+        name: &'static str,
+        reverse_name: &'static str,
+        cardinality: EdgeCardinality,
+        properties: Vec<PropertySchema>,
+    ) -> EdgeSchema {
+        EdgeSchema {
+            edge_name: EdgeName::try_from(name).unwrap(),
+            reverse_edge_name: EdgeName::try_from(reverse_name).unwrap(),
+            cardinality,
+            reverse_cardinality: EdgeCardinality::ToOne,
+            properties,
+        }
+    }
+
+    fn process(
+        static_key: &'static str,
+        properties: Vec<PropertySchema>,
+        edges: Vec<EdgeSchema>,
+    ) -> NodeSchema {
+        NodeSchema {
+            node_type: NodeType::try_from("Process").unwrap(),
+            schema_version: 0,
+            identity_algorithm: IdentityAlgorithm::Static(StaticIdentity {
+                static_key_properties: vec![PropertyName::try_from(static_key).unwrap()],
+            }),
+            properties,
+            edges,
+        }
+    }
+
+    #[test]
+    fn test_additions_and_removals_are_compatible() {
+        let from = vec![process(
+            "pid",
+            vec![property("exe", PropertyType::ImmutableString)],
+            vec![edge("children", "parent", EdgeCardinality::ToMany, vec![])],
+        )];
+        let to = vec![process(
+            "pid",
+            vec![property("name", PropertyType::ImmutableString)],
+            vec![edge(
+                "files",
+                "process",
+                EdgeCardinality::ToMany,
+                vec![property("first_seen", PropertyType::MinU64)],
+            )],
+        )];
+
+        assert_eq!(check_compatibility(&diff_schemas(from, to)), vec![]);
+    }
+
+    #[test]
+    fn test_incompatible_changes() {
+        let from = vec![process(
+            "pid",
+            vec![property("exe", PropertyType::ImmutableString)],
+            vec![edge(
+                "children",
+                "parent",
+                EdgeCardinality::ToMany,
+                vec![property("first_seen", PropertyType::MinU64)],
+            )],
+        )];
+        let to = vec![process(
+            "exe",
+            vec![property("exe", PropertyType::ImmutableI64)],
+            vec![edge(
+                "children",
+                "parents",
+                EdgeCardinality::ToOne,
+                vec![property("first_seen", PropertyType::MaxU64)],
+            )],
+        )];
+
+        let node_type = "Process".to_string();
+        let edge_name = "children".to_string();
+        assert_eq!(
+            check_compatibility(&diff_schemas(from, to)),
+            vec![
+                CompatibilityViolation::IdentityAlgorithmChanged {
+                    node_type: node_type.clone(),
+                },
+                CompatibilityViolation::PropertyTypeChanged {
+                    node_type: node_type.clone(),
+                    property_name: "exe".to_string(),
+                    from: PropertyType::ImmutableString,
+                    to: PropertyType::ImmutableI64,
+                },
+                CompatibilityViolation::ReverseEdgeNameChanged {
+                    node_type: node_type.clone(),
+                    edge_name: edge_name.clone(),
+                    from: "parent".to_string(),
+                    to: "parents".to_string(),
+                },
+                CompatibilityViolation::EdgeCardinalityChanged {
+                    node_type: node_type.clone(),
+                    edge_name: edge_name.clone(),
+                    from: EdgeCardinality::ToMany,
+                    to: EdgeCardinality::ToOne,
+                },
+                CompatibilityViolation::EdgePropertyTypeChanged {
+                    node_type,
+                    edge_name,
+                    property_name: "first_seen".to_string(),
+                    from: PropertyType::MinU64,
+                    to: PropertyType::MaxU64,
+                },
+            ]
+        );
+    }
+}
@@ -4,6 +4,7 @@ use rust_proto::graplinc::grapl::common::v1beta1::types::{
     NodeType,
 };
 use sqlx::{
+    types::time::PrimitiveDateTime,
     Postgres,
     Transaction,
 };
@@ -12,7 +13,14 @@ use super::models::StoredPropertyType;
 use crate::{
     config::SchemaDbConfig,
     db::models::{
+        EdgePropertySchemaRow,
+        EdgeSchemaRow,
         GetEdgeSchemaRequestRow,
+        NodeSchemaRow,
+        NodeSchemaVersionRow,
+        PropertySchemaRow,
+        RawSchemaRow,
+        RetentionPolicyRow,
         StoredEdgeCardinality,
     },
 };
@@ -207,6 +215,41 @@ impl SchemaDbClient {
         Ok(())
     }
 
+    pub async fn insert_edge_property(
+        &self,
+        txn: &mut Txn<'_>,
+        tenant_id: uuid::Uuid,
+        node_type_name: &str,
+        schema_version: u32,
+        edge_name: &str,
+        property_name: &str,
+        predicate_type_name: StoredPropertyType,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO graph_schema_manager.edge_property_schemas (
+                tenant_id,
+                node_type,
+                schema_version,
+                edge_name,
+                property_name,
+                property_type
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            tenant_id,
+            node_type_name,
+            schema_version as i16,
+            edge_name,
+            property_name,
+            predicate_type_name as StoredPropertyType,
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn insert_node_schema(
         &self,
         txn: &mut Txn<'_>,
@@ -302,4 +345,366 @@ impl SchemaDbClient {
 
         Ok(())
     }
+
+    pub async fn get_tenant_retention_policy(
+        &self,
+        tenant_id: uuid::Uuid,
+    ) -> Result<Option<RetentionPolicyRow>, sqlx::Error> {
+        sqlx::query_as!(
+            RetentionPolicyRow,
+            r#"
+            SELECT retention_seconds
+            FROM graph_schema_manager.tenant_retention_policies
+            WHERE tenant_id = $1
+            "#,
+            tenant_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn get_node_type_retention_policy(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type_name: &str,
+    ) -> Result<Option<RetentionPolicyRow>, sqlx::Error> {
+        sqlx::query_as!(
+            RetentionPolicyRow,
+            r#"
+            SELECT retention_seconds
+            FROM graph_schema_manager.node_type_retention_policies
+            WHERE tenant_id = $1 AND node_type = $2
+            "#,
+            tenant_id,
+            node_type_name,
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// `retention_seconds` of None means the data is retained forever
+    pub async fn upsert_tenant_retention_policy(
+        &self,
+        tenant_id: uuid::Uuid,
+        retention_seconds: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO graph_schema_manager.tenant_retention_policies (
+                tenant_id,
+                retention_seconds
+            )
+            VALUES ($1, $2)
+            ON CONFLICT (tenant_id)
+            DO UPDATE SET retention_seconds = excluded.retention_seconds
+            "#,
+            tenant_id,
+            retention_seconds,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `retention_seconds` of None means the data is retained forever
+    pub async fn upsert_node_type_retention_policy(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type_name: &str,
+        retention_seconds: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO graph_schema_manager.node_type_retention_policies (
+                tenant_id,
+                node_type,
+                retention_seconds
+            )
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id, node_type)
+            DO UPDATE SET retention_seconds = excluded.retention_seconds
+            "#,
+            tenant_id,
+            node_type_name,
+            retention_seconds,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_tenant_retention_policy(
+        &self,
+        tenant_id: uuid::Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM graph_schema_manager.tenant_retention_policies
+            WHERE tenant_id = $1
+            "#,
+            tenant_id,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_node_type_retention_policy(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type_name: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM graph_schema_manager.node_type_retention_policies
+            WHERE tenant_id = $1 AND node_type = $2
+            "#,
+            tenant_id,
+            node_type_name,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Claims `schema_version` as the tenant's latest, unless it isn't greater
+    /// than the latest version already deployed, which is returned instead.
+    /// Either way the tenant's row stays locked until `txn` ends.
+    pub async fn claim_schema_version(
+        &self,
+        txn: &mut Txn<'_>,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let claimed = sqlx::query!(
+            r#"
+            INSERT INTO graph_schema_manager.tenant_schema_versions (
+                tenant_id,
+                latest_schema_version
+            )
+            VALUES ($1, $2)
+            ON CONFLICT (tenant_id)
+            DO UPDATE SET latest_schema_version = excluded.latest_schema_version
+            WHERE tenant_schema_versions.latest_schema_version < excluded.latest_schema_version
+            RETURNING latest_schema_version
+            "#,
+            tenant_id,
+            schema_version as i16,
+        )
+        .fetch_optional(&mut *txn)
+        .await?;
+        if claimed.is_some() {
+            return Ok(None);
+        }
+
+        let row = sqlx::query!(
+            r#"
+            SELECT latest_schema_version
+            FROM graph_schema_manager.tenant_schema_versions
+            WHERE tenant_id = $1
+            "#,
+            tenant_id,
+        )
+        .fetch_one(&mut *txn)
+        .await?;
+        Ok(Some(row.latest_schema_version as u32))
+    }
+
+    /// The latest schema version the tenant deployed, or the latest version
+    /// `node_type_name` was deployed in
+    pub async fn get_latest_schema_version(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type_name: Option<&str>,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT max(schema_version) AS schema_version
+            FROM graph_schema_manager.node_schemas
+            WHERE
+                tenant_id = $1 AND
+                ($2::text IS NULL OR node_type = $2)
+            "#,
+            tenant_id,
+            node_type_name,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.schema_version.map(|v| v as u32))
+    }
+
+    /// The node types deployed in a schema version, optionally only
+    /// `node_type_name`, ordered by node type
+    pub async fn get_node_schemas(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+        node_type_name: Option<&str>,
+    ) -> Result<Vec<NodeSchemaRow>, sqlx::Error> {
+        sqlx::query_as!(
+            NodeSchemaRow,
+            r#"
+            SELECT
+                node_schemas.node_type,
+                node_schemas.identity_algorithm,
+                static_args.static_key_properties AS "static_key_properties?",
+                session_args.pseudo_key_properties AS "pseudo_key_properties?",
+                session_args.negation_key_properties AS "negation_key_properties?",
+                session_args.creation_timestamp_property AS "creation_timestamp_property?",
+                session_args.last_seen_timestamp_property AS "last_seen_timestamp_property?",
+                session_args.termination_timestamp_property AS "termination_timestamp_property?"
+            FROM graph_schema_manager.node_schemas
+            LEFT JOIN graph_schema_manager.static_identity_arguments AS static_args
+                USING (tenant_id, node_type, schema_version, identity_algorithm)
+            LEFT JOIN graph_schema_manager.session_identity_arguments AS session_args
+                USING (tenant_id, node_type, schema_version, identity_algorithm)
+            WHERE
+                node_schemas.tenant_id = $1 AND
+                node_schemas.schema_version = $2 AND
+                ($3::text IS NULL OR node_schemas.node_type = $3)
+            ORDER BY node_schemas.node_type
+            "#,
+            tenant_id,
+            schema_version as i16,
+            node_type_name,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Ordered by node type, then property name
+    pub async fn get_property_schemas(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+        node_type_name: Option<&str>,
+    ) -> Result<Vec<PropertySchemaRow>, sqlx::Error> {
+        sqlx::query_as!(
+            PropertySchemaRow,
+            r#"
+            SELECT
+                node_type,
+                property_name,
+                property_type AS "property_type: StoredPropertyType",
+                identity_only
+            FROM graph_schema_manager.property_schemas
+            WHERE
+                tenant_id = $1 AND
+                schema_version = $2 AND
+                ($3::text IS NULL OR node_type = $3)
+            ORDER BY node_type, property_name
+            "#,
+            tenant_id,
+            schema_version as i16,
+            node_type_name,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Ordered by node type, then edge name
+    pub async fn get_edge_schemas(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+        node_type_name: Option<&str>,
+    ) -> Result<Vec<EdgeSchemaRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EdgeSchemaRow,
+            r#"
+            SELECT
+                node_type,
+                forward_edge_name,
+                reverse_edge_name,
+                forward_edge_cardinality AS "forward_edge_cardinality: StoredEdgeCardinality",
+                reverse_edge_cardinality AS "reverse_edge_cardinality: StoredEdgeCardinality"
+            FROM graph_schema_manager.edge_schemas
+            WHERE
+                tenant_id = $1 AND
+                schema_version = $2 AND
+                ($3::text IS NULL OR node_type = $3)
+            ORDER BY node_type, forward_edge_name, reverse_edge_name
+            "#,
+            tenant_id,
+            schema_version as i16,
+            node_type_name,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Ordered by node type, then edge name, then property name
+    pub async fn get_edge_property_schemas(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+        node_type_name: Option<&str>,
+    ) -> Result<Vec<EdgePropertySchemaRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EdgePropertySchemaRow,
+            r#"
+            SELECT
+                node_type,
+                edge_name,
+                property_name,
+                property_type AS "property_type: StoredPropertyType"
+            FROM graph_schema_manager.edge_property_schemas
+            WHERE
+                tenant_id = $1 AND
+                schema_version = $2 AND
+                ($3::text IS NULL OR node_type = $3)
+            ORDER BY node_type, edge_name, property_name
+            "#,
+            tenant_id,
+            schema_version as i16,
+            node_type_name,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Ordered by node type, then schema version
+    pub async fn list_node_schema_versions(
+        &self,
+        tenant_id: uuid::Uuid,
+    ) -> Result<Vec<NodeSchemaVersionRow>, sqlx::Error> {
+        sqlx::query_as!(
+            NodeSchemaVersionRow,
+            r#"
+            SELECT
+                node_type,
+                schema_version,
+                deployment_timestamp AS "deployment_timestamp: PrimitiveDateTime"
+            FROM graph_schema_manager.node_schemas
+            WHERE tenant_id = $1
+            ORDER BY node_type, schema_version
+            "#,
+            tenant_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Every node type in a schema version holds the whole raw schema, so any
+    /// one of them will do
+    pub async fn get_raw_schema(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+    ) -> Result<Option<RawSchemaRow>, sqlx::Error> {
+        sqlx::query_as!(
+            RawSchemaRow,
+            r#"
+            SELECT schema_version, schema_type, raw_schema
+            FROM graph_schema_manager.node_schemas
+            WHERE tenant_id = $1 AND schema_version = $2
+            LIMIT 1
+            "#,
+            tenant_id,
+            schema_version as i16,
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
 }
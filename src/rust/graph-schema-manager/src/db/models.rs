@@ -1,4 +1,30 @@
-use rust_proto::graplinc::grapl::api::graph_schema_manager::v1beta1::messages::EdgeCardinality;
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use bytes::Bytes;
+use rust_proto::{
+    graplinc::grapl::{
+        api::graph_schema_manager::v1beta1::messages::{
+            EdgeCardinality,
+            GetRawSchemaResponse,
+            IdentityAlgorithm,
+            NodeSchemaVersion,
+            PropertySchema,
+            PropertyType,
+            RetentionPolicy,
+            SchemaType,
+            SessionIdentity,
+            StaticIdentity,
+        },
+        common::v1beta1::types::PropertyName,
+    },
+    SerDeError,
+};
+use sqlx::types::time::PrimitiveDateTime;
+
+use crate::deploy_graphql_schema::SCHEMA_TYPE;
 
 #[derive(sqlx::Type, Copy, Clone, Debug)]
 #[sqlx(type_name = "edge_cardinality")]
@@ -23,6 +49,21 @@ impl From<StoredEdgeCardinality> for EdgeCardinality {
     }
 }
 
+#[derive(sqlx::Type, Copy, Clone, Debug)]
+pub struct RetentionPolicyRow {
+    /// None means the data is retained forever
+    pub retention_seconds: Option<i64>,
+}
+
+impl From<RetentionPolicyRow> for RetentionPolicy {
+    fn from(row: RetentionPolicyRow) -> Self {
+        match row.retention_seconds {
+            Some(seconds) => RetentionPolicy::TimeToLive(Duration::from_secs(seconds as u64)),
+            None => RetentionPolicy::RetainForever,
+        }
+    }
+}
+
 #[derive(sqlx::Type, Copy, Clone, Debug)]
 #[sqlx(type_name = "property_type")]
 pub enum StoredPropertyType {
@@ -35,54 +76,193 @@ pub enum StoredPropertyType {
     MinU64,
 }
 
+impl From<StoredPropertyType> for PropertyType {
+    fn from(t: StoredPropertyType) -> Self {
+        match t {
+            StoredPropertyType::ImmutableString => PropertyType::ImmutableString,
+            StoredPropertyType::ImmutableI64 => PropertyType::ImmutableI64,
+            StoredPropertyType::MaxI64 => PropertyType::MaxI64,
+            StoredPropertyType::MinI64 => PropertyType::MinI64,
+            StoredPropertyType::ImmutableU64 => PropertyType::ImmutableU64,
+            StoredPropertyType::MaxU64 => PropertyType::MaxU64,
+            StoredPropertyType::MinU64 => PropertyType::MinU64,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum InvalidStoredSchema {
+    #[error("invalid name: {0}")]
+    InvalidName(#[from] SerDeError),
+    #[error("unknown identity algorithm '{0}'")]
+    UnknownIdentityAlgorithm(String),
+    #[error("missing {identity_algorithm} identity arguments for node_type {node_type}")]
+    MissingIdentityArguments {
+        identity_algorithm: String,
+        node_type: String,
+    },
+    #[error("unknown schema type '{0}'")]
+    UnknownSchemaType(String),
+}
+
+/// A node type's schema, joined with the arguments of its identity algorithm
 #[derive(sqlx::Type, Clone, Debug)]
-struct NodeIdentityRow {
-    identity_algorithm: String,
-    tenant_id: uuid::Uuid,
-    node_type: String,
-    schema_version: i16,
+pub struct NodeSchemaRow {
+    pub node_type: String,
+    pub identity_algorithm: String,
+    pub static_key_properties: Option<Vec<String>>,
+    pub pseudo_key_properties: Option<Vec<String>>,
+    pub negation_key_properties: Option<Vec<String>>,
+    pub creation_timestamp_property: Option<String>,
+    pub last_seen_timestamp_property: Option<String>,
+    pub termination_timestamp_property: Option<String>,
+}
+
+impl NodeSchemaRow {
+    pub fn identity_algorithm(&self) -> Result<IdentityAlgorithm, InvalidStoredSchema> {
+        let missing_arguments = || InvalidStoredSchema::MissingIdentityArguments {
+            identity_algorithm: self.identity_algorithm.clone(),
+            node_type: self.node_type.clone(),
+        };
+
+        match self.identity_algorithm.as_str() {
+            "static" => {
+                let static_key_properties = self
+                    .static_key_properties
+                    .clone()
+                    .ok_or_else(missing_arguments)?;
+                Ok(IdentityAlgorithm::Static(StaticIdentity {
+                    static_key_properties: property_names(static_key_properties)?,
+                }))
+            }
+            "session" => match (
+                &self.pseudo_key_properties,
+                &self.negation_key_properties,
+                &self.creation_timestamp_property,
+                &self.last_seen_timestamp_property,
+                &self.termination_timestamp_property,
+            ) {
+                (
+                    Some(pseudo_key_properties),
+                    Some(negation_key_properties),
+                    Some(creation_timestamp_property),
+                    Some(last_seen_timestamp_property),
+                    Some(termination_timestamp_property),
+                ) => Ok(IdentityAlgorithm::Session(SessionIdentity {
+                    pseudo_key_properties: property_names(pseudo_key_properties.clone())?,
+                    negation_key_properties: property_names(negation_key_properties.clone())?,
+                    creation_timestamp_property: PropertyName::try_from(
+                        creation_timestamp_property.clone(),
+                    )?,
+                    last_seen_timestamp_property: PropertyName::try_from(
+                        last_seen_timestamp_property.clone(),
+                    )?,
+                    termination_timestamp_property: PropertyName::try_from(
+                        termination_timestamp_property.clone(),
+                    )?,
+                })),
+                _ => Err(missing_arguments()),
+            },
+            other => Err(InvalidStoredSchema::UnknownIdentityAlgorithm(
+                other.to_owned(),
+            )),
+        }
+    }
+}
+
+fn property_names(names: Vec<String>) -> Result<Vec<PropertyName>, InvalidStoredSchema> {
+    Ok(names
+        .into_iter()
+        .map(PropertyName::try_from)
+        .collect::<Result<Vec<_>, _>>()?)
 }
 
 #[derive(sqlx::Type, Clone, Debug)]
-struct SessionIdentityRow {
-    tenant_id: uuid::Uuid,
-    identity_algorithm: String,
-    node_type: String,
-    schema_version: i16,
-    pseudo_key_properties: Vec<String>,
-    negation_key_properties: Vec<String>,
-    creation_timestamp_property: String,
-    last_seen_timestamp_property: String,
-    termination_timestamp_property: String,
+pub struct PropertySchemaRow {
+    pub node_type: String,
+    pub property_name: String,
+    pub property_type: StoredPropertyType,
+    pub identity_only: bool,
+}
+
+impl TryFrom<PropertySchemaRow> for PropertySchema {
+    type Error = InvalidStoredSchema;
+
+    fn try_from(row: PropertySchemaRow) -> Result<Self, Self::Error> {
+        Ok(PropertySchema {
+            property_name: PropertyName::try_from(row.property_name)?,
+            property_type: row.property_type.into(),
+            identity_only: row.identity_only,
+        })
+    }
 }
 
 #[derive(sqlx::Type, Clone, Debug)]
-struct NodeSchemaRow {
-    tenant_id: sqlx::types::uuid::Uuid,
-    identity_algorithm: String,
-    node_type: String,
-    schema_version: i16,
-    deployment_timestamp: sqlx::types::time::PrimitiveDateTime,
-    schema_type: String,
+pub struct EdgeSchemaRow {
+    pub node_type: String,
+    pub forward_edge_name: String,
+    pub reverse_edge_name: String,
+    pub forward_edge_cardinality: StoredEdgeCardinality,
+    pub reverse_edge_cardinality: StoredEdgeCardinality,
+}
+
+#[derive(sqlx::Type, Clone, Debug)]
+pub struct EdgePropertySchemaRow {
+    pub node_type: String,
+    pub edge_name: String,
+    pub property_name: String,
+    pub property_type: StoredPropertyType,
+}
+
+impl TryFrom<EdgePropertySchemaRow> for PropertySchema {
+    type Error = InvalidStoredSchema;
+
+    fn try_from(row: EdgePropertySchemaRow) -> Result<Self, Self::Error> {
+        Ok(PropertySchema {
+            property_name: PropertyName::try_from(row.property_name)?,
+            property_type: row.property_type.into(),
+            identity_only: false,
+        })
+    }
 }
 
 #[derive(sqlx::Type, Clone, Debug)]
-struct PropertySchemaRow {
-    tenant_id: sqlx::types::uuid::Uuid,
-    node_type: String,
-    schema_version: i16,
-    property_name: String,
-    property_type: StoredPropertyType,
-    identity_only: bool,
+pub struct NodeSchemaVersionRow {
+    pub node_type: String,
+    pub schema_version: i16,
+    pub deployment_timestamp: PrimitiveDateTime,
+}
+
+impl From<NodeSchemaVersionRow> for NodeSchemaVersion {
+    fn from(row: NodeSchemaVersionRow) -> Self {
+        NodeSchemaVersion {
+            schema_version: row.schema_version as u32,
+            // deployment_timestamp defaults to CURRENT_TIMESTAMP, which is UTC
+            deployment_timestamp: SystemTime::from(row.deployment_timestamp.assume_utc()),
+        }
+    }
 }
 
 #[derive(sqlx::Type, Clone, Debug)]
-struct EdgeSchemaRow {
-    tenant_id: sqlx::types::uuid::Uuid,
-    node_type: String,
-    schema_version: i16,
-    forward_edge_name: String,
-    reverse_edge_name: String,
-    forward_edge_cardinality: StoredEdgeCardinality,
-    reverse_edge_cardinality: StoredEdgeCardinality,
+pub struct RawSchemaRow {
+    pub schema_version: i16,
+    pub schema_type: String,
+    pub raw_schema: Vec<u8>,
+}
+
+impl TryFrom<RawSchemaRow> for GetRawSchemaResponse {
+    type Error = InvalidStoredSchema;
+
+    fn try_from(row: RawSchemaRow) -> Result<Self, Self::Error> {
+        let schema_type = match row.schema_type.as_str() {
+            SCHEMA_TYPE => SchemaType::GraphqlV0,
+            other => return Err(InvalidStoredSchema::UnknownSchemaType(other.to_owned())),
+        };
+
+        Ok(GetRawSchemaResponse {
+            schema_version: row.schema_version as u32,
+            schema_type,
+            schema: Bytes::from(row.raw_schema),
+        })
+    }
 }
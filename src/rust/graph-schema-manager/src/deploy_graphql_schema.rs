@@ -3,6 +3,11 @@ use grapl_graphql_codegen::{
     edge::Edge,
     identification_algorithm::IdentificationAlgorithm,
     identity_predicate_type::IdentityPredicateType,
+    lint::{
+        self,
+        Diagnostic,
+        Severity,
+    },
     node_predicate::NodePredicate,
     node_type,
     node_type::NodeType,
@@ -11,6 +16,21 @@ use grapl_graphql_codegen::{
     Document,
     ParseError,
 };
+use rust_proto::graplinc::grapl::{
+    api::graph_schema_manager::v1beta1::messages::{
+        EdgeSchema,
+        IdentityAlgorithm,
+        NodeSchema,
+        PropertySchema,
+        SessionIdentity,
+        StaticIdentity,
+    },
+    common::v1beta1::types::{
+        EdgeName,
+        NodeType as NodeTypeName,
+        PropertyName,
+    },
+};
 use sqlx::{
     Postgres,
     Transaction,
@@ -27,7 +47,7 @@ use crate::db::{
     },
 };
 
-const SCHEMA_TYPE: &str = "Graphql_V0";
+pub(crate) const SCHEMA_TYPE: &str = "Graphql_V0";
 
 #[derive(thiserror::Error, Debug)]
 pub enum DeployGraphqlError {
@@ -39,28 +59,140 @@ pub enum DeployGraphqlError {
     GraphqlParseError(#[from] ParseError),
     #[error("Schema is invalid {0}")]
     InvalidSchema(&'static str),
+    #[error("Schema is invalid\n{}", lint::render_human("schema", .0))]
+    LintErrors(Vec<Diagnostic>),
+    #[error("Schema has an invalid name {0}")]
+    InvalidName(#[from] rust_proto::SerDeError),
+}
+
+pub fn parse_graphql_schema(raw_schema: &str) -> Result<Vec<NodeType>, DeployGraphqlError> {
+    let document: Document<String> = parse_schema(raw_schema)?;
+
+    // Report every problem with the schema at once, rather than just the
+    // first one that parsing it into node types runs into
+    let lint_errors: Vec<Diagnostic> = lint::lint_document(&document)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+        .collect();
+    if !lint_errors.is_empty() {
+        return Err(DeployGraphqlError::LintErrors(lint_errors));
+    }
+
+    let document = document.into_static();
+
+    node_type::parse_into_node_types(document)
+        .map_err(|e| DeployGraphqlError::ParseError(e.to_string()))
+}
+
+/// The schemas of the parsed node types, as they'll be stored once deployed
+pub fn node_schemas(
+    node_types: &[NodeType],
+    schema_version: u32,
+) -> Result<Vec<NodeSchema>, DeployGraphqlError> {
+    let mut node_schemas = node_types
+        .iter()
+        .map(|node_type| node_schema(node_type, schema_version))
+        .collect::<Result<Vec<_>, _>>()?;
+    node_schemas.sort_by(|a, b| a.node_type.value.cmp(&b.node_type.value));
+    Ok(node_schemas)
+}
+
+fn node_schema(
+    node_type: &NodeType,
+    schema_version: u32,
+) -> Result<NodeSchema, DeployGraphqlError> {
+    let identity_algorithm = match node_type.identification_algorithm {
+        IdentificationAlgorithm::Session => {
+            IdentityAlgorithm::Session(session_identity(node_type)?)
+        }
+        IdentificationAlgorithm::Static => IdentityAlgorithm::Static(static_identity(node_type)?),
+    };
+
+    let mut properties = node_type
+        .predicates
+        .iter()
+        .map(|property| {
+            Ok(PropertySchema {
+                property_name: PropertyName::try_from(property.predicate_name.clone())?,
+                property_type: get_predicate_type_name(
+                    property.predicate_type,
+                    property.conflict_resolution,
+                )?
+                .into(),
+                identity_only: false,
+            })
+        })
+        .collect::<Result<Vec<_>, DeployGraphqlError>>()?;
+    properties.sort_by(|a, b| a.property_name.value.cmp(&b.property_name.value));
+
+    // Both directions of an edge are stored under the node type declaring it
+    let mut edges = Vec::with_capacity(node_type.edges.len() * 2);
+    for edge in node_type.edges.iter() {
+        let (cardinality, reverse_cardinality) = edge_cardinalities(edge);
+
+        let mut edge_properties = edge
+            .properties
+            .iter()
+            .map(|property| {
+                Ok(PropertySchema {
+                    property_name: PropertyName::try_from(property.property_name.clone())?,
+                    property_type: get_predicate_type_name(
+                        property.predicate_type,
+                        property.conflict_resolution,
+                    )?
+                    .into(),
+                    identity_only: false,
+                })
+            })
+            .collect::<Result<Vec<_>, DeployGraphqlError>>()?;
+        edge_properties.sort_by(|a, b| a.property_name.value.cmp(&b.property_name.value));
+
+        edges.push(EdgeSchema {
+            edge_name: EdgeName::try_from(edge.edge_name.as_str())?,
+            reverse_edge_name: EdgeName::try_from(edge.reverse_edge_name.as_str())?,
+            cardinality: cardinality.into(),
+            reverse_cardinality: reverse_cardinality.into(),
+            properties: edge_properties,
+        });
+        edges.push(EdgeSchema {
+            edge_name: EdgeName::try_from(edge.reverse_edge_name.as_str())?,
+            reverse_edge_name: EdgeName::try_from(edge.edge_name.as_str())?,
+            cardinality: reverse_cardinality.into(),
+            reverse_cardinality: cardinality.into(),
+            properties: vec![],
+        });
+    }
+    edges.sort_by(|a, b| {
+        (&a.edge_name.value, &a.reverse_edge_name.value)
+            .cmp(&(&b.edge_name.value, &b.reverse_edge_name.value))
+    });
+
+    Ok(NodeSchema {
+        node_type: NodeTypeName {
+            value: node_type.type_name.clone(),
+        },
+        schema_version,
+        identity_algorithm,
+        properties,
+        edges,
+    })
 }
 
+/// Writes the parsed node types in `txn`, which the caller commits, or rolls
+/// back for a dry run
 pub async fn deploy_graphql_schema(
+    txn: &mut Txn<'_>,
     tenant_id: uuid::Uuid,
     raw_schema: &str,
+    node_types: &[NodeType],
     schema_version: u32,
     db_client: &SchemaDbClient,
 ) -> Result<(), DeployGraphqlError> {
-    let document: Document<String> = parse_schema(raw_schema)?;
-    let document = document.into_static();
-
-    let node_types = node_type::parse_into_node_types(document)
-        .map_err(|e| DeployGraphqlError::ParseError(e.to_string()))?;
-
-    let mut txn = db_client.begin_txn().await?;
-
     for node_type in node_types.iter() {
-        deploy_identity_algorithm(&mut txn, db_client, tenant_id, node_type, schema_version)
-            .await?;
+        deploy_identity_algorithm(txn, db_client, tenant_id, node_type, schema_version).await?;
 
         deploy_node_type(
-            &mut txn,
+            txn,
             db_client,
             tenant_id,
             node_type,
@@ -71,7 +203,7 @@ pub async fn deploy_graphql_schema(
 
         for property in node_type.predicates.iter() {
             deploy_node_property(
-                &mut txn,
+                txn,
                 db_client,
                 tenant_id,
                 node_type,
@@ -82,20 +214,10 @@ pub async fn deploy_graphql_schema(
         }
 
         for edge in node_type.edges.iter() {
-            deploy_edge(
-                &mut txn,
-                db_client,
-                tenant_id,
-                node_type,
-                edge,
-                schema_version,
-            )
-            .await?;
+            deploy_edge(txn, db_client, tenant_id, node_type, edge, schema_version).await?;
         }
     }
 
-    txn.commit().await?;
-
     Ok(())
 }
 
@@ -169,8 +291,34 @@ async fn deploy_session_identity(
     node_type: &NodeType,
     schema_version: u32,
 ) -> Result<(), DeployGraphqlError> {
-    let node_type_name = &node_type.type_name;
+    let SessionIdentity {
+        pseudo_key_properties,
+        creation_timestamp_property,
+        last_seen_timestamp_property,
+        termination_timestamp_property,
+        ..
+    } = session_identity(node_type)?;
+
+    db_client
+        .insert_session_identity_args(
+            txn,
+            tenant_id,
+            &node_type.type_name,
+            schema_version,
+            pseudo_key_properties
+                .into_iter()
+                .map(|property| property.value)
+                .collect(),
+            &creation_timestamp_property.value,
+            &last_seen_timestamp_property.value,
+            &termination_timestamp_property.value,
+        )
+        .await?;
 
+    Ok(())
+}
+
+fn session_identity(node_type: &NodeType) -> Result<SessionIdentity, DeployGraphqlError> {
     let mut pseudo_keys = Vec::with_capacity(1);
 
     let mut creation_timestamp_property: Option<String> = None;
@@ -216,20 +364,17 @@ async fn deploy_session_identity(
         DeployGraphqlError::InvalidSchema("termination_timestamp_property must be present")
     })?;
 
-    db_client
-        .insert_session_identity_args(
-            txn,
-            tenant_id,
-            node_type_name,
-            schema_version,
-            pseudo_keys,
-            &creation_timestamp_property,
-            &last_seen_timestamp_property,
-            &termination_timestamp_property,
-        )
-        .await?;
-
-    Ok(())
+    Ok(SessionIdentity {
+        pseudo_key_properties: pseudo_keys
+            .into_iter()
+            .map(PropertyName::try_from)
+            .collect::<Result<Vec<_>, _>>()?,
+        // todo: negation keys are not supported in the parser
+        negation_key_properties: vec![],
+        creation_timestamp_property: PropertyName::try_from(creation_timestamp_property)?,
+        last_seen_timestamp_property: PropertyName::try_from(last_seen_timestamp_property)?,
+        termination_timestamp_property: PropertyName::try_from(termination_timestamp_property)?,
+    })
 }
 
 async fn deploy_static_identity(
@@ -239,8 +384,27 @@ async fn deploy_static_identity(
     node_type: &NodeType,
     schema_version: u32,
 ) -> Result<(), DeployGraphqlError> {
-    let node_type_name = &node_type.type_name;
+    let StaticIdentity {
+        static_key_properties,
+    } = static_identity(node_type)?;
+
+    db_client
+        .insert_static_identity_args(
+            txn,
+            tenant_id,
+            &node_type.type_name,
+            schema_version,
+            static_key_properties
+                .into_iter()
+                .map(|property| property.value)
+                .collect(),
+        )
+        .await?;
+
+    Ok(())
+}
 
+fn static_identity(node_type: &NodeType) -> Result<StaticIdentity, DeployGraphqlError> {
     let mut static_keys = Vec::with_capacity(1);
     for field in node_type.predicates.iter() {
         match field.identity_predicate_type {
@@ -277,11 +441,12 @@ async fn deploy_static_identity(
         ));
     }
 
-    db_client
-        .insert_static_identity_args(txn, tenant_id, node_type_name, schema_version, static_keys)
-        .await?;
-
-    Ok(())
+    Ok(StaticIdentity {
+        static_key_properties: static_keys
+            .into_iter()
+            .map(PropertyName::try_from)
+            .collect::<Result<Vec<_>, _>>()?,
+    })
 }
 
 async fn deploy_node_property(
@@ -317,17 +482,7 @@ async fn deploy_edge(
     edge: &Edge,
     schema_version: u32,
 ) -> Result<(), DeployGraphqlError> {
-    let forward_edge_cardinality = if edge.relationship.to_one() {
-        StoredEdgeCardinality::ToOne
-    } else {
-        StoredEdgeCardinality::ToMany
-    };
-
-    let reverse_edge_cardinality = if edge.relationship.reverse().to_one() {
-        StoredEdgeCardinality::ToOne
-    } else {
-        StoredEdgeCardinality::ToMany
-    };
+    let (forward_edge_cardinality, reverse_edge_cardinality) = edge_cardinalities(edge);
 
     db_client
         .insert_edge_schema(
@@ -341,9 +496,41 @@ async fn deploy_edge(
             schema_version,
         )
         .await?;
+
+    for property in edge.properties.iter() {
+        let predicate_type_name =
+            get_predicate_type_name(property.predicate_type, property.conflict_resolution)?;
+        db_client
+            .insert_edge_property(
+                txn,
+                tenant_id,
+                &node_type.type_name,
+                schema_version,
+                &edge.edge_name,
+                &property.property_name,
+                predicate_type_name,
+            )
+            .await?;
+    }
     Ok(())
 }
 
+/// The edge's forward and reverse cardinalities
+fn edge_cardinalities(edge: &Edge) -> (StoredEdgeCardinality, StoredEdgeCardinality) {
+    let cardinality = |to_one: bool| {
+        if to_one {
+            StoredEdgeCardinality::ToOne
+        } else {
+            StoredEdgeCardinality::ToMany
+        }
+    };
+
+    (
+        cardinality(edge.relationship.to_one()),
+        cardinality(edge.relationship.reverse().to_one()),
+    )
+}
+
 fn get_predicate_type_name(
     predicate_type: PredicateType,
     conflict_resolution: ConflictResolution,
@@ -372,3 +559,31 @@ fn get_predicate_type_name(
 
     Ok(type_name)
 }
+
+#[cfg(test)]
+mod tests {
+    use grapl_graphql_codegen::lint::Rule;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_graphql_schema_lint_errors() {
+        let raw_schema = r#"
+type Asset @grapl(identity_algorithm: "static") {
+    hostname: String! @increment_only,
+}
+"#;
+        let lint_errors = match parse_graphql_schema(raw_schema) {
+            Err(DeployGraphqlError::LintErrors(lint_errors)) => lint_errors,
+            _ => panic!("expected the schema to fail linting"),
+        };
+        let rules: Vec<_> = lint_errors
+            .iter()
+            .map(|diagnostic| diagnostic.rule)
+            .collect();
+        assert_eq!(
+            rules,
+            vec![Rule::MissingStaticId, Rule::UnsupportedPropertyType]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+
+use rust_proto::graplinc::grapl::api::graph_schema_manager::v1beta1::messages::{
+    EdgeSchema,
+    EdgeSchemaChange,
+    IdentityAlgorithmChange,
+    NodeSchema,
+    NodeSchemaChanges,
+    NodeSchemaDiff,
+    PropertySchema,
+    PropertySchemaChange,
+};
+
+/// Diffs the node types of two schema versions. Node types that didn't change
+/// are left out, and the rest are ordered by node type.
+pub fn diff_schemas(from: Vec<NodeSchema>, to: Vec<NodeSchema>) -> Vec<NodeSchemaDiff> {
+    let mut from: BTreeMap<String, NodeSchema> = from
+        .into_iter()
+        .map(|node_schema| (node_schema.node_type.value.clone(), node_schema))
+        .collect();
+
+    let mut diffs = Vec::new();
+    for to_schema in to {
+        match from.remove(&to_schema.node_type.value) {
+            Some(from_schema) => {
+                let changes = diff_node_schema(from_schema, to_schema);
+                if !changes.is_empty() {
+                    diffs.push(NodeSchemaDiff::Changed(changes));
+                }
+            }
+            None => diffs.push(NodeSchemaDiff::Added(to_schema)),
+        }
+    }
+    diffs.extend(from.into_values().map(NodeSchemaDiff::Removed));
+
+    diffs.sort_by(|a, b| a.node_type().value.cmp(&b.node_type().value));
+    diffs
+}
+
+/// Diffs a node type present in both schema versions. The schema version
+/// itself is not considered a change.
+pub fn diff_node_schema(from: NodeSchema, to: NodeSchema) -> NodeSchemaChanges {
+    let identity_algorithm_change = if from.identity_algorithm != to.identity_algorithm {
+        Some(IdentityAlgorithmChange {
+            from: from.identity_algorithm,
+            to: to.identity_algorithm,
+        })
+    } else {
+        None
+    };
+
+    let (added_properties, removed_properties, changed_properties) = diff_by_name(
+        from.properties,
+        to.properties,
+        |property: &PropertySchema| property.property_name.value.clone(),
+    );
+
+    let (added_edges, removed_edges, changed_edges) =
+        diff_by_name(from.edges, to.edges, |edge: &EdgeSchema| {
+            edge.edge_name.value.clone()
+        });
+
+    NodeSchemaChanges {
+        node_type: to.node_type,
+        identity_algorithm_change,
+        added_properties,
+        removed_properties,
+        changed_properties: changed_properties
+            .into_iter()
+            .map(|(from, to)| PropertySchemaChange { from, to })
+            .collect(),
+        added_edges,
+        removed_edges,
+        changed_edges: changed_edges
+            .into_iter()
+            .map(|(from, to)| EdgeSchemaChange { from, to })
+            .collect(),
+    }
+}
+
+/// Splits two lists into what was added, removed and changed, matching items
+/// up by name. Each result is ordered by name.
+pub(crate) fn diff_by_name<T: PartialEq>(
+    from: Vec<T>,
+    to: Vec<T>,
+    name: impl Fn(&T) -> String,
+) -> (Vec<T>, Vec<T>, Vec<(T, T)>) {
+    let mut from: BTreeMap<String, T> = from.into_iter().map(|t| (name(&t), t)).collect();
+    let to: BTreeMap<String, T> = to.into_iter().map(|t| (name(&t), t)).collect();
+
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for (name, to_item) in to {
+        match from.remove(&name) {
+            Some(from_item) if from_item != to_item => changed.push((from_item, to_item)),
+            Some(_) => {}
+            None => added.push(to_item),
+        }
+    }
+    let removed = from.into_values().collect();
+
+    (added, removed, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_proto::graplinc::grapl::{
+        api::graph_schema_manager::v1beta1::messages::{
+            EdgeCardinality,
+            IdentityAlgorithm,
+            PropertyType,
+            StaticIdentity,
+        },
+        common::v1beta1::types::{
+            EdgeName,
+            NodeType,
+            PropertyName,
+        },
+    };
+
+    use super::*;
+
+    fn property(name: &'static str, property_type: PropertyType) -> PropertySchema {
+        PropertySchema {
+            property_name: PropertyName::try_from(name).unwrap(),
+            property_type,
+            identity_only: false,
+        }
+    }
+
+    fn edge(name: &'static str, cardinality: EdgeCardinality) -> EdgeSchema {
+        EdgeSchema {
+            edge_name: EdgeName::try_from(name).unwrap(),
+            reverse_edge_name: EdgeName::try_from("reverse").unwrap(),
+            cardinality,
+            reverse_cardinality: EdgeCardinality::ToMany,
+            properties: vec![],
+        }
+    }
+
+    fn node_schema(
+        node_type: &'static str,
+        schema_version: u32,
+        properties: Vec<PropertySchema>,
+        edges: Vec<EdgeSchema>,
+    ) -> NodeSchema {
+        NodeSchema {
+            node_type: NodeType::try_from(node_type).unwrap(),
+            schema_version,
+            identity_algorithm: IdentityAlgorithm::Static(StaticIdentity {
+                static_key_properties: vec![PropertyName::try_from("key").unwrap()],
+            }),
+            properties,
+            edges,
+        }
+    }
+
+    #[test]
+    fn test_unchanged_node_types_are_left_out() {
+        let from = vec![node_schema(
+            "Process",
+            0,
+            vec![property("pid", PropertyType::ImmutableU64)],
+            vec![edge("children", EdgeCardinality::ToMany)],
+        )];
+        let to = vec![node_schema(
+            "Process",
+            1,
+            vec![property("pid", PropertyType::ImmutableU64)],
+            vec![edge("children", EdgeCardinality::ToMany)],
+        )];
+
+        assert!(diff_schemas(from, to).is_empty());
+    }
+
+    #[test]
+    fn test_added_and_removed_node_types() {
+        let from = vec![
+            node_schema("Process", 0, vec![], vec![]),
+            node_schema("File", 0, vec![], vec![]),
+        ];
+        let to = vec![
+            node_schema("Process", 1, vec![], vec![]),
+            node_schema("Asset", 1, vec![], vec![]),
+        ];
+
+        let diffs = diff_schemas(from, to);
+        assert_eq!(
+            diffs,
+            vec![
+                NodeSchemaDiff::Added(node_schema("Asset", 1, vec![], vec![])),
+                NodeSchemaDiff::Removed(node_schema("File", 0, vec![], vec![])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_properties_and_edges() {
+        let from = node_schema(
+            "Process",
+            0,
+            vec![
+                property("pid", PropertyType::ImmutableU64),
+                property("exe", PropertyType::ImmutableString),
+            ],
+            vec![
+                edge("children", EdgeCardinality::ToMany),
+                edge("parent", EdgeCardinality::ToOne),
+            ],
+        );
+        let to = node_schema(
+            "Process",
+            1,
+            vec![
+                property("pid", PropertyType::MaxU64),
+                property("name", PropertyType::ImmutableString),
+            ],
+            vec![
+                edge("children", EdgeCardinality::ToMany),
+                edge("parent", EdgeCardinality::ToMany),
+                edge("files", EdgeCardinality::ToMany),
+            ],
+        );
+
+        let changes = diff_node_schema(from, to);
+        assert_eq!(changes.identity_algorithm_change, None);
+        assert_eq!(
+            changes.added_properties,
+            vec![property("name", PropertyType::ImmutableString)]
+        );
+        assert_eq!(
+            changes.removed_properties,
+            vec![property("exe", PropertyType::ImmutableString)]
+        );
+        assert_eq!(
+            changes.changed_properties,
+            vec![PropertySchemaChange {
+                from: property("pid", PropertyType::ImmutableU64),
+                to: property("pid", PropertyType::MaxU64),
+            }]
+        );
+        assert_eq!(
+            changes.added_edges,
+            vec![edge("files", EdgeCardinality::ToMany)]
+        );
+        assert!(changes.removed_edges.is_empty());
+        assert_eq!(
+            changes.changed_edges,
+            vec![EdgeSchemaChange {
+                from: edge("parent", EdgeCardinality::ToOne),
+                to: edge("parent", EdgeCardinality::ToMany),
+            }]
+        );
+    }
+}
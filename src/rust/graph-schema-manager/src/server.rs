@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use rust_proto::{
     graplinc::grapl::{
         api::{
@@ -5,27 +7,63 @@ use rust_proto::{
                 messages::{
                     DeploySchemaRequest,
                     DeploySchemaResponse,
+                    DiffSchemasRequest,
+                    DiffSchemasResponse,
+                    EdgeSchema,
                     GetEdgeSchemaRequest,
                     GetEdgeSchemaResponse,
+                    GetNodeSchemaRequest,
+                    GetNodeSchemaResponse,
+                    GetRawSchemaRequest,
+                    GetRawSchemaResponse,
+                    GetRetentionPolicyRequest,
+                    GetRetentionPolicyResponse,
+                    ListNodeSchemasRequest,
+                    ListNodeSchemasResponse,
+                    NodeSchema,
+                    NodeSchemaVersions,
+                    PropertySchema,
+                    RetentionPolicy,
                     SchemaType,
+                    SetRetentionPolicyRequest,
+                    SetRetentionPolicyResponse,
                 },
                 server::GraphSchemaManagerApi,
             },
             protocol::status::Status,
         },
-        common::v1beta1::types::EdgeName,
+        common::v1beta1::types::{
+            EdgeName,
+            NodeType,
+        },
     },
     SerDeError,
 };
 
 use crate::{
-    db::client::SchemaDbClient,
+    compatibility::{
+        check_compatibility,
+        CompatibilityViolation,
+    },
+    db::{
+        client::{
+            SchemaDbClient,
+            Txn,
+        },
+        models::InvalidStoredSchema,
+    },
     deploy_graphql_schema::{
         deploy_graphql_schema,
+        node_schemas,
+        parse_graphql_schema,
         DeployGraphqlError,
     },
+    schema_diff::diff_schemas,
 };
 
+/// Scylla's largest allowed TTL, 20 years
+const MAX_RETENTION_SECONDS: u64 = 630_720_000;
+
 #[derive(thiserror::Error, Debug)]
 pub enum GraphSchemaManagerServiceError {
     #[error("NonUtf8 GraphQL Schema: '{0}'")]
@@ -42,6 +80,37 @@ pub enum GraphSchemaManagerServiceError {
         node_type: String,
         edge_name: String,
     },
+    #[error("Invalid RetentionPolicy: time to live must be between 1 and {MAX_RETENTION_SECONDS} seconds, was {0:?}")]
+    InvalidRetentionPolicy(std::time::Duration),
+    #[error("RetentionPolicy sqlx error: '{0}'")]
+    RetentionPolicySqlxError(#[from] sqlx::Error),
+    #[error("NodeSchema not found for tenant_id={tenant_id}, node_type={node_type}, schema_version={schema_version:?}")]
+    NodeSchemaNotFound {
+        tenant_id: uuid::Uuid,
+        node_type: String,
+        schema_version: Option<u32>,
+    },
+    #[error(
+        "Schema version not found for tenant_id={tenant_id}, schema_version={schema_version:?}"
+    )]
+    SchemaVersionNotFound {
+        tenant_id: uuid::Uuid,
+        schema_version: Option<u32>,
+    },
+    #[error("ReadSchema sqlx error: '{0}'")]
+    ReadSchemaSqlxError(sqlx::Error),
+    #[error("Invalid stored schema: '{0}'")]
+    InvalidStoredSchema(#[from] InvalidStoredSchema),
+    #[error("Incompatible schema: {}", format_violations(.0))]
+    IncompatibleSchema(Vec<CompatibilityViolation>),
+}
+
+fn format_violations(violations: &[CompatibilityViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 impl From<GraphSchemaManagerServiceError> for Status {
@@ -58,6 +127,15 @@ impl From<GraphSchemaManagerServiceError> for Status {
             GraphSchemaManagerServiceError::EdgeSchemaNotFound { .. } => Status::internal(msg),
             GraphSchemaManagerServiceError::GetEdgeSchemaSqlxError(_) => Status::internal(msg),
             GraphSchemaManagerServiceError::InvalidReverseEdgeName(_) => Status::internal(msg),
+            GraphSchemaManagerServiceError::InvalidRetentionPolicy(_) => {
+                Status::invalid_argument(msg)
+            }
+            GraphSchemaManagerServiceError::RetentionPolicySqlxError(_) => Status::internal(msg),
+            GraphSchemaManagerServiceError::NodeSchemaNotFound { .. } => Status::not_found(msg),
+            GraphSchemaManagerServiceError::SchemaVersionNotFound { .. } => Status::not_found(msg),
+            GraphSchemaManagerServiceError::ReadSchemaSqlxError(_) => Status::internal(msg),
+            GraphSchemaManagerServiceError::InvalidStoredSchema(_) => Status::internal(msg),
+            GraphSchemaManagerServiceError::IncompatibleSchema(_) => Status::invalid_argument(msg),
         }
     }
 }
@@ -66,6 +144,195 @@ pub struct GraphSchemaManager {
     pub db_client: SchemaDbClient,
 }
 
+impl GraphSchemaManager {
+    /// The node types deployed in a schema version, optionally only
+    /// `node_type`, ordered by node type
+    async fn get_node_schemas(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+        node_type: Option<&NodeType>,
+    ) -> Result<Vec<NodeSchema>, GraphSchemaManagerServiceError> {
+        let node_type_name = node_type.map(|node_type| node_type.value.as_str());
+
+        let node_rows = self
+            .db_client
+            .get_node_schemas(tenant_id, schema_version, node_type_name)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?;
+
+        let mut properties: HashMap<String, Vec<PropertySchema>> = HashMap::new();
+        for row in self
+            .db_client
+            .get_property_schemas(tenant_id, schema_version, node_type_name)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?
+        {
+            properties
+                .entry(row.node_type.clone())
+                .or_default()
+                .push(row.try_into()?);
+        }
+
+        let mut edge_properties: HashMap<(String, String), Vec<PropertySchema>> = HashMap::new();
+        for row in self
+            .db_client
+            .get_edge_property_schemas(tenant_id, schema_version, node_type_name)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?
+        {
+            edge_properties
+                .entry((row.node_type.clone(), row.edge_name.clone()))
+                .or_default()
+                .push(row.try_into()?);
+        }
+
+        // Both directions of an edge are stored under the node type that
+        // declared it, and edge properties are stored under the forward edge
+        let mut edges: HashMap<String, Vec<EdgeSchema>> = HashMap::new();
+        for row in self
+            .db_client
+            .get_edge_schemas(tenant_id, schema_version, node_type_name)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?
+        {
+            let properties = edge_properties
+                .remove(&(row.node_type.clone(), row.forward_edge_name.clone()))
+                .unwrap_or_default();
+            edges.entry(row.node_type).or_default().push(EdgeSchema {
+                edge_name: EdgeName::try_from(row.forward_edge_name)
+                    .map_err(InvalidStoredSchema::from)?,
+                reverse_edge_name: EdgeName::try_from(row.reverse_edge_name)
+                    .map_err(InvalidStoredSchema::from)?,
+                cardinality: row.forward_edge_cardinality.into(),
+                reverse_cardinality: row.reverse_edge_cardinality.into(),
+                properties,
+            });
+        }
+
+        node_rows
+            .into_iter()
+            .map(|row| {
+                Ok(NodeSchema {
+                    identity_algorithm: row.identity_algorithm()?,
+                    properties: properties.remove(&row.node_type).unwrap_or_default(),
+                    edges: edges.remove(&row.node_type).unwrap_or_default(),
+                    schema_version,
+                    node_type: NodeType {
+                        value: row.node_type,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// `schema_version`, or the latest version the tenant deployed if None
+    async fn resolve_schema_version(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: Option<u32>,
+    ) -> Result<u32, GraphSchemaManagerServiceError> {
+        if let Some(schema_version) = schema_version {
+            return Ok(schema_version);
+        }
+
+        self.db_client
+            .get_latest_schema_version(tenant_id, None)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?
+            .ok_or(GraphSchemaManagerServiceError::SchemaVersionNotFound {
+                tenant_id,
+                schema_version,
+            })
+    }
+
+    /// Claims `schema_version` as the tenant's latest, erroring unless it's
+    /// greater than every version deployed before. The tenant stays locked
+    /// until `txn` ends, so that deployments are checked one at a time.
+    async fn claim_schema_version(
+        &self,
+        txn: &mut Txn<'_>,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+    ) -> Result<(), GraphSchemaManagerServiceError> {
+        let last_schema_version = self
+            .db_client
+            .claim_schema_version(txn, tenant_id, schema_version)
+            .await
+            .map_err(DeployGraphqlError::from)?;
+        match last_schema_version {
+            Some(last_schema_version) => {
+                Err(GraphSchemaManagerServiceError::IncompatibleSchema(vec![
+                    CompatibilityViolation::SchemaVersionNotIncreasing {
+                        last_schema_version,
+                        schema_version,
+                    },
+                ]))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the schema being deployed against every version deployed
+    /// before it, so that a property or edge removed in one version can't be
+    /// added back in a later one with a different type
+    async fn check_compatibility(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_schemas: &[NodeSchema],
+    ) -> Result<(), GraphSchemaManagerServiceError> {
+        let mut schema_versions: Vec<u32> = self
+            .db_client
+            .list_node_schema_versions(tenant_id)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?
+            .into_iter()
+            .map(|row| row.schema_version as u32)
+            .collect();
+        schema_versions.sort_unstable();
+        schema_versions.dedup();
+
+        let mut violations = Vec::new();
+        for schema_version in schema_versions {
+            let deployed_node_schemas = self
+                .get_node_schemas(tenant_id, schema_version, None)
+                .await?;
+            for violation in
+                check_compatibility(&diff_schemas(deployed_node_schemas, node_schemas.to_vec()))
+            {
+                if !violations.contains(&violation) {
+                    violations.push(violation);
+                }
+            }
+        }
+        if !violations.is_empty() {
+            return Err(GraphSchemaManagerServiceError::IncompatibleSchema(
+                violations,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Every node type in a schema version, erroring if none were deployed
+    async fn get_schema_version(
+        &self,
+        tenant_id: uuid::Uuid,
+        schema_version: u32,
+    ) -> Result<Vec<NodeSchema>, GraphSchemaManagerServiceError> {
+        let node_schemas = self
+            .get_node_schemas(tenant_id, schema_version, None)
+            .await?;
+        if node_schemas.is_empty() {
+            return Err(GraphSchemaManagerServiceError::SchemaVersionNotFound {
+                tenant_id,
+                schema_version: Some(schema_version),
+            });
+        }
+        Ok(node_schemas)
+    }
+}
+
 #[async_trait::async_trait]
 impl GraphSchemaManagerApi for GraphSchemaManager {
     type Error = GraphSchemaManagerServiceError;
@@ -75,18 +342,49 @@ impl GraphSchemaManagerApi for GraphSchemaManager {
         &self,
         request: DeploySchemaRequest,
     ) -> Result<DeploySchemaResponse, Self::Error> {
-        match request.schema_type {
+        let DeploySchemaRequest {
+            tenant_id,
+            schema,
+            schema_type,
+            schema_version,
+            dry_run,
+        } = request;
+
+        match schema_type {
             SchemaType::GraphqlV0 => {
-                let schema = String::from_utf8(request.schema.to_vec())
+                let schema = String::from_utf8(schema.to_vec())
                     .map_err(GraphSchemaManagerServiceError::NonUtf8GraphQLSchema)?;
 
+                let node_types = parse_graphql_schema(&schema)?;
+                let node_schemas = node_schemas(&node_types, schema_version)?;
+
+                // The schema is checked and written in the same transaction,
+                // so that no other deployment can slip in between
+                let mut txn = self
+                    .db_client
+                    .begin_txn()
+                    .await
+                    .map_err(DeployGraphqlError::from)?;
+                self.claim_schema_version(&mut txn, tenant_id, schema_version)
+                    .await?;
+                self.check_compatibility(tenant_id, &node_schemas).await?;
                 deploy_graphql_schema(
-                    request.tenant_id,
+                    &mut txn,
+                    tenant_id,
                     &schema,
-                    request.schema_version,
+                    &node_types,
+                    schema_version,
                     &self.db_client,
                 )
                 .await?;
+
+                // A dry run writes everything before rolling it back, so that
+                // database constraints are still checked
+                if dry_run {
+                    txn.rollback().await.map_err(DeployGraphqlError::from)?;
+                } else {
+                    txn.commit().await.map_err(DeployGraphqlError::from)?;
+                }
                 Ok(DeploySchemaResponse {})
             }
         }
@@ -123,4 +421,207 @@ impl GraphSchemaManagerApi for GraphSchemaManager {
             reverse_cardinality: response.reverse_edge_cardinality.into(),
         })
     }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn set_retention_policy(
+        &self,
+        request: SetRetentionPolicyRequest,
+    ) -> Result<SetRetentionPolicyResponse, Self::Error> {
+        let SetRetentionPolicyRequest {
+            tenant_id,
+            node_type,
+            retention_policy,
+        } = request;
+
+        match (node_type, retention_policy) {
+            (Some(node_type), Some(retention_policy)) => {
+                let retention_seconds = retention_seconds(retention_policy)?;
+                self.db_client
+                    .upsert_node_type_retention_policy(
+                        tenant_id,
+                        &node_type.value,
+                        retention_seconds,
+                    )
+                    .await?;
+            }
+            (Some(node_type), None) => {
+                self.db_client
+                    .delete_node_type_retention_policy(tenant_id, &node_type.value)
+                    .await?;
+            }
+            (None, Some(retention_policy)) => {
+                let retention_seconds = retention_seconds(retention_policy)?;
+                self.db_client
+                    .upsert_tenant_retention_policy(tenant_id, retention_seconds)
+                    .await?;
+            }
+            (None, None) => {
+                self.db_client
+                    .delete_tenant_retention_policy(tenant_id)
+                    .await?;
+            }
+        }
+
+        Ok(SetRetentionPolicyResponse {})
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn get_retention_policy(
+        &self,
+        request: GetRetentionPolicyRequest,
+    ) -> Result<GetRetentionPolicyResponse, Self::Error> {
+        let GetRetentionPolicyRequest {
+            tenant_id,
+            node_type,
+        } = request;
+
+        let row = match self
+            .db_client
+            .get_node_type_retention_policy(tenant_id, &node_type.value)
+            .await?
+        {
+            Some(row) => Some(row),
+            None => {
+                self.db_client
+                    .get_tenant_retention_policy(tenant_id)
+                    .await?
+            }
+        };
+
+        Ok(GetRetentionPolicyResponse {
+            retention_policy: row.map_or(RetentionPolicy::RetainForever, RetentionPolicy::from),
+        })
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn get_node_schema(
+        &self,
+        request: GetNodeSchemaRequest,
+    ) -> Result<GetNodeSchemaResponse, Self::Error> {
+        let GetNodeSchemaRequest {
+            tenant_id,
+            node_type,
+            schema_version,
+        } = request;
+
+        let not_found = || GraphSchemaManagerServiceError::NodeSchemaNotFound {
+            tenant_id,
+            node_type: node_type.value.clone(),
+            schema_version,
+        };
+
+        let resolved_schema_version = match schema_version {
+            Some(schema_version) => schema_version,
+            None => self
+                .db_client
+                .get_latest_schema_version(tenant_id, Some(&node_type.value))
+                .await
+                .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?
+                .ok_or_else(not_found)?,
+        };
+
+        let node_schema = self
+            .get_node_schemas(tenant_id, resolved_schema_version, Some(&node_type))
+            .await?
+            .pop()
+            .ok_or_else(not_found)?;
+
+        Ok(GetNodeSchemaResponse { node_schema })
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn list_node_schemas(
+        &self,
+        request: ListNodeSchemasRequest,
+    ) -> Result<ListNodeSchemasResponse, Self::Error> {
+        let rows = self
+            .db_client
+            .list_node_schema_versions(request.tenant_id)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?;
+
+        // Rows are ordered by node type, so each node type's versions are
+        // contiguous
+        let mut node_schemas: Vec<NodeSchemaVersions> = Vec::new();
+        for row in rows {
+            match node_schemas.last_mut() {
+                Some(last) if last.node_type.value == row.node_type => {
+                    last.versions.push(row.into());
+                }
+                _ => node_schemas.push(NodeSchemaVersions {
+                    node_type: NodeType {
+                        value: row.node_type.clone(),
+                    },
+                    versions: vec![row.into()],
+                }),
+            }
+        }
+
+        Ok(ListNodeSchemasResponse { node_schemas })
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn get_raw_schema(
+        &self,
+        request: GetRawSchemaRequest,
+    ) -> Result<GetRawSchemaResponse, Self::Error> {
+        let GetRawSchemaRequest {
+            tenant_id,
+            schema_version,
+        } = request;
+
+        let resolved_schema_version = self
+            .resolve_schema_version(tenant_id, schema_version)
+            .await?;
+
+        let row = self
+            .db_client
+            .get_raw_schema(tenant_id, resolved_schema_version)
+            .await
+            .map_err(GraphSchemaManagerServiceError::ReadSchemaSqlxError)?
+            .ok_or(GraphSchemaManagerServiceError::SchemaVersionNotFound {
+                tenant_id,
+                schema_version,
+            })?;
+
+        Ok(GetRawSchemaResponse::try_from(row)?)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn diff_schemas(
+        &self,
+        request: DiffSchemasRequest,
+    ) -> Result<DiffSchemasResponse, Self::Error> {
+        let DiffSchemasRequest {
+            tenant_id,
+            from_schema_version,
+            to_schema_version,
+        } = request;
+
+        let from = self
+            .get_schema_version(tenant_id, from_schema_version)
+            .await?;
+        let to = self
+            .get_schema_version(tenant_id, to_schema_version)
+            .await?;
+
+        Ok(DiffSchemasResponse {
+            node_schema_diffs: diff_schemas(from, to),
+        })
+    }
+}
+
+/// The policy's time to live in whole seconds, or None to retain forever
+fn retention_seconds(
+    retention_policy: RetentionPolicy,
+) -> Result<Option<i64>, GraphSchemaManagerServiceError> {
+    match retention_policy {
+        RetentionPolicy::TimeToLive(ttl) => match retention_policy.ttl_seconds() {
+            Some(seconds) if (1..=MAX_RETENTION_SECONDS).contains(&seconds) => {
+                Ok(Some(seconds as i64))
+            }
+            _ => Err(GraphSchemaManagerServiceError::InvalidRetentionPolicy(ttl)),
+        },
+        RetentionPolicy::RetainForever => Ok(None),
+    }
 }
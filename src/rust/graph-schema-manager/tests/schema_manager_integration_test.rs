@@ -36,6 +36,7 @@ async fn test_deploy_schema() -> eyre::Result<()> {
             schema: get_example_graphql_schema()?,
             schema_type: sm_api::SchemaType::GraphqlV0,
             schema_version: 0,
+            dry_run: false,
         })
         .await?;
 
@@ -54,3 +55,201 @@ async fn test_deploy_schema() -> eyre::Result<()> {
     assert_eq!(edge_schema.reverse_edge_name.value, "executed_as_processes");
     Ok(())
 }
+
+#[tokio::test]
+async fn test_read_and_diff_schemas() -> eyre::Result<()> {
+    let client_config = Figment::new()
+        .merge(Env::prefixed("GRAPH_SCHEMA_MANAGER_CLIENT_"))
+        .extract()?;
+    let mut client = GraphSchemaManagerClient::connect(client_config).await?;
+
+    let tenant_id = uuid::Uuid::new_v4();
+    let schema = get_example_graphql_schema()?;
+
+    for schema_version in [0, 1] {
+        client
+            .deploy_schema(sm_api::DeploySchemaRequest {
+                tenant_id,
+                schema: schema.clone(),
+                schema_type: sm_api::SchemaType::GraphqlV0,
+                schema_version,
+                dry_run: false,
+            })
+            .await?;
+    }
+
+    let process_schema = client
+        .get_node_schema(sm_api::GetNodeSchemaRequest {
+            tenant_id,
+            node_type: common_api::NodeType {
+                value: "Process".to_string(),
+            },
+            schema_version: None,
+        })
+        .await?
+        .node_schema;
+
+    assert_eq!(process_schema.schema_version, 1);
+    match &process_schema.identity_algorithm {
+        sm_api::IdentityAlgorithm::Session(session_identity) => {
+            assert_eq!(
+                session_identity.pseudo_key_properties,
+                vec![common_api::PropertyName {
+                    value: "process_id".to_string()
+                }]
+            );
+        }
+        other => eyre::bail!("expected session identity, got {other:?}"),
+    }
+
+    let children = process_schema
+        .edges
+        .iter()
+        .find(|edge| edge.edge_name.value == "children")
+        .ok_or_else(|| eyre::eyre!("missing children edge"))?;
+    assert_eq!(children.reverse_edge_name.value, "parent");
+    assert_eq!(children.cardinality, sm_api::EdgeCardinality::ToMany);
+    assert_eq!(children.properties.len(), 2);
+
+    let listed = client
+        .list_node_schemas(sm_api::ListNodeSchemasRequest { tenant_id })
+        .await?;
+    let node_types: Vec<_> = listed
+        .node_schemas
+        .iter()
+        .map(|node_schema| node_schema.node_type.value.as_str())
+        .collect();
+    assert_eq!(node_types, vec!["Asset", "File", "Process"]);
+    for node_schema in listed.node_schemas.iter() {
+        let versions: Vec<_> = node_schema
+            .versions
+            .iter()
+            .map(|version| version.schema_version)
+            .collect();
+        assert_eq!(versions, vec![0, 1]);
+    }
+
+    let raw_schema = client
+        .get_raw_schema(sm_api::GetRawSchemaRequest {
+            tenant_id,
+            schema_version: Some(0),
+        })
+        .await?;
+    assert_eq!(raw_schema.schema_version, 0);
+    assert_eq!(raw_schema.schema, schema);
+
+    let diff = client
+        .diff_schemas(sm_api::DiffSchemasRequest {
+            tenant_id,
+            from_schema_version: 0,
+            to_schema_version: 1,
+        })
+        .await?;
+    assert!(diff.node_schema_diffs.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deploy_schema_compatibility() -> eyre::Result<()> {
+    let client_config = Figment::new()
+        .merge(Env::prefixed("GRAPH_SCHEMA_MANAGER_CLIENT_"))
+        .extract()?;
+    let mut client = GraphSchemaManagerClient::connect(client_config).await?;
+
+    let tenant_id = uuid::Uuid::new_v4();
+    let schema = get_example_graphql_schema()?;
+    let incompatible_schema = Bytes::from(
+        std::str::from_utf8(&schema)?
+            .replace("process_name: String!", "process_name: UInt!")
+            .replace(
+                "reverse: \"parent\", reverse_relationship: \"ToOne\"",
+                "reverse: \"parent\", reverse_relationship: \"ToMany\"",
+            ),
+    );
+
+    let deploy_schema_request =
+        |schema: &Bytes, schema_version: u32, dry_run: bool| sm_api::DeploySchemaRequest {
+            tenant_id,
+            schema: schema.clone(),
+            schema_type: sm_api::SchemaType::GraphqlV0,
+            schema_version,
+            dry_run,
+        };
+
+    // A dry run deploys nothing
+    client
+        .deploy_schema(deploy_schema_request(&schema, 0, true))
+        .await?;
+    let listed = client
+        .list_node_schemas(sm_api::ListNodeSchemasRequest { tenant_id })
+        .await?;
+    assert!(listed.node_schemas.is_empty());
+
+    client
+        .deploy_schema(deploy_schema_request(&schema, 0, false))
+        .await?;
+
+    // The schema version must increase
+    assert!(client
+        .deploy_schema(deploy_schema_request(&schema, 0, true))
+        .await
+        .is_err());
+
+    // Property types and edge cardinalities can't change, even in a dry run
+    assert!(client
+        .deploy_schema(deploy_schema_request(&incompatible_schema, 1, true))
+        .await
+        .is_err());
+    assert!(client
+        .deploy_schema(deploy_schema_request(&incompatible_schema, 1, false))
+        .await
+        .is_err());
+
+    client
+        .deploy_schema(deploy_schema_request(&schema, 1, false))
+        .await?;
+
+    let raw_schema = client
+        .get_raw_schema(sm_api::GetRawSchemaRequest {
+            tenant_id,
+            schema_version: None,
+        })
+        .await?;
+    assert_eq!(raw_schema.schema_version, 1);
+
+    // A removed property can't come back with a different type, since data
+    // written under its old type may still be stored
+    let schema_without_process_name = Bytes::from(
+        std::str::from_utf8(&schema)?.replace("    process_name: String! @immutable,\n", ""),
+    );
+    let schema_with_retyped_process_name = Bytes::from(std::str::from_utf8(&schema)?.replace(
+        "process_name: String! @immutable",
+        "process_name: UInt! @immutable",
+    ));
+    client
+        .deploy_schema(deploy_schema_request(
+            &schema_without_process_name,
+            2,
+            false,
+        ))
+        .await?;
+    assert!(client
+        .deploy_schema(deploy_schema_request(
+            &schema_with_retyped_process_name,
+            3,
+            false
+        ))
+        .await
+        .is_err());
+
+    // Of two concurrent deployments of the same version, only one succeeds
+    let mut other_client = client.clone();
+    let (result, other_result) = tokio::join!(
+        client.deploy_schema(deploy_schema_request(&schema, 3, false)),
+        other_client.deploy_schema(deploy_schema_request(&schema, 3, false)),
+    );
+    assert!(result.is_ok() != other_result.is_ok());
+
+    Ok(())
+}
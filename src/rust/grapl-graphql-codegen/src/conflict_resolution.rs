@@ -18,7 +18,7 @@ use crate::{
 
 /// ConflictResolution represents how, given two instances of the same predicate, those
 /// predicates should be merged together.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ConflictResolution {
     /// Immutable can be thought of as a "pick any value", though the most common implementation
     /// is a "First Write Wins".
@@ -38,6 +38,16 @@ impl ConflictResolution {
         }
     }
 
+    /// The `#[grapl(..)]` attribute that derive-dynamic-node resolves conflicts
+    /// with
+    pub fn as_grapl_attribute(&self) -> &'static str {
+        match self {
+            Self::Immutable => "immutable",
+            Self::IncrementOnly => "increment",
+            Self::DecrementOnly => "decrement",
+        }
+    }
+
     pub fn from_directive(directive: &Directive<'static, String>) -> Option<Self> {
         match directive.name.as_str() {
             PSEUDO_KEY => Some(ConflictResolution::Immutable),
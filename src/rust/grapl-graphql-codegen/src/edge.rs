@@ -9,6 +9,7 @@ use graphql_parser::schema::{
 };
 
 use crate::{
+    edge_property::EdgeProperty,
     edge_rel::EdgeRel,
     errors::CodeGenError,
 };
@@ -27,6 +28,8 @@ pub struct Edge {
     pub target_type_name: String,
     /// Relationships denote whether the forward/reverse edges are to one or many nodes
     pub relationship: EdgeRel,
+    /// The properties carried by the edge, shared by both of its directions
+    pub properties: Vec<EdgeProperty>,
 }
 
 // Python generation code for Edge
@@ -38,6 +41,7 @@ impl Edge {
             source_type_name,
             target_type_name,
             relationship,
+            properties,
         } = self;
 
         Edge {
@@ -46,6 +50,7 @@ impl Edge {
             source_type_name: target_type_name,
             target_type_name: source_type_name,
             relationship: relationship.reverse(),
+            properties,
         }
     }
 
@@ -138,6 +143,109 @@ impl Edge {
     }
 }
 
+// Rust generation code for Edge
+impl Edge {
+    /// A method on the source node that adds the edge to a
+    /// `GraphDescription`, taking the edge's properties as arguments
+    pub fn generate_rust_add_edge_method(&self) -> Result<String, CodeGenError> {
+        let mut add_edge_method = String::with_capacity(512);
+        let edge_name = self.edge_name.as_str();
+
+        let mut property_arguments = String::with_capacity(128);
+        let mut property_entries = String::with_capacity(256);
+        for property in self.properties.iter() {
+            let property_type = property
+                .predicate_type
+                .into_rust_property_type(property.conflict_resolution)
+                .ok_or_else(|| CodeGenError::UnsupportedPropertyType {
+                    property_name: property.property_name.clone(),
+                    predicate_type: property.predicate_type,
+                    conflict_resolution: property.conflict_resolution,
+                })?;
+            property_arguments = property_arguments
+                + &format!(
+                    "        {}: impl Into<{}>,",
+                    property.property_name, property_type
+                )
+                + "\n";
+            property_entries = property_entries
+                + &format!(
+                    r#"                ("{name}".to_owned(), NodeProperty::from({name}.into())),"#,
+                    name = property.property_name
+                )
+                + "\n";
+        }
+
+        add_edge_method = add_edge_method
+            + &format!(
+                "    /// Adds a `{}` edge from this node to `to`. Its reverse edge is `{}`.",
+                edge_name, self.reverse_edge_name
+            )
+            + "\n";
+        add_edge_method = add_edge_method + &format!("    pub fn add_{}_edge(", edge_name) + "\n";
+        add_edge_method += "        &self,\n";
+        add_edge_method += "        graph: &mut GraphDescription,\n";
+        add_edge_method =
+            add_edge_method + &format!("        to: &{}Node,", self.target_type_name) + "\n";
+        add_edge_method += &property_arguments;
+        add_edge_method += "    ) {\n";
+
+        if self.properties.is_empty() {
+            add_edge_method = add_edge_method
+                + &format!(
+                    r#"        graph.add_edge("{}", self.clone_node_key(), to.clone_node_key());"#,
+                    edge_name
+                )
+                + "\n";
+        } else {
+            add_edge_method += "        graph.add_edge_with_properties(\n";
+            add_edge_method = add_edge_method + &format!(r#"            "{}","#, edge_name) + "\n";
+            add_edge_method += "            self.clone_node_key(),\n";
+            add_edge_method += "            to.clone_node_key(),\n";
+            add_edge_method += "            HashMap::from([\n";
+            add_edge_method += &property_entries;
+            add_edge_method += "            ]),\n";
+            add_edge_method += "        );\n";
+        }
+        add_edge_method += "    }\n";
+
+        Ok(add_edge_method)
+    }
+
+    /// A query builder method that adds a neighbor query through the edge
+    pub fn generate_rust_query_method(&self) -> String {
+        let mut query_method = String::with_capacity(512);
+        let edge_name = self.edge_name.as_str();
+        let target_query_name = format!("{}Query", self.target_type_name);
+
+        query_method = query_method
+            + &format!(
+                "    pub fn with_{}(&mut self, init_edge: impl FnOnce(&mut {}<'_>)) -> &mut Self {{",
+                edge_name, target_query_name
+            )
+            + "\n";
+        query_method += "        self.0.with_edge_to(\n";
+        for name in [edge_name, self.reverse_edge_name.as_str()] {
+            query_method += "            EdgeName {\n";
+            query_method =
+                query_method + &format!(r#"                value: "{}".to_owned(),"#, name) + "\n";
+            query_method += "            },\n";
+        }
+        query_method =
+            query_method + &format!("            {}::node_type(),", target_query_name) + "\n";
+        query_method = query_method
+            + &format!(
+                "            |neighbor| init_edge(&mut {}::new(neighbor)),",
+                target_query_name
+            )
+            + "\n";
+        query_method += "        );\n";
+        query_method += "        self\n";
+        query_method += "    }\n";
+        query_method
+    }
+}
+
 impl TryFrom<(String, &Field<'static, String>)> for Edge {
     type Error = CodeGenError;
 
@@ -164,6 +272,11 @@ impl TryFrom<(String, &Field<'static, String>)> for Edge {
 
         let target_type_name = get_type_name(&field.field_type);
         let relationship = field.try_into()?;
+        let properties = field
+            .arguments
+            .iter()
+            .map(EdgeProperty::try_from)
+            .collect::<Result<_, _>>()?;
 
         Ok(Edge {
             edge_name,
@@ -171,6 +284,7 @@ impl TryFrom<(String, &Field<'static, String>)> for Edge {
             source_type_name,
             target_type_name,
             relationship,
+            properties,
         })
     }
 }
@@ -0,0 +1,38 @@
+use std::convert::{
+    TryFrom,
+    TryInto,
+};
+
+use graphql_parser::schema::InputValue;
+
+use crate::{
+    conflict_resolution::ConflictResolution,
+    errors::CodeGenError,
+    predicate_type::PredicateType,
+};
+
+/// An EdgeProperty is a property carried by an edge, such as when it was
+/// first or last seen. They're declared as arguments of the edge's field,
+/// e.g. `connected_to(first_seen_at: UInt! @decrement_only): [IpAddress]`.
+#[derive(Debug, Clone)]
+pub struct EdgeProperty {
+    pub property_name: String,
+    pub predicate_type: PredicateType,
+    pub conflict_resolution: ConflictResolution,
+}
+
+impl TryFrom<&InputValue<'static, String>> for EdgeProperty {
+    type Error = CodeGenError;
+
+    fn try_from(value: &InputValue<'static, String>) -> Result<Self, Self::Error> {
+        let property_name = value.name.to_string();
+        let predicate_type = PredicateType::try_from(&value.value_type)?;
+        let conflict_resolution = value.directives.as_slice().try_into()?;
+
+        Ok(Self {
+            property_name,
+            predicate_type,
+            conflict_resolution,
+        })
+    }
+}
@@ -1,6 +1,10 @@
 use graphql_parser::schema::Directive;
 
-use crate::node_type::MergeFailure;
+use crate::{
+    conflict_resolution::ConflictResolution,
+    node_type::MergeFailure,
+    predicate_type::PredicateType,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum CodeGenError {
@@ -22,6 +26,12 @@ pub enum CodeGenError {
     MissingGraplDirectiveArguments {
         directives: Vec<Directive<'static, String>>,
     },
+    #[error("Unsupported property type")]
+    UnsupportedPropertyType {
+        property_name: String,
+        predicate_type: PredicateType,
+        conflict_resolution: ConflictResolution,
+    },
     #[error("Failed to extend node schema")]
     MergeFailure(#[from] MergeFailure),
 }
@@ -46,3 +46,26 @@ pub fn validate_code(code: &str) -> Result<()> {
     assert!(output.status.success());
     Ok(())
 }
+
+/// Helpers for the CLI, allowing it to check that the generated code parses
+pub fn execute_rustfmt(code: &[u8]) -> Result<Output> {
+    let mut rustfmt = Command::new("rustfmt")
+        .arg("--edition=2021")
+        .arg("--emit=stdout")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start rustfmt process");
+
+    {
+        let child_stdin = rustfmt.stdin.as_mut().unwrap();
+        child_stdin.write_all(code)?;
+    }
+    Ok(rustfmt.wait_with_output()?)
+}
+
+pub fn validate_rust_code(code: &str) -> Result<()> {
+    let output = execute_rustfmt(code.as_bytes())?;
+    assert!(output.status.success());
+    Ok(())
+}
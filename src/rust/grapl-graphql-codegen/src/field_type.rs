@@ -10,17 +10,14 @@ pub enum FieldType {
 
 impl From<&Field<'static, String>> for FieldType {
     fn from(field: &Field<'static, String>) -> Self {
-        field
+        if field
             .directives
             .iter()
-            .map(|d| {
-                match d.name.as_str() {
-                    "edge" => FieldType::Edge,
-                    // todo: We should be more specific here
-                    _ => FieldType::Predicate,
-                }
-            })
-            .next()
-            .unwrap()
+            .any(|directive| directive.name == "edge")
+        {
+            FieldType::Edge
+        } else {
+            FieldType::Predicate
+        }
     }
 }
@@ -2,7 +2,7 @@ use graphql_parser::schema::Directive;
 
 /// Identity Algorithms take various parameters, and the IdentityPreidcateType
 /// represents which of those paramters a given field may be
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum IdentityPredicateType {
     SessionPseudoKey,
     SessionCreateTime,
@@ -30,4 +30,16 @@ impl IdentityPredicateType {
             _ => None,
         }
     }
+
+    /// The `#[grapl(..)]` attribute that marks an identity property for
+    /// derive-dynamic-node
+    pub fn as_grapl_attribute(&self) -> &'static str {
+        match self {
+            Self::SessionPseudoKey => PSEUDO_KEY,
+            Self::SessionCreateTime => CREATE_TIME,
+            Self::SessionLastSeenTime => LAST_SEEN_TIME,
+            Self::SessionTerminateTime => TERMINATE_TIME,
+            Self::StaticId => STATIC_ID,
+        }
+    }
 }
@@ -0,0 +1,176 @@
+//! The languages that code can be generated in, and the parts of a generated
+//! file that aren't specific to any one node type
+
+use std::{
+    collections::BTreeSet,
+    str::FromStr,
+};
+
+use crate::{
+    errors::CodeGenError,
+    identification_algorithm::IdentificationAlgorithm,
+    node_type::NodeType,
+    predicate_type::PredicateType,
+};
+
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown language {0}, expected python or rust")]
+pub struct UnknownLang(String);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    Python,
+    Rust,
+}
+
+impl FromStr for Lang {
+    type Err = UnknownLang;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "python" => Ok(Self::Python),
+            "rust" => Ok(Self::Rust),
+            other => Err(UnknownLang(other.to_owned())),
+        }
+    }
+}
+
+/// Generates a single file of code for all of the node types
+pub fn generate_code(lang: Lang, node_types: &[NodeType]) -> Result<String, CodeGenError> {
+    match lang {
+        Lang::Python => Ok(generate_python_code(node_types)),
+        Lang::Rust => generate_rust_code(node_types),
+    }
+}
+
+pub fn generate_python_code(node_types: &[NodeType]) -> String {
+    let mut all_code = String::with_capacity(1024 * node_types.len());
+    all_code.push_str(&standin_imports());
+    for node_type in node_types {
+        let pycode = node_type.generate_python_code();
+        all_code.push_str(&pycode);
+    }
+    all_code
+}
+
+pub fn generate_rust_code(node_types: &[NodeType]) -> Result<String, CodeGenError> {
+    let mut all_code = String::with_capacity(2048 * node_types.len());
+    all_code.push_str(&rust_imports(node_types));
+    for node_type in node_types {
+        all_code.push('\n');
+        all_code.push_str(&node_type.generate_rust_code()?);
+    }
+    Ok(all_code)
+}
+
+fn standin_imports() -> String {
+    let mut code = String::new();
+    code.push_str("from __future__ import annotations\n");
+    code.push_str("from typing import Optional, Any, Set, List, Dict, Tuple\n");
+    code.push_str("import grapl_analyzerlib\n");
+    code.push_str("import grapl_analyzerlib.node_types\n");
+    code.push_str("import grapl_analyzerlib.nodes.entity\n");
+    code.push_str("import grapl_analyzerlib.queryable\n");
+    code
+}
+
+/// Only what the node types use is imported, so that the generated code
+/// compiles without unused import warnings. The derive-dynamic-node macros
+/// expect `IdStrategy`, `NodeProperty` and the identity strategy types to be
+/// in scope.
+fn rust_imports(node_types: &[NodeType]) -> String {
+    let mut derives = BTreeSet::from(["NodeDescription"]);
+    let mut graph_types = BTreeSet::from(["IdStrategy", "NodeDescription", "NodeProperty"]);
+    let mut query_types = BTreeSet::new();
+    let mut common_types = BTreeSet::from(["NodeType"]);
+    let mut std_types = BTreeSet::new();
+
+    for node_type in node_types {
+        match node_type.identification_algorithm {
+            IdentificationAlgorithm::Session => {
+                derives.insert("GraplSessionId");
+                graph_types.insert("Session");
+            }
+            IdentificationAlgorithm::Static => {
+                derives.insert("GraplStaticId");
+                graph_types.insert("Static");
+            }
+        }
+
+        if node_type
+            .predicates
+            .iter()
+            .any(|predicate| matches!(predicate.predicate_type, PredicateType::String))
+        {
+            query_types.insert("AndStringFilters");
+            common_types.insert("PropertyName");
+        }
+
+        for edge in node_type.edges.iter() {
+            graph_types.insert("GraphDescription");
+            common_types.insert("EdgeName");
+            for property in edge.properties.iter() {
+                std_types.insert("HashMap");
+                if let Some(property_type) = property
+                    .predicate_type
+                    .into_rust_property_type(property.conflict_resolution)
+                {
+                    graph_types.insert(property_type);
+                }
+            }
+        }
+    }
+
+    let mut imports = String::with_capacity(1024);
+    imports.push_str("// Generated by grapl-graphql-codegen. Do not edit.\n");
+    if !std_types.is_empty() {
+        imports.push_str(&use_statement("std::collections", &std_types));
+        imports.push('\n');
+    }
+    imports.push_str(&use_statement("derive_dynamic_node", &derives));
+    imports.push_str(&use_statement(
+        "graph_query::node_query",
+        &BTreeSet::from(["NodeQuery"]),
+    ));
+    imports.push_str(&use_statement(
+        "rust_proto::graplinc::grapl::api::graph::v1beta1",
+        &graph_types,
+    ));
+    if !query_types.is_empty() {
+        imports.push_str(&use_statement(
+            "rust_proto::graplinc::grapl::api::graph_query::v1beta1::messages",
+            &query_types,
+        ));
+    }
+    imports.push_str(&use_statement(
+        "rust_proto::graplinc::grapl::common::v1beta1::types",
+        &common_types,
+    ));
+    imports
+}
+
+fn use_statement(path: &str, names: &BTreeSet<&str>) -> String {
+    match names.len() {
+        1 => format!("use {}::{};\n", path, names.iter().next().unwrap()),
+        _ => {
+            let mut statement = format!("use {}::{{\n", path);
+            for name in names {
+                statement = statement + "    " + name + ",\n";
+            }
+            statement += "};\n";
+            statement
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lang_from_str() {
+        assert_eq!(Lang::from_str("python").unwrap(), Lang::Python);
+        assert_eq!(Lang::from_str("rust").unwrap(), Lang::Rust);
+        assert!(Lang::from_str("go").is_err());
+    }
+}
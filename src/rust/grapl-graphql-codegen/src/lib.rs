@@ -2,12 +2,15 @@ pub mod as_static_python;
 pub mod conflict_resolution;
 pub mod constants;
 pub mod edge;
+pub mod edge_property;
 pub mod edge_rel;
 pub mod errors;
 pub mod external_helpers;
 pub mod field_type;
 pub mod identification_algorithm;
 pub mod identity_predicate_type;
+pub mod lang;
+pub mod lint;
 pub mod node_predicate;
 pub mod node_type;
 pub mod predicate_type;
@@ -0,0 +1,1429 @@
+//! Checks a schema against the rules that graph-schema-manager and
+//! node-identifier rely on. Parsing a schema into node types stops at the
+//! first problem, and some problems (like names that are too long to be
+//! stored) aren't noticed until the schema is deployed, so the linter instead
+//! checks the whole document up front and reports every problem along with
+//! where it is in the schema.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+};
+
+use graphql_parser::{
+    schema::{
+        Definition,
+        Directive,
+        Document,
+        Field,
+        InputValue,
+        ObjectType,
+        Type,
+        TypeDefinition,
+        Value,
+    },
+    Pos,
+};
+
+use crate::{
+    conflict_resolution::ConflictResolution,
+    constants::{
+        CREATE_TIME,
+        DECREMENT_ONLY,
+        IMMUTABLE,
+        INCREMENT_ONLY,
+        INT,
+        LAST_SEEN_TIME,
+        PSEUDO_KEY,
+        SESSION_ALGORITHM,
+        STATIC_ALGORITHM,
+        STATIC_ID,
+        STRING,
+        TERMINATE_TIME,
+        UINT,
+    },
+    identification_algorithm::IdentificationAlgorithm,
+    identity_predicate_type::IdentityPredicateType,
+    parse_schema,
+    predicate_type::PredicateType,
+    ParseError,
+};
+
+/// graph-schema-manager only stores names that are shorter than this
+pub const NAME_LENGTH_LIMIT: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("error"),
+            Self::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// The rule that a diagnostic was raised for. Each rule has a stable code, so
+/// that tools consuming the JSON output can match on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// A type has no `@grapl` directive
+    MissingGraplDirective,
+    /// A `@grapl` directive doesn't declare exactly one of a known
+    /// `identity_algorithm` or the type it `extends`
+    InvalidGraplDirective,
+    /// A type extends a node type that isn't declared in the schema
+    UnknownExtendedType,
+    /// Two types share a name
+    DuplicateNodeType,
+    /// A node type, including its extensions and the reverse edges pointing
+    /// at it, uses the same name twice, or an edge has two properties with
+    /// the same name
+    DuplicateField,
+    /// A name is empty, too long to be stored, or not a valid identifier
+    InvalidName,
+    /// A property has no conflict resolution, so it's neither a property nor
+    /// an edge
+    MissingDirective,
+    /// A field's directives contradict each other
+    ConflictingDirectives,
+    /// A directive or directive argument that Grapl doesn't know about, and
+    /// would ignore
+    UnknownDirective,
+    /// A field's type isn't one that Grapl can store
+    UnsupportedType,
+    /// A property's type can't be merged with its conflict resolution, e.g.
+    /// an increment only string
+    UnsupportedPropertyType,
+    /// An identity directive on a node type with a different identity
+    /// algorithm, or on an edge property
+    MisplacedIdentityProperty,
+    /// A session identified node type has no `@pseudo_key` properties
+    MissingPseudoKey,
+    /// A session identified node type is missing one of its create, last seen
+    /// or terminate time properties
+    MissingSessionTimestamp,
+    /// A session identified node type has more than one create, last seen or
+    /// terminate time property
+    DuplicateSessionTimestamp,
+    /// A session timestamp property isn't a `UInt`
+    InvalidSessionTimestamp,
+    /// A static identified node type has no `@static_id` properties
+    MissingStaticId,
+    /// A property has arguments, which only edges can have
+    UnexpectedArguments,
+    /// An edge doesn't name its reverse edge
+    MissingReverseEdge,
+    /// An edge's `reverse_relationship` is missing, or isn't ToOne or ToMany
+    InvalidReverseRelationship,
+    /// An edge points to a type that isn't a node type in the schema
+    UnknownEdgeTarget,
+    /// An edge's reverse clashes with a name already used by the node type
+    /// it points to
+    ReverseEdgeConflict,
+}
+
+impl Rule {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingGraplDirective => "missing-grapl-directive",
+            Self::InvalidGraplDirective => "invalid-grapl-directive",
+            Self::UnknownExtendedType => "unknown-extended-type",
+            Self::DuplicateNodeType => "duplicate-node-type",
+            Self::DuplicateField => "duplicate-field",
+            Self::InvalidName => "invalid-name",
+            Self::MissingDirective => "missing-directive",
+            Self::ConflictingDirectives => "conflicting-directives",
+            Self::UnknownDirective => "unknown-directive",
+            Self::UnsupportedType => "unsupported-type",
+            Self::UnsupportedPropertyType => "unsupported-property-type",
+            Self::MisplacedIdentityProperty => "misplaced-identity-property",
+            Self::MissingPseudoKey => "missing-pseudo-key",
+            Self::MissingSessionTimestamp => "missing-session-timestamp",
+            Self::DuplicateSessionTimestamp => "duplicate-session-timestamp",
+            Self::InvalidSessionTimestamp => "invalid-session-timestamp",
+            Self::MissingStaticId => "missing-static-id",
+            Self::UnexpectedArguments => "unexpected-arguments",
+            Self::MissingReverseEdge => "missing-reverse-edge",
+            Self::InvalidReverseRelationship => "invalid-reverse-relationship",
+            Self::UnknownEdgeTarget => "unknown-edge-target",
+            Self::ReverseEdgeConflict => "reverse-edge-conflict",
+        }
+    }
+
+    /// Warnings are for things Grapl would ignore. Everything else would
+    /// fail to parse, fail to deploy, or break node identification.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::UnknownDirective => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub message: String,
+    /// Where in the schema the problem is. Lines and columns start at 1.
+    pub position: Pos,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        self.rule.severity()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "severity": self.severity().to_string(),
+            "rule": self.rule.code(),
+            "message": self.message,
+            "line": self.position.line,
+            "column": self.position.column,
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}[{}]: {}",
+            self.position,
+            self.severity(),
+            self.rule.code(),
+            self.message
+        )
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown output format {0}, expected human or json")]
+pub struct UnknownOutputFormat(String);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = UnknownOutputFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            other => Err(UnknownOutputFormat(other.to_owned())),
+        }
+    }
+}
+
+/// Renders the diagnostics for a schema read from `source`, one per line
+pub fn render(format: OutputFormat, source: &str, diagnostics: &[Diagnostic]) -> String {
+    match format {
+        OutputFormat::Human => render_human(source, diagnostics),
+        OutputFormat::Json => render_json(source, diagnostics).to_string() + "\n",
+    }
+}
+
+pub fn render_human(source: &str, diagnostics: &[Diagnostic]) -> String {
+    let mut rendered = String::with_capacity(128 * diagnostics.len());
+    for diagnostic in diagnostics {
+        rendered = rendered + source + ":" + &diagnostic.to_string() + "\n";
+    }
+    rendered
+}
+
+pub fn render_json(source: &str, diagnostics: &[Diagnostic]) -> serde_json::Value {
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity() == severity)
+            .count()
+    };
+    serde_json::json!({
+        "source": source,
+        "error_count": count(Severity::Error),
+        "warning_count": count(Severity::Warning),
+        "diagnostics": diagnostics.iter().map(Diagnostic::to_json).collect::<Vec<_>>(),
+    })
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity() == Severity::Error)
+}
+
+/// Lints a raw schema. Only a schema that isn't valid GraphQL is an `Err`,
+/// any other problem is reported as a diagnostic.
+pub fn lint_schema(raw_schema: &str) -> Result<Vec<Diagnostic>, ParseError> {
+    let document: Document<String> = parse_schema(raw_schema)?;
+    Ok(lint_document(&document))
+}
+
+/// Lints a parsed schema, returning its diagnostics in the order they appear
+/// in the schema
+pub fn lint_document<'a>(document: &Document<'a, String>) -> Vec<Diagnostic> {
+    let mut linter = Linter::default();
+
+    let objects: Vec<&ObjectType<'a, String>> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(TypeDefinition::Object(object)) => Some(object),
+            _ => None,
+        })
+        .collect();
+
+    // Work out what every type is before looking at any fields, since
+    // extensions and edges can refer to types declared after them
+    let mut type_positions: HashMap<&str, Pos> = HashMap::new();
+    let mut scopes: HashMap<&str, NodeTypeScope> = HashMap::new();
+    let mut kinds = Vec::with_capacity(objects.len());
+    for object in objects {
+        if let Some(first_position) = type_positions.get(object.name.as_str()) {
+            linter.report(
+                Rule::DuplicateNodeType,
+                object.position,
+                format!(
+                    "type `{}` is already declared at {}",
+                    object.name, first_position
+                ),
+            );
+            continue;
+        }
+        type_positions.insert(&object.name, object.position);
+
+        let kind = linter.lint_object_directives(object);
+        if let ObjectKind::NodeType(identification_algorithm) = kind {
+            linter.check_name(object.position, "node type", &object.name);
+            scopes.insert(
+                &object.name,
+                NodeTypeScope::new(object.position, identification_algorithm),
+            );
+        }
+        kinds.push((object, kind));
+    }
+
+    let mut edges = vec![];
+    for (object, kind) in kinds {
+        let owner = match kind {
+            ObjectKind::NodeType(_) => Some(object.name.as_str()),
+            ObjectKind::Extension(extends_type, position) => {
+                if scopes.contains_key(extends_type) {
+                    Some(extends_type)
+                } else {
+                    linter.report(
+                        Rule::UnknownExtendedType,
+                        position,
+                        format!(
+                            "type `{}` extends `{}`, which isn't a node type in this schema",
+                            object.name, extends_type
+                        ),
+                    );
+                    None
+                }
+            }
+            ObjectKind::Invalid => None,
+        };
+
+        for field in object.fields.iter() {
+            let linted_field = linter.lint_field(field);
+            let (owner, scope) =
+                match owner.and_then(|owner| scopes.get_mut(owner).map(|scope| (owner, scope))) {
+                    Some(owner_and_scope) => owner_and_scope,
+                    // Fields of a type that isn't a node type are still linted,
+                    // but don't belong to any node type
+                    None => continue,
+                };
+
+            if let Some(first_position) = scope.names.get(field.name.as_str()) {
+                linter.report(
+                    Rule::DuplicateField,
+                    field.position,
+                    format!(
+                        "`{}.{}` is already declared at {}",
+                        owner, field.name, first_position
+                    ),
+                );
+                continue;
+            }
+            scope.names.insert(&field.name, field.position);
+
+            match linted_field {
+                LintedField::Property(Some(identity)) => scope.identity.push(identity),
+                LintedField::Property(None) => {}
+                LintedField::Edge(edge) => edges.push((owner, edge)),
+            }
+        }
+    }
+
+    let mut scopes_by_position: Vec<_> = scopes.iter().collect();
+    scopes_by_position.sort_by_key(|(_, scope)| scope.position);
+    for (type_name, scope) in scopes_by_position {
+        linter.lint_identity(type_name, scope);
+    }
+
+    for (source_type_name, edge) in edges {
+        linter.lint_reverse_edge(source_type_name, edge, &mut scopes);
+    }
+
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.position);
+    diagnostics
+}
+
+/// What a type declares itself to be in its `@grapl` directive
+enum ObjectKind<'d> {
+    /// The identification algorithm is None if it isn't one we know about
+    NodeType(Option<IdentificationAlgorithm>),
+    Extension(&'d str, Pos),
+    Invalid,
+}
+
+/// Everything declared on a node type by itself and its extensions
+struct NodeTypeScope<'d> {
+    position: Pos,
+    identification_algorithm: Option<IdentificationAlgorithm>,
+    /// The names of its properties, edges and the reverse edges pointing at
+    /// it, which all share one namespace
+    names: HashMap<&'d str, Pos>,
+    identity: Vec<IdentityProperty<'d>>,
+}
+
+impl<'d> NodeTypeScope<'d> {
+    fn new(position: Pos, identification_algorithm: Option<IdentificationAlgorithm>) -> Self {
+        Self {
+            position,
+            identification_algorithm,
+            names: HashMap::new(),
+            identity: vec![],
+        }
+    }
+}
+
+/// A property used to identify its node
+struct IdentityProperty<'d> {
+    property_name: &'d str,
+    identity_predicate_type: IdentityPredicateType,
+    /// None if the property's type isn't supported
+    predicate_type: Option<PredicateType>,
+    position: Pos,
+}
+
+struct EdgeDeclaration<'d> {
+    edge_name: &'d str,
+    position: Pos,
+    /// None if the edge's type isn't supported
+    target_type_name: Option<&'d str>,
+    /// None if the edge doesn't name its reverse
+    reverse_edge_name: Option<(&'d str, Pos)>,
+}
+
+enum LintedField<'d> {
+    Property(Option<IdentityProperty<'d>>),
+    Edge(EdgeDeclaration<'d>),
+}
+
+/// A field's or argument's directives, sorted by what they mean to Grapl
+#[derive(Default)]
+struct FieldDirectives<'d, 'a> {
+    edges: Vec<&'d Directive<'a, String>>,
+    identity: Vec<(IdentityPredicateType, &'d Directive<'a, String>)>,
+    conflict_resolutions: Vec<(ConflictResolution, &'d Directive<'a, String>)>,
+}
+
+#[derive(Default)]
+struct Linter {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter {
+    fn report(&mut self, rule: Rule, position: Pos, message: String) {
+        self.diagnostics.push(Diagnostic {
+            rule,
+            message,
+            position,
+        });
+    }
+
+    fn check_name(&mut self, position: Pos, kind: &str, name: &str) {
+        if name.is_empty() {
+            self.report(
+                Rule::InvalidName,
+                position,
+                format!("{} names can't be empty", kind),
+            );
+        } else if name.len() >= NAME_LENGTH_LIMIT {
+            self.report(
+                Rule::InvalidName,
+                position,
+                format!(
+                    "{} `{}` is {} characters long, but names must be shorter than {} characters",
+                    kind,
+                    name,
+                    name.len(),
+                    NAME_LENGTH_LIMIT
+                ),
+            );
+        }
+    }
+
+    fn lint_object_directives<'d, 'a>(
+        &mut self,
+        object: &'d ObjectType<'a, String>,
+    ) -> ObjectKind<'d> {
+        let mut grapl_directives = vec![];
+        for directive in object.directives.iter() {
+            match directive.name.as_str() {
+                "grapl" => grapl_directives.push(directive),
+                unknown => self.report(
+                    Rule::UnknownDirective,
+                    directive.position,
+                    format!("unknown directive `@{}` on type `{}`", unknown, object.name),
+                ),
+            }
+        }
+
+        let grapl_directive = match grapl_directives.as_slice() {
+            [] => {
+                self.report(
+                    Rule::MissingGraplDirective,
+                    object.position,
+                    format!(
+                        "type `{}` needs a `@grapl` directive declaring its identity_algorithm, or the type it extends",
+                        object.name
+                    ),
+                );
+                return ObjectKind::Invalid;
+            }
+            [grapl_directive, others @ ..] => {
+                for other in others {
+                    self.report(
+                        Rule::ConflictingDirectives,
+                        other.position,
+                        format!(
+                            "type `{}` has more than one `@grapl` directive",
+                            object.name
+                        ),
+                    );
+                }
+                grapl_directive
+            }
+        };
+
+        let mut identity_algorithm = None;
+        let mut extends_type = None;
+        for (argument_name, argument) in grapl_directive.arguments.iter() {
+            match (argument_name.as_str(), argument) {
+                ("identity_algorithm", Value::String(algorithm)) => {
+                    identity_algorithm = Some(match algorithm.as_str() {
+                        SESSION_ALGORITHM => Some(IdentificationAlgorithm::Session),
+                        STATIC_ALGORITHM => Some(IdentificationAlgorithm::Static),
+                        unknown => {
+                            self.report(
+                                Rule::InvalidGraplDirective,
+                                grapl_directive.position,
+                                format!(
+                                    "unknown identity_algorithm `{}`, expected `{}` or `{}`",
+                                    unknown, SESSION_ALGORITHM, STATIC_ALGORITHM
+                                ),
+                            );
+                            None
+                        }
+                    })
+                }
+                ("extends", Value::String(extended)) => extends_type = Some(extended.as_str()),
+                ("identity_algorithm" | "extends", _) => self.report(
+                    Rule::InvalidGraplDirective,
+                    grapl_directive.position,
+                    format!(
+                        "the `{}` argument of `@grapl` must be a string",
+                        argument_name
+                    ),
+                ),
+                (unknown, _) => self.report(
+                    Rule::UnknownDirective,
+                    grapl_directive.position,
+                    format!("unknown argument `{}` on `@grapl`", unknown),
+                ),
+            }
+        }
+
+        match (identity_algorithm, extends_type) {
+            (Some(identification_algorithm), None) => {
+                ObjectKind::NodeType(identification_algorithm)
+            }
+            (None, Some(extends_type)) => {
+                ObjectKind::Extension(extends_type, grapl_directive.position)
+            }
+            (Some(_), Some(_)) => {
+                self.report(
+                    Rule::InvalidGraplDirective,
+                    grapl_directive.position,
+                    format!(
+                        "type `{}` can't both declare an identity_algorithm and extend another type",
+                        object.name
+                    ),
+                );
+                ObjectKind::Invalid
+            }
+            (None, None) => {
+                self.report(
+                    Rule::InvalidGraplDirective,
+                    grapl_directive.position,
+                    format!(
+                        "the `@grapl` directive of type `{}` needs either an identity_algorithm or an extends argument",
+                        object.name
+                    ),
+                );
+                ObjectKind::Invalid
+            }
+        }
+    }
+
+    fn classify_directives<'d, 'a>(
+        &mut self,
+        directives: &'d [Directive<'a, String>],
+    ) -> FieldDirectives<'d, 'a> {
+        let mut field_directives = FieldDirectives::default();
+        for directive in directives {
+            let identity_predicate_type = match directive.name.as_str() {
+                "edge" => {
+                    field_directives.edges.push(directive);
+                    continue;
+                }
+                IMMUTABLE | INCREMENT_ONLY | DECREMENT_ONLY => {
+                    let conflict_resolution = match directive.name.as_str() {
+                        IMMUTABLE => ConflictResolution::Immutable,
+                        INCREMENT_ONLY => ConflictResolution::IncrementOnly,
+                        _ => ConflictResolution::DecrementOnly,
+                    };
+                    field_directives
+                        .conflict_resolutions
+                        .push((conflict_resolution, directive));
+                    continue;
+                }
+                PSEUDO_KEY => IdentityPredicateType::SessionPseudoKey,
+                CREATE_TIME => IdentityPredicateType::SessionCreateTime,
+                LAST_SEEN_TIME => IdentityPredicateType::SessionLastSeenTime,
+                TERMINATE_TIME => IdentityPredicateType::SessionTerminateTime,
+                STATIC_ID => IdentityPredicateType::StaticId,
+                unknown => {
+                    self.report(
+                        Rule::UnknownDirective,
+                        directive.position,
+                        format!("unknown directive `@{}`", unknown),
+                    );
+                    continue;
+                }
+            };
+            field_directives
+                .identity
+                .push((identity_predicate_type, directive));
+        }
+        field_directives
+    }
+
+    fn lint_field<'d, 'a>(&mut self, field: &'d Field<'a, String>) -> LintedField<'d> {
+        let directives = self.classify_directives(&field.directives);
+
+        if let Some((edge_directive, other_edge_directives)) = directives.edges.split_first() {
+            for other in other_edge_directives {
+                self.report(
+                    Rule::ConflictingDirectives,
+                    other.position,
+                    format!("edge `{}` has more than one `@edge` directive", field.name),
+                );
+            }
+            let property_directives = directives
+                .identity
+                .iter()
+                .map(|(_, directive)| directive)
+                .chain(
+                    directives
+                        .conflict_resolutions
+                        .iter()
+                        .map(|(_, directive)| directive),
+                );
+            for directive in property_directives {
+                self.report(
+                    Rule::ConflictingDirectives,
+                    directive.position,
+                    format!(
+                        "`{}` is an edge, so it can't also be `@{}`",
+                        field.name, directive.name
+                    ),
+                );
+            }
+            return LintedField::Edge(self.lint_edge(field, edge_directive));
+        }
+
+        self.check_name(field.position, "property", &field.name);
+        if let Some(argument) = field.arguments.first() {
+            self.report(
+                Rule::UnexpectedArguments,
+                argument.position,
+                format!(
+                    "only edges can have properties, but property `{}` has arguments",
+                    field.name
+                ),
+            );
+        }
+
+        let predicate_type =
+            self.lint_property_type(field.position, &field.name, &field.field_type);
+        self.lint_conflict_resolution(field.position, &field.name, predicate_type, &directives);
+
+        let identity = directives
+            .identity
+            .first()
+            .map(|(identity_predicate_type, directive)| IdentityProperty {
+                property_name: &field.name,
+                identity_predicate_type: *identity_predicate_type,
+                predicate_type,
+                position: directive.position,
+            });
+        LintedField::Property(identity)
+    }
+
+    /// Checks that a property or edge property has exactly one way of
+    /// resolving conflicts, and that it's one its type supports
+    fn lint_conflict_resolution(
+        &mut self,
+        position: Pos,
+        property_name: &str,
+        predicate_type: Option<PredicateType>,
+        directives: &FieldDirectives<'_, '_>,
+    ) {
+        for (_, directive) in directives.identity.iter().skip(1) {
+            self.report(
+                Rule::ConflictingDirectives,
+                directive.position,
+                format!("`{}` has more than one identity directive", property_name),
+            );
+        }
+        for (_, directive) in directives.conflict_resolutions.iter().skip(1) {
+            self.report(
+                Rule::ConflictingDirectives,
+                directive.position,
+                format!(
+                    "`{}` has more than one conflict resolution directive",
+                    property_name
+                ),
+            );
+        }
+
+        let implied = directives
+            .identity
+            .first()
+            .map(|(identity_predicate_type, directive)| {
+                (
+                    implied_conflict_resolution(*identity_predicate_type),
+                    *directive,
+                )
+            });
+        let declared = directives.conflict_resolutions.first().copied();
+        let (conflict_resolution, directive) = match (implied, declared) {
+            (Some((implied, identity_directive)), Some((declared, directive))) => {
+                if implied != declared {
+                    self.report(
+                        Rule::ConflictingDirectives,
+                        directive.position,
+                        format!(
+                            "`@{}` properties are {}, so `{}` can't also be `@{}`",
+                            identity_directive.name,
+                            implied.as_grapl_attribute(),
+                            property_name,
+                            directive.name
+                        ),
+                    );
+                }
+                (declared, directive)
+            }
+            (Some(implied), None) => implied,
+            (None, Some(declared)) => declared,
+            (None, None) => {
+                self.report(
+                    Rule::MissingDirective,
+                    position,
+                    format!(
+                        "`{}` needs an `@edge` directive, or one of `@{}`, `@{}` or `@{}`",
+                        property_name, IMMUTABLE, INCREMENT_ONLY, DECREMENT_ONLY
+                    ),
+                );
+                return;
+            }
+        };
+
+        if predicate_type == Some(PredicateType::String)
+            && conflict_resolution != ConflictResolution::Immutable
+        {
+            self.report(
+                Rule::UnsupportedPropertyType,
+                directive.position,
+                format!(
+                    "`{}` is a String, and Strings can only be immutable, but it's `@{}`",
+                    property_name, directive.name
+                ),
+            );
+        }
+    }
+
+    fn lint_property_type(
+        &mut self,
+        position: Pos,
+        property_name: &str,
+        property_type: &Type<'_, String>,
+    ) -> Option<PredicateType> {
+        match property_type {
+            Type::NonNullType(property_type) => {
+                self.lint_property_type(position, property_name, property_type)
+            }
+            Type::NamedType(type_name) => match type_name.as_str() {
+                STRING => Some(PredicateType::String),
+                INT => Some(PredicateType::I64),
+                UINT => Some(PredicateType::U64),
+                unsupported => {
+                    self.report(
+                        Rule::UnsupportedType,
+                        position,
+                        format!(
+                            "`{}` is a `{}`, but properties must be a {}, {} or {}",
+                            property_name, unsupported, STRING, INT, UINT
+                        ),
+                    );
+                    None
+                }
+            },
+            Type::ListType(_) => {
+                self.report(
+                    Rule::UnsupportedType,
+                    position,
+                    format!(
+                        "`{}` is a list, but properties can only hold a single value",
+                        property_name
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    fn lint_edge<'d, 'a>(
+        &mut self,
+        field: &'d Field<'a, String>,
+        edge_directive: &'d Directive<'a, String>,
+    ) -> EdgeDeclaration<'d> {
+        self.check_name(field.position, "edge", &field.name);
+
+        // Each argument is None if it's missing, and Some(None) if it isn't a
+        // string
+        let mut reverse_argument = None;
+        let mut reverse_relationship_argument = None;
+        for (argument_name, argument) in edge_directive.arguments.iter() {
+            let string_argument = match argument {
+                Value::String(argument) => Some(argument.as_str()),
+                _ => None,
+            };
+            match argument_name.as_str() {
+                "reverse" => reverse_argument = Some(string_argument),
+                "reverse_relationship" => reverse_relationship_argument = Some(string_argument),
+                unknown => self.report(
+                    Rule::UnknownDirective,
+                    edge_directive.position,
+                    format!("unknown argument `{}` on `@edge`", unknown),
+                ),
+            }
+        }
+
+        let reverse_edge_name = reverse_argument.flatten();
+        match reverse_argument {
+            Some(Some(reverse_edge_name)) if !is_valid_name(reverse_edge_name) => self.report(
+                Rule::InvalidName,
+                edge_directive.position,
+                format!(
+                    "reverse edge name `{}` of edge `{}` isn't a valid name",
+                    reverse_edge_name, field.name
+                ),
+            ),
+            Some(Some(reverse_edge_name)) => {
+                self.check_name(edge_directive.position, "reverse edge", reverse_edge_name)
+            }
+            Some(None) => self.report(
+                Rule::MissingReverseEdge,
+                edge_directive.position,
+                format!(
+                    "the `reverse` argument of edge `{}` must be a string",
+                    field.name
+                ),
+            ),
+            None => self.report(
+                Rule::MissingReverseEdge,
+                edge_directive.position,
+                format!(
+                    "edge `{}` needs a `reverse` argument naming its reverse edge",
+                    field.name
+                ),
+            ),
+        }
+
+        match reverse_relationship_argument {
+            Some(Some("ToOne" | "ToMany")) => {}
+            Some(Some(unknown)) => self.report(
+                Rule::InvalidReverseRelationship,
+                edge_directive.position,
+                format!(
+                    "unknown reverse_relationship `{}` on edge `{}`, expected `ToOne` or `ToMany`",
+                    unknown, field.name
+                ),
+            ),
+            Some(None) | None => self.report(
+                Rule::InvalidReverseRelationship,
+                edge_directive.position,
+                format!(
+                    "edge `{}` needs a `reverse_relationship` argument, either `ToOne` or `ToMany`",
+                    field.name
+                ),
+            ),
+        }
+
+        let target_type_name = edge_target_type_name(&field.field_type);
+        if target_type_name.is_none() {
+            self.report(
+                Rule::UnsupportedType,
+                field.position,
+                format!(
+                    "edge `{}` must point to a node type, or a list of a node type, e.g. `File` or `[File!]`",
+                    field.name
+                ),
+            );
+        }
+
+        let mut property_positions: HashMap<&str, Pos> = HashMap::new();
+        for property in field.arguments.iter() {
+            if let Some(first_position) = property_positions.get(property.name.as_str()) {
+                self.report(
+                    Rule::DuplicateField,
+                    property.position,
+                    format!(
+                        "edge `{}` already has a property `{}` at {}",
+                        field.name, property.name, first_position
+                    ),
+                );
+            }
+            property_positions.insert(&property.name, property.position);
+            self.lint_edge_property(property);
+        }
+
+        EdgeDeclaration {
+            edge_name: &field.name,
+            position: field.position,
+            target_type_name,
+            reverse_edge_name: reverse_edge_name.map(|name| (name, edge_directive.position)),
+        }
+    }
+
+    fn lint_edge_property(&mut self, property: &InputValue<'_, String>) {
+        self.check_name(property.position, "edge property", &property.name);
+
+        let directives = self.classify_directives(&property.directives);
+        for directive in directives.edges.iter() {
+            self.report(
+                Rule::UnsupportedType,
+                directive.position,
+                format!("edge property `{}` can't be an edge", property.name),
+            );
+        }
+        for (_, directive) in directives.identity.iter() {
+            self.report(
+                Rule::MisplacedIdentityProperty,
+                directive.position,
+                format!(
+                    "edges aren't identified by their properties, so edge property `{}` can't be `@{}`",
+                    property.name, directive.name
+                ),
+            );
+        }
+
+        let predicate_type =
+            self.lint_property_type(property.position, &property.name, &property.value_type);
+        self.lint_conflict_resolution(
+            property.position,
+            &property.name,
+            predicate_type,
+            &directives,
+        );
+    }
+
+    /// Checks that a node type has exactly the identity properties that
+    /// node-identifier needs for its identification algorithm
+    fn lint_identity(&mut self, type_name: &str, scope: &NodeTypeScope<'_>) {
+        match scope.identification_algorithm {
+            Some(IdentificationAlgorithm::Session) => {
+                let has_pseudo_key = scope.identity.iter().any(|property| {
+                    property.identity_predicate_type == IdentityPredicateType::SessionPseudoKey
+                });
+                if !has_pseudo_key {
+                    self.report(
+                        Rule::MissingPseudoKey,
+                        scope.position,
+                        format!(
+                            "session identified node type `{}` needs at least one `@{}` property",
+                            type_name, PSEUDO_KEY
+                        ),
+                    );
+                }
+
+                let timestamps = [
+                    (IdentityPredicateType::SessionCreateTime, CREATE_TIME),
+                    (IdentityPredicateType::SessionLastSeenTime, LAST_SEEN_TIME),
+                    (IdentityPredicateType::SessionTerminateTime, TERMINATE_TIME),
+                ];
+                for (identity_predicate_type, directive_name) in timestamps {
+                    let mut properties = scope.identity.iter().filter(|property| {
+                        property.identity_predicate_type == identity_predicate_type
+                    });
+                    match properties.next() {
+                        Some(property) => {
+                            self.lint_session_timestamp(directive_name, property);
+                        }
+                        None => self.report(
+                            Rule::MissingSessionTimestamp,
+                            scope.position,
+                            format!(
+                                "session identified node type `{}` needs a `@{}` property",
+                                type_name, directive_name
+                            ),
+                        ),
+                    }
+                    for property in properties {
+                        self.report(
+                            Rule::DuplicateSessionTimestamp,
+                            property.position,
+                            format!(
+                                "node type `{}` already has a `@{}` property",
+                                type_name, directive_name
+                            ),
+                        );
+                    }
+                }
+
+                for property in scope.identity.iter() {
+                    if property.identity_predicate_type == IdentityPredicateType::StaticId {
+                        self.report(
+                            Rule::MisplacedIdentityProperty,
+                            property.position,
+                            format!(
+                                "`{}` is a `@{}`, but node type `{}` is identified by session",
+                                property.property_name, STATIC_ID, type_name
+                            ),
+                        );
+                    }
+                }
+            }
+            Some(IdentificationAlgorithm::Static) => {
+                let mut has_static_id = false;
+                for property in scope.identity.iter() {
+                    if property.identity_predicate_type == IdentityPredicateType::StaticId {
+                        has_static_id = true;
+                    } else {
+                        self.report(
+                            Rule::MisplacedIdentityProperty,
+                            property.position,
+                            format!(
+                                "`{}` is a `@{}`, but node type `{}` is identified statically",
+                                property.property_name,
+                                property.identity_predicate_type.as_grapl_attribute(),
+                                type_name
+                            ),
+                        );
+                    }
+                }
+                if !has_static_id {
+                    self.report(
+                        Rule::MissingStaticId,
+                        scope.position,
+                        format!(
+                            "statically identified node type `{}` needs at least one `@{}` property",
+                            type_name, STATIC_ID
+                        ),
+                    );
+                }
+            }
+            // The identity algorithm has already been reported as invalid
+            None => {}
+        }
+    }
+
+    fn lint_session_timestamp(&mut self, directive_name: &str, property: &IdentityProperty<'_>) {
+        match property.predicate_type {
+            Some(PredicateType::U64) | None => {}
+            Some(_) => self.report(
+                Rule::InvalidSessionTimestamp,
+                property.position,
+                format!(
+                    "`@{}` property `{}` must be a {}",
+                    directive_name, property.property_name, UINT
+                ),
+            ),
+        }
+    }
+
+    /// Checks that an edge points at a node type, and that its reverse edge
+    /// can be added to that node type
+    fn lint_reverse_edge<'d>(
+        &mut self,
+        source_type_name: &str,
+        edge: EdgeDeclaration<'d>,
+        scopes: &mut HashMap<&str, NodeTypeScope<'d>>,
+    ) {
+        let target_type_name = match edge.target_type_name {
+            Some(target_type_name) => target_type_name,
+            None => return,
+        };
+        let target_scope = match scopes.get_mut(target_type_name) {
+            Some(target_scope) => target_scope,
+            None => {
+                self.report(
+                    Rule::UnknownEdgeTarget,
+                    edge.position,
+                    format!(
+                        "edge `{}.{}` points to `{}`, which isn't a node type in this schema",
+                        source_type_name, edge.edge_name, target_type_name
+                    ),
+                );
+                return;
+            }
+        };
+        let (reverse_edge_name, position) = match edge.reverse_edge_name {
+            Some(reverse_edge_name) => reverse_edge_name,
+            None => return,
+        };
+
+        if source_type_name == target_type_name && reverse_edge_name == edge.edge_name {
+            self.report(
+                Rule::ReverseEdgeConflict,
+                position,
+                format!(
+                    "edge `{}.{}` can't be its own reverse edge",
+                    source_type_name, edge.edge_name
+                ),
+            );
+        } else if let Some(existing_position) = target_scope.names.get(reverse_edge_name) {
+            self.report(
+                Rule::ReverseEdgeConflict,
+                position,
+                format!(
+                    "reverse edge `{}` of `{}.{}` conflicts with `{}.{}`, declared at {}",
+                    reverse_edge_name,
+                    source_type_name,
+                    edge.edge_name,
+                    target_type_name,
+                    reverse_edge_name,
+                    existing_position
+                ),
+            );
+        } else {
+            target_scope.names.insert(reverse_edge_name, position);
+        }
+    }
+}
+
+fn implied_conflict_resolution(
+    identity_predicate_type: IdentityPredicateType,
+) -> ConflictResolution {
+    match identity_predicate_type {
+        IdentityPredicateType::SessionLastSeenTime => ConflictResolution::IncrementOnly,
+        _ => ConflictResolution::Immutable,
+    }
+}
+
+/// The node type an edge points to, if its type is `T`, `T!`, `[T]`, `[T!]`,
+/// `[T]!` or `[T!]!`
+fn edge_target_type_name<'d>(edge_type: &'d Type<'_, String>) -> Option<&'d str> {
+    fn named_type<'d>(edge_type: &'d Type<'_, String>) -> Option<&'d str> {
+        match edge_type {
+            Type::NamedType(type_name) => Some(type_name),
+            Type::NonNullType(edge_type) => match edge_type.as_ref() {
+                Type::NamedType(type_name) => Some(type_name),
+                _ => None,
+            },
+            Type::ListType(_) => None,
+        }
+    }
+
+    let list_type = match edge_type {
+        Type::ListType(list_type) => list_type,
+        Type::NonNullType(edge_type) => match edge_type.as_ref() {
+            Type::ListType(list_type) => list_type,
+            _ => return named_type(edge_type),
+        },
+        Type::NamedType(type_name) => return Some(type_name),
+    };
+    named_type(list_type)
+}
+
+/// Reverse edge names are arbitrary strings, but they're used as names in
+/// generated code, so they must be valid GraphQL names
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first == '_' || first.is_ascii_alphabetic() => {
+            chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_SCHEMA: &str = include_str!("../tests/golden/example.graphql");
+
+    fn lint(raw_schema: &str) -> Vec<(Rule, usize, usize)> {
+        lint_schema(raw_schema)
+            .unwrap()
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.rule,
+                    diagnostic.position.line,
+                    diagnostic.position.column,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_schema() {
+        assert_eq!(lint(VALID_SCHEMA), vec![]);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(lint_schema("type Process {").is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        let schema = r#"
+type Asset @grapl(identity_algorithm: "static") {
+    asset_id: String! @static_id,
+    a_property_name_that_is_too_long: String! @immutable,
+    files: [Asset!] @edge(reverse: "not a name", reverse_relationship: "ToOne"),
+}
+"#;
+        assert_eq!(
+            lint(schema),
+            vec![(Rule::InvalidName, 4, 5), (Rule::InvalidName, 5, 21)]
+        );
+    }
+
+    #[test]
+    fn test_session_identity() {
+        let schema = r#"
+type Process @grapl(identity_algorithm: "session") {
+    process_name: String! @immutable,
+    created_at: Int! @create_time,
+    asset_id: String! @static_id,
+    terminated_at: UInt! @terminate_time,
+    exited_at: UInt! @terminate_time,
+}
+"#;
+        assert_eq!(
+            lint(schema),
+            vec![
+                (Rule::MissingPseudoKey, 2, 1),
+                (Rule::MissingSessionTimestamp, 2, 1),
+                (Rule::InvalidSessionTimestamp, 4, 22),
+                (Rule::MisplacedIdentityProperty, 5, 23),
+                (Rule::DuplicateSessionTimestamp, 7, 22),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_static_identity() {
+        let schema = r#"
+type Asset @grapl(identity_algorithm: "static") {
+    hostname: String! @pseudo_key,
+}
+"#;
+        assert_eq!(
+            lint(schema),
+            vec![
+                (Rule::MissingStaticId, 2, 1),
+                (Rule::MisplacedIdentityProperty, 3, 23),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_property_directives() {
+        let schema = r#"
+type Asset @grapl(identity_algorithm: "static") {
+    asset_id: String! @static_id @increment_only,
+    hostname: String!,
+    launch_time: UInt! @immutable @decrement_only,
+    os: String! @increment_only,
+    tags: [String!] @immutable,
+    region: Region! @immutable @deprecated,
+}
+"#;
+        let diagnostics = lint(schema);
+        assert_eq!(
+            diagnostics,
+            vec![
+                (Rule::ConflictingDirectives, 3, 34),
+                (Rule::UnsupportedPropertyType, 3, 34),
+                (Rule::MissingDirective, 4, 5),
+                (Rule::ConflictingDirectives, 5, 35),
+                (Rule::UnsupportedPropertyType, 6, 17),
+                (Rule::UnsupportedType, 7, 5),
+                (Rule::UnsupportedType, 8, 5),
+                (Rule::UnknownDirective, 8, 32),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edges() {
+        let schema = r#"
+type Asset @grapl(identity_algorithm: "static") {
+    asset_id: String! @static_id,
+    files: [File!] @edge(reverse_relationship: "ToOne"),
+    processes: [Process!] @edge(reverse: "asset", reverse_relationship: "ToOne"),
+    peers(
+        first_seen_at: String! @decrement_only,
+    ): [Asset!] @edge(reverse: "peers", reverse_relationship: "ToMany"),
+    owner: Asset @edge(reverse: "owned_assets", reverse_relationship: "ToSome") @immutable,
+}
+"#;
+        assert_eq!(
+            lint(schema),
+            vec![
+                (Rule::UnknownEdgeTarget, 4, 5),
+                (Rule::MissingReverseEdge, 4, 20),
+                (Rule::UnknownEdgeTarget, 5, 5),
+                (Rule::UnsupportedPropertyType, 7, 32),
+                (Rule::ReverseEdgeConflict, 8, 17),
+                (Rule::InvalidReverseRelationship, 9, 18),
+                (Rule::ConflictingDirectives, 9, 81),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reverse_edge_conflicts() {
+        let schema = r#"
+type Asset @grapl(identity_algorithm: "static") {
+    asset_id: String! @static_id,
+    hostname: String! @immutable,
+}
+
+type AssetExtension @grapl(extends: "Asset") {
+    installed_by: Asset @edge(reverse: "hostname", reverse_relationship: "ToMany"),
+    managed_by: Asset @edge(reverse: "managed_assets", reverse_relationship: "ToMany"),
+    owned_by: Asset @edge(reverse: "managed_assets", reverse_relationship: "ToMany"),
+    asset_id: String! @immutable,
+}
+
+type DeviceExtension @grapl(extends: "Device") {
+    device_id: String! @immutable,
+}
+"#;
+        assert_eq!(
+            lint(schema),
+            vec![
+                (Rule::ReverseEdgeConflict, 8, 25),
+                (Rule::ReverseEdgeConflict, 10, 21),
+                (Rule::DuplicateField, 11, 5),
+                (Rule::UnknownExtendedType, 14, 22),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_directives() {
+        let schema = r#"
+type Asset {
+    asset_id: String! @static_id,
+}
+
+type Asset @grapl(identity_algorithm: "static") {
+    asset_id: String! @static_id,
+}
+
+type Process @grapl(identity_algorithm: "dynamic") {
+    process_id: UInt! @pseudo_key,
+}
+
+type ThisNodeTypeNameIsMuchTooLongToStore @grapl(identity_algorithm: "static", extends: "Process") {
+    asset_id: String! @static_id,
+}
+"#;
+        assert_eq!(
+            lint(schema),
+            vec![
+                (Rule::MissingGraplDirective, 2, 1),
+                (Rule::DuplicateNodeType, 6, 1),
+                (Rule::InvalidGraplDirective, 10, 14),
+                (Rule::InvalidGraplDirective, 14, 43),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let diagnostics = lint_schema(
+            r#"
+type Asset @grapl(identity_algorithm: "static") {
+    hostname: String! @immutable @deprecated,
+}
+"#,
+        )
+        .unwrap();
+        assert!(has_errors(&diagnostics));
+
+        assert_eq!(
+            render_human("asset.graphql", &diagnostics),
+            "asset.graphql:2:1: error[missing-static-id]: statically identified node type `Asset` needs at least one `@static_id` property\n\
+             asset.graphql:3:34: warning[unknown-directive]: unknown directive `@deprecated`\n"
+        );
+
+        let json = render_json("asset.graphql", &diagnostics);
+        assert_eq!(json["source"], "asset.graphql");
+        assert_eq!(json["error_count"], 1);
+        assert_eq!(json["warning_count"], 1);
+        assert_eq!(
+            json["diagnostics"][1],
+            serde_json::json!({
+                "severity": "warning",
+                "rule": "unknown-directive",
+                "message": "unknown directive `@deprecated`",
+                "line": 3,
+                "column": 34,
+            })
+        );
+    }
+
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!(
+            OutputFormat::from_str("human").unwrap(),
+            OutputFormat::Human
+        );
+        assert_eq!(OutputFormat::from_str("json").unwrap(), OutputFormat::Json);
+        assert!(OutputFormat::from_str("xml").is_err());
+    }
+}
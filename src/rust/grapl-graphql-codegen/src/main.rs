@@ -8,25 +8,26 @@ use color_eyre::eyre::{
     Result,
     WrapErr,
 };
-use graphql_parser::schema::parse_schema;
-
-pub mod as_static_python;
-pub mod conflict_resolution;
-pub mod constants;
-pub mod edge;
-pub mod edge_rel;
-pub mod errors;
-pub mod external_helpers;
-pub mod field_type;
-pub mod identification_algorithm;
-pub mod identity_predicate_type;
-pub mod node_predicate;
-pub mod node_type;
-pub mod predicate_type;
+use grapl_graphql_codegen::{
+    external_helpers,
+    lang::{
+        self,
+        Lang,
+    },
+    lint::{
+        self,
+        OutputFormat,
+    },
+    node_type,
+    parse_schema,
+};
 
 #[derive(clap::Parser, Debug)]
 #[clap(name = "grapl-graphql-codegen", about = "Codegen for Grapl plugins")]
 struct Opt {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Input file, stdin if not present
     #[clap(short = 'i', long = "input", parse(from_os_str), env)]
     input: Option<PathBuf>,
@@ -35,6 +36,10 @@ struct Opt {
     #[clap(short = 'o', long = "output", parse(from_os_str), env)]
     output: Option<PathBuf>,
 
+    /// The language to generate code in, either python or rust
+    #[clap(long, default_value = "python", env)]
+    lang: Lang,
+
     /// Do not emit any generated code - useful with 'validate'
     #[clap(long = "no-emit", parse(from_flag))]
     no_emit: bool,
@@ -43,8 +48,8 @@ struct Opt {
     #[clap(long = "line-num", parse(from_flag))]
     line_num: bool,
 
-    /// Generated code will be passed to the system Python interpreter, and mypy will be executed
-    /// against the code as well
+    /// Generated Python code will be passed to the system Python interpreter, and mypy will be
+    /// executed against the code as well. Generated Rust code will be parsed by rustfmt.
     #[clap(long, parse(from_flag))]
     validate: bool,
 
@@ -55,10 +60,46 @@ struct Opt {
     /// option so that this broken-ness has a traceable explanation
     /// instead of just surprising the next unlucky soul who runs
     /// grapl-graphql-codegen.
+    /// Only the Python backend is broken, so this isn't needed with `--lang rust`.
     #[clap(long, parse(from_flag))]
     acknowledge_this_tool_needs_to_be_updated_for_new_grapl_analyzerlib: bool,
 }
 
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Check a schema for everything that would stop it from being deployed,
+    /// or from being used to identify nodes, without generating any code.
+    /// Exits with a non-zero status if any errors are found.
+    Lint(LintOpt),
+}
+
+#[derive(clap::Args, Debug)]
+struct LintOpt {
+    /// Input file, stdin if not present
+    #[clap(short = 'i', long = "input", parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// How to print diagnostics, either human or json
+    #[clap(long, default_value = "human")]
+    format: OutputFormat,
+}
+
+fn lint(opt: LintOpt) -> Result<()> {
+    let raw_schema = read_in_schema(&opt.input)?;
+    let source = match opt.input {
+        Some(ref path) => path.display().to_string(),
+        None => "<stdin>".to_owned(),
+    };
+
+    let diagnostics = lint::lint_schema(&raw_schema)?;
+    print!("{}", lint::render(opt.format, &source, &diagnostics));
+
+    if lint::has_errors(&diagnostics) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 fn read_in_schema(input: &Option<PathBuf>) -> Result<String> {
     match input {
         Some(path) => Ok(std::fs::read_to_string(path)
@@ -74,17 +115,6 @@ fn read_in_schema(input: &Option<PathBuf>) -> Result<String> {
     }
 }
 
-fn standin_imports() -> String {
-    let mut code = String::new();
-    code.push_str("from __future__ import annotations\n");
-    code.push_str("from typing import Optional, Any, Set, List, Dict, Tuple\n");
-    code.push_str("import grapl_analyzerlib\n");
-    code.push_str("import grapl_analyzerlib.node_types\n");
-    code.push_str("import grapl_analyzerlib.nodes.entity\n");
-    code.push_str("import grapl_analyzerlib.queryable\n");
-    code
-}
-
 #[tracing::instrument]
 fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
@@ -92,7 +122,13 @@ fn main() -> Result<()> {
 
     let opt = Opt::parse();
 
-    if !opt.acknowledge_this_tool_needs_to_be_updated_for_new_grapl_analyzerlib {
+    if let Some(Command::Lint(lint_opt)) = opt.command {
+        return lint(lint_opt);
+    }
+
+    if opt.lang == Lang::Python
+        && !opt.acknowledge_this_tool_needs_to_be_updated_for_new_grapl_analyzerlib
+    {
         panic!(
             r"#This tool is currently broken.
 Please read the documentation on 
@@ -108,15 +144,13 @@ Please read the documentation on
 
     let node_types = node_type::parse_into_node_types(document).expect("Failed");
 
-    let mut all_code = String::with_capacity(1024 * node_types.len());
-    all_code.push_str(&standin_imports());
-    for node_type in node_types {
-        let pycode = node_type.generate_python_code();
-        all_code.push_str(&pycode);
-    }
+    let all_code = lang::generate_code(opt.lang, &node_types)?;
 
     if opt.validate {
-        external_helpers::validate_code(&all_code)?;
+        match opt.lang {
+            Lang::Python => external_helpers::validate_code(&all_code)?,
+            Lang::Rust => external_helpers::validate_rust_code(&all_code)?,
+        }
     }
 
     // If `no_emit` is set, return early
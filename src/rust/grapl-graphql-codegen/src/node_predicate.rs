@@ -142,6 +142,76 @@ impl NodePredicate {
     }
 }
 
+// Rust code generation for NodePredicate
+impl NodePredicate {
+    /// The property's field in a struct that derives `NodeDescription`
+    pub fn generate_rust_field(&self) -> Result<String, CodeGenError> {
+        let mut field = String::with_capacity(128);
+
+        let property_name = self.predicate_name.as_str();
+        let rust_ty = self.predicate_type.into_rust_primitive_type();
+        if self
+            .predicate_type
+            .into_rust_property_type(self.conflict_resolution)
+            .is_none()
+        {
+            return Err(CodeGenError::UnsupportedPropertyType {
+                property_name: property_name.to_owned(),
+                predicate_type: self.predicate_type,
+                conflict_resolution: self.conflict_resolution,
+            });
+        }
+
+        let conflict_resolution = self.conflict_resolution.as_grapl_attribute();
+        let grapl_attributes = match self.identity_predicate_type {
+            Some(identity_predicate_type) => format!(
+                "{}, {}",
+                identity_predicate_type.as_grapl_attribute(),
+                conflict_resolution
+            ),
+            None => conflict_resolution.to_owned(),
+        };
+
+        if let Some(ref description) = self.description {
+            for line in description.lines() {
+                field = field + "    /// " + line.trim() + "\n";
+            }
+        }
+        field = field + "    #[grapl(" + &grapl_attributes + ")]\n";
+        field = field + "    " + property_name + ": " + rust_ty + ",\n";
+        Ok(field)
+    }
+
+    /// The query builder method that filters on the property. Only string
+    /// properties can be filtered on, so this is None for any other property.
+    pub fn generate_rust_query_method(&self) -> Option<String> {
+        if !matches!(self.predicate_type, PredicateType::String) {
+            return None;
+        }
+
+        let mut query_method = String::with_capacity(256);
+        let property_name = self.predicate_name.as_str();
+
+        query_method = query_method
+            + &format!(
+                "    pub fn with_{}(&mut self, comparisons: impl Into<AndStringFilters>) -> &mut Self {{",
+                property_name
+            )
+            + "\n";
+        query_method += "        self.0.with_string_comparisons(\n";
+        query_method += "            PropertyName {\n";
+        query_method = query_method
+            + &format!(r#"                value: "{}".to_owned(),"#, property_name)
+            + "\n";
+        query_method += "            },\n";
+        query_method += "            comparisons,\n";
+        query_method += "        );\n";
+        query_method += "        self\n";
+        query_method += "    }\n";
+        Some(query_method)
+    }
+}
+
 impl TryFrom<&Field<'static, String>> for NodePredicate {
     type Error = CodeGenError;
 
@@ -196,4 +266,35 @@ mod tests {
             expected_str
         );
     }
+
+    #[test]
+    fn generate_rust_field() -> Result<(), CodeGenError> {
+        let expected_str = "    #[grapl(pseudo_key, immutable)]\n    process_id: u64,\n";
+        let node_predicate = NodePredicate {
+            predicate_name: String::from("process_id"),
+            description: None,
+            predicate_type: PredicateType::U64,
+            conflict_resolution: ConflictResolution::Immutable,
+            identity_predicate_type: Some(IdentityPredicateType::SessionPseudoKey),
+            nullable: false,
+        };
+        assert_eq!(node_predicate.generate_rust_field()?, expected_str);
+        Ok(())
+    }
+
+    #[test]
+    fn generate_rust_field_unsupported_property_type() {
+        let node_predicate = NodePredicate {
+            predicate_name: String::from("process_name"),
+            description: None,
+            predicate_type: PredicateType::String,
+            conflict_resolution: ConflictResolution::IncrementOnly,
+            identity_predicate_type: None,
+            nullable: false,
+        };
+        assert!(matches!(
+            node_predicate.generate_rust_field(),
+            Err(CodeGenError::UnsupportedPropertyType { .. })
+        ));
+    }
 }
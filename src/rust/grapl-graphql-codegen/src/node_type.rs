@@ -386,6 +386,154 @@ impl NodeType {
     }
 }
 
+// Rust code generation
+impl NodeType {
+    /// Generates a struct that derives the node's `{type_name}Node` builder,
+    /// helpers that add its edges to a `GraphDescription`, and a
+    /// `{type_name}Query` query builder
+    pub fn generate_rust_code(&self) -> Result<String, CodeGenError> {
+        let mut rscode = String::with_capacity(1024);
+
+        rscode += &self.generate_rust_struct()?;
+        rscode += "\n";
+
+        rscode += &self.generate_rust_node_impl();
+        rscode += "\n";
+
+        if !self.edges.is_empty() {
+            rscode += &self.generate_rust_edge_methods()?;
+            rscode += "\n";
+        }
+
+        rscode += &self.generate_rust_query();
+
+        Ok(rscode)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn generate_rust_struct(&self) -> Result<String, CodeGenError> {
+        let mut rust_struct = String::with_capacity(512);
+
+        tracing::trace!(
+            message="Generating Rust struct",
+            node_type=?self.type_name,
+        );
+
+        let identity_derive = match self.identification_algorithm {
+            IdentificationAlgorithm::Session => "GraplSessionId",
+            IdentificationAlgorithm::Static => "GraplStaticId",
+        };
+        rust_struct =
+            rust_struct + &format!("#[derive(NodeDescription, {})]", identity_derive) + "\n";
+        rust_struct = rust_struct + "pub struct " + &self.type_name + " {\n";
+        for predicate in self.predicates.iter() {
+            rust_struct += &predicate.generate_rust_field()?;
+        }
+        rust_struct += "}\n";
+
+        Ok(rust_struct)
+    }
+
+    /// derive-dynamic-node leaves implementing the `I{type_name}Node` trait to
+    /// us, so that property setters can be overridden
+    pub fn generate_rust_node_impl(&self) -> String {
+        let mut node_impl = String::with_capacity(256);
+
+        node_impl = node_impl + &format!("impl I{0}Node for {0}Node {{", self.type_name) + "\n";
+        node_impl += "    fn get_mut_dynamic_node(&mut self) -> &mut NodeDescription {\n";
+        node_impl += "        &mut self.dynamic_node\n";
+        node_impl += "    }\n";
+        node_impl += "\n";
+        node_impl += "    fn get_dynamic_node(&self) -> &NodeDescription {\n";
+        node_impl += "        &self.dynamic_node\n";
+        node_impl += "    }\n";
+        node_impl += "}\n";
+        node_impl
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn generate_rust_edge_methods(&self) -> Result<String, CodeGenError> {
+        let mut edge_methods = String::with_capacity(512);
+
+        edge_methods = edge_methods + "impl " + &self.type_name + "Node {\n";
+        for (i, edge) in self.edges.iter().enumerate() {
+            tracing::trace!(
+                message="Generating Rust edge method",
+                node_type=?self.type_name,
+                edge_name=?edge.edge_name,
+                reverse_edge_name=?edge.reverse_edge_name,
+                target_type_name=?edge.target_type_name,
+            );
+            if i > 0 {
+                edge_methods.push('\n');
+            }
+            edge_methods += &edge.generate_rust_add_edge_method()?;
+        }
+        edge_methods += "}\n";
+
+        Ok(edge_methods)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn generate_rust_query(&self) -> String {
+        let mut query = String::with_capacity(1024);
+        let query_name = self.get_query_name();
+
+        tracing::trace!(
+            message="Generating Rust query builder",
+            node_type=?self.type_name,
+            query_name=?query_name,
+        );
+
+        query = query
+            + &format!(
+                "/// Builds a query for {} nodes, and their neighbors",
+                self.type_name
+            )
+            + "\n";
+        query = query + "pub struct " + &query_name + "<'a>(&'a mut NodeQuery);\n";
+        query += "\n";
+        query = query + "impl<'a> " + &query_name + "<'a> {\n";
+        query += "    pub fn node_type() -> NodeType {\n";
+        query += "        NodeType {\n";
+        query = query + &format!(r#"            value: "{}".to_owned(),"#, self.type_name) + "\n";
+        query += "        }\n";
+        query += "    }\n";
+        query += "\n";
+        query = query
+            + &format!(
+                "    /// A query whose root is a {} node. Call `build` on it once it's been",
+                self.type_name
+            )
+            + "\n";
+        query = query + &format!("    /// filtered with `{}::new`.", query_name) + "\n";
+        query += "    pub fn root() -> NodeQuery {\n";
+        query += "        NodeQuery::root(Self::node_type())\n";
+        query += "    }\n";
+        query += "\n";
+        query += "    pub fn new(node_query: &'a mut NodeQuery) -> Self {\n";
+        query += "        Self(node_query)\n";
+        query += "    }\n";
+
+        for query_method in self
+            .predicates
+            .iter()
+            .filter_map(NodePredicate::generate_rust_query_method)
+        {
+            query += "\n";
+            query += &query_method;
+        }
+
+        for edge in self.edges.iter() {
+            query += "\n";
+            query += &edge.generate_rust_query_method();
+        }
+        query += "}\n";
+
+        query
+    }
+}
+
 impl TryFrom<&ObjectType<'static, String>> for NodeType {
     type Error = CodeGenError;
 
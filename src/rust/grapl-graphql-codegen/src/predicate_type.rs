@@ -6,6 +6,7 @@ use std::convert::{
 use graphql_parser::schema::Type;
 
 use crate::{
+    conflict_resolution::ConflictResolution,
     constants::{
         INT,
         STRING,
@@ -15,7 +16,7 @@ use crate::{
 };
 
 /// PredicateType represents one of the supported types in Grapl
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PredicateType {
     String,
     I64,
@@ -51,6 +52,40 @@ impl PredicateType {
     }
 }
 
+// Rust code generation
+impl PredicateType {
+    pub fn into_rust_primitive_type(self) -> &'static str {
+        match self {
+            PredicateType::String => "String",
+            PredicateType::I64 => "i64",
+            PredicateType::U64 => "u64",
+        }
+    }
+
+    /// The `rust_proto` property type that a value of this type is stored as,
+    /// or None if Grapl can't resolve conflicts between values of this type
+    /// that way, e.g. increment-only strings
+    pub fn into_rust_property_type(
+        self,
+        conflict_resolution: ConflictResolution,
+    ) -> Option<&'static str> {
+        match (self, conflict_resolution) {
+            (PredicateType::String, ConflictResolution::Immutable) => Some("ImmutableStrProp"),
+            (PredicateType::String, _) => None,
+            (PredicateType::I64, ConflictResolution::Immutable) => Some("ImmutableIntProp"),
+            (PredicateType::I64, ConflictResolution::IncrementOnly) => Some("IncrementOnlyIntProp"),
+            (PredicateType::I64, ConflictResolution::DecrementOnly) => Some("DecrementOnlyIntProp"),
+            (PredicateType::U64, ConflictResolution::Immutable) => Some("ImmutableUintProp"),
+            (PredicateType::U64, ConflictResolution::IncrementOnly) => {
+                Some("IncrementOnlyUintProp")
+            }
+            (PredicateType::U64, ConflictResolution::DecrementOnly) => {
+                Some("DecrementOnlyUintProp")
+            }
+        }
+    }
+}
+
 impl TryFrom<&Type<'static, String>> for PredicateType {
     type Error = CodeGenError;
 
@@ -91,4 +126,20 @@ mod tests {
             "IntOrNot"
         );
     }
+
+    #[test]
+    fn test_rust_property_type() {
+        assert_eq!(
+            PredicateType::String.into_rust_property_type(ConflictResolution::Immutable),
+            Some("ImmutableStrProp")
+        );
+        assert_eq!(
+            PredicateType::String.into_rust_property_type(ConflictResolution::IncrementOnly),
+            None
+        );
+        assert_eq!(
+            PredicateType::U64.into_rust_property_type(ConflictResolution::DecrementOnly),
+            Some("DecrementOnlyUintProp")
+        );
+    }
 }
@@ -0,0 +1,69 @@
+//! Golden file tests for the Rust backend. Every `golden/*.graphql` schema is
+//! generated into Rust and compared against the `.rs` file beside it. The
+//! `.rs` files are compiled by `golden_compiles.rs`.
+//!
+//! After an intentional change to the generated code, regenerate the `.rs`
+//! files with `UPDATE_GOLDEN=1 cargo test -p grapl-graphql-codegen --test golden`
+//! and review the diff.
+
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use grapl_graphql_codegen::{
+    lang::generate_rust_code,
+    node_type::parse_into_node_types,
+    parse_schema,
+};
+
+fn golden_schemas() -> Vec<PathBuf> {
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut schemas: Vec<PathBuf> = std::fs::read_dir(golden_dir)
+        .expect("golden directory")
+        .map(|entry| entry.expect("golden directory entry").path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "graphql"))
+        .collect();
+    schemas.sort();
+    schemas
+}
+
+fn generate(schema_path: &Path) -> String {
+    let raw_schema = std::fs::read_to_string(schema_path).expect("schema");
+    let document = parse_schema::<String>(&raw_schema)
+        .expect("valid schema")
+        .into_static();
+    let node_types = parse_into_node_types(document).expect("node types");
+    generate_rust_code(&node_types).expect("rust code")
+}
+
+#[test]
+fn test_rust_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let schemas = golden_schemas();
+    assert!(!schemas.is_empty(), "no golden schemas found");
+
+    for schema_path in schemas {
+        let golden_path = schema_path.with_extension("rs");
+        let generated = generate(&schema_path);
+
+        if update {
+            std::fs::write(&golden_path, &generated).expect("write golden file");
+            continue;
+        }
+
+        let golden = std::fs::read_to_string(&golden_path).unwrap_or_else(|_| {
+            panic!(
+                "missing {}, run with UPDATE_GOLDEN=1 to create it",
+                golden_path.display()
+            )
+        });
+        assert_eq!(
+            generated,
+            golden,
+            "generated code for {} differs from {}",
+            schema_path.display(),
+            golden_path.display()
+        );
+    }
+}
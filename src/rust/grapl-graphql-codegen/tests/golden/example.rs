@@ -0,0 +1,325 @@
+// Generated by grapl-graphql-codegen. Do not edit.
+use std::collections::HashMap;
+
+use derive_dynamic_node::{
+    GraplSessionId,
+    GraplStaticId,
+    NodeDescription,
+};
+use graph_query::node_query::NodeQuery;
+use rust_proto::graplinc::grapl::api::graph::v1beta1::{
+    DecrementOnlyUintProp,
+    GraphDescription,
+    IdStrategy,
+    IncrementOnlyUintProp,
+    NodeDescription,
+    NodeProperty,
+    Session,
+    Static,
+};
+use rust_proto::graplinc::grapl::api::graph_query::v1beta1::messages::AndStringFilters;
+use rust_proto::graplinc::grapl::common::v1beta1::types::{
+    EdgeName,
+    NodeType,
+    PropertyName,
+};
+
+#[derive(NodeDescription, GraplStaticId)]
+pub struct Asset {
+    #[grapl(static_id, immutable)]
+    asset_id: String,
+    #[grapl(immutable)]
+    hostname: String,
+    #[grapl(immutable)]
+    launch_time: u64,
+    #[grapl(increment)]
+    last_seen_time: u64,
+}
+
+impl IAssetNode for AssetNode {
+    fn get_mut_dynamic_node(&mut self) -> &mut NodeDescription {
+        &mut self.dynamic_node
+    }
+
+    fn get_dynamic_node(&self) -> &NodeDescription {
+        &self.dynamic_node
+    }
+}
+
+impl AssetNode {
+    /// Adds a `files_on_asset` edge from this node to `to`. Its reverse edge is `file_asset`.
+    pub fn add_files_on_asset_edge(
+        &self,
+        graph: &mut GraphDescription,
+        to: &FileNode,
+    ) {
+        graph.add_edge("files_on_asset", self.clone_node_key(), to.clone_node_key());
+    }
+}
+
+/// Builds a query for Asset nodes, and their neighbors
+pub struct AssetQuery<'a>(&'a mut NodeQuery);
+
+impl<'a> AssetQuery<'a> {
+    pub fn node_type() -> NodeType {
+        NodeType {
+            value: "Asset".to_owned(),
+        }
+    }
+
+    /// A query whose root is a Asset node. Call `build` on it once it's been
+    /// filtered with `AssetQuery::new`.
+    pub fn root() -> NodeQuery {
+        NodeQuery::root(Self::node_type())
+    }
+
+    pub fn new(node_query: &'a mut NodeQuery) -> Self {
+        Self(node_query)
+    }
+
+    pub fn with_asset_id(&mut self, comparisons: impl Into<AndStringFilters>) -> &mut Self {
+        self.0.with_string_comparisons(
+            PropertyName {
+                value: "asset_id".to_owned(),
+            },
+            comparisons,
+        );
+        self
+    }
+
+    pub fn with_hostname(&mut self, comparisons: impl Into<AndStringFilters>) -> &mut Self {
+        self.0.with_string_comparisons(
+            PropertyName {
+                value: "hostname".to_owned(),
+            },
+            comparisons,
+        );
+        self
+    }
+
+    pub fn with_files_on_asset(&mut self, init_edge: impl FnOnce(&mut FileQuery<'_>)) -> &mut Self {
+        self.0.with_edge_to(
+            EdgeName {
+                value: "files_on_asset".to_owned(),
+            },
+            EdgeName {
+                value: "file_asset".to_owned(),
+            },
+            FileQuery::node_type(),
+            |neighbor| init_edge(&mut FileQuery::new(neighbor)),
+        );
+        self
+    }
+}
+
+#[derive(NodeDescription, GraplSessionId)]
+pub struct File {
+    #[grapl(pseudo_key, immutable)]
+    file_path: String,
+    #[grapl(create_time, immutable)]
+    created_at: u64,
+    #[grapl(last_seen_time, increment)]
+    last_seen_at: u64,
+    #[grapl(terminate_time, immutable)]
+    terminated_at: u64,
+}
+
+impl IFileNode for FileNode {
+    fn get_mut_dynamic_node(&mut self) -> &mut NodeDescription {
+        &mut self.dynamic_node
+    }
+
+    fn get_dynamic_node(&self) -> &NodeDescription {
+        &self.dynamic_node
+    }
+}
+
+/// Builds a query for File nodes, and their neighbors
+pub struct FileQuery<'a>(&'a mut NodeQuery);
+
+impl<'a> FileQuery<'a> {
+    pub fn node_type() -> NodeType {
+        NodeType {
+            value: "File".to_owned(),
+        }
+    }
+
+    /// A query whose root is a File node. Call `build` on it once it's been
+    /// filtered with `FileQuery::new`.
+    pub fn root() -> NodeQuery {
+        NodeQuery::root(Self::node_type())
+    }
+
+    pub fn new(node_query: &'a mut NodeQuery) -> Self {
+        Self(node_query)
+    }
+
+    pub fn with_file_path(&mut self, comparisons: impl Into<AndStringFilters>) -> &mut Self {
+        self.0.with_string_comparisons(
+            PropertyName {
+                value: "file_path".to_owned(),
+            },
+            comparisons,
+        );
+        self
+    }
+}
+
+#[derive(NodeDescription, GraplSessionId)]
+pub struct Process {
+    #[grapl(immutable)]
+    process_name: String,
+    #[grapl(pseudo_key, immutable)]
+    process_id: u64,
+    #[grapl(create_time, immutable)]
+    created_at: u64,
+    #[grapl(last_seen_time, increment)]
+    last_seen_at: u64,
+    #[grapl(terminate_time, immutable)]
+    terminated_at: u64,
+}
+
+impl IProcessNode for ProcessNode {
+    fn get_mut_dynamic_node(&mut self) -> &mut NodeDescription {
+        &mut self.dynamic_node
+    }
+
+    fn get_dynamic_node(&self) -> &NodeDescription {
+        &self.dynamic_node
+    }
+}
+
+impl ProcessNode {
+    /// Adds a `binary_file` edge from this node to `to`. Its reverse edge is `executed_as_processes`.
+    pub fn add_binary_file_edge(
+        &self,
+        graph: &mut GraphDescription,
+        to: &FileNode,
+    ) {
+        graph.add_edge("binary_file", self.clone_node_key(), to.clone_node_key());
+    }
+
+    /// Adds a `created_files` edge from this node to `to`. Its reverse edge is `created_by_process`.
+    pub fn add_created_files_edge(
+        &self,
+        graph: &mut GraphDescription,
+        to: &FileNode,
+    ) {
+        graph.add_edge("created_files", self.clone_node_key(), to.clone_node_key());
+    }
+
+    /// Adds a `process_asset` edge from this node to `to`. Its reverse edge is `asset_processes`.
+    pub fn add_process_asset_edge(
+        &self,
+        graph: &mut GraphDescription,
+        to: &AssetNode,
+    ) {
+        graph.add_edge("process_asset", self.clone_node_key(), to.clone_node_key());
+    }
+
+    /// Adds a `children` edge from this node to `to`. Its reverse edge is `parent`.
+    pub fn add_children_edge(
+        &self,
+        graph: &mut GraphDescription,
+        to: &ProcessNode,
+        first_seen_at: impl Into<DecrementOnlyUintProp>,
+        last_seen_at: impl Into<IncrementOnlyUintProp>,
+    ) {
+        graph.add_edge_with_properties(
+            "children",
+            self.clone_node_key(),
+            to.clone_node_key(),
+            HashMap::from([
+                ("first_seen_at".to_owned(), NodeProperty::from(first_seen_at.into())),
+                ("last_seen_at".to_owned(), NodeProperty::from(last_seen_at.into())),
+            ]),
+        );
+    }
+}
+
+/// Builds a query for Process nodes, and their neighbors
+pub struct ProcessQuery<'a>(&'a mut NodeQuery);
+
+impl<'a> ProcessQuery<'a> {
+    pub fn node_type() -> NodeType {
+        NodeType {
+            value: "Process".to_owned(),
+        }
+    }
+
+    /// A query whose root is a Process node. Call `build` on it once it's been
+    /// filtered with `ProcessQuery::new`.
+    pub fn root() -> NodeQuery {
+        NodeQuery::root(Self::node_type())
+    }
+
+    pub fn new(node_query: &'a mut NodeQuery) -> Self {
+        Self(node_query)
+    }
+
+    pub fn with_process_name(&mut self, comparisons: impl Into<AndStringFilters>) -> &mut Self {
+        self.0.with_string_comparisons(
+            PropertyName {
+                value: "process_name".to_owned(),
+            },
+            comparisons,
+        );
+        self
+    }
+
+    pub fn with_binary_file(&mut self, init_edge: impl FnOnce(&mut FileQuery<'_>)) -> &mut Self {
+        self.0.with_edge_to(
+            EdgeName {
+                value: "binary_file".to_owned(),
+            },
+            EdgeName {
+                value: "executed_as_processes".to_owned(),
+            },
+            FileQuery::node_type(),
+            |neighbor| init_edge(&mut FileQuery::new(neighbor)),
+        );
+        self
+    }
+
+    pub fn with_created_files(&mut self, init_edge: impl FnOnce(&mut FileQuery<'_>)) -> &mut Self {
+        self.0.with_edge_to(
+            EdgeName {
+                value: "created_files".to_owned(),
+            },
+            EdgeName {
+                value: "created_by_process".to_owned(),
+            },
+            FileQuery::node_type(),
+            |neighbor| init_edge(&mut FileQuery::new(neighbor)),
+        );
+        self
+    }
+
+    pub fn with_process_asset(&mut self, init_edge: impl FnOnce(&mut AssetQuery<'_>)) -> &mut Self {
+        self.0.with_edge_to(
+            EdgeName {
+                value: "process_asset".to_owned(),
+            },
+            EdgeName {
+                value: "asset_processes".to_owned(),
+            },
+            AssetQuery::node_type(),
+            |neighbor| init_edge(&mut AssetQuery::new(neighbor)),
+        );
+        self
+    }
+
+    pub fn with_children(&mut self, init_edge: impl FnOnce(&mut ProcessQuery<'_>)) -> &mut Self {
+        self.0.with_edge_to(
+            EdgeName {
+                value: "children".to_owned(),
+            },
+            EdgeName {
+                value: "parent".to_owned(),
+            },
+            ProcessQuery::node_type(),
+            |neighbor| init_edge(&mut ProcessQuery::new(neighbor)),
+        );
+        self
+    }
+}
@@ -0,0 +1,81 @@
+// Generated by grapl-graphql-codegen. Do not edit.
+use derive_dynamic_node::{
+    GraplStaticId,
+    NodeDescription,
+};
+use graph_query::node_query::NodeQuery;
+use rust_proto::graplinc::grapl::api::graph::v1beta1::{
+    IdStrategy,
+    NodeDescription,
+    NodeProperty,
+    Static,
+};
+use rust_proto::graplinc::grapl::api::graph_query::v1beta1::messages::AndStringFilters;
+use rust_proto::graplinc::grapl::common::v1beta1::types::{
+    NodeType,
+    PropertyName,
+};
+
+#[derive(NodeDescription, GraplStaticId)]
+pub struct AwsEc2Instance {
+    /// The instance's Amazon Resource Name
+    #[grapl(static_id, immutable)]
+    arn: String,
+    #[grapl(static_id, immutable)]
+    launch_time: u64,
+    #[grapl(decrement)]
+    cpu_credit_balance: i64,
+    #[grapl(immutable)]
+    instance_type: String,
+}
+
+impl IAwsEc2InstanceNode for AwsEc2InstanceNode {
+    fn get_mut_dynamic_node(&mut self) -> &mut NodeDescription {
+        &mut self.dynamic_node
+    }
+
+    fn get_dynamic_node(&self) -> &NodeDescription {
+        &self.dynamic_node
+    }
+}
+
+/// Builds a query for AwsEc2Instance nodes, and their neighbors
+pub struct AwsEc2InstanceQuery<'a>(&'a mut NodeQuery);
+
+impl<'a> AwsEc2InstanceQuery<'a> {
+    pub fn node_type() -> NodeType {
+        NodeType {
+            value: "AwsEc2Instance".to_owned(),
+        }
+    }
+
+    /// A query whose root is a AwsEc2Instance node. Call `build` on it once it's been
+    /// filtered with `AwsEc2InstanceQuery::new`.
+    pub fn root() -> NodeQuery {
+        NodeQuery::root(Self::node_type())
+    }
+
+    pub fn new(node_query: &'a mut NodeQuery) -> Self {
+        Self(node_query)
+    }
+
+    pub fn with_arn(&mut self, comparisons: impl Into<AndStringFilters>) -> &mut Self {
+        self.0.with_string_comparisons(
+            PropertyName {
+                value: "arn".to_owned(),
+            },
+            comparisons,
+        );
+        self
+    }
+
+    pub fn with_instance_type(&mut self, comparisons: impl Into<AndStringFilters>) -> &mut Self {
+        self.0.with_string_comparisons(
+            PropertyName {
+                value: "instance_type".to_owned(),
+            },
+            comparisons,
+        );
+        self
+    }
+}
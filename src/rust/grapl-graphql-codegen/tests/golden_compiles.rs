@@ -0,0 +1,97 @@
+//! Compiles every golden file against the crates generated code depends on,
+//! so that a golden file that matches the generator but doesn't build fails
+//! here rather than in a plugin.
+//!
+//! Add a module here for each new `golden/*.rs` file.
+
+use rust_proto::graplinc::grapl::api::{
+    graph::v1beta1::{
+        GraphDescription,
+        Strategy,
+    },
+    graph_query::v1beta1::messages::AndStringFilters,
+};
+
+#[allow(dead_code)]
+mod example {
+    include!("golden/example.rs");
+}
+
+#[allow(dead_code)]
+mod static_node {
+    include!("golden/static_node.rs");
+}
+
+#[test]
+fn test_example_nodes_and_edges() {
+    use example::*;
+
+    let mut parent = ProcessNode::new(ProcessNode::session_strategy());
+    parent.with_process_id(1u64).with_created_at(100u64);
+    let child = ProcessNode::new(ProcessNode::session_strategy());
+    let binary = FileNode::new(FileNode::session_strategy());
+
+    assert_eq!(parent.get_process_id().map(|p| p.prop), Some(1));
+    match &parent.get_dynamic_node().id_strategy[0].strategy {
+        Strategy::Session(session) => {
+            assert_eq!(session.primary_key_properties, vec!["process_id"]);
+            assert_eq!(session.create_time, 100);
+        }
+        strategy => panic!("expected a session strategy, got {strategy:?}"),
+    }
+
+    let mut graph = GraphDescription::new();
+    parent.add_binary_file_edge(&mut graph, &binary);
+    parent.add_children_edge(&mut graph, &child, 10u64, 20u64);
+
+    let edges = &graph.edges[parent.get_node_key()].edges;
+    assert_eq!(edges.len(), 2);
+    let children = edges
+        .iter()
+        .find(|edge| edge.edge_name == "children")
+        .expect("children edge");
+    assert_eq!(children.to_node_key, child.get_node_key());
+    assert_eq!(children.properties.len(), 2);
+}
+
+#[test]
+fn test_example_queries() {
+    use example::*;
+
+    let mut query = ProcessQuery::root();
+    ProcessQuery::new(&mut query)
+        .with_process_name(AndStringFilters::default())
+        .with_binary_file(|file| {
+            file.with_file_path(AndStringFilters::default());
+        })
+        .with_process_asset(|asset| {
+            asset.with_files_on_asset(|_| {});
+        })
+        .with_children(|_| {});
+}
+
+#[test]
+fn test_static_node() {
+    use static_node::*;
+
+    let mut instance = AwsEc2InstanceNode::new(AwsEc2InstanceNode::static_strategy());
+    instance
+        .with_arn("arn:aws:ec2:instance")
+        .with_cpu_credit_balance(-5i64);
+
+    assert_eq!(
+        instance.get_arn().map(|arn| arn.prop),
+        Some("arn:aws:ec2:instance".to_owned())
+    );
+    match &instance.get_dynamic_node().id_strategy[0].strategy {
+        Strategy::Static(r#static) => {
+            assert_eq!(r#static.primary_key_properties, vec!["arn", "launch_time"]);
+        }
+        strategy => panic!("expected a static strategy, got {strategy:?}"),
+    }
+
+    let mut query = AwsEc2InstanceQuery::root();
+    AwsEc2InstanceQuery::new(&mut query)
+        .with_arn(AndStringFilters::default())
+        .with_instance_type(AndStringFilters::default());
+}
@@ -2,10 +2,12 @@ use clap::Parser;
 use futures::stream::StreamExt;
 use kafka::{
     config::{
+        DeadLetterProducerConfig,
         ProducerConfig,
         RetryConsumerConfig,
     },
     ConfigurationError,
+    RetryOutcome,
     RetryProcessor,
 };
 use tracing::instrument::WithSubscriber;
@@ -13,6 +15,7 @@ use tracing::instrument::WithSubscriber;
 struct KafkaRetryConfig {
     kafka_retry_consumer_config: RetryConsumerConfig,
     kafka_producer_config: ProducerConfig,
+    kafka_dead_letter_producer_config: DeadLetterProducerConfig,
 }
 
 impl KafkaRetryConfig {
@@ -20,6 +23,7 @@ impl KafkaRetryConfig {
         KafkaRetryConfig {
             kafka_retry_consumer_config: RetryConsumerConfig::parse(),
             kafka_producer_config: ProducerConfig::parse(),
+            kafka_dead_letter_producer_config: DeadLetterProducerConfig::parse(),
         }
     }
 }
@@ -40,19 +44,26 @@ async fn handler() -> Result<(), ConfigurationError> {
     let retry_processor = RetryProcessor::new(
         config.kafka_retry_consumer_config,
         config.kafka_producer_config,
+        config.kafka_dead_letter_producer_config,
     )?;
 
     retry_processor
         .stream()
         .for_each(|res| async move {
-            if let Err(e) = res {
-                tracing::error!(
-                    message = "Error processing Kafka message",
-                    reason =% e,
-                );
-            } else {
-                // TODO: collect metrics
-                tracing::debug!("Processed Kafka message");
+            match res {
+                Ok(RetryOutcome::Retried) => {
+                    tracing::debug!("Retried Kafka message");
+                }
+                Ok(RetryOutcome::DeadLettered) => {
+                    // the RetryProcessor has already logged why
+                    tracing::debug!("Dead-lettered Kafka message");
+                }
+                Err(e) => {
+                    tracing::error!(
+                        message = "Error processing Kafka message",
+                        reason =% e,
+                    );
+                }
             }
         })
         .with_current_subscriber()
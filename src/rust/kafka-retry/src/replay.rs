@@ -0,0 +1,120 @@
+//! Replays the messages in a service's dead-letter topic back to the topic
+//! they came from, e.g. once the bug which caused them to fail has been fixed.
+//! Exits once the dead-letter topic has been idle for
+//! `KAFKA_DEAD_LETTER_REPLAY_IDLE_TIMEOUT_MS`.
+//!
+//! Dead letters whose source topic isn't `KAFKA_PRODUCER_TOPIC` are skipped,
+//! so only the messages of a single topic are replayed. A dead letter's payload
+//! is republished unchanged, including its retry count, which is already past
+//! `KAFKA_RETRY_MAXIMUM_RETRIES`. A replayed message which fails again is
+//! therefore dead-lettered again straight away, without being retried.
+
+use std::time::Duration;
+
+use clap::Parser;
+use futures::{
+    pin_mut,
+    stream::StreamExt,
+};
+use kafka::{
+    config::{
+        DeadLetterConsumerConfig,
+        ProducerConfig,
+    },
+    format_iso8601,
+    BytesProducer,
+    CommitError,
+    ConfigurationError,
+    ConsumerError,
+    DeadLetterConsumer,
+    ProducerError,
+};
+use thiserror::Error;
+
+#[derive(clap::Parser, Clone, Debug)]
+struct ReplayConfig {
+    #[clap(
+        long,
+        env = "KAFKA_DEAD_LETTER_REPLAY_IDLE_TIMEOUT_MS",
+        default_value = "10000"
+    )]
+    idle_timeout_ms: u64,
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+enum ReplayError {
+    #[error("failed to configure kafka client {0}")]
+    Configuration(#[from] ConfigurationError),
+
+    #[error("failed to consume dead letter {0}")]
+    Consumer(#[from] ConsumerError),
+
+    #[error("failed to replay dead letter {0}")]
+    Producer(#[from] ProducerError),
+
+    #[error("failed to commit consumer offsets {0}")]
+    Commit(#[from] CommitError),
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = grapl_tracing::setup_tracing("kafka-dead-letter-replay")?;
+
+    Ok(handler().await?)
+}
+
+#[tracing::instrument(err)]
+async fn handler() -> Result<(), ReplayError> {
+    let replay_config = ReplayConfig::parse();
+    let idle_timeout = Duration::from_millis(replay_config.idle_timeout_ms);
+
+    let consumer = DeadLetterConsumer::new(DeadLetterConsumerConfig::parse())?;
+    let producer = BytesProducer::new(ProducerConfig::parse())?;
+
+    tracing::info!(
+        message = "replaying dead letters",
+        topic =% producer.topic(),
+    );
+
+    let mut replayed = 0usize;
+    let mut skipped = 0usize;
+
+    let stream = consumer.stream();
+    pin_mut!(stream);
+
+    loop {
+        let dead_letter = match tokio::time::timeout(idle_timeout, stream.next()).await {
+            Ok(Some(dead_letter)) => dead_letter?,
+            Ok(None) | Err(_) => break,
+        };
+
+        if dead_letter.source_topic() == producer.topic() {
+            tracing::debug!(
+                message = "replaying dead letter",
+                reason =% dead_letter.reason(),
+                retry_count = dead_letter.retry_count(),
+                dead_lettered_time = format_iso8601(dead_letter.dead_lettered_time()),
+            );
+
+            producer.send(dead_letter.payload()).await?;
+            replayed += 1;
+        } else {
+            tracing::warn!(
+                message = "skipping dead letter from another topic",
+                source_topic =% dead_letter.source_topic(),
+            );
+            skipped += 1;
+        }
+
+        consumer.commit()?;
+    }
+
+    tracing::info!(
+        message = "finished replaying dead letters",
+        replayed = replayed,
+        skipped = skipped,
+    );
+
+    Ok(())
+}
@@ -12,6 +12,14 @@ pub struct ConsumerConfig {
     pub consumer_group_name: String,
     #[clap(long, env = "KAFKA_CONSUMER_TOPIC")]
     pub topic: String,
+    /// Where a consumer group without committed offsets starts consuming
+    /// from, "latest" or "earliest"
+    #[clap(
+        long,
+        env = "KAFKA_CONSUMER_AUTO_OFFSET_RESET",
+        default_value = "latest"
+    )]
+    pub auto_offset_reset: String,
 }
 
 impl ConsumerConfig {
@@ -38,8 +46,16 @@ pub struct RetryConsumerConfig {
     pub consumer_group_name: String,
     #[clap(long, env = "KAFKA_RETRY_TOPIC")]
     pub topic: String,
+    /// Delay before a message's first retry. The delay doubles with each
+    /// subsequent retry, up to `maximum_delay_ms`.
     #[clap(long, env = "KAFKA_RETRY_DELAY_MS")]
     pub delay_ms: u64,
+    #[clap(long, env = "KAFKA_RETRY_MAXIMUM_DELAY_MS", default_value = "60000")]
+    pub maximum_delay_ms: u64,
+    /// A message retried more than this many times is sent to the
+    /// dead-letter topic instead
+    #[clap(long, env = "KAFKA_RETRY_MAXIMUM_RETRIES", default_value = "10")]
+    pub maximum_retries: u32,
 }
 
 #[derive(clap::Parser, Clone, Debug)]
@@ -93,3 +109,65 @@ pub struct RetryProducerConfig {
     #[clap(long, env = "KAFKA_RETRY_TOPIC")]
     pub topic: String,
 }
+
+#[derive(clap::Parser, Clone, Debug)]
+pub struct DeadLetterProducerConfig {
+    #[clap(long, env = "KAFKA_BOOTSTRAP_SERVERS")]
+    pub bootstrap_servers: String,
+    #[clap(long, env = "KAFKA_SASL_USERNAME")]
+    pub sasl_username: String,
+    #[clap(long, env = "KAFKA_SASL_PASSWORD")]
+    pub sasl_password: secrecy::SecretString,
+    #[clap(long, env = "KAFKA_DEAD_LETTER_TOPIC")]
+    pub topic: String,
+}
+
+impl From<DeadLetterProducerConfig> for ProducerConfig {
+    fn from(dead_letter_producer_config: DeadLetterProducerConfig) -> Self {
+        Self {
+            bootstrap_servers: dead_letter_producer_config.bootstrap_servers,
+            sasl_username: dead_letter_producer_config.sasl_username,
+            sasl_password: dead_letter_producer_config.sasl_password,
+            topic: dead_letter_producer_config.topic,
+        }
+    }
+}
+
+#[derive(clap::Parser, Clone, Debug)]
+pub struct DeadLetterConsumerConfig {
+    #[clap(long, env = "KAFKA_BOOTSTRAP_SERVERS")]
+    pub bootstrap_servers: String,
+    #[clap(long, env = "KAFKA_SASL_USERNAME")]
+    pub sasl_username: String,
+    #[clap(long, env = "KAFKA_SASL_PASSWORD")]
+    pub sasl_password: secrecy::SecretString,
+    #[clap(long, env = "KAFKA_CONSUMER_GROUP_NAME")]
+    pub consumer_group_name: String,
+    #[clap(long, env = "KAFKA_DEAD_LETTER_TOPIC")]
+    pub topic: String,
+}
+
+/// Enables exactly-once processing in a `StreamProcessor`. See
+/// `StreamProcessor::new_transactional`.
+#[derive(clap::Parser, Clone, Debug)]
+pub struct TransactionConfig {
+    /// Identifies the producer across restarts, so that a transaction left
+    /// open by a crashed instance is aborted when it comes back. This must be
+    /// unique to each instance of a service (e.g. include the nomad alloc
+    /// index) and stable across restarts of that instance.
+    #[clap(long, env = "KAFKA_TRANSACTIONAL_ID")]
+    pub transactional_id: String,
+    #[clap(long, env = "KAFKA_TRANSACTION_TIMEOUT_MS", default_value = "60000")]
+    pub transaction_timeout_ms: u64,
+    /// Maximum number of consumed messages processed in a single transaction
+    #[clap(long, env = "KAFKA_TRANSACTION_MAX_BATCH_SIZE", default_value = "100")]
+    pub max_batch_size: usize,
+}
+
+impl TransactionConfig {
+    /// Transactions are opt-in, this returns None unless
+    /// KAFKA_TRANSACTIONAL_ID is set.
+    pub fn from_env_vars() -> Option<Self> {
+        std::env::var_os("KAFKA_TRANSACTIONAL_ID").map(|_| Self::parse())
+    }
+}
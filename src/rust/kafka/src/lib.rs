@@ -4,6 +4,7 @@ pub mod config;
 pub mod test_utils;
 
 use std::{
+    collections::BTreeMap,
     marker::PhantomData,
     time::{
         Duration,
@@ -21,11 +22,15 @@ use chrono::{
 };
 use config::{
     ConsumerConfig,
+    DeadLetterConsumerConfig,
+    DeadLetterProducerConfig,
     ProducerConfig,
     RetryConsumerConfig,
     RetryProducerConfig,
+    TransactionConfig,
 };
 use futures::{
+    future::Either,
     stream::{
         Stream,
         StreamExt,
@@ -41,16 +46,31 @@ use rdkafka::{
         CommitMode,
         Consumer as KafkaConsumer,
     },
-    error::KafkaError,
+    error::{
+        KafkaError,
+        KafkaResult,
+        RDKafkaErrorCode,
+    },
+    message::{
+        Headers,
+        OwnedHeaders,
+    },
     producer::{
         FutureProducer,
         FutureRecord,
+        Producer as KafkaProducer,
     },
     util::Timeout,
     Message,
+    Offset,
+    TopicPartitionList,
 };
 use rust_proto::{
-    graplinc::grapl::pipeline::v1beta1::Envelope,
+    graplinc::grapl::pipeline::v1beta1::{
+        DeadLetter,
+        Envelope,
+        EnvelopeMetadata,
+    },
     SerDe,
     SerDeError,
 };
@@ -78,6 +98,9 @@ pub enum ConfigurationError {
 
     #[error("failed to subscribe kafka consumer {0}")]
     SubscriptionFailed(KafkaError),
+
+    #[error("failed to initialize kafka transactions {0}")]
+    TransactionInitFailed(KafkaError),
 }
 
 fn configure(
@@ -125,6 +148,34 @@ fn producer(
         .map_err(|e| ConfigurationError::ProducerCreateFailed(e))
 }
 
+fn transactional_producer(
+    bootstrap_servers: String,
+    sasl_username: String,
+    sasl_password: secrecy::SecretString,
+    transaction_config: &TransactionConfig,
+) -> Result<FutureProducer, ConfigurationError> {
+    let producer: FutureProducer = configure(bootstrap_servers, sasl_username, sasl_password)
+        .set("compression.type", "zstd")
+        .set("acks", "all")
+        .set("transactional.id", &transaction_config.transactional_id)
+        .set(
+            "transaction.timeout.ms",
+            transaction_config.transaction_timeout_ms.to_string(),
+        )
+        .create()
+        .map_err(|e| ConfigurationError::ProducerCreateFailed(e))?;
+
+    // This also aborts any transaction left open by a previous producer with
+    // the same transactional.id, and fences that producer off.
+    producer
+        .init_transactions(Duration::from_millis(
+            transaction_config.transaction_timeout_ms,
+        ))
+        .map_err(ConfigurationError::TransactionInitFailed)?;
+
+    Ok(producer)
+}
+
 #[non_exhaustive]
 #[derive(Error, Debug, Clone)]
 pub enum ProducerError {
@@ -133,6 +184,20 @@ pub enum ProducerError {
 
     #[error("failed to deliver message to kafka {0}")]
     KafkaError(#[from] KafkaError),
+
+    #[error("message of {size} bytes exceeds the broker's maximum message size")]
+    MessageTooLarge { size: usize },
+}
+
+impl ProducerError {
+    fn from_delivery_error(error: KafkaError, size: usize) -> Self {
+        match error {
+            KafkaError::MessageProduction(RDKafkaErrorCode::MessageSizeTooLarge) => {
+                ProducerError::MessageTooLarge { size }
+            }
+            error => ProducerError::KafkaError(error),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -162,18 +227,31 @@ impl<T: SerDe> Producer<T> {
 
     #[tracing::instrument(err, skip(self))]
     pub async fn send(&self, msg: Envelope<T>) -> Result<(), ProducerError> {
+        self.send_with_headers(msg, None).await
+    }
+
+    async fn send_with_headers(
+        &self,
+        msg: Envelope<T>,
+        headers: Option<OwnedHeaders>,
+    ) -> Result<(), ProducerError> {
         let tenant_id = msg.tenant_id();
         let trace_id = msg.trace_id();
         let event_source_id = msg.event_source_id();
 
         let serialized = msg.serialize()?;
-        let record: FutureRecord<[u8], [u8]> = FutureRecord::to(&self.topic).payload(&serialized);
+        let size = serialized.len();
+        let mut record: FutureRecord<[u8], [u8]> =
+            FutureRecord::to(&self.topic).payload(&serialized);
+        if let Some(headers) = headers {
+            record = record.headers(headers);
+        }
 
         let result = self
             .producer
             .send(record, Timeout::Never)
             .map(|res| -> Result<(), ProducerError> {
-                res.map_err(|(e, _)| -> ProducerError { e.into() })
+                res.map_err(|(e, _)| ProducerError::from_delivery_error(e, size))
                     .map(|(partition, offset)| {
                         tracing::debug!(
                             message = "wrote kafka message",
@@ -209,14 +287,19 @@ impl BytesProducer {
         })
     }
 
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
     #[tracing::instrument(err, skip(self))]
     pub async fn send(&self, msg: Bytes) -> Result<(), ProducerError> {
+        let size = msg.len();
         let record: FutureRecord<[u8], [u8]> = FutureRecord::to(&self.topic).payload(&msg);
 
         self.producer
             .send(record, Timeout::Never)
             .map(|res| -> Result<(), ProducerError> {
-                res.map_err(|(e, _)| -> ProducerError { e.into() })
+                res.map_err(|(e, _)| ProducerError::from_delivery_error(e, size))
                     .map(|(partition, offset)| {
                         tracing::debug!(
                             message = "wrote kafka message",
@@ -229,6 +312,10 @@ impl BytesProducer {
     }
 }
 
+/// The name of the Kafka header in which a `RetryProducer` records why a
+/// message was retried.
+pub const RETRY_REASON_HEADER: &str = "grapl-retry-reason";
+
 #[derive(Clone)]
 pub struct RetryProducer<T>
 where
@@ -249,10 +336,14 @@ impl<T: SerDe> RetryProducer<T> {
         })
     }
 
+    /// Publish a message to the retry topic. The `reason` travels with the
+    /// message in a header, so that it can be recorded if the message runs out
+    /// of retries and is dead-lettered.
     #[tracing::instrument(err, skip(self))]
-    pub async fn send(&self, mut msg: Envelope<T>) -> Result<(), ProducerError> {
+    pub async fn send(&self, mut msg: Envelope<T>, reason: &str) -> Result<(), ProducerError> {
         msg.increment_retry_count();
-        self.producer.send(msg).await
+        let headers = OwnedHeaders::new().add(RETRY_REASON_HEADER, reason);
+        self.producer.send_with_headers(msg, Some(headers)).await
     }
 }
 
@@ -265,12 +356,13 @@ fn consumer(
     sasl_username: String,
     sasl_password: secrecy::SecretString,
     consumer_group_name: String,
+    auto_offset_reset: &str,
 ) -> Result<StreamConsumer, ConfigurationError> {
     configure(bootstrap_servers, sasl_username, sasl_password)
         .set("group.id", consumer_group_name)
         .set("enable.auto.commit", "false")
         .set("enable.auto.offset.store", "true")
-        .set("auto.offset.reset", "latest")
+        .set("auto.offset.reset", auto_offset_reset)
         .set("session.timeout.ms", "45000")
         .create()
         .map_err(|e| ConfigurationError::ConsumerCreateFailed(e))
@@ -314,6 +406,7 @@ impl<T: SerDe> Consumer<T> {
             config.sasl_username,
             config.sasl_password,
             config.consumer_group_name,
+            &config.auto_offset_reset,
         )?;
 
         // the .subscribe(..) call must be fully-qualified here because the
@@ -336,39 +429,76 @@ impl<T: SerDe> Consumer<T> {
         &self,
     ) -> impl Stream<Item = Result<(tracing::Span, Envelope<T>), ConsumerError>> + '_ {
         self.consumer.stream().then(move |res| async move {
-            res.map_err(ConsumerError::from).and_then(move |msg| {
-                let deserialized =
-                    Envelope::deserialize(msg.payload().ok_or(ConsumerError::PayloadAbsent)?)
-                        .map_err(ConsumerError::from);
-
-                deserialized.map(|envelope| {
-                    let span = tracing::span!(
-                        target: "stream_processor",
-                        tracing::Level::INFO,
-                        "envelope_span",
-                        tenant_id =% envelope.tenant_id(),
-                        trace_id =% envelope.trace_id(),
-                        event_source_id =% envelope.event_source_id(),
-                        retry_count =% envelope.retry_count(),
-                        created_time = format_iso8601(envelope.created_time()),
-                        last_updated_time = format_iso8601(envelope.last_updated_time()),
-                    );
-
-                    (span, envelope)
-                })
-            })
+            res.map_err(ConsumerError::from)
+                .and_then(move |msg| Self::deserialize_message(&msg))
         })
     }
 
+    /// Like `stream`, but each message is accompanied by its position in the
+    /// topic. A message which couldn't be deserialized still has a position.
+    fn stream_with_positions(
+        &self,
+    ) -> impl Stream<
+        Item = Result<
+            (
+                MessagePosition,
+                Result<(tracing::Span, Envelope<T>), ConsumerError>,
+            ),
+            ConsumerError,
+        >,
+    > + '_ {
+        self.consumer
+            .stream()
+            .map(move |res| -> Result<_, ConsumerError> {
+                let msg = res?;
+                let position = MessagePosition {
+                    topic: msg.topic().to_string(),
+                    partition: msg.partition(),
+                    offset: msg.offset(),
+                };
+
+                Ok((position, Self::deserialize_message(&msg)))
+            })
+    }
+
+    fn deserialize_message<M: Message>(
+        msg: &M,
+    ) -> Result<(tracing::Span, Envelope<T>), ConsumerError> {
+        let envelope: Envelope<T> =
+            Envelope::deserialize(msg.payload().ok_or(ConsumerError::PayloadAbsent)?)?;
+
+        let span = tracing::span!(
+            target: "stream_processor",
+            tracing::Level::INFO,
+            "envelope_span",
+            tenant_id =% envelope.tenant_id(),
+            trace_id =% envelope.trace_id(),
+            event_source_id =% envelope.event_source_id(),
+            retry_count =% envelope.retry_count(),
+            created_time = format_iso8601(envelope.created_time()),
+            last_updated_time = format_iso8601(envelope.last_updated_time()),
+        );
+
+        Ok((span, envelope))
+    }
+
     #[tracing::instrument(skip(self), err)]
     pub fn commit(&self) -> Result<(), CommitError> {
         Ok(self.consumer.commit_consumer_state(CommitMode::Sync)?)
     }
 }
 
+/// A message consumed from a retry topic.
+#[derive(Debug, Clone)]
+pub struct RetryMessage {
+    /// The message, exactly as it was consumed
+    pub payload: Bytes,
+    /// Why the message was retried, if its producer said so
+    pub retry_reason: Option<String>,
+}
+
 pub struct BytesConsumer {
     consumer: StreamConsumer,
-    delay_ms: u64,
 }
 
 impl BytesConsumer {
@@ -378,6 +508,7 @@ impl BytesConsumer {
             config.sasl_username,
             config.sasl_password,
             config.consumer_group_name,
+            "latest",
         )?;
 
         // the .subscribe(..) call must be fully-qualified here because the
@@ -386,57 +517,78 @@ impl BytesConsumer {
             return Err(ConfigurationError::SubscriptionFailed(e));
         }
 
-        Ok(Self {
-            consumer,
-            delay_ms: config.delay_ms,
-        })
+        Ok(Self { consumer })
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn stream(&self) -> impl Stream<Item = Result<Bytes, ConsumerError>> + '_ {
-        self.consumer.stream().then(move |res| async move {
-            match res {
-                Ok(msg) => {
-                    let timestamp = msg.timestamp();
-                    if let Some(millis_i64) = timestamp.to_millis() {
-                        let millis = if millis_i64 < 0 {
-                            0
-                        } else {
-                            millis_i64.unsigned_abs()
-                        };
-
-                        let message_ts = SystemTime::UNIX_EPOCH + Duration::from_millis(millis);
-                        let target = message_ts + Duration::from_millis(self.delay_ms);
-
-                        // If the current time is less than the target time we
-                        // delay for the duration between the current time and
-                        // the target time. In other words, if the target time
-                        // has elapsed we continue without delay, otherwise we
-                        // wait long enough for it to have elapsed and then
-                        // continue.
-                        if let Err(e) = target.elapsed() {
-                            let duration = e.duration();
-
-                            tracing::debug!(
-                                message = "delaying kafka message consumption",
-                                kafka_timestamp = format_iso8601(message_ts),
-                                delay_ms =% self.delay_ms,
-                                delay_duration_ms =% duration.as_millis(),
-                            );
-
-                            tokio::time::sleep(duration).await;
-                        }
-                    } else {
-                        tracing::error!("kafka message timestamp unavailable");
-                    }
+    pub fn stream(&self) -> impl Stream<Item = Result<RetryMessage, ConsumerError>> + '_ {
+        self.consumer
+            .stream()
+            .map(move |res| -> Result<RetryMessage, ConsumerError> {
+                match res {
+                    Ok(msg) => {
+                        let mut buf = BytesMut::with_capacity(msg.payload_len());
+                        buf.extend_from_slice(msg.payload().ok_or(ConsumerError::PayloadAbsent)?);
+
+                        let retry_reason = msg.headers().and_then(|headers| {
+                            (0..headers.count())
+                                .filter_map(|idx| headers.get(idx))
+                                .find(|(name, _)| *name == RETRY_REASON_HEADER)
+                                .map(|(_, value)| String::from_utf8_lossy(value).into_owned())
+                        });
 
-                    let mut buf = BytesMut::with_capacity(msg.payload_len());
-                    buf.extend_from_slice(msg.payload().ok_or(ConsumerError::PayloadAbsent)?);
-                    Ok(buf.freeze())
+                        Ok(RetryMessage {
+                            payload: buf.freeze(),
+                            retry_reason,
+                        })
+                    }
+                    Err(err) => Err(ConsumerError::from(err)),
                 }
-                Err(err) => Err(ConsumerError::from(err)),
-            }
-        })
+            })
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub fn commit(&self) -> Result<(), CommitError> {
+        Ok(self.consumer.commit_consumer_state(CommitMode::Sync)?)
+    }
+}
+
+/// Consumes the messages in a dead-letter topic. Unlike the other consumers,
+/// a new consumer group starts from the beginning of the topic, so that
+/// nothing which was dead-lettered before it started is missed.
+pub struct DeadLetterConsumer {
+    consumer: StreamConsumer,
+}
+
+impl DeadLetterConsumer {
+    pub fn new(config: DeadLetterConsumerConfig) -> Result<Self, ConfigurationError> {
+        let consumer = consumer(
+            config.bootstrap_servers,
+            config.sasl_username,
+            config.sasl_password,
+            config.consumer_group_name,
+            "earliest",
+        )?;
+
+        // the .subscribe(..) call must be fully-qualified here because the
+        // Consumer name is shadowed in this crate
+        if let Err(e) = rdkafka::consumer::Consumer::subscribe(&consumer, &[&config.topic]) {
+            return Err(ConfigurationError::SubscriptionFailed(e));
+        }
+
+        Ok(Self { consumer })
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn stream(&self) -> impl Stream<Item = Result<DeadLetter, ConsumerError>> + '_ {
+        self.consumer
+            .stream()
+            .map(move |res| -> Result<DeadLetter, ConsumerError> {
+                let msg = res?;
+                Ok(DeadLetter::deserialize(
+                    msg.payload().ok_or(ConsumerError::PayloadAbsent)?,
+                )?)
+            })
     }
 
     #[tracing::instrument(skip(self), err)]
@@ -463,6 +615,53 @@ pub enum StreamProcessorError {
 
     #[error("event handler error {0}")]
     EventHandlerError(String),
+
+    #[error("transaction error {0}")]
+    TransactionError(KafkaError),
+
+    #[error("consumer group metadata unavailable")]
+    ConsumerGroupMetadataUnavailable,
+}
+
+/// Where a consumed message is in its topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MessagePosition {
+    topic: String,
+    partition: i32,
+    offset: i64,
+}
+
+/// The range of offsets consumed from each partition in a transaction
+#[derive(Debug, Default)]
+struct BatchPositions {
+    offsets: BTreeMap<(String, i32), (i64, i64)>,
+}
+
+impl BatchPositions {
+    fn add(&mut self, position: &MessagePosition) {
+        self.offsets
+            .entry((position.topic.clone(), position.partition))
+            .and_modify(|(first, last)| {
+                *first = (*first).min(position.offset);
+                *last = (*last).max(position.offset);
+            })
+            .or_insert((position.offset, position.offset));
+    }
+
+    /// The offsets to commit, i.e. of the next message to consume from each
+    /// partition
+    fn next_offsets(&self) -> KafkaResult<TopicPartitionList> {
+        let mut offsets = TopicPartitionList::new();
+        for ((topic, partition), (_, last)) in self.offsets.iter() {
+            offsets.add_partition_offset(topic, *partition, Offset::Offset(last + 1))?;
+        }
+        Ok(offsets)
+    }
+}
+
+struct TransactionSettings {
+    timeout: Duration,
+    max_batch_size: usize,
 }
 
 /// A stream processor consumes data from a topic, does things with the data,
@@ -475,6 +674,7 @@ where
 {
     consumer: Consumer<C>,
     producer: Producer<P>,
+    transactions: Option<TransactionSettings>,
 }
 
 impl<C, P> StreamProcessor<C, P>
@@ -489,6 +689,38 @@ where
         Ok(StreamProcessor {
             consumer: Consumer::new(consumer_config)?,
             producer: Producer::new(producer_config)?,
+            transactions: None,
+        })
+    }
+
+    /// Construct a StreamProcessor which processes each message exactly once.
+    /// Consumed messages are processed in batches, and the messages produced
+    /// for a batch are published in a Kafka transaction along with the
+    /// batch's consumer offsets. If the processor crashes part way through a
+    /// batch the transaction is aborted, so none of the batch's messages are
+    /// visible to downstream consumers (which read committed messages only)
+    /// and the whole batch is consumed again.
+    pub fn new_transactional(
+        consumer_config: ConsumerConfig,
+        producer_config: ProducerConfig,
+        transaction_config: TransactionConfig,
+    ) -> Result<StreamProcessor<C, P>, ConfigurationError> {
+        Ok(StreamProcessor {
+            consumer: Consumer::new(consumer_config)?,
+            producer: Producer {
+                producer: transactional_producer(
+                    producer_config.bootstrap_servers,
+                    producer_config.sasl_username,
+                    producer_config.sasl_password,
+                    &transaction_config,
+                )?,
+                topic: producer_config.topic,
+                _t: PhantomData,
+            },
+            transactions: Some(TransactionSettings {
+                timeout: Duration::from_millis(transaction_config.transaction_timeout_ms),
+                max_batch_size: transaction_config.max_batch_size.max(1),
+            }),
         })
     }
 
@@ -510,6 +742,13 @@ where
     /// subsequent call to your event_handler returns a non-empty stream causing
     /// a later offset to be committed.
     ///
+    /// In transactional mode (see `new_transactional`) steps 4 and 5 happen
+    /// atomically for a batch of messages. Each message's offset is committed
+    /// regardless of what the event_handler returns. If a transaction fails
+    /// the stream yields the error and the batch is consumed again. If the
+    /// batch can't be consumed again (e.g. the producer was fenced off by
+    /// another instance) the stream yields the error and ends.
+    ///
     /// N.B.: You must consume this stream serially to ensure proper commit
     /// ordering. Consuming this stream concurrently could result in data loss.
     #[tracing::instrument(skip(self, event_handler))]
@@ -522,74 +761,436 @@ where
         R: Stream<Item = Result<Envelope<P>, E>> + 'a,
         E: Into<StreamProcessorError> + 'a,
     {
-        self.consumer
-            .stream()
-            .map_err(StreamProcessorError::from)
-            .map(event_handler)
-            .flatten()
-            .then(move |result| async move {
-                match result {
-                    Ok(msg) => {
-                        self.producer
-                            .clone()
-                            .send(msg)
-                            .map_err(StreamProcessorError::from)
-                            .await
+        if let Some(ref transactions) = self.transactions {
+            return Either::Left(self.transactional_stream(transactions, event_handler));
+        }
+
+        Either::Right(
+            self.consumer
+                .stream()
+                .map_err(StreamProcessorError::from)
+                .map(event_handler)
+                .flatten()
+                .then(move |result| async move {
+                    match result {
+                        Ok(msg) => {
+                            self.producer
+                                .clone()
+                                .send(msg)
+                                .map_err(StreamProcessorError::from)
+                                .await
+                        }
+                        Err(e) => Err(e.into()),
+                    }
+                })
+                .then(move |result| async { result.and_then(|_| Ok(self.consumer.commit()?)) }),
+        )
+    }
+
+    fn transactional_stream<'a, F, R, E>(
+        &'a self,
+        transactions: &'a TransactionSettings,
+        event_handler: F,
+    ) -> impl Stream<Item = Result<(), StreamProcessorError>> + 'a
+    where
+        F: FnMut(Result<(tracing::Span, Envelope<C>), StreamProcessorError>) -> R + 'a,
+        R: Stream<Item = Result<Envelope<P>, E>> + 'a,
+        E: Into<StreamProcessorError> + 'a,
+    {
+        // ready_chunks batches up whatever has already been consumed, so a
+        // quiet topic doesn't wait for a batch to fill up
+        let batches = Box::pin(
+            self.consumer
+                .stream_with_positions()
+                .ready_chunks(transactions.max_batch_size),
+        );
+
+        futures::stream::unfold(
+            (batches, event_handler, false),
+            move |(mut batches, mut event_handler, finished)| async move {
+                if finished {
+                    return None;
+                }
+
+                let batch = batches.next().await?;
+                let (results, finished) = self
+                    .process_transaction(transactions, batch, &mut event_handler)
+                    .await;
+
+                Some((
+                    futures::stream::iter(results),
+                    (batches, event_handler, finished),
+                ))
+            },
+        )
+        .flatten()
+    }
+
+    /// Process a batch in a single transaction. Returns a result for each
+    /// message produced or error encountered, and whether the stream must end.
+    #[tracing::instrument(skip(self, transactions, batch, event_handler), fields(batch_size = batch.len()))]
+    async fn process_transaction<F, R, E>(
+        &self,
+        transactions: &TransactionSettings,
+        batch: Vec<
+            Result<
+                (
+                    MessagePosition,
+                    Result<(tracing::Span, Envelope<C>), ConsumerError>,
+                ),
+                ConsumerError,
+            >,
+        >,
+        event_handler: &mut F,
+    ) -> (Vec<Result<(), StreamProcessorError>>, bool)
+    where
+        F: FnMut(Result<(tracing::Span, Envelope<C>), StreamProcessorError>) -> R,
+        R: Stream<Item = Result<Envelope<P>, E>>,
+        E: Into<StreamProcessorError>,
+    {
+        let mut results = vec![];
+        let mut positions = BatchPositions::default();
+        let mut produced = 0usize;
+
+        let transaction = async {
+            self.producer
+                .producer
+                .begin_transaction()
+                .map_err(StreamProcessorError::TransactionError)?;
+
+            for consumed in batch {
+                let (position, event) = match consumed {
+                    Ok(consumed) => consumed,
+                    Err(e) => {
+                        results.push(Err(e.into()));
+                        continue;
+                    }
+                };
+                positions.add(&position);
+
+                let mut messages = vec![];
+                let outputs: Vec<Result<Envelope<P>, E>> =
+                    event_handler(event.map_err(StreamProcessorError::from))
+                        .collect()
+                        .await;
+
+                for output in outputs {
+                    match output {
+                        Ok(msg) => messages.push(msg),
+                        Err(e) => results.push(Err(e.into())),
                     }
-                    Err(e) => Err(e.into()),
                 }
+
+                // Each message's outputs are published before the next
+                // message is handled, so they're in the same order as the
+                // messages which produced them.
+                let sent = futures::future::join_all(
+                    messages.into_iter().map(|msg| self.producer.send(msg)),
+                )
+                .await;
+                for result in sent {
+                    result?;
+                    produced += 1;
+                }
+            }
+
+            let offsets = positions
+                .next_offsets()
+                .map_err(StreamProcessorError::TransactionError)?;
+            let group_metadata =
+                rdkafka::consumer::Consumer::group_metadata(&self.consumer.consumer)
+                    .ok_or(StreamProcessorError::ConsumerGroupMetadataUnavailable)?;
+
+            let producer = self.producer.producer.clone();
+            let timeout = transactions.timeout;
+            blocking(move || {
+                producer.send_offsets_to_transaction(&offsets, &group_metadata, timeout)?;
+                producer.commit_transaction(timeout)
             })
-            .then(move |result| async { result.and_then(|_| Ok(self.consumer.commit()?)) })
+            .await
+            .map_err(StreamProcessorError::TransactionError)
+        }
+        .await;
+
+        match transaction {
+            Ok(()) => {
+                tracing::debug!(message = "committed transaction", produced = produced,);
+                results.extend((0..produced).map(|_| Ok(())));
+                (results, false)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    message = "aborting transaction",
+                    error =% e,
+                );
+                let finished = self.abort_transaction(transactions, &positions).await;
+                results.push(Err(e));
+                (results, finished)
+            }
+        }
+    }
+
+    /// Abort the open transaction and rewind the consumer to the start of the
+    /// batch. Returns true if that wasn't possible, in which case the batch
+    /// would be lost if the stream continued.
+    async fn abort_transaction(
+        &self,
+        transactions: &TransactionSettings,
+        positions: &BatchPositions,
+    ) -> bool {
+        let producer = self.producer.producer.clone();
+        let timeout = transactions.timeout;
+        if let Err(e) = blocking(move || producer.abort_transaction(timeout)).await {
+            tracing::error!(
+                message = "failed to abort transaction",
+                error =% e,
+            );
+            return true;
+        }
+
+        for ((topic, partition), (first, _)) in positions.offsets.iter() {
+            if let Err(e) = rdkafka::consumer::Consumer::seek(
+                &self.consumer.consumer,
+                topic,
+                *partition,
+                Offset::Offset(*first),
+                timeout,
+            ) {
+                tracing::error!(
+                    message = "failed to rewind consumer",
+                    topic =% topic,
+                    partition = partition,
+                    offset = first,
+                    error =% e,
+                );
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Run a blocking librdkafka call without blocking the async runtime
+async fn blocking<T, F>(f: F) -> KafkaResult<T>
+where
+    F: FnOnce() -> KafkaResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .expect("blocking kafka call panicked")
+}
+
+/// How long a RetryProcessor waits before retrying a message, and how many
+/// times it retries a message before giving up on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub initial_delay: Duration,
+    pub maximum_delay: Duration,
+    pub maximum_retries: u32,
+}
+
+impl RetryPolicy {
+    /// The delay before the given retry (the first retry is 1). It doubles
+    /// with each retry, up to `maximum_delay`.
+    pub fn delay(&self, retry_count: u32) -> Duration {
+        self.initial_delay
+            .checked_mul(2u32.saturating_pow(retry_count.saturating_sub(1)))
+            .unwrap_or(self.maximum_delay)
+            .min(self.maximum_delay)
     }
 }
 
+impl From<&RetryConsumerConfig> for RetryPolicy {
+    fn from(config: &RetryConsumerConfig) -> Self {
+        RetryPolicy {
+            initial_delay: Duration::from_millis(config.delay_ms),
+            maximum_delay: Duration::from_millis(config.maximum_delay_ms),
+            maximum_retries: config.maximum_retries,
+        }
+    }
+}
+
+/// What a RetryProcessor did with a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryOutcome {
+    /// The message was sent back to the service's main topic
+    Retried,
+    /// The message was sent to the service's dead-letter topic
+    DeadLettered,
+}
+
 /// A RetryProcessor handles messages on a service's retry topic, imposes a
-/// configurable delay, and then sends the message back to the service's main
-/// topic. The RetryProcessor does no serialization or deserialization, it just
-/// passes the bytes along. This is to prevent a deserialization error, which
-/// may have broken the main service, from also breaking the retry service.
+/// delay which grows exponentially with the message's retry count, and then
+/// sends the message back to the service's main topic. A message which has
+/// exhausted its retries, or which isn't a valid Envelope at all, is sent to
+/// the service's dead-letter topic instead.
+///
+/// The RetryProcessor only deserializes the Envelope's metadata, it passes the
+/// bytes themselves along untouched. This is to prevent a deserialization
+/// error, which may have broken the main service, from also breaking the retry
+/// service.
 pub struct RetryProcessor {
     consumer: BytesConsumer,
     producer: BytesProducer,
+    dead_letter_producer: BytesProducer,
+    retry_policy: RetryPolicy,
 }
 
 impl RetryProcessor {
     pub fn new(
         consumer_config: RetryConsumerConfig,
         producer_config: ProducerConfig,
+        dead_letter_producer_config: DeadLetterProducerConfig,
     ) -> Result<RetryProcessor, ConfigurationError> {
         Ok(RetryProcessor {
+            retry_policy: RetryPolicy::from(&consumer_config),
             consumer: BytesConsumer::new(consumer_config)?,
             producer: BytesProducer::new(producer_config)?,
+            dead_letter_producer: BytesProducer::new(dead_letter_producer_config.into())?,
         })
     }
 
     /// Constructs a stream which does the following things:
     ///
     ///  1. Consumes a message from Kafka
-    ///  2. Inspects the message timestamp, and if necessary pauses for long
-    ///  enough to impose this processor's configured delay
-    ///  3. Publishes the message to Kafka
-    ///  4. Commits the message offset
+    ///  2. Deserializes the message's Envelope metadata, and if it can't be
+    ///  deserialized or the message has exhausted its retries publishes the
+    ///  message to the dead-letter topic
+    ///  3. Otherwise inspects the Envelope's last_updated_time, and if
+    ///  necessary pauses for long enough to impose the retry policy's delay
+    ///  4. Publishes the message to Kafka
+    ///  5. Commits the message offset
     ///
     /// N.B.: You must consume this stream serially to ensure proper commit
     /// ordering. Consuming this stream concurrently could result in data loss.
     #[tracing::instrument(skip(self))]
-    pub fn stream<'a>(&'a self) -> impl Stream<Item = Result<(), StreamProcessorError>> + '_ {
+    pub fn stream<'a>(
+        &'a self,
+    ) -> impl Stream<Item = Result<RetryOutcome, StreamProcessorError>> + '_ {
         self.consumer
             .stream()
             .map_err(StreamProcessorError::from)
             .then(move |result| async move {
-                if let Ok(msg) = result {
-                    self.producer
-                        .clone()
-                        .send(msg)
-                        .map_err(StreamProcessorError::from)
-                        .await
-                } else {
-                    result.map(|_| ())
+                match result {
+                    Ok(msg) => self.process(msg).await,
+                    Err(e) => Err(e),
                 }
             })
-            .then(move |result| async { result.and_then(|_| Ok(self.consumer.commit()?)) })
+            .then(move |result| async {
+                result.and_then(|outcome| {
+                    self.consumer.commit()?;
+                    Ok(outcome)
+                })
+            })
+    }
+
+    async fn process(&self, msg: RetryMessage) -> Result<RetryOutcome, StreamProcessorError> {
+        let RetryMessage {
+            payload,
+            retry_reason,
+        } = msg;
+
+        let metadata = match EnvelopeMetadata::deserialize(payload.clone()) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                return self
+                    .dead_letter(payload, format!("failed to deserialize envelope {e}"), 0)
+                    .await;
+            }
+        };
+
+        let retry_count = metadata.retry_count();
+        if retry_count > self.retry_policy.maximum_retries {
+            let reason = match retry_reason {
+                Some(retry_reason) => format!(
+                    "exceeded the maximum of {} retries, last retried because {}",
+                    self.retry_policy.maximum_retries, retry_reason
+                ),
+                None => format!(
+                    "exceeded the maximum of {} retries",
+                    self.retry_policy.maximum_retries
+                ),
+            };
+
+            return self.dead_letter(payload, reason, retry_count).await;
+        }
+
+        let delay = self.retry_policy.delay(retry_count);
+        let target = metadata.last_updated_time() + delay;
+
+        // If the current time is less than the target time we delay for the
+        // duration between the current time and the target time. In other
+        // words, if the target time has elapsed we continue without delay,
+        // otherwise we wait long enough for it to have elapsed and then
+        // continue.
+        if let Err(e) = target.elapsed() {
+            let duration = e.duration();
+
+            tracing::debug!(
+                message = "delaying retry",
+                tenant_id =% metadata.tenant_id(),
+                trace_id =% metadata.trace_id(),
+                retry_count = retry_count,
+                last_updated_time = format_iso8601(metadata.last_updated_time()),
+                delay_ms =% delay.as_millis(),
+                delay_duration_ms =% duration.as_millis(),
+            );
+
+            tokio::time::sleep(duration).await;
+        }
+
+        self.producer.send(payload).await?;
+
+        Ok(RetryOutcome::Retried)
+    }
+
+    async fn dead_letter(
+        &self,
+        payload: Bytes,
+        reason: String,
+        retry_count: u32,
+    ) -> Result<RetryOutcome, StreamProcessorError> {
+        tracing::warn!(
+            message = "sending message to dead-letter topic",
+            dead_letter_topic =% self.dead_letter_producer.topic(),
+            retry_count = retry_count,
+            reason =% reason,
+        );
+
+        let dead_letter = DeadLetter::new(
+            self.producer.topic().to_string(),
+            payload,
+            reason,
+            retry_count,
+        );
+
+        self.dead_letter_producer
+            .send(dead_letter.serialize().map_err(ProducerError::from)?)
+            .await?;
+
+        Ok(RetryOutcome::DeadLettered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_up_to_maximum() {
+        let retry_policy = RetryPolicy {
+            initial_delay: Duration::from_millis(500),
+            maximum_delay: Duration::from_secs(5),
+            maximum_retries: 10,
+        };
+
+        assert_eq!(retry_policy.delay(0), Duration::from_millis(500));
+        assert_eq!(retry_policy.delay(1), Duration::from_millis(500));
+        assert_eq!(retry_policy.delay(2), Duration::from_secs(1));
+        assert_eq!(retry_policy.delay(4), Duration::from_secs(4));
+        assert_eq!(retry_policy.delay(5), Duration::from_secs(5));
+        assert_eq!(retry_policy.delay(u32::MAX), Duration::from_secs(5));
     }
 }
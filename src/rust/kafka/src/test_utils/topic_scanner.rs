@@ -99,6 +99,7 @@ where
                 uuid::Uuid::new_v4(),
             ),
             topic: consumer_config.topic,
+            auto_offset_reset: consumer_config.auto_offset_reset,
         };
 
         Self {
@@ -0,0 +1,225 @@
+#![cfg(feature = "integration_tests")]
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{
+    pin_mut,
+    StreamExt,
+};
+use kafka::{
+    config::{
+        ConsumerConfig,
+        ProducerConfig,
+        TransactionConfig,
+    },
+    Consumer,
+    Producer,
+    StreamProcessor,
+    StreamProcessorError,
+};
+use rdkafka::{
+    admin::{
+        AdminClient,
+        AdminOptions,
+        NewTopic,
+        TopicReplication,
+    },
+    client::DefaultClientContext,
+    config::ClientConfig,
+};
+use rust_proto::graplinc::grapl::pipeline::v1beta1::{
+    Envelope,
+    RawLog,
+};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+const MESSAGE_COUNT: usize = 10;
+
+const MAX_BATCH_SIZE: usize = 4;
+
+/// The first processor is killed while handling this message (counting from
+/// 1), which is in the middle of its second batch.
+const KILL_AT: usize = 6;
+
+async fn create_topics(bootstrap_servers: &str, topics: &[&str]) -> eyre::Result<()> {
+    let admin_client: AdminClient<DefaultClientContext> = ClientConfig::new()
+        .set("bootstrap.servers", bootstrap_servers)
+        .create()?;
+
+    let new_topics: Vec<NewTopic> = topics
+        .iter()
+        .map(|topic| NewTopic::new(topic, 1, TopicReplication::Fixed(1)))
+        .collect();
+
+    for result in admin_client
+        .create_topics(&new_topics, &AdminOptions::new())
+        .await?
+    {
+        result.map_err(|(topic, e)| eyre::eyre!("failed to create topic {topic}: {e}"))?;
+    }
+
+    Ok(())
+}
+
+fn stream_processor(
+    consumer_config: ConsumerConfig,
+    producer_config: ProducerConfig,
+    transactional_id: &str,
+) -> eyre::Result<StreamProcessor<RawLog, RawLog>> {
+    Ok(StreamProcessor::new_transactional(
+        consumer_config,
+        producer_config,
+        TransactionConfig {
+            transactional_id: transactional_id.to_string(),
+            transaction_timeout_ms: 60_000,
+            max_batch_size: MAX_BATCH_SIZE,
+        },
+    )?)
+}
+
+fn forward(
+    event: Result<(tracing::Span, Envelope<RawLog>), StreamProcessorError>,
+) -> Result<Envelope<RawLog>, StreamProcessorError> {
+    let (_, envelope) = event?;
+    Ok(Envelope::new(
+        envelope.tenant_id(),
+        envelope.trace_id(),
+        envelope.event_source_id(),
+        envelope.inner_message(),
+    ))
+}
+
+/// Consume every committed message in the output topic, waiting a while
+/// after the expected number have arrived to catch any duplicates.
+async fn consume_outputs(consumer_config: ConsumerConfig) -> eyre::Result<Vec<Bytes>> {
+    let consumer: Consumer<RawLog> = Consumer::new(consumer_config)?;
+    let stream = consumer.stream();
+    pin_mut!(stream);
+
+    let mut outputs = vec![];
+    loop {
+        let timeout = if outputs.len() < MESSAGE_COUNT {
+            Duration::from_secs(60)
+        } else {
+            Duration::from_secs(5)
+        };
+
+        match tokio::time::timeout(timeout, stream.next()).await {
+            Ok(Some(result)) => {
+                let (_, envelope) = result?;
+                outputs.push(envelope.inner_message().log_event());
+            }
+            Ok(None) | Err(_) => break,
+        }
+    }
+
+    Ok(outputs)
+}
+
+#[tokio::test]
+async fn test_transactional_stream_processor_survives_being_killed_mid_batch() -> eyre::Result<()> {
+    let test_id = Uuid::new_v4();
+    let input_topic = format!("kafka-transactions-test-input-{test_id}");
+    let output_topic = format!("kafka-transactions-test-output-{test_id}");
+    let transactional_id = format!("kafka-transactions-test-{test_id}");
+
+    let base_config = ConsumerConfig::with_topic(&input_topic);
+    create_topics(
+        &base_config.bootstrap_servers,
+        &[&input_topic, &output_topic],
+    )
+    .await?;
+
+    // Both processors belong to the same consumer group, so the second picks
+    // up from the first's last committed transaction.
+    let input_consumer_config = ConsumerConfig {
+        consumer_group_name: format!("kafka-transactions-test-{test_id}"),
+        auto_offset_reset: "earliest".to_string(),
+        ..base_config.clone()
+    };
+    let output_consumer_config = ConsumerConfig {
+        consumer_group_name: format!("kafka-transactions-test-verifier-{test_id}"),
+        topic: output_topic.clone(),
+        auto_offset_reset: "earliest".to_string(),
+        ..base_config.clone()
+    };
+    let output_producer_config = ProducerConfig {
+        topic: output_topic.clone(),
+        ..ProducerConfig::from(base_config.clone())
+    };
+
+    let tenant_id = Uuid::new_v4();
+    let mut inputs: Vec<Bytes> = (0..MESSAGE_COUNT)
+        .map(|i| Bytes::from(format!("message {i}")))
+        .collect();
+
+    let input_producer: Producer<RawLog> = Producer::new(ProducerConfig::from(base_config))?;
+    for input in inputs.iter() {
+        input_producer
+            .send(Envelope::new(
+                tenant_id,
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                RawLog::new(input.clone()),
+            ))
+            .await?;
+    }
+
+    // The first processor hangs while handling a message in the middle of a
+    // batch, after the batch's earlier messages have been published in the
+    // open transaction, and is then dropped without committing it.
+    {
+        let processor = stream_processor(
+            input_consumer_config.clone(),
+            output_producer_config.clone(),
+            &transactional_id,
+        )?;
+
+        let (killed_tx, killed_rx) = oneshot::channel();
+        let mut killed_tx = Some(killed_tx);
+        let mut handled = 0;
+
+        let stream = processor.stream::<_, _, StreamProcessorError>(move |event| {
+            handled += 1;
+            if handled == KILL_AT {
+                if let Some(killed_tx) = killed_tx.take() {
+                    let _ = killed_tx.send(());
+                }
+                futures::stream::pending().left_stream()
+            } else {
+                futures::stream::once(futures::future::ready(forward(event))).right_stream()
+            }
+        });
+
+        tokio::select! {
+            _ = stream.for_each(|_| async {}) => eyre::bail!("stream processor stopped"),
+            _ = killed_rx => (),
+        }
+    }
+
+    // The second processor uses the same transactional.id, which aborts the
+    // first processor's open transaction.
+    let processor = stream_processor(
+        input_consumer_config,
+        output_producer_config,
+        &transactional_id,
+    )?;
+    let stream = processor.stream::<_, _, StreamProcessorError>(|event| {
+        futures::stream::once(futures::future::ready(forward(event)))
+    });
+
+    let mut outputs = tokio::select! {
+        _ = stream.for_each(|_| async {}) => eyre::bail!("stream processor stopped"),
+        outputs = consume_outputs(output_consumer_config) => outputs?,
+    };
+
+    // Every message was processed exactly once, despite the first processor
+    // having published some of the second batch's outputs before it died.
+    inputs.sort();
+    outputs.sort();
+    assert_eq!(outputs, inputs);
+
+    Ok(())
+}
@@ -0,0 +1,353 @@
+//! An example configuration file:
+//!
+//! ```json
+//! {
+//!   "syslog_listeners": [
+//!     {
+//!       "bind_address": "0.0.0.0:6514",
+//!       "protocol": "tls",
+//!       "tls": {
+//!         "certificate_file": "/etc/log-collector/cert.pem",
+//!         "private_key_file": "/etc/log-collector/key.pem"
+//!       },
+//!       "tenant_id": "6a1e5a8c-3f64-4bc3-9bd3-7d3f0c0bd1b7",
+//!       "event_source_id": "0c2e3b4a-7e0a-4c53-a0c9-3c3b4e6b7e11"
+//!     }
+//!   ],
+//!   "http_listener": {
+//!     "bind_address": "0.0.0.0:8080",
+//!     "api_keys": [
+//!       {
+//!         "api_key": "...",
+//!         "tenant_id": "6a1e5a8c-3f64-4bc3-9bd3-7d3f0c0bd1b7",
+//!         "event_source_id": "d7b1f0f5-2a4e-4a8b-8e0e-7f3a8e3c2b90"
+//!       }
+//!     ]
+//!   },
+//!   "forwarder": {
+//!     "batch_size": 100,
+//!     "flush_interval": "250ms",
+//!     "max_in_flight": 16,
+//!     "max_request_size": 3145728,
+//!     "queue_capacity": 10000,
+//!     "max_retries": 5,
+//!     "initial_retry_delay": "100ms",
+//!     "maximum_retry_delay": "10s"
+//!   }
+//! }
+//! ```
+
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    time::Duration,
+};
+
+use clap::Parser;
+use figment::{
+    providers::{
+        Env,
+        Format,
+        Json,
+    },
+    Figment,
+};
+use secrecy::{
+    ExposeSecret,
+    SecretString,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// Command-line / environment arguments for the log-collector binary. The
+/// bulk of the configuration (listeners and API keys) is structured, so it
+/// lives in a JSON file whose path is given here.
+#[derive(Parser, Clone, Debug)]
+pub struct LogCollectorArgs {
+    /// Path to a JSON file containing a `LogCollectorConfig`. Any value in
+    /// the file may be overridden by an environment variable prefixed with
+    /// `LOG_COLLECTOR_`, using `__` to separate nested keys (e.g.
+    /// `LOG_COLLECTOR_FORWARDER__BATCH_SIZE=500`).
+    #[clap(long, env)]
+    pub log_collector_config_file: PathBuf,
+}
+
+impl LogCollectorArgs {
+    /// An alias for clap::parse, so that consumers don't need to
+    /// declare a dependency on clap
+    pub fn from_env_vars() -> Self {
+        Self::parse()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LogCollectorConfig {
+    /// Syslog listeners. Each listener is bound to exactly one
+    /// `(tenant_id, event_source_id)` pair; run one listener per event source.
+    #[serde(default)]
+    pub syslog_listeners: Vec<SyslogListenerConfig>,
+
+    /// The HTTP listener accepting newline-delimited JSON. Omit to disable.
+    #[serde(default)]
+    pub http_listener: Option<HttpListenerConfig>,
+
+    #[serde(default)]
+    pub forwarder: ForwarderConfig,
+}
+
+impl LogCollectorConfig {
+    pub fn load(args: &LogCollectorArgs) -> Result<Self, figment::Error> {
+        Figment::new()
+            .merge(Json::file(&args.log_collector_config_file))
+            .merge(Env::prefixed("LOG_COLLECTOR_").split("__"))
+            .extract()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.syslog_listeners.is_empty() && self.http_listener.is_none() {
+            return Err("at least one syslog or http listener must be configured".to_string());
+        }
+
+        for listener in self.syslog_listeners.iter() {
+            if let SyslogProtocol::Tls = listener.protocol {
+                if listener.tls.is_none() {
+                    return Err(format!(
+                        "syslog listener {} uses tls but has no tls configuration",
+                        listener.bind_address
+                    ));
+                }
+            }
+
+            if listener.max_message_size == 0 {
+                return Err(format!(
+                    "syslog listener {} max_message_size must be greater than 0",
+                    listener.bind_address
+                ));
+            }
+        }
+
+        if let Some(ref http_listener) = self.http_listener {
+            if http_listener.api_keys.is_empty() {
+                return Err("http listener requires at least one api key".to_string());
+            }
+        }
+
+        if self.forwarder.batch_size == 0 {
+            return Err("forwarder batch_size must be greater than 0".to_string());
+        }
+
+        if self.forwarder.max_in_flight == 0 {
+            return Err("forwarder max_in_flight must be greater than 0".to_string());
+        }
+
+        if self.forwarder.max_request_size == 0 {
+            return Err("forwarder max_request_size must be greater than 0".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Where collected events are published to in pipeline-ingress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct Destination {
+    pub tenant_id: Uuid,
+    pub event_source_id: Uuid,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogProtocol {
+    /// RFC 5426, one message per datagram
+    Udp,
+    /// RFC 6587, octet-counting or LF-delimited framing
+    Tcp,
+    /// RFC 5425, octet-counting or LF-delimited framing inside TLS
+    Tls,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SyslogListenerConfig {
+    pub bind_address: SocketAddr,
+
+    pub protocol: SyslogProtocol,
+
+    /// Required when `protocol` is `tls`
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+
+    #[serde(flatten)]
+    pub destination: Destination,
+
+    /// Messages larger than this are dropped. For stream protocols an
+    /// oversized frame closes the connection, since we can't resynchronize.
+    #[serde(default = "default_max_message_size")]
+    pub max_message_size: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TlsConfig {
+    /// PEM-encoded certificate chain
+    pub certificate_file: PathBuf,
+    /// PEM-encoded PKCS#8 or RSA private key
+    pub private_key_file: PathBuf,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HttpListenerConfig {
+    pub bind_address: SocketAddr,
+
+    /// Each API key maps to the event source its requests are published to.
+    pub api_keys: Vec<ApiKeyConfig>,
+
+    /// Maximum size of a request body, in bytes
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
+}
+
+impl HttpListenerConfig {
+    /// Find the destination for an API key presented by a client. Every
+    /// configured key is compared so that the time taken doesn't reveal which
+    /// key (if any) matched.
+    pub fn destination_for(&self, presented: &str) -> Option<Destination> {
+        self.api_keys.iter().fold(None, |found, api_key| {
+            if constant_time_eq(
+                api_key.api_key.expose_secret().as_bytes(),
+                presented.as_bytes(),
+            ) {
+                Some(api_key.destination)
+            } else {
+                found
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ApiKeyConfig {
+    pub api_key: SecretString,
+
+    #[serde(flatten)]
+    pub destination: Destination,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ForwarderConfig {
+    /// Maximum number of events published together before the batch is
+    /// flushed
+    pub batch_size: usize,
+
+    /// Maximum time an event waits for its batch to fill up
+    #[serde(with = "humantime_serde")]
+    pub flush_interval: Duration,
+
+    /// Maximum number of concurrent `PublishRawLogs` requests
+    pub max_in_flight: usize,
+
+    /// Payload bytes per `PublishRawLogs` request, a batch larger than this
+    /// is split across several requests. Keep it below pipeline-ingress'
+    /// maximum gRPC message size.
+    pub max_request_size: usize,
+
+    /// Capacity of the queue between the listeners and the forwarder, in
+    /// submissions: a syslog message, or all the events of an HTTP request.
+    /// When it's full, listeners stop reading from their sockets.
+    pub queue_capacity: usize,
+
+    /// How many times to retry a request that failed transiently before
+    /// dropping its events
+    pub max_retries: u32,
+
+    /// Delay before the first retry, doubled on each subsequent retry
+    #[serde(with = "humantime_serde")]
+    pub initial_retry_delay: Duration,
+
+    /// Upper bound on the delay between retries
+    #[serde(with = "humantime_serde")]
+    pub maximum_retry_delay: Duration,
+}
+
+impl Default for ForwarderConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            flush_interval: Duration::from_millis(250),
+            max_in_flight: 16,
+            max_request_size: 3 * 1024 * 1024,
+            queue_capacity: 10_000,
+            max_retries: 5,
+            initial_retry_delay: Duration::from_millis(100),
+            maximum_retry_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+fn default_max_message_size() -> usize {
+    64 * 1024
+}
+
+fn default_max_body_size() -> usize {
+    8 * 1024 * 1024
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http_listener_config() -> HttpListenerConfig {
+        serde_json::from_str(
+            r#"{
+                "bind_address": "127.0.0.1:8080",
+                "api_keys": [
+                    {
+                        "api_key": "key-one",
+                        "tenant_id": "8f2b4b4c-1c1c-4b1c-9c1c-000000000001",
+                        "event_source_id": "8f2b4b4c-1c1c-4b1c-9c1c-000000000002"
+                    },
+                    {
+                        "api_key": "key-two",
+                        "tenant_id": "8f2b4b4c-1c1c-4b1c-9c1c-000000000003",
+                        "event_source_id": "8f2b4b4c-1c1c-4b1c-9c1c-000000000004"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid http listener config")
+    }
+
+    #[test]
+    fn api_key_maps_to_its_destination() {
+        let config = http_listener_config();
+
+        let destination = config.destination_for("key-two").expect("no destination");
+        assert_eq!(
+            destination.tenant_id,
+            Uuid::parse_str("8f2b4b4c-1c1c-4b1c-9c1c-000000000003").unwrap()
+        );
+        assert_eq!(
+            destination.event_source_id,
+            Uuid::parse_str("8f2b4b4c-1c1c-4b1c-9c1c-000000000004").unwrap()
+        );
+    }
+
+    #[test]
+    fn unknown_api_key_is_rejected() {
+        let config = http_listener_config();
+
+        assert_eq!(config.destination_for("key-three"), None);
+        assert_eq!(config.destination_for("key-on"), None);
+        assert_eq!(config.destination_for(""), None);
+    }
+}
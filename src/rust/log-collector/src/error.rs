@@ -0,0 +1,27 @@
+use grapl_tracing::SetupTracingError;
+use rust_proto::graplinc::grapl::api::client::ClientError;
+use thiserror::Error;
+
+use crate::forwarder::ForwarderClosed;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CollectorError {
+    #[error("invalid configuration {0}")]
+    Configuration(String),
+
+    #[error("failed to extract configuration {0}")]
+    Figment(#[from] figment::Error),
+
+    #[error("io error {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("gRPC client error {0}")]
+    Client(#[from] ClientError),
+
+    #[error(transparent)]
+    ForwarderClosed(#[from] ForwarderClosed),
+
+    #[error("failed to configure tracing {0}")]
+    SetupTracing(#[from] SetupTracingError),
+}
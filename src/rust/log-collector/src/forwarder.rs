@@ -0,0 +1,320 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use rust_proto::graplinc::grapl::api::{
+    client::ClientError,
+    pipeline_ingress::v1beta1::{
+        client::PipelineIngressClient,
+        PublishRawLogRequest,
+        PublishRawLogsRequest,
+    },
+    protocol::status::Code,
+};
+use tokio::sync::mpsc;
+
+use crate::config::{
+    Destination,
+    ForwarderConfig,
+};
+
+/// A single framed event, ready to be published to pipeline-ingress.
+#[derive(Debug, Clone)]
+pub struct CollectedEvent {
+    pub destination: Destination,
+    pub payload: Bytes,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("the forwarder has shut down")]
+pub struct ForwarderClosed;
+
+/// The listeners' handle on the forwarder. Cloning it is cheap. `send` and
+/// `send_all` wait when the forwarder's queue is full, which applies
+/// backpressure to the listener's socket.
+#[derive(Clone)]
+pub struct Forwarder {
+    sender: mpsc::Sender<Vec<CollectedEvent>>,
+}
+
+impl Forwarder {
+    pub fn new(
+        pipeline_ingress_client: PipelineIngressClient,
+        config: ForwarderConfig,
+    ) -> (Self, ForwarderTask) {
+        let (sender, receiver) = mpsc::channel(config.queue_capacity);
+        (
+            Forwarder { sender },
+            ForwarderTask {
+                pipeline_ingress_client,
+                config,
+                receiver,
+            },
+        )
+    }
+
+    pub async fn send(&self, event: CollectedEvent) -> Result<(), ForwarderClosed> {
+        self.send_all(vec![event]).await
+    }
+
+    /// Queue every event, or none of them if the forwarder has shut down.
+    pub async fn send_all(&self, events: Vec<CollectedEvent>) -> Result<(), ForwarderClosed> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        self.sender.send(events).await.map_err(|_| ForwarderClosed)
+    }
+}
+
+/// Drains the queue fed by the listeners, grouping events into batches which
+/// are flushed when `batch_size` events have accumulated or `flush_interval`
+/// has elapsed since the first event of the batch arrived, whichever comes
+/// first. A batch is published with as few `PublishRawLogs` requests as
+/// `max_request_size` allows, up to `max_in_flight` of them concurrently.
+///
+/// N.B.: the collector doesn't persist anything. A request which still fails
+/// after `max_retries` retries is logged and its events are dropped.
+pub struct ForwarderTask {
+    pipeline_ingress_client: PipelineIngressClient,
+    config: ForwarderConfig,
+    receiver: mpsc::Receiver<Vec<CollectedEvent>>,
+}
+
+impl ForwarderTask {
+    /// Runs until every `Forwarder` handle has been dropped and the queue has
+    /// been drained.
+    pub async fn run(mut self) {
+        while let Some(batch) = self.next_batch().await {
+            self.flush(batch).await;
+        }
+
+        tracing::info!(message = "forwarder queue closed, shutting down");
+    }
+
+    async fn next_batch(&mut self) -> Option<Vec<CollectedEvent>> {
+        // The flush interval starts when the first event of a batch arrives,
+        // an idle collector doesn't spin.
+        let mut batch = self.receiver.recv().await?;
+
+        let deadline = tokio::time::sleep(self.config.flush_interval);
+        tokio::pin!(deadline);
+
+        while batch.len() < self.config.batch_size {
+            tokio::select! {
+                events = self.receiver.recv() => match events {
+                    Some(events) => batch.extend(events),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+
+        Some(batch)
+    }
+
+    #[tracing::instrument(skip(self, batch), fields(batch_size = batch.len()))]
+    async fn flush(&self, batch: Vec<CollectedEvent>) {
+        let requests = chunk_requests(batch, self.config.batch_size, self.config.max_request_size);
+
+        let failures: usize = futures::stream::iter(requests)
+            .map(|request| async move {
+                let len = request.len();
+                match self.publish(request).await {
+                    Ok(()) => 0,
+                    Err(_) => len,
+                }
+            })
+            .buffer_unordered(self.config.max_in_flight)
+            .fold(0, |failures, failed| {
+                futures::future::ready(failures + failed)
+            })
+            .await;
+
+        if failures > 0 {
+            tracing::error!(
+                message = "dropped events which could not be published",
+                dropped = failures,
+            );
+        } else {
+            tracing::debug!(message = "flushed batch");
+        }
+    }
+
+    async fn publish(&self, request: PublishRawLogsRequest) -> Result<(), ClientError> {
+        let mut attempt = 0;
+        loop {
+            // PipelineIngressClient retries Unavailable responses on its own,
+            // here we additionally ride out timeouts, an open circuit breaker
+            // and any other transient failure. Anything else would fail the
+            // same way again, so it's returned immediately.
+            let mut client = self.pipeline_ingress_client.clone();
+            let result = client.publish_raw_logs(request.clone()).await;
+
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) if is_transient(&e) && attempt < self.config.max_retries => {
+                    let delay = self.retry_delay(attempt);
+                    tracing::warn!(
+                        message = "failed to publish events, retrying",
+                        events = request.len(),
+                        attempt = attempt + 1,
+                        delay_ms = delay.as_millis() as u64,
+                        error =% e,
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    tracing::error!(
+                        message = "failed to publish events, giving up",
+                        events = request.len(),
+                        error =% e,
+                    );
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        retry_delay(
+            self.config.initial_retry_delay,
+            self.config.maximum_retry_delay,
+            attempt,
+        )
+    }
+}
+
+/// Whether a failed request may succeed if it's retried
+fn is_transient(error: &ClientError) -> bool {
+    match error {
+        ClientError::Status(status) => matches!(
+            status.code(),
+            Code::Unavailable | Code::DeadlineExceeded | Code::ResourceExhausted | Code::Aborted
+        ),
+        ClientError::ConnectionFailed(_)
+        | ClientError::CircuitBreakerOpen
+        | ClientError::TimeoutElapsed => true,
+        ClientError::BadConfiguration(_) | ClientError::SerDe(_) => false,
+    }
+}
+
+/// Split a batch into `PublishRawLogs` requests of at most `max_events` events
+/// and, where possible, at most `max_request_size` bytes of payload. An event
+/// larger than `max_request_size` is sent in a request of its own, so that
+/// pipeline-ingress decides whether it's too large.
+fn chunk_requests(
+    batch: Vec<CollectedEvent>,
+    max_events: usize,
+    max_request_size: usize,
+) -> Vec<PublishRawLogsRequest> {
+    let mut requests = Vec::new();
+    let mut raw_logs: Vec<PublishRawLogRequest> = Vec::new();
+    let mut request_size = 0;
+
+    for event in batch {
+        let event_size = event.payload.len();
+        if !raw_logs.is_empty()
+            && (raw_logs.len() >= max_events || request_size + event_size > max_request_size)
+        {
+            requests.push(PublishRawLogsRequest::new(std::mem::take(&mut raw_logs)));
+            request_size = 0;
+        }
+
+        request_size += event_size;
+        raw_logs.push(PublishRawLogRequest::new(
+            event.destination.event_source_id,
+            event.destination.tenant_id,
+            event.payload,
+        ));
+    }
+
+    if !raw_logs.is_empty() {
+        requests.push(PublishRawLogsRequest::new(raw_logs));
+    }
+
+    requests
+}
+
+fn retry_delay(initial: Duration, maximum: Duration, attempt: u32) -> Duration {
+    initial
+        .checked_mul(2u32.saturating_pow(attempt))
+        .unwrap_or(maximum)
+        .min(maximum)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_proto::graplinc::grapl::api::protocol::status::Status;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn event(size: usize) -> CollectedEvent {
+        CollectedEvent {
+            destination: Destination {
+                tenant_id: Uuid::new_v4(),
+                event_source_id: Uuid::new_v4(),
+            },
+            payload: Bytes::from(vec![b'x'; size]),
+        }
+    }
+
+    #[test]
+    fn chunk_requests_respects_event_and_size_limits() {
+        let batch = vec![event(10), event(10), event(10), event(25), event(5)];
+
+        let sizes: Vec<usize> = chunk_requests(batch, 2, 30)
+            .into_iter()
+            .map(|request| request.len())
+            .collect();
+
+        // [10, 10] hits max_events, [10] would exceed 30 bytes with 25,
+        // [25, 5] fits exactly
+        assert_eq!(sizes, vec![2, 1, 2]);
+    }
+
+    #[test]
+    fn chunk_requests_sends_oversized_events_alone() {
+        let batch = vec![event(5), event(100), event(5)];
+
+        let sizes: Vec<usize> = chunk_requests(batch, 10, 30)
+            .into_iter()
+            .map(|request| request.len())
+            .collect();
+
+        assert_eq!(sizes, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn chunk_requests_of_empty_batch_is_empty() {
+        assert!(chunk_requests(vec![], 10, 30).is_empty());
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_maximum() {
+        let initial = Duration::from_millis(100);
+        let maximum = Duration::from_secs(1);
+
+        assert_eq!(retry_delay(initial, maximum, 0), Duration::from_millis(100));
+        assert_eq!(retry_delay(initial, maximum, 1), Duration::from_millis(200));
+        assert_eq!(retry_delay(initial, maximum, 3), Duration::from_millis(800));
+        assert_eq!(retry_delay(initial, maximum, 4), maximum);
+        assert_eq!(retry_delay(initial, maximum, 64), maximum);
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried() {
+        let status = |code| ClientError::Status(Status::new(code, "arbitrary message"));
+
+        assert!(is_transient(&status(Code::Unavailable)));
+        assert!(is_transient(&status(Code::DeadlineExceeded)));
+        assert!(is_transient(&status(Code::ResourceExhausted)));
+        assert!(is_transient(&status(Code::Aborted)));
+        assert!(is_transient(&ClientError::TimeoutElapsed));
+        assert!(!is_transient(&status(Code::InvalidArgument)));
+        assert!(!is_transient(&status(Code::Unauthenticated)));
+        assert!(!is_transient(&status(Code::Internal)));
+    }
+}
@@ -0,0 +1,311 @@
+use bytes::{
+    Buf,
+    Bytes,
+    BytesMut,
+};
+use thiserror::Error;
+use tokio_util::codec::Decoder;
+
+#[derive(Debug, Error)]
+pub enum FramingError {
+    #[error("frame of {size} bytes exceeds the maximum of {max_size} bytes")]
+    FrameTooLarge { size: usize, max_size: usize },
+
+    #[error("invalid octet count {0:?}")]
+    InvalidOctetCount(String),
+
+    #[error("io error {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Decodes syslog messages from a byte stream (TCP or TLS), supporting both
+/// framing methods from RFC 6587:
+///
+/// - octet-counting: `MSG-LEN SP SYSLOG-MSG`, which is what RFC 5425 mandates
+///   for TLS and what most modern senders use
+/// - non-transparent framing: messages terminated by LF (an optional
+///   preceding CR is stripped)
+///
+/// The method is detected per frame: a frame starting with a non-zero digit is
+/// octet-counted, anything else is LF-delimited (a syslog message always
+/// starts with `<`).
+#[derive(Debug, Clone)]
+pub struct SyslogFrameDecoder {
+    max_message_size: usize,
+}
+
+impl SyslogFrameDecoder {
+    pub fn new(max_message_size: usize) -> Self {
+        Self { max_message_size }
+    }
+
+    fn decode_octet_counted(&self, src: &mut BytesMut) -> Result<Option<Bytes>, FramingError> {
+        let space = match src.iter().position(|b| *b == b' ') {
+            Some(space) => space,
+            None => {
+                // Longest length prefix we could accept is the number of digits
+                // in max_message_size; anything longer can't be valid.
+                if src.len() > max_digits(self.max_message_size) {
+                    return Err(FramingError::InvalidOctetCount(
+                        String::from_utf8_lossy(src).into_owned(),
+                    ));
+                }
+                return Ok(None);
+            }
+        };
+
+        let prefix = &src[..space];
+        if !prefix.iter().all(u8::is_ascii_digit) {
+            return Err(FramingError::InvalidOctetCount(
+                String::from_utf8_lossy(prefix).into_owned(),
+            ));
+        }
+
+        let size: usize = std::str::from_utf8(prefix)
+            .ok()
+            .and_then(|prefix| prefix.parse().ok())
+            .ok_or_else(|| {
+                FramingError::InvalidOctetCount(String::from_utf8_lossy(prefix).into_owned())
+            })?;
+
+        if size > self.max_message_size {
+            return Err(FramingError::FrameTooLarge {
+                size,
+                max_size: self.max_message_size,
+            });
+        }
+
+        let frame_end = space + 1 + size;
+        if src.len() < frame_end {
+            src.reserve(frame_end - src.len());
+            return Ok(None);
+        }
+
+        src.advance(space + 1);
+        Ok(Some(src.split_to(size).freeze()))
+    }
+
+    fn decode_lf_delimited(&self, src: &mut BytesMut) -> Result<Option<Bytes>, FramingError> {
+        match src.iter().position(|b| *b == b'\n') {
+            Some(newline) => {
+                if newline > self.max_message_size {
+                    return Err(FramingError::FrameTooLarge {
+                        size: newline,
+                        max_size: self.max_message_size,
+                    });
+                }
+
+                let mut frame = src.split_to(newline + 1);
+                frame.truncate(newline);
+                if frame.last() == Some(&b'\r') {
+                    frame.truncate(newline - 1);
+                }
+                Ok(Some(frame.freeze()))
+            }
+            None if src.len() > self.max_message_size => Err(FramingError::FrameTooLarge {
+                size: src.len(),
+                max_size: self.max_message_size,
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Decoder for SyslogFrameDecoder {
+    type Item = Bytes;
+    type Error = FramingError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            // Skip stray line terminators between frames, some senders emit a
+            // trailing LF even when octet-counting.
+            while matches!(src.first(), Some(b'\n') | Some(b'\r')) {
+                src.advance(1);
+            }
+
+            let frame = match src.first() {
+                None => return Ok(None),
+                Some(b'1'..=b'9') => self.decode_octet_counted(src)?,
+                Some(_) => self.decode_lf_delimited(src)?,
+            };
+
+            match frame {
+                Some(frame) if frame.is_empty() => continue,
+                frame => return Ok(frame),
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(frame) => Ok(Some(frame)),
+            None if src.is_empty() => Ok(None),
+            None => match src.first() {
+                // A truncated octet-counted frame is dropped: we can't tell
+                // whether what we have is a complete message.
+                Some(b'1'..=b'9') => {
+                    tracing::warn!(
+                        message = "discarding truncated octet-counted frame at end of stream",
+                        bytes = src.len(),
+                    );
+                    src.clear();
+                    Ok(None)
+                }
+                // The last LF-delimited message of a stream needn't be
+                // terminated.
+                _ => Ok(Some(src.split().freeze())),
+            },
+        }
+    }
+}
+
+/// Split a newline-delimited JSON body into its individual events. Blank lines
+/// are skipped. Returns the 1-indexed line number of the first line that isn't
+/// valid JSON.
+pub fn split_ndjson(body: Bytes) -> Result<Vec<Bytes>, usize> {
+    let mut events = Vec::new();
+    let mut start = 0;
+
+    for (line_number, line) in body.split(|b| *b == b'\n').enumerate() {
+        let end = start + line.len();
+        let event = trim_ascii_whitespace(body.slice(start..end));
+        start = end + 1;
+
+        if event.is_empty() {
+            continue;
+        }
+
+        if serde_json::from_slice::<serde::de::IgnoredAny>(&event).is_err() {
+            return Err(line_number + 1);
+        }
+
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+fn trim_ascii_whitespace(bytes: Bytes) -> Bytes {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map(|end| end + 1)
+        .unwrap_or(start);
+    bytes.slice(start..end)
+}
+
+fn max_digits(n: usize) -> usize {
+    n.to_string().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(decoder: &mut SyslogFrameDecoder, input: &[u8]) -> Vec<Bytes> {
+        let mut buf = BytesMut::from(input);
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.decode(&mut buf).expect("decode failed") {
+            frames.push(frame);
+        }
+        if let Some(frame) = decoder.decode_eof(&mut buf).expect("decode_eof failed") {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    const MSG_ONE: &[u8] =
+        b"<34>1 2003-10-11T22:14:15.003Z mymachine.example.com su - ID47 - BOM'su root' failed";
+    const MSG_TWO: &[u8] = b"<165>1 2003-08-24T05:14:15.000003-07:00 192.0.2.1 myproc 8710 - - %% It's time to make the do-nuts.";
+
+    #[test]
+    fn decodes_octet_counted_frames() {
+        let mut decoder = SyslogFrameDecoder::new(1024);
+        let mut input = Vec::new();
+        for msg in [MSG_ONE, MSG_TWO] {
+            input.extend_from_slice(format!("{} ", msg.len()).as_bytes());
+            input.extend_from_slice(msg);
+        }
+
+        let frames = decode_all(&mut decoder, &input);
+        assert_eq!(frames, vec![Bytes::from(MSG_ONE), Bytes::from(MSG_TWO)]);
+    }
+
+    #[test]
+    fn decodes_lf_delimited_frames() {
+        let mut decoder = SyslogFrameDecoder::new(1024);
+        let mut input = Vec::new();
+        input.extend_from_slice(MSG_ONE);
+        input.extend_from_slice(b"\r\n");
+        input.extend_from_slice(MSG_TWO);
+
+        let frames = decode_all(&mut decoder, &input);
+        assert_eq!(frames, vec![Bytes::from(MSG_ONE), Bytes::from(MSG_TWO)]);
+    }
+
+    #[test]
+    fn waits_for_partial_octet_counted_frame() {
+        let mut decoder = SyslogFrameDecoder::new(1024);
+        let mut buf = BytesMut::from(format!("{} ", MSG_ONE.len()).as_bytes());
+        buf.extend_from_slice(&MSG_ONE[..10]);
+
+        assert!(decoder.decode(&mut buf).unwrap().is_none());
+
+        buf.extend_from_slice(&MSG_ONE[10..]);
+        assert_eq!(
+            decoder.decode(&mut buf).unwrap(),
+            Some(Bytes::from(MSG_ONE))
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_frames() {
+        let mut decoder = SyslogFrameDecoder::new(16);
+
+        let mut buf = BytesMut::from(&b"17 "[..]);
+        assert!(matches!(
+            decoder.decode(&mut buf),
+            Err(FramingError::FrameTooLarge { size: 17, .. })
+        ));
+
+        let mut buf = BytesMut::from(&b"<34>1 this line never ends"[..]);
+        assert!(matches!(
+            decoder.decode(&mut buf),
+            Err(FramingError::FrameTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_garbage_octet_count() {
+        let mut decoder = SyslogFrameDecoder::new(1024);
+        let mut buf = BytesMut::from(&b"12x <34>1 hello"[..]);
+        assert!(matches!(
+            decoder.decode(&mut buf),
+            Err(FramingError::InvalidOctetCount(_))
+        ));
+    }
+
+    #[test]
+    fn splits_ndjson() {
+        let body = Bytes::from_static(b"{\"a\": 1}\n\n  {\"b\": [2, 3]}  \r\n{\"c\": null}");
+        let events = split_ndjson(body).expect("invalid ndjson");
+        assert_eq!(
+            events,
+            vec![
+                Bytes::from_static(b"{\"a\": 1}"),
+                Bytes::from_static(b"{\"b\": [2, 3]}"),
+                Bytes::from_static(b"{\"c\": null}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_ndjson_line() {
+        let body = Bytes::from_static(b"{\"a\": 1}\n{\"b\": \n{\"c\": null}\n");
+        assert_eq!(split_ndjson(body), Err(2));
+    }
+}
@@ -0,0 +1,127 @@
+use actix_web::{
+    http::StatusCode,
+    web,
+    App,
+    HttpRequest,
+    HttpResponse,
+    HttpServer,
+};
+
+use crate::{
+    config::HttpListenerConfig,
+    error::CollectorError,
+    forwarder::{
+        CollectedEvent,
+        Forwarder,
+    },
+    framing::split_ndjson,
+};
+
+/// Clients authenticate by sending one of the configured API keys in this
+/// header. The key determines the tenant and event source the request's
+/// events are published to.
+pub const API_KEY_HEADER: &str = "x-grapl-api-key";
+
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum HttpCollectorError {
+    #[error("missing or unknown api key")]
+    Unauthorized,
+
+    #[error("line {0} is not valid JSON")]
+    InvalidJson(usize),
+
+    #[error("the collector is shutting down")]
+    Unavailable,
+}
+
+impl actix_web::error::ResponseError for HttpCollectorError {
+    fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+        HttpResponse::build(self.status_code()).body(self.to_string())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            HttpCollectorError::Unauthorized => StatusCode::UNAUTHORIZED,
+            HttpCollectorError::InvalidJson(_) => StatusCode::BAD_REQUEST,
+            HttpCollectorError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+pub struct PublishLogsResponse {
+    /// Number of events queued for publishing
+    pub accepted: usize,
+}
+
+/// Serve the HTTP listener until the server shuts down.
+#[tracing::instrument(skip(config, forwarder), fields(bind_address =% config.bind_address), err)]
+pub async fn serve(config: HttpListenerConfig, forwarder: Forwarder) -> Result<(), CollectorError> {
+    let bind_address = config.bind_address;
+    let payload_config = web::PayloadConfig::new(config.max_body_size);
+    let config = web::Data::new(config);
+    let forwarder = web::Data::new(forwarder);
+
+    tracing::info!(message = "http listener started");
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(config.clone())
+            .app_data(forwarder.clone())
+            .app_data(payload_config.clone())
+            .route("/health", web::get().to(HttpResponse::Ok))
+            .route("/api/v1/logs", web::post().to(publish_logs))
+    })
+    .bind(bind_address)?
+    .run()
+    .await?;
+
+    Ok(())
+}
+
+/// Accepts a newline-delimited JSON body, each line of which becomes a single
+/// event. The request is accepted or rejected as a whole, so a client can
+/// safely retry a rejected request. A successful response means the events were
+/// queued, not that they've reached pipeline-ingress yet.
+#[tracing::instrument(skip(config, forwarder, request, body), err)]
+async fn publish_logs(
+    config: web::Data<HttpListenerConfig>,
+    forwarder: web::Data<Forwarder>,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, HttpCollectorError> {
+    let destination = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|api_key| api_key.to_str().ok())
+        .and_then(|api_key| config.destination_for(api_key))
+        .ok_or(HttpCollectorError::Unauthorized)?;
+
+    let events = split_ndjson(body).map_err(HttpCollectorError::InvalidJson)?;
+    let accepted = events.len();
+
+    tracing::debug!(
+        message = "publishing logs",
+        tenant_id =% destination.tenant_id,
+        event_source_id =% destination.event_source_id,
+        events = accepted,
+    );
+
+    // Queued as a single submission, so that a shutting down collector
+    // rejects the whole request rather than keeping part of it.
+    forwarder
+        .send_all(
+            events
+                .into_iter()
+                .map(|payload| CollectedEvent {
+                    destination,
+                    payload,
+                })
+                .collect(),
+        )
+        .await
+        .map_err(|_| HttpCollectorError::Unavailable)?;
+
+    Ok(HttpResponse::Accepted().json(PublishLogsResponse { accepted }))
+}
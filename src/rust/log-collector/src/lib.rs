@@ -0,0 +1,67 @@
+//! Collects logs over protocols which can't speak gRPC and publishes them to
+//! pipeline-ingress. Two kinds of listener are supported:
+//!
+//! - syslog (RFC 5424 messages over UDP, TCP or TLS), each listener bound to a
+//!   single `(tenant_id, event_source_id)`
+//! - HTTP, accepting newline-delimited JSON, where the API key sent with each
+//!   request selects the `(tenant_id, event_source_id)`
+//!
+//! Every framed event is handed to a shared `Forwarder`, which batches them
+//! into `PublishRawLogs` requests. Each event becomes a separate `RawLog`.
+
+pub mod config;
+pub mod error;
+pub mod forwarder;
+pub mod framing;
+pub mod http;
+pub mod syslog;
+
+use futures::{
+    future::LocalBoxFuture,
+    FutureExt,
+};
+use rust_proto::graplinc::grapl::api::pipeline_ingress::v1beta1::client::PipelineIngressClient;
+
+use crate::{
+    config::LogCollectorConfig,
+    error::CollectorError,
+    forwarder::Forwarder,
+};
+
+/// Run every configured listener until one of them fails.
+pub async fn run(
+    config: LogCollectorConfig,
+    pipeline_ingress_client: PipelineIngressClient,
+) -> Result<(), CollectorError> {
+    config.validate().map_err(CollectorError::Configuration)?;
+
+    let (forwarder, forwarder_task) = Forwarder::new(pipeline_ingress_client, config.forwarder);
+    let forwarder_handle = tokio::spawn(forwarder_task.run());
+
+    let mut listeners: Vec<LocalBoxFuture<'static, Result<(), CollectorError>>> = config
+        .syslog_listeners
+        .into_iter()
+        .map(|listener_config| syslog::serve(listener_config, forwarder.clone()).boxed_local())
+        .collect();
+
+    if let Some(http_config) = config.http_listener {
+        listeners.push(http::serve(http_config, forwarder.clone()).boxed_local());
+    }
+
+    // The listeners hold the only remaining handles, once they're all gone the
+    // forwarder drains its queue and exits.
+    drop(forwarder);
+
+    if let Err(e) = futures::future::try_join_all(listeners).await {
+        // Connection tasks and server workers may still hold forwarder
+        // handles, so the forwarder wouldn't exit on its own.
+        forwarder_handle.abort();
+        return Err(e);
+    }
+
+    if let Err(e) = forwarder_handle.await {
+        tracing::error!(message = "forwarder task failed", error =% e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use figment::{
+    providers::Env,
+    Figment,
+};
+use grapl_tracing::setup_tracing;
+use log_collector::{
+    config::{
+        LogCollectorArgs,
+        LogCollectorConfig,
+    },
+    error::CollectorError,
+};
+use rust_proto::graplinc::grapl::api::{
+    client::Connect,
+    pipeline_ingress::v1beta1::client::PipelineIngressClient,
+};
+
+const SERVICE_NAME: &'static str = "log-collector";
+
+#[tokio::main]
+async fn main() -> Result<(), CollectorError> {
+    let _guard = setup_tracing(SERVICE_NAME)?;
+
+    tracing::info!("starting up!");
+
+    match handler().await {
+        Ok(res) => {
+            tracing::info!("shutting down");
+            Ok(res)
+        }
+        Err(err) => {
+            tracing::error!(
+                message = "log-collector failed",
+                error = ?err,
+            );
+            Err(err)
+        }
+    }
+}
+
+#[tracing::instrument(err)]
+async fn handler() -> Result<(), CollectorError> {
+    let args = LogCollectorArgs::from_env_vars();
+    let config = LogCollectorConfig::load(&args)?;
+
+    tracing::info!(
+        message = "configuring log collector",
+        syslog_listeners = config.syslog_listeners.len(),
+        http_listener = config.http_listener.is_some(),
+        forwarder_config = ?config.forwarder,
+    );
+
+    let pipeline_ingress_client_config = Figment::new()
+        .merge(Env::prefixed("PIPELINE_INGRESS_CLIENT_"))
+        .extract()?;
+    let pipeline_ingress_client =
+        PipelineIngressClient::connect(pipeline_ingress_client_config).await?;
+
+    log_collector::run(config, pipeline_ingress_client).await
+}
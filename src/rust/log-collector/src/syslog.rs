@@ -0,0 +1,233 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use tokio::{
+    io::AsyncRead,
+    net::{
+        TcpListener,
+        UdpSocket,
+    },
+};
+use tokio_rustls::{
+    rustls,
+    TlsAcceptor,
+};
+use tokio_util::codec::FramedRead;
+
+use crate::{
+    config::{
+        Destination,
+        SyslogListenerConfig,
+        SyslogProtocol,
+        TlsConfig,
+    },
+    error::CollectorError,
+    forwarder::{
+        CollectedEvent,
+        Forwarder,
+    },
+    framing::SyslogFrameDecoder,
+};
+
+/// Largest possible UDP payload
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// Serve a single syslog listener until its socket fails or the forwarder
+/// shuts down. Every message received is published to the listener's
+/// configured destination.
+#[tracing::instrument(skip(config, forwarder), fields(
+    bind_address =% config.bind_address,
+    protocol =? config.protocol,
+    tenant_id =% config.destination.tenant_id,
+    event_source_id =% config.destination.event_source_id,
+), err)]
+pub async fn serve(
+    config: SyslogListenerConfig,
+    forwarder: Forwarder,
+) -> Result<(), CollectorError> {
+    match config.protocol {
+        SyslogProtocol::Udp => serve_udp(config, forwarder).await,
+        SyslogProtocol::Tcp => serve_tcp(config, forwarder, None).await,
+        SyslogProtocol::Tls => {
+            let tls_config = config
+                .tls
+                .as_ref()
+                .ok_or_else(|| CollectorError::Configuration("missing tls configuration".into()))?;
+            let acceptor = load_tls_acceptor(tls_config)?;
+            serve_tcp(config, forwarder, Some(acceptor)).await
+        }
+    }
+}
+
+async fn serve_udp(
+    config: SyslogListenerConfig,
+    forwarder: Forwarder,
+) -> Result<(), CollectorError> {
+    let socket = UdpSocket::bind(config.bind_address).await?;
+    tracing::info!(message = "syslog udp listener started");
+
+    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+    loop {
+        let (len, peer) = socket.recv_from(&mut buf).await?;
+
+        if len > config.max_message_size {
+            tracing::warn!(
+                message = "dropping oversized syslog datagram",
+                peer =% peer,
+                size = len,
+                max_size = config.max_message_size,
+            );
+            continue;
+        }
+
+        let payload = trim_trailing_newline(Bytes::copy_from_slice(&buf[..len]));
+        if payload.is_empty() {
+            continue;
+        }
+
+        forwarder
+            .send(CollectedEvent {
+                destination: config.destination,
+                payload,
+            })
+            .await?;
+    }
+}
+
+async fn serve_tcp(
+    config: SyslogListenerConfig,
+    forwarder: Forwarder,
+    tls_acceptor: Option<TlsAcceptor>,
+) -> Result<(), CollectorError> {
+    let listener = TcpListener::bind(config.bind_address).await?;
+    tracing::info!(message = "syslog stream listener started");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let forwarder = forwarder.clone();
+        let destination = config.destination;
+        let decoder = SyslogFrameDecoder::new(config.max_message_size);
+
+        match tls_acceptor.clone() {
+            Some(acceptor) => {
+                tokio::spawn(async move {
+                    match acceptor.accept(stream).await {
+                        Ok(stream) => {
+                            handle_stream(stream, peer, decoder, destination, forwarder).await
+                        }
+                        Err(e) => tracing::warn!(
+                            message = "tls handshake failed",
+                            peer =% peer,
+                            error =% e,
+                        ),
+                    }
+                });
+            }
+            None => {
+                tokio::spawn(handle_stream(stream, peer, decoder, destination, forwarder));
+            }
+        }
+    }
+}
+
+#[tracing::instrument(skip(stream, decoder, destination, forwarder))]
+async fn handle_stream<S>(
+    stream: S,
+    peer: SocketAddr,
+    decoder: SyslogFrameDecoder,
+    destination: Destination,
+    forwarder: Forwarder,
+) where
+    S: AsyncRead + Unpin,
+{
+    tracing::debug!(message = "syslog connection opened");
+
+    let mut frames = FramedRead::new(stream, decoder);
+    while let Some(frame) = frames.next().await {
+        match frame {
+            Ok(payload) => {
+                if forwarder
+                    .send(CollectedEvent {
+                        destination,
+                        payload,
+                    })
+                    .await
+                    .is_err()
+                {
+                    tracing::warn!(message = "forwarder shut down, closing connection");
+                    return;
+                }
+            }
+            Err(e) => {
+                // Once framing is lost there's no way to find the start of the
+                // next message, so the connection is dropped.
+                tracing::warn!(
+                    message = "closing syslog connection after framing error",
+                    error =% e,
+                );
+                return;
+            }
+        }
+    }
+
+    tracing::debug!(message = "syslog connection closed");
+}
+
+fn load_tls_acceptor(config: &TlsConfig) -> Result<TlsAcceptor, CollectorError> {
+    let certificates = rustls_pemfile::certs(&mut open(&config.certificate_file)?)?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect::<Vec<_>>();
+
+    if certificates.is_empty() {
+        return Err(CollectorError::Configuration(format!(
+            "no certificates found in {}",
+            config.certificate_file.display()
+        )));
+    }
+
+    let private_key = load_private_key(&config.private_key_file)?;
+
+    let server_config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certificates, private_key)
+        .map_err(|e| CollectorError::Configuration(format!("invalid tls configuration {e}")))?;
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+fn load_private_key(path: &Path) -> Result<rustls::PrivateKey, CollectorError> {
+    let mut reader = open(path)?;
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(rustls::PrivateKey(key)),
+            _ => continue,
+        }
+    }
+
+    Err(CollectorError::Configuration(format!(
+        "no private key found in {}",
+        path.display()
+    )))
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, CollectorError> {
+    Ok(BufReader::new(File::open(path)?))
+}
+
+fn trim_trailing_newline(mut payload: Bytes) -> Bytes {
+    while matches!(payload.last(), Some(b'\n') | Some(b'\r')) {
+        payload.truncate(payload.len() - 1);
+    }
+    payload
+}
@@ -0,0 +1,218 @@
+#![cfg(feature = "integration_tests")]
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use figment::{
+    providers::Env,
+    Figment,
+};
+use grapl_tracing::{
+    setup_tracing,
+    WorkerGuard,
+};
+use kafka::{
+    config::ConsumerConfig,
+    test_utils::topic_scanner::KafkaTopicScanner,
+};
+use log_collector::{
+    config::LogCollectorConfig,
+    http::API_KEY_HEADER,
+};
+use rust_proto::graplinc::grapl::{
+    api::{
+        client::Connect,
+        pipeline_ingress::v1beta1::client::PipelineIngressClient,
+    },
+    pipeline::v1beta1::{
+        Envelope,
+        RawLog,
+    },
+};
+use test_context::{
+    test_context,
+    AsyncTestContext,
+};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+static CONSUMER_TOPIC: &'static str = "raw-logs";
+
+const API_KEY: &'static str = "log-collector-integration-test-key";
+
+struct LogCollectorTestContext {
+    tenant_id: Uuid,
+    event_source_id: Uuid,
+    _guard: WorkerGuard,
+}
+
+#[async_trait::async_trait]
+impl AsyncTestContext for LogCollectorTestContext {
+    async fn setup() -> Self {
+        let _guard = setup_tracing("log-collector-integration-tests").expect("setup_tracing");
+
+        LogCollectorTestContext {
+            tenant_id: Uuid::new_v4(),
+            event_source_id: Uuid::new_v4(),
+            _guard,
+        }
+    }
+}
+
+/// Run a collector on its own thread and runtime, so that it outlives the
+/// test's runtime without having to be `Send`.
+async fn spawn_log_collector(config: serde_json::Value) {
+    let config: LogCollectorConfig =
+        serde_json::from_value(config).expect("invalid log collector config");
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("failed to build runtime");
+        runtime.block_on(async move {
+            let client_config = Figment::new()
+                .merge(Env::prefixed("PIPELINE_INGRESS_CLIENT_"))
+                .extract()
+                .expect("failed to configure pipeline ingress client");
+            let pipeline_ingress_client = PipelineIngressClient::connect(client_config)
+                .await
+                .expect("failed to connect to pipeline ingress");
+
+            if let Err(e) = log_collector::run(config, pipeline_ingress_client).await {
+                tracing::error!(message = "log collector failed", error =% e);
+            }
+        })
+    });
+
+    // give the listeners a moment to bind
+    tokio::time::sleep(Duration::from_secs(2)).await;
+}
+
+fn topic_scanner() -> KafkaTopicScanner<RawLog> {
+    KafkaTopicScanner::new(
+        ConsumerConfig::with_topic(CONSUMER_TOPIC),
+        Duration::from_secs(30),
+        Envelope::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            RawLog::new(Bytes::from_static(b"priming message")),
+        ),
+    )
+}
+
+#[test_context(LogCollectorTestContext)]
+#[tokio::test]
+async fn test_syslog_tcp_messages_are_published(
+    ctx: &mut LogCollectorTestContext,
+) -> eyre::Result<()> {
+    let syslog_address = "127.0.0.1:16514";
+    spawn_log_collector(serde_json::json!({
+        "syslog_listeners": [{
+            "bind_address": syslog_address,
+            "protocol": "tcp",
+            "tenant_id": ctx.tenant_id,
+            "event_source_id": ctx.event_source_id,
+        }],
+    }))
+    .await;
+
+    let messages: Vec<Bytes> = vec![
+        Bytes::from_static(b"<34>1 2003-10-11T22:14:15.003Z mymachine.example.com su - ID47 - 'su root' failed"),
+        Bytes::from_static(b"<165>1 2003-08-24T05:14:15.000003-07:00 192.0.2.1 myproc 8710 - - It's time to make the do-nuts."),
+    ];
+
+    let handle = topic_scanner()
+        .scan_for_tenant(ctx.tenant_id, 2, |_: RawLog| true)
+        .await;
+
+    let mut stream = tokio::net::TcpStream::connect(syslog_address).await?;
+    for message in messages.iter() {
+        // octet-counted framing
+        stream
+            .write_all(format!("{} ", message.len()).as_bytes())
+            .await?;
+        stream.write_all(message).await?;
+    }
+    stream.shutdown().await?;
+
+    let envelopes = handle.await?;
+    assert_eq!(envelopes.len(), 2);
+
+    let mut received: Vec<Bytes> = envelopes
+        .into_iter()
+        .map(|envelope| {
+            assert_eq!(envelope.event_source_id(), ctx.event_source_id);
+            envelope.inner_message().log_event()
+        })
+        .collect();
+    received.sort();
+
+    let mut expected = messages;
+    expected.sort();
+    assert_eq!(received, expected);
+
+    Ok(())
+}
+
+#[test_context(LogCollectorTestContext)]
+#[tokio::test]
+async fn test_http_ndjson_lines_are_published(
+    ctx: &mut LogCollectorTestContext,
+) -> eyre::Result<()> {
+    let http_address = "127.0.0.1:16580";
+    spawn_log_collector(serde_json::json!({
+        "http_listener": {
+            "bind_address": http_address,
+            "api_keys": [{
+                "api_key": API_KEY,
+                "tenant_id": ctx.tenant_id,
+                "event_source_id": ctx.event_source_id,
+            }],
+        },
+    }))
+    .await;
+
+    let handle = topic_scanner()
+        .scan_for_tenant(ctx.tenant_id, 3, |_: RawLog| true)
+        .await;
+
+    let http_client = reqwest::Client::new();
+
+    let unauthorized = http_client
+        .post(format!("http://{http_address}/api/v1/logs"))
+        .header(API_KEY_HEADER, "not-the-key")
+        .body("{\"event\": 0}\n")
+        .send()
+        .await?;
+    assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let accepted = http_client
+        .post(format!("http://{http_address}/api/v1/logs"))
+        .header(API_KEY_HEADER, API_KEY)
+        .body("{\"event\": 1}\n{\"event\": 2}\n\n{\"event\": 3}\n")
+        .send()
+        .await?;
+    assert_eq!(accepted.status(), reqwest::StatusCode::ACCEPTED);
+
+    let envelopes = handle.await?;
+    assert_eq!(envelopes.len(), 3);
+
+    let mut received: Vec<Bytes> = envelopes
+        .into_iter()
+        .map(|envelope| {
+            assert_eq!(envelope.event_source_id(), ctx.event_source_id);
+            envelope.inner_message().log_event()
+        })
+        .collect();
+    received.sort();
+
+    assert_eq!(
+        received,
+        vec![
+            Bytes::from_static(b"{\"event\": 1}"),
+            Bytes::from_static(b"{\"event\": 2}"),
+            Bytes::from_static(b"{\"event\": 3}"),
+        ]
+    );
+
+    Ok(())
+}
@@ -1,8 +1,49 @@
+use std::net::SocketAddr;
+
+use crate::identity_store::IdentityStoreBackend;
+
 #[derive(clap::Parser, Debug)]
 #[clap(name = "node-identifier", about = "Node Identifier Service")]
 pub struct NodeIdentifierConfig {
+    /// Where sessions and static mappings are stored, either dynamodb or
+    /// scylla
+    #[clap(long, env, default_value = "dynamodb")]
+    pub node_identifier_identity_store: IdentityStoreBackend,
+    /// Required by the dynamodb identity store
     #[clap(long, env)]
-    pub grapl_static_mapping_table: String,
+    pub grapl_static_mapping_table: Option<String>,
+    /// Required by the dynamodb identity store
     #[clap(long, env)]
-    pub grapl_dynamic_session_table: String,
+    pub grapl_dynamic_session_table: Option<String>,
+    #[clap(flatten)]
+    pub scylla_config: ScyllaIdentityStoreConfig,
+    /// Maximum number of identities resolved at once for a single graph
+    #[clap(long, env, default_value = "16")]
+    pub node_identifier_max_concurrency: usize,
+    /// Maximum number of static mappings kept in memory
+    #[clap(long, env, default_value = "100000")]
+    pub node_identifier_static_mapping_cache_size: u64,
+    /// Maximum number of node types' identity arguments kept in memory
+    #[clap(long, env, default_value = "10000")]
+    pub node_identifier_identity_arguments_cache_size: u64,
+    /// How long a node type's identity arguments are cached for, so that
+    /// newly deployed schemas are picked up
+    #[clap(long, env, default_value = "60000")]
+    pub node_identifier_identity_arguments_cache_ttl_ms: u64,
+}
+
+/// Only used by the scylla identity store
+#[derive(clap::Parser, Debug)]
+pub struct ScyllaIdentityStoreConfig {
+    #[clap(long, env, value_delimiter = ',')]
+    /// The addresses of the Scylla cluster
+    pub graph_db_addresses: Vec<SocketAddr>,
+    #[clap(long, env)]
+    pub graph_db_auth_username: Option<String>,
+    #[clap(long, env)]
+    pub graph_db_auth_password: Option<String>,
+    /// The keyspace sessions and static mappings are stored in, whose tables
+    /// are created by scylla-provisioner
+    #[clap(long, env, default_value = "tenant_graph_ks")]
+    pub node_identifier_scylla_keyspace: String,
 }
@@ -1,51 +1,71 @@
+use std::collections::HashMap;
+
 use failure::{
     bail,
     Error,
 };
-use rusoto_dynamodb::DynamoDb;
-use rust_proto::graplinc::grapl::api::{
-    graph::v1beta1::{
-        IdentifiedNode,
-        NodeDescription,
-        Session,
-        Static,
-        Strategy,
+use futures::StreamExt;
+use rust_proto::graplinc::grapl::{
+    api::{
+        graph::v1beta1::{
+            NodeDescription,
+            Session,
+            Strategy,
+        },
+        graph_mutation::v1beta1::client::GraphMutationClient,
     },
-    graph_mutation::v1beta1::client::GraphMutationClient,
+    common::v1beta1::types::Uid,
 };
 
 use crate::{
+    identity_arguments::IdentityArgumentsResolver,
+    identity_store::IdentityStore,
     sessiondb::SessionDb,
     sessions::UnidSession,
+    static_mapping_db::UnidStatic,
     StaticMappingDb,
 };
 
+/// What a node is identified by. Nodes within a graph with the same identity
+/// are identified once, and always receive the same uid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Identity {
+    Session(UnidSession),
+    Static(UnidStatic),
+}
+
 #[derive(Debug, Clone)]
-pub(crate) struct NodeDescriptionIdentifier<D>
+pub(crate) struct NodeDescriptionIdentifier<S>
 where
-    D: DynamoDb,
+    S: IdentityStore,
 {
-    dyn_session_db: SessionDb<D>,
+    dyn_session_db: SessionDb<S>,
     graph_mutation_client: GraphMutationClient,
-    static_mapping_db: StaticMappingDb<D>,
+    static_mapping_db: StaticMappingDb<S>,
+    identity_arguments_resolver: IdentityArgumentsResolver,
     should_guess: bool,
+    max_concurrency: usize,
 }
 
-impl<D> NodeDescriptionIdentifier<D>
+impl<S> NodeDescriptionIdentifier<S>
 where
-    D: DynamoDb,
+    S: IdentityStore,
 {
     pub fn new(
-        dyn_session_db: SessionDb<D>,
+        dyn_session_db: SessionDb<S>,
         graph_mutation_client: GraphMutationClient,
-        static_mapping_db: StaticMappingDb<D>,
+        static_mapping_db: StaticMappingDb<S>,
+        identity_arguments_resolver: IdentityArgumentsResolver,
         should_guess: bool,
+        max_concurrency: usize,
     ) -> Self {
         Self {
             dyn_session_db,
             graph_mutation_client,
             static_mapping_db,
+            identity_arguments_resolver,
             should_guess,
+            max_concurrency,
         }
     }
 
@@ -79,13 +99,13 @@ where
         Ok(pseudo_key)
     }
 
-    #[tracing::instrument(skip(self, strategy), err)]
-    pub(crate) async fn attribute_dynamic_session(
+    #[tracing::instrument(skip(self, node, strategy), err)]
+    async fn unid_session(
         &self,
         tenant_id: uuid::Uuid,
         node: &NodeDescription,
         strategy: &Session,
-    ) -> Result<IdentifiedNode, Error> {
+    ) -> Result<UnidSession, Error> {
         let mut attributed_node = node.clone();
 
         let pseudo_key = self.pseudo_key(&mut attributed_node, strategy).await?;
@@ -115,61 +135,94 @@ where
             ),
         };
 
-        let session_id = self
-            .dyn_session_db
-            .handle_unid_session(
-                tenant_id,
-                unid,
-                &self.graph_mutation_client,
-                self.should_guess,
-            )
-            .await?;
-
-        Ok(IdentifiedNode {
-            properties: node.properties.clone(),
-            uid: session_id,
-            node_type: node.node_type.to_string(),
-        })
+        Ok(unid)
     }
 
-    #[tracing::instrument(skip(self, node, strategy), err)]
-    pub(crate) async fn attribute_static_mapping(
+    /// Determines what the node should be identified by, without making any
+    /// requests to the identity stores. The strategy comes from the tenant's
+    /// deployed schema where the node type has been deployed.
+    #[tracing::instrument(skip(self, node), err)]
+    pub(crate) async fn identity(
         &self,
         tenant_id: uuid::Uuid,
         node: &NodeDescription,
-        strategy: &Static,
-    ) -> Result<IdentifiedNode, Error> {
-        let uid = self
-            .static_mapping_db
-            .map_unid(tenant_id, node, strategy)
+    ) -> Result<Identity, Error> {
+        let strategy = self
+            .identity_arguments_resolver
+            .resolve_strategy(tenant_id, node)
             .await?;
 
-        Ok(IdentifiedNode {
-            properties: node.properties.clone(),
-            uid,
-            node_type: node.node_type.to_string(),
-        })
+        match strategy {
+            Strategy::Session(ref strategy) => Ok(Identity::Session(
+                self.unid_session(tenant_id, node, strategy).await?,
+            )),
+            Strategy::Static(ref strategy) => Ok(Identity::Static(UnidStatic::new(
+                tenant_id, node, strategy,
+            )?)),
+        }
     }
 
-    #[tracing::instrument(skip(self, node), err)]
-    pub(crate) async fn attribute_dynamic_node(
+    /// Resolves each distinct identity to a uid.
+    ///
+    /// Session identities sharing a pseudo key are resolved one at a time, in
+    /// timestamp order, because each may create or extend a session that the
+    /// next one should find. Different pseudo keys are independent, so up to
+    /// `max_concurrency` of them are resolved at once. DynamoDB has no batched
+    /// form of Query, so each session lookup is still its own request.
+    ///
+    /// Static identities are resolved by the `StaticMappingDb` in batches.
+    #[tracing::instrument(skip(self, identities), fields(identity_count = identities.len()))]
+    pub(crate) async fn resolve_identities(
         &self,
         tenant_id: uuid::Uuid,
-        node: &NodeDescription,
-    ) -> Result<IdentifiedNode, Error> {
-        let strategy = &node.id_strategy[0];
-
-        match strategy.strategy {
-            Strategy::Session(ref strategy) => {
-                tracing::info!("Attributing dynamic node via session");
-                self.attribute_dynamic_session(tenant_id, node, strategy)
-                    .await
-            }
-            Strategy::Static(ref strategy) => {
-                tracing::info!("Attributing dynamic node via static mapping");
-                self.attribute_static_mapping(tenant_id, node, strategy)
-                    .await
+        identities: Vec<Identity>,
+    ) -> Vec<(Identity, Result<Uid, Error>)> {
+        let mut sessions_by_pseudo_key: HashMap<String, Vec<UnidSession>> = HashMap::new();
+        let mut statics = vec![];
+        for identity in identities {
+            match identity {
+                Identity::Session(unid) => sessions_by_pseudo_key
+                    .entry(unid.pseudo_key.clone())
+                    .or_default()
+                    .push(unid),
+                Identity::Static(unid) => statics.push(unid),
             }
         }
+
+        let sessions = futures::stream::iter(sessions_by_pseudo_key.into_values())
+            .map(|mut unids| async move {
+                unids.sort_by_key(|unid| (unid.timestamp, !unid.is_creation));
+
+                let mut results = Vec::with_capacity(unids.len());
+                for unid in unids {
+                    let result = self
+                        .dyn_session_db
+                        .handle_unid_session(
+                            tenant_id,
+                            unid.clone(),
+                            &self.graph_mutation_client,
+                            self.should_guess,
+                        )
+                        .await;
+                    results.push((Identity::Session(unid), result));
+                }
+                futures::stream::iter(results)
+            })
+            .buffer_unordered(self.max_concurrency)
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let statics = self
+            .static_mapping_db
+            .map_unids(tenant_id, statics, self.max_concurrency);
+
+        let (mut sessions, statics) = futures::join!(sessions, statics);
+
+        sessions.extend(
+            statics
+                .into_iter()
+                .map(|(unid, result)| (Identity::Static(unid), result)),
+        );
+        sessions
     }
 }
@@ -0,0 +1,417 @@
+use std::{
+    fmt::Debug,
+    time::Duration,
+};
+
+use moka::future::{
+    Cache,
+    CacheBuilder,
+};
+use rust_proto::graplinc::grapl::{
+    api::{
+        client::ClientError,
+        graph::v1beta1::{
+            NodeDescription,
+            Property,
+            Session,
+            Static,
+            Strategy,
+        },
+        graph_schema_manager::v1beta1::{
+            client::GraphSchemaManagerClient,
+            messages::{
+                GetNodeSchemaRequest,
+                IdentityAlgorithm,
+                SessionIdentity,
+                StaticIdentity,
+            },
+        },
+        protocol::status::{
+            Code,
+            Status,
+        },
+    },
+    common::v1beta1::types::{
+        NodeType,
+        PropertyName,
+    },
+};
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum IdentityArgumentsError {
+    #[error("gRPC client error: {0}")]
+    ClientError(#[from] ClientError),
+    #[error("{node_type} is not in the deployed schema, and the node has no identity strategy")]
+    MissingStrategy { node_type: String },
+    #[error("{node_type} is identified by a {deployed} identity in the deployed schema, but the node has a {embedded} identity strategy")]
+    AlgorithmMismatch {
+        node_type: String,
+        deployed: &'static str,
+        embedded: &'static str,
+    },
+    #[error("{node_type} is identified by {deployed:?} in the deployed schema, but the node's identity strategy uses {embedded:?}")]
+    KeyPropertiesMismatch {
+        node_type: String,
+        deployed: Vec<String>,
+        embedded: Vec<String>,
+    },
+    #[error(
+        "{node_type} is identified by a session, but the node has no {property_name} timestamp"
+    )]
+    MissingTimestamp {
+        node_type: String,
+        property_name: String,
+    },
+}
+
+/// Resolves the strategy a node is identified by from the tenant's deployed
+/// schema. Identity arguments are only cached for `time_to_live`, so that
+/// newly deployed node types are picked up.
+///
+/// The strategy embedded in a `NodeDescription` is only used for node types
+/// that haven't been deployed. Otherwise it must agree with the deployed
+/// identity arguments, or the node is rejected.
+#[derive(Clone)]
+pub(crate) struct IdentityArgumentsResolver {
+    schema_client: GraphSchemaManagerClient,
+    identity_cache: Cache<(uuid::Uuid, String), Option<IdentityAlgorithm>>,
+}
+
+impl Debug for IdentityArgumentsResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdentityArgumentsResolver")
+            .field("cache_entries", &self.identity_cache.entry_count())
+            .finish()
+    }
+}
+
+impl IdentityArgumentsResolver {
+    pub fn new(
+        schema_client: GraphSchemaManagerClient,
+        cache_size: u64,
+        time_to_live: Duration,
+    ) -> Self {
+        let identity_cache = CacheBuilder::new(cache_size)
+            .time_to_live(time_to_live)
+            .build();
+        Self {
+            schema_client,
+            identity_cache,
+        }
+    }
+
+    #[tracing::instrument(skip(self, node), fields(node_type = %node.node_type), err)]
+    pub(crate) async fn resolve_strategy(
+        &self,
+        tenant_id: uuid::Uuid,
+        node: &NodeDescription,
+    ) -> Result<Strategy, IdentityArgumentsError> {
+        let embedded = node
+            .id_strategy
+            .first()
+            .map(|id_strategy| &id_strategy.strategy);
+
+        match (
+            self.deployed_identity(tenant_id, &node.node_type).await?,
+            embedded,
+        ) {
+            (Some(deployed), Some(embedded)) => {
+                check_strategy(&node.node_type, &deployed, embedded)?;
+                strategy_from_identity(&deployed, node, Some(embedded))
+            }
+            (Some(deployed), None) => strategy_from_identity(&deployed, node, None),
+            (None, Some(embedded)) => Ok(embedded.clone()),
+            (None, None) => Err(IdentityArgumentsError::MissingStrategy {
+                node_type: node.node_type.clone(),
+            }),
+        }
+    }
+
+    /// The identity arguments of the latest schema version the node type was
+    /// deployed in, or None if it never was
+    async fn deployed_identity(
+        &self,
+        tenant_id: uuid::Uuid,
+        node_type: &str,
+    ) -> Result<Option<IdentityAlgorithm>, IdentityArgumentsError> {
+        let cache = &self.identity_cache;
+        let key = (tenant_id, node_type.to_owned());
+
+        if let Some(identity_algorithm) = cache.get(&key) {
+            return Ok(identity_algorithm);
+        }
+
+        let mut schema_client = self.schema_client.clone();
+        let identity_algorithm = match schema_client
+            .get_node_schema(GetNodeSchemaRequest {
+                tenant_id,
+                node_type: NodeType {
+                    value: node_type.to_owned(),
+                },
+                schema_version: None,
+            })
+            .await
+        {
+            Ok(response) => Some(response.node_schema.identity_algorithm),
+            Err(ClientError::Status(Status {
+                code: Code::NotFound,
+                ..
+            })) => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        cache.insert(key, identity_algorithm.clone()).await;
+        Ok(identity_algorithm)
+    }
+}
+
+/// Checks that a node's embedded strategy identifies it the same way as the
+/// deployed identity arguments. Key properties must be listed in the same
+/// order, since the order determines the key a node is identified by.
+fn check_strategy(
+    node_type: &str,
+    deployed: &IdentityAlgorithm,
+    embedded: &Strategy,
+) -> Result<(), IdentityArgumentsError> {
+    let (deployed_keys, embedded_keys) = match (deployed, embedded) {
+        (IdentityAlgorithm::Session(deployed), Strategy::Session(embedded)) => (
+            &deployed.pseudo_key_properties,
+            &embedded.primary_key_properties,
+        ),
+        (IdentityAlgorithm::Static(deployed), Strategy::Static(embedded)) => (
+            &deployed.static_key_properties,
+            &embedded.primary_key_properties,
+        ),
+        (deployed, embedded) => {
+            return Err(IdentityArgumentsError::AlgorithmMismatch {
+                node_type: node_type.to_owned(),
+                deployed: match deployed {
+                    IdentityAlgorithm::Session(_) => "session",
+                    IdentityAlgorithm::Static(_) => "static",
+                },
+                embedded: match embedded {
+                    Strategy::Session(_) => "session",
+                    Strategy::Static(_) => "static",
+                },
+            })
+        }
+    };
+
+    let deployed_keys = property_names(deployed_keys);
+    if &deployed_keys != embedded_keys {
+        return Err(IdentityArgumentsError::KeyPropertiesMismatch {
+            node_type: node_type.to_owned(),
+            deployed: deployed_keys,
+            embedded: embedded_keys.clone(),
+        });
+    }
+    Ok(())
+}
+
+fn strategy_from_identity(
+    deployed: &IdentityAlgorithm,
+    node: &NodeDescription,
+    embedded: Option<&Strategy>,
+) -> Result<Strategy, IdentityArgumentsError> {
+    match deployed {
+        IdentityAlgorithm::Session(identity) => {
+            let embedded = match embedded {
+                Some(Strategy::Session(session)) => Some(session),
+                _ => None,
+            };
+            Ok(Strategy::Session(session_from_identity(
+                identity, node, embedded,
+            )?))
+        }
+        IdentityAlgorithm::Static(identity) => Ok(Strategy::Static(static_from_identity(identity))),
+    }
+}
+
+/// Session timestamps are read from the properties the deployed schema names,
+/// falling back to the ones embedded in the node's strategy. A node with
+/// neither is rejected rather than given a timestamp of 0.
+fn session_from_identity(
+    identity: &SessionIdentity,
+    node: &NodeDescription,
+    embedded: Option<&Session>,
+) -> Result<Session, IdentityArgumentsError> {
+    let timestamp = |property_name: &PropertyName, embedded_time: fn(&Session) -> u64| {
+        uint_property(node, property_name)
+            .or_else(|| embedded.map(embedded_time))
+            .ok_or_else(|| IdentityArgumentsError::MissingTimestamp {
+                node_type: node.node_type.clone(),
+                property_name: property_name.value.clone(),
+            })
+    };
+
+    Ok(Session {
+        primary_key_properties: property_names(&identity.pseudo_key_properties),
+        primary_key_requires_asset_id: false,
+        create_time: timestamp(&identity.creation_timestamp_property, |s| s.create_time)?,
+        last_seen_time: timestamp(&identity.last_seen_timestamp_property, |s| s.last_seen_time)?,
+        terminate_time: timestamp(&identity.termination_timestamp_property, |s| {
+            s.terminate_time
+        })?,
+    })
+}
+
+fn static_from_identity(identity: &StaticIdentity) -> Static {
+    Static {
+        primary_key_properties: property_names(&identity.static_key_properties),
+        primary_key_requires_asset_id: false,
+    }
+}
+
+fn property_names(property_names: &[PropertyName]) -> Vec<String> {
+    property_names.iter().map(|p| p.value.clone()).collect()
+}
+
+fn uint_property(node: &NodeDescription, property_name: &PropertyName) -> Option<u64> {
+    match node.properties.get(&property_name.value)?.property {
+        Property::ImmutableUintProp(ref p) => Some(p.prop),
+        Property::IncrementOnlyUintProp(ref p) => Some(p.prop),
+        Property::DecrementOnlyUintProp(ref p) => Some(p.prop),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rust_proto::graplinc::grapl::api::graph::v1beta1::{
+        ImmutableUintProp,
+        NodeProperty,
+    };
+
+    use super::*;
+
+    fn property_name(value: &str) -> PropertyName {
+        PropertyName {
+            value: value.to_string(),
+        }
+    }
+
+    fn session_identity() -> SessionIdentity {
+        SessionIdentity {
+            pseudo_key_properties: vec![property_name("process_id"), property_name("hostname")],
+            negation_key_properties: vec![],
+            creation_timestamp_property: property_name("created"),
+            last_seen_timestamp_property: property_name("last_seen"),
+            termination_timestamp_property: property_name("terminated"),
+        }
+    }
+
+    fn session(primary_key_properties: &[&str]) -> Session {
+        Session {
+            primary_key_properties: primary_key_properties
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            primary_key_requires_asset_id: false,
+            create_time: 10,
+            last_seen_time: 20,
+            terminate_time: 30,
+        }
+    }
+
+    fn node(timestamps: &[(&str, u64)]) -> NodeDescription {
+        NodeDescription {
+            properties: timestamps
+                .iter()
+                .map(|(name, prop)| {
+                    (
+                        name.to_string(),
+                        NodeProperty {
+                            property: ImmutableUintProp { prop: *prop }.into(),
+                        },
+                    )
+                })
+                .collect::<HashMap<_, _>>(),
+            node_key: "node-key".to_string(),
+            node_type: "Process".to_string(),
+            id_strategy: vec![],
+        }
+    }
+
+    #[test]
+    fn check_strategy_accepts_matching_keys() {
+        let deployed = IdentityAlgorithm::Session(session_identity());
+        let embedded = Strategy::Session(session(&["process_id", "hostname"]));
+
+        assert!(check_strategy("Process", &deployed, &embedded).is_ok());
+    }
+
+    #[test]
+    fn check_strategy_rejects_algorithm_mismatch() {
+        let deployed = IdentityAlgorithm::Session(session_identity());
+        let embedded = Strategy::Static(Static {
+            primary_key_properties: vec!["process_id".to_string(), "hostname".to_string()],
+            primary_key_requires_asset_id: false,
+        });
+
+        assert!(matches!(
+            check_strategy("Process", &deployed, &embedded),
+            Err(IdentityArgumentsError::AlgorithmMismatch {
+                deployed: "session",
+                embedded: "static",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn check_strategy_rejects_key_mismatch() {
+        let deployed = IdentityAlgorithm::Static(StaticIdentity {
+            static_key_properties: vec![property_name("path")],
+        });
+        let embedded = Strategy::Static(Static {
+            primary_key_properties: vec!["file_path".to_string()],
+            primary_key_requires_asset_id: false,
+        });
+
+        assert!(matches!(
+            check_strategy("File", &deployed, &embedded),
+            Err(IdentityArgumentsError::KeyPropertiesMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn check_strategy_rejects_reordered_keys() {
+        let deployed = IdentityAlgorithm::Session(session_identity());
+        let embedded = Strategy::Session(session(&["hostname", "process_id"]));
+
+        assert!(matches!(
+            check_strategy("Process", &deployed, &embedded),
+            Err(IdentityArgumentsError::KeyPropertiesMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn session_from_identity_prefers_node_properties() -> Result<(), IdentityArgumentsError> {
+        let node = node(&[("created", 1), ("last_seen", 2)]);
+        let embedded = session(&["process_id", "hostname"]);
+
+        let session = session_from_identity(&session_identity(), &node, Some(&embedded))?;
+
+        assert_eq!(
+            session.primary_key_properties,
+            vec!["process_id".to_string(), "hostname".to_string()]
+        );
+        assert_eq!(session.create_time, 1);
+        assert_eq!(session.last_seen_time, 2);
+        assert_eq!(session.terminate_time, 30);
+        Ok(())
+    }
+
+    #[test]
+    fn session_from_identity_rejects_missing_timestamp() {
+        let node = node(&[("created", 1), ("last_seen", 2)]);
+
+        assert!(matches!(
+            session_from_identity(&session_identity(), &node, None),
+            Err(IdentityArgumentsError::MissingTimestamp { property_name, .. })
+                if property_name == "terminated"
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+};
+
+use async_trait::async_trait;
+use failure::Error;
+use rust_proto::graplinc::grapl::common::v1beta1::types::Uid;
+
+use crate::sessions::{
+    Session,
+    UnidSession,
+};
+
+pub mod dynamodb;
+pub mod scylla;
+
+/// Where sessions and static mappings are persisted.
+///
+/// Sessions are keyed by `(pseudo_key, create_time)`, and are looked up by
+/// the nearest `create_time` on either side of a timestamp. The identification
+/// logic built on top of these lookups lives in `SessionDb` and
+/// `StaticMappingDb`, so every backend must behave identically; the
+/// conformance suite in `tests/identity_store_conformance.rs` checks that
+/// they do.
+#[async_trait]
+pub trait IdentityStore: Clone + Send + Sync + 'static {
+    /// The session with the earliest `create_time` at or after
+    /// `unid.timestamp`
+    async fn find_first_session_after(&self, unid: &UnidSession) -> Result<Option<Session>, Error>;
+
+    /// The session with the latest `create_time` at or before
+    /// `unid.timestamp`
+    async fn find_last_session_before(&self, unid: &UnidSession) -> Result<Option<Session>, Error>;
+
+    /// Stores `session`. Fails if a session with the same `pseudo_key` and
+    /// `create_time` is already stored.
+    async fn create_session(&self, session: &Session) -> Result<(), Error>;
+
+    /// Atomically replaces `session` with a copy that has the new
+    /// `create_time` and `is_create_canon`, and an incremented version
+    async fn update_session_create_time(
+        &self,
+        session: &Session,
+        new_time: u64,
+        is_canon: bool,
+    ) -> Result<(), Error>;
+
+    /// Marks the session's `create_time` as canonical. Fails if the stored
+    /// session's version no longer matches `session.version`.
+    async fn make_create_time_canonical(&self, session: &Session) -> Result<(), Error>;
+
+    /// Sets the session's `end_time` and `is_end_canon`. Fails if the stored
+    /// session's version no longer matches `session.version`.
+    async fn update_session_end_time(
+        &self,
+        session: &Session,
+        new_time: u64,
+        is_canon: bool,
+    ) -> Result<(), Error>;
+
+    /// Deletes the session. Fails if the stored session's version no longer
+    /// matches `session.version`.
+    async fn delete_session(&self, session: &Session) -> Result<(), Error>;
+
+    /// The uids stored for many static ids. Static ids with no stored uid are
+    /// absent from the returned map.
+    async fn get_static_uids(&self, static_ids: Vec<String>)
+        -> Result<HashMap<String, Uid>, Error>;
+
+    async fn put_static_uid(&self, static_id: String, uid: Uid) -> Result<(), Error>;
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown identity store {0}, expected dynamodb or scylla")]
+pub struct UnknownIdentityStoreBackend(String);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentityStoreBackend {
+    DynamoDb,
+    Scylla,
+}
+
+impl FromStr for IdentityStoreBackend {
+    type Err = UnknownIdentityStoreBackend;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dynamodb" => Ok(Self::DynamoDb),
+            "scylla" => Ok(Self::Scylla),
+            other => Err(UnknownIdentityStoreBackend(other.to_owned())),
+        }
+    }
+}
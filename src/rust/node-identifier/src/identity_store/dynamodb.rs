@@ -0,0 +1,448 @@
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use failure::{
+    bail,
+    Error,
+};
+use hmap::hmap;
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{
+    AttributeValue,
+    BatchGetItemInput,
+    Delete,
+    DeleteItemInput,
+    DynamoDb,
+    KeysAndAttributes,
+    Put,
+    PutItemInput,
+    QueryInput,
+    TransactWriteItem,
+    TransactWriteItemsInput,
+    UpdateItemInput,
+};
+use rust_proto::graplinc::grapl::common::v1beta1::types::Uid;
+use tracing::info;
+
+use super::IdentityStore;
+use crate::sessions::{
+    Session,
+    UnidSession,
+};
+
+/// DynamoDB rejects a BatchGetItem request with more keys than this
+const MAX_BATCH_GET_KEYS: usize = 100;
+
+/// How many times keys left unprocessed by a throttled BatchGetItem request
+/// are retried before giving up
+const MAX_BATCH_GET_ATTEMPTS: u32 = 5;
+
+/// Sessions and static mappings stored in two DynamoDB tables. The session
+/// table's hash key is `pseudo_key` and its range key is `create_time`; the
+/// static mapping table's hash key is `static_id`.
+#[derive(Debug, Clone)]
+pub struct DynamoDbIdentityStore<D>
+where
+    D: DynamoDb,
+{
+    dynamo: D,
+    session_table_name: String,
+    static_mapping_table_name: String,
+}
+
+impl<D> DynamoDbIdentityStore<D>
+where
+    D: DynamoDb,
+{
+    pub fn new(
+        dynamo: D,
+        session_table_name: impl Into<String>,
+        static_mapping_table_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            dynamo,
+            session_table_name: session_table_name.into(),
+            static_mapping_table_name: static_mapping_table_name.into(),
+        }
+    }
+
+    fn session_key(session: &Session) -> HashMap<String, AttributeValue> {
+        hmap! {
+            "pseudo_key".to_owned() => AttributeValue {
+                s: session.pseudo_key.clone().into(),
+                ..Default::default()
+            },
+            "create_time".to_owned() => AttributeValue {
+                n: session.create_time.to_string().into(),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Applies `update_expression` to the session, if its version still
+    /// matches `session.version`. The version is incremented, and is bound
+    /// to `:version` and `:new_version` for use in the expression.
+    async fn update_session_if_unchanged(
+        &self,
+        session: &Session,
+        update_expression: &str,
+        mut expression_attribute_values: HashMap<String, AttributeValue>,
+    ) -> Result<(), Error> {
+        expression_attribute_values.insert(
+            ":version".to_owned(),
+            AttributeValue {
+                n: session.version.to_string().into(),
+                ..Default::default()
+            },
+        );
+        expression_attribute_values.insert(
+            ":new_version".to_owned(),
+            AttributeValue {
+                n: (session.version + 1).to_string().into(),
+                ..Default::default()
+            },
+        );
+
+        let upd_req = UpdateItemInput {
+            key: Self::session_key(session),
+            table_name: self.session_table_name.clone(),
+            update_expression: Some(format!("{update_expression}, version = :new_version")),
+            condition_expression: Some("version = :version".into()),
+            expression_attribute_values: Some(expression_attribute_values),
+            ..Default::default()
+        };
+
+        self.dynamo.update_item(upd_req).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<D> IdentityStore for DynamoDbIdentityStore<D>
+where
+    D: DynamoDb,
+{
+    #[tracing::instrument(skip(self, unid), err)]
+    async fn find_first_session_after(&self, unid: &UnidSession) -> Result<Option<Session>, Error> {
+        info!(message="Finding first session after", table_name=?&self.session_table_name);
+        let query = QueryInput {
+            consistent_read: Some(true),
+            limit: Some(1),
+            table_name: self.session_table_name.clone(),
+            key_condition_expression: Some(
+                "pseudo_key = :pseudo_key AND create_time >= :create_time".into(),
+            ),
+            expression_attribute_values: Some(hmap! {
+                ":pseudo_key".to_owned() => AttributeValue {
+                    s: unid.pseudo_key.clone().into(),
+                    ..Default::default()
+                },
+                ":create_time".to_owned() => AttributeValue {
+                    n: unid.timestamp.to_string().into(),
+                    ..Default::default()
+                }
+            }),
+            ..Default::default()
+        };
+
+        let res = self.dynamo.query(query).await;
+        if let Err(RusotoError::Unknown(ref e)) = res {
+            bail!("Query failed with error: {:?}", e);
+        };
+
+        if let Some(items) = res?.items {
+            match &items[..] {
+                [] => Ok(None),
+                [item] => Session::try_from(item.clone()).map(Option::from),
+                _ => bail!("Unexpected number of items returned"),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[tracing::instrument(skip(self, unid), err)]
+    async fn find_last_session_before(&self, unid: &UnidSession) -> Result<Option<Session>, Error> {
+        info!(message = "Finding last session before");
+        let query = QueryInput {
+            consistent_read: Some(true),
+            limit: Some(1),
+            scan_index_forward: Some(false),
+            table_name: self.session_table_name.clone(),
+            key_condition_expression: Some(
+                "pseudo_key = :pseudo_key AND create_time <= :create_time".into(),
+            ),
+            expression_attribute_values: Some(hmap! {
+                ":pseudo_key".to_owned() => AttributeValue {
+                    s: unid.pseudo_key.clone().into(),
+                    ..Default::default()
+                },
+                ":create_time".to_owned() => AttributeValue {
+                    n: unid.timestamp.to_string().into(),
+                    ..Default::default()
+                }
+            }),
+            ..Default::default()
+        };
+
+        let res = self.dynamo.query(query).await?;
+
+        if let Some(items) = res.items {
+            match &items[..] {
+                [] => Ok(None),
+                [item] => Session::try_from(item.clone()).map(Option::from),
+                _ => bail!("Unexpected number of items returned"),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn create_session(&self, session: &Session) -> Result<(), Error> {
+        let put_req = PutItemInput {
+            item: serde_dynamodb::to_hashmap(session).unwrap(),
+            table_name: self.session_table_name.clone(),
+            condition_expression: Some("attribute_not_exists(pseudo_key)".into()),
+            ..Default::default()
+        };
+
+        self.dynamo.put_item(put_req).await?;
+
+        Ok(())
+    }
+
+    // `create_time` is the sort key in the table, so updating it is not possible.
+    // Instead, in one transaction, the row must be deleted and recreated with the
+    // new create_time. The delete is conditioned on the session's version, so
+    // the whole transaction fails if the session has changed
+    #[tracing::instrument(skip(self, session), err)]
+    async fn update_session_create_time(
+        &self,
+        session: &Session,
+        new_time: u64,
+        is_canon: bool,
+    ) -> Result<(), Error> {
+        info!(message = "Updating session create time");
+        let mut new_session = session.to_owned();
+        new_session.create_time = new_time;
+        new_session.is_create_canon = is_canon;
+        new_session.version += 1;
+        // Create new session with new create_time, increment version
+
+        let put_req = Put {
+            item: serde_dynamodb::to_hashmap(&new_session).unwrap(),
+            table_name: self.session_table_name.clone(),
+            ..Default::default()
+        };
+
+        let del_req = Delete {
+            key: Self::session_key(session),
+            table_name: self.session_table_name.clone(),
+            condition_expression: Some("version = :version".into()),
+            expression_attribute_values: Some(hmap! {
+                ":version".to_owned() => AttributeValue {
+                    n: session.version.to_string().into(),
+                    ..Default::default()
+                }
+            }),
+            ..Default::default()
+        };
+
+        self.dynamo
+            .transact_write_items(TransactWriteItemsInput {
+                transact_items: vec![
+                    TransactWriteItem {
+                        delete: del_req.into(),
+                        ..Default::default()
+                    },
+                    TransactWriteItem {
+                        put: put_req.into(),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn make_create_time_canonical(&self, session: &Session) -> Result<(), Error> {
+        info!(message = "Making session create time canonical");
+        self.update_session_if_unchanged(
+            session,
+            "SET is_create_canon = :is_create_canon",
+            hmap! {
+                ":is_create_canon".to_owned() => AttributeValue {
+                    bool: true.into(),
+                    ..Default::default()
+                }
+            },
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn update_session_end_time(
+        &self,
+        session: &Session,
+        new_time: u64,
+        is_canon: bool,
+    ) -> Result<(), Error> {
+        info!(message = "Updating session end time");
+        self.update_session_if_unchanged(
+            session,
+            "SET end_time = :end_time, is_end_canon = :is_end_canon",
+            hmap! {
+                ":end_time".to_owned() => AttributeValue {
+                    n: new_time.to_string().into(),
+                    ..Default::default()
+                },
+                ":is_end_canon".to_owned() => AttributeValue {
+                    bool: is_canon.into(),
+                    ..Default::default()
+                }
+            },
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn delete_session(&self, session: &Session) -> Result<(), Error> {
+        let del_req = DeleteItemInput {
+            key: Self::session_key(session),
+            table_name: self.session_table_name.clone(),
+            condition_expression: Some("version = :version".into()),
+            expression_attribute_values: Some(hmap! {
+                ":version".to_owned() => AttributeValue {
+                    n: session.version.to_string().into(),
+                    ..Default::default()
+                }
+            }),
+            ..Default::default()
+        };
+
+        self.dynamo.delete_item(del_req).await?;
+        Ok(())
+    }
+
+    /// Uses as few BatchGetItem requests as possible
+    async fn get_static_uids(
+        &self,
+        static_ids: Vec<String>,
+    ) -> Result<HashMap<String, Uid>, Error> {
+        let mut uids = HashMap::with_capacity(static_ids.len());
+
+        for chunk in static_ids.chunks(MAX_BATCH_GET_KEYS) {
+            let keys = chunk
+                .iter()
+                .map(|static_id| {
+                    HashMap::from([(
+                        "static_id".to_string(),
+                        AttributeValue {
+                            s: Some(static_id.clone()),
+                            ..Default::default()
+                        },
+                    )])
+                })
+                .collect();
+
+            let mut request_items = HashMap::from([(
+                self.static_mapping_table_name.clone(),
+                KeysAndAttributes {
+                    attributes_to_get: Some(vec!["static_id".to_string(), "uid".to_string()]),
+                    consistent_read: Some(true),
+                    keys,
+                    ..Default::default()
+                },
+            )]);
+
+            let mut attempt = 0;
+            while !request_items.is_empty() {
+                if attempt == MAX_BATCH_GET_ATTEMPTS {
+                    bail!(
+                        "static mapping lookups were still unprocessed after {} attempts",
+                        attempt
+                    );
+                }
+                if attempt > 0 {
+                    // unprocessed keys are the result of throttling, so back off
+                    tokio::time::sleep(Duration::from_millis(50 * 2u64.pow(attempt))).await;
+                }
+                attempt += 1;
+
+                let response = self
+                    .dynamo
+                    .batch_get_item(BatchGetItemInput {
+                        request_items,
+                        ..Default::default()
+                    })
+                    .await?;
+
+                let items = response
+                    .responses
+                    .and_then(|mut responses| responses.remove(&self.static_mapping_table_name))
+                    .unwrap_or_default();
+
+                for mut item in items {
+                    let static_id = item.remove("static_id").and_then(|v| v.s);
+                    let uid = item.remove("uid").and_then(|v| v.n);
+                    if let (Some(static_id), Some(uid)) = (static_id, uid) {
+                        let uid = uid.parse::<u64>()?;
+                        match Uid::from_u64(uid) {
+                            Some(uid) => uids.insert(static_id, uid),
+                            None => bail!("Invalid uid: {}", uid),
+                        };
+                    }
+                }
+
+                request_items = response
+                    .unprocessed_keys
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|(_, keys_and_attributes)| !keys_and_attributes.keys.is_empty())
+                    .collect();
+            }
+        }
+
+        Ok(uids)
+    }
+
+    async fn put_static_uid(&self, static_id: String, uid: Uid) -> Result<(), Error> {
+        let item = HashMap::from([
+            (
+                "static_id".to_string(),
+                AttributeValue {
+                    s: Some(static_id),
+                    ..Default::default()
+                },
+            ),
+            (
+                "uid".to_string(),
+                AttributeValue {
+                    n: Some(uid.as_u64().to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        // todo: Consider a `condition_expression` here to ensure the uid is not already set
+        //       and if it is, retrieve it from dynamodb instead
+        self.dynamo
+            .put_item(PutItemInput {
+                table_name: self.static_mapping_table_name.clone(),
+                item,
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(())
+    }
+}
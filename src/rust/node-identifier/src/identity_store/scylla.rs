@@ -0,0 +1,361 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use failure::{
+    bail,
+    Error,
+};
+use rust_proto::graplinc::grapl::common::v1beta1::types::Uid;
+use scylla::{
+    batch::Batch,
+    query::Query,
+    CachingSession,
+    QueryResult,
+    Session as ScyllaSession,
+};
+use tracing::info;
+
+use super::IdentityStore;
+use crate::sessions::{
+    Session,
+    UnidSession,
+};
+
+const SESSIONS_TABLE_NAME: &str = "identity_sessions";
+const STATIC_MAPPINGS_TABLE_NAME: &str = "identity_static_mappings";
+
+/// How many static ids are looked up by a single `IN` query
+const MAX_STATIC_ID_LOOKUPS: usize = 100;
+
+const SESSION_COLUMNS: &str =
+    "session_id, pseudo_key, create_time, end_time, is_create_canon, is_end_canon, version";
+
+type SessionRow = (i64, String, i64, i64, bool, bool, i64);
+
+/// Sessions and static mappings stored in a Scylla keyspace.
+///
+/// Sessions are partitioned by `pseudo_key` and clustered by `create_time`,
+/// so both session lookups read a single partition, and a session is moved
+/// to a new `create_time` by a single-partition batch, which Scylla applies
+/// atomically and in isolation. Every write of a session is a lightweight
+/// transaction: a new session is only inserted if none exists at its
+/// `create_time`, and every update or delete of an existing one is
+/// conditioned on its version. Mixing lightweight transactions with plain
+/// writes to the same rows would leave the plain writes unordered with them.
+///
+/// Scylla has no unsigned integers, so `u64`s are stored as the `bigint` with
+/// the same bits.
+#[derive(Clone)]
+pub struct ScyllaIdentityStore {
+    scylla_client: Arc<CachingSession>,
+    keyspace: String,
+}
+
+impl Debug for ScyllaIdentityStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScyllaIdentityStore")
+            .field("keyspace", &self.keyspace)
+            .finish()
+    }
+}
+
+impl ScyllaIdentityStore {
+    /// The keyspace's tables are created by scylla-provisioner's migrations
+    pub fn new(session: ScyllaSession, keyspace: String) -> Self {
+        Self {
+            scylla_client: Arc::new(CachingSession::from(session, 100)),
+            keyspace,
+        }
+    }
+
+    async fn find_session(
+        &self,
+        query: String,
+        unid: &UnidSession,
+    ) -> Result<Option<Session>, Error> {
+        let mut query = Query::new(query);
+        query.set_is_idempotent(true);
+
+        let query_result = self
+            .scylla_client
+            .execute(query, &(&unid.pseudo_key, unid.timestamp as i64))
+            .await?;
+        match query_result.rows_typed_or_empty::<SessionRow>().next() {
+            Some(row) => Ok(Some(session_from_row(row?))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityStore for ScyllaIdentityStore {
+    #[tracing::instrument(skip(self, unid), err)]
+    async fn find_first_session_after(&self, unid: &UnidSession) -> Result<Option<Session>, Error> {
+        info!(message = "Finding first session after", keyspace = %self.keyspace);
+        self.find_session(
+            format!(
+                "SELECT {SESSION_COLUMNS} FROM {}.{SESSIONS_TABLE_NAME} \
+                WHERE pseudo_key = ? AND create_time >= ? \
+                ORDER BY create_time ASC LIMIT 1",
+                self.keyspace
+            ),
+            unid,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self, unid), err)]
+    async fn find_last_session_before(&self, unid: &UnidSession) -> Result<Option<Session>, Error> {
+        info!(message = "Finding last session before", keyspace = %self.keyspace);
+        self.find_session(
+            format!(
+                "SELECT {SESSION_COLUMNS} FROM {}.{SESSIONS_TABLE_NAME} \
+                WHERE pseudo_key = ? AND create_time <= ? \
+                ORDER BY create_time DESC LIMIT 1",
+                self.keyspace
+            ),
+            unid,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn create_session(&self, session: &Session) -> Result<(), Error> {
+        let query = Query::new(format!(
+            "INSERT INTO {}.{SESSIONS_TABLE_NAME} ({SESSION_COLUMNS}) \
+            VALUES (?, ?, ?, ?, ?, ?, ?) IF NOT EXISTS",
+            self.keyspace
+        ));
+
+        let query_result = self
+            .scylla_client
+            .execute(query, session_values(session))
+            .await?;
+        if !is_applied(query_result)? {
+            bail!(
+                "Session {} already exists at create time {}",
+                session.pseudo_key,
+                session.create_time
+            );
+        }
+        Ok(())
+    }
+
+    // `create_time` is a clustering column, so updating it is not possible.
+    // Instead, in one conditional batch, the row is deleted and recreated with
+    // the new create_time
+    #[tracing::instrument(skip(self, session), err)]
+    async fn update_session_create_time(
+        &self,
+        session: &Session,
+        new_time: u64,
+        is_canon: bool,
+    ) -> Result<(), Error> {
+        info!(message = "Updating session create time");
+        let mut new_session = session.to_owned();
+        new_session.create_time = new_time;
+        new_session.is_create_canon = is_canon;
+        new_session.version += 1;
+
+        let mut batch: Batch = Default::default();
+        batch.append_statement(Query::new(format!(
+            "DELETE FROM {}.{SESSIONS_TABLE_NAME} \
+            WHERE pseudo_key = ? AND create_time = ? IF version = ?",
+            self.keyspace
+        )));
+        batch.append_statement(Query::new(format!(
+            "INSERT INTO {}.{SESSIONS_TABLE_NAME} ({SESSION_COLUMNS}) \
+            VALUES (?, ?, ?, ?, ?, ?, ?)",
+            self.keyspace
+        )));
+
+        let query_result = self
+            .scylla_client
+            .batch(
+                &batch,
+                (
+                    (
+                        &session.pseudo_key,
+                        session.create_time as i64,
+                        session.version as i64,
+                    ),
+                    session_values(&new_session),
+                ),
+            )
+            .await?;
+        check_applied(query_result, session)
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn make_create_time_canonical(&self, session: &Session) -> Result<(), Error> {
+        info!(message = "Making session create time canonical");
+        let query = Query::new(format!(
+            "UPDATE {}.{SESSIONS_TABLE_NAME} SET is_create_canon = true, version = ? \
+            WHERE pseudo_key = ? AND create_time = ? IF version = ?",
+            self.keyspace
+        ));
+
+        let query_result = self
+            .scylla_client
+            .execute(
+                query,
+                (
+                    (session.version + 1) as i64,
+                    &session.pseudo_key,
+                    session.create_time as i64,
+                    session.version as i64,
+                ),
+            )
+            .await?;
+        check_applied(query_result, session)
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn update_session_end_time(
+        &self,
+        session: &Session,
+        new_time: u64,
+        is_canon: bool,
+    ) -> Result<(), Error> {
+        info!(message = "Updating session end time");
+        let query = Query::new(format!(
+            "UPDATE {}.{SESSIONS_TABLE_NAME} SET end_time = ?, is_end_canon = ?, version = ? \
+            WHERE pseudo_key = ? AND create_time = ? IF version = ?",
+            self.keyspace
+        ));
+
+        let query_result = self
+            .scylla_client
+            .execute(
+                query,
+                (
+                    new_time as i64,
+                    is_canon,
+                    (session.version + 1) as i64,
+                    &session.pseudo_key,
+                    session.create_time as i64,
+                    session.version as i64,
+                ),
+            )
+            .await?;
+        check_applied(query_result, session)
+    }
+
+    #[tracing::instrument(skip(self, session), err)]
+    async fn delete_session(&self, session: &Session) -> Result<(), Error> {
+        let query = Query::new(format!(
+            "DELETE FROM {}.{SESSIONS_TABLE_NAME} \
+            WHERE pseudo_key = ? AND create_time = ? IF version = ?",
+            self.keyspace
+        ));
+
+        let query_result = self
+            .scylla_client
+            .execute(
+                query,
+                (
+                    &session.pseudo_key,
+                    session.create_time as i64,
+                    session.version as i64,
+                ),
+            )
+            .await?;
+        check_applied(query_result, session)
+    }
+
+    async fn get_static_uids(
+        &self,
+        static_ids: Vec<String>,
+    ) -> Result<HashMap<String, Uid>, Error> {
+        let mut uids = HashMap::with_capacity(static_ids.len());
+
+        for chunk in static_ids.chunks(MAX_STATIC_ID_LOOKUPS) {
+            let mut query = Query::new(format!(
+                "SELECT static_id, uid FROM {}.{STATIC_MAPPINGS_TABLE_NAME} WHERE static_id IN ?",
+                self.keyspace
+            ));
+            query.set_is_idempotent(true);
+
+            let query_result = self.scylla_client.execute(query, (chunk.to_vec(),)).await?;
+            for row in query_result.rows_typed_or_empty::<(String, i64)>() {
+                let (static_id, uid) = row?;
+                match Uid::from_i64(uid) {
+                    Some(uid) => uids.insert(static_id, uid),
+                    None => bail!("Invalid uid: {}", uid),
+                };
+            }
+        }
+
+        Ok(uids)
+    }
+
+    async fn put_static_uid(&self, static_id: String, uid: Uid) -> Result<(), Error> {
+        let mut query = Query::new(format!(
+            "INSERT INTO {}.{STATIC_MAPPINGS_TABLE_NAME} (static_id, uid) VALUES (?, ?)",
+            self.keyspace
+        ));
+        query.set_is_idempotent(true);
+
+        self.scylla_client
+            .execute(query, (static_id, uid.as_i64()))
+            .await?;
+        Ok(())
+    }
+}
+
+fn session_values(session: &Session) -> (i64, &str, i64, i64, bool, bool, i64) {
+    (
+        session.session_id as i64,
+        session.pseudo_key.as_str(),
+        session.create_time as i64,
+        session.end_time as i64,
+        session.is_create_canon,
+        session.is_end_canon,
+        session.version as i64,
+    )
+}
+
+fn session_from_row(row: SessionRow) -> Session {
+    let (session_id, pseudo_key, create_time, end_time, is_create_canon, is_end_canon, version) =
+        row;
+    Session {
+        session_id: session_id as u64,
+        pseudo_key,
+        create_time: create_time as u64,
+        end_time: end_time as u64,
+        is_create_canon,
+        is_end_canon,
+        version: version as u64,
+    }
+}
+
+/// Whether a lightweight transaction was applied. `[applied]` is always the
+/// first column of its result.
+fn is_applied(query_result: QueryResult) -> Result<bool, Error> {
+    let applied = query_result
+        .rows
+        .and_then(|rows| rows.into_iter().next())
+        .and_then(|row| row.columns.into_iter().next().flatten())
+        .and_then(|applied| applied.as_boolean());
+
+    match applied {
+        Some(applied) => Ok(applied),
+        None => bail!("Malformed lightweight transaction result"),
+    }
+}
+
+/// Fails unless a version-conditioned write was applied
+fn check_applied(query_result: QueryResult, session: &Session) -> Result<(), Error> {
+    if !is_applied(query_result)? {
+        bail!(
+            "Session {} was modified since version {} was read",
+            session.pseudo_key,
+            session.version
+        );
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 #![recursion_limit = "256"]
 
 pub mod config;
+pub mod identity_store;
 pub mod sessiondb;
 pub mod sessions;
 pub mod static_mapping_db;
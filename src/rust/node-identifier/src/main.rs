@@ -1,5 +1,7 @@
 #![recursion_limit = "1024"]
 
+use std::time::Duration;
+
 use clap::Parser;
 use figment::{
     providers::Env,
@@ -15,6 +17,7 @@ use kafka::{
     config::{
         ConsumerConfig,
         ProducerConfig,
+        TransactionConfig,
     },
     StreamProcessor,
     StreamProcessorError,
@@ -28,6 +31,7 @@ use rust_proto::graplinc::grapl::{
             IdentifiedGraph,
         },
         graph_mutation::v1beta1::client::GraphMutationClient,
+        graph_schema_manager::v1beta1::client::GraphSchemaManagerClient,
     },
     pipeline::v1beta1::Envelope,
 };
@@ -40,6 +44,13 @@ use crate::{
     config::NodeIdentifierConfig,
     dynamic_sessiondb::NodeDescriptionIdentifier,
     error::NodeIdentifierError,
+    identity_arguments::IdentityArgumentsResolver,
+    identity_store::{
+        dynamodb::DynamoDbIdentityStore,
+        scylla::ScyllaIdentityStore,
+        IdentityStore,
+        IdentityStoreBackend,
+    },
     node_identifier::NodeIdentifier,
     sessiondb::SessionDb,
     static_mapping_db::StaticMappingDb,
@@ -48,6 +59,8 @@ use crate::{
 mod config;
 mod dynamic_sessiondb;
 mod error;
+mod identity_arguments;
+mod identity_store;
 mod node_identifier;
 mod sessiondb;
 mod sessions;
@@ -65,24 +78,77 @@ async fn main() -> eyre::Result<()> {
 #[tracing::instrument]
 async fn handler() -> eyre::Result<()> {
     let service_config = NodeIdentifierConfig::parse();
-    let dynamo = DynamoDbClient::from_env();
-    let dyn_session_db = SessionDb::new(dynamo.clone(), service_config.grapl_dynamic_session_table);
+
+    match service_config.node_identifier_identity_store {
+        IdentityStoreBackend::DynamoDb => {
+            let session_table = service_config
+                .grapl_dynamic_session_table
+                .clone()
+                .ok_or_else(|| {
+                    eyre::eyre!("the dynamodb identity store requires GRAPL_DYNAMIC_SESSION_TABLE")
+                })?;
+            let static_mapping_table = service_config
+                .grapl_static_mapping_table
+                .clone()
+                .ok_or_else(|| {
+                    eyre::eyre!("the dynamodb identity store requires GRAPL_STATIC_MAPPING_TABLE")
+                })?;
+            let store = DynamoDbIdentityStore::new(
+                DynamoDbClient::from_env(),
+                session_table,
+                static_mapping_table,
+            );
+            run(service_config, store).await
+        }
+        IdentityStoreBackend::Scylla => {
+            let scylla_config = &service_config.scylla_config;
+            let mut session_config = scylla::SessionConfig::new();
+            session_config.add_known_nodes_addr(&scylla_config.graph_db_addresses[..]);
+            session_config.auth_username = scylla_config.graph_db_auth_username.clone();
+            session_config.auth_password = scylla_config.graph_db_auth_password.clone();
+
+            let store = ScyllaIdentityStore::new(
+                scylla::Session::connect(session_config).await?,
+                scylla_config.node_identifier_scylla_keyspace.clone(),
+            );
+            run(service_config, store).await
+        }
+    }
+}
+
+#[tracing::instrument(skip(service_config, store))]
+async fn run<S: IdentityStore>(service_config: NodeIdentifierConfig, store: S) -> eyre::Result<()> {
+    let dyn_session_db = SessionDb::new(store.clone());
     let graph_mutation_client_config = Figment::new()
         .merge(Env::prefixed("GRAPH_MUTATION_CLIENT_"))
         .extract()?;
     let graph_mutation_client = GraphMutationClient::connect(graph_mutation_client_config).await?;
 
     let static_mapping_db = StaticMappingDb::new(
-        dynamo.clone(),
+        store,
         graph_mutation_client.clone(),
-        service_config.grapl_static_mapping_table,
+        service_config.node_identifier_static_mapping_cache_size,
+    );
+
+    let graph_schema_manager_client_config = Figment::new()
+        .merge(Env::prefixed("GRAPH_SCHEMA_MANAGER_CLIENT_"))
+        .extract()?;
+    let graph_schema_manager_client =
+        GraphSchemaManagerClient::connect(graph_schema_manager_client_config).await?;
+
+    let identity_arguments_resolver = IdentityArgumentsResolver::new(
+        graph_schema_manager_client,
+        service_config.node_identifier_identity_arguments_cache_size,
+        Duration::from_millis(service_config.node_identifier_identity_arguments_cache_ttl_ms),
     );
 
     let node_identifier = NodeIdentifier::new(NodeDescriptionIdentifier::new(
         dyn_session_db,
         graph_mutation_client,
         static_mapping_db,
+        identity_arguments_resolver,
         true,
+        service_config.node_identifier_max_concurrency,
     ));
     let consumer_config = ConsumerConfig::parse();
     let producer_config = ProducerConfig::parse();
@@ -96,7 +162,20 @@ async fn handler() -> eyre::Result<()> {
     // TODO: also construct a stream processor for retries
 
     let stream_processor: StreamProcessor<GraphDescription, IdentifiedGraph> =
-        StreamProcessor::new(consumer_config, producer_config)?;
+        match TransactionConfig::from_env_vars() {
+            Some(transaction_config) => {
+                tracing::info!(
+                    message = "Using Kafka transactions",
+                    transactional_id = %transaction_config.transactional_id,
+                );
+                StreamProcessor::new_transactional(
+                    consumer_config,
+                    producer_config,
+                    transaction_config,
+                )?
+            }
+            None => StreamProcessor::new(consumer_config, producer_config)?,
+        };
 
     tracing::info!(message = "Kafka StreamProcessor configured successfully");
 
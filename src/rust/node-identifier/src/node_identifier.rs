@@ -1,7 +1,5 @@
 use std::collections::HashMap;
 
-use failure::Error;
-use grapl_utils::rusoto_ext::dynamodb::GraplDynamoDbClientExt;
 use rust_proto::graplinc::grapl::{
     api::graph::v1beta1::{
         GraphDescription,
@@ -14,8 +12,12 @@ use rust_proto::graplinc::grapl::{
 use tap::tap::TapOptional;
 
 use crate::{
-    dynamic_sessiondb::NodeDescriptionIdentifier,
+    dynamic_sessiondb::{
+        Identity,
+        NodeDescriptionIdentifier,
+    },
     error::NodeIdentifierError,
+    identity_store::IdentityStore,
 };
 
 /**
@@ -32,36 +34,27 @@ use crate::{
       for nodes with canonical and unique identifiers (e.g. aws events)
 */
 #[derive(Clone)]
-pub(crate) struct NodeIdentifier<D>
+pub(crate) struct NodeIdentifier<S>
 where
-    D: GraplDynamoDbClientExt,
+    S: IdentityStore,
 {
-    dynamic_identifier: NodeDescriptionIdentifier<D>,
+    dynamic_identifier: NodeDescriptionIdentifier<S>,
 }
 
-impl<D> NodeIdentifier<D>
+impl<S> NodeIdentifier<S>
 where
-    D: GraplDynamoDbClientExt,
+    S: IdentityStore,
 {
-    pub(crate) fn new(dynamic_identifier: NodeDescriptionIdentifier<D>) -> Self {
+    pub(crate) fn new(dynamic_identifier: NodeDescriptionIdentifier<S>) -> Self {
         Self { dynamic_identifier }
     }
 
-    // todo: We should be yielding IdentifiedNode's here
-    #[tracing::instrument(fields(node_key=?node.node_key), skip(self, node))]
-    async fn attribute_node_key(
-        &self,
-        tenant_id: uuid::Uuid,
-        node: &NodeDescription,
-    ) -> Result<IdentifiedNode, Error> {
-        self.dynamic_identifier
-            .attribute_dynamic_node(tenant_id, node)
-            .await
-    }
-
     /// Performs batch identification of unidentified nodes into identified
     /// nodes.
     ///
+    /// Nodes which share an identity are only identified once, and all of
+    /// the graph's identities are resolved concurrently.
+    ///
     /// A map of unidentified node keys to identified node keys will be returned
     /// in addition to the last error, if any, that occurred while identifying
     /// nodes.
@@ -75,11 +68,18 @@ where
         let mut identified_nodekey_map = HashMap::new();
         let mut attribution_failure = None;
 
-        // new method
+        let mut nodes_by_identity: HashMap<Identity, Vec<(&String, &NodeDescription)>> =
+            HashMap::new();
         for (unidentified_node_key, unidentified_node) in unidentified_subgraph.nodes.iter() {
-            let identified_node = match self.attribute_node_key(tenant_id, unidentified_node).await
+            match self
+                .dynamic_identifier
+                .identity(tenant_id, unidentified_node)
+                .await
             {
-                Ok(identified_node) => identified_node,
+                Ok(identity) => nodes_by_identity
+                    .entry(identity)
+                    .or_default()
+                    .push((unidentified_node_key, unidentified_node)),
                 Err(e) => {
                     tracing::warn!(
                         message="Failed to attribute node_key",
@@ -87,12 +87,45 @@ where
                         error=?e
                     );
                     attribution_failure = Some(e);
-                    continue;
                 }
-            };
+            }
+        }
 
-            identified_nodekey_map.insert(unidentified_node_key.to_owned(), identified_node.uid);
-            identified_graph.add_node(identified_node);
+        tracing::debug!(
+            message = "Deduplicated node identities",
+            node_count = unidentified_subgraph.nodes.len(),
+            identity_count = nodes_by_identity.len(),
+        );
+
+        let resolved = self
+            .dynamic_identifier
+            .resolve_identities(tenant_id, nodes_by_identity.keys().cloned().collect())
+            .await;
+
+        for (identity, result) in resolved {
+            let nodes = nodes_by_identity.remove(&identity).unwrap_or_default();
+            match result {
+                Ok(uid) => {
+                    for (unidentified_node_key, unidentified_node) in nodes {
+                        identified_nodekey_map.insert(unidentified_node_key.to_owned(), uid);
+                        identified_graph.add_node(IdentifiedNode {
+                            properties: unidentified_node.properties.clone(),
+                            uid,
+                            node_type: unidentified_node.node_type.clone(),
+                        });
+                    }
+                }
+                Err(e) => {
+                    for (unidentified_node_key, _) in nodes {
+                        tracing::warn!(
+                            message="Failed to attribute node_key",
+                            node_key=?unidentified_node_key,
+                            error=?e
+                        );
+                    }
+                    attribution_failure = Some(e);
+                }
+            }
         }
 
         (identified_nodekey_map, attribution_failure)
@@ -143,15 +176,16 @@ where
                                 to_key=?edge.to_node_key
                             )
                         )
-                        .map(|identified_to_edge| (identified_to_edge, &edge.edge_name))
+                        .map(|identified_to_edge| (identified_to_edge, edge))
                 });
 
-            // add all identified edges into the `identified_graph`
-            for (identified_to_key, edge_name) in identified_edges {
-                identified_graph.add_edge(
-                    edge_name.to_owned(),
+            // add all identified edges, with their properties, into the `identified_graph`
+            for (identified_to_key, edge) in identified_edges {
+                identified_graph.add_edge_with_properties(
+                    edge.edge_name.to_owned(),
                     identified_from_key.to_owned(),
                     identified_to_key.to_owned(),
+                    edge.properties.clone(),
                 );
             }
         }
@@ -1,24 +1,7 @@
-use std::convert::TryFrom;
-
 use failure::{
     bail,
     Error,
 };
-use hmap::hmap;
-use rusoto_core::RusotoError;
-use rusoto_dynamodb::{
-    AttributeValue,
-    AttributeValueUpdate,
-    Delete,
-    DeleteItemInput,
-    DynamoDb,
-    Put,
-    PutItemInput,
-    QueryInput,
-    TransactWriteItem,
-    TransactWriteItemsInput,
-    UpdateItemInput,
-};
 use rust_proto::graplinc::grapl::{
     api::graph_mutation::v1beta1::{
         client::GraphMutationClient,
@@ -35,319 +18,27 @@ use tracing::{
 };
 use uuid::Uuid;
 
-use crate::sessions::*;
+use crate::{
+    identity_store::IdentityStore,
+    sessions::*,
+};
 
+/// Identifies nodes by the sessions stored in an `IdentityStore`
 #[derive(Debug, Clone)]
-pub struct SessionDb<D>
+pub struct SessionDb<S>
 where
-    D: DynamoDb,
+    S: IdentityStore,
 {
-    dynamo: D,
-    table_name: String,
+    store: S,
 }
 
-impl<D> SessionDb<D>
+impl<S> SessionDb<S>
 where
-    D: DynamoDb,
+    S: IdentityStore,
 {
     // exposed for integration tests
-    pub fn new(dynamo: D, table_name: impl Into<String>) -> Self {
-        Self {
-            dynamo,
-            table_name: table_name.into(),
-        }
-    }
-
-    #[tracing::instrument(skip(self, unid), err)]
-    pub(crate) async fn find_first_session_after(
-        &self,
-        unid: &UnidSession,
-    ) -> Result<Option<Session>, Error> {
-        info!(message="Finding first session after", table_name=?&self.table_name);
-        let query = QueryInput {
-            consistent_read: Some(true),
-            limit: Some(1),
-            table_name: self.table_name.clone(),
-            key_condition_expression: Some(
-                "pseudo_key = :pseudo_key AND create_time >= :create_time".into(),
-            ),
-            expression_attribute_values: Some(hmap! {
-                ":pseudo_key".to_owned() => AttributeValue {
-                    s: unid.pseudo_key.clone().into(),
-                    ..Default::default()
-                },
-                ":create_time".to_owned() => AttributeValue {
-                    n: unid.timestamp.to_string().into(),
-                    ..Default::default()
-                }
-            }),
-            ..Default::default()
-        };
-
-        let res = self.dynamo.query(query).await;
-        if let Err(RusotoError::Unknown(ref e)) = res {
-            bail!("Query failed with error: {:?}", e);
-        };
-
-        if let Some(items) = res?.items {
-            match &items[..] {
-                [] => Ok(None),
-                [item] => Session::try_from(item.clone()).map(Option::from),
-                _ => bail!("Unexpected number of items returned"),
-            }
-        } else {
-            Ok(None)
-        }
-    }
-
-    #[tracing::instrument(skip(self, unid), err)]
-    pub(crate) async fn find_last_session_before(
-        &self,
-        unid: &UnidSession,
-    ) -> Result<Option<Session>, Error> {
-        info!(message = "Finding last session before");
-        let query = QueryInput {
-            consistent_read: Some(true),
-            limit: Some(1),
-            scan_index_forward: Some(false),
-            table_name: self.table_name.clone(),
-            key_condition_expression: Some(
-                "pseudo_key = :pseudo_key AND create_time <= :create_time".into(),
-            ),
-            expression_attribute_values: Some(hmap! {
-                ":pseudo_key".to_owned() => AttributeValue {
-                    s: unid.pseudo_key.clone().into(),
-                    ..Default::default()
-                },
-                ":create_time".to_owned() => AttributeValue {
-                    n: unid.timestamp.to_string().into(),
-                    ..Default::default()
-                }
-            }),
-            ..Default::default()
-        };
-
-        let res = self.dynamo.query(query).await?;
-
-        if let Some(items) = res.items {
-            match &items[..] {
-                [] => Ok(None),
-                [item] => Session::try_from(item.clone()).map(Option::from),
-                _ => bail!("Unexpected number of items returned"),
-            }
-        } else {
-            Ok(None)
-        }
-    }
-
-    // `create_time` is the sort key in the table, so updating it is not possible.
-    // Instead, in one transaction, the row must be deleted and recreated with the
-    // new create_time
-    // This method assumes that the `session` passed in has already been modified
-    #[tracing::instrument(skip(self, session), err)]
-    pub(crate) async fn update_session_create_time(
-        &self,
-        session: &Session,
-        new_time: u64,
-        is_canon: bool,
-    ) -> Result<(), Error> {
-        info!(message = "Updating session create time");
-        let mut new_session = session.to_owned();
-        new_session.create_time = new_time;
-        new_session.is_create_canon = is_canon;
-        new_session.version += 1;
-        // Create new session with new create_time, increment version
-
-        let put_req = Put {
-            item: serde_dynamodb::to_hashmap(&new_session).unwrap(),
-            table_name: self.table_name.clone(),
-            ..Default::default()
-        };
-
-        let del_req = Delete {
-            key: hmap! {
-                "pseudo_key".to_owned() => AttributeValue {
-                    s: session.pseudo_key.clone().into(),
-                    ..Default::default()
-                },
-                "create_time".to_owned() => AttributeValue {
-                    n: session.create_time.to_string().into(),
-                    ..Default::default()
-                }
-            },
-            table_name: self.table_name.clone(),
-            ..Default::default()
-        };
-
-        self.dynamo
-            .transact_write_items(TransactWriteItemsInput {
-                transact_items: vec![
-                    TransactWriteItem {
-                        delete: del_req.into(),
-                        ..Default::default()
-                    },
-                    TransactWriteItem {
-                        put: put_req.into(),
-                        ..Default::default()
-                    },
-                ],
-                ..Default::default()
-            })
-            .await?;
-
-        Ok(())
-    }
-
-    // disabled while 'guessing' is disabled
-    // https://github.com/grapl-security/issue-tracker/issues/1002
-    #[allow(dead_code)]
-    #[tracing::instrument(skip(self, session), err)]
-    pub(crate) async fn make_create_time_canonical(&self, session: &Session) -> Result<(), Error> {
-        info!(message = "Updating session end time");
-        // Use version as a constraint
-        let upd_req = UpdateItemInput {
-            key: hmap! {
-                "pseudo_key".to_owned() => AttributeValue {
-                    s: session.pseudo_key.clone().into(),
-                    ..Default::default()
-                },
-                "create_time".to_owned() => AttributeValue {
-                    n: session.create_time.to_string().into(),
-                    ..Default::default()
-                }
-            },
-            attribute_updates: Some(hmap! {
-                "is_create_canon".to_owned() => AttributeValueUpdate {
-                    value: Some(AttributeValue {
-                            bool: true.into(),
-                            ..Default::default()
-                        }),
-                    ..Default::default()
-                },
-                "version".to_owned() => AttributeValueUpdate {
-                    value: Some(AttributeValue {
-                        n: (session.version + 1).to_string().into(),
-                        ..Default::default()
-                    }),
-                    ..Default::default()
-                }
-            }),
-            table_name: self.table_name.clone(),
-            condition_expression: Some("version = :version".into()),
-            expression_attribute_values: Some(hmap! {
-                ":version".to_owned() => AttributeValue {
-                    n: session.version.to_string().into(),
-                    ..Default::default()
-                }
-            }),
-            ..Default::default()
-        };
-
-        self.dynamo.update_item(upd_req).await?;
-
-        Ok(())
-    }
-
-    // disabled while 'guessing' is disabled
-    // https://github.com/grapl-security/issue-tracker/issues/1002
-    #[allow(dead_code)]
-    // Update version, and use it as a constraint
-    #[tracing::instrument(skip(self, session), err)]
-    pub(crate) async fn update_session_end_time(
-        &self,
-        session: &Session,
-        new_time: u64,
-        is_canon: bool,
-    ) -> Result<(), Error> {
-        info!(message = "Updating session end time");
-        // Use version as a constraint
-        let upd_req = UpdateItemInput {
-            key: hmap! {
-                "pseudo_key".to_owned() => AttributeValue {
-                    s: session.pseudo_key.clone().into(),
-                    ..Default::default()
-                },
-                "create_time".to_owned() => AttributeValue {
-                    n: session.create_time.to_string().into(),
-                    ..Default::default()
-                }
-            },
-            attribute_updates: Some(hmap! {
-                "end_time".to_owned() => AttributeValueUpdate {
-                    value: Some(AttributeValue {
-                        n: new_time.to_string().into(),
-                        ..Default::default()
-                    }),
-                    ..Default::default()
-                },
-                "is_end_canon".to_owned() => AttributeValueUpdate {
-                    value: Some(AttributeValue {
-                            bool: is_canon.into(),
-                            ..Default::default()
-                        }),
-                    ..Default::default()
-                },
-                "version".to_owned() => AttributeValueUpdate {
-                    value: Some(AttributeValue {
-                        n: (session.version + 1).to_string().into(),
-                        ..Default::default()
-                    }),
-                    ..Default::default()
-
-                }
-            }),
-            table_name: self.table_name.clone(),
-            condition_expression: Some("version = :version".into()),
-            expression_attribute_values: Some(hmap! {
-                ":version".to_owned() => AttributeValue {
-                    n: session.version.to_string().into(),
-                    ..Default::default()
-                }
-            }),
-            ..Default::default()
-        };
-
-        self.dynamo.update_item(upd_req).await?;
-
-        Ok(())
-    }
-
-    // exposed for integration tests
-    #[tracing::instrument(skip(self, session), err)]
-    pub async fn create_session(&self, session: &Session) -> Result<(), Error> {
-        let put_req = PutItemInput {
-            item: serde_dynamodb::to_hashmap(session).unwrap(),
-            table_name: self.table_name.clone(),
-            ..Default::default()
-        };
-
-        self.dynamo.put_item(put_req).await?;
-
-        Ok(())
-    }
-
-    // disabled while 'guessing' is disabled
-    // https://github.com/grapl-security/issue-tracker/issues/1002
-    #[allow(dead_code)]
-    #[tracing::instrument(skip(self, session), err)]
-    pub(crate) async fn delete_session(&self, session: &Session) -> Result<(), Error> {
-        let del_req = DeleteItemInput {
-            key: hmap! {
-                "pseudo_key".to_owned() => AttributeValue {
-                    s: session.pseudo_key.clone().into(),
-                    ..Default::default()
-                },
-                "create_time".to_owned() => AttributeValue {
-                    n: session.create_time.to_string().into(),
-                    ..Default::default()
-                }
-            },
-            table_name: self.table_name.clone(),
-            ..Default::default()
-        };
-
-        self.dynamo.delete_item(del_req).await?;
-        Ok(())
+    pub fn new(store: S) -> Self {
+        Self { store }
     }
 
     #[tracing::instrument(skip(self, unid, graph_mutation_client), err)]
@@ -363,7 +54,7 @@ where
         );
 
         // Look for first session where session.create_time >= unid.create_time
-        let session = self.find_first_session_after(&unid).await?;
+        let session = self.store.find_first_session_after(&unid).await?;
 
         if let Some(session) = session {
             let session_id = match Uid::from_u64(session.session_id) {
@@ -375,7 +66,8 @@ where
             // and we should consider this the canonical ID for that session
             if !session.is_create_canon && session.create_time != unid.timestamp {
                 info!(message = "Extending session create_time");
-                self.update_session_create_time(&session, unid.timestamp, true)
+                self.store
+                    .update_session_create_time(&session, unid.timestamp, true)
                     .await?;
                 return Ok(session_id);
             }
@@ -407,7 +99,7 @@ where
         }
 
         // Look for last session where session.create_time <= unid.create_time
-        let session = self.find_last_session_before(&unid).await?;
+        let session = self.store.find_last_session_before(&unid).await?;
 
         if let Some(session) = session {
             // If session.end_time >= unid.create_time (indicates overlapping sessions, error)
@@ -421,7 +113,7 @@ where
                     session, unid
                 );
                 // if session.end_time is NOT canonical, we can update it
-                //                self.update_session_end_time(&session, unid.timestamp - 100, session.is_end_canon)?;
+                //                self.store.update_session_end_time(&session, unid.timestamp - 100, session.is_end_canon)?;
             }
         }
 
@@ -448,7 +140,7 @@ where
         };
 
         info!(message = "Creating session");
-        self.create_session(&session).await?;
+        self.store.create_session(&session).await?;
         Ok(uid)
     }
 
@@ -467,7 +159,7 @@ where
 
         // Look for session where session.create_time <= unid.create_time <= session.end_time
         // Look for last session where session.create_time <= unid.create_time
-        let session = self.find_last_session_before(&unid).await?;
+        let session = self.store.find_last_session_before(&unid).await?;
         if let Some(mut session) = session {
             let session_id = match Uid::from_u64(session.session_id) {
                 Some(uid) => uid,
@@ -481,13 +173,13 @@ where
             if !session.is_end_canon {
                 session.end_time = unid.timestamp;
                 info!(message = "Updating session end_time.");
-                //                self.update_session_end_time(&session, unid.timestamp, false)?;
+                //                self.store.update_session_end_time(&session, unid.timestamp, false)?;
 
                 return Ok(session_id);
             }
         }
 
-        let session = self.find_first_session_after(&unid).await?;
+        let session = self.store.find_first_session_after(&unid).await?;
         if let Some(session) = session {
             if !session.is_create_canon {
                 info!(message = "Found a later, non canonical session. Extending create_time.");
@@ -496,7 +188,8 @@ where
                     None => bail!("Invalid session_id: {}", session.session_id),
                 };
 
-                self.update_session_create_time(&session, unid.timestamp, false)
+                self.store
+                    .update_session_create_time(&session, unid.timestamp, false)
                     .await?;
                 return Ok(session_id);
             }
@@ -525,7 +218,7 @@ where
                 version: 0,
                 pseudo_key: unid.pseudo_key,
             };
-            self.create_session(&session).await?;
+            self.store.create_session(&session).await?;
 
             Ok(uid)
         } else {
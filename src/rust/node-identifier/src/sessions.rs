@@ -17,7 +17,7 @@ use serde::{
 
 type Blake2b16 = Blake2b<U16>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UnidSession {
     pub pseudo_key: String,
     pub node_type: String,
@@ -47,7 +47,7 @@ impl UnidSession {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Session {
     pub session_id: u64,
     pub pseudo_key: String,
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::HashSet,
     fmt::Debug,
 };
 
@@ -8,14 +8,8 @@ use blake2::{
     Blake2b,
     Digest,
 };
-use rusoto_dynamodb::{
-    AttributeValue,
-    DynamoDb,
-    GetItemInput,
-    GetItemOutput,
-    PutItemInput,
-    PutItemOutput,
-};
+use futures::StreamExt;
+use moka::future::Cache;
 use rust_proto::graplinc::grapl::{
     api::{
         graph::v1beta1::{
@@ -33,137 +27,166 @@ use rust_proto::graplinc::grapl::{
     },
 };
 
+use crate::identity_store::IdentityStore;
+
 type Blake2b16 = Blake2b<U16>;
 
+/// A node that should be identified via a static mapping. Nodes with the same
+/// static id are the same node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnidStatic {
+    pub static_id: String,
+    pub node_type: String,
+}
+
+impl UnidStatic {
+    pub fn new(
+        tenant_id: uuid::Uuid,
+        node: &NodeDescription,
+        strategy: &Static,
+    ) -> Result<Self, failure::Error> {
+        Ok(Self {
+            static_id: get_static_id(tenant_id, node, strategy)?,
+            node_type: node.node_type.clone(),
+        })
+    }
+}
+
 #[derive(Clone)]
-pub struct StaticMappingDb<D> {
-    static_mapping_db: D,
+pub struct StaticMappingDb<S> {
+    store: S,
     graph_mutation_client: GraphMutationClient,
-    table_name: String,
+    // A static id always maps to the same uid, so cached entries never go
+    // stale.
+    cache: Cache<String, Uid>,
 }
 
-impl<D> Debug for StaticMappingDb<D> {
+impl<S> Debug for StaticMappingDb<S>
+where
+    S: Debug,
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("StaticMappingDb")
+            .field("store", &self.store)
             .field("graph_mutation_client", &self.graph_mutation_client)
-            .field("table_name", &self.table_name)
+            .field("cache_entries", &self.cache.entry_count())
             .finish()
     }
 }
 
-impl<D> StaticMappingDb<D>
+impl<S> StaticMappingDb<S>
 where
-    D: DynamoDb,
+    S: IdentityStore,
 {
-    pub fn new(
-        static_mapping_db: D,
-        uid_allocator_client: GraphMutationClient,
-        table_name: String,
-    ) -> Self {
+    pub fn new(store: S, uid_allocator_client: GraphMutationClient, cache_size: u64) -> Self {
         Self {
-            static_mapping_db,
+            store,
             graph_mutation_client: uid_allocator_client,
-            table_name,
+            cache: Cache::new(cache_size),
         }
     }
 
-    pub async fn map_unid(
+    /// Identifies many nodes at once. Cached mappings are used where possible,
+    /// the rest are read from the identity store in batches, and nodes which have never
+    /// been seen before are created with at most `max_concurrency` requests in
+    /// flight.
+    ///
+    /// Every distinct `UnidStatic` is returned exactly once, along with its uid
+    /// or the error that prevented it from being identified.
+    #[tracing::instrument(skip(self, unids), fields(unid_count = unids.len()))]
+    pub async fn map_unids(
         &self,
         tenant_id: uuid::Uuid,
-        node: &NodeDescription,
-        strategy: &Static,
-    ) -> Result<Uid, failure::Error> {
-        let static_id = get_static_id(tenant_id, node, strategy)?;
+        unids: Vec<UnidStatic>,
+        max_concurrency: usize,
+    ) -> Vec<(UnidStatic, Result<Uid, failure::Error>)> {
+        let unids: HashSet<UnidStatic> = unids.into_iter().collect();
+
+        let mut results = Vec::with_capacity(unids.len());
+        let mut uncached = vec![];
+        for unid in unids {
+            match self.cache.get(&unid.static_id) {
+                Some(uid) => results.push((unid, Ok(uid))),
+                None => uncached.push(unid),
+            }
+        }
+
+        tracing::debug!(
+            message = "Checked static mapping cache",
+            cache_hits = results.len(),
+            cache_misses = uncached.len(),
+        );
+
+        if uncached.is_empty() {
+            return results;
+        }
+
+        let static_ids = uncached.iter().map(|unid| unid.static_id.clone()).collect();
+        let mut stored = match self.store.get_static_uids(static_ids).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                // The error can't be cloned, so each node gets its own copy of
+                // the message.
+                let message = format!("failed to retrieve static mappings: {}", e);
+                results.extend(
+                    uncached
+                        .into_iter()
+                        .map(|unid| (unid, Err(failure::err_msg(message.clone())))),
+                );
+                return results;
+            }
+        };
 
-        if let Some(uid) = self.retrieve_uid_from_dynamodb(static_id.clone()).await? {
-            return Ok(uid);
+        let mut unmapped = vec![];
+        for unid in uncached {
+            match stored.remove(&unid.static_id) {
+                Some(uid) => {
+                    self.cache.insert(unid.static_id.clone(), uid).await;
+                    results.push((unid, Ok(uid)));
+                }
+                None => unmapped.push(unid),
+            }
         }
 
+        let created: Vec<(UnidStatic, Result<Uid, failure::Error>)> =
+            futures::stream::iter(unmapped)
+                .map(|unid| async move {
+                    let result = self.create_mapping(tenant_id, &unid).await;
+                    if let Ok(uid) = result {
+                        self.cache.insert(unid.static_id.clone(), uid).await;
+                    }
+                    (unid, result)
+                })
+                .buffer_unordered(max_concurrency)
+                .collect()
+                .await;
+
+        results.extend(created);
+        results
+    }
+
+    async fn create_mapping(
+        &self,
+        tenant_id: uuid::Uuid,
+        unid: &UnidStatic,
+    ) -> Result<Uid, failure::Error> {
         let mut graph_mutation_client = self.graph_mutation_client.clone();
         let uid = graph_mutation_client
             .create_node(CreateNodeRequest {
                 tenant_id,
                 node_type: NodeType {
-                    value: node.node_type.clone(),
+                    value: unid.node_type.clone(),
                 },
             })
             .await?
             .uid;
 
         // todo: Retry this operation if it fails.
-        self.store_uid_in_dynamodb(static_id, uid).await?;
-
-        Ok(uid)
-    }
-
-    pub async fn store_uid_in_dynamodb(
-        &self,
-        static_id: String,
-        uid: Uid,
-    ) -> Result<(), failure::Error> {
-        let key = HashMap::from([
-            (
-                "static_id".to_string(),
-                AttributeValue {
-                    s: Some(static_id),
-                    ..Default::default()
-                },
-            ),
-            (
-                "uid".to_string(),
-                AttributeValue {
-                    n: Some(uid.as_u64().to_string()),
-                    ..Default::default()
-                },
-            ),
-        ]);
-
-        // todo: Consider a `condition_expression` here to ensure the uid is not already set
-        //       and if it is, retrieve it from dynamodb instead
-        let _response: PutItemOutput = self
-            .static_mapping_db
-            .put_item(PutItemInput {
-                table_name: self.table_name.clone(),
-                item: key,
-                ..Default::default()
-            })
+        self.store
+            .put_static_uid(unid.static_id.clone(), uid)
             .await?;
 
-        Ok(())
-    }
-
-    pub async fn retrieve_uid_from_dynamodb(
-        &self,
-        static_id: String,
-    ) -> Result<Option<Uid>, failure::Error> {
-        let key = HashMap::from([(
-            "static_id".to_string(),
-            AttributeValue {
-                s: Some(static_id),
-                ..Default::default()
-            },
-        )]);
-        let response: GetItemOutput = self
-            .static_mapping_db
-            .get_item(GetItemInput {
-                attributes_to_get: Some(vec!["uid".to_string()]),
-                consistent_read: Some(true),
-                key,
-                table_name: self.table_name.clone(),
-                ..GetItemInput::default()
-            })
-            .await?;
-
-        let mut item = match response.item {
-            Some(item) => item,
-            None => return Ok(None),
-        };
-
-        Ok(item
-            .remove("uid")
-            .and_then(|v| v.n)
-            .map(|uid| uid.parse::<u64>().map(|uid| Uid::from_u64(uid).unwrap()))
-            .transpose()?)
+        Ok(uid)
     }
 }
 
@@ -0,0 +1,315 @@
+#![cfg(feature = "integration_tests")]
+
+//! Every `IdentityStore` backend runs the same checks, so that sessions and
+//! static mappings are identified the same way whichever one is deployed.
+
+use std::collections::HashMap;
+
+use clap::Parser;
+use failure::Error;
+use grapl_config::env_helpers::FromEnv;
+use node_identifier::{
+    config::ScyllaIdentityStoreConfig,
+    identity_store::{
+        dynamodb::DynamoDbIdentityStore,
+        scylla::ScyllaIdentityStore,
+        IdentityStore,
+    },
+    sessions::{
+        Session,
+        UnidSession,
+    },
+};
+use rusoto_dynamodb::DynamoDbClient;
+use rust_proto::graplinc::grapl::common::v1beta1::types::Uid;
+use scylla_provisioner::migrations::run_migrations;
+use uuid::Uuid;
+
+async fn dynamodb_store() -> Result<DynamoDbIdentityStore<DynamoDbClient>, Error> {
+    Ok(DynamoDbIdentityStore::new(
+        DynamoDbClient::from_env(),
+        std::env::var("GRAPL_DYNAMIC_SESSION_TABLE")?,
+        std::env::var("GRAPL_STATIC_MAPPING_TABLE")?,
+    ))
+}
+
+async fn scylla_store() -> Result<ScyllaIdentityStore, Error> {
+    let config = ScyllaIdentityStoreConfig::parse_from(["node-identifier"]);
+    let mut session_config = scylla::SessionConfig::new();
+    session_config.add_known_nodes_addr(&config.graph_db_addresses[..]);
+    session_config.auth_username = config.graph_db_auth_username;
+    session_config.auth_password = config.graph_db_auth_password;
+
+    let session = scylla::Session::connect(session_config).await?;
+
+    let keyspace = "node_identifier_conformance";
+    session
+        .query(
+            format!(
+                "CREATE KEYSPACE IF NOT EXISTS {keyspace} WITH REPLICATION = \
+                {{'class': 'SimpleStrategy', 'replication_factor': 1}}"
+            ),
+            &(),
+        )
+        .await?;
+    run_migrations(&session, keyspace).await?;
+
+    Ok(ScyllaIdentityStore::new(session, keyspace.to_owned()))
+}
+
+/// A pseudo key no other test uses
+fn new_unid(timestamp: u64) -> UnidSession {
+    UnidSession::new(
+        Uuid::new_v4(),
+        "Process".to_owned(),
+        "conformance",
+        timestamp,
+        true,
+    )
+}
+
+fn unid_at(unid: &UnidSession, timestamp: u64) -> UnidSession {
+    UnidSession {
+        timestamp,
+        ..unid.clone()
+    }
+}
+
+fn session(unid: &UnidSession, session_id: u64, create_time: u64) -> Session {
+    Session {
+        session_id,
+        pseudo_key: unid.pseudo_key.clone(),
+        create_time,
+        end_time: create_time + 101,
+        is_create_canon: true,
+        is_end_canon: false,
+        version: 0,
+    }
+}
+
+async fn check_find_sessions_around_a_timestamp(store: impl IdentityStore) -> Result<(), Error> {
+    let unid = new_unid(2000);
+    let sessions = [
+        session(&unid, 1, 1000),
+        session(&unid, 2, 2000),
+        session(&unid, 3, 3000),
+    ];
+    for session in sessions.iter() {
+        store.create_session(session).await?;
+    }
+    // Sessions under other pseudo keys are never found
+    let other_unid = new_unid(2000);
+    store.create_session(&session(&other_unid, 4, 1500)).await?;
+
+    let first_after = |timestamp| {
+        let store = store.clone();
+        let unid = unid_at(&unid, timestamp);
+        async move { store.find_first_session_after(&unid).await }
+    };
+    let last_before = |timestamp| {
+        let store = store.clone();
+        let unid = unid_at(&unid, timestamp);
+        async move { store.find_last_session_before(&unid).await }
+    };
+
+    assert_eq!(first_after(500).await?, Some(sessions[0].clone()));
+    assert_eq!(first_after(1500).await?, Some(sessions[1].clone()));
+    assert_eq!(first_after(2000).await?, Some(sessions[1].clone()));
+    assert_eq!(first_after(3500).await?, None);
+
+    assert_eq!(last_before(500).await?, None);
+    assert_eq!(last_before(2000).await?, Some(sessions[1].clone()));
+    assert_eq!(last_before(2500).await?, Some(sessions[1].clone()));
+    assert_eq!(last_before(3500).await?, Some(sessions[2].clone()));
+
+    // A stored session is never replaced by a new one at its create time
+    assert!(store
+        .create_session(&session(&unid, 5, 2000))
+        .await
+        .is_err());
+    assert_eq!(last_before(2000).await?, Some(sessions[1].clone()));
+
+    Ok(())
+}
+
+async fn check_update_session_create_time(store: impl IdentityStore) -> Result<(), Error> {
+    let unid = new_unid(2000);
+    let guessed = Session {
+        is_create_canon: false,
+        ..session(&unid, 1, 2000)
+    };
+    store.create_session(&guessed).await?;
+
+    store
+        .update_session_create_time(&guessed, 1500, true)
+        .await?;
+
+    let updated = Session {
+        create_time: 1500,
+        is_create_canon: true,
+        version: 1,
+        ..guessed.clone()
+    };
+    assert_eq!(
+        store
+            .find_last_session_before(&unid_at(&unid, 1900))
+            .await?,
+        Some(updated.clone())
+    );
+    // The session at the old create_time is gone
+    assert_eq!(
+        store
+            .find_first_session_after(&unid_at(&unid, 1600))
+            .await?,
+        None
+    );
+    assert_eq!(
+        store.find_first_session_after(&unid_at(&unid, 0)).await?,
+        Some(updated.clone())
+    );
+
+    // Moves based on a version that's since changed are rejected
+    assert!(store
+        .update_session_create_time(&guessed, 1000, false)
+        .await
+        .is_err());
+    assert_eq!(
+        store.find_first_session_after(&unid_at(&unid, 0)).await?,
+        Some(updated)
+    );
+
+    Ok(())
+}
+
+async fn check_version_conditioned_updates(store: impl IdentityStore) -> Result<(), Error> {
+    let unid = new_unid(2000);
+    let stale = Session {
+        is_create_canon: false,
+        ..session(&unid, 1, 2000)
+    };
+    store.create_session(&stale).await?;
+
+    store.update_session_end_time(&stale, 5000, true).await?;
+    let current = Session {
+        end_time: 5000,
+        is_end_canon: true,
+        version: 1,
+        ..stale.clone()
+    };
+    assert_eq!(
+        store.find_last_session_before(&unid).await?,
+        Some(current.clone())
+    );
+
+    // Updates based on a version that's since changed are rejected
+    assert!(store
+        .update_session_end_time(&stale, 6000, false)
+        .await
+        .is_err());
+    assert!(store.make_create_time_canonical(&stale).await.is_err());
+    assert_eq!(
+        store.find_last_session_before(&unid).await?,
+        Some(current.clone())
+    );
+
+    store.make_create_time_canonical(&current).await?;
+    assert_eq!(
+        store.find_last_session_before(&unid).await?,
+        Some(Session {
+            is_create_canon: true,
+            version: 2,
+            ..current
+        })
+    );
+
+    Ok(())
+}
+
+async fn check_delete_session(store: impl IdentityStore) -> Result<(), Error> {
+    let unid = new_unid(2000);
+    let session = session(&unid, 1, 2000);
+    store.create_session(&session).await?;
+
+    // Deletes based on a version that's since changed are rejected
+    store.update_session_end_time(&session, 5000, true).await?;
+    assert!(store.delete_session(&session).await.is_err());
+    let session = Session {
+        end_time: 5000,
+        is_end_canon: true,
+        version: 1,
+        ..session
+    };
+    assert_eq!(
+        store.find_last_session_before(&unid).await?,
+        Some(session.clone())
+    );
+
+    store.delete_session(&session).await?;
+
+    assert_eq!(store.find_last_session_before(&unid).await?, None);
+    assert_eq!(
+        store.find_first_session_after(&unid_at(&unid, 0)).await?,
+        None
+    );
+
+    Ok(())
+}
+
+async fn check_static_mappings(store: impl IdentityStore) -> Result<(), Error> {
+    let prefix = Uuid::new_v4();
+    // More than a single lookup request can hold
+    let mappings: HashMap<String, Uid> = (1..=150)
+        .map(|i| (format!("{prefix}-{i}"), Uid::from_u64(i).unwrap()))
+        .collect();
+
+    assert!(store
+        .get_static_uids(mappings.keys().cloned().collect())
+        .await?
+        .is_empty());
+
+    for (static_id, uid) in mappings.iter() {
+        store.put_static_uid(static_id.clone(), *uid).await?;
+    }
+
+    let mut static_ids: Vec<String> = mappings.keys().cloned().collect();
+    static_ids.push(format!("{prefix}-unmapped"));
+    assert_eq!(store.get_static_uids(static_ids).await?, mappings);
+
+    Ok(())
+}
+
+macro_rules! conformance_suite {
+    ($backend:ident, $store:expr) => {
+        mod $backend {
+            use super::*;
+
+            #[tokio::test]
+            async fn test_find_sessions_around_a_timestamp() -> Result<(), Error> {
+                check_find_sessions_around_a_timestamp($store.await?).await
+            }
+
+            #[tokio::test]
+            async fn test_update_session_create_time() -> Result<(), Error> {
+                check_update_session_create_time($store.await?).await
+            }
+
+            #[tokio::test]
+            async fn test_version_conditioned_updates() -> Result<(), Error> {
+                check_version_conditioned_updates($store.await?).await
+            }
+
+            #[tokio::test]
+            async fn test_delete_session() -> Result<(), Error> {
+                check_delete_session($store.await?).await
+            }
+
+            #[tokio::test]
+            async fn test_static_mappings() -> Result<(), Error> {
+                check_static_mappings($store.await?).await
+            }
+        }
+    };
+}
+
+conformance_suite!(dynamodb_identity_store, dynamodb_store());
+conformance_suite!(scylla_identity_store, scylla_store());
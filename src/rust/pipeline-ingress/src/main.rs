@@ -1,6 +1,7 @@
 use std::{
     env::VarError,
     num::ParseIntError,
+    str::FromStr,
     time::Duration,
 };
 
@@ -24,6 +25,8 @@ use rust_proto::graplinc::grapl::{
             },
             PublishRawLogRequest,
             PublishRawLogResponse,
+            PublishRawLogsRequest,
+            PublishRawLogsResponse,
         },
         protocol::{
             error::ServeError,
@@ -33,8 +36,10 @@ use rust_proto::graplinc::grapl::{
     },
     pipeline::v1beta1::{
         Envelope,
+        PayloadEncoding,
         RawLog,
     },
+    SerDeError,
 };
 use thiserror::Error;
 use tokio::net::TcpListener;
@@ -43,35 +48,67 @@ use uuid::Uuid;
 #[non_exhaustive]
 #[derive(Debug, Error)]
 enum IngressApiError {
+    #[error("log event of {size} bytes exceeds the maximum of {max_size} bytes")]
+    PayloadTooLarge { size: usize, max_size: usize },
+
     #[error("failed to send message to kafka {0}")]
     ProducerError(#[from] ProducerError),
 }
 
 impl From<IngressApiError> for Status {
     fn from(e: IngressApiError) -> Self {
-        Status::unknown(e.to_string())
+        match e {
+            IngressApiError::PayloadTooLarge { .. } => Status::invalid_argument(e.to_string()),
+            IngressApiError::ProducerError(ProducerError::MessageTooLarge { .. }) => {
+                Status::invalid_argument(e.to_string())
+            }
+            e => Status::unknown(e.to_string()),
+        }
     }
 }
 
 struct IngressApi {
     producer: Producer<RawLog>,
+    payload_encoding: PayloadEncoding,
+    max_payload_size_bytes: usize,
 }
 
 impl IngressApi {
-    fn new(producer: Producer<RawLog>) -> Self {
-        IngressApi { producer }
+    fn new(
+        producer: Producer<RawLog>,
+        payload_encoding: PayloadEncoding,
+        max_payload_size_bytes: usize,
+    ) -> Self {
+        IngressApi {
+            producer,
+            payload_encoding,
+            max_payload_size_bytes,
+        }
     }
-}
 
-#[async_trait::async_trait]
-impl PipelineIngressApi for IngressApi {
-    type Error = IngressApiError;
+    // Large payloads are rejected rather than split: a RawLog is handed to a
+    // generator as a single unit, and we can't know where it's safe to split
+    // an arbitrary log format.
+    fn check_payload_size(&self, request: &PublishRawLogRequest) -> Result<(), IngressApiError> {
+        let size = request.log_event_len();
+        if size > self.max_payload_size_bytes {
+            tracing::warn!(
+                message = "rejecting oversized raw log",
+                tenant_id =% request.tenant_id(),
+                event_source_id =% request.event_source_id(),
+                size = size,
+                max_size = self.max_payload_size_bytes,
+            );
+            return Err(IngressApiError::PayloadTooLarge {
+                size,
+                max_size: self.max_payload_size_bytes,
+            });
+        }
 
-    #[tracing::instrument(skip(self))]
-    async fn publish_raw_log(
-        &self,
-        request: PublishRawLogRequest,
-    ) -> Result<PublishRawLogResponse, Self::Error> {
+        Ok(())
+    }
+
+    async fn publish(&self, request: PublishRawLogRequest) -> Result<(), IngressApiError> {
         let tenant_id = request.tenant_id();
         let event_source_id = request.event_source_id();
         // TODO: trace_id should be generated at the edge. This service is
@@ -88,16 +125,58 @@ impl PipelineIngressApi for IngressApi {
         );
 
         self.producer
-            .send(Envelope::new(
-                tenant_id,
-                trace_id,
-                event_source_id,
-                RawLog::new(request.log_event()),
-            ))
+            .send(
+                Envelope::new(
+                    tenant_id,
+                    trace_id,
+                    event_source_id,
+                    RawLog::new(request.log_event()),
+                )
+                .with_inner_message_encoding(self.payload_encoding),
+            )
             .await?;
 
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl PipelineIngressApi for IngressApi {
+    type Error = IngressApiError;
+
+    #[tracing::instrument(skip(self))]
+    async fn publish_raw_log(
+        &self,
+        request: PublishRawLogRequest,
+    ) -> Result<PublishRawLogResponse, Self::Error> {
+        self.check_payload_size(&request)?;
+        self.publish(request).await?;
+
         Ok(PublishRawLogResponse::ok())
     }
+
+    #[tracing::instrument(skip(self, request), fields(batch_size = request.len()))]
+    async fn publish_raw_logs(
+        &self,
+        request: PublishRawLogsRequest,
+    ) -> Result<PublishRawLogsResponse, Self::Error> {
+        let raw_logs = request.raw_logs();
+
+        // Validate the whole batch up front so that a batch with an oversized
+        // log publishes nothing, and the caller can drop or split it without
+        // duplicates.
+        for raw_log in raw_logs.iter() {
+            self.check_payload_size(raw_log)?;
+        }
+
+        // Each log is published on its own, so if publishing fails part way
+        // through, the logs already published stay published. Delivery of a
+        // retried batch is therefore at-least-once.
+        futures::future::try_join_all(raw_logs.into_iter().map(|raw_log| self.publish(raw_log)))
+            .await?;
+
+        Ok(PublishRawLogsResponse::ok())
+    }
 }
 
 #[non_exhaustive]
@@ -118,6 +197,9 @@ enum ConfigurationError {
     #[error("failed to parse integer value {0}")]
     ParseInt(#[from] ParseIntError),
 
+    #[error("failed to parse payload encoding {0}")]
+    PayloadEncoding(#[from] SerDeError),
+
     #[error("failed to configure tracing {0}")]
     SetupTracingError(#[from] SetupTracingError),
 }
@@ -129,6 +211,24 @@ async fn handler() -> Result<(), ConfigurationError> {
     let healthcheck_polling_interval_ms =
         std::env::var("PIPELINE_INGRESS_HEALTHCHECK_POLLING_INTERVAL_MS")?.parse()?;
 
+    let payload_encoding = match std::env::var("PIPELINE_INGRESS_PAYLOAD_ENCODING") {
+        Ok(payload_encoding) => PayloadEncoding::from_str(&payload_encoding)?,
+        Err(VarError::NotPresent) => PayloadEncoding::default(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let max_payload_size_bytes = match std::env::var("PIPELINE_INGRESS_MAX_PAYLOAD_SIZE_BYTES") {
+        Ok(max_payload_size_bytes) => max_payload_size_bytes.parse()?,
+        Err(VarError::NotPresent) => DEFAULT_MAX_PAYLOAD_SIZE_BYTES,
+        Err(e) => return Err(e.into()),
+    };
+
+    tracing::info!(
+        message = "configured raw log payloads",
+        payload_encoding =% payload_encoding,
+        max_payload_size_bytes = max_payload_size_bytes,
+    );
+
     let producer_config = ProducerConfig::parse();
 
     tracing::info!(
@@ -143,7 +243,7 @@ async fn handler() -> Result<(), ConfigurationError> {
         socket_address = %socket_address,
     );
     let (server, _shutdown_tx) = PipelineIngressServer::new(
-        IngressApi::new(producer),
+        IngressApi::new(producer, payload_encoding, max_payload_size_bytes),
         TcpListener::bind(socket_address.clone()).await?,
         || async { Ok(HealthcheckStatus::Serving) }, // FIXME: this is garbage
         Duration::from_millis(healthcheck_polling_interval_ms),
@@ -163,6 +263,10 @@ async fn handler() -> Result<(), ConfigurationError> {
 
 const SERVICE_NAME: &'static str = "pipeline-ingress";
 
+/// Kafka's default `message.max.bytes` is 1MB. Leave some headroom for the
+/// envelope's metadata.
+const DEFAULT_MAX_PAYLOAD_SIZE_BYTES: usize = 1_000_000 - 16 * 1024;
+
 #[tokio::main]
 async fn main() -> Result<(), ConfigurationError> {
     let _guard = setup_tracing(SERVICE_NAME)?;
@@ -82,8 +82,10 @@ use figment::{
     Provider,
 };
 use futures::{
+    stream::BoxStream,
     Future,
     Stream,
+    StreamExt,
 };
 use serde::{
     Deserialize,
@@ -553,4 +555,77 @@ where
 
         Ok(NU::try_from(response_proto)?)
     }
+
+    /// Execute a server streaming gRPC request against an underlying gRPC
+    /// client of type `C` generated by Tonic. This should be thought of as a
+    /// function which takes a native `ProtobufSerializable` type `NT` to a
+    /// stream of native `ProtobufSerializable` types `NU` via intermediate
+    /// `prost::Message` types `PT` and `PU`. The caller provides the code which
+    /// actually executes the gRPC API call on the Tonic client `C` in the form
+    /// of a `grpc_call` closure `F`.
+    /// \
+    /// # Params
+    /// \
+    /// - `request` -- The native `ProtobufSerializable` representation of a
+    ///   gRPC request.
+    ///
+    /// - `request_timeout` -- The max duration the request is allowed to take
+    ///    (gRPC timeout), including consuming the whole response stream. Like
+    ///    client streaming requests, these aren't automatically retried, since
+    ///    the caller may already have consumed part of the stream.
+    ///
+    /// - `grpc_call` -- A closure which executes the gRPC API call in terms of
+    ///    types `PT` and `PU` on the underlying Tonic client of type `C`.
+    /// \
+    /// # Example usage
+    /// \
+    /// See [api/graph_query/v1beta1/client.rs](../api/graph_query/v1beta1/client.rs).
+    /// \
+    /// ```ignore
+    /// pub async fn query_all_graphs_with_uid(
+    ///     &mut self,
+    ///     request_timeout: Duration,
+    ///     request: native::QueryAllGraphsWithUidRequest,
+    /// ) -> Result<BoxStream<'static, Result<native::QueryAllGraphsWithUidResponse, ClientError>>, ClientError> {
+    ///     self.client
+    ///         .execute_server_streaming(
+    ///             request,
+    ///             request_timeout,
+    ///             |mut client, request| async move { client.query_all_graphs_with_uid(request).await },
+    ///         )
+    ///         .await
+    /// }
+    /// ```
+    pub(crate) async fn execute_server_streaming<PT, NT, PU, NU, F, R>(
+        &self,
+        request: NT,
+        request_timeout: Duration,
+        mut grpc_call: F,
+    ) -> Result<BoxStream<'static, Result<NU, ClientError>>, ClientError>
+    where
+        PT: prost::Message + From<NT>,
+        NT: ProtobufSerializable<ProtobufMessage = PT> + TryFrom<PT>,
+        PU: prost::Message + 'static,
+        NU: ProtobufSerializable<ProtobufMessage = PU> + TryFrom<PU> + Send + 'static,
+        F: FnMut(C, tonic::Request<PT>) -> R,
+        R: Future<Output = Result<tonic::Response<tonic::Streaming<PU>>, tonic::Status>>,
+        ClientError: From<<NU as TryFrom<PU>>::Error>,
+    {
+        let proto_request = PT::try_from(request)?;
+        let mut tonic_request = tonic::Request::new(proto_request);
+        tonic_request.set_timeout(request_timeout);
+
+        let proto_stream = grpc_call(self.proto_client.clone(), tonic_request)
+            .in_current_span()
+            .await
+            .map_err(Status::from)?
+            .into_inner();
+
+        Ok(proto_stream
+            .map(|response_proto| -> Result<NU, ClientError> {
+                let response_proto = response_proto.map_err(Status::from)?;
+                Ok(NU::try_from(response_proto)?)
+            })
+            .boxed())
+    }
 }
@@ -1010,6 +1010,22 @@ where
     }
 }
 
+/// Merges `other` into `properties`, following each property's merge
+/// constraint
+fn merge_properties(
+    properties: &mut HashMap<String, NodeProperty>,
+    other: &HashMap<String, NodeProperty>,
+) {
+    for (prop_name, prop_value) in other.iter() {
+        match properties.get_mut(prop_name) {
+            Some(self_prop) => self_prop.merge(prop_value),
+            None => {
+                properties.insert(prop_name.clone(), prop_value.clone());
+            }
+        }
+    }
+}
+
 //
 // NodeDescription
 //
@@ -1040,15 +1056,7 @@ impl NodeDescription {
     pub fn merge(&mut self, other: &Self) {
         debug_assert_eq!(self.node_type, other.node_type);
         debug_assert_eq!(self.node_key, other.node_key);
-        for (prop_name, prop_value) in other.properties.iter() {
-            match self.properties.get_mut(prop_name) {
-                Some(self_prop) => self_prop.merge(prop_value),
-                None => {
-                    self.properties
-                        .insert(prop_name.clone(), prop_value.clone());
-                }
-            }
-        }
+        merge_properties(&mut self.properties, &other.properties);
     }
     pub fn get_node_key(&self) -> &str {
         self.node_key.as_str()
@@ -1134,15 +1142,7 @@ impl IdentifiedNode {
 
     pub fn merge(&mut self, other: &Self) {
         debug_assert_eq!(self.node_type, other.node_type);
-        for (prop_name, prop_value) in other.properties.iter() {
-            match self.properties.get_mut(prop_name) {
-                Some(self_prop) => self_prop.merge(prop_value),
-                None => {
-                    self.properties
-                        .insert(prop_name.clone(), prop_value.clone());
-                }
-            }
-        }
+        merge_properties(&mut self.properties, &other.properties);
     }
 }
 
@@ -1196,29 +1196,52 @@ impl serde_impl::ProtobufSerializable for IdentifiedNode {
 // Edge
 //
 
-#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Edge {
     pub to_node_key: String,
     pub from_node_key: String,
     pub edge_name: String,
+    pub properties: HashMap<String, NodeProperty>,
+}
+
+impl Edge {
+    pub fn merge(&mut self, other: &Self) {
+        debug_assert_eq!(self.edge_name, other.edge_name);
+        debug_assert_eq!(self.to_node_key, other.to_node_key);
+        merge_properties(&mut self.properties, &other.properties);
+    }
 }
 
-impl From<EdgeProto> for Edge {
-    fn from(edge_proto: EdgeProto) -> Self {
-        Edge {
+impl TryFrom<EdgeProto> for Edge {
+    type Error = SerDeError;
+
+    fn try_from(edge_proto: EdgeProto) -> Result<Self, Self::Error> {
+        let mut properties = HashMap::with_capacity(edge_proto.properties.len());
+        for (key, property) in edge_proto.properties {
+            properties.insert(key, NodeProperty::try_from(property)?);
+        }
+
+        Ok(Edge {
             to_node_key: edge_proto.to_node_key,
             from_node_key: edge_proto.from_node_key,
             edge_name: edge_proto.edge_name,
-        }
+            properties,
+        })
     }
 }
 
 impl From<Edge> for EdgeProto {
     fn from(edge: Edge) -> Self {
+        let mut properties = HashMap::with_capacity(edge.properties.len());
+        for (key, property) in edge.properties {
+            properties.insert(key, NodePropertyProto::from(property));
+        }
+
         EdgeProto {
             from_node_key: edge.from_node_key,
             to_node_key: edge.to_node_key,
             edge_name: edge.edge_name,
+            properties,
         }
     }
 }
@@ -1240,6 +1263,15 @@ pub struct IdentifiedEdge {
     pub from_uid: Uid,
     pub to_uid: Uid,
     pub edge_name: String,
+    pub properties: HashMap<String, NodeProperty>,
+}
+
+impl IdentifiedEdge {
+    pub fn merge(&mut self, other: &Self) {
+        debug_assert_eq!(self.edge_name, other.edge_name);
+        debug_assert_eq!(self.to_uid, other.to_uid);
+        merge_properties(&mut self.properties, &other.properties);
+    }
 }
 
 impl TryFrom<IdentifiedEdgeProto> for IdentifiedEdge {
@@ -1254,20 +1286,32 @@ impl TryFrom<IdentifiedEdgeProto> for IdentifiedEdge {
             .ok_or(SerDeError::MissingField("IdentifiedEdge.to_uid"))?
             .try_into()?;
 
+        let mut properties = HashMap::with_capacity(identified_edge_proto.properties.len());
+        for (key, property) in identified_edge_proto.properties {
+            properties.insert(key, NodeProperty::try_from(property)?);
+        }
+
         Ok(IdentifiedEdge {
             from_uid,
             to_uid,
             edge_name: identified_edge_proto.edge_name,
+            properties,
         })
     }
 }
 
 impl From<IdentifiedEdge> for IdentifiedEdgeProto {
     fn from(identified_edge: IdentifiedEdge) -> Self {
+        let mut properties = HashMap::with_capacity(identified_edge.properties.len());
+        for (key, property) in identified_edge.properties {
+            properties.insert(key, NodePropertyProto::from(property));
+        }
+
         IdentifiedEdgeProto {
             from_uid: Some(identified_edge.from_uid.into()),
             to_uid: Some(identified_edge.to_uid.into()),
             edge_name: identified_edge.edge_name,
+            properties,
         }
     }
 }
@@ -1332,14 +1376,16 @@ pub struct EdgeList {
     pub edges: Vec<Edge>,
 }
 
-impl From<EdgeListProto> for EdgeList {
-    fn from(edge_list_proto: EdgeListProto) -> Self {
+impl TryFrom<EdgeListProto> for EdgeList {
+    type Error = SerDeError;
+
+    fn try_from(edge_list_proto: EdgeListProto) -> Result<Self, Self::Error> {
         let mut edges = Vec::with_capacity(edge_list_proto.edges.len());
         for edge in edge_list_proto.edges {
-            edges.push(Edge::from(edge));
+            edges.push(Edge::try_from(edge)?);
         }
 
-        EdgeList { edges }
+        Ok(EdgeList { edges })
     }
 }
 
@@ -1525,6 +1571,18 @@ impl GraphDescription {
         edge_name: impl Into<String>,
         from_node_key: impl Into<String>,
         to_node_key: impl Into<String>,
+    ) {
+        self.add_edge_with_properties(edge_name, from_node_key, to_node_key, HashMap::new());
+    }
+
+    /// Adds an edge with properties. If the graph already has the edge, the
+    /// properties are merged into it.
+    pub fn add_edge_with_properties(
+        &mut self,
+        edge_name: impl Into<String>,
+        from_node_key: impl Into<String>,
+        to_node_key: impl Into<String>,
+        properties: HashMap<String, NodeProperty>,
     ) {
         let from_node_key = from_node_key.into();
         let to_node_key = to_node_key.into();
@@ -1536,6 +1594,7 @@ impl GraphDescription {
             from_node_key: from_node_key.clone(),
             to_node_key,
             edge_name,
+            properties,
         };
 
         let edge_list: &mut Vec<Edge> = &mut self
@@ -1545,7 +1604,13 @@ impl GraphDescription {
                 edges: Vec::with_capacity(1),
             })
             .edges;
-        edge_list.push(edge);
+        match edge_list
+            .iter_mut()
+            .find(|e| e.edge_name == edge.edge_name && e.to_node_key == edge.to_node_key)
+        {
+            Some(e) => e.merge(&edge),
+            None => edge_list.push(edge),
+        }
     }
 
     pub fn merge(&mut self, other: &Self) {
@@ -1560,10 +1625,11 @@ impl GraphDescription {
 
         for edge_list in other.edges.values() {
             for edge in edge_list.edges.iter() {
-                self.add_edge(
+                self.add_edge_with_properties(
                     edge.edge_name.clone(),
                     edge.from_node_key.clone(),
                     edge.to_node_key.clone(),
+                    edge.properties.clone(),
                 );
             }
         }
@@ -1585,7 +1651,7 @@ impl TryFrom<GraphDescriptionProto> for GraphDescription {
 
         let mut edges = HashMap::with_capacity(graph_description_proto.edges.len());
         for (key, edge_list) in graph_description_proto.edges {
-            edges.insert(key, EdgeList::from(edge_list));
+            edges.insert(key, EdgeList::try_from(edge_list)?);
         }
 
         Ok(GraphDescription { nodes, edges })
@@ -1645,6 +1711,18 @@ impl IdentifiedGraph {
     }
 
     pub fn add_edge(&mut self, edge_name: impl Into<String>, from_uid: Uid, to_uid: Uid) {
+        self.add_edge_with_properties(edge_name, from_uid, to_uid, HashMap::new());
+    }
+
+    /// Adds an edge with properties. If the graph already has the edge, the
+    /// properties are merged into it.
+    pub fn add_edge_with_properties(
+        &mut self,
+        edge_name: impl Into<String>,
+        from_uid: Uid,
+        to_uid: Uid,
+        properties: HashMap<String, NodeProperty>,
+    ) {
         assert_ne!(from_uid, to_uid);
 
         let edge_name = edge_name.into();
@@ -1652,6 +1730,7 @@ impl IdentifiedGraph {
             from_uid,
             to_uid,
             edge_name,
+            properties,
         };
 
         let edge_list: &mut Vec<_> = &mut self
@@ -1661,7 +1740,13 @@ impl IdentifiedGraph {
                 edges: Vec::with_capacity(1),
             })
             .edges;
-        edge_list.push(edge);
+        match edge_list
+            .iter_mut()
+            .find(|e| e.edge_name == edge.edge_name && e.to_uid == edge.to_uid)
+        {
+            Some(e) => e.merge(&edge),
+            None => edge_list.push(edge),
+        }
     }
 
     pub fn merge(&mut self, other: &Self) {
@@ -1676,7 +1761,12 @@ impl IdentifiedGraph {
 
         for edge_list in other.edges.values() {
             for edge in edge_list.edges.iter() {
-                self.add_edge(edge.edge_name.clone(), edge.from_uid, edge.to_uid);
+                self.add_edge_with_properties(
+                    edge.edge_name.clone(),
+                    edge.from_uid,
+                    edge.to_uid,
+                    edge.properties.clone(),
+                );
             }
         }
     }
@@ -83,4 +83,74 @@ impl GraphMutationClient {
             )
             .await
     }
+
+    pub async fn apply_graph(
+        &mut self,
+        request: native::ApplyGraphRequest,
+    ) -> Result<native::ApplyGraphResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.apply_graph(request).await },
+            )
+            .await
+    }
+
+    pub async fn delete_node(
+        &mut self,
+        request: native::DeleteNodeRequest,
+    ) -> Result<native::DeleteNodeResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.delete_node(request).await },
+            )
+            .await
+    }
+
+    pub async fn delete_edge(
+        &mut self,
+        request: native::DeleteEdgeRequest,
+    ) -> Result<native::DeleteEdgeResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.delete_edge(request).await },
+            )
+            .await
+    }
+
+    pub async fn purge_tenant(
+        &mut self,
+        request: native::PurgeTenantRequest,
+    ) -> Result<native::PurgeTenantResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.purge_tenant(request).await },
+            )
+            .await
+    }
+
+    pub async fn get_purge_status(
+        &mut self,
+        request: native::GetPurgeStatusRequest,
+    ) -> Result<native::GetPurgeStatusResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.get_purge_status(request).await },
+            )
+            .await
+    }
 }
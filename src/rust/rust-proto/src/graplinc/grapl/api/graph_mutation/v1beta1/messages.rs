@@ -1,6 +1,9 @@
 use crate::{
     graplinc::grapl::{
-        api::graph::v1beta1::NodeProperty,
+        api::graph::v1beta1::{
+            IdentifiedGraph,
+            NodeProperty,
+        },
         common::v1beta1::types::{
             EdgeName,
             NodeType,
@@ -9,11 +12,25 @@ use crate::{
         },
     },
     protobufs::graplinc::grapl::api::graph_mutation::v1beta1::{
+        ApplyGraphRequest as ApplyGraphRequestProto,
+        ApplyGraphResponse as ApplyGraphResponseProto,
         CreateEdgeRequest as CreateEdgeRequestProto,
         CreateEdgeResponse as CreateEdgeResponseProto,
         CreateNodeRequest as CreateNodeRequestProto,
         CreateNodeResponse as CreateNodeResponseProto,
+        DeleteEdgeRequest as DeleteEdgeRequestProto,
+        DeleteEdgeResponse as DeleteEdgeResponseProto,
+        DeleteNodeRequest as DeleteNodeRequestProto,
+        DeleteNodeResponse as DeleteNodeResponseProto,
+        EdgeResult as EdgeResultProto,
+        GetPurgeStatusRequest as GetPurgeStatusRequestProto,
+        GetPurgeStatusResponse as GetPurgeStatusResponseProto,
         MutationRedundancy as MutationRedundancyProto,
+        NodeResult as NodeResultProto,
+        PropertyResult as PropertyResultProto,
+        PurgeStatus as PurgeStatusProto,
+        PurgeTenantRequest as PurgeTenantRequestProto,
+        PurgeTenantResponse as PurgeTenantResponseProto,
         SetNodePropertyRequest as SetNodePropertyRequestProto,
         SetNodePropertyResponse as SetNodePropertyResponseProto,
     },
@@ -273,6 +290,444 @@ impl From<CreateNodeResponse> for CreateNodeResponseProto {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplyGraphRequest {
+    pub tenant_id: uuid::Uuid,
+    pub identified_graph: IdentifiedGraph,
+}
+
+impl TryFrom<ApplyGraphRequestProto> for ApplyGraphRequest {
+    type Error = SerDeError;
+    fn try_from(proto: ApplyGraphRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("tenant_id"))?
+            .into();
+        let identified_graph = proto
+            .identified_graph
+            .ok_or(SerDeError::MissingField("identified_graph"))?
+            .try_into()?;
+        Ok(Self {
+            tenant_id,
+            identified_graph,
+        })
+    }
+}
+
+impl From<ApplyGraphRequest> for ApplyGraphRequestProto {
+    fn from(value: ApplyGraphRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+            identified_graph: Some(value.identified_graph.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyResult {
+    pub property_name: PropertyName,
+    pub mutation_redundancy: MutationRedundancy,
+}
+
+impl TryFrom<PropertyResultProto> for PropertyResult {
+    type Error = SerDeError;
+    fn try_from(proto: PropertyResultProto) -> Result<Self, Self::Error> {
+        let mutation_redundancy = proto.mutation_redundancy().try_into()?;
+        let property_name = proto
+            .property_name
+            .ok_or(SerDeError::MissingField("property_name"))?
+            .try_into()?;
+        Ok(Self {
+            property_name,
+            mutation_redundancy,
+        })
+    }
+}
+
+impl From<PropertyResult> for PropertyResultProto {
+    fn from(value: PropertyResult) -> Self {
+        let mutation_redundancy: MutationRedundancyProto = value.mutation_redundancy.into();
+        Self {
+            property_name: Some(value.property_name.into()),
+            mutation_redundancy: mutation_redundancy as i32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeResult {
+    pub uid: Uid,
+    pub property_results: Vec<PropertyResult>,
+}
+
+impl TryFrom<NodeResultProto> for NodeResult {
+    type Error = SerDeError;
+    fn try_from(proto: NodeResultProto) -> Result<Self, Self::Error> {
+        let uid = proto
+            .uid
+            .ok_or(SerDeError::MissingField("uid"))?
+            .try_into()?;
+        let property_results = proto
+            .property_results
+            .into_iter()
+            .map(PropertyResult::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            uid,
+            property_results,
+        })
+    }
+}
+
+impl From<NodeResult> for NodeResultProto {
+    fn from(value: NodeResult) -> Self {
+        Self {
+            uid: Some(value.uid.into()),
+            property_results: value
+                .property_results
+                .into_iter()
+                .map(PropertyResultProto::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeResult {
+    pub from_uid: Uid,
+    pub to_uid: Uid,
+    pub edge_name: EdgeName,
+    pub reverse_edge_name: EdgeName,
+    pub mutation_redundancy: MutationRedundancy,
+    pub property_results: Vec<PropertyResult>,
+}
+
+impl TryFrom<EdgeResultProto> for EdgeResult {
+    type Error = SerDeError;
+    fn try_from(proto: EdgeResultProto) -> Result<Self, Self::Error> {
+        let mutation_redundancy = proto.mutation_redundancy().try_into()?;
+        let from_uid = proto
+            .from_uid
+            .ok_or(SerDeError::MissingField("from_uid"))?
+            .try_into()?;
+        let to_uid = proto
+            .to_uid
+            .ok_or(SerDeError::MissingField("to_uid"))?
+            .try_into()?;
+        let edge_name = proto
+            .edge_name
+            .ok_or(SerDeError::MissingField("edge_name"))?
+            .try_into()?;
+        let reverse_edge_name = proto
+            .reverse_edge_name
+            .ok_or(SerDeError::MissingField("reverse_edge_name"))?
+            .try_into()?;
+        let property_results = proto
+            .property_results
+            .into_iter()
+            .map(PropertyResult::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            from_uid,
+            to_uid,
+            edge_name,
+            reverse_edge_name,
+            mutation_redundancy,
+            property_results,
+        })
+    }
+}
+
+impl From<EdgeResult> for EdgeResultProto {
+    fn from(value: EdgeResult) -> Self {
+        let mutation_redundancy: MutationRedundancyProto = value.mutation_redundancy.into();
+        Self {
+            from_uid: Some(value.from_uid.into()),
+            to_uid: Some(value.to_uid.into()),
+            edge_name: Some(value.edge_name.into()),
+            reverse_edge_name: Some(value.reverse_edge_name.into()),
+            mutation_redundancy: mutation_redundancy as i32,
+            property_results: value
+                .property_results
+                .into_iter()
+                .map(PropertyResultProto::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplyGraphResponse {
+    pub node_results: Vec<NodeResult>,
+    pub edge_results: Vec<EdgeResult>,
+}
+
+impl TryFrom<ApplyGraphResponseProto> for ApplyGraphResponse {
+    type Error = SerDeError;
+    fn try_from(proto: ApplyGraphResponseProto) -> Result<Self, Self::Error> {
+        let node_results = proto
+            .node_results
+            .into_iter()
+            .map(NodeResult::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let edge_results = proto
+            .edge_results
+            .into_iter()
+            .map(EdgeResult::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            node_results,
+            edge_results,
+        })
+    }
+}
+
+impl From<ApplyGraphResponse> for ApplyGraphResponseProto {
+    fn from(value: ApplyGraphResponse) -> Self {
+        Self {
+            node_results: value
+                .node_results
+                .into_iter()
+                .map(NodeResultProto::from)
+                .collect(),
+            edge_results: value
+                .edge_results
+                .into_iter()
+                .map(EdgeResultProto::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteNodeRequest {
+    pub tenant_id: uuid::Uuid,
+    pub uid: Uid,
+}
+
+impl TryFrom<DeleteNodeRequestProto> for DeleteNodeRequest {
+    type Error = SerDeError;
+    fn try_from(proto: DeleteNodeRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("tenant_id"))?
+            .into();
+        let uid = proto
+            .uid
+            .ok_or(SerDeError::MissingField("uid"))?
+            .try_into()?;
+        Ok(Self { tenant_id, uid })
+    }
+}
+
+impl From<DeleteNodeRequest> for DeleteNodeRequestProto {
+    fn from(value: DeleteNodeRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+            uid: Some(value.uid.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteNodeResponse {
+    pub deleted_edges: u64,
+}
+
+impl TryFrom<DeleteNodeResponseProto> for DeleteNodeResponse {
+    type Error = SerDeError;
+    fn try_from(proto: DeleteNodeResponseProto) -> Result<Self, Self::Error> {
+        Ok(Self {
+            deleted_edges: proto.deleted_edges,
+        })
+    }
+}
+
+impl From<DeleteNodeResponse> for DeleteNodeResponseProto {
+    fn from(value: DeleteNodeResponse) -> Self {
+        Self {
+            deleted_edges: value.deleted_edges,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteEdgeRequest {
+    pub tenant_id: uuid::Uuid,
+    pub from_uid: Uid,
+    pub to_uid: Uid,
+    pub edge_name: EdgeName,
+}
+
+impl TryFrom<DeleteEdgeRequestProto> for DeleteEdgeRequest {
+    type Error = SerDeError;
+    fn try_from(proto: DeleteEdgeRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("tenant_id"))?
+            .into();
+        let from_uid = proto
+            .from_uid
+            .ok_or(SerDeError::MissingField("from_uid"))?
+            .try_into()?;
+        let to_uid = proto
+            .to_uid
+            .ok_or(SerDeError::MissingField("to_uid"))?
+            .try_into()?;
+        let edge_name = proto
+            .edge_name
+            .ok_or(SerDeError::MissingField("edge_name"))?
+            .try_into()?;
+        Ok(Self {
+            tenant_id,
+            from_uid,
+            to_uid,
+            edge_name,
+        })
+    }
+}
+
+impl From<DeleteEdgeRequest> for DeleteEdgeRequestProto {
+    fn from(value: DeleteEdgeRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+            from_uid: Some(value.from_uid.into()),
+            to_uid: Some(value.to_uid.into()),
+            edge_name: Some(value.edge_name.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteEdgeResponse {
+    pub existed: bool,
+}
+
+impl TryFrom<DeleteEdgeResponseProto> for DeleteEdgeResponse {
+    type Error = SerDeError;
+    fn try_from(proto: DeleteEdgeResponseProto) -> Result<Self, Self::Error> {
+        Ok(Self {
+            existed: proto.existed,
+        })
+    }
+}
+
+impl From<DeleteEdgeResponse> for DeleteEdgeResponseProto {
+    fn from(value: DeleteEdgeResponse) -> Self {
+        Self {
+            existed: value.existed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurgeTenantRequest {
+    pub tenant_id: uuid::Uuid,
+}
+
+impl TryFrom<PurgeTenantRequestProto> for PurgeTenantRequest {
+    type Error = SerDeError;
+    fn try_from(proto: PurgeTenantRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("tenant_id"))?
+            .into();
+        Ok(Self { tenant_id })
+    }
+}
+
+impl From<PurgeTenantRequest> for PurgeTenantRequestProto {
+    fn from(value: PurgeTenantRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurgeTenantResponse {}
+
+impl TryFrom<PurgeTenantResponseProto> for PurgeTenantResponse {
+    type Error = SerDeError;
+    fn try_from(_proto: PurgeTenantResponseProto) -> Result<Self, Self::Error> {
+        Ok(Self {})
+    }
+}
+
+impl From<PurgeTenantResponse> for PurgeTenantResponseProto {
+    fn from(_value: PurgeTenantResponse) -> Self {
+        Self {}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetPurgeStatusRequest {
+    pub tenant_id: uuid::Uuid,
+}
+
+impl TryFrom<GetPurgeStatusRequestProto> for GetPurgeStatusRequest {
+    type Error = SerDeError;
+    fn try_from(proto: GetPurgeStatusRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("tenant_id"))?
+            .into();
+        Ok(Self { tenant_id })
+    }
+}
+
+impl From<GetPurgeStatusRequest> for GetPurgeStatusRequestProto {
+    fn from(value: GetPurgeStatusRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurgeStatus {
+    NotStarted,
+    Running,
+    Completed,
+    Failed { reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetPurgeStatusResponse {
+    pub purge_status: PurgeStatus,
+}
+
+impl TryFrom<GetPurgeStatusResponseProto> for GetPurgeStatusResponse {
+    type Error = SerDeError;
+    fn try_from(proto: GetPurgeStatusResponseProto) -> Result<Self, Self::Error> {
+        let purge_status = match proto.purge_status() {
+            PurgeStatusProto::Unspecified => return Err(SerDeError::UnknownVariant("PurgeStatus")),
+            PurgeStatusProto::NotStarted => PurgeStatus::NotStarted,
+            PurgeStatusProto::Running => PurgeStatus::Running,
+            PurgeStatusProto::Completed => PurgeStatus::Completed,
+            PurgeStatusProto::Failed => PurgeStatus::Failed {
+                reason: proto.failure_reason,
+            },
+        };
+        Ok(Self { purge_status })
+    }
+}
+
+impl From<GetPurgeStatusResponse> for GetPurgeStatusResponseProto {
+    fn from(value: GetPurgeStatusResponse) -> Self {
+        let (purge_status, failure_reason) = match value.purge_status {
+            PurgeStatus::NotStarted => (PurgeStatusProto::NotStarted, String::new()),
+            PurgeStatus::Running => (PurgeStatusProto::Running, String::new()),
+            PurgeStatus::Completed => (PurgeStatusProto::Completed, String::new()),
+            PurgeStatus::Failed { reason } => (PurgeStatusProto::Failed, reason),
+        };
+        Self {
+            purge_status: purge_status as i32,
+            failure_reason,
+        }
+    }
+}
+
 impl serde_impl::ProtobufSerializable for SetNodePropertyRequest {
     type ProtobufMessage = SetNodePropertyRequestProto;
 }
@@ -327,3 +782,120 @@ impl type_url::TypeUrl for CreateNodeResponse {
         "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.CreateNodeResponse\
     ";
 }
+
+impl serde_impl::ProtobufSerializable for ApplyGraphRequest {
+    type ProtobufMessage = ApplyGraphRequestProto;
+}
+
+impl type_url::TypeUrl for ApplyGraphRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.ApplyGraphRequest";
+}
+
+impl serde_impl::ProtobufSerializable for ApplyGraphResponse {
+    type ProtobufMessage = ApplyGraphResponseProto;
+}
+
+impl type_url::TypeUrl for ApplyGraphResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.ApplyGraphResponse";
+}
+
+impl serde_impl::ProtobufSerializable for PropertyResult {
+    type ProtobufMessage = PropertyResultProto;
+}
+
+impl type_url::TypeUrl for PropertyResult {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.PropertyResult";
+}
+
+impl serde_impl::ProtobufSerializable for NodeResult {
+    type ProtobufMessage = NodeResultProto;
+}
+
+impl type_url::TypeUrl for NodeResult {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.NodeResult";
+}
+
+impl serde_impl::ProtobufSerializable for EdgeResult {
+    type ProtobufMessage = EdgeResultProto;
+}
+
+impl type_url::TypeUrl for EdgeResult {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.EdgeResult";
+}
+
+impl serde_impl::ProtobufSerializable for DeleteNodeRequest {
+    type ProtobufMessage = DeleteNodeRequestProto;
+}
+
+impl type_url::TypeUrl for DeleteNodeRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.DeleteNodeRequest";
+}
+
+impl serde_impl::ProtobufSerializable for DeleteNodeResponse {
+    type ProtobufMessage = DeleteNodeResponseProto;
+}
+
+impl type_url::TypeUrl for DeleteNodeResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.DeleteNodeResponse";
+}
+
+impl serde_impl::ProtobufSerializable for DeleteEdgeRequest {
+    type ProtobufMessage = DeleteEdgeRequestProto;
+}
+
+impl type_url::TypeUrl for DeleteEdgeRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.DeleteEdgeRequest";
+}
+
+impl serde_impl::ProtobufSerializable for DeleteEdgeResponse {
+    type ProtobufMessage = DeleteEdgeResponseProto;
+}
+
+impl type_url::TypeUrl for DeleteEdgeResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.DeleteEdgeResponse";
+}
+
+impl serde_impl::ProtobufSerializable for PurgeTenantRequest {
+    type ProtobufMessage = PurgeTenantRequestProto;
+}
+
+impl type_url::TypeUrl for PurgeTenantRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.PurgeTenantRequest";
+}
+
+impl serde_impl::ProtobufSerializable for PurgeTenantResponse {
+    type ProtobufMessage = PurgeTenantResponseProto;
+}
+
+impl type_url::TypeUrl for PurgeTenantResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.PurgeTenantResponse";
+}
+
+impl serde_impl::ProtobufSerializable for GetPurgeStatusRequest {
+    type ProtobufMessage = GetPurgeStatusRequestProto;
+}
+
+impl type_url::TypeUrl for GetPurgeStatusRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.GetPurgeStatusRequest";
+}
+
+impl serde_impl::ProtobufSerializable for GetPurgeStatusResponse {
+    type ProtobufMessage = GetPurgeStatusResponseProto;
+}
+
+impl type_url::TypeUrl for GetPurgeStatusResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_mutation.v1beta1.GetPurgeStatusResponse";
+}
@@ -63,6 +63,26 @@ pub trait GraphMutationApi {
         &self,
         request: native::CreateEdgeRequest,
     ) -> Result<native::CreateEdgeResponse, Self::Error>;
+    async fn apply_graph(
+        &self,
+        request: native::ApplyGraphRequest,
+    ) -> Result<native::ApplyGraphResponse, Self::Error>;
+    async fn delete_node(
+        &self,
+        request: native::DeleteNodeRequest,
+    ) -> Result<native::DeleteNodeResponse, Self::Error>;
+    async fn delete_edge(
+        &self,
+        request: native::DeleteEdgeRequest,
+    ) -> Result<native::DeleteEdgeResponse, Self::Error>;
+    async fn purge_tenant(
+        &self,
+        request: native::PurgeTenantRequest,
+    ) -> Result<native::PurgeTenantResponse, Self::Error>;
+    async fn get_purge_status(
+        &self,
+        request: native::GetPurgeStatusRequest,
+    ) -> Result<native::GetPurgeStatusResponse, Self::Error>;
 }
 
 #[tonic::async_trait]
@@ -93,6 +113,44 @@ where
     ) -> Result<tonic::Response<proto::CreateEdgeResponse>, tonic::Status> {
         execute_rpc!(self, request, create_edge)
     }
+    /// ApplyGraph writes every node property and edge of an identified graph,
+    /// using batches, and reports the redundancy of each write.
+    async fn apply_graph(
+        &self,
+        request: tonic::Request<proto::ApplyGraphRequest>,
+    ) -> Result<tonic::Response<proto::ApplyGraphResponse>, tonic::Status> {
+        execute_rpc!(self, request, apply_graph)
+    }
+    /// DeleteNode deletes the node with the given uid, along with its properties
+    /// and all of its edges in both directions.
+    async fn delete_node(
+        &self,
+        request: tonic::Request<proto::DeleteNodeRequest>,
+    ) -> Result<tonic::Response<proto::DeleteNodeResponse>, tonic::Status> {
+        execute_rpc!(self, request, delete_node)
+    }
+    /// DeleteEdge deletes the edge with the name edge_name between the nodes
+    /// that have the given uids. It will also delete the reverse edge.
+    async fn delete_edge(
+        &self,
+        request: tonic::Request<proto::DeleteEdgeRequest>,
+    ) -> Result<tonic::Response<proto::DeleteEdgeResponse>, tonic::Status> {
+        execute_rpc!(self, request, delete_edge)
+    }
+    /// PurgeTenant starts deleting a tenant's whole graph in the background.
+    async fn purge_tenant(
+        &self,
+        request: tonic::Request<proto::PurgeTenantRequest>,
+    ) -> Result<tonic::Response<proto::PurgeTenantResponse>, tonic::Status> {
+        execute_rpc!(self, request, purge_tenant)
+    }
+    /// GetPurgeStatus reports on the state of a tenant's purge.
+    async fn get_purge_status(
+        &self,
+        request: tonic::Request<proto::GetPurgeStatusRequest>,
+    ) -> Result<tonic::Response<proto::GetPurgeStatusResponse>, tonic::Status> {
+        execute_rpc!(self, request, get_purge_status)
+    }
 }
 
 /**
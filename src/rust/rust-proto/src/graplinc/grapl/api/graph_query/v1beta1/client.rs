@@ -1,3 +1,6 @@
+use std::time::Duration;
+
+use futures::stream::BoxStream;
 use tonic::transport::Endpoint;
 
 use crate::{
@@ -59,4 +62,50 @@ impl GraphQueryClient {
             )
             .await
     }
+
+    pub async fn query_graph(
+        &mut self,
+        request: native::QueryGraphRequest,
+    ) -> Result<native::QueryGraphResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.query_graph(request).await },
+            )
+            .await
+    }
+
+    pub async fn get_neighborhood(
+        &mut self,
+        request: native::GetNeighborhoodRequest,
+    ) -> Result<native::GetNeighborhoodResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.get_neighborhood(request).await },
+            )
+            .await
+    }
+
+    /// Stream every match of the query that the node takes part in, in
+    /// ascending order of root uid. The whole stream must be consumed within
+    /// `request_timeout`.
+    pub async fn query_all_graphs_with_uid(
+        &mut self,
+        request_timeout: Duration,
+        request: native::QueryAllGraphsWithUidRequest,
+    ) -> Result<
+        BoxStream<'static, Result<native::QueryAllGraphsWithUidResponse, ClientError>>,
+        ClientError,
+    > {
+        self.client
+            .execute_server_streaming(request, request_timeout, |mut client, request| async move {
+                client.query_all_graphs_with_uid(request).await
+            })
+            .await
+    }
 }
@@ -1,4 +1,7 @@
-use std::collections::hash_map::Entry;
+use std::{
+    collections::hash_map::Entry,
+    time::Duration,
+};
 
 use rustc_hash::{
     FxHashMap,
@@ -587,6 +590,72 @@ impl serde_impl::ProtobufSerializable for UidFilters {
     type ProtobufMessage = proto::UidFilters;
 }
 
+/// A window of time, in milliseconds since the epoch, that a
+/// session-identified node must overlap in order to match.
+///
+/// A session lasts from its create time until its terminate time. A session
+/// without a terminate time lasts until it was last seen, and one without a
+/// create time begins when it was last seen. The properties holding these
+/// timestamps are the ones the node type's deployed session identity declares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeWindow {
+    /// The start of the window, inclusive, or `None` if it has no start
+    pub start_time: Option<u64>,
+    /// The end of the window, inclusive, or `None` if it has no end
+    pub end_time: Option<u64>,
+}
+
+impl TimeWindow {
+    pub fn new(start_time: Option<u64>, end_time: Option<u64>) -> Self {
+        Self {
+            start_time,
+            end_time,
+        }
+    }
+
+    /// Whether a session lasting from `session_start` to `session_end`,
+    /// inclusive, overlaps the window
+    pub fn overlaps(&self, session_start: u64, session_end: u64) -> bool {
+        self.start_time.map_or(true, |start| session_end >= start)
+            && self.end_time.map_or(true, |end| session_start <= end)
+    }
+}
+
+impl TryFrom<proto::TimeWindow> for TimeWindow {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::TimeWindow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            start_time: match value.start_time {
+                0 => None,
+                start_time => Some(start_time),
+            },
+            end_time: match value.end_time {
+                0 => None,
+                end_time => Some(end_time),
+            },
+        })
+    }
+}
+
+impl From<TimeWindow> for proto::TimeWindow {
+    fn from(value: TimeWindow) -> Self {
+        Self {
+            start_time: value.start_time.unwrap_or(0),
+            end_time: value.end_time.unwrap_or(0),
+        }
+    }
+}
+
+impl type_url::TypeUrl for TimeWindow {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.TimeWindow";
+}
+
+impl serde_impl::ProtobufSerializable for TimeWindow {
+    type ProtobufMessage = proto::TimeWindow;
+}
+
 #[derive(Debug, Clone)]
 pub struct NodePropertyQuery {
     pub query_id: QueryId,
@@ -594,6 +663,8 @@ pub struct NodePropertyQuery {
     pub int_filters: FxHashMap<PropertyName, OrIntFilters>,
     pub string_filters: FxHashMap<PropertyName, OrStringFilters>,
     pub uid_filters: UidFilters,
+    /// The window of time the node's session must overlap, if any
+    pub time_window: Option<TimeWindow>,
 }
 
 impl NodePropertyQuery {
@@ -604,6 +675,7 @@ impl NodePropertyQuery {
             int_filters: Default::default(),
             string_filters: Default::default(),
             uid_filters: Default::default(),
+            time_window: None,
         }
     }
 
@@ -611,6 +683,14 @@ impl NodePropertyQuery {
         debug_assert_eq!(self.query_id, other.query_id);
         debug_assert_eq!(self.node_type, other.node_type);
         self.string_filters.extend(other.string_filters);
+        if other.time_window.is_some() {
+            self.time_window = other.time_window;
+        }
+    }
+
+    pub fn with_time_window(&mut self, time_window: TimeWindow) -> &mut Self {
+        self.time_window = Some(time_window);
+        self
     }
 
     pub fn with_string_filters(
@@ -672,12 +752,16 @@ impl TryFrom<proto::NodePropertyQuery> for NodePropertyQuery {
             .query_id
             .ok_or(SerDeError::MissingField("query_id"))?
             .try_into()?;
+
+        let time_window = value.time_window.map(TimeWindow::try_from).transpose()?;
+
         Ok(Self {
             query_id,
             node_type,
             int_filters,
             string_filters,
             uid_filters,
+            time_window,
         })
     }
 }
@@ -706,6 +790,7 @@ impl From<NodePropertyQuery> for proto::NodePropertyQuery {
             int_filters,
             string_filters,
             uid_filters: Some(uid_filters),
+            time_window: value.time_window.map(Into::into),
         }
     }
 }
@@ -719,15 +804,172 @@ impl serde_impl::ProtobufSerializable for NodePropertyQuery {
     type ProtobufMessage = proto::NodePropertyQuery;
 }
 
+/// How an edge between two node queries constrains a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeConstraint {
+    /// At least one neighbor must match the neighbor query
+    Required,
+    /// Neighbors matching the neighbor query are included if there are any
+    Optional,
+    /// No neighbor may match the neighbor query
+    Negated,
+}
+
+impl Default for EdgeConstraint {
+    fn default() -> Self {
+        Self::Required
+    }
+}
+
+impl From<proto::EdgeConstraint> for EdgeConstraint {
+    fn from(value_proto: proto::EdgeConstraint) -> Self {
+        match value_proto {
+            // Queries built before edge constraints existed never set one
+            proto::EdgeConstraint::Unspecified => Self::Required,
+            proto::EdgeConstraint::Required => Self::Required,
+            proto::EdgeConstraint::Optional => Self::Optional,
+            proto::EdgeConstraint::Negated => Self::Negated,
+        }
+    }
+}
+
+impl From<EdgeConstraint> for proto::EdgeConstraint {
+    fn from(value: EdgeConstraint) -> Self {
+        match value {
+            EdgeConstraint::Required => Self::Required,
+            EdgeConstraint::Optional => Self::Optional,
+            EdgeConstraint::Negated => Self::Negated,
+        }
+    }
+}
+
+/// A group of edges from the node query `query_id`, at least one of which
+/// must match. The members are not required individually.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnyOfEdges {
+    pub query_id: QueryId,
+    pub edges: FxHashSet<(EdgeName, QueryId)>,
+}
+
+impl AnyOfEdges {
+    pub fn new(query_id: QueryId) -> Self {
+        Self {
+            query_id,
+            edges: FxHashSet::default(),
+        }
+    }
+
+    pub fn contains(&self, edge_name: &EdgeName, neighbor_query_id: QueryId) -> bool {
+        self.edges.contains(&(edge_name.clone(), neighbor_query_id))
+    }
+}
+
+impl TryFrom<proto::AnyOfEdges> for AnyOfEdges {
+    type Error = SerDeError;
+
+    fn try_from(value_proto: proto::AnyOfEdges) -> Result<Self, Self::Error> {
+        let query_id = value_proto
+            .query_id
+            .ok_or_else(|| SerDeError::MissingField("query_id"))?
+            .try_into()?;
+
+        let edges = value_proto
+            .edges
+            .into_iter()
+            .map(|member| {
+                let edge_name = member
+                    .edge_name
+                    .ok_or_else(|| SerDeError::MissingField("edge_name"))?
+                    .try_into()?;
+                let neighbor_query_id = member
+                    .neighbor_query_id
+                    .ok_or_else(|| SerDeError::MissingField("neighbor_query_id"))?
+                    .try_into()?;
+                Ok((edge_name, neighbor_query_id))
+            })
+            .collect::<Result<FxHashSet<_>, SerDeError>>()?;
+
+        Ok(Self { query_id, edges })
+    }
+}
+
+impl From<AnyOfEdges> for proto::AnyOfEdges {
+    fn from(value: AnyOfEdges) -> Self {
+        Self {
+            query_id: Some(value.query_id.into()),
+            edges: value
+                .edges
+                .into_iter()
+                .map(|(edge_name, neighbor_query_id)| proto::AnyOfEdgesMember {
+                    edge_name: Some(edge_name.into()),
+                    neighbor_query_id: Some(neighbor_query_id.into()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl type_url::TypeUrl for AnyOfEdges {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.AnyOfEdges";
+}
+
+impl serde_impl::ProtobufSerializable for AnyOfEdges {
+    type ProtobufMessage = proto::AnyOfEdges;
+}
+
 #[derive(Debug, Clone)]
 pub struct GraphQuery {
     pub root_query_id: QueryId,
     pub node_property_queries: FxHashMap<QueryId, NodePropertyQuery>,
     pub edge_filters: FxHashMap<(QueryId, EdgeName), FxHashSet<QueryId>>,
     pub edge_map: FxHashMap<EdgeName, EdgeName>,
+    /// Constraints on edges in `edge_filters`, keyed by
+    /// (source query, edge name, neighbor query) in the direction the edge
+    /// was added to the query. Edges without an entry are required.
+    pub edge_constraints: FxHashMap<(QueryId, EdgeName, QueryId), EdgeConstraint>,
+    pub any_of_edges: Vec<AnyOfEdges>,
 }
 
 impl GraphQuery {
+    pub fn edge_constraint(
+        &self,
+        query_id: QueryId,
+        edge_name: &EdgeName,
+        neighbor_query_id: QueryId,
+    ) -> EdgeConstraint {
+        self.edge_constraints
+            .get(&(query_id, edge_name.clone(), neighbor_query_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The node queries which are the target of a negated edge, along with
+    /// every node query that is only reachable through one. A match never
+    /// contains nodes for these queries, so they can't be used as the
+    /// starting point for one.
+    pub fn negated_query_ids(&self) -> FxHashSet<QueryId> {
+        let mut negated = FxHashSet::default();
+        for ((query_id, _, neighbor_query_id), constraint) in self.edge_constraints.iter() {
+            if *constraint != EdgeConstraint::Negated {
+                continue;
+            }
+
+            let mut to_visit = vec![*neighbor_query_id];
+            while let Some(next) = to_visit.pop() {
+                if next == *query_id || !negated.insert(next) {
+                    continue;
+                }
+                for ((src_id, _), neighbor_query_ids) in self.edge_filters.iter() {
+                    if *src_id == next {
+                        to_visit.extend(neighbor_query_ids.iter().copied());
+                    }
+                }
+            }
+        }
+        negated
+    }
+
     pub fn add_node(&mut self, query_id: QueryId, node_type: NodeType) {
         self.node_property_queries.insert(
             query_id,
@@ -737,6 +979,7 @@ impl GraphQuery {
                 int_filters: Default::default(),
                 string_filters: Default::default(),
                 uid_filters: Default::default(),
+                time_window: None,
             },
         );
     }
@@ -823,11 +1066,42 @@ impl TryFrom<proto::GraphQuery> for GraphQuery {
             edge_map.insert(forward_edge_name, reverse_edge_name);
         }
 
+        // Absent from queries built before edge constraints existed
+        let edge_constraints_proto = value.edge_constraints.unwrap_or_default();
+        let mut edge_constraints: FxHashMap<(QueryId, EdgeName, QueryId), EdgeConstraint> =
+            FxHashMap::default();
+        edge_constraints.reserve(edge_constraints_proto.entries.len());
+
+        for edge_constraint in edge_constraints_proto.entries {
+            let constraint = edge_constraint.constraint().into();
+            let query_id = edge_constraint
+                .query_id
+                .ok_or_else(|| SerDeError::MissingField("query_id"))?
+                .try_into()?;
+            let edge_name = edge_constraint
+                .edge_name
+                .ok_or_else(|| SerDeError::MissingField("edge_name"))?
+                .try_into()?;
+            let neighbor_query_id = edge_constraint
+                .neighbor_query_id
+                .ok_or_else(|| SerDeError::MissingField("neighbor_query_id"))?
+                .try_into()?;
+            edge_constraints.insert((query_id, edge_name, neighbor_query_id), constraint);
+        }
+
+        let any_of_edges = value
+            .any_of_edges
+            .into_iter()
+            .map(AnyOfEdges::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
         Ok(Self {
             root_query_id,
             node_property_queries,
             edge_filters,
             edge_map,
+            edge_constraints,
+            any_of_edges,
         })
     }
 }
@@ -867,11 +1141,35 @@ impl From<GraphQuery> for proto::GraphQuery {
                 })
                 .collect(),
         });
+
+        let edge_constraints = Some(proto::EdgeConstraintMap {
+            entries: value
+                .edge_constraints
+                .into_iter()
+                .map(|((query_id, edge_name, neighbor_query_id), constraint)| {
+                    proto::EdgeConstraintEntry {
+                        query_id: Some(query_id.into()),
+                        edge_name: Some(edge_name.into()),
+                        neighbor_query_id: Some(neighbor_query_id.into()),
+                        constraint: proto::EdgeConstraint::from(constraint) as i32,
+                    }
+                })
+                .collect(),
+        });
+
+        let any_of_edges = value
+            .any_of_edges
+            .into_iter()
+            .map(proto::AnyOfEdges::from)
+            .collect();
+
         Self {
             root_query_id,
             node_property_queries,
             edge_filters,
             edge_map,
+            edge_constraints,
+            any_of_edges,
         }
     }
 }
@@ -942,11 +1240,125 @@ impl serde_impl::ProtobufSerializable for StringProperties {
     type ProtobufMessage = proto::StringProperties;
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct IntProperties {
+    pub prop_map: FxHashMap<PropertyName, i64>,
+}
+
+impl IntProperties {
+    pub fn merge(&mut self, other: Self) {
+        self.prop_map.extend(other.prop_map);
+    }
+
+    pub fn add_int_property(&mut self, property_name: PropertyName, value: i64) {
+        self.prop_map.insert(property_name, value);
+    }
+}
+
+impl TryFrom<proto::IntProperties> for IntProperties {
+    type Error = SerDeError;
+    fn try_from(value: proto::IntProperties) -> Result<Self, Self::Error> {
+        let mut prop_map = FxHashMap::default();
+        prop_map.reserve(value.properties.len());
+
+        for int_property in value.properties {
+            let property_name = int_property
+                .property_name
+                .ok_or(SerDeError::MissingField("property_name"))?;
+            prop_map.insert(property_name.try_into()?, int_property.property_value);
+        }
+
+        Ok(Self { prop_map })
+    }
+}
+
+impl From<IntProperties> for proto::IntProperties {
+    fn from(value: IntProperties) -> Self {
+        proto::IntProperties {
+            properties: value
+                .prop_map
+                .into_iter()
+                .map(|(k, v)| proto::IntProperty {
+                    property_name: Some(k.into()),
+                    property_value: v,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl type_url::TypeUrl for IntProperties {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.IntProperties";
+}
+
+impl serde_impl::ProtobufSerializable for IntProperties {
+    type ProtobufMessage = proto::IntProperties;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UintProperties {
+    pub prop_map: FxHashMap<PropertyName, u64>,
+}
+
+impl UintProperties {
+    pub fn merge(&mut self, other: Self) {
+        self.prop_map.extend(other.prop_map);
+    }
+
+    pub fn add_uint_property(&mut self, property_name: PropertyName, value: u64) {
+        self.prop_map.insert(property_name, value);
+    }
+}
+
+impl TryFrom<proto::UintProperties> for UintProperties {
+    type Error = SerDeError;
+    fn try_from(value: proto::UintProperties) -> Result<Self, Self::Error> {
+        let mut prop_map = FxHashMap::default();
+        prop_map.reserve(value.properties.len());
+
+        for uint_property in value.properties {
+            let property_name = uint_property
+                .property_name
+                .ok_or(SerDeError::MissingField("property_name"))?;
+            prop_map.insert(property_name.try_into()?, uint_property.property_value);
+        }
+
+        Ok(Self { prop_map })
+    }
+}
+
+impl From<UintProperties> for proto::UintProperties {
+    fn from(value: UintProperties) -> Self {
+        proto::UintProperties {
+            properties: value
+                .prop_map
+                .into_iter()
+                .map(|(k, v)| proto::UintProperty {
+                    property_name: Some(k.into()),
+                    property_value: v,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl type_url::TypeUrl for UintProperties {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.UintProperties";
+}
+
+impl serde_impl::ProtobufSerializable for UintProperties {
+    type ProtobufMessage = proto::UintProperties;
+}
+
 #[derive(Debug, Clone)]
 pub struct NodePropertiesView {
     pub uid: Uid,
     pub node_type: NodeType,
     pub string_properties: StringProperties,
+    pub int_properties: IntProperties,
+    pub uint_properties: UintProperties,
 }
 
 impl NodePropertiesView {
@@ -955,6 +1367,8 @@ impl NodePropertiesView {
             uid,
             node_type,
             string_properties,
+            int_properties: IntProperties::default(),
+            uint_properties: UintProperties::default(),
         }
     }
 
@@ -962,12 +1376,22 @@ impl NodePropertiesView {
         debug_assert_eq!(self.uid, other.uid);
         debug_assert_eq!(self.node_type, other.node_type);
         self.string_properties.merge(other.string_properties);
+        self.int_properties.merge(other.int_properties);
+        self.uint_properties.merge(other.uint_properties);
     }
 
     pub fn add_string_property(&mut self, property_name: PropertyName, value: String) {
         self.string_properties
             .add_string_property(property_name, value);
     }
+
+    pub fn add_int_property(&mut self, property_name: PropertyName, value: i64) {
+        self.int_properties.add_int_property(property_name, value);
+    }
+
+    pub fn add_uint_property(&mut self, property_name: PropertyName, value: u64) {
+        self.uint_properties.add_uint_property(property_name, value);
+    }
 }
 
 impl TryFrom<proto::NodePropertiesView> for NodePropertiesView {
@@ -989,6 +1413,17 @@ impl TryFrom<proto::NodePropertiesView> for NodePropertiesView {
                 .ok_or(SerDeError::MissingField("node_type"))?
                 .try_into()?,
             string_properties,
+            // Older senders only ever sent string properties
+            int_properties: value
+                .int_properties
+                .map(IntProperties::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+            uint_properties: value
+                .uint_properties
+                .map(UintProperties::try_from)
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
@@ -1001,6 +1436,8 @@ impl From<NodePropertiesView> for proto::NodePropertiesView {
             uid: Some(value.uid.into()),
             node_type: Some(value.node_type.into()),
             string_properties: Some(string_properties),
+            int_properties: Some(value.int_properties.into()),
+            uint_properties: Some(value.uint_properties.into()),
         }
     }
 }
@@ -1205,10 +1642,112 @@ impl serde_impl::ProtobufSerializable for EdgeViewMap {
     type ProtobufMessage = proto::EdgeViewMap;
 }
 
+/// The properties of a single edge, from its source node's side
+#[derive(Debug, Clone, Default)]
+pub struct EdgePropertiesView {
+    pub string_properties: StringProperties,
+    pub int_properties: IntProperties,
+    pub uint_properties: UintProperties,
+}
+
+impl EdgePropertiesView {
+    pub fn merge(&mut self, other: Self) {
+        self.string_properties.merge(other.string_properties);
+        self.int_properties.merge(other.int_properties);
+        self.uint_properties.merge(other.uint_properties);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.string_properties.prop_map.is_empty()
+            && self.int_properties.prop_map.is_empty()
+            && self.uint_properties.prop_map.is_empty()
+    }
+
+    pub fn add_string_property(&mut self, property_name: PropertyName, value: String) {
+        self.string_properties
+            .add_string_property(property_name, value);
+    }
+
+    pub fn add_int_property(&mut self, property_name: PropertyName, value: i64) {
+        self.int_properties.add_int_property(property_name, value);
+    }
+
+    pub fn add_uint_property(&mut self, property_name: PropertyName, value: u64) {
+        self.uint_properties.add_uint_property(property_name, value);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EdgePropertiesViewEntry {
+    pub source_uid: Uid,
+    pub edge_name: EdgeName,
+    pub destination_uid: Uid,
+    pub properties: EdgePropertiesView,
+}
+
+impl TryFrom<proto::EdgePropertiesViewEntry> for EdgePropertiesViewEntry {
+    type Error = SerDeError;
+    fn try_from(value: proto::EdgePropertiesViewEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            source_uid: value
+                .source_uid
+                .ok_or(SerDeError::MissingField("source_uid"))?
+                .try_into()?,
+            edge_name: value
+                .edge_name
+                .ok_or(SerDeError::MissingField("edge_name"))?
+                .try_into()?,
+            destination_uid: value
+                .destination_uid
+                .ok_or(SerDeError::MissingField("destination_uid"))?
+                .try_into()?,
+            properties: EdgePropertiesView {
+                string_properties: value
+                    .string_properties
+                    .ok_or(SerDeError::MissingField("string_properties"))?
+                    .try_into()?,
+                int_properties: value
+                    .int_properties
+                    .ok_or(SerDeError::MissingField("int_properties"))?
+                    .try_into()?,
+                uint_properties: value
+                    .uint_properties
+                    .ok_or(SerDeError::MissingField("uint_properties"))?
+                    .try_into()?,
+            },
+        })
+    }
+}
+
+impl From<EdgePropertiesViewEntry> for proto::EdgePropertiesViewEntry {
+    fn from(value: EdgePropertiesViewEntry) -> Self {
+        proto::EdgePropertiesViewEntry {
+            source_uid: Some(value.source_uid.into()),
+            edge_name: Some(value.edge_name.into()),
+            destination_uid: Some(value.destination_uid.into()),
+            string_properties: Some(value.properties.string_properties.into()),
+            int_properties: Some(value.properties.int_properties.into()),
+            uint_properties: Some(value.properties.uint_properties.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for EdgePropertiesViewEntry {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.EdgePropertiesViewEntry";
+}
+
+impl serde_impl::ProtobufSerializable for EdgePropertiesViewEntry {
+    type ProtobufMessage = proto::EdgePropertiesViewEntry;
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct GraphView {
     pub nodes: FxHashMap<Uid, NodePropertiesView>,
     pub edges: FxHashMap<(Uid, EdgeName), FxHashSet<Uid>>,
+    /// Keyed by (source uid, edge name, destination uid). Edges without
+    /// properties have no entry.
+    pub edge_properties: FxHashMap<(Uid, EdgeName, Uid), EdgePropertiesView>,
 }
 
 impl GraphView {
@@ -1243,10 +1782,41 @@ impl GraphView {
             .extend(dst_uids);
     }
 
+    /// Adds an edge's properties, merging them into any already in the view.
+    /// The edge itself must be added separately.
+    pub fn add_edge_properties(
+        &mut self,
+        from: Uid,
+        edge_name: EdgeName,
+        to: Uid,
+        properties: EdgePropertiesView,
+    ) {
+        if properties.is_empty() {
+            return;
+        }
+        match self.edge_properties.entry((from, edge_name, to)) {
+            Entry::Occupied(e) => {
+                e.into_mut().merge(properties);
+            }
+            Entry::Vacant(e) => {
+                e.insert(properties);
+            }
+        }
+    }
+
     pub fn get_node(&self, uid: Uid) -> Option<&NodePropertiesView> {
         self.nodes.get(&uid)
     }
 
+    pub fn get_edge_properties(
+        &self,
+        from: Uid,
+        edge_name: &EdgeName,
+        to: Uid,
+    ) -> Option<&EdgePropertiesView> {
+        self.edge_properties.get(&(from, edge_name.clone(), to))
+    }
+
     pub fn get_edges(&self, from: Uid) -> impl Iterator<Item = (&EdgeName, &FxHashSet<Uid>)> {
         self.edges
             .iter()
@@ -1262,6 +1832,10 @@ impl GraphView {
         for ((src_uid, edge_name), dst_uids) in other.edges.into_iter() {
             self.add_edges(src_uid.clone(), edge_name.clone(), dst_uids.clone());
         }
+
+        for ((src_uid, edge_name, dst_uid), properties) in other.edge_properties.into_iter() {
+            self.add_edge_properties(src_uid, edge_name, dst_uid, properties);
+        }
     }
 
     pub fn get_nodes(&self) -> &FxHashMap<Uid, NodePropertiesView> {
@@ -1280,10 +1854,20 @@ impl TryFrom<proto::GraphView> for GraphView {
             .edges
             .ok_or(SerDeError::MissingField("edges"))?
             .try_into()?;
+        let mut edge_properties = FxHashMap::default();
+        edge_properties.reserve(value.edge_properties.len());
+        for entry in value.edge_properties.into_iter() {
+            let entry = EdgePropertiesViewEntry::try_from(entry)?;
+            edge_properties.insert(
+                (entry.source_uid, entry.edge_name, entry.destination_uid),
+                entry.properties,
+            );
+        }
 
         Ok(Self {
             nodes: nodes.entries,
             edges: edges.entries,
+            edge_properties,
         })
     }
 }
@@ -1298,9 +1882,23 @@ impl From<GraphView> for proto::GraphView {
             entries: value.edges,
         }
         .into();
+        let edge_properties = value
+            .edge_properties
+            .into_iter()
+            .map(|((source_uid, edge_name, destination_uid), properties)| {
+                EdgePropertiesViewEntry {
+                    source_uid,
+                    edge_name,
+                    destination_uid,
+                    properties,
+                }
+                .into()
+            })
+            .collect();
         Self {
             nodes: Some(nodes),
             edges: Some(edges),
+            edge_properties,
         }
     }
 }
@@ -1314,11 +1912,128 @@ impl serde_impl::ProtobufSerializable for GraphView {
     type ProtobufMessage = proto::GraphView;
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryIdProfile {
+    pub query_id: QueryId,
+    /// The number of nodes evaluated against the node query
+    pub nodes_examined: u64,
+    /// The number of examined nodes whose properties matched
+    pub nodes_matched: u64,
+    /// The number of examined nodes rejected by each property's filters,
+    /// including nodes where the property wasn't set
+    pub rejected_by_property: FxHashMap<PropertyName, u64>,
+    /// The number of edges fetched from the matched nodes
+    pub edges_fetched: u64,
+    /// The number of graph database round-trips made for the node query
+    pub database_reads: u64,
+    /// The time spent fetching properties and edges for the node query
+    pub elapsed: Duration,
+}
+
+impl TryFrom<proto::QueryIdProfile> for QueryIdProfile {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::QueryIdProfile) -> Result<Self, Self::Error> {
+        Ok(Self {
+            query_id: value
+                .query_id
+                .ok_or(SerDeError::MissingField("query_id"))?
+                .try_into()?,
+            nodes_examined: value.nodes_examined,
+            nodes_matched: value.nodes_matched,
+            rejected_by_property: value
+                .rejected_by_property
+                .into_iter()
+                .map(|(property_name, count)| Ok((PropertyName::try_from(property_name)?, count)))
+                .collect::<Result<_, SerDeError>>()?,
+            edges_fetched: value.edges_fetched,
+            database_reads: value.database_reads,
+            elapsed: value
+                .elapsed
+                .ok_or(SerDeError::MissingField("elapsed"))?
+                .into(),
+        })
+    }
+}
+
+impl From<QueryIdProfile> for proto::QueryIdProfile {
+    fn from(value: QueryIdProfile) -> Self {
+        Self {
+            query_id: Some(value.query_id.into()),
+            nodes_examined: value.nodes_examined,
+            nodes_matched: value.nodes_matched,
+            rejected_by_property: value
+                .rejected_by_property
+                .into_iter()
+                .map(|(property_name, count)| (property_name.value, count))
+                .collect(),
+            edges_fetched: value.edges_fetched,
+            database_reads: value.database_reads,
+            elapsed: Some(value.elapsed.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryIdProfile {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.QueryIdProfile";
+}
+
+impl serde_impl::ProtobufSerializable for QueryIdProfile {
+    type ProtobufMessage = proto::QueryIdProfile;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryProfile {
+    /// One entry per node query that was evaluated
+    pub query_ids: Vec<QueryIdProfile>,
+    /// The time spent evaluating the whole query
+    pub elapsed: Duration,
+}
+
+impl TryFrom<proto::QueryProfile> for QueryProfile {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::QueryProfile) -> Result<Self, Self::Error> {
+        Ok(Self {
+            query_ids: value
+                .query_ids
+                .into_iter()
+                .map(QueryIdProfile::try_from)
+                .collect::<Result<_, _>>()?,
+            elapsed: value
+                .elapsed
+                .ok_or(SerDeError::MissingField("elapsed"))?
+                .into(),
+        })
+    }
+}
+
+impl From<QueryProfile> for proto::QueryProfile {
+    fn from(value: QueryProfile) -> Self {
+        Self {
+            query_ids: value.query_ids.into_iter().map(Into::into).collect(),
+            elapsed: Some(value.elapsed.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryProfile {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.QueryProfile";
+}
+
+impl serde_impl::ProtobufSerializable for QueryProfile {
+    type ProtobufMessage = proto::QueryProfile;
+}
+
 #[derive(Debug, Clone)]
 pub struct QueryGraphWithUidRequest {
     pub tenant_id: uuid::Uuid,
     pub node_uid: Uid,
     pub graph_query: GraphQuery,
+    /// Whether to return a `QueryProfile` along with the result
+    pub profile: bool,
 }
 
 impl TryFrom<proto::QueryGraphWithUidRequest> for QueryGraphWithUidRequest {
@@ -1338,6 +2053,7 @@ impl TryFrom<proto::QueryGraphWithUidRequest> for QueryGraphWithUidRequest {
                 .graph_query
                 .ok_or(SerDeError::MissingField("graph_query"))?
                 .try_into()?,
+            profile: value.profile,
         })
     }
 }
@@ -1348,6 +2064,7 @@ impl From<QueryGraphWithUidRequest> for proto::QueryGraphWithUidRequest {
             tenant_id: Some(value.tenant_id.into()),
             node_uid: Some(value.node_uid.into()),
             graph_query: Some(value.graph_query.into()),
+            profile: value.profile,
         }
     }
 }
@@ -1474,6 +2191,8 @@ impl serde_impl::ProtobufSerializable for MaybeMatchWithUid {
 #[derive(Debug, Clone)]
 pub struct QueryGraphWithUidResponse {
     pub maybe_match: MaybeMatchWithUid,
+    /// How the query was evaluated, if it was requested
+    pub profile: Option<QueryProfile>,
 }
 
 impl TryFrom<proto::QueryGraphWithUidResponse> for QueryGraphWithUidResponse {
@@ -1484,6 +2203,7 @@ impl TryFrom<proto::QueryGraphWithUidResponse> for QueryGraphWithUidResponse {
                 .maybe_match
                 .ok_or(SerDeError::MissingField("maybe_match"))?
                 .try_into()?,
+            profile: value.profile.map(QueryProfile::try_from).transpose()?,
         })
     }
 }
@@ -1492,6 +2212,7 @@ impl From<QueryGraphWithUidResponse> for proto::QueryGraphWithUidResponse {
     fn from(value: QueryGraphWithUidResponse) -> Self {
         Self {
             maybe_match: Some(value.maybe_match.into()),
+            profile: value.profile.map(Into::into),
         }
     }
 }
@@ -1510,6 +2231,8 @@ pub struct QueryGraphFromUidRequest {
     pub tenant_id: uuid::Uuid,
     pub node_uid: Uid,
     pub graph_query: GraphQuery,
+    /// Whether to return a `QueryProfile` along with the result
+    pub profile: bool,
 }
 
 impl TryFrom<proto::QueryGraphFromUidRequest> for QueryGraphFromUidRequest {
@@ -1529,6 +2252,7 @@ impl TryFrom<proto::QueryGraphFromUidRequest> for QueryGraphFromUidRequest {
                 .graph_query
                 .ok_or(SerDeError::MissingField("graph_query"))?
                 .try_into()?,
+            profile: value.profile,
         })
     }
 }
@@ -1539,6 +2263,7 @@ impl From<QueryGraphFromUidRequest> for proto::QueryGraphFromUidRequest {
             tenant_id: Some(value.tenant_id.into()),
             node_uid: Some(value.node_uid.into()),
             graph_query: Some(value.graph_query.into()),
+            profile: value.profile,
         }
     }
 }
@@ -1555,6 +2280,8 @@ impl serde_impl::ProtobufSerializable for QueryGraphFromUidRequest {
 #[derive(Debug, Clone)]
 pub struct QueryGraphFromUidResponse {
     pub matched_graph: Option<GraphView>,
+    /// How the query was evaluated, if it was requested
+    pub profile: Option<QueryProfile>,
 }
 
 impl TryFrom<proto::QueryGraphFromUidResponse> for QueryGraphFromUidResponse {
@@ -1562,6 +2289,7 @@ impl TryFrom<proto::QueryGraphFromUidResponse> for QueryGraphFromUidResponse {
     fn try_from(value: proto::QueryGraphFromUidResponse) -> Result<Self, Self::Error> {
         Ok(Self {
             matched_graph: value.matched_graph.map(|g| g.try_into()).transpose()?,
+            profile: value.profile.map(QueryProfile::try_from).transpose()?,
         })
     }
 }
@@ -1570,6 +2298,7 @@ impl From<QueryGraphFromUidResponse> for proto::QueryGraphFromUidResponse {
     fn from(value: QueryGraphFromUidResponse) -> Self {
         Self {
             matched_graph: value.matched_graph.map(Into::into),
+            profile: value.profile.map(Into::into),
         }
     }
 }
@@ -1582,3 +2311,293 @@ impl type_url::TypeUrl for QueryGraphFromUidResponse {
 impl serde_impl::ProtobufSerializable for QueryGraphFromUidResponse {
     type ProtobufMessage = proto::QueryGraphFromUidResponse;
 }
+
+#[derive(Debug, Clone)]
+pub struct QueryAllGraphsWithUidRequest {
+    pub tenant_id: uuid::Uuid,
+    pub node_uid: Uid,
+    pub graph_query: GraphQuery,
+    /// The maximum number of matches to return, or `None` for all of them
+    pub limit: Option<u32>,
+    /// Only matches whose root uid is greater than this one are returned
+    pub after_root_uid: Option<Uid>,
+}
+
+impl TryFrom<proto::QueryAllGraphsWithUidRequest> for QueryAllGraphsWithUidRequest {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::QueryAllGraphsWithUidRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tenant_id: value
+                .tenant_id
+                .ok_or(SerDeError::MissingField("tenant_id"))?
+                .into(),
+            node_uid: value
+                .node_uid
+                .ok_or(SerDeError::MissingField("node_uid"))?
+                .try_into()?,
+            graph_query: value
+                .graph_query
+                .ok_or(SerDeError::MissingField("graph_query"))?
+                .try_into()?,
+            limit: match value.limit {
+                0 => None,
+                limit => Some(limit),
+            },
+            after_root_uid: value.after_root_uid.map(|u| u.try_into()).transpose()?,
+        })
+    }
+}
+
+impl From<QueryAllGraphsWithUidRequest> for proto::QueryAllGraphsWithUidRequest {
+    fn from(value: QueryAllGraphsWithUidRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+            node_uid: Some(value.node_uid.into()),
+            graph_query: Some(value.graph_query.into()),
+            limit: value.limit.unwrap_or(0),
+            after_root_uid: value.after_root_uid.map(Into::into),
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryAllGraphsWithUidRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.QueryAllGraphsWithUidRequest";
+}
+
+impl serde_impl::ProtobufSerializable for QueryAllGraphsWithUidRequest {
+    type ProtobufMessage = proto::QueryAllGraphsWithUidRequest;
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryAllGraphsWithUidResponse {
+    pub matched: MatchedGraphWithUid,
+}
+
+impl TryFrom<proto::QueryAllGraphsWithUidResponse> for QueryAllGraphsWithUidResponse {
+    type Error = SerDeError;
+    fn try_from(value: proto::QueryAllGraphsWithUidResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            matched: value
+                .matched
+                .ok_or(SerDeError::MissingField("matched"))?
+                .try_into()?,
+        })
+    }
+}
+
+impl From<QueryAllGraphsWithUidResponse> for proto::QueryAllGraphsWithUidResponse {
+    fn from(value: QueryAllGraphsWithUidResponse) -> Self {
+        Self {
+            matched: Some(value.matched.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryAllGraphsWithUidResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.QueryAllGraphsWithUidResponse";
+}
+
+impl serde_impl::ProtobufSerializable for QueryAllGraphsWithUidResponse {
+    type ProtobufMessage = proto::QueryAllGraphsWithUidResponse;
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryGraphRequest {
+    pub tenant_id: uuid::Uuid,
+    pub graph_query: GraphQuery,
+    /// The maximum number of matches to return, or `None` for all of them
+    pub limit: Option<u32>,
+}
+
+impl TryFrom<proto::QueryGraphRequest> for QueryGraphRequest {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::QueryGraphRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tenant_id: value
+                .tenant_id
+                .ok_or(SerDeError::MissingField("tenant_id"))?
+                .into(),
+            graph_query: value
+                .graph_query
+                .ok_or(SerDeError::MissingField("graph_query"))?
+                .try_into()?,
+            limit: match value.limit {
+                0 => None,
+                limit => Some(limit),
+            },
+        })
+    }
+}
+
+impl From<QueryGraphRequest> for proto::QueryGraphRequest {
+    fn from(value: QueryGraphRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+            graph_query: Some(value.graph_query.into()),
+            limit: value.limit.unwrap_or(0),
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryGraphRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.QueryGraphRequest";
+}
+
+impl serde_impl::ProtobufSerializable for QueryGraphRequest {
+    type ProtobufMessage = proto::QueryGraphRequest;
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryGraphResponse {
+    pub matches: Vec<MatchedGraphWithUid>,
+}
+
+impl TryFrom<proto::QueryGraphResponse> for QueryGraphResponse {
+    type Error = SerDeError;
+    fn try_from(value: proto::QueryGraphResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            matches: value
+                .matches
+                .into_iter()
+                .map(MatchedGraphWithUid::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<QueryGraphResponse> for proto::QueryGraphResponse {
+    fn from(value: QueryGraphResponse) -> Self {
+        Self {
+            matches: value.matches.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryGraphResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.QueryGraphResponse";
+}
+
+impl serde_impl::ProtobufSerializable for QueryGraphResponse {
+    type ProtobufMessage = proto::QueryGraphResponse;
+}
+
+#[derive(Debug, Clone)]
+pub struct GetNeighborhoodRequest {
+    pub tenant_id: uuid::Uuid,
+    pub node_uid: Uid,
+    /// The maximum number of hops from `node_uid`
+    pub max_depth: u32,
+    /// Only these edges are followed, or every edge if empty
+    pub edge_names: Vec<EdgeName>,
+    /// Only nodes of these types are included, apart from `node_uid` itself,
+    /// or nodes of every type if empty
+    pub node_types: Vec<NodeType>,
+    /// The maximum number of nodes to return, or `None` for no limit
+    pub max_nodes: Option<u32>,
+    /// The maximum number of edges to return, or `None` for no limit
+    pub max_edges: Option<u32>,
+}
+
+impl TryFrom<proto::GetNeighborhoodRequest> for GetNeighborhoodRequest {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::GetNeighborhoodRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tenant_id: value
+                .tenant_id
+                .ok_or(SerDeError::MissingField("tenant_id"))?
+                .into(),
+            node_uid: value
+                .node_uid
+                .ok_or(SerDeError::MissingField("node_uid"))?
+                .try_into()?,
+            max_depth: value.max_depth,
+            edge_names: value
+                .edge_names
+                .into_iter()
+                .map(EdgeName::try_from)
+                .collect::<Result<_, _>>()?,
+            node_types: value
+                .node_types
+                .into_iter()
+                .map(NodeType::try_from)
+                .collect::<Result<_, _>>()?,
+            max_nodes: match value.max_nodes {
+                0 => None,
+                max_nodes => Some(max_nodes),
+            },
+            max_edges: match value.max_edges {
+                0 => None,
+                max_edges => Some(max_edges),
+            },
+        })
+    }
+}
+
+impl From<GetNeighborhoodRequest> for proto::GetNeighborhoodRequest {
+    fn from(value: GetNeighborhoodRequest) -> Self {
+        Self {
+            tenant_id: Some(value.tenant_id.into()),
+            node_uid: Some(value.node_uid.into()),
+            max_depth: value.max_depth,
+            edge_names: value.edge_names.into_iter().map(Into::into).collect(),
+            node_types: value.node_types.into_iter().map(Into::into).collect(),
+            max_nodes: value.max_nodes.unwrap_or(0),
+            max_edges: value.max_edges.unwrap_or(0),
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetNeighborhoodRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.GetNeighborhoodRequest";
+}
+
+impl serde_impl::ProtobufSerializable for GetNeighborhoodRequest {
+    type ProtobufMessage = proto::GetNeighborhoodRequest;
+}
+
+#[derive(Debug, Clone)]
+pub struct GetNeighborhoodResponse {
+    pub neighborhood: GraphView,
+    /// Whether `max_nodes` or `max_edges` left out part of the neighborhood
+    pub truncated: bool,
+}
+
+impl TryFrom<proto::GetNeighborhoodResponse> for GetNeighborhoodResponse {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::GetNeighborhoodResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            neighborhood: value
+                .neighborhood
+                .ok_or(SerDeError::MissingField("neighborhood"))?
+                .try_into()?,
+            truncated: value.truncated,
+        })
+    }
+}
+
+impl From<GetNeighborhoodResponse> for proto::GetNeighborhoodResponse {
+    fn from(value: GetNeighborhoodResponse) -> Self {
+        Self {
+            neighborhood: Some(value.neighborhood.into()),
+            truncated: value.truncated,
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetNeighborhoodResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_service.v1beta1.GetNeighborhoodResponse";
+}
+
+impl serde_impl::ProtobufSerializable for GetNeighborhoodResponse {
+    type ProtobufMessage = proto::GetNeighborhoodResponse;
+}
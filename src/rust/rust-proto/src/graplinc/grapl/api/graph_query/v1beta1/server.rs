@@ -9,6 +9,7 @@ use futures::{
         Receiver,
         Sender,
     },
+    stream::BoxStream,
     Future,
     FutureExt,
 };
@@ -21,10 +22,17 @@ use tonic::transport::{
 
 use crate::{
     execute_rpc,
+    execute_server_streaming_rpc,
     graplinc::grapl::api::{
         graph_query::v1beta1::messages::{
+            GetNeighborhoodRequest,
+            GetNeighborhoodResponse,
+            QueryAllGraphsWithUidRequest,
+            QueryAllGraphsWithUidResponse,
             QueryGraphFromUidRequest,
             QueryGraphFromUidResponse,
+            QueryGraphRequest,
+            QueryGraphResponse,
             QueryGraphWithUidRequest,
             QueryGraphWithUidResponse,
         },
@@ -44,8 +52,14 @@ use crate::{
             GraphQueryService as GraphQueryServiceProto,
             GraphQueryServiceServer as GraphQueryServiceServerProto,
         },
+        GetNeighborhoodRequest as GetNeighborhoodRequestProto,
+        GetNeighborhoodResponse as GetNeighborhoodResponseProto,
+        QueryAllGraphsWithUidRequest as QueryAllGraphsWithUidRequestProto,
+        QueryAllGraphsWithUidResponse as QueryAllGraphsWithUidResponseProto,
         QueryGraphFromUidRequest as QueryGraphFromUidRequestProto,
         QueryGraphFromUidResponse as QueryGraphFromUidResponseProto,
+        QueryGraphRequest as QueryGraphRequestProto,
+        QueryGraphResponse as QueryGraphResponseProto,
         QueryGraphWithUidRequest as QueryGraphWithUidRequestProto,
         QueryGraphWithUidResponse as QueryGraphWithUidResponseProto,
     },
@@ -70,6 +84,18 @@ pub trait GraphQueryApi {
         &self,
         request: QueryGraphFromUidRequest,
     ) -> Result<QueryGraphFromUidResponse, Self::Error>;
+    async fn query_all_graphs_with_uid(
+        &self,
+        request: QueryAllGraphsWithUidRequest,
+    ) -> Result<BoxStream<'static, Result<QueryAllGraphsWithUidResponse, Self::Error>>, Self::Error>;
+    async fn query_graph(
+        &self,
+        request: QueryGraphRequest,
+    ) -> Result<QueryGraphResponse, Self::Error>;
+    async fn get_neighborhood(
+        &self,
+        request: GetNeighborhoodRequest,
+    ) -> Result<GetNeighborhoodResponse, Self::Error>;
 }
 
 #[tonic::async_trait]
@@ -77,6 +103,9 @@ impl<T> GraphQueryServiceProto for GrpcApi<T>
 where
     T: GraphQueryApi + Send + Sync + 'static,
 {
+    type QueryAllGraphsWithUidStream =
+        BoxStream<'static, Result<QueryAllGraphsWithUidResponseProto, tonic::Status>>;
+
     async fn query_graph_with_uid(
         &self,
         request: tonic::Request<QueryGraphWithUidRequestProto>,
@@ -90,6 +119,27 @@ where
     ) -> Result<tonic::Response<QueryGraphFromUidResponseProto>, tonic::Status> {
         execute_rpc!(self, request, query_graph_from_uid)
     }
+
+    async fn query_all_graphs_with_uid(
+        &self,
+        request: tonic::Request<QueryAllGraphsWithUidRequestProto>,
+    ) -> Result<tonic::Response<Self::QueryAllGraphsWithUidStream>, tonic::Status> {
+        execute_server_streaming_rpc!(self, request, query_all_graphs_with_uid)
+    }
+
+    async fn query_graph(
+        &self,
+        request: tonic::Request<QueryGraphRequestProto>,
+    ) -> Result<tonic::Response<QueryGraphResponseProto>, tonic::Status> {
+        execute_rpc!(self, request, query_graph)
+    }
+
+    async fn get_neighborhood(
+        &self,
+        request: tonic::Request<GetNeighborhoodRequestProto>,
+    ) -> Result<tonic::Response<GetNeighborhoodResponseProto>, tonic::Status> {
+        execute_rpc!(self, request, get_neighborhood)
+    }
 }
 
 /**
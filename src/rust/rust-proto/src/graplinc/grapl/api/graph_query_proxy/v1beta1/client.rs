@@ -1,3 +1,6 @@
+use std::time::Duration;
+
+use futures::stream::BoxStream;
 use tonic::transport::Endpoint;
 
 use crate::{
@@ -61,4 +64,22 @@ impl GraphQueryProxyClient {
             )
             .await
     }
+
+    /// Stream every match of the query that the node takes part in, in
+    /// ascending order of root uid. The whole stream must be consumed within
+    /// `request_timeout`.
+    pub async fn query_all_graphs_with_uid(
+        &mut self,
+        request_timeout: Duration,
+        request: native::QueryAllGraphsWithUidRequest,
+    ) -> Result<
+        BoxStream<'static, Result<native::QueryAllGraphsWithUidResponse, ClientError>>,
+        ClientError,
+    > {
+        self.client
+            .execute_server_streaming(request, request_timeout, |mut client, request| async move {
+                client.query_all_graphs_with_uid(request).await
+            })
+            .await
+    }
 }
@@ -13,6 +13,8 @@ use crate::{
 pub struct QueryGraphWithUidRequest {
     pub node_uid: Uid,
     pub graph_query: graph_query_messages::GraphQuery,
+    /// Whether to return a `QueryProfile` along with the result
+    pub profile: bool,
 }
 
 impl TryFrom<proto::QueryGraphWithUidRequest> for QueryGraphWithUidRequest {
@@ -28,6 +30,7 @@ impl TryFrom<proto::QueryGraphWithUidRequest> for QueryGraphWithUidRequest {
                 .graph_query
                 .ok_or(SerDeError::MissingField("graph_query"))?
                 .try_into()?,
+            profile: value.profile,
         })
     }
 }
@@ -37,6 +40,7 @@ impl From<QueryGraphWithUidRequest> for proto::QueryGraphWithUidRequest {
         Self {
             node_uid: Some(value.node_uid.into()),
             graph_query: Some(value.graph_query.into()),
+            profile: value.profile,
         }
     }
 }
@@ -53,6 +57,8 @@ impl serde_impl::ProtobufSerializable for QueryGraphWithUidRequest {
 #[derive(Debug, Clone)]
 pub struct QueryGraphWithUidResponse {
     pub maybe_match: graph_query_messages::MaybeMatchWithUid,
+    /// How the query was evaluated, if it was requested
+    pub profile: Option<graph_query_messages::QueryProfile>,
 }
 
 impl TryFrom<proto::QueryGraphWithUidResponse> for QueryGraphWithUidResponse {
@@ -63,6 +69,10 @@ impl TryFrom<proto::QueryGraphWithUidResponse> for QueryGraphWithUidResponse {
                 .maybe_match
                 .ok_or(SerDeError::MissingField("maybe_match"))?
                 .try_into()?,
+            profile: value
+                .profile
+                .map(graph_query_messages::QueryProfile::try_from)
+                .transpose()?,
         })
     }
 }
@@ -71,6 +81,7 @@ impl From<QueryGraphWithUidResponse> for proto::QueryGraphWithUidResponse {
     fn from(value: QueryGraphWithUidResponse) -> Self {
         Self {
             maybe_match: Some(value.maybe_match.into()),
+            profile: value.profile.map(Into::into),
         }
     }
 }
@@ -80,6 +91,7 @@ impl From<graph_query_messages::QueryGraphWithUidResponse> for QueryGraphWithUid
     fn from(other: graph_query_messages::QueryGraphWithUidResponse) -> Self {
         Self {
             maybe_match: other.maybe_match,
+            profile: other.profile,
         }
     }
 }
@@ -97,6 +109,8 @@ impl serde_impl::ProtobufSerializable for QueryGraphWithUidResponse {
 pub struct QueryGraphFromUidRequest {
     pub node_uid: Uid,
     pub graph_query: graph_query_messages::GraphQuery,
+    /// Whether to return a `QueryProfile` along with the result
+    pub profile: bool,
 }
 
 impl TryFrom<proto::QueryGraphFromUidRequest> for QueryGraphFromUidRequest {
@@ -112,6 +126,7 @@ impl TryFrom<proto::QueryGraphFromUidRequest> for QueryGraphFromUidRequest {
                 .graph_query
                 .ok_or(SerDeError::MissingField("graph_query"))?
                 .try_into()?,
+            profile: value.profile,
         })
     }
 }
@@ -121,6 +136,7 @@ impl From<QueryGraphFromUidRequest> for proto::QueryGraphFromUidRequest {
         Self {
             node_uid: Some(value.node_uid.into()),
             graph_query: Some(value.graph_query.into()),
+            profile: value.profile,
         }
     }
 }
@@ -137,6 +153,8 @@ impl serde_impl::ProtobufSerializable for QueryGraphFromUidRequest {
 #[derive(Debug, Clone)]
 pub struct QueryGraphFromUidResponse {
     pub matched_graph: Option<graph_query_messages::GraphView>,
+    /// How the query was evaluated, if it was requested
+    pub profile: Option<graph_query_messages::QueryProfile>,
 }
 
 impl TryFrom<proto::QueryGraphFromUidResponse> for QueryGraphFromUidResponse {
@@ -144,6 +162,10 @@ impl TryFrom<proto::QueryGraphFromUidResponse> for QueryGraphFromUidResponse {
     fn try_from(value: proto::QueryGraphFromUidResponse) -> Result<Self, Self::Error> {
         Ok(Self {
             matched_graph: value.matched_graph.map(|g| g.try_into()).transpose()?,
+            profile: value
+                .profile
+                .map(graph_query_messages::QueryProfile::try_from)
+                .transpose()?,
         })
     }
 }
@@ -152,6 +174,7 @@ impl From<QueryGraphFromUidResponse> for proto::QueryGraphFromUidResponse {
     fn from(value: QueryGraphFromUidResponse) -> Self {
         Self {
             matched_graph: value.matched_graph.map(Into::into),
+            profile: value.profile.map(Into::into),
         }
     }
 }
@@ -161,6 +184,7 @@ impl From<graph_query_messages::QueryGraphFromUidResponse> for QueryGraphFromUid
     fn from(other: graph_query_messages::QueryGraphFromUidResponse) -> Self {
         Self {
             matched_graph: other.matched_graph,
+            profile: other.profile,
         }
     }
 }
@@ -173,3 +197,98 @@ impl type_url::TypeUrl for QueryGraphFromUidResponse {
 impl serde_impl::ProtobufSerializable for QueryGraphFromUidResponse {
     type ProtobufMessage = proto::QueryGraphFromUidResponse;
 }
+
+#[derive(Debug, Clone)]
+pub struct QueryAllGraphsWithUidRequest {
+    pub node_uid: Uid,
+    pub graph_query: graph_query_messages::GraphQuery,
+    /// The maximum number of matches to return, or `None` for all of them
+    pub limit: Option<u32>,
+    /// Only matches whose root uid is greater than this one are returned
+    pub after_root_uid: Option<Uid>,
+}
+
+impl TryFrom<proto::QueryAllGraphsWithUidRequest> for QueryAllGraphsWithUidRequest {
+    type Error = SerDeError;
+
+    fn try_from(value: proto::QueryAllGraphsWithUidRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            node_uid: value
+                .node_uid
+                .ok_or(SerDeError::MissingField("node_uid"))?
+                .try_into()?,
+            graph_query: value
+                .graph_query
+                .ok_or(SerDeError::MissingField("graph_query"))?
+                .try_into()?,
+            limit: match value.limit {
+                0 => None,
+                limit => Some(limit),
+            },
+            after_root_uid: value.after_root_uid.map(|u| u.try_into()).transpose()?,
+        })
+    }
+}
+
+impl From<QueryAllGraphsWithUidRequest> for proto::QueryAllGraphsWithUidRequest {
+    fn from(value: QueryAllGraphsWithUidRequest) -> Self {
+        Self {
+            node_uid: Some(value.node_uid.into()),
+            graph_query: Some(value.graph_query.into()),
+            limit: value.limit.unwrap_or(0),
+            after_root_uid: value.after_root_uid.map(Into::into),
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryAllGraphsWithUidRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_proxy.v1beta1.QueryAllGraphsWithUidRequest";
+}
+
+impl serde_impl::ProtobufSerializable for QueryAllGraphsWithUidRequest {
+    type ProtobufMessage = proto::QueryAllGraphsWithUidRequest;
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryAllGraphsWithUidResponse {
+    pub matched: graph_query_messages::MatchedGraphWithUid,
+}
+
+impl TryFrom<proto::QueryAllGraphsWithUidResponse> for QueryAllGraphsWithUidResponse {
+    type Error = SerDeError;
+    fn try_from(value: proto::QueryAllGraphsWithUidResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            matched: value
+                .matched
+                .ok_or(SerDeError::MissingField("matched"))?
+                .try_into()?,
+        })
+    }
+}
+
+impl From<QueryAllGraphsWithUidResponse> for proto::QueryAllGraphsWithUidResponse {
+    fn from(value: QueryAllGraphsWithUidResponse) -> Self {
+        Self {
+            matched: Some(value.matched.into()),
+        }
+    }
+}
+
+// Convert from a Graph Query response to a Graph Query Proxy response.
+impl From<graph_query_messages::QueryAllGraphsWithUidResponse> for QueryAllGraphsWithUidResponse {
+    fn from(other: graph_query_messages::QueryAllGraphsWithUidResponse) -> Self {
+        Self {
+            matched: other.matched,
+        }
+    }
+}
+
+impl type_url::TypeUrl for QueryAllGraphsWithUidResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_query_proxy.v1beta1.QueryAllGraphsWithUidResponse";
+}
+
+impl serde_impl::ProtobufSerializable for QueryAllGraphsWithUidResponse {
+    type ProtobufMessage = proto::QueryAllGraphsWithUidResponse;
+}
@@ -9,6 +9,7 @@ use futures::{
         Receiver,
         Sender,
     },
+    stream::BoxStream,
     Future,
     FutureExt,
 };
@@ -21,8 +22,11 @@ use tonic::transport::{
 
 use crate::{
     execute_rpc,
+    execute_server_streaming_rpc,
     graplinc::grapl::api::{
         graph_query_proxy::v1beta1::messages::{
+            QueryAllGraphsWithUidRequest,
+            QueryAllGraphsWithUidResponse,
             QueryGraphFromUidRequest,
             QueryGraphFromUidResponse,
             QueryGraphWithUidRequest,
@@ -59,6 +63,10 @@ pub trait GraphQueryProxyApi {
         &self,
         request: QueryGraphFromUidRequest,
     ) -> Result<QueryGraphFromUidResponse, Self::Error>;
+    async fn query_all_graphs_with_uid(
+        &self,
+        request: QueryAllGraphsWithUidRequest,
+    ) -> Result<BoxStream<'static, Result<QueryAllGraphsWithUidResponse, Self::Error>>, Self::Error>;
 }
 
 #[tonic::async_trait]
@@ -66,6 +74,9 @@ impl<T> GraphQueryProxyService for GrpcApi<T>
 where
     T: GraphQueryProxyApi + Send + Sync + 'static,
 {
+    type QueryAllGraphsWithUidStream =
+        BoxStream<'static, Result<proto::QueryAllGraphsWithUidResponse, tonic::Status>>;
+
     async fn query_graph_with_uid(
         &self,
         request: tonic::Request<proto::QueryGraphWithUidRequest>,
@@ -79,6 +90,13 @@ where
     ) -> Result<tonic::Response<proto::QueryGraphFromUidResponse>, tonic::Status> {
         execute_rpc!(self, request, query_graph_from_uid)
     }
+
+    async fn query_all_graphs_with_uid(
+        &self,
+        request: tonic::Request<proto::QueryAllGraphsWithUidRequest>,
+    ) -> Result<tonic::Response<Self::QueryAllGraphsWithUidStream>, tonic::Status> {
+        execute_server_streaming_rpc!(self, request, query_all_graphs_with_uid)
+    }
 }
 
 /**
@@ -63,4 +63,88 @@ impl GraphSchemaManagerClient {
             )
             .await
     }
+
+    pub async fn set_retention_policy(
+        &mut self,
+        request: native::SetRetentionPolicyRequest,
+    ) -> Result<native::SetRetentionPolicyResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.set_retention_policy(request).await },
+            )
+            .await
+    }
+
+    pub async fn get_retention_policy(
+        &mut self,
+        request: native::GetRetentionPolicyRequest,
+    ) -> Result<native::GetRetentionPolicyResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.get_retention_policy(request).await },
+            )
+            .await
+    }
+
+    pub async fn get_node_schema(
+        &mut self,
+        request: native::GetNodeSchemaRequest,
+    ) -> Result<native::GetNodeSchemaResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.get_node_schema(request).await },
+            )
+            .await
+    }
+
+    pub async fn list_node_schemas(
+        &mut self,
+        request: native::ListNodeSchemasRequest,
+    ) -> Result<native::ListNodeSchemasResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.list_node_schemas(request).await },
+            )
+            .await
+    }
+
+    pub async fn get_raw_schema(
+        &mut self,
+        request: native::GetRawSchemaRequest,
+    ) -> Result<native::GetRawSchemaResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.get_raw_schema(request).await },
+            )
+            .await
+    }
+
+    pub async fn diff_schemas(
+        &mut self,
+        request: native::DiffSchemasRequest,
+    ) -> Result<native::DiffSchemasResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.diff_schemas(request).await },
+            )
+            .await
+    }
 }
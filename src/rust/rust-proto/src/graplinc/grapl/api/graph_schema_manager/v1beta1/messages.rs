@@ -1,17 +1,54 @@
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
 use bytes::Bytes;
 
 use crate::{
     graplinc::grapl::common::v1beta1::types::{
         EdgeName,
         NodeType,
+        PropertyName,
     },
     protobufs::graplinc::grapl::api::graph_schema_manager::v1beta1::{
+        identity_algorithm::Algorithm as IdentityAlgorithmAlgorithmProto,
+        node_schema_diff::Diff as NodeSchemaDiffDiffProto,
+        retention_policy::Policy as RetentionPolicyPolicyProto,
         DeploySchemaRequest as DeploySchemaRequestProto,
         DeploySchemaResponse as DeploySchemaResponseProto,
+        DiffSchemasRequest as DiffSchemasRequestProto,
+        DiffSchemasResponse as DiffSchemasResponseProto,
         EdgeCardinality as EdgeCardinalityProto,
+        EdgeSchema as EdgeSchemaProto,
+        EdgeSchemaChange as EdgeSchemaChangeProto,
         GetEdgeSchemaRequest as GetEdgeSchemaRequestProto,
         GetEdgeSchemaResponse as GetEdgeSchemaResponseProto,
+        GetNodeSchemaRequest as GetNodeSchemaRequestProto,
+        GetNodeSchemaResponse as GetNodeSchemaResponseProto,
+        GetRawSchemaRequest as GetRawSchemaRequestProto,
+        GetRawSchemaResponse as GetRawSchemaResponseProto,
+        GetRetentionPolicyRequest as GetRetentionPolicyRequestProto,
+        GetRetentionPolicyResponse as GetRetentionPolicyResponseProto,
+        IdentityAlgorithm as IdentityAlgorithmProto,
+        IdentityAlgorithmChange as IdentityAlgorithmChangeProto,
+        ListNodeSchemasRequest as ListNodeSchemasRequestProto,
+        ListNodeSchemasResponse as ListNodeSchemasResponseProto,
+        NodeSchema as NodeSchemaProto,
+        NodeSchemaChanges as NodeSchemaChangesProto,
+        NodeSchemaDiff as NodeSchemaDiffProto,
+        NodeSchemaVersion as NodeSchemaVersionProto,
+        NodeSchemaVersions as NodeSchemaVersionsProto,
+        PropertySchema as PropertySchemaProto,
+        PropertySchemaChange as PropertySchemaChangeProto,
+        PropertyType as PropertyTypeProto,
+        RetainForever as RetainForeverProto,
+        RetentionPolicy as RetentionPolicyProto,
         SchemaType as SchemaTypeProto,
+        SessionIdentity as SessionIdentityProto,
+        SetRetentionPolicyRequest as SetRetentionPolicyRequestProto,
+        SetRetentionPolicyResponse as SetRetentionPolicyResponseProto,
+        StaticIdentity as StaticIdentityProto,
     },
     serde_impl,
     type_url,
@@ -53,6 +90,8 @@ pub struct DeploySchemaRequest {
     pub schema: Bytes,
     pub schema_type: SchemaType,
     pub schema_version: u32,
+    /// Validate the schema without deploying it
+    pub dry_run: bool,
 }
 
 impl TryFrom<DeploySchemaRequestProto> for DeploySchemaRequest {
@@ -78,6 +117,7 @@ impl TryFrom<DeploySchemaRequestProto> for DeploySchemaRequest {
             schema,
             schema_type,
             schema_version: value.schema_version,
+            dry_run: value.dry_run,
         })
     }
 }
@@ -90,6 +130,7 @@ impl From<DeploySchemaRequest> for DeploySchemaRequestProto {
             schema_type: schema_type as i32,
             schema: value.schema,
             schema_version: value.schema_version,
+            dry_run: value.dry_run,
         }
     }
 }
@@ -265,3 +306,1272 @@ impl type_url::TypeUrl for EdgeCardinality {
     const TYPE_URL: &'static str =
         "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.EdgeCardinality";
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetentionPolicy {
+    /// Data expires this long after it's written, rounded up to the second
+    TimeToLive(Duration),
+    /// Data never expires
+    RetainForever,
+}
+
+impl RetentionPolicy {
+    /// The time to live in seconds, if data expires
+    pub fn ttl_seconds(&self) -> Option<u64> {
+        match self {
+            RetentionPolicy::TimeToLive(ttl) => {
+                let seconds = ttl.as_secs();
+                if ttl.subsec_nanos() > 0 {
+                    Some(seconds.saturating_add(1))
+                } else {
+                    Some(seconds)
+                }
+            }
+            RetentionPolicy::RetainForever => None,
+        }
+    }
+}
+
+impl TryFrom<RetentionPolicyProto> for RetentionPolicy {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: RetentionPolicyProto) -> Result<Self, Self::Error> {
+        match response_proto.policy {
+            Some(RetentionPolicyPolicyProto::TimeToLive(ttl)) => {
+                Ok(RetentionPolicy::TimeToLive(ttl.into()))
+            }
+            Some(RetentionPolicyPolicyProto::RetainForever(RetainForeverProto {})) => {
+                Ok(RetentionPolicy::RetainForever)
+            }
+            None => Err(SerDeError::MissingField("RetentionPolicy.policy")),
+        }
+    }
+}
+
+impl From<RetentionPolicy> for RetentionPolicyProto {
+    fn from(value: RetentionPolicy) -> Self {
+        let policy = match value {
+            RetentionPolicy::TimeToLive(ttl) => RetentionPolicyPolicyProto::TimeToLive(ttl.into()),
+            RetentionPolicy::RetainForever => {
+                RetentionPolicyPolicyProto::RetainForever(RetainForeverProto {})
+            }
+        };
+        RetentionPolicyProto {
+            policy: Some(policy),
+        }
+    }
+}
+
+impl type_url::TypeUrl for RetentionPolicy {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.RetentionPolicy";
+}
+
+impl serde_impl::ProtobufSerializable for RetentionPolicy {
+    type ProtobufMessage = RetentionPolicyProto;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetRetentionPolicyRequest {
+    pub tenant_id: uuid::Uuid,
+    /// The node type the policy applies to, or None for the tenant's default
+    pub node_type: Option<NodeType>,
+    /// The policy, or None to remove the existing policy
+    pub retention_policy: Option<RetentionPolicy>,
+}
+
+impl TryFrom<SetRetentionPolicyRequestProto> for SetRetentionPolicyRequest {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: SetRetentionPolicyRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = response_proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField(
+                "SetRetentionPolicyRequest.tenant_id",
+            ))?
+            .into();
+
+        let node_type = response_proto
+            .node_type
+            .map(NodeType::try_from)
+            .transpose()?;
+
+        let retention_policy = response_proto
+            .retention_policy
+            .map(RetentionPolicy::try_from)
+            .transpose()?;
+
+        Ok(SetRetentionPolicyRequest {
+            tenant_id,
+            node_type,
+            retention_policy,
+        })
+    }
+}
+
+impl From<SetRetentionPolicyRequest> for SetRetentionPolicyRequestProto {
+    fn from(value: SetRetentionPolicyRequest) -> Self {
+        SetRetentionPolicyRequestProto {
+            tenant_id: Some(value.tenant_id.into()),
+            node_type: value.node_type.map(Into::into),
+            retention_policy: value.retention_policy.map(Into::into),
+        }
+    }
+}
+
+impl type_url::TypeUrl for SetRetentionPolicyRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.SetRetentionPolicyRequest";
+}
+
+impl serde_impl::ProtobufSerializable for SetRetentionPolicyRequest {
+    type ProtobufMessage = SetRetentionPolicyRequestProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SetRetentionPolicyResponse {}
+
+impl TryFrom<SetRetentionPolicyResponseProto> for SetRetentionPolicyResponse {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: SetRetentionPolicyResponseProto) -> Result<Self, Self::Error> {
+        let SetRetentionPolicyResponseProto {} = response_proto;
+        Ok(SetRetentionPolicyResponse {})
+    }
+}
+
+impl From<SetRetentionPolicyResponse> for SetRetentionPolicyResponseProto {
+    fn from(response: SetRetentionPolicyResponse) -> Self {
+        let SetRetentionPolicyResponse {} = response;
+        SetRetentionPolicyResponseProto {}
+    }
+}
+
+impl type_url::TypeUrl for SetRetentionPolicyResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.SetRetentionPolicyResponse";
+}
+
+impl serde_impl::ProtobufSerializable for SetRetentionPolicyResponse {
+    type ProtobufMessage = SetRetentionPolicyResponseProto;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetRetentionPolicyRequest {
+    pub tenant_id: uuid::Uuid,
+    pub node_type: NodeType,
+}
+
+impl TryFrom<GetRetentionPolicyRequestProto> for GetRetentionPolicyRequest {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: GetRetentionPolicyRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = response_proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField(
+                "GetRetentionPolicyRequest.tenant_id",
+            ))?
+            .into();
+
+        let node_type = response_proto
+            .node_type
+            .ok_or(SerDeError::MissingField(
+                "GetRetentionPolicyRequest.node_type",
+            ))?
+            .try_into()?;
+
+        Ok(GetRetentionPolicyRequest {
+            tenant_id,
+            node_type,
+        })
+    }
+}
+
+impl From<GetRetentionPolicyRequest> for GetRetentionPolicyRequestProto {
+    fn from(value: GetRetentionPolicyRequest) -> Self {
+        GetRetentionPolicyRequestProto {
+            tenant_id: Some(value.tenant_id.into()),
+            node_type: Some(value.node_type.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetRetentionPolicyRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.GetRetentionPolicyRequest";
+}
+
+impl serde_impl::ProtobufSerializable for GetRetentionPolicyRequest {
+    type ProtobufMessage = GetRetentionPolicyRequestProto;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetRetentionPolicyResponse {
+    pub retention_policy: RetentionPolicy,
+}
+
+impl TryFrom<GetRetentionPolicyResponseProto> for GetRetentionPolicyResponse {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: GetRetentionPolicyResponseProto) -> Result<Self, Self::Error> {
+        let retention_policy = response_proto
+            .retention_policy
+            .ok_or(SerDeError::MissingField(
+                "GetRetentionPolicyResponse.retention_policy",
+            ))?
+            .try_into()?;
+
+        Ok(GetRetentionPolicyResponse { retention_policy })
+    }
+}
+
+impl From<GetRetentionPolicyResponse> for GetRetentionPolicyResponseProto {
+    fn from(value: GetRetentionPolicyResponse) -> Self {
+        GetRetentionPolicyResponseProto {
+            retention_policy: Some(value.retention_policy.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetRetentionPolicyResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.GetRetentionPolicyResponse";
+}
+
+impl serde_impl::ProtobufSerializable for GetRetentionPolicyResponse {
+    type ProtobufMessage = GetRetentionPolicyResponseProto;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PropertyType {
+    ImmutableString,
+    ImmutableI64,
+    MaxI64,
+    MinI64,
+    ImmutableU64,
+    MaxU64,
+    MinU64,
+}
+
+impl TryFrom<PropertyTypeProto> for PropertyType {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: PropertyTypeProto) -> Result<Self, Self::Error> {
+        match response_proto {
+            PropertyTypeProto::ImmutableString => Ok(PropertyType::ImmutableString),
+            PropertyTypeProto::ImmutableI64 => Ok(PropertyType::ImmutableI64),
+            PropertyTypeProto::MaxI64 => Ok(PropertyType::MaxI64),
+            PropertyTypeProto::MinI64 => Ok(PropertyType::MinI64),
+            PropertyTypeProto::ImmutableU64 => Ok(PropertyType::ImmutableU64),
+            PropertyTypeProto::MaxU64 => Ok(PropertyType::MaxU64),
+            PropertyTypeProto::MinU64 => Ok(PropertyType::MinU64),
+            PropertyTypeProto::Unspecified => Err(SerDeError::UnknownVariant("PropertyType")),
+        }
+    }
+}
+
+impl From<PropertyType> for PropertyTypeProto {
+    fn from(value: PropertyType) -> Self {
+        match value {
+            PropertyType::ImmutableString => PropertyTypeProto::ImmutableString,
+            PropertyType::ImmutableI64 => PropertyTypeProto::ImmutableI64,
+            PropertyType::MaxI64 => PropertyTypeProto::MaxI64,
+            PropertyType::MinI64 => PropertyTypeProto::MinI64,
+            PropertyType::ImmutableU64 => PropertyTypeProto::ImmutableU64,
+            PropertyType::MaxU64 => PropertyTypeProto::MaxU64,
+            PropertyType::MinU64 => PropertyTypeProto::MinU64,
+        }
+    }
+}
+
+impl type_url::TypeUrl for PropertyType {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.PropertyType";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PropertySchema {
+    pub property_name: PropertyName,
+    pub property_type: PropertyType,
+    /// Always false for edge properties
+    pub identity_only: bool,
+}
+
+impl TryFrom<PropertySchemaProto> for PropertySchema {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: PropertySchemaProto) -> Result<Self, Self::Error> {
+        let property_type = response_proto.property_type().try_into()?;
+
+        let property_name = response_proto
+            .property_name
+            .ok_or(SerDeError::MissingField("PropertySchema.property_name"))?
+            .try_into()?;
+
+        Ok(PropertySchema {
+            property_name,
+            property_type,
+            identity_only: response_proto.identity_only,
+        })
+    }
+}
+
+impl From<PropertySchema> for PropertySchemaProto {
+    fn from(value: PropertySchema) -> Self {
+        let property_type: PropertyTypeProto = value.property_type.into();
+        PropertySchemaProto {
+            property_name: Some(value.property_name.into()),
+            property_type: property_type as i32,
+            identity_only: value.identity_only,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EdgeSchema {
+    pub edge_name: EdgeName,
+    pub reverse_edge_name: EdgeName,
+    pub cardinality: EdgeCardinality,
+    pub reverse_cardinality: EdgeCardinality,
+    pub properties: Vec<PropertySchema>,
+}
+
+impl TryFrom<EdgeSchemaProto> for EdgeSchema {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: EdgeSchemaProto) -> Result<Self, Self::Error> {
+        let cardinality = response_proto.cardinality().try_into()?;
+        let reverse_cardinality = response_proto.reverse_cardinality().try_into()?;
+
+        let edge_name = response_proto
+            .edge_name
+            .ok_or(SerDeError::MissingField("EdgeSchema.edge_name"))?
+            .try_into()?;
+
+        let reverse_edge_name = response_proto
+            .reverse_edge_name
+            .ok_or(SerDeError::MissingField("EdgeSchema.reverse_edge_name"))?
+            .try_into()?;
+
+        let properties = response_proto
+            .properties
+            .into_iter()
+            .map(PropertySchema::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(EdgeSchema {
+            edge_name,
+            reverse_edge_name,
+            cardinality,
+            reverse_cardinality,
+            properties,
+        })
+    }
+}
+
+impl From<EdgeSchema> for EdgeSchemaProto {
+    fn from(value: EdgeSchema) -> Self {
+        let cardinality: EdgeCardinalityProto = value.cardinality.into();
+        let reverse_cardinality: EdgeCardinalityProto = value.reverse_cardinality.into();
+        EdgeSchemaProto {
+            edge_name: Some(value.edge_name.into()),
+            reverse_edge_name: Some(value.reverse_edge_name.into()),
+            cardinality: cardinality as i32,
+            reverse_cardinality: reverse_cardinality as i32,
+            properties: value.properties.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StaticIdentity {
+    pub static_key_properties: Vec<PropertyName>,
+}
+
+impl TryFrom<StaticIdentityProto> for StaticIdentity {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: StaticIdentityProto) -> Result<Self, Self::Error> {
+        let static_key_properties = response_proto
+            .static_key_properties
+            .into_iter()
+            .map(PropertyName::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(StaticIdentity {
+            static_key_properties,
+        })
+    }
+}
+
+impl From<StaticIdentity> for StaticIdentityProto {
+    fn from(value: StaticIdentity) -> Self {
+        StaticIdentityProto {
+            static_key_properties: value
+                .static_key_properties
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionIdentity {
+    pub pseudo_key_properties: Vec<PropertyName>,
+    pub negation_key_properties: Vec<PropertyName>,
+    pub creation_timestamp_property: PropertyName,
+    pub last_seen_timestamp_property: PropertyName,
+    pub termination_timestamp_property: PropertyName,
+}
+
+impl TryFrom<SessionIdentityProto> for SessionIdentity {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: SessionIdentityProto) -> Result<Self, Self::Error> {
+        let pseudo_key_properties = response_proto
+            .pseudo_key_properties
+            .into_iter()
+            .map(PropertyName::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let negation_key_properties = response_proto
+            .negation_key_properties
+            .into_iter()
+            .map(PropertyName::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let creation_timestamp_property = response_proto
+            .creation_timestamp_property
+            .ok_or(SerDeError::MissingField(
+                "SessionIdentity.creation_timestamp_property",
+            ))?
+            .try_into()?;
+
+        let last_seen_timestamp_property = response_proto
+            .last_seen_timestamp_property
+            .ok_or(SerDeError::MissingField(
+                "SessionIdentity.last_seen_timestamp_property",
+            ))?
+            .try_into()?;
+
+        let termination_timestamp_property = response_proto
+            .termination_timestamp_property
+            .ok_or(SerDeError::MissingField(
+                "SessionIdentity.termination_timestamp_property",
+            ))?
+            .try_into()?;
+
+        Ok(SessionIdentity {
+            pseudo_key_properties,
+            negation_key_properties,
+            creation_timestamp_property,
+            last_seen_timestamp_property,
+            termination_timestamp_property,
+        })
+    }
+}
+
+impl From<SessionIdentity> for SessionIdentityProto {
+    fn from(value: SessionIdentity) -> Self {
+        SessionIdentityProto {
+            pseudo_key_properties: value
+                .pseudo_key_properties
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            negation_key_properties: value
+                .negation_key_properties
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            creation_timestamp_property: Some(value.creation_timestamp_property.into()),
+            last_seen_timestamp_property: Some(value.last_seen_timestamp_property.into()),
+            termination_timestamp_property: Some(value.termination_timestamp_property.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IdentityAlgorithm {
+    Static(StaticIdentity),
+    Session(SessionIdentity),
+}
+
+impl TryFrom<IdentityAlgorithmProto> for IdentityAlgorithm {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: IdentityAlgorithmProto) -> Result<Self, Self::Error> {
+        match response_proto.algorithm {
+            Some(IdentityAlgorithmAlgorithmProto::Static(static_identity)) => {
+                Ok(IdentityAlgorithm::Static(static_identity.try_into()?))
+            }
+            Some(IdentityAlgorithmAlgorithmProto::Session(session_identity)) => {
+                Ok(IdentityAlgorithm::Session(session_identity.try_into()?))
+            }
+            None => Err(SerDeError::MissingField("IdentityAlgorithm.algorithm")),
+        }
+    }
+}
+
+impl From<IdentityAlgorithm> for IdentityAlgorithmProto {
+    fn from(value: IdentityAlgorithm) -> Self {
+        let algorithm = match value {
+            IdentityAlgorithm::Static(static_identity) => {
+                IdentityAlgorithmAlgorithmProto::Static(static_identity.into())
+            }
+            IdentityAlgorithm::Session(session_identity) => {
+                IdentityAlgorithmAlgorithmProto::Session(session_identity.into())
+            }
+        };
+        IdentityAlgorithmProto {
+            algorithm: Some(algorithm),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeSchema {
+    pub node_type: NodeType,
+    pub schema_version: u32,
+    pub identity_algorithm: IdentityAlgorithm,
+    /// Ordered by name
+    pub properties: Vec<PropertySchema>,
+    /// Includes the reverse of each edge. Ordered by name.
+    pub edges: Vec<EdgeSchema>,
+}
+
+impl TryFrom<NodeSchemaProto> for NodeSchema {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: NodeSchemaProto) -> Result<Self, Self::Error> {
+        let node_type = response_proto
+            .node_type
+            .ok_or(SerDeError::MissingField("NodeSchema.node_type"))?
+            .try_into()?;
+
+        let identity_algorithm = response_proto
+            .identity_algorithm
+            .ok_or(SerDeError::MissingField("NodeSchema.identity_algorithm"))?
+            .try_into()?;
+
+        let properties = response_proto
+            .properties
+            .into_iter()
+            .map(PropertySchema::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let edges = response_proto
+            .edges
+            .into_iter()
+            .map(EdgeSchema::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(NodeSchema {
+            node_type,
+            schema_version: response_proto.schema_version,
+            identity_algorithm,
+            properties,
+            edges,
+        })
+    }
+}
+
+impl From<NodeSchema> for NodeSchemaProto {
+    fn from(value: NodeSchema) -> Self {
+        NodeSchemaProto {
+            node_type: Some(value.node_type.into()),
+            schema_version: value.schema_version,
+            identity_algorithm: Some(value.identity_algorithm.into()),
+            properties: value.properties.into_iter().map(Into::into).collect(),
+            edges: value.edges.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl type_url::TypeUrl for NodeSchema {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.NodeSchema";
+}
+
+impl serde_impl::ProtobufSerializable for NodeSchema {
+    type ProtobufMessage = NodeSchemaProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GetNodeSchemaRequest {
+    pub tenant_id: uuid::Uuid,
+    pub node_type: NodeType,
+    /// None for the latest version the node type was deployed in
+    pub schema_version: Option<u32>,
+}
+
+impl TryFrom<GetNodeSchemaRequestProto> for GetNodeSchemaRequest {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: GetNodeSchemaRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = response_proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("GetNodeSchemaRequest.tenant_id"))?
+            .into();
+
+        let node_type = response_proto
+            .node_type
+            .ok_or(SerDeError::MissingField("GetNodeSchemaRequest.node_type"))?
+            .try_into()?;
+
+        Ok(GetNodeSchemaRequest {
+            tenant_id,
+            node_type,
+            schema_version: response_proto.schema_version,
+        })
+    }
+}
+
+impl From<GetNodeSchemaRequest> for GetNodeSchemaRequestProto {
+    fn from(value: GetNodeSchemaRequest) -> Self {
+        GetNodeSchemaRequestProto {
+            tenant_id: Some(value.tenant_id.into()),
+            node_type: Some(value.node_type.into()),
+            schema_version: value.schema_version,
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetNodeSchemaRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.GetNodeSchemaRequest";
+}
+
+impl serde_impl::ProtobufSerializable for GetNodeSchemaRequest {
+    type ProtobufMessage = GetNodeSchemaRequestProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GetNodeSchemaResponse {
+    pub node_schema: NodeSchema,
+}
+
+impl TryFrom<GetNodeSchemaResponseProto> for GetNodeSchemaResponse {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: GetNodeSchemaResponseProto) -> Result<Self, Self::Error> {
+        let node_schema = response_proto
+            .node_schema
+            .ok_or(SerDeError::MissingField(
+                "GetNodeSchemaResponse.node_schema",
+            ))?
+            .try_into()?;
+
+        Ok(GetNodeSchemaResponse { node_schema })
+    }
+}
+
+impl From<GetNodeSchemaResponse> for GetNodeSchemaResponseProto {
+    fn from(value: GetNodeSchemaResponse) -> Self {
+        GetNodeSchemaResponseProto {
+            node_schema: Some(value.node_schema.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetNodeSchemaResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.GetNodeSchemaResponse";
+}
+
+impl serde_impl::ProtobufSerializable for GetNodeSchemaResponse {
+    type ProtobufMessage = GetNodeSchemaResponseProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListNodeSchemasRequest {
+    pub tenant_id: uuid::Uuid,
+}
+
+impl TryFrom<ListNodeSchemasRequestProto> for ListNodeSchemasRequest {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: ListNodeSchemasRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = response_proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("ListNodeSchemasRequest.tenant_id"))?
+            .into();
+
+        Ok(ListNodeSchemasRequest { tenant_id })
+    }
+}
+
+impl From<ListNodeSchemasRequest> for ListNodeSchemasRequestProto {
+    fn from(value: ListNodeSchemasRequest) -> Self {
+        ListNodeSchemasRequestProto {
+            tenant_id: Some(value.tenant_id.into()),
+        }
+    }
+}
+
+impl type_url::TypeUrl for ListNodeSchemasRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.ListNodeSchemasRequest";
+}
+
+impl serde_impl::ProtobufSerializable for ListNodeSchemasRequest {
+    type ProtobufMessage = ListNodeSchemasRequestProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeSchemaVersion {
+    pub schema_version: u32,
+    pub deployment_timestamp: SystemTime,
+}
+
+impl TryFrom<NodeSchemaVersionProto> for NodeSchemaVersion {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: NodeSchemaVersionProto) -> Result<Self, Self::Error> {
+        let deployment_timestamp = response_proto
+            .deployment_timestamp
+            .ok_or(SerDeError::MissingField(
+                "NodeSchemaVersion.deployment_timestamp",
+            ))?
+            .try_into()?;
+
+        Ok(NodeSchemaVersion {
+            schema_version: response_proto.schema_version,
+            deployment_timestamp,
+        })
+    }
+}
+
+impl TryFrom<NodeSchemaVersion> for NodeSchemaVersionProto {
+    type Error = SerDeError;
+
+    fn try_from(value: NodeSchemaVersion) -> Result<Self, Self::Error> {
+        Ok(NodeSchemaVersionProto {
+            schema_version: value.schema_version,
+            deployment_timestamp: Some(value.deployment_timestamp.try_into()?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeSchemaVersions {
+    pub node_type: NodeType,
+    /// Oldest first
+    pub versions: Vec<NodeSchemaVersion>,
+}
+
+impl TryFrom<NodeSchemaVersionsProto> for NodeSchemaVersions {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: NodeSchemaVersionsProto) -> Result<Self, Self::Error> {
+        let node_type = response_proto
+            .node_type
+            .ok_or(SerDeError::MissingField("NodeSchemaVersions.node_type"))?
+            .try_into()?;
+
+        let versions = response_proto
+            .versions
+            .into_iter()
+            .map(NodeSchemaVersion::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(NodeSchemaVersions {
+            node_type,
+            versions,
+        })
+    }
+}
+
+impl TryFrom<NodeSchemaVersions> for NodeSchemaVersionsProto {
+    type Error = SerDeError;
+
+    fn try_from(value: NodeSchemaVersions) -> Result<Self, Self::Error> {
+        let versions = value
+            .versions
+            .into_iter()
+            .map(NodeSchemaVersionProto::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(NodeSchemaVersionsProto {
+            node_type: Some(value.node_type.into()),
+            versions,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListNodeSchemasResponse {
+    /// Ordered by node type
+    pub node_schemas: Vec<NodeSchemaVersions>,
+}
+
+impl TryFrom<ListNodeSchemasResponseProto> for ListNodeSchemasResponse {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: ListNodeSchemasResponseProto) -> Result<Self, Self::Error> {
+        let node_schemas = response_proto
+            .node_schemas
+            .into_iter()
+            .map(NodeSchemaVersions::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ListNodeSchemasResponse { node_schemas })
+    }
+}
+
+impl TryFrom<ListNodeSchemasResponse> for ListNodeSchemasResponseProto {
+    type Error = SerDeError;
+
+    fn try_from(value: ListNodeSchemasResponse) -> Result<Self, Self::Error> {
+        let node_schemas = value
+            .node_schemas
+            .into_iter()
+            .map(NodeSchemaVersionsProto::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ListNodeSchemasResponseProto { node_schemas })
+    }
+}
+
+impl type_url::TypeUrl for ListNodeSchemasResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.ListNodeSchemasResponse";
+}
+
+impl serde_impl::ProtobufSerializable for ListNodeSchemasResponse {
+    type ProtobufMessage = ListNodeSchemasResponseProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GetRawSchemaRequest {
+    pub tenant_id: uuid::Uuid,
+    /// None for the latest version
+    pub schema_version: Option<u32>,
+}
+
+impl TryFrom<GetRawSchemaRequestProto> for GetRawSchemaRequest {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: GetRawSchemaRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = response_proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("GetRawSchemaRequest.tenant_id"))?
+            .into();
+
+        Ok(GetRawSchemaRequest {
+            tenant_id,
+            schema_version: response_proto.schema_version,
+        })
+    }
+}
+
+impl From<GetRawSchemaRequest> for GetRawSchemaRequestProto {
+    fn from(value: GetRawSchemaRequest) -> Self {
+        GetRawSchemaRequestProto {
+            tenant_id: Some(value.tenant_id.into()),
+            schema_version: value.schema_version,
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetRawSchemaRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.GetRawSchemaRequest";
+}
+
+impl serde_impl::ProtobufSerializable for GetRawSchemaRequest {
+    type ProtobufMessage = GetRawSchemaRequestProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GetRawSchemaResponse {
+    pub schema_version: u32,
+    pub schema_type: SchemaType,
+    pub schema: Bytes,
+}
+
+impl TryFrom<GetRawSchemaResponseProto> for GetRawSchemaResponse {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: GetRawSchemaResponseProto) -> Result<Self, Self::Error> {
+        let schema_type = response_proto.schema_type().try_into()?;
+
+        Ok(GetRawSchemaResponse {
+            schema_version: response_proto.schema_version,
+            schema_type,
+            schema: response_proto.schema,
+        })
+    }
+}
+
+impl From<GetRawSchemaResponse> for GetRawSchemaResponseProto {
+    fn from(value: GetRawSchemaResponse) -> Self {
+        let schema_type: SchemaTypeProto = value.schema_type.into();
+        GetRawSchemaResponseProto {
+            schema_version: value.schema_version,
+            schema_type: schema_type as i32,
+            schema: value.schema,
+        }
+    }
+}
+
+impl type_url::TypeUrl for GetRawSchemaResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.GetRawSchemaResponse";
+}
+
+impl serde_impl::ProtobufSerializable for GetRawSchemaResponse {
+    type ProtobufMessage = GetRawSchemaResponseProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiffSchemasRequest {
+    pub tenant_id: uuid::Uuid,
+    pub from_schema_version: u32,
+    pub to_schema_version: u32,
+}
+
+impl TryFrom<DiffSchemasRequestProto> for DiffSchemasRequest {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: DiffSchemasRequestProto) -> Result<Self, Self::Error> {
+        let tenant_id = response_proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("DiffSchemasRequest.tenant_id"))?
+            .into();
+
+        Ok(DiffSchemasRequest {
+            tenant_id,
+            from_schema_version: response_proto.from_schema_version,
+            to_schema_version: response_proto.to_schema_version,
+        })
+    }
+}
+
+impl From<DiffSchemasRequest> for DiffSchemasRequestProto {
+    fn from(value: DiffSchemasRequest) -> Self {
+        DiffSchemasRequestProto {
+            tenant_id: Some(value.tenant_id.into()),
+            from_schema_version: value.from_schema_version,
+            to_schema_version: value.to_schema_version,
+        }
+    }
+}
+
+impl type_url::TypeUrl for DiffSchemasRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.DiffSchemasRequest";
+}
+
+impl serde_impl::ProtobufSerializable for DiffSchemasRequest {
+    type ProtobufMessage = DiffSchemasRequestProto;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PropertySchemaChange {
+    pub from: PropertySchema,
+    pub to: PropertySchema,
+}
+
+impl TryFrom<PropertySchemaChangeProto> for PropertySchemaChange {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: PropertySchemaChangeProto) -> Result<Self, Self::Error> {
+        let from = response_proto
+            .from
+            .ok_or(SerDeError::MissingField("PropertySchemaChange.from"))?
+            .try_into()?;
+
+        let to = response_proto
+            .to
+            .ok_or(SerDeError::MissingField("PropertySchemaChange.to"))?
+            .try_into()?;
+
+        Ok(PropertySchemaChange { from, to })
+    }
+}
+
+impl From<PropertySchemaChange> for PropertySchemaChangeProto {
+    fn from(value: PropertySchemaChange) -> Self {
+        PropertySchemaChangeProto {
+            from: Some(value.from.into()),
+            to: Some(value.to.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EdgeSchemaChange {
+    pub from: EdgeSchema,
+    pub to: EdgeSchema,
+}
+
+impl TryFrom<EdgeSchemaChangeProto> for EdgeSchemaChange {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: EdgeSchemaChangeProto) -> Result<Self, Self::Error> {
+        let from = response_proto
+            .from
+            .ok_or(SerDeError::MissingField("EdgeSchemaChange.from"))?
+            .try_into()?;
+
+        let to = response_proto
+            .to
+            .ok_or(SerDeError::MissingField("EdgeSchemaChange.to"))?
+            .try_into()?;
+
+        Ok(EdgeSchemaChange { from, to })
+    }
+}
+
+impl From<EdgeSchemaChange> for EdgeSchemaChangeProto {
+    fn from(value: EdgeSchemaChange) -> Self {
+        EdgeSchemaChangeProto {
+            from: Some(value.from.into()),
+            to: Some(value.to.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdentityAlgorithmChange {
+    pub from: IdentityAlgorithm,
+    pub to: IdentityAlgorithm,
+}
+
+impl TryFrom<IdentityAlgorithmChangeProto> for IdentityAlgorithmChange {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: IdentityAlgorithmChangeProto) -> Result<Self, Self::Error> {
+        let from = response_proto
+            .from
+            .ok_or(SerDeError::MissingField("IdentityAlgorithmChange.from"))?
+            .try_into()?;
+
+        let to = response_proto
+            .to
+            .ok_or(SerDeError::MissingField("IdentityAlgorithmChange.to"))?
+            .try_into()?;
+
+        Ok(IdentityAlgorithmChange { from, to })
+    }
+}
+
+impl From<IdentityAlgorithmChange> for IdentityAlgorithmChangeProto {
+    fn from(value: IdentityAlgorithmChange) -> Self {
+        IdentityAlgorithmChangeProto {
+            from: Some(value.from.into()),
+            to: Some(value.to.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeSchemaChanges {
+    pub node_type: NodeType,
+    /// None if the identity algorithm didn't change
+    pub identity_algorithm_change: Option<IdentityAlgorithmChange>,
+    pub added_properties: Vec<PropertySchema>,
+    pub removed_properties: Vec<PropertySchema>,
+    pub changed_properties: Vec<PropertySchemaChange>,
+    pub added_edges: Vec<EdgeSchema>,
+    pub removed_edges: Vec<EdgeSchema>,
+    pub changed_edges: Vec<EdgeSchemaChange>,
+}
+
+impl NodeSchemaChanges {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.identity_algorithm_change.is_none()
+            && self.added_properties.is_empty()
+            && self.removed_properties.is_empty()
+            && self.changed_properties.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_edges.is_empty()
+    }
+}
+
+impl TryFrom<NodeSchemaChangesProto> for NodeSchemaChanges {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: NodeSchemaChangesProto) -> Result<Self, Self::Error> {
+        let node_type = response_proto
+            .node_type
+            .ok_or(SerDeError::MissingField("NodeSchemaChanges.node_type"))?
+            .try_into()?;
+
+        let identity_algorithm_change = response_proto
+            .identity_algorithm_change
+            .map(IdentityAlgorithmChange::try_from)
+            .transpose()?;
+
+        let added_properties = response_proto
+            .added_properties
+            .into_iter()
+            .map(PropertySchema::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let removed_properties = response_proto
+            .removed_properties
+            .into_iter()
+            .map(PropertySchema::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let changed_properties = response_proto
+            .changed_properties
+            .into_iter()
+            .map(PropertySchemaChange::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let added_edges = response_proto
+            .added_edges
+            .into_iter()
+            .map(EdgeSchema::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let removed_edges = response_proto
+            .removed_edges
+            .into_iter()
+            .map(EdgeSchema::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let changed_edges = response_proto
+            .changed_edges
+            .into_iter()
+            .map(EdgeSchemaChange::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(NodeSchemaChanges {
+            node_type,
+            identity_algorithm_change,
+            added_properties,
+            removed_properties,
+            changed_properties,
+            added_edges,
+            removed_edges,
+            changed_edges,
+        })
+    }
+}
+
+impl From<NodeSchemaChanges> for NodeSchemaChangesProto {
+    fn from(value: NodeSchemaChanges) -> Self {
+        NodeSchemaChangesProto {
+            node_type: Some(value.node_type.into()),
+            identity_algorithm_change: value.identity_algorithm_change.map(Into::into),
+            added_properties: value.added_properties.into_iter().map(Into::into).collect(),
+            removed_properties: value
+                .removed_properties
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            changed_properties: value
+                .changed_properties
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            added_edges: value.added_edges.into_iter().map(Into::into).collect(),
+            removed_edges: value.removed_edges.into_iter().map(Into::into).collect(),
+            changed_edges: value.changed_edges.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NodeSchemaDiff {
+    /// The node type is only in the later version
+    Added(NodeSchema),
+    /// The node type is only in the earlier version
+    Removed(NodeSchema),
+    /// The node type is in both versions, with changes
+    Changed(NodeSchemaChanges),
+}
+
+impl NodeSchemaDiff {
+    pub fn node_type(&self) -> &NodeType {
+        match self {
+            NodeSchemaDiff::Added(node_schema) => &node_schema.node_type,
+            NodeSchemaDiff::Removed(node_schema) => &node_schema.node_type,
+            NodeSchemaDiff::Changed(node_schema_changes) => &node_schema_changes.node_type,
+        }
+    }
+}
+
+impl TryFrom<NodeSchemaDiffProto> for NodeSchemaDiff {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: NodeSchemaDiffProto) -> Result<Self, Self::Error> {
+        match response_proto.diff {
+            Some(NodeSchemaDiffDiffProto::Added(node_schema)) => {
+                Ok(NodeSchemaDiff::Added(node_schema.try_into()?))
+            }
+            Some(NodeSchemaDiffDiffProto::Removed(node_schema)) => {
+                Ok(NodeSchemaDiff::Removed(node_schema.try_into()?))
+            }
+            Some(NodeSchemaDiffDiffProto::Changed(node_schema_changes)) => {
+                Ok(NodeSchemaDiff::Changed(node_schema_changes.try_into()?))
+            }
+            None => Err(SerDeError::MissingField("NodeSchemaDiff.diff")),
+        }
+    }
+}
+
+impl From<NodeSchemaDiff> for NodeSchemaDiffProto {
+    fn from(value: NodeSchemaDiff) -> Self {
+        let diff = match value {
+            NodeSchemaDiff::Added(node_schema) => {
+                NodeSchemaDiffDiffProto::Added(node_schema.into())
+            }
+            NodeSchemaDiff::Removed(node_schema) => {
+                NodeSchemaDiffDiffProto::Removed(node_schema.into())
+            }
+            NodeSchemaDiff::Changed(node_schema_changes) => {
+                NodeSchemaDiffDiffProto::Changed(node_schema_changes.into())
+            }
+        };
+        NodeSchemaDiffProto { diff: Some(diff) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiffSchemasResponse {
+    /// Ordered by node type. Node types that didn't change are left out.
+    pub node_schema_diffs: Vec<NodeSchemaDiff>,
+}
+
+impl TryFrom<DiffSchemasResponseProto> for DiffSchemasResponse {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: DiffSchemasResponseProto) -> Result<Self, Self::Error> {
+        let node_schema_diffs = response_proto
+            .node_schema_diffs
+            .into_iter()
+            .map(NodeSchemaDiff::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DiffSchemasResponse { node_schema_diffs })
+    }
+}
+
+impl From<DiffSchemasResponse> for DiffSchemasResponseProto {
+    fn from(value: DiffSchemasResponse) -> Self {
+        DiffSchemasResponseProto {
+            node_schema_diffs: value
+                .node_schema_diffs
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl type_url::TypeUrl for DiffSchemasResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.graph_schema_manager.v1beta1.DiffSchemasResponse";
+}
+
+impl serde_impl::ProtobufSerializable for DiffSchemasResponse {
+    type ProtobufMessage = DiffSchemasResponseProto;
+}
@@ -23,8 +23,20 @@ use crate::{
         graph_schema_manager::v1beta1::messages::{
             DeploySchemaRequest,
             DeploySchemaResponse,
+            DiffSchemasRequest,
+            DiffSchemasResponse,
             GetEdgeSchemaRequest,
             GetEdgeSchemaResponse,
+            GetNodeSchemaRequest,
+            GetNodeSchemaResponse,
+            GetRawSchemaRequest,
+            GetRawSchemaResponse,
+            GetRetentionPolicyRequest,
+            GetRetentionPolicyResponse,
+            ListNodeSchemasRequest,
+            ListNodeSchemasResponse,
+            SetRetentionPolicyRequest,
+            SetRetentionPolicyResponse,
         },
         protocol::{
             error::ServeError,
@@ -66,6 +78,36 @@ pub trait GraphSchemaManagerApi {
         &self,
         request: GetEdgeSchemaRequest,
     ) -> Result<GetEdgeSchemaResponse, Self::Error>;
+
+    async fn set_retention_policy(
+        &self,
+        request: SetRetentionPolicyRequest,
+    ) -> Result<SetRetentionPolicyResponse, Self::Error>;
+
+    async fn get_retention_policy(
+        &self,
+        request: GetRetentionPolicyRequest,
+    ) -> Result<GetRetentionPolicyResponse, Self::Error>;
+
+    async fn get_node_schema(
+        &self,
+        request: GetNodeSchemaRequest,
+    ) -> Result<GetNodeSchemaResponse, Self::Error>;
+
+    async fn list_node_schemas(
+        &self,
+        request: ListNodeSchemasRequest,
+    ) -> Result<ListNodeSchemasResponse, Self::Error>;
+
+    async fn get_raw_schema(
+        &self,
+        request: GetRawSchemaRequest,
+    ) -> Result<GetRawSchemaResponse, Self::Error>;
+
+    async fn diff_schemas(
+        &self,
+        request: DiffSchemasRequest,
+    ) -> Result<DiffSchemasResponse, Self::Error>;
 }
 
 #[tonic::async_trait]
@@ -86,6 +128,48 @@ where
     ) -> Result<tonic::Response<proto::GetEdgeSchemaResponse>, tonic::Status> {
         execute_rpc!(self, request, get_edge_schema)
     }
+
+    async fn set_retention_policy(
+        &self,
+        request: tonic::Request<proto::SetRetentionPolicyRequest>,
+    ) -> Result<tonic::Response<proto::SetRetentionPolicyResponse>, tonic::Status> {
+        execute_rpc!(self, request, set_retention_policy)
+    }
+
+    async fn get_retention_policy(
+        &self,
+        request: tonic::Request<proto::GetRetentionPolicyRequest>,
+    ) -> Result<tonic::Response<proto::GetRetentionPolicyResponse>, tonic::Status> {
+        execute_rpc!(self, request, get_retention_policy)
+    }
+
+    async fn get_node_schema(
+        &self,
+        request: tonic::Request<proto::GetNodeSchemaRequest>,
+    ) -> Result<tonic::Response<proto::GetNodeSchemaResponse>, tonic::Status> {
+        execute_rpc!(self, request, get_node_schema)
+    }
+
+    async fn list_node_schemas(
+        &self,
+        request: tonic::Request<proto::ListNodeSchemasRequest>,
+    ) -> Result<tonic::Response<proto::ListNodeSchemasResponse>, tonic::Status> {
+        execute_rpc!(self, request, list_node_schemas)
+    }
+
+    async fn get_raw_schema(
+        &self,
+        request: tonic::Request<proto::GetRawSchemaRequest>,
+    ) -> Result<tonic::Response<proto::GetRawSchemaResponse>, tonic::Status> {
+        execute_rpc!(self, request, get_raw_schema)
+    }
+
+    async fn diff_schemas(
+        &self,
+        request: tonic::Request<proto::DiffSchemasRequest>,
+    ) -> Result<tonic::Response<proto::DiffSchemasResponse>, tonic::Status> {
+        execute_rpc!(self, request, diff_schemas)
+    }
 }
 
 /**
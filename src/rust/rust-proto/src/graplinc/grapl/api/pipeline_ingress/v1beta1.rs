@@ -11,6 +11,8 @@ use crate::{
     protobufs::graplinc::grapl::api::pipeline_ingress::v1beta1::{
         PublishRawLogRequest as PublishRawLogRequestProto,
         PublishRawLogResponse as PublishRawLogResponseProto,
+        PublishRawLogsRequest as PublishRawLogsRequestProto,
+        PublishRawLogsResponse as PublishRawLogsResponseProto,
     },
     serde_impl,
     type_url,
@@ -48,6 +50,10 @@ impl PublishRawLogRequest {
     pub fn log_event(self) -> Bytes {
         self.log_event
     }
+
+    pub fn log_event_len(&self) -> usize {
+        self.log_event.len()
+    }
 }
 
 impl TryFrom<PublishRawLogRequestProto> for PublishRawLogRequest {
@@ -147,3 +153,120 @@ impl type_url::TypeUrl for PublishRawLogResponse {
 impl serde_impl::ProtobufSerializable for PublishRawLogResponse {
     type ProtobufMessage = PublishRawLogResponseProto;
 }
+
+//
+// PublishRawLogsRequest
+//
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishRawLogsRequest {
+    raw_logs: Vec<PublishRawLogRequest>,
+}
+
+impl PublishRawLogsRequest {
+    pub fn new(raw_logs: Vec<PublishRawLogRequest>) -> Self {
+        Self { raw_logs }
+    }
+
+    pub fn raw_logs(self) -> Vec<PublishRawLogRequest> {
+        self.raw_logs
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw_logs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw_logs.is_empty()
+    }
+}
+
+impl TryFrom<PublishRawLogsRequestProto> for PublishRawLogsRequest {
+    type Error = SerDeError;
+
+    fn try_from(request_proto: PublishRawLogsRequestProto) -> Result<Self, Self::Error> {
+        let raw_logs = request_proto
+            .raw_logs
+            .into_iter()
+            .map(PublishRawLogRequest::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PublishRawLogsRequest { raw_logs })
+    }
+}
+
+impl From<PublishRawLogsRequest> for PublishRawLogsRequestProto {
+    fn from(request: PublishRawLogsRequest) -> Self {
+        PublishRawLogsRequestProto {
+            raw_logs: request.raw_logs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl type_url::TypeUrl for PublishRawLogsRequest {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.pipeline_ingress.v1beta1.PublishRawLogsRequest";
+}
+
+impl serde_impl::ProtobufSerializable for PublishRawLogsRequest {
+    type ProtobufMessage = PublishRawLogsRequestProto;
+}
+
+//
+// PublishRawLogsResponse
+//
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishRawLogsResponse {
+    created_time: SystemTime,
+}
+
+impl PublishRawLogsResponse {
+    pub fn new(created_time: SystemTime) -> Self {
+        Self { created_time }
+    }
+
+    /// build a response with created_time set to SystemTime::now()
+    pub fn ok() -> Self {
+        PublishRawLogsResponse {
+            created_time: SystemTime::now(),
+        }
+    }
+
+    pub fn created_time(&self) -> SystemTime {
+        self.created_time
+    }
+}
+
+impl TryFrom<PublishRawLogsResponseProto> for PublishRawLogsResponse {
+    type Error = SerDeError;
+
+    fn try_from(response_proto: PublishRawLogsResponseProto) -> Result<Self, Self::Error> {
+        let created_time = response_proto
+            .created_time
+            .ok_or(SerDeError::MissingField("created_time"))?;
+
+        Ok(PublishRawLogsResponse {
+            created_time: created_time.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<PublishRawLogsResponse> for PublishRawLogsResponseProto {
+    type Error = SerDeError;
+
+    fn try_from(response: PublishRawLogsResponse) -> Result<Self, Self::Error> {
+        Ok(PublishRawLogsResponseProto {
+            created_time: Some(response.created_time.try_into()?),
+        })
+    }
+}
+
+impl type_url::TypeUrl for PublishRawLogsResponse {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.api.pipeline_ingress.v1beta1.PublishRawLogsResponse";
+}
+
+impl serde_impl::ProtobufSerializable for PublishRawLogsResponse {
+    type ProtobufMessage = PublishRawLogsResponseProto;
+}
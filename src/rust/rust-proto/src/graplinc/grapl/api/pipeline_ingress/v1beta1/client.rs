@@ -47,4 +47,18 @@ impl PipelineIngressClient {
             )
             .await
     }
+
+    pub async fn publish_raw_logs(
+        &mut self,
+        request: native::PublishRawLogsRequest,
+    ) -> Result<native::PublishRawLogsResponse, ClientError> {
+        self.client
+            .execute(
+                request,
+                |status| status.code() == tonic::Code::Unavailable,
+                10,
+                |mut client, request| async move { client.publish_raw_logs(request).await },
+            )
+            .await
+    }
 }
@@ -22,6 +22,8 @@ use crate::{
         pipeline_ingress::v1beta1::{
             PublishRawLogRequest,
             PublishRawLogResponse,
+            PublishRawLogsRequest,
+            PublishRawLogsResponse,
         },
         protocol::{
             error::ServeError,
@@ -41,6 +43,8 @@ use crate::{
         },
         PublishRawLogRequest as PublishRawLogRequestProto,
         PublishRawLogResponse as PublishRawLogResponseProto,
+        PublishRawLogsRequest as PublishRawLogsRequestProto,
+        PublishRawLogsResponse as PublishRawLogsResponseProto,
     },
 };
 
@@ -59,6 +63,13 @@ where
     ) -> Result<tonic::Response<PublishRawLogResponseProto>, tonic::Status> {
         execute_rpc!(self, request, publish_raw_log)
     }
+
+    async fn publish_raw_logs(
+        &self,
+        request: tonic::Request<PublishRawLogsRequestProto>,
+    ) -> Result<tonic::Response<PublishRawLogsResponseProto>, tonic::Status> {
+        execute_rpc!(self, request, publish_raw_logs)
+    }
 }
 
 //
@@ -73,6 +84,13 @@ pub trait PipelineIngressApi {
         &self,
         request: PublishRawLogRequest,
     ) -> Result<PublishRawLogResponse, Self::Error>;
+
+    /// Publish every log in the batch. Implementations should validate the
+    /// whole batch before publishing any of it.
+    async fn publish_raw_logs(
+        &self,
+        request: PublishRawLogsRequest,
+    ) -> Result<PublishRawLogsResponse, Self::Error>;
 }
 
 /// The pipeline-ingress server serves the pipeline-ingress API
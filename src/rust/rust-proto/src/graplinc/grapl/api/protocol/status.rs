@@ -19,6 +19,9 @@ pub enum Code {
     /// Client specified an invalid argument.
     InvalidArgument,
 
+    /// Deadline expired before operation could complete.
+    DeadlineExceeded,
+
     /// Some requested entity was not found.
     NotFound,
 
@@ -28,9 +31,15 @@ pub enum Code {
     /// The caller does not have permission to execute the specified operation.
     PermissionDenied,
 
+    /// Some resource has been exhausted.
+    ResourceExhausted,
+
     /// The system is not in a state required for the operation's execution.
     FailedPrecondition,
 
+    /// The operation was aborted.
+    Aborted,
+
     /// Operation is not implemented or not supported.
     Unimplemented,
 
@@ -62,14 +71,17 @@ impl Code {
             Code::Ok => "The operation completed successfully",
             Code::Unknown => "Unknown error",
             Code::InvalidArgument => "Client specified an invalid argument",
+            Code::DeadlineExceeded => "Deadline expired before operation could complete",
             Code::NotFound => "Some requested entity was not found",
             Code::AlreadyExists => "Some entity that we attempted to create already exists",
             Code::PermissionDenied => {
                 "The caller does not have permission to execute the specified operation"
             }
+            Code::ResourceExhausted => "Some resource has been exhausted",
             Code::FailedPrecondition => {
                 "The system is not in a state required for the operation's execution"
             }
+            Code::Aborted => "The operation was aborted",
             Code::Unimplemented => "Operation is not implemented or not supported",
             Code::Internal => "Internal error",
             Code::Unavailable => "The service is currently unavailable",
@@ -90,10 +102,13 @@ impl From<Code> for GrpcCode {
             Code::Ok => GrpcCode::Ok,
             Code::Unknown => GrpcCode::Unknown,
             Code::InvalidArgument => GrpcCode::InvalidArgument,
+            Code::DeadlineExceeded => GrpcCode::DeadlineExceeded,
             Code::NotFound => GrpcCode::NotFound,
             Code::AlreadyExists => GrpcCode::AlreadyExists,
             Code::PermissionDenied => GrpcCode::PermissionDenied,
+            Code::ResourceExhausted => GrpcCode::ResourceExhausted,
             Code::FailedPrecondition => GrpcCode::FailedPrecondition,
+            Code::Aborted => GrpcCode::Aborted,
             Code::Unimplemented => GrpcCode::Unimplemented,
             Code::Internal => GrpcCode::Internal,
             Code::Unavailable => GrpcCode::Unavailable,
@@ -109,13 +124,13 @@ impl From<GrpcCode> for Code {
             GrpcCode::Cancelled => Code::Internal,
             GrpcCode::Unknown => Code::Unknown,
             GrpcCode::InvalidArgument => Code::InvalidArgument,
-            GrpcCode::DeadlineExceeded => Code::Internal,
+            GrpcCode::DeadlineExceeded => Code::DeadlineExceeded,
             GrpcCode::NotFound => Code::NotFound,
             GrpcCode::AlreadyExists => Code::AlreadyExists,
             GrpcCode::PermissionDenied => Code::PermissionDenied,
-            GrpcCode::ResourceExhausted => Code::Internal,
+            GrpcCode::ResourceExhausted => Code::ResourceExhausted,
             GrpcCode::FailedPrecondition => Code::FailedPrecondition,
-            GrpcCode::Aborted => Code::Internal,
+            GrpcCode::Aborted => Code::Aborted,
             GrpcCode::OutOfRange => Code::Internal,
             GrpcCode::Unimplemented => Code::Unimplemented,
             GrpcCode::Internal => Code::Internal,
@@ -184,6 +199,12 @@ impl Status {
         Status::new(Code::PermissionDenied, message)
     }
 
+    /// Some resource has been exhausted, perhaps a per-user quota, or perhaps
+    /// the entire file system is out of space.
+    pub fn resource_exhausted(message: impl Into<String>) -> Status {
+        Status::new(Code::ResourceExhausted, message)
+    }
+
     /// Operation was rejected because the system is not in a state required for
     /// the operation's execution. For example, directory to be deleted may be
     /// non-empty, an rmdir operation is applied to a non-directory, etc.
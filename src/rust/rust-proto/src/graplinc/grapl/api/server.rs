@@ -61,3 +61,43 @@ macro_rules! execute_rpc {
         }
     }};
 }
+
+/// The server streaming counterpart of `execute_rpc!`. The api server returns
+/// a stream of Rust-native responses, each of which is turned into a
+/// Protobuf response as it's sent to the client. An error in the stream is
+/// sent as the final status of the RPC.
+#[macro_export]
+macro_rules! execute_server_streaming_rpc {
+    ($self: ident, $request: ident, $rpc_name: ident) => {{
+        {
+            use futures::StreamExt;
+
+            let rpc_name = stringify!($rpc_name);
+            tracing::debug!("Executing server streaming RPC {rpc_name}");
+
+            let proto_request = $request.into_inner();
+
+            let native_request = proto_request.try_into()?;
+
+            let native_stream = $self
+                .api_server
+                .$rpc_name(native_request)
+                .await
+                .map_err(Into::into)?;
+
+            let proto_stream = native_stream
+                .map(|native_response| -> Result<_, tonic::Status> {
+                    let native_response = native_response.map_err(
+                        Into::<$crate::graplinc::grapl::api::protocol::status::Status>::into,
+                    )?;
+                    let proto_response = native_response
+                        .try_into()
+                        .map_err($crate::SerDeError::from)?;
+                    Ok(proto_response)
+                })
+                .boxed();
+
+            Ok(tonic::Response::new(proto_stream))
+        }
+    }};
+}
@@ -1,3 +1,12 @@
+use std::{
+    fmt::Display,
+    io::{
+        Read,
+        Write,
+    },
+    str::FromStr,
+};
+
 use bytes::Bytes;
 
 use crate::{
@@ -8,7 +17,10 @@ use crate::{
     protobufs::{
         google::protobuf::Any as AnyProto,
         graplinc::grapl::pipeline::v1beta1::{
+            DeadLetter as DeadLetterProto,
             Envelope as EnvelopeProto,
+            EnvelopeMetadata as EnvelopeMetadataProto,
+            PayloadEncoding as PayloadEncodingProto,
             RawLog as RawLogProto,
         },
     },
@@ -18,6 +30,134 @@ use crate::{
     SerDeError,
 };
 
+//
+// PayloadEncoding
+//
+
+/// An upper bound on the size of a decoded inner_message, so that a
+/// maliciously (or accidentally) crafted payload can't exhaust a consumer's
+/// memory. This matches the largest payload plugin-work-queue will accept.
+pub const MAX_DECODED_PAYLOAD_SIZE: usize = 256 * 1024 * 1024;
+
+/// Compression applied to the serialized inner_message of an `Envelope`.
+/// Encoding and decoding happen transparently when an `Envelope` is
+/// serialized and deserialized, so consumers never observe compressed bytes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PayloadEncoding {
+    Identity,
+    Zstd,
+    Gzip,
+}
+
+impl Default for PayloadEncoding {
+    fn default() -> Self {
+        PayloadEncoding::Identity
+    }
+}
+
+impl PayloadEncoding {
+    /// zstd's default level trades a little ratio for a lot of speed
+    const ZSTD_LEVEL: i32 = 3;
+
+    pub fn encode(&self, payload: Bytes) -> Result<Bytes, SerDeError> {
+        match self {
+            PayloadEncoding::Identity => Ok(payload),
+            PayloadEncoding::Zstd => zstd::bulk::compress(&payload, Self::ZSTD_LEVEL)
+                .map(Bytes::from)
+                .map_err(|e| SerDeError::EncodingFailedWith(e.to_string())),
+            PayloadEncoding::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder
+                    .write_all(&payload)
+                    .and_then(|_| encoder.finish())
+                    .map(Bytes::from)
+                    .map_err(|e| SerDeError::EncodingFailedWith(e.to_string()))
+            }
+        }
+    }
+
+    pub fn decode(&self, payload: Bytes) -> Result<Bytes, SerDeError> {
+        match self {
+            PayloadEncoding::Identity => Ok(payload),
+            PayloadEncoding::Zstd => {
+                let decoder = zstd::stream::read::Decoder::new(payload.as_ref())
+                    .map_err(|e| SerDeError::DecodingFailedWith(e.to_string()))?;
+                read_bounded(decoder)
+            }
+            PayloadEncoding::Gzip => read_bounded(flate2::read::GzDecoder::new(payload.as_ref())),
+        }
+    }
+}
+
+fn read_bounded(reader: impl Read) -> Result<Bytes, SerDeError> {
+    let mut decoded = Vec::new();
+    // read one byte past the limit so we can tell a payload which is exactly
+    // at the limit from one which exceeds it
+    reader
+        .take(MAX_DECODED_PAYLOAD_SIZE as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| SerDeError::DecodingFailedWith(e.to_string()))?;
+
+    if decoded.len() > MAX_DECODED_PAYLOAD_SIZE {
+        return Err(SerDeError::InvalidField {
+            field_name: "inner_message",
+            assertion: format!(
+                "decoded size must not exceed {} bytes",
+                MAX_DECODED_PAYLOAD_SIZE
+            ),
+        });
+    }
+
+    Ok(Bytes::from(decoded))
+}
+
+impl From<PayloadEncodingProto> for PayloadEncoding {
+    fn from(payload_encoding_proto: PayloadEncodingProto) -> Self {
+        match payload_encoding_proto {
+            // Envelopes produced before this field was introduced are not
+            // compressed
+            PayloadEncodingProto::Unspecified => PayloadEncoding::Identity,
+            PayloadEncodingProto::Identity => PayloadEncoding::Identity,
+            PayloadEncodingProto::Zstd => PayloadEncoding::Zstd,
+            PayloadEncodingProto::Gzip => PayloadEncoding::Gzip,
+        }
+    }
+}
+
+impl From<PayloadEncoding> for PayloadEncodingProto {
+    fn from(payload_encoding: PayloadEncoding) -> Self {
+        match payload_encoding {
+            PayloadEncoding::Identity => PayloadEncodingProto::Identity,
+            PayloadEncoding::Zstd => PayloadEncodingProto::Zstd,
+            PayloadEncoding::Gzip => PayloadEncodingProto::Gzip,
+        }
+    }
+}
+
+impl FromStr for PayloadEncoding {
+    type Err = SerDeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "identity" | "none" => Ok(PayloadEncoding::Identity),
+            "zstd" => Ok(PayloadEncoding::Zstd),
+            "gzip" => Ok(PayloadEncoding::Gzip),
+            _ => Err(SerDeError::UnknownVariant("PayloadEncoding")),
+        }
+    }
+}
+
+impl Display for PayloadEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PayloadEncoding::Identity => write!(f, "identity"),
+            PayloadEncoding::Zstd => write!(f, "zstd"),
+            PayloadEncoding::Gzip => write!(f, "gzip"),
+        }
+    }
+}
+
 //
 // Envelope
 //
@@ -34,6 +174,7 @@ where
     last_updated_time: SystemTime,
     event_source_id: Uuid,
     inner_message: T,
+    inner_message_encoding: PayloadEncoding,
 }
 
 impl<T> Envelope<T>
@@ -50,9 +191,17 @@ where
             last_updated_time: now,
             event_source_id,
             inner_message,
+            inner_message_encoding: PayloadEncoding::Identity,
         }
     }
 
+    /// Compress the inner_message with the given encoding when this envelope
+    /// is serialized.
+    pub fn with_inner_message_encoding(mut self, inner_message_encoding: PayloadEncoding) -> Self {
+        self.inner_message_encoding = inner_message_encoding;
+        self
+    }
+
     pub fn increment_retry_count(&mut self) {
         self.retry_count += 1;
         self.last_updated_time = SystemTime::now();
@@ -82,6 +231,10 @@ where
         self.event_source_id
     }
 
+    pub fn inner_message_encoding(&self) -> PayloadEncoding {
+        self.inner_message_encoding
+    }
+
     pub fn inner_message(self) -> T {
         self.inner_message
     }
@@ -114,6 +267,9 @@ where
             .event_source_id
             .ok_or(SerDeError::MissingField("event_source_id"))?;
 
+        let inner_message_encoding: PayloadEncoding =
+            envelope_proto.inner_message_encoding().into();
+
         if let Some(any_proto) = envelope_proto.inner_message {
             Ok(Envelope {
                 tenant_id: tenant_id.into(),
@@ -122,7 +278,8 @@ where
                 created_time: created_time.try_into()?,
                 last_updated_time: last_updated_time.try_into()?,
                 event_source_id: event_source_id.into(),
-                inner_message: SerDe::deserialize(any_proto.value)?,
+                inner_message: SerDe::deserialize(inner_message_encoding.decode(any_proto.value)?)?,
+                inner_message_encoding,
             })
         } else {
             Err(SerDeError::MissingField("inner_message"))
@@ -137,6 +294,7 @@ where
     type Error = SerDeError;
 
     fn try_from(envelope: Envelope<T>) -> Result<Self, Self::Error> {
+        let inner_message_encoding = envelope.inner_message_encoding;
         Ok(EnvelopeProto {
             tenant_id: Some(envelope.tenant_id.into()),
             trace_id: Some(envelope.trace_id.into()),
@@ -146,8 +304,9 @@ where
             event_source_id: Some(envelope.event_source_id.into()),
             inner_message: Some(AnyProto {
                 type_url: T::TYPE_URL.to_string(),
-                value: envelope.inner_message.serialize()?,
+                value: inner_message_encoding.encode(envelope.inner_message.serialize()?)?,
             }),
+            inner_message_encoding: PayloadEncodingProto::from(inner_message_encoding) as i32,
         })
     }
 }
@@ -166,6 +325,210 @@ where
     type ProtobufMessage = EnvelopeProto;
 }
 
+//
+// EnvelopeMetadata
+//
+
+/// The metadata of an `Envelope`, without its inner_message. A serialized
+/// `Envelope<T>` of any `T` may be deserialized as an `EnvelopeMetadata`, which
+/// is useful for services (like kafka-retry) that handle messages of a type
+/// they don't know about.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EnvelopeMetadata {
+    tenant_id: Uuid,
+    trace_id: Uuid,
+    retry_count: u32,
+    created_time: SystemTime,
+    last_updated_time: SystemTime,
+    event_source_id: Uuid,
+}
+
+impl EnvelopeMetadata {
+    pub fn tenant_id(&self) -> Uuid {
+        self.tenant_id
+    }
+
+    pub fn trace_id(&self) -> Uuid {
+        self.trace_id
+    }
+
+    pub fn retry_count(&self) -> u32 {
+        self.retry_count
+    }
+
+    pub fn created_time(&self) -> SystemTime {
+        self.created_time
+    }
+
+    pub fn last_updated_time(&self) -> SystemTime {
+        self.last_updated_time
+    }
+
+    pub fn event_source_id(&self) -> Uuid {
+        self.event_source_id
+    }
+}
+
+impl<T> From<&Envelope<T>> for EnvelopeMetadata
+where
+    T: SerDe,
+{
+    fn from(envelope: &Envelope<T>) -> Self {
+        EnvelopeMetadata {
+            tenant_id: envelope.tenant_id,
+            trace_id: envelope.trace_id,
+            retry_count: envelope.retry_count,
+            created_time: envelope.created_time,
+            last_updated_time: envelope.last_updated_time,
+            event_source_id: envelope.event_source_id,
+        }
+    }
+}
+
+impl TryFrom<EnvelopeMetadataProto> for EnvelopeMetadata {
+    type Error = SerDeError;
+
+    fn try_from(envelope_metadata_proto: EnvelopeMetadataProto) -> Result<Self, Self::Error> {
+        let tenant_id = envelope_metadata_proto
+            .tenant_id
+            .ok_or(SerDeError::MissingField("tenant_id"))?;
+
+        let trace_id = envelope_metadata_proto
+            .trace_id
+            .ok_or(SerDeError::MissingField("trace_id"))?;
+
+        let created_time = envelope_metadata_proto
+            .created_time
+            .ok_or(SerDeError::MissingField("created_time"))?;
+
+        let last_updated_time = envelope_metadata_proto
+            .last_updated_time
+            .ok_or(SerDeError::MissingField("last_updated_time"))?;
+
+        let event_source_id = envelope_metadata_proto
+            .event_source_id
+            .ok_or(SerDeError::MissingField("event_source_id"))?;
+
+        Ok(EnvelopeMetadata {
+            tenant_id: tenant_id.into(),
+            trace_id: trace_id.into(),
+            retry_count: envelope_metadata_proto.retry_count,
+            created_time: created_time.try_into()?,
+            last_updated_time: last_updated_time.try_into()?,
+            event_source_id: event_source_id.into(),
+        })
+    }
+}
+
+impl TryFrom<EnvelopeMetadata> for EnvelopeMetadataProto {
+    type Error = SerDeError;
+
+    fn try_from(envelope_metadata: EnvelopeMetadata) -> Result<Self, Self::Error> {
+        Ok(EnvelopeMetadataProto {
+            tenant_id: Some(envelope_metadata.tenant_id.into()),
+            trace_id: Some(envelope_metadata.trace_id.into()),
+            retry_count: envelope_metadata.retry_count,
+            created_time: Some(envelope_metadata.created_time.try_into()?),
+            last_updated_time: Some(envelope_metadata.last_updated_time.try_into()?),
+            event_source_id: Some(envelope_metadata.event_source_id.into()),
+        })
+    }
+}
+
+impl type_url::TypeUrl for EnvelopeMetadata {
+    const TYPE_URL: &'static str =
+        "graplsecurity.com/graplinc.grapl.pipeline.v1beta1.EnvelopeMetadata";
+}
+
+impl serde_impl::ProtobufSerializable for EnvelopeMetadata {
+    type ProtobufMessage = EnvelopeMetadataProto;
+}
+
+//
+// DeadLetter
+//
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DeadLetter {
+    source_topic: String,
+    payload: Bytes,
+    reason: String,
+    retry_count: u32,
+    dead_lettered_time: SystemTime,
+}
+
+impl DeadLetter {
+    pub fn new(source_topic: String, payload: Bytes, reason: String, retry_count: u32) -> Self {
+        DeadLetter {
+            source_topic,
+            payload,
+            reason,
+            retry_count,
+            dead_lettered_time: SystemTime::now(),
+        }
+    }
+
+    pub fn source_topic(&self) -> &str {
+        &self.source_topic
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn retry_count(&self) -> u32 {
+        self.retry_count
+    }
+
+    pub fn dead_lettered_time(&self) -> SystemTime {
+        self.dead_lettered_time
+    }
+
+    pub fn payload(self) -> Bytes {
+        self.payload
+    }
+}
+
+impl TryFrom<DeadLetterProto> for DeadLetter {
+    type Error = SerDeError;
+
+    fn try_from(dead_letter_proto: DeadLetterProto) -> Result<Self, Self::Error> {
+        let dead_lettered_time = dead_letter_proto
+            .dead_lettered_time
+            .ok_or(SerDeError::MissingField("dead_lettered_time"))?;
+
+        Ok(DeadLetter {
+            source_topic: dead_letter_proto.source_topic,
+            payload: dead_letter_proto.payload,
+            reason: dead_letter_proto.reason,
+            retry_count: dead_letter_proto.retry_count,
+            dead_lettered_time: dead_lettered_time.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<DeadLetter> for DeadLetterProto {
+    type Error = SerDeError;
+
+    fn try_from(dead_letter: DeadLetter) -> Result<Self, Self::Error> {
+        Ok(DeadLetterProto {
+            source_topic: dead_letter.source_topic,
+            payload: dead_letter.payload,
+            reason: dead_letter.reason,
+            retry_count: dead_letter.retry_count,
+            dead_lettered_time: Some(dead_letter.dead_lettered_time.try_into()?),
+        })
+    }
+}
+
+impl type_url::TypeUrl for DeadLetter {
+    const TYPE_URL: &'static str = "graplsecurity.com/graplinc.grapl.pipeline.v1beta1.DeadLetter";
+}
+
+impl serde_impl::ProtobufSerializable for DeadLetter {
+    type ProtobufMessage = DeadLetterProto;
+}
+
 //
 // RawLog
 //
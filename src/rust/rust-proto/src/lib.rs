@@ -323,6 +323,12 @@ pub enum SerDeError {
     #[error("failed to deserialize {0}")]
     DecodingFailed(#[from] DecodeError),
 
+    #[error("failed to encode payload {0}")]
+    EncodingFailedWith(String),
+
+    #[error("failed to decode payload {0}")]
+    DecodingFailedWith(String),
+
     #[error("bad timestamp {0}")]
     BadTimestamp(#[from] SystemTimeError),
 
@@ -17,6 +17,8 @@ use rust_proto::graplinc::{
             },
             PublishRawLogRequest,
             PublishRawLogResponse,
+            PublishRawLogsRequest,
+            PublishRawLogsResponse,
         },
         protocol::{
             error::ServeError,
@@ -101,6 +103,17 @@ impl PipelineIngressApi for MockPipelineIngressApi {
             Ok(PublishRawLogResponse::ok())
         }
     }
+
+    async fn publish_raw_logs(
+        &self,
+        request: PublishRawLogsRequest,
+    ) -> Result<PublishRawLogsResponse, Self::Error> {
+        for raw_log in request.raw_logs() {
+            self.publish_raw_log(raw_log).await?;
+        }
+
+        Ok(PublishRawLogsResponse::ok())
+    }
 }
 
 struct PipelineIngressTestContext {
@@ -206,3 +219,42 @@ async fn test_publish_raw_log_returns_err_response(ctx: &mut PipelineIngressTest
         // 👍 great success 👍
     }
 }
+
+#[test_context(PipelineIngressTestContext)]
+#[tokio::test]
+async fn test_publish_raw_logs_returns_ok_response(ctx: &mut PipelineIngressTestContext) {
+    let tenant_id = Uuid::parse_str(TENANT_ID).expect("failed to parse TENANT_ID");
+
+    ctx.client
+        .publish_raw_logs(PublishRawLogsRequest::new(vec![
+            PublishRawLogRequest::new(Uuid::new_v4(), tenant_id, "success!".into()),
+            PublishRawLogRequest::new(Uuid::new_v4(), tenant_id, "success again!".into()),
+        ]))
+        .await
+        .expect("received error response");
+}
+
+#[test_context(PipelineIngressTestContext)]
+#[tokio::test]
+async fn test_publish_raw_logs_returns_err_response(ctx: &mut PipelineIngressTestContext) {
+    let tenant_id = Uuid::parse_str(TENANT_ID).expect("failed to parse tenant_id");
+
+    if let Ok(res) = ctx
+        .client
+        .publish_raw_logs(PublishRawLogsRequest::new(vec![
+            PublishRawLogRequest::new(Uuid::new_v4(), tenant_id, "success!".into()),
+            PublishRawLogRequest::new(
+                Uuid::parse_str(BAD_EVENT_SOURCE_ID).expect("failed to parse BAD_EVENT_SOURCE_ID"),
+                tenant_id,
+                "fail!".into(),
+            ),
+        ]))
+        .await
+    {
+        tracing::error!(
+            message = "expected error response",
+            response = ?res,
+        );
+        panic!("expected error response");
+    }
+}
@@ -260,6 +260,64 @@ mod pipeline {
         ){
             check_encode_decode_invariant(envelope)
         }
+
+        #[test]
+        fn test_envelope_decodes_as_envelope_metadata(
+            envelope in st::envelopes(st::raw_logs())
+        ) {
+            use rust_proto::{
+                graplinc::grapl::pipeline::v1beta1::EnvelopeMetadata,
+                SerDe,
+            };
+
+            let expected = EnvelopeMetadata::from(&envelope);
+            let serialized = envelope.serialize().expect("serialization failed");
+            let deserialized = EnvelopeMetadata::deserialize(serialized)
+                .expect("deserialization failed");
+            prop_assert_eq!(deserialized.clone(), expected);
+            check_encode_decode_invariant(deserialized)
+        }
+
+        #[test]
+        fn test_dead_letter_encode_decode(dead_letter in st::dead_letters()) {
+            check_encode_decode_invariant(dead_letter)
+        }
+    }
+
+    #[test]
+    fn test_compressed_envelope_is_smaller() {
+        use bytes::Bytes;
+        use rust_proto::{
+            graplinc::grapl::pipeline::v1beta1::{
+                Envelope,
+                PayloadEncoding,
+                RawLog,
+            },
+            SerDe,
+        };
+
+        let log_event = Bytes::from("<Event>the same old event</Event>\n".repeat(1024));
+        let envelope = Envelope::new(
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            RawLog::new(log_event.clone()),
+        );
+
+        let uncompressed_len = envelope.clone().serialize().unwrap().len();
+
+        for encoding in [PayloadEncoding::Zstd, PayloadEncoding::Gzip] {
+            let serialized = envelope
+                .clone()
+                .with_inner_message_encoding(encoding)
+                .serialize()
+                .unwrap();
+            assert!(serialized.len() < uncompressed_len / 10);
+
+            let deserialized: Envelope<RawLog> = Envelope::deserialize(serialized).unwrap();
+            assert_eq!(deserialized.inner_message_encoding(), encoding);
+            assert_eq!(deserialized.inner_message().log_event(), log_event);
+        }
     }
 }
 
@@ -282,6 +340,20 @@ mod pipeline_ingress {
         ) {
             check_encode_decode_invariant(publish_raw_log_response)
         }
+
+        #[test]
+        fn test_publish_raw_logs_request_encode_decode(
+            publish_raw_logs_request in st::publish_raw_logs_requests()
+        ) {
+            check_encode_decode_invariant(publish_raw_logs_request)
+        }
+
+        #[test]
+        fn test_publish_raw_logs_response_encode_decode(
+            publish_raw_logs_response in st::publish_raw_logs_responses()
+        ) {
+            check_encode_decode_invariant(publish_raw_logs_response)
+        }
     }
 }
 
@@ -506,6 +578,61 @@ mod graph_schema_manager {
         fn test_get_edge_schema_responses(value in sm_strats::get_edge_schema_responses()) {
             check_encode_decode_invariant(value)
         }
+
+        #[test]
+        fn test_set_retention_policy_requests(value in sm_strats::set_retention_policy_requests()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_get_retention_policy_requests(value in sm_strats::get_retention_policy_requests()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_get_retention_policy_responses(value in sm_strats::get_retention_policy_responses()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_get_node_schema_requests(value in sm_strats::get_node_schema_requests()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_get_node_schema_responses(value in sm_strats::get_node_schema_responses()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_list_node_schemas_requests(value in sm_strats::list_node_schemas_requests()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_list_node_schemas_responses(value in sm_strats::list_node_schemas_responses()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_get_raw_schema_requests(value in sm_strats::get_raw_schema_requests()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_get_raw_schema_responses(value in sm_strats::get_raw_schema_responses()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_diff_schemas_requests(value in sm_strats::diff_schemas_requests()) {
+            check_encode_decode_invariant(value)
+        }
+
+        #[test]
+        fn test_diff_schemas_responses(value in sm_strats::diff_schemas_responses()) {
+            check_encode_decode_invariant(value)
+        }
     }
 }
 
@@ -37,7 +37,9 @@ pub mod pipeline {
 
     use rust_proto::{
         graplinc::grapl::pipeline::v1beta1::{
+            DeadLetter,
             Envelope,
+            PayloadEncoding,
             RawLog,
         },
         SerDe,
@@ -57,6 +59,18 @@ pub mod pipeline {
         }
     }
 
+    //
+    // PayloadEncoding
+    //
+
+    pub fn payload_encodings() -> impl Strategy<Value = PayloadEncoding> {
+        prop_oneof![
+            Just(PayloadEncoding::Identity),
+            Just(PayloadEncoding::Zstd),
+            Just(PayloadEncoding::Gzip),
+        ]
+    }
+
     //
     // Envelope
     //
@@ -67,17 +81,42 @@ pub mod pipeline {
     where
         T: SerDe + Debug,
     {
-        (uuids(), uuids(), uuids(), inner_strategy).prop_map(
-            |(tenant_id, trace_id, event_source_id, inner_message)| -> Envelope<T> {
-                Envelope::new(tenant_id, trace_id, event_source_id, inner_message)
-            },
+        (
+            uuids(),
+            uuids(),
+            uuids(),
+            inner_strategy,
+            payload_encodings(),
         )
+            .prop_map(
+                |(tenant_id, trace_id, event_source_id, inner_message, inner_message_encoding)| -> Envelope<T> {
+                    Envelope::new(tenant_id, trace_id, event_source_id, inner_message)
+                        .with_inner_message_encoding(inner_message_encoding)
+                },
+            )
+    }
+
+    //
+    // DeadLetter
+    //
+
+    prop_compose! {
+        pub fn dead_letters()(
+            source_topic in string_not_empty(),
+            payload in bytes(256),
+            reason in string_not_empty(),
+            retry_count in any::<u32>(),
+        ) -> DeadLetter {
+            DeadLetter::new(source_topic, payload, reason, retry_count)
+        }
     }
 }
 
 pub mod common {
 
-    use rust_proto::graplinc::grapl::common::v1beta1::types::{self as native,};
+    use rust_proto::graplinc::grapl::common::v1beta1::types::{
+        self as native,
+    };
 
     use super::*;
     prop_compose! {
@@ -280,11 +319,13 @@ pub mod graph {
             to_node_key in any::<String>(),
             from_node_key in any::<String>(),
             edge_name in any::<String>(),
+            properties in collection::hash_map(any::<String>(), node_properties(), 10),
         ) -> Edge {
             Edge {
                 to_node_key,
                 from_node_key,
                 edge_name,
+                properties,
             }
         }
     }
@@ -494,11 +535,13 @@ pub mod graph {
             from_uid in common::uids(),
             to_uid in common::uids(),
             edge_name in any::<String>(),
+            properties in collection::hash_map(any::<String>(), node_properties(), 10),
         ) -> IdentifiedEdge {
             IdentifiedEdge {
                 from_uid,
                 to_uid,
-                edge_name
+                edge_name,
+                properties,
             }
         }
     }
@@ -520,6 +563,8 @@ pub mod pipeline_ingress {
     use rust_proto::graplinc::grapl::api::pipeline_ingress::v1beta1::{
         PublishRawLogRequest,
         PublishRawLogResponse,
+        PublishRawLogsRequest,
+        PublishRawLogsResponse,
     };
 
     use super::*;
@@ -555,6 +600,32 @@ pub mod pipeline_ingress {
             )
         }
     }
+
+    //
+    // PublishRawLogsRequest
+    //
+
+    prop_compose! {
+        pub fn publish_raw_logs_requests()(
+            raw_logs in proptest::collection::vec(publish_raw_log_requests(), 0..10),
+        ) -> PublishRawLogsRequest {
+            PublishRawLogsRequest::new(raw_logs)
+        }
+    }
+
+    //
+    // PublishRawLogsResponse
+    //
+
+    prop_compose! {
+        pub fn publish_raw_logs_responses()(
+            created_time in any::<SystemTime>(),
+        ) -> PublishRawLogsResponse {
+            PublishRawLogsResponse::new(
+                created_time,
+            )
+        }
+    }
 }
 
 pub mod event_source {
@@ -1074,6 +1145,7 @@ pub mod plugin_work_queue {
 }
 
 pub mod graph_schema_manager {
+    use proptest::collection;
     use rust_proto::graplinc::grapl::api::graph_schema_manager::v1beta1::messages::{
         self as native,
     };
@@ -1117,15 +1189,313 @@ pub mod graph_schema_manager {
             schema in bytes(32),
             schema_type in schema_types(),
             schema_version in any::<u32>(),
+            dry_run in any::<bool>(),
         ) -> native::DeploySchemaRequest {
             native::DeploySchemaRequest{
                 tenant_id,
                 schema,
                 schema_type,
-                schema_version
+                schema_version,
+                dry_run,
+            }
+        }
+    }
+
+    pub fn retention_policies() -> BoxedStrategy<native::RetentionPolicy> {
+        prop_oneof![
+            (any::<u64>(), 0..1_000_000_000u32).prop_map(|(seconds, nanos)| {
+                native::RetentionPolicy::TimeToLive(std::time::Duration::new(seconds, nanos))
+            }),
+            Just(native::RetentionPolicy::RetainForever),
+        ]
+        .boxed()
+    }
+
+    prop_compose! {
+        pub fn set_retention_policy_requests()(
+            tenant_id in uuids(),
+            node_type in proptest::option::of(common::node_types()),
+            retention_policy in proptest::option::of(retention_policies()),
+        ) -> native::SetRetentionPolicyRequest {
+            native::SetRetentionPolicyRequest {
+                tenant_id,
+                node_type,
+                retention_policy,
             }
         }
     }
+
+    prop_compose! {
+        pub fn get_retention_policy_requests()(
+            tenant_id in uuids(),
+            node_type in common::node_types(),
+        ) -> native::GetRetentionPolicyRequest {
+            native::GetRetentionPolicyRequest {
+                tenant_id,
+                node_type,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn get_retention_policy_responses()(
+            retention_policy in retention_policies(),
+        ) -> native::GetRetentionPolicyResponse {
+            native::GetRetentionPolicyResponse { retention_policy }
+        }
+    }
+
+    pub fn property_types() -> BoxedStrategy<native::PropertyType> {
+        prop_oneof![
+            Just(native::PropertyType::ImmutableString),
+            Just(native::PropertyType::ImmutableI64),
+            Just(native::PropertyType::MaxI64),
+            Just(native::PropertyType::MinI64),
+            Just(native::PropertyType::ImmutableU64),
+            Just(native::PropertyType::MaxU64),
+            Just(native::PropertyType::MinU64),
+        ]
+        .boxed()
+    }
+
+    prop_compose! {
+        pub fn property_schemas()(
+            property_name in common::property_names(),
+            property_type in property_types(),
+            identity_only in any::<bool>(),
+        ) -> native::PropertySchema {
+            native::PropertySchema {
+                property_name,
+                property_type,
+                identity_only,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn edge_schemas()(
+            edge_name in common::edge_names(),
+            reverse_edge_name in common::edge_names(),
+            cardinality in edge_cardinalities(),
+            reverse_cardinality in edge_cardinalities(),
+            properties in collection::vec(property_schemas(), 0..4),
+        ) -> native::EdgeSchema {
+            native::EdgeSchema {
+                edge_name,
+                reverse_edge_name,
+                cardinality,
+                reverse_cardinality,
+                properties,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn static_identities()(
+            static_key_properties in collection::vec(common::property_names(), 0..4),
+        ) -> native::StaticIdentity {
+            native::StaticIdentity {
+                static_key_properties,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn session_identities()(
+            pseudo_key_properties in collection::vec(common::property_names(), 0..4),
+            negation_key_properties in collection::vec(common::property_names(), 0..4),
+            creation_timestamp_property in common::property_names(),
+            last_seen_timestamp_property in common::property_names(),
+            termination_timestamp_property in common::property_names(),
+        ) -> native::SessionIdentity {
+            native::SessionIdentity {
+                pseudo_key_properties,
+                negation_key_properties,
+                creation_timestamp_property,
+                last_seen_timestamp_property,
+                termination_timestamp_property,
+            }
+        }
+    }
+
+    pub fn identity_algorithms() -> BoxedStrategy<native::IdentityAlgorithm> {
+        prop_oneof![
+            static_identities().prop_map(native::IdentityAlgorithm::Static),
+            session_identities().prop_map(native::IdentityAlgorithm::Session),
+        ]
+        .boxed()
+    }
+
+    prop_compose! {
+        pub fn node_schemas()(
+            node_type in common::node_types(),
+            schema_version in any::<u32>(),
+            identity_algorithm in identity_algorithms(),
+            properties in collection::vec(property_schemas(), 0..4),
+            edges in collection::vec(edge_schemas(), 0..4),
+        ) -> native::NodeSchema {
+            native::NodeSchema {
+                node_type,
+                schema_version,
+                identity_algorithm,
+                properties,
+                edges,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn get_node_schema_requests()(
+            tenant_id in uuids(),
+            node_type in common::node_types(),
+            schema_version in proptest::option::of(any::<u32>()),
+        ) -> native::GetNodeSchemaRequest {
+            native::GetNodeSchemaRequest {
+                tenant_id,
+                node_type,
+                schema_version,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn get_node_schema_responses()(
+            node_schema in node_schemas(),
+        ) -> native::GetNodeSchemaResponse {
+            native::GetNodeSchemaResponse { node_schema }
+        }
+    }
+
+    prop_compose! {
+        pub fn list_node_schemas_requests()(
+            tenant_id in uuids(),
+        ) -> native::ListNodeSchemasRequest {
+            native::ListNodeSchemasRequest { tenant_id }
+        }
+    }
+
+    prop_compose! {
+        pub fn node_schema_versions()(
+            schema_version in any::<u32>(),
+            deployment_timestamp in any::<SystemTime>(),
+        ) -> native::NodeSchemaVersion {
+            native::NodeSchemaVersion {
+                schema_version,
+                deployment_timestamp,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn list_node_schemas_responses()(
+            node_schemas in collection::vec(
+                (common::node_types(), collection::vec(node_schema_versions(), 0..4)),
+                0..4,
+            ),
+        ) -> native::ListNodeSchemasResponse {
+            native::ListNodeSchemasResponse {
+                node_schemas: node_schemas
+                    .into_iter()
+                    .map(|(node_type, versions)| native::NodeSchemaVersions {
+                        node_type,
+                        versions,
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn get_raw_schema_requests()(
+            tenant_id in uuids(),
+            schema_version in proptest::option::of(any::<u32>()),
+        ) -> native::GetRawSchemaRequest {
+            native::GetRawSchemaRequest {
+                tenant_id,
+                schema_version,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn get_raw_schema_responses()(
+            schema_version in any::<u32>(),
+            schema_type in schema_types(),
+            schema in bytes(32),
+        ) -> native::GetRawSchemaResponse {
+            native::GetRawSchemaResponse {
+                schema_version,
+                schema_type,
+                schema,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn diff_schemas_requests()(
+            tenant_id in uuids(),
+            from_schema_version in any::<u32>(),
+            to_schema_version in any::<u32>(),
+        ) -> native::DiffSchemasRequest {
+            native::DiffSchemasRequest {
+                tenant_id,
+                from_schema_version,
+                to_schema_version,
+            }
+        }
+    }
+
+    prop_compose! {
+        pub fn node_schema_changes()(
+            node_type in common::node_types(),
+            identity_algorithm_change in proptest::option::of(
+                (identity_algorithms(), identity_algorithms())
+                    .prop_map(|(from, to)| native::IdentityAlgorithmChange { from, to })
+            ),
+            added_properties in collection::vec(property_schemas(), 0..4),
+            removed_properties in collection::vec(property_schemas(), 0..4),
+            changed_properties in collection::vec(
+                (property_schemas(), property_schemas())
+                    .prop_map(|(from, to)| native::PropertySchemaChange { from, to }),
+                0..4,
+            ),
+            added_edges in collection::vec(edge_schemas(), 0..4),
+            removed_edges in collection::vec(edge_schemas(), 0..4),
+            changed_edges in collection::vec(
+                (edge_schemas(), edge_schemas())
+                    .prop_map(|(from, to)| native::EdgeSchemaChange { from, to }),
+                0..4,
+            ),
+        ) -> native::NodeSchemaChanges {
+            native::NodeSchemaChanges {
+                node_type,
+                identity_algorithm_change,
+                added_properties,
+                removed_properties,
+                changed_properties,
+                added_edges,
+                removed_edges,
+                changed_edges,
+            }
+        }
+    }
+
+    pub fn node_schema_diffs() -> BoxedStrategy<native::NodeSchemaDiff> {
+        prop_oneof![
+            node_schemas().prop_map(native::NodeSchemaDiff::Added),
+            node_schemas().prop_map(native::NodeSchemaDiff::Removed),
+            node_schema_changes().prop_map(native::NodeSchemaDiff::Changed),
+        ]
+        .boxed()
+    }
+
+    prop_compose! {
+        pub fn diff_schemas_responses()(
+            node_schema_diffs in collection::vec(node_schema_diffs(), 0..4),
+        ) -> native::DiffSchemasResponse {
+            native::DiffSchemasResponse { node_schema_diffs }
+        }
+    }
 }
 
 pub mod analyzer_sdk {
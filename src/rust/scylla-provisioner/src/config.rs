@@ -1,4 +1,7 @@
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    str::FromStr,
+};
 
 use secrecy::ExposeSecret;
 
@@ -26,6 +29,108 @@ impl GraphDbConfig {
     }
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ReplicationConfigError {
+    #[error(
+        "Unknown replication strategy {0}, expected SimpleStrategy or NetworkTopologyStrategy"
+    )]
+    UnknownStrategy(String),
+    #[error("Invalid datacenter replication {0}, expected <datacenter>:<replication factor>")]
+    InvalidDatacenterReplication(String),
+    #[error("NetworkTopologyStrategy requires at least one datacenter replication factor")]
+    MissingDatacenters,
+    #[error("The replication factor must be at least 1")]
+    ZeroReplicationFactor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplicationStrategy {
+    SimpleStrategy,
+    NetworkTopologyStrategy,
+}
+
+impl FromStr for ReplicationStrategy {
+    type Err = ReplicationConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SimpleStrategy" => Ok(Self::SimpleStrategy),
+            "NetworkTopologyStrategy" => Ok(Self::NetworkTopologyStrategy),
+            other => Err(ReplicationConfigError::UnknownStrategy(other.to_owned())),
+        }
+    }
+}
+
+/// A datacenter's replication factor, written as `<datacenter>:<factor>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatacenterReplication {
+    pub datacenter: String,
+    pub replication_factor: u32,
+}
+
+impl FromStr for DatacenterReplication {
+    type Err = ReplicationConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReplicationConfigError::InvalidDatacenterReplication(s.to_owned());
+        let (datacenter, replication_factor) = s.rsplit_once(':').ok_or_else(invalid)?;
+        if datacenter.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            datacenter: datacenter.to_owned(),
+            replication_factor: replication_factor.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(clap::Parser, Clone, Debug)]
+pub struct ReplicationConfig {
+    #[clap(long, env, default_value = "SimpleStrategy")]
+    /// The replication strategy of the graph keyspace, either SimpleStrategy
+    /// or NetworkTopologyStrategy
+    pub graph_db_replication_strategy: ReplicationStrategy,
+    #[clap(long, env, default_value = "1")]
+    /// The replication factor of the graph keyspace under SimpleStrategy
+    pub graph_db_replication_factor: u32,
+    #[clap(long, env, value_delimiter = ',')]
+    /// The replication factor of each datacenter under
+    /// NetworkTopologyStrategy, e.g. `us-east:3,eu-west:3`
+    pub graph_db_datacenter_replication: Vec<DatacenterReplication>,
+}
+
+impl ReplicationConfig {
+    /// The keyspace's replication options, as a CQL map literal
+    pub fn to_cql(&self) -> Result<String, ReplicationConfigError> {
+        match self.graph_db_replication_strategy {
+            ReplicationStrategy::SimpleStrategy => {
+                if self.graph_db_replication_factor == 0 {
+                    return Err(ReplicationConfigError::ZeroReplicationFactor);
+                }
+                Ok(format!(
+                    "{{'class': 'SimpleStrategy', 'replication_factor': {}}}",
+                    self.graph_db_replication_factor
+                ))
+            }
+            ReplicationStrategy::NetworkTopologyStrategy => {
+                if self.graph_db_datacenter_replication.is_empty() {
+                    return Err(ReplicationConfigError::MissingDatacenters);
+                }
+                let mut options = vec!["'class': 'NetworkTopologyStrategy'".to_owned()];
+                for dc in self.graph_db_datacenter_replication.iter() {
+                    if dc.replication_factor == 0 {
+                        return Err(ReplicationConfigError::ZeroReplicationFactor);
+                    }
+                    // Datacenter names are quoted as string literals
+                    let datacenter = dc.datacenter.replace('\'', "''");
+                    options.push(format!("'{datacenter}': {}", dc.replication_factor));
+                }
+                Ok(format!("{{{}}}", options.join(", ")))
+            }
+        }
+    }
+}
+
 #[derive(clap::Parser, Debug, Clone)]
 pub struct ScyllaProvisionerServiceConfig {
     #[clap(env)]
@@ -34,4 +139,66 @@ pub struct ScyllaProvisionerServiceConfig {
 
     #[clap(flatten)]
     pub graph_db_config: GraphDbConfig,
+
+    #[clap(flatten)]
+    pub replication_config: ReplicationConfig,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replication_config(
+        graph_db_replication_strategy: ReplicationStrategy,
+        graph_db_replication_factor: u32,
+        graph_db_datacenter_replication: &[&str],
+    ) -> ReplicationConfig {
+        ReplicationConfig {
+            graph_db_replication_strategy,
+            graph_db_replication_factor,
+            graph_db_datacenter_replication: graph_db_datacenter_replication
+                .iter()
+                .map(|dc| dc.parse().unwrap())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_simple_strategy_to_cql() {
+        let config = replication_config(ReplicationStrategy::SimpleStrategy, 3, &[]);
+        assert_eq!(
+            config.to_cql().unwrap(),
+            "{'class': 'SimpleStrategy', 'replication_factor': 3}"
+        );
+    }
+
+    #[test]
+    fn test_network_topology_strategy_to_cql() {
+        let config = replication_config(
+            ReplicationStrategy::NetworkTopologyStrategy,
+            1,
+            &["us-east:3", "eu-west:2"],
+        );
+        assert_eq!(
+            config.to_cql().unwrap(),
+            "{'class': 'NetworkTopologyStrategy', 'us-east': 3, 'eu-west': 2}"
+        );
+    }
+
+    #[test]
+    fn test_invalid_replication() {
+        assert!("us-east".parse::<DatacenterReplication>().is_err());
+        assert!(":3".parse::<DatacenterReplication>().is_err());
+        assert!("us-east:three".parse::<DatacenterReplication>().is_err());
+        assert!(
+            replication_config(ReplicationStrategy::NetworkTopologyStrategy, 1, &[])
+                .to_cql()
+                .is_err()
+        );
+        assert!(
+            replication_config(ReplicationStrategy::SimpleStrategy, 0, &[])
+                .to_cql()
+                .is_err()
+        );
+    }
 }
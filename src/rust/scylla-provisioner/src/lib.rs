@@ -1,3 +1,4 @@
 pub mod config;
+pub mod migrations;
 pub mod server;
 pub mod table_names;
@@ -0,0 +1,326 @@
+use scylla::{
+    transport::errors::QueryError,
+    Session,
+};
+
+use crate::table_names::{
+    EDGE_I_64_TABLE_NAME,
+    EDGE_STRING_TABLE_NAME,
+    EDGE_U_64_TABLE_NAME,
+    GRAPH_DELETIONS_TABLE_NAME,
+    IDENTITY_SESSIONS_TABLE_NAME,
+    IDENTITY_STATIC_MAPPINGS_TABLE_NAME,
+    IMM_I_64_BUCKETED_INDEX_TABLE_NAME,
+    IMM_I_64_TABLE_NAME,
+    IMM_STRING_INDEX_TABLE_NAME,
+    IMM_STRING_TABLE_NAME,
+    IMM_U_64_BUCKETED_INDEX_TABLE_NAME,
+    IMM_U_64_TABLE_NAME,
+    MAX_I_64_TABLE_NAME,
+    MAX_U_64_TABLE_NAME,
+    MIN_I_64_TABLE_NAME,
+    MIN_U_64_TABLE_NAME,
+    TENANT_PURGES_TABLE_NAME,
+};
+
+/// The table recording which migrations have been applied to a keyspace
+pub const SCHEMA_MIGRATIONS_TABLE_NAME: &str = "schema_migrations";
+
+#[derive(thiserror::Error, Debug)]
+pub enum MigrationError {
+    #[error("Scylla Error {0}")]
+    ScyllaError(#[from] QueryError),
+    #[error("Failed to read applied migrations {0}")]
+    InvalidMigrationRow(String),
+}
+
+/// A versioned change to the graph keyspace's tables.
+///
+/// A migration's statements must be idempotent, since a migration that fails
+/// part-way through is run again from its first statement. Once released, a
+/// migration must never change; changes go in a new migration instead.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    /// The statements to run, given the keyspace's name
+    pub statements: fn(&str) -> Vec<String>,
+}
+
+/// Every migration, in the order they're applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Node properties, immutable property indexes, node types and edges",
+        statements: initial_tables,
+    },
+    Migration {
+        version: 2,
+        description: "Edge properties",
+        statements: edge_property_tables,
+    },
+    Migration {
+        version: 3,
+        description: "Tenant purge statuses and graph deletion generations",
+        statements: deletion_tables,
+    },
+    Migration {
+        version: 4,
+        description: "node-identifier sessions and static mappings",
+        statements: identity_tables,
+    },
+];
+
+/// The version of the latest migration
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Applies every migration that hasn't yet been applied to `keyspace`, which
+/// must already exist, in order of version. Returns the versions applied.
+#[tracing::instrument(skip(session), err)]
+pub async fn run_migrations(session: &Session, keyspace: &str) -> Result<Vec<u32>, MigrationError> {
+    session
+        .query(
+            format!(
+                r"CREATE TABLE IF NOT EXISTS {keyspace}.{SCHEMA_MIGRATIONS_TABLE_NAME} (
+                    version int,
+                    description text,
+                    applied_at timestamp,
+                    PRIMARY KEY (version)
+                )"
+            ),
+            &(),
+        )
+        .await?;
+    session.await_schema_agreement().await?;
+
+    let applied = applied_versions(session, keyspace).await?;
+
+    let mut newly_applied = Vec::new();
+    for migration in MIGRATIONS {
+        if applied.contains(&migration.version) {
+            continue;
+        }
+        tracing::info!(
+            message = "Applying migration",
+            version = migration.version,
+            description = migration.description,
+        );
+        for statement in (migration.statements)(keyspace) {
+            session.query(statement, &()).await?;
+        }
+        session.await_schema_agreement().await?;
+
+        // Only recorded once every statement has succeeded, so a failed
+        // migration is retried by the next provisioning
+        session
+            .query(
+                format!(
+                    "INSERT INTO {keyspace}.{SCHEMA_MIGRATIONS_TABLE_NAME} \
+                    (version, description, applied_at) \
+                    VALUES (?, ?, toTimestamp(now()))"
+                ),
+                (migration.version as i32, migration.description),
+            )
+            .await?;
+        newly_applied.push(migration.version);
+    }
+
+    Ok(newly_applied)
+}
+
+/// The versions of the migrations applied to `keyspace`
+pub async fn applied_versions(
+    session: &Session,
+    keyspace: &str,
+) -> Result<Vec<u32>, MigrationError> {
+    let query_result = session
+        .query(
+            format!("SELECT version FROM {keyspace}.{SCHEMA_MIGRATIONS_TABLE_NAME}"),
+            &(),
+        )
+        .await?;
+
+    let mut versions = Vec::new();
+    for row in query_result.rows_typed_or_empty::<(i32,)>() {
+        let (version,) = row.map_err(|e| MigrationError::InvalidMigrationRow(e.to_string()))?;
+        let version = u32::try_from(version)
+            .map_err(|e| MigrationError::InvalidMigrationRow(e.to_string()))?;
+        versions.push(version);
+    }
+    versions.sort_unstable();
+    Ok(versions)
+}
+
+fn initial_tables(keyspace: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+
+    let property_table_names = [
+        (IMM_STRING_TABLE_NAME, "text"),
+        (MAX_I_64_TABLE_NAME, "bigint"),
+        (MIN_I_64_TABLE_NAME, "bigint"),
+        (IMM_I_64_TABLE_NAME, "bigint"),
+        (MAX_U_64_TABLE_NAME, "bigint"),
+        (MIN_U_64_TABLE_NAME, "bigint"),
+        (IMM_U_64_TABLE_NAME, "bigint"),
+    ];
+
+    for (table_name, value_type) in property_table_names.into_iter() {
+        statements.push(format!(
+            r"CREATE TABLE IF NOT EXISTS {keyspace}.{table_name} (
+                tenant_id uuid,
+                uid bigint,
+                populated_field text,
+                value {value_type},
+                PRIMARY KEY (tenant_id, uid, populated_field)
+            )"
+        ));
+    }
+
+    // Secondary indexes from immutable property values back to uids, so
+    // queries can start from a property predicate rather than a uid.
+    // Strings are only ever looked up by equality, ints by range. Ints are
+    // partitioned by `value >> 20`, so that a partition holds a bounded range
+    // of values and a lookup reads only the buckets its range spans.
+    statements.push(format!(
+        r"CREATE TABLE IF NOT EXISTS {keyspace}.{IMM_STRING_INDEX_TABLE_NAME} (
+            tenant_id uuid,
+            node_type text,
+            populated_field text,
+            value text,
+            uid bigint,
+            PRIMARY KEY ((tenant_id, node_type, populated_field, value), uid)
+        )"
+    ));
+
+    for table_name in [
+        IMM_I_64_BUCKETED_INDEX_TABLE_NAME,
+        IMM_U_64_BUCKETED_INDEX_TABLE_NAME,
+    ] {
+        statements.push(format!(
+            r"CREATE TABLE IF NOT EXISTS {keyspace}.{table_name} (
+                tenant_id uuid,
+                node_type text,
+                populated_field text,
+                bucket bigint,
+                value bigint,
+                uid bigint,
+                PRIMARY KEY ((tenant_id, node_type, populated_field, bucket), value, uid)
+            )"
+        ));
+    }
+
+    statements.push(format!(
+        r"CREATE TABLE IF NOT EXISTS {keyspace}.node_type (
+            tenant_id uuid,
+            uid bigint,
+            node_type text,
+            PRIMARY KEY (tenant_id, uid, node_type)
+        )"
+    ));
+    statements.push(format!(
+        r"CREATE TABLE IF NOT EXISTS {keyspace}.edges (
+            tenant_id uuid,
+            source_uid bigint,
+            destination_uid bigint,
+            f_edge_name text,
+            r_edge_name text,
+            PRIMARY KEY (tenant_id, source_uid, f_edge_name, destination_uid)
+        )"
+    ));
+
+    statements
+}
+
+/// Edge properties are stored once for each direction of the edge, so they
+/// can be read alongside the edges. Unlike node properties they aren't split
+/// up by how they're merged, so that reading an edge's properties takes one
+/// query per value type.
+fn edge_property_tables(keyspace: &str) -> Vec<String> {
+    [
+        (EDGE_STRING_TABLE_NAME, "text"),
+        (EDGE_I_64_TABLE_NAME, "bigint"),
+        (EDGE_U_64_TABLE_NAME, "bigint"),
+    ]
+    .into_iter()
+    .map(|(table_name, value_type)| {
+        format!(
+            r"CREATE TABLE IF NOT EXISTS {keyspace}.{table_name} (
+                tenant_id uuid,
+                source_uid bigint,
+                f_edge_name text,
+                destination_uid bigint,
+                populated_field text,
+                value {value_type},
+                PRIMARY KEY (tenant_id, source_uid, f_edge_name, destination_uid, populated_field)
+            )"
+        )
+    })
+    .collect()
+}
+
+/// Purge statuses are stored so that a tenant's graph stays closed to writes
+/// while it's purged, whichever server is asked and even across restarts.
+/// A tenant's deletion generation is replaced whenever anything is deleted
+/// from its graph, so that graph-query can tell its cached properties and
+/// edges may be stale.
+fn deletion_tables(keyspace: &str) -> Vec<String> {
+    vec![
+        format!(
+            r"CREATE TABLE IF NOT EXISTS {keyspace}.{TENANT_PURGES_TABLE_NAME} (
+                tenant_id uuid,
+                status text,
+                reason text,
+                PRIMARY KEY (tenant_id)
+            )"
+        ),
+        format!(
+            r"CREATE TABLE IF NOT EXISTS {keyspace}.{GRAPH_DELETIONS_TABLE_NAME} (
+                tenant_id uuid,
+                generation timeuuid,
+                PRIMARY KEY (tenant_id)
+            )"
+        ),
+    ]
+}
+
+/// The tables node-identifier's scylla identity store keeps sessions and
+/// static ids in. Sessions are partitioned by pseudo key and clustered by
+/// create time, so that finding the session around a timestamp reads a
+/// single partition.
+fn identity_tables(keyspace: &str) -> Vec<String> {
+    vec![
+        format!(
+            r"CREATE TABLE IF NOT EXISTS {keyspace}.{IDENTITY_SESSIONS_TABLE_NAME} (
+                pseudo_key text,
+                create_time bigint,
+                session_id bigint,
+                end_time bigint,
+                is_create_canon boolean,
+                is_end_canon boolean,
+                version bigint,
+                PRIMARY KEY (pseudo_key, create_time)
+            ) WITH CLUSTERING ORDER BY (create_time ASC)"
+        ),
+        format!(
+            r"CREATE TABLE IF NOT EXISTS {keyspace}.{IDENTITY_STATIC_MAPPINGS_TABLE_NAME} (
+                static_id text,
+                uid bigint,
+                PRIMARY KEY (static_id)
+            )"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_versions_are_increasing() {
+        for window in MIGRATIONS.windows(2) {
+            assert!(window[0].version < window[1].version);
+        }
+        assert!(MIGRATIONS.iter().all(|migration| migration.version > 0));
+    }
+}
@@ -27,35 +27,51 @@ use scylla::{
 use tokio::net::TcpListener;
 
 use crate::{
-    config::ScyllaProvisionerServiceConfig,
-    table_names::{
-        IMM_I_64_TABLE_NAME,
-        IMM_STRING_TABLE_NAME,
-        IMM_U_64_TABLE_NAME,
-        MAX_I_64_TABLE_NAME,
-        MAX_U_64_TABLE_NAME,
-        MIN_I_64_TABLE_NAME,
-        MIN_U_64_TABLE_NAME,
+    config::{
+        ReplicationConfigError,
+        ScyllaProvisionerServiceConfig,
+    },
+    migrations::{
+        run_migrations,
+        MigrationError,
     },
 };
 
+/// The keyspace holding every tenant's graph
+pub const GRAPH_KEYSPACE_NAME: &str = "tenant_graph_ks";
+
 #[derive(thiserror::Error, Debug)]
 pub enum ScyllaProvisionerError {
     #[error("Scylla Error {0}")]
     ScyllaError(#[from] QueryError),
+    #[error("Migration Error {0}")]
+    MigrationError(#[from] MigrationError),
+    #[error("Invalid replication config {0}")]
+    ReplicationConfigError(#[from] ReplicationConfigError),
 }
 
 impl From<ScyllaProvisionerError> for Status {
     fn from(error: ScyllaProvisionerError) -> Self {
         match error {
             ScyllaProvisionerError::ScyllaError(error) => Status::unknown(error.to_string()),
+            ScyllaProvisionerError::MigrationError(error) => Status::unknown(error.to_string()),
+            ScyllaProvisionerError::ReplicationConfigError(error) => {
+                Status::internal(error.to_string())
+            }
         }
     }
 }
 
+/// Creates the graph keyspace and brings its tables up to date.
+///
+/// The keyspace's replication only applies when it's created. Changing the
+/// replication of an existing keyspace needs a repair afterwards, so it's
+/// left to an operator.
 #[derive(Clone)]
 pub struct ScyllaProvisioner {
     scylla_client: Arc<Session>,
+    /// The keyspace's replication options, as a CQL map literal
+    replication: String,
     already_provisioned: Arc<AtomicBool>,
 }
 
@@ -76,64 +92,19 @@ impl ScyllaProvisionerApi for ScyllaProvisioner {
         let native::ProvisionGraphForTenantRequest { tenant_id: _ } = request;
         let session = self.scylla_client.as_ref();
 
-        session.query(
-            r"CREATE KEYSPACE IF NOT EXISTS tenant_graph_ks WITH REPLICATION = {'class' : 'SimpleStrategy', 'replication_factor' : 1};",
-            &[],
-        ).await?;
-
-        let property_table_names = [
-            (IMM_STRING_TABLE_NAME, "text"),
-            (MAX_I_64_TABLE_NAME, "bigint"),
-            (MIN_I_64_TABLE_NAME, "bigint"),
-            (IMM_I_64_TABLE_NAME, "bigint"),
-            (MAX_U_64_TABLE_NAME, "bigint"),
-            (MIN_U_64_TABLE_NAME, "bigint"),
-            (IMM_U_64_TABLE_NAME, "bigint"),
-        ];
-
-        for (table_name, value_type) in property_table_names.into_iter() {
-            session
-                .query(
-                    format!(
-                        r"CREATE TABLE IF NOT EXISTS tenant_graph_ks.{table_name} (
-                            tenant_id uuid,
-                            uid bigint,
-                            populated_field text,
-                            value {value_type},
-                            PRIMARY KEY (tenant_id, uid, populated_field)
-                        )"
-                    ),
-                    &(),
-                )
-                .await?;
-        }
-
-        session
-            .query(
-                "CREATE TABLE IF NOT EXISTS tenant_graph_ks.node_type (
-                        tenant_id uuid,
-                        uid bigint,
-                        node_type text,
-                        PRIMARY KEY (tenant_id, uid, node_type)
-                    )",
-                &(),
-            )
-            .await?;
+        let replication = &self.replication;
         session
             .query(
-                r"CREATE TABLE IF NOT EXISTS tenant_graph_ks.edges (
-                        tenant_id uuid,
-                        source_uid bigint,
-                        destination_uid bigint,
-                        f_edge_name text,
-                        r_edge_name text,
-                        PRIMARY KEY (tenant_id, source_uid, f_edge_name, destination_uid)
-                    )",
-                &(),
+                format!(
+                    "CREATE KEYSPACE IF NOT EXISTS {GRAPH_KEYSPACE_NAME} WITH REPLICATION = {replication};"
+                ),
+                &[],
             )
             .await?;
-
         session.await_schema_agreement().await?;
+
+        run_migrations(session, GRAPH_KEYSPACE_NAME).await?;
+
         self.already_provisioned
             .store(true, std::sync::atomic::Ordering::SeqCst);
 
@@ -154,8 +125,12 @@ pub async fn exec_service(
         graph_db_addresses=?graph_db_config.graph_db_addresses,
     );
 
+    let replication = config.replication_config.to_cql()?;
+    tracing::info!(message = "Graph keyspace replication", replication = %replication);
+
     let plugin_registry = ScyllaProvisioner {
         scylla_client: Arc::new(graph_db_config.connect().await?),
+        replication,
         already_provisioned: Arc::new(AtomicBool::new(false)),
     };
 
@@ -7,3 +7,13 @@ pub const MAX_U_64_TABLE_NAME: &str = "max_u64";
 pub const MIN_U_64_TABLE_NAME: &str = "min_u64";
 pub const IMM_U_64_TABLE_NAME: &str = "imm_u64";
 pub const IMM_STRING_TABLE_NAME: &str = "imm_string";
+pub const IMM_I_64_BUCKETED_INDEX_TABLE_NAME: &str = "imm_i64_bucketed_index";
+pub const IMM_U_64_BUCKETED_INDEX_TABLE_NAME: &str = "imm_u64_bucketed_index";
+pub const IMM_STRING_INDEX_TABLE_NAME: &str = "imm_string_index";
+pub const EDGE_I_64_TABLE_NAME: &str = "edge_i64";
+pub const EDGE_U_64_TABLE_NAME: &str = "edge_u64";
+pub const EDGE_STRING_TABLE_NAME: &str = "edge_string";
+pub const TENANT_PURGES_TABLE_NAME: &str = "tenant_purges";
+pub const GRAPH_DELETIONS_TABLE_NAME: &str = "graph_deletions";
+pub const IDENTITY_SESSIONS_TABLE_NAME: &str = "identity_sessions";
+pub const IDENTITY_STATIC_MAPPINGS_TABLE_NAME: &str = "identity_static_mappings";
@@ -0,0 +1,82 @@
+#![cfg(feature = "integration_tests")]
+
+use clap::Parser;
+use scylla_provisioner::{
+    config::GraphDbConfig,
+    migrations::{
+        applied_versions,
+        latest_version,
+        run_migrations,
+        MIGRATIONS,
+    },
+};
+
+#[tokio::test]
+async fn test_migrations_roll_forward_idempotently() -> eyre::Result<()> {
+    let graph_db_config = GraphDbConfig::try_parse_from(["scylla-provisioner-integration-test"])?;
+    let session = graph_db_config
+        .connect()
+        .await
+        .map_err(|e| eyre::eyre!("failed to connect: {e}"))?;
+
+    // A fresh keyspace, so that the test doesn't depend on what's already
+    // been provisioned
+    let keyspace = format!("migrations_test_{}", uuid::Uuid::new_v4().simple());
+    session
+        .query(
+            format!(
+                "CREATE KEYSPACE {keyspace} WITH REPLICATION = \
+                {{'class': 'SimpleStrategy', 'replication_factor': 1}}"
+            ),
+            &(),
+        )
+        .await?;
+
+    let all_versions: Vec<u32> = MIGRATIONS
+        .iter()
+        .map(|migration| migration.version)
+        .collect();
+
+    let applied = run_migrations(&session, &keyspace).await?;
+    assert_eq!(applied, all_versions);
+    assert_eq!(applied_versions(&session, &keyspace).await?, all_versions);
+    assert_eq!(applied.last().copied(), Some(latest_version()));
+
+    // Running them again is a no-op
+    let applied = run_migrations(&session, &keyspace).await?;
+    assert!(applied.is_empty());
+    assert_eq!(applied_versions(&session, &keyspace).await?, all_versions);
+
+    // A migration that was interrupted before it was recorded is re-run
+    // without failing on the tables it already created
+    session
+        .query(
+            format!("DELETE FROM {keyspace}.schema_migrations WHERE version = ?"),
+            (latest_version() as i32,),
+        )
+        .await?;
+    let applied = run_migrations(&session, &keyspace).await?;
+    assert_eq!(applied, vec![latest_version()]);
+
+    let tables = session
+        .query(
+            "SELECT table_name FROM system_schema.tables WHERE keyspace_name = ?",
+            (&keyspace,),
+        )
+        .await?;
+    let table_names: Vec<String> = tables
+        .rows_typed_or_empty::<(String,)>()
+        .map(|row| row.map(|(table_name,)| table_name))
+        .collect::<Result<_, _>>()?;
+    for table_name in ["imm_string", "edges", "node_type", "edge_u64"] {
+        assert!(
+            table_names.iter().any(|t| t == table_name),
+            "missing table {table_name}"
+        );
+    }
+
+    session
+        .query(format!("DROP KEYSPACE {keyspace}"), &())
+        .await?;
+    Ok(())
+}